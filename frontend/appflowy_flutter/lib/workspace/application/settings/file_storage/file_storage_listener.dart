@@ -39,6 +39,9 @@ class StoreageNotificationListener {
                     case StorageNotification.SingleFileLimitExceeded:
                       onError?.call(FlowyError.fromBuffer(data));
                       break;
+                    case StorageNotification.InvalidStorageConfig:
+                      onError?.call(FlowyError.fromBuffer(data));
+                      break;
                   }
                 } catch (e) {
                   Log.error(
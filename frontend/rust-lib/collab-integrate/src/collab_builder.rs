@@ -1,6 +1,7 @@
 use std::borrow::BorrowMut;
 use std::fmt::{Debug, Display};
 use std::sync::{Arc, Weak};
+use std::time::Duration;
 
 use crate::CollabKVDB;
 use anyhow::{anyhow, Error};
@@ -36,6 +37,10 @@ use collab_user::core::{UserAwareness, UserAwarenessNotifier};
 use lib_infra::{if_native, if_wasm};
 use tracing::{error, instrument, trace, warn};
 
+/// How often an opened collab is checked for changes. When the collab has changed since the last
+/// check, its state is written as a local snapshot through the [SnapshotPersistence].
+const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
 #[derive(Clone, Debug)]
 pub enum CollabPluginProviderType {
   Local,
@@ -340,9 +345,79 @@ impl AppFlowyCollabBuilder {
 
     (*write_collab).borrow_mut().initialize();
     drop(write_collab);
+
+    #[cfg(not(target_arch = "wasm32"))]
+    self.spawn_snapshot_task(&object, &collab);
     Ok(collab)
   }
 
+  /// Periodically persist a snapshot of the collab until the collab is dropped. Only the collab
+  /// types that support version history are snapshotted, and a snapshot is only created when the
+  /// state vector changed since the previous check.
+  #[cfg(not(target_arch = "wasm32"))]
+  fn spawn_snapshot_task<T>(&self, object: &CollabObject, collab: &Arc<RwLock<T>>)
+  where
+    T: BorrowMut<Collab> + Send + Sync + 'static,
+  {
+    if !matches!(
+      object.collab_type,
+      CollabType::Document | CollabType::Folder | CollabType::Database
+    ) {
+      return;
+    }
+
+    let snapshot_persistence = match self.snapshot_persistence.load_full() {
+      None => return,
+      Some(snapshot_persistence) => snapshot_persistence,
+    };
+
+    let uid = object.uid;
+    let object_id = object.object_id.clone();
+    let collab_type = object.collab_type.clone();
+    let weak_collab = Arc::downgrade(collab);
+    tokio::spawn(async move {
+      let mut interval = tokio::time::interval(SNAPSHOT_INTERVAL);
+      let mut last_state_vector = None;
+      loop {
+        interval.tick().await;
+        let encoded_collab = match weak_collab.upgrade() {
+          None => break,
+          Some(collab) => {
+            let read_guard = collab.read().await;
+            let collab: &Collab = (*read_guard).borrow();
+            collab.encode_collab_v1(|collab| collab_type.validate_require_data(collab))
+          },
+        };
+
+        match encoded_collab {
+          Ok(encoded_collab) => {
+            // The first tick completes immediately, which records the state of the collab when
+            // it was opened. Only the changes made after that are worth a snapshot.
+            let is_first_tick = last_state_vector.is_none();
+            if last_state_vector.as_ref() == Some(&encoded_collab.state_vector) {
+              continue;
+            }
+            last_state_vector = Some(encoded_collab.state_vector.clone());
+            if is_first_tick {
+              continue;
+            }
+
+            trace!("create snapshot for {}:{}", collab_type, object_id);
+            if let Err(err) = snapshot_persistence.create_snapshot(
+              uid,
+              &object_id,
+              &collab_type,
+              encoded_collab.doc_state.to_vec(),
+            ) {
+              warn!("create snapshot for {} failed: {}", object_id, err);
+            }
+          },
+          Err(err) => warn!("encode collab {} for snapshot failed: {}", object_id, err),
+        }
+      }
+    });
+  }
+
   /// Remove all updates in disk and write the final state vector to disk.
   #[instrument(level = "trace", skip_all, err)]
   pub fn write_collab_to_disk<T>(
@@ -1,12 +1,17 @@
 use crate::event_builder::EventBuilder;
 use crate::EventIntegrationTest;
 use flowy_ai::entities::{
-  ChatMessageListPB, ChatMessageTypePB, CompleteTextPB, CompleteTextTaskPB, CompletionTypePB,
-  LoadNextChatMessagePB, LoadPrevChatMessagePB, SendChatPayloadPB,
+  AIUsageLimitPB, AIUsageQueryPB, AIUsageSummaryPB, ChatMessageBranchesPB, ChatMessageIdPB,
+  ChatMessageListPB, ChatMessagePB, ChatMessageSearchHitPB, ChatMessageTypePB, CompleteTextPB,
+  CompleteTextTaskPB, CompletionTypePB, CreateCustomPromptPB, CustomPromptIdPB, CustomPromptPB,
+  EditQuestionPB, LoadNextChatMessagePB, LoadPrevChatMessagePB, OpenAICompatibleSettingPB,
+  RegenerateAnswerPB, RepeatedAIUsageSummaryPB, RepeatedChatMessageSearchHitPB,
+  RepeatedCustomPromptPB, SearchChatMessagesPB, SendChatPayloadPB, UpdateCustomPromptPB,
 };
 use flowy_ai::event_map::AIEvent;
 use flowy_folder::entities::{CreateViewPayloadPB, ViewLayoutPB, ViewPB};
 use flowy_folder::event_map::FolderEvent;
+use flowy_user::errors::FlowyError;
 
 impl EventIntegrationTest {
   pub async fn create_chat(&self, parent_id: &str) -> ViewPB {
@@ -97,6 +102,8 @@ impl EventIntegrationTest {
       text: text.to_string(),
       completion_type,
       stream_port: 0,
+      custom_prompt_id: None,
+      context: None,
     };
     EventBuilder::new(self.clone())
       .event(AIEvent::CompleteText)
@@ -105,4 +112,169 @@ impl EventIntegrationTest {
       .await
       .parse::<CompleteTextTaskPB>()
   }
+
+  pub async fn get_openai_compatible_setting(&self) -> OpenAICompatibleSettingPB {
+    EventBuilder::new(self.clone())
+      .event(AIEvent::GetOpenAICompatibleSetting)
+      .async_send()
+      .await
+      .parse::<OpenAICompatibleSettingPB>()
+  }
+
+  pub async fn update_openai_compatible_setting(
+    &self,
+    setting: OpenAICompatibleSettingPB,
+  ) -> Option<FlowyError> {
+    EventBuilder::new(self.clone())
+      .event(AIEvent::UpdateOpenAICompatibleSetting)
+      .payload(setting)
+      .async_send()
+      .await
+      .error()
+  }
+
+  pub async fn get_custom_prompts(&self) -> Vec<CustomPromptPB> {
+    EventBuilder::new(self.clone())
+      .event(AIEvent::GetCustomPrompts)
+      .async_send()
+      .await
+      .parse::<RepeatedCustomPromptPB>()
+      .items
+  }
+
+  pub async fn create_custom_prompt(&self, name: &str, template: &str) -> CustomPromptPB {
+    let payload = CreateCustomPromptPB {
+      name: name.to_string(),
+      template: template.to_string(),
+    };
+    EventBuilder::new(self.clone())
+      .event(AIEvent::CreateCustomPrompt)
+      .payload(payload)
+      .async_send()
+      .await
+      .parse::<CustomPromptPB>()
+  }
+
+  pub async fn update_custom_prompt(&self, payload: UpdateCustomPromptPB) -> CustomPromptPB {
+    EventBuilder::new(self.clone())
+      .event(AIEvent::UpdateCustomPrompt)
+      .payload(payload)
+      .async_send()
+      .await
+      .parse::<CustomPromptPB>()
+  }
+
+  pub async fn delete_custom_prompt(&self, id: &str) -> Option<FlowyError> {
+    EventBuilder::new(self.clone())
+      .event(AIEvent::DeleteCustomPrompt)
+      .payload(CustomPromptIdPB { id: id.to_string() })
+      .async_send()
+      .await
+      .error()
+  }
+
+  pub async fn get_ai_usage_summary(&self, query: AIUsageQueryPB) -> Vec<AIUsageSummaryPB> {
+    EventBuilder::new(self.clone())
+      .event(AIEvent::GetAIUsageSummary)
+      .payload(query)
+      .async_send()
+      .await
+      .parse::<RepeatedAIUsageSummaryPB>()
+      .items
+  }
+
+  pub async fn get_ai_usage_limit(&self) -> AIUsageLimitPB {
+    EventBuilder::new(self.clone())
+      .event(AIEvent::GetAIUsageLimit)
+      .async_send()
+      .await
+      .parse::<AIUsageLimitPB>()
+  }
+
+  pub async fn update_ai_usage_limit(&self, limit: AIUsageLimitPB) -> Option<FlowyError> {
+    EventBuilder::new(self.clone())
+      .event(AIEvent::UpdateAIUsageLimit)
+      .payload(limit)
+      .async_send()
+      .await
+      .error()
+  }
+
+  pub async fn regenerate_answer(&self, chat_id: &str, question_message_id: i64) {
+    let payload = RegenerateAnswerPB {
+      chat_id: chat_id.to_string(),
+      question_message_id,
+      answer_stream_port: 0,
+    };
+    EventBuilder::new(self.clone())
+      .event(AIEvent::RegenerateAnswer)
+      .payload(payload)
+      .async_send()
+      .await;
+  }
+
+  pub async fn edit_question(
+    &self,
+    chat_id: &str,
+    message_id: i64,
+    message: &str,
+  ) -> ChatMessagePB {
+    let payload = EditQuestionPB {
+      chat_id: chat_id.to_string(),
+      message_id,
+      message: message.to_string(),
+      answer_stream_port: 0,
+      question_stream_port: 0,
+    };
+    EventBuilder::new(self.clone())
+      .event(AIEvent::EditQuestion)
+      .payload(payload)
+      .async_send()
+      .await
+      .parse::<ChatMessagePB>()
+  }
+
+  pub async fn get_message_branches(
+    &self,
+    chat_id: &str,
+    message_id: i64,
+  ) -> ChatMessageBranchesPB {
+    let payload = ChatMessageIdPB {
+      chat_id: chat_id.to_string(),
+      message_id,
+    };
+    EventBuilder::new(self.clone())
+      .event(AIEvent::GetMessageBranches)
+      .payload(payload)
+      .async_send()
+      .await
+      .parse::<ChatMessageBranchesPB>()
+  }
+
+  pub async fn switch_message_branch(&self, chat_id: &str, message_id: i64) -> Option<FlowyError> {
+    let payload = ChatMessageIdPB {
+      chat_id: chat_id.to_string(),
+      message_id,
+    };
+    EventBuilder::new(self.clone())
+      .event(AIEvent::SwitchMessageBranch)
+      .payload(payload)
+      .async_send()
+      .await
+      .error()
+  }
+
+  pub async fn search_chat_messages(&self, query: &str, limit: i64) -> Vec<ChatMessageSearchHitPB> {
+    let payload = SearchChatMessagesPB {
+      query: query.to_string(),
+      limit,
+    };
+    EventBuilder::new(self.clone())
+      .event(AIEvent::SearchChatMessages)
+      .payload(payload)
+      .async_send()
+      .await
+      .parse::<RepeatedChatMessageSearchHitPB>()
+      .items
+  }
 }
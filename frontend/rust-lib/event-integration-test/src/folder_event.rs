@@ -118,6 +118,22 @@ impl EventIntegrationTest {
       .unwrap()
   }
 
+  pub fn get_document_search_handler(&self) -> &Arc<dyn SearchHandler> {
+    self
+      .appflowy_core
+      .search_manager
+      .get_handler(SearchType::Document)
+      .unwrap()
+  }
+
+  pub fn get_database_search_handler(&self) -> &Arc<dyn SearchHandler> {
+    self
+      .appflowy_core
+      .search_manager
+      .get_handler(SearchType::Database)
+      .unwrap()
+  }
+
   /// create views in the folder.
   pub async fn create_views(&self, views: Vec<View>) {
     let create_view_params = views
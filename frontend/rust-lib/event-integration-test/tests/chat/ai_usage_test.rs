@@ -0,0 +1,44 @@
+use event_integration_test::EventIntegrationTest;
+use flowy_ai::entities::{AIUsageGroupPB, AIUsageLimitPB, AIUsageQueryPB};
+
+#[tokio::test]
+async fn update_ai_usage_limit_test() {
+  let test = EventIntegrationTest::new_anon().await;
+  let limit = test.get_ai_usage_limit().await;
+  assert_eq!(limit.daily_request_limit, 0);
+  assert_eq!(limit.daily_token_limit, 0);
+
+  let error = test
+    .update_ai_usage_limit(AIUsageLimitPB {
+      daily_request_limit: 50,
+      daily_token_limit: 100_000,
+    })
+    .await;
+  assert!(error.is_none());
+  let limit = test.get_ai_usage_limit().await;
+  assert_eq!(limit.daily_request_limit, 50);
+  assert_eq!(limit.daily_token_limit, 100_000);
+
+  // The limits must not be negative
+  let error = test
+    .update_ai_usage_limit(AIUsageLimitPB {
+      daily_request_limit: -1,
+      daily_token_limit: 0,
+    })
+    .await;
+  assert!(error.is_some());
+  assert_eq!(test.get_ai_usage_limit().await.daily_request_limit, 50);
+}
+
+#[tokio::test]
+async fn get_empty_ai_usage_summary_test() {
+  let test = EventIntegrationTest::new_anon().await;
+  let summary = test
+    .get_ai_usage_summary(AIUsageQueryPB {
+      group_by: AIUsageGroupPB::Model,
+      start_time: 0,
+      end_time: 0,
+    })
+    .await;
+  assert!(summary.is_empty());
+}
@@ -4,9 +4,10 @@ use event_integration_test::EventIntegrationTest;
 use flowy_ai::entities::ChatMessageListPB;
 use flowy_ai::notification::ChatNotification;
 
-use flowy_ai_pub::cloud::ChatMessageType;
+use flowy_ai_pub::cloud::{ChatMessage, ChatMessageType};
 
 use std::time::Duration;
+use tokio::time::sleep;
 
 #[tokio::test]
 async fn af_cloud_create_chat_message_test() {
@@ -127,6 +128,188 @@ async fn af_cloud_load_remote_system_message_test() {
   assert_eq!(first_five_messages.messages[4].content, "hello server 0");
 }
 
+#[tokio::test]
+async fn af_cloud_search_chat_message_test() {
+  use_localhost_af_cloud().await;
+  let test = EventIntegrationTest::new().await;
+  test.af_cloud_sign_up().await;
+
+  let current_workspace = test.get_current_workspace().await;
+  let first_chat = test.create_chat(&current_workspace.id).await;
+  let second_chat = test.create_chat(&current_workspace.id).await;
+  let chat_service = test.server_provider.get_server().unwrap().chat_service();
+  for (chat_id, message) in [
+    (&first_chat.id, "How do I rename a database field?"),
+    (&first_chat.id, "Where are the kanban settings?"),
+    (&second_chat.id, "Rename the selected rows"),
+    (&second_chat.id, "如何重命名字段"),
+  ] {
+    let _ = chat_service
+      .create_question(
+        &current_workspace.id,
+        chat_id,
+        message,
+        ChatMessageType::System,
+        &[],
+      )
+      .await
+      .unwrap();
+  }
+
+  // The messages are searchable once they are saved on disk
+  for chat_id in [&first_chat.id, &second_chat.id] {
+    let rx = test
+      .notification_sender
+      .subscribe::<ChatMessageListPB>(chat_id, ChatNotification::DidLoadLatestChatMessage);
+    let _ = test.load_next_message(chat_id, 10, None).await;
+    let _ = receive_with_timeout(rx, Duration::from_secs(30))
+      .await
+      .unwrap();
+  }
+
+  let hits = test.search_chat_messages("renam", 10).await;
+  assert_eq!(hits.len(), 2);
+  assert!(hits.iter().any(|hit| hit.chat_id == first_chat.id));
+  assert!(hits.iter().any(|hit| hit.chat_id == second_chat.id));
+  assert!(hits.iter().all(|hit| hit.snippet.contains("**")));
+
+  let hits = test.search_chat_messages("kanban", 10).await;
+  assert_eq!(hits.len(), 1);
+  assert_eq!(hits[0].snippet, "Where are the **kanban** settings?");
+
+  // The words of the scripts without spaces are matched as substrings, the short terms too
+  let hits = test.search_chat_messages("重命名", 10).await;
+  assert_eq!(hits.len(), 1);
+  assert_eq!(hits[0].snippet, "如何**重命名**字段");
+  let hits = test.search_chat_messages("字段", 10).await;
+  assert_eq!(hits.len(), 1);
+  assert_eq!(hits[0].chat_id, second_chat.id);
+}
+
+/// Creates a question and its answer, and waits until they are saved on disk.
+async fn create_question_and_answer(
+  test: &EventIntegrationTest,
+  chat_id: &str,
+) -> (ChatMessage, ChatMessage) {
+  let workspace_id = test.get_current_workspace().await.id;
+  let chat_service = test.server_provider.get_server().unwrap().chat_service();
+  let question = chat_service
+    .create_question(
+      &workspace_id,
+      chat_id,
+      "What is a kanban board?",
+      ChatMessageType::User,
+      &[],
+    )
+    .await
+    .unwrap();
+  let answer = chat_service
+    .create_answer(
+      &workspace_id,
+      chat_id,
+      "A board of cards",
+      question.message_id,
+      None,
+    )
+    .await
+    .unwrap();
+
+  let rx = test
+    .notification_sender
+    .subscribe::<ChatMessageListPB>(chat_id, ChatNotification::DidLoadLatestChatMessage);
+  let _ = test.load_next_message(chat_id, 10, None).await;
+  let _ = receive_with_timeout(rx, Duration::from_secs(30))
+    .await
+    .unwrap();
+  (question, answer)
+}
+
+async fn shown_message_ids(test: &EventIntegrationTest, chat_id: &str) -> Vec<i64> {
+  test
+    .load_prev_message(chat_id, 10, None)
+    .await
+    .messages
+    .into_iter()
+    .map(|message| message.message_id)
+    .collect()
+}
+
+#[tokio::test]
+async fn af_cloud_edit_question_and_switch_branch_test() {
+  use_localhost_af_cloud().await;
+  let test = EventIntegrationTest::new().await;
+  test.af_cloud_sign_up().await;
+
+  let current_workspace = test.get_current_workspace().await;
+  let chat_id = test.create_chat(&current_workspace.id).await.id;
+  let (question, answer) = create_question_and_answer(&test, &chat_id).await;
+
+  // The edited question is shown instead of the question and its answer
+  let edited = test
+    .edit_question(&chat_id, question.message_id, "What is a grid?")
+    .await;
+  let ids = shown_message_ids(&test, &chat_id).await;
+  assert!(ids.contains(&edited.message_id));
+  assert!(!ids.contains(&question.message_id));
+  assert!(!ids.contains(&answer.message_id));
+  // The hidden question isn't found by the search
+  let hits = test.search_chat_messages("kanban", 10).await;
+  assert!(hits.is_empty());
+
+  let branches = test.get_message_branches(&chat_id, edited.message_id).await;
+  let branch_ids = branches
+    .branches
+    .iter()
+    .map(|message| message.message_id)
+    .collect::<Vec<_>>();
+  assert_eq!(branch_ids, vec![question.message_id, edited.message_id]);
+  assert_eq!(branches.active_message_id, edited.message_id);
+
+  // Switching back shows the question with its answer
+  let error = test
+    .switch_message_branch(&chat_id, question.message_id)
+    .await;
+  assert!(error.is_none());
+  let ids = shown_message_ids(&test, &chat_id).await;
+  assert!(ids.contains(&question.message_id));
+  assert!(ids.contains(&answer.message_id));
+  assert!(!ids.contains(&edited.message_id));
+  let hits = test.search_chat_messages("kanban", 10).await;
+  assert_eq!(hits.len(), 1);
+  assert_eq!(hits[0].message_id, question.message_id);
+}
+
+#[tokio::test]
+async fn af_cloud_regenerate_answer_test() {
+  use_localhost_af_cloud().await;
+  let test = EventIntegrationTest::new().await;
+  test.af_cloud_sign_up().await;
+
+  let current_workspace = test.get_current_workspace().await;
+  let chat_id = test.create_chat(&current_workspace.id).await.id;
+  let (question, answer) = create_question_and_answer(&test, &chat_id).await;
+
+  test.regenerate_answer(&chat_id, question.message_id).await;
+  // The new answer is saved after it was streamed
+  let mut branches = test.get_message_branches(&chat_id, answer.message_id).await;
+  for _ in 0..60 {
+    if branches.branches.len() == 2 {
+      break;
+    }
+    sleep(Duration::from_secs(1)).await;
+    branches = test.get_message_branches(&chat_id, answer.message_id).await;
+  }
+  assert_eq!(branches.branches.len(), 2);
+  assert_eq!(branches.branches[0].message_id, answer.message_id);
+  let regenerated_id = branches.branches[1].message_id;
+  assert_eq!(branches.active_message_id, regenerated_id);
+
+  let ids = shown_message_ids(&test, &chat_id).await;
+  assert!(ids.contains(&question.message_id));
+  assert!(ids.contains(&regenerated_id));
+  assert!(!ids.contains(&answer.message_id));
+}
+
 // #[tokio::test]
 // async fn af_cloud_load_remote_user_message_test() {
 //   user_localhost_af_cloud().await;
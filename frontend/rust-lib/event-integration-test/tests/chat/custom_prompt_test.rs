@@ -0,0 +1,29 @@
+use event_integration_test::EventIntegrationTest;
+use flowy_ai::entities::UpdateCustomPromptPB;
+
+#[tokio::test]
+async fn create_update_and_delete_custom_prompt_test() {
+  let test = EventIntegrationTest::new_anon().await;
+  assert!(test.get_custom_prompts().await.is_empty());
+
+  let prompt = test
+    .create_custom_prompt("Translate", "Translate {{selection}} into French")
+    .await;
+  assert_eq!(prompt.name, "Translate");
+  assert_eq!(test.get_custom_prompts().await.len(), 1);
+
+  // An empty name is ignored
+  let updated = test
+    .update_custom_prompt(UpdateCustomPromptPB {
+      id: prompt.id.clone(),
+      name: Some("".to_string()),
+      template: Some("Translate {{selection}} into German".to_string()),
+    })
+    .await;
+  assert_eq!(updated.name, "Translate");
+  assert_eq!(updated.template, "Translate {{selection}} into German");
+
+  assert!(test.delete_custom_prompt(&prompt.id).await.is_none());
+  assert!(test.get_custom_prompts().await.is_empty());
+  assert!(test.delete_custom_prompt(&prompt.id).await.is_some());
+}
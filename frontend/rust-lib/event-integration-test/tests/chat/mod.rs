@@ -1,2 +1,5 @@
 mod ai_tool_test;
+mod ai_usage_test;
 mod chat_message_test;
+mod custom_prompt_test;
+mod openai_compatible_test;
@@ -0,0 +1,40 @@
+use event_integration_test::EventIntegrationTest;
+use flowy_ai::entities::OpenAICompatibleSettingPB;
+
+#[tokio::test]
+async fn update_openai_compatible_setting_test() {
+  let test = EventIntegrationTest::new_anon().await;
+  let setting = test.get_openai_compatible_setting().await;
+  assert!(!setting.enabled);
+  assert!(setting.base_url.is_empty());
+
+  let error = test
+    .update_openai_compatible_setting(OpenAICompatibleSettingPB {
+      enabled: true,
+      base_url: "http://192.168.1.2:11434".to_string(),
+      model: "llama3.2".to_string(),
+      api_key: "".to_string(),
+    })
+    .await;
+  assert!(error.is_none());
+
+  let setting = test.get_openai_compatible_setting().await;
+  assert!(setting.enabled);
+  assert_eq!(setting.base_url, "http://192.168.1.2:11434");
+  assert_eq!(setting.model, "llama3.2");
+
+  // The base URL must be an http(s) URL
+  let error = test
+    .update_openai_compatible_setting(OpenAICompatibleSettingPB {
+      enabled: true,
+      base_url: "192.168.1.2:11434".to_string(),
+      model: "llama3.2".to_string(),
+      api_key: "".to_string(),
+    })
+    .await;
+  assert!(error.is_some());
+  assert_eq!(
+    test.get_openai_compatible_setting().await.base_url,
+    "http://192.168.1.2:11434"
+  );
+}
@@ -2,7 +2,10 @@ mod folder_test;
 mod import_test;
 mod script;
 mod subscription_test;
+mod template_test;
 mod test;
+mod view_tag_test;
+mod transfer_test;
 
 mod publish_database_test;
 mod publish_document_test;
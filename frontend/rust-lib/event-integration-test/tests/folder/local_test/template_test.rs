@@ -0,0 +1,59 @@
+use std::env::temp_dir;
+
+use event_integration_test::event_builder::EventBuilder;
+use event_integration_test::EventIntegrationTest;
+use flowy_folder::entities::{
+  CreateViewFromTemplatePayloadPB, SaveViewAsTemplatePayloadPB, ViewLayoutPB, ViewPB,
+};
+use flowy_folder::event_map::FolderEvent;
+
+#[tokio::test]
+async fn save_view_as_template_and_create_from_template_test() {
+  let test = EventIntegrationTest::new_anon().await;
+  let current_workspace = test.get_current_workspace().await;
+  let parent = test
+    .create_view(&current_workspace.id, "My template".to_string())
+    .await;
+  let _child_document = test.create_view(&parent.id, "Notes".to_string()).await;
+  let _child_grid = test
+    .create_view_with_layout(&parent.id, "Tasks".to_string(), ViewLayoutPB::Grid)
+    .await;
+
+  let file_path = temp_dir()
+    .join(format!("{}.json", uuid::Uuid::new_v4()))
+    .to_str()
+    .unwrap()
+    .to_string();
+  let error = EventBuilder::new(test.clone())
+    .event(FolderEvent::SaveViewAsTemplate)
+    .payload(SaveViewAsTemplatePayloadPB {
+      view_id: parent.id.clone(),
+      name: "My template".to_string(),
+      file_path: file_path.clone(),
+    })
+    .async_send()
+    .await
+    .error();
+  assert!(error.is_none());
+
+  let new_view = EventBuilder::new(test.clone())
+    .event(FolderEvent::CreateViewFromTemplate)
+    .payload(CreateViewFromTemplatePayloadPB {
+      file_path: file_path.clone(),
+      parent_view_id: None,
+    })
+    .async_send()
+    .await
+    .parse::<ViewPB>();
+
+  assert_ne!(new_view.id, parent.id);
+  assert_eq!(new_view.name, "My template");
+  assert_eq!(new_view.parent_view_id, current_workspace.id);
+  let child_views = test.get_view(&new_view.id).await.child_views;
+  assert_eq!(child_views.len(), 2);
+  assert_eq!(child_views[0].name, "Notes");
+  assert_eq!(child_views[1].name, "Tasks");
+  assert_eq!(child_views[1].layout, ViewLayoutPB::Grid);
+
+  let _ = std::fs::remove_file(file_path);
+}
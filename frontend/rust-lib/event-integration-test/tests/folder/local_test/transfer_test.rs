@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+
+use collab::core::origin::CollabOrigin;
+use collab::preclude::Collab;
+use collab_folder::{Folder, FolderData, Workspace};
+use collab_plugins::local_storage::kv::doc::CollabKVAction;
+use collab_plugins::local_storage::kv::KVTransactionDB;
+use event_integration_test::document::document_event::DocumentEventTest;
+use event_integration_test::event_builder::EventBuilder;
+use event_integration_test::EventIntegrationTest;
+use flowy_folder::entities::MoveViewToWorkspacePayloadPB;
+use flowy_folder::event_map::FolderEvent;
+use flowy_user::errors::FlowyError;
+use flowy_user_pub::entities::UserWorkspace;
+
+async fn move_view_to_workspace(
+  test: &EventIntegrationTest,
+  view_id: &str,
+  workspace_id: &str,
+) -> Option<FlowyError> {
+  EventBuilder::new(test.clone())
+    .event(FolderEvent::MoveViewToWorkspace)
+    .payload(MoveViewToWorkspacePayloadPB {
+      view_id: view_id.to_string(),
+      workspace_id: workspace_id.to_string(),
+      parent_view_id: None,
+      keep_original: false,
+    })
+    .async_send()
+    .await
+    .error()
+}
+
+/// Write an empty folder for the workspace that was never opened, the local server can't provide
+/// the folder of the workspace.
+fn create_workspace_folder(test: &EventIntegrationTest, uid: i64, workspace_id: &str) {
+  let folder_data = FolderData {
+    workspace: Workspace {
+      id: workspace_id.to_string(),
+      name: "Target".to_string(),
+      child_views: Default::default(),
+      created_at: 0,
+      created_by: Some(uid),
+      last_edited_time: 0,
+      last_edited_by: Some(uid),
+    },
+    current_view: "".to_string(),
+    views: vec![],
+    favorites: Default::default(),
+    recent: Default::default(),
+    trash: Default::default(),
+    private: Default::default(),
+  };
+  let collab = Collab::new_with_origin(CollabOrigin::Empty, workspace_id, vec![], false);
+  let folder = Folder::create(uid, collab, None, folder_data);
+  let encoded_collab = folder.encode_collab().unwrap();
+  let collab_db = test
+    .user_manager
+    .get_collab_db(uid)
+    .unwrap()
+    .upgrade()
+    .unwrap();
+  let write_txn = collab_db.write_txn();
+  write_txn
+    .flush_doc(
+      uid,
+      workspace_id,
+      workspace_id,
+      encoded_collab.state_vector.to_vec(),
+      encoded_collab.doc_state.to_vec(),
+    )
+    .unwrap();
+  write_txn.commit_transaction().unwrap();
+}
+
+fn open_workspace_folder(test: &EventIntegrationTest, uid: i64, workspace_id: &str) -> Folder {
+  let collab_db = test
+    .user_manager
+    .get_collab_db(uid)
+    .unwrap()
+    .upgrade()
+    .unwrap();
+  let mut collab = Collab::new_with_origin(CollabOrigin::Empty, workspace_id, vec![], false);
+  collab_db
+    .read_txn()
+    .load_doc_with_txn(uid, workspace_id, workspace_id, &mut collab.transact_mut())
+    .unwrap();
+  Folder::open(uid, collab, None).unwrap()
+}
+
+#[tokio::test]
+async fn move_view_to_workspace_retry_after_failure_test() {
+  let test = EventIntegrationTest::new_anon().await;
+  let uid = test.get_user_profile().await.unwrap().id;
+  let current_workspace = test.get_current_workspace().await;
+  let document = test
+    .create_view(&current_workspace.id, "A".to_string())
+    .await;
+  let _child = test.create_view(&document.id, "A1".to_string()).await;
+
+  let target_workspace = UserWorkspace::new(&uuid::Uuid::new_v4().to_string(), uid);
+  test
+    .user_manager
+    .save_user_workspace(uid, &target_workspace)
+    .unwrap();
+  create_workspace_folder(&test, uid, &target_workspace.id);
+
+  // The document refers to a file that doesn't exist, so the file can't be copied.
+  let document_event = DocumentEventTest::new_with_core(test.clone());
+  let page_id = document_event.get_page_id(&document.id).await;
+  let missing_file_url = format!(
+    "appflowy-local://{}/{}/missing.png",
+    current_workspace.id, document.id
+  );
+  document_event
+    .update_data(
+      &document.id,
+      &page_id,
+      HashMap::from([("url".to_string(), missing_file_url.into())]),
+    )
+    .await;
+  let error = move_view_to_workspace(&test, &document.id, &target_workspace.id).await;
+  assert!(error.is_some());
+
+  // The original views are kept when the copy fails and nothing is added to the target.
+  let views = test.get_all_workspace_views().await;
+  assert!(views.iter().any(|view| view.id == document.id));
+  assert!(test.get_trash().await.items.is_empty());
+  let folder = open_workspace_folder(&test, uid, &target_workspace.id);
+  assert!(folder.get_views_belong_to(&target_workspace.id).is_empty());
+
+  // Retry after the broken file reference is removed.
+  document_event
+    .update_data(&document.id, &page_id, HashMap::new())
+    .await;
+  let error = move_view_to_workspace(&test, &document.id, &target_workspace.id).await;
+  assert!(error.is_none());
+
+  let views = test.get_all_workspace_views().await;
+  assert!(views.iter().all(|view| view.id != document.id));
+  let trash = test.get_trash().await;
+  assert!(trash.items.iter().any(|trash| trash.id == document.id));
+
+  // The copies are created in the target workspace right away.
+  let folder = open_workspace_folder(&test, uid, &target_workspace.id);
+  let moved_views = folder.get_views_belong_to(&target_workspace.id);
+  assert_eq!(moved_views.len(), 1);
+  assert_eq!(moved_views[0].name, "A");
+  assert_ne!(moved_views[0].id, document.id);
+  let child_views = folder.get_views_belong_to(&moved_views[0].id);
+  assert_eq!(child_views.len(), 1);
+  assert_eq!(child_views[0].name, "A1");
+
+  let collab_db = test
+    .user_manager
+    .get_collab_db(uid)
+    .unwrap()
+    .upgrade()
+    .unwrap();
+  let read_txn = collab_db.read_txn();
+  assert!(read_txn.is_exist(uid, &target_workspace.id, &moved_views[0].id));
+  assert!(read_txn.is_exist(uid, &target_workspace.id, &child_views[0].id));
+}
@@ -0,0 +1,98 @@
+use event_integration_test::event_builder::EventBuilder;
+use event_integration_test::EventIntegrationTest;
+use flowy_folder::entities::{
+  RepeatedViewPB, RepeatedViewTagPB, UpdateViewTagsPayloadPB, ViewTagNamePB,
+};
+use flowy_folder::event_map::FolderEvent;
+
+async fn add_view_tags(test: &EventIntegrationTest, view_id: &str, tags: Vec<&str>) {
+  let error = EventBuilder::new(test.clone())
+    .event(FolderEvent::AddViewTags)
+    .payload(UpdateViewTagsPayloadPB {
+      view_id: view_id.to_string(),
+      tags: tags.into_iter().map(|tag| tag.to_string()).collect(),
+    })
+    .async_send()
+    .await
+    .error();
+  assert!(error.is_none());
+}
+
+async fn get_workspace_view_tags(test: &EventIntegrationTest) -> RepeatedViewTagPB {
+  EventBuilder::new(test.clone())
+    .event(FolderEvent::GetWorkspaceViewTags)
+    .async_send()
+    .await
+    .parse::<RepeatedViewTagPB>()
+}
+
+async fn get_views_by_tag(test: &EventIntegrationTest, name: &str) -> RepeatedViewPB {
+  EventBuilder::new(test.clone())
+    .event(FolderEvent::GetViewsByTag)
+    .payload(ViewTagNamePB {
+      name: name.to_string(),
+    })
+    .async_send()
+    .await
+    .parse::<RepeatedViewPB>()
+}
+
+#[tokio::test]
+async fn add_and_remove_view_tags_test() {
+  let test = EventIntegrationTest::new_anon().await;
+  let workspace_id = test.get_current_workspace().await.id;
+  let view_a = test.create_view(&workspace_id, "A".to_string()).await;
+  let view_b = test.create_view(&workspace_id, "B".to_string()).await;
+
+  add_view_tags(&test, &view_a.id, vec!["work", " urgent "]).await;
+  // tags are compared case-insensitively
+  add_view_tags(&test, &view_b.id, vec!["Work"]).await;
+
+  let view = test.get_view(&view_a.id).await;
+  assert_eq!(view.tags, vec!["work".to_string(), "urgent".to_string()]);
+
+  let tags = get_workspace_view_tags(&test).await.items;
+  assert_eq!(tags.len(), 2);
+  assert_eq!(tags[0].name, "urgent");
+  assert_eq!(tags[0].view_count, 1);
+  assert_eq!(tags[1].name, "work");
+  assert_eq!(tags[1].view_count, 2);
+
+  let views = get_views_by_tag(&test, "WORK").await.items;
+  assert_eq!(views.len(), 2);
+
+  let error = EventBuilder::new(test.clone())
+    .event(FolderEvent::RemoveViewTags)
+    .payload(UpdateViewTagsPayloadPB {
+      view_id: view_a.id.clone(),
+      tags: vec!["work".to_string()],
+    })
+    .async_send()
+    .await
+    .error();
+  assert!(error.is_none());
+
+  let view = test.get_view(&view_a.id).await;
+  assert_eq!(view.tags, vec!["urgent".to_string()]);
+  let views = get_views_by_tag(&test, "work").await.items;
+  assert_eq!(views.len(), 1);
+  assert_eq!(views[0].id, view_b.id);
+}
+
+#[tokio::test]
+async fn add_empty_view_tag_test() {
+  let test = EventIntegrationTest::new_anon().await;
+  let workspace_id = test.get_current_workspace().await.id;
+  let view = test.create_view(&workspace_id, "A".to_string()).await;
+
+  let error = EventBuilder::new(test.clone())
+    .event(FolderEvent::AddViewTags)
+    .payload(UpdateViewTagsPayloadPB {
+      view_id: view.id.clone(),
+      tags: vec!["  ".to_string()],
+    })
+    .async_send()
+    .await
+    .error();
+  assert!(error.is_some());
+}
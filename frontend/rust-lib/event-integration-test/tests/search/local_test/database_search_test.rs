@@ -0,0 +1,103 @@
+use event_integration_test::EventIntegrationTest;
+use flowy_database2::entities::{CellChangesetPB, FieldType};
+use flowy_search::entities::{IndexTypePB, SearchFilterPB, SearchHighlightPB, SearchResultPB};
+use flowy_search::services::manager::DEFAULT_SEARCH_LIMIT;
+use std::time::Duration;
+use tokio::time::sleep;
+
+async fn search_rows(test: &EventIntegrationTest, query: &str) -> Vec<SearchResultPB> {
+  let workspace_id = test.get_current_workspace().await.id;
+  test
+    .get_database_search_handler()
+    .perform_search(
+      query.to_string(),
+      Some(SearchFilterPB {
+        workspace_id: Some(workspace_id),
+        ..Default::default()
+      }),
+      DEFAULT_SEARCH_LIMIT,
+    )
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_database_index_search_text_cell() {
+  let test = EventIntegrationTest::new_anon().await;
+  let current_workspace = test.get_current_workspace().await;
+  let grid_view = test
+    .create_grid(&current_workspace.id, "Garden".to_owned(), vec![])
+    .await;
+  let database = test.get_database(&grid_view.id).await;
+  let fields = test.get_all_database_fields(&grid_view.id).await.items;
+  assert_eq!(fields[0].field_type, FieldType::RichText);
+
+  let row_id = database.rows[0].id.clone();
+  let field_id = fields[0].id.clone();
+  let error = test
+    .update_cell(CellChangesetPB {
+      view_id: grid_view.id.clone(),
+      row_id: row_id.clone(),
+      field_id: field_id.clone(),
+      cell_changeset: "Water the orchids".to_string(),
+    })
+    .await;
+  assert!(error.is_none());
+
+  // Wait for the row to be re-indexed after the change
+  sleep(Duration::from_secs(3)).await;
+
+  let results = search_rows(&test, "orchids").await;
+  assert_eq!(results.len(), 1);
+  assert_eq!(results[0].index_type, IndexTypePB::DatabaseRow);
+  assert_eq!(results[0].view_id, grid_view.id);
+  assert_eq!(results[0].id, row_id);
+  assert_eq!(results[0].field_id.as_deref(), Some(field_id.as_str()));
+  assert_eq!(results[0].data, "Water the orchids");
+  assert_eq!(
+    results[0].highlights,
+    vec![SearchHighlightPB { start: 10, end: 17 }]
+  );
+
+  let error = test.delete_row(&grid_view.id, &row_id).await;
+  assert!(error.is_none());
+  sleep(Duration::from_secs(3)).await;
+
+  let results = search_rows(&test, "orchids").await;
+  assert!(results.is_empty());
+}
+
+#[tokio::test]
+async fn test_database_index_remove_deleted_field() {
+  let test = EventIntegrationTest::new_anon().await;
+  let current_workspace = test.get_current_workspace().await;
+  let grid_view = test
+    .create_grid(&current_workspace.id, "Garden".to_owned(), vec![])
+    .await;
+  let database = test.get_database(&grid_view.id).await;
+  let field = test.create_field(&grid_view.id, FieldType::RichText).await;
+
+  let row_id = database.rows[0].id.clone();
+  let error = test
+    .update_cell(CellChangesetPB {
+      view_id: grid_view.id.clone(),
+      row_id: row_id.clone(),
+      field_id: field.id.clone(),
+      cell_changeset: "Water the orchids".to_string(),
+    })
+    .await;
+  assert!(error.is_none());
+  sleep(Duration::from_secs(3)).await;
+
+  let results = search_rows(&test, "orchids").await;
+  assert_eq!(results.len(), 1);
+  assert_eq!(results[0].field_id.as_deref(), Some(field.id.as_str()));
+
+  // The cells of the deleted field are removed from the index
+  let error = test.delete_field(&grid_view.id, &field.id).await;
+  assert!(error.is_none());
+  sleep(Duration::from_secs(3)).await;
+
+  let results = search_rows(&test, "orchids").await;
+  assert!(results.is_empty());
+}
@@ -0,0 +1,102 @@
+use event_integration_test::document::document_event::DocumentEventTest;
+use event_integration_test::EventIntegrationTest;
+use flowy_search::entities::{IndexTypePB, SearchFilterPB, SearchHighlightPB};
+use flowy_search::services::manager::DEFAULT_SEARCH_LIMIT;
+use std::time::Duration;
+use tokio::time::sleep;
+
+#[tokio::test]
+async fn test_document_index_search_block_content() {
+  let test = EventIntegrationTest::new_anon().await;
+  let document = DocumentEventTest::new_with_core(test.clone());
+  let workspace_id = test.get_current_workspace().await.id;
+
+  let view = document.create_document().await;
+  document.open_document(view.id.clone()).await;
+  let block_id = document
+    .insert_index(&view.id, "Remember to water the orchids", 1, None)
+    .await;
+
+  // Wait for the document to be re-indexed after the change
+  sleep(Duration::from_secs(5)).await;
+
+  let handler = test.get_document_search_handler();
+  let results = handler
+    .perform_search(
+      "orchids".to_string(),
+      Some(SearchFilterPB {
+        workspace_id: Some(workspace_id),
+        ..Default::default()
+      }),
+      DEFAULT_SEARCH_LIMIT,
+    )
+    .await
+    .unwrap();
+  let result = results
+    .iter()
+    .find(|result| result.index_type == IndexTypePB::DocumentBlock)
+    .unwrap();
+  assert_eq!(result.view_id, view.id);
+  assert_eq!(result.id, block_id);
+  assert_eq!(
+    result.preview.as_deref(),
+    Some("Remember to water the orchids")
+  );
+  assert_eq!(
+    result.highlights,
+    vec![SearchHighlightPB { start: 22, end: 29 }]
+  );
+
+  let results = handler
+    .perform_search(
+      "tulips".to_string(),
+      Some(SearchFilterPB {
+        workspace_id: Some(test.get_current_workspace().await.id),
+        ..Default::default()
+      }),
+      DEFAULT_SEARCH_LIMIT,
+    )
+    .await
+    .unwrap();
+  assert!(results
+    .iter()
+    .all(|result| result.index_type != IndexTypePB::DocumentBlock));
+}
+
+#[tokio::test]
+async fn test_document_index_search_skip_trashed_documents_within_limit() {
+  let test = EventIntegrationTest::new_anon().await;
+  let document = DocumentEventTest::new_with_core(test.clone());
+  let workspace_id = test.get_current_workspace().await.id;
+
+  let trashed_view = document.create_document().await;
+  document.open_document(trashed_view.id.clone()).await;
+  document
+    .insert_index(&trashed_view.id, "orchids, orchids and more orchids", 1, None)
+    .await;
+  let view = document.create_document().await;
+  document.open_document(view.id.clone()).await;
+  document
+    .insert_index(&view.id, "Remember to water the orchids", 1, None)
+    .await;
+
+  // Wait for the documents to be re-indexed after the change
+  sleep(Duration::from_secs(5)).await;
+  test.delete_view(&trashed_view.id).await;
+
+  // The trashed document matches better, but it must not take the only result
+  let results = test
+    .get_document_search_handler()
+    .perform_search(
+      "orchids".to_string(),
+      Some(SearchFilterPB {
+        workspace_id: Some(workspace_id),
+        ..Default::default()
+      }),
+      1,
+    )
+    .await
+    .unwrap();
+  assert_eq!(results.len(), 1);
+  assert_eq!(results[0].view_id, view.id);
+}
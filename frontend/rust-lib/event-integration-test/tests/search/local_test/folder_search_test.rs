@@ -1,9 +1,19 @@
 use crate::util::{unzip_test_asset, zip};
 use collab_folder::View;
+use event_integration_test::event_builder::EventBuilder;
 use event_integration_test::EventIntegrationTest;
 use flowy_core::DEFAULT_NAME;
-use flowy_folder::entities::UpdateViewPayloadPB;
+use flowy_folder::entities::{
+  MoveNestedViewPayloadPB, UpdateViewPayloadPB, UpdateViewTagsPayloadPB, ViewLayoutPB,
+};
+use flowy_folder::event_map::FolderEvent;
 use flowy_folder_pub::folder_builder::{FlattedViews, NestedViewBuilder};
+use flowy_search::entities::{
+  RepeatedSearchIndexStatusPB, SearchDateRangePB, SearchFilterPB, SearchIndexTypePB,
+  ViewLayoutFilterPB,
+};
+use flowy_search::event_map::SearchEvent;
+use flowy_search::services::manager::{SearchPage, DEFAULT_SEARCH_LIMIT};
 use std::time::Duration;
 use tokio::time::sleep;
 
@@ -79,7 +89,9 @@ async fn test_folder_index_create_view() {
   // Wait for the index to be updated
   sleep(Duration::from_millis(500)).await;
 
-  let results = folder_search_manager.perform_search(view.name.clone(), None);
+  let results = folder_search_manager
+    .perform_search(view.name.clone(), None, DEFAULT_SEARCH_LIMIT)
+    .await;
   if let Err(e) = results {
     panic!("Error performing search: {:?}", e);
   }
@@ -114,12 +126,16 @@ async fn test_folder_index_rename_view() {
   // Wait for the index to be updated
   sleep(Duration::from_millis(500)).await;
 
-  let first = folder_search_manager.perform_search(view.name, None);
+  let first = folder_search_manager
+    .perform_search(view.name, None, DEFAULT_SEARCH_LIMIT)
+    .await;
   if let Err(e) = first {
     panic!("Error performing search: {:?}", e);
   }
 
-  let second = folder_search_manager.perform_search(new_view_name.clone(), None);
+  let second = folder_search_manager
+    .perform_search(new_view_name.clone(), None, DEFAULT_SEARCH_LIMIT)
+    .await;
   if let Err(e) = second {
     panic!("Error performing search: {:?}", e);
   }
@@ -132,6 +148,251 @@ async fn test_folder_index_rename_view() {
   assert_eq!(second[0].data, new_view_name);
 }
 
+#[tokio::test]
+async fn test_folder_index_view_tags() {
+  let test = EventIntegrationTest::new_anon().await;
+  let folder_search_manager = test.get_folder_search_handler();
+
+  // Wait for the index to be created/updated
+  sleep(Duration::from_secs(1)).await;
+
+  let workspace_id = test.get_current_workspace().await.id;
+  let view = test.create_view(&workspace_id, "Flowers".to_owned()).await;
+  EventBuilder::new(test.clone())
+    .event(FolderEvent::AddViewTags)
+    .payload(UpdateViewTagsPayloadPB {
+      view_id: view.id.clone(),
+      tags: vec!["gardening".to_string()],
+    })
+    .async_send()
+    .await;
+
+  // Wait for the index to be updated
+  sleep(Duration::from_millis(500)).await;
+
+  let results = folder_search_manager
+    .perform_search("gardening".to_string(), None, DEFAULT_SEARCH_LIMIT)
+    .await
+    .unwrap();
+  assert_eq!(results.len(), 1);
+  assert_eq!(results[0].view_id, view.id);
+}
+
+#[tokio::test]
+async fn test_folder_index_filter() {
+  let test = EventIntegrationTest::new_anon().await;
+  let folder_search_manager = test.get_folder_search_handler();
+
+  // Wait for the index to be created/updated
+  sleep(Duration::from_secs(1)).await;
+
+  let workspace_id = test.get_current_workspace().await.id;
+  let garden = test.create_view(&workspace_id, "Garden".to_owned()).await;
+  let document = test.create_view(&garden.id, "Flowers".to_owned()).await;
+  let grid = test
+    .create_view_with_layout(&workspace_id, "Flowers".to_owned(), ViewLayoutPB::Grid)
+    .await;
+
+  // Wait for the index to be updated
+  sleep(Duration::from_millis(500)).await;
+
+  let search = |filter: SearchFilterPB| {
+    folder_search_manager.perform_search("Flowers".to_string(), Some(filter), DEFAULT_SEARCH_LIMIT)
+  };
+
+  let results = search(SearchFilterPB {
+    layouts: vec![ViewLayoutFilterPB {
+      layout: ViewLayoutPB::Grid,
+    }],
+    ..Default::default()
+  })
+  .await
+  .unwrap();
+  assert_eq!(results.len(), 1);
+  assert_eq!(results[0].view_id, grid.id);
+
+  let results = search(SearchFilterPB {
+    ancestor_view_id: Some(garden.id.clone()),
+    ..Default::default()
+  })
+  .await
+  .unwrap();
+  assert_eq!(results.len(), 1);
+  assert_eq!(results[0].view_id, document.id);
+
+  let results = search(SearchFilterPB {
+    created_at: Some(SearchDateRangePB {
+      start: Some(grid.create_time + 1),
+      end: None,
+    }),
+    ..Default::default()
+  })
+  .await
+  .unwrap();
+  assert!(results.is_empty());
+
+  let results = search(SearchFilterPB {
+    created_by: Some(test.get_user_profile().await.unwrap().id),
+    ..Default::default()
+  })
+  .await
+  .unwrap();
+  assert_eq!(results.len(), 2);
+}
+
+#[tokio::test]
+async fn test_folder_index_move_view_with_children() {
+  let test = EventIntegrationTest::new_anon().await;
+  let folder_search_manager = test.get_folder_search_handler();
+
+  // Wait for the index to be created/updated
+  sleep(Duration::from_secs(1)).await;
+
+  let workspace_id = test.get_current_workspace().await.id;
+  let garden = test.create_view(&workspace_id, "Garden".to_owned()).await;
+  let beds = test.create_view(&workspace_id, "Beds".to_owned()).await;
+  let document = test.create_view(&beds.id, "Tulips".to_owned()).await;
+  sleep(Duration::from_millis(500)).await;
+
+  let search = |ancestor_view_id: String| {
+    folder_search_manager.perform_search(
+      "Tulips".to_string(),
+      Some(SearchFilterPB {
+        ancestor_view_id: Some(ancestor_view_id),
+        ..Default::default()
+      }),
+      DEFAULT_SEARCH_LIMIT,
+    )
+  };
+  let results = search(garden.id.clone()).await.unwrap();
+  assert!(results.is_empty());
+
+  // The child of the moved view is indexed with its new ancestors
+  EventBuilder::new(test.clone())
+    .event(FolderEvent::MoveNestedView)
+    .payload(MoveNestedViewPayloadPB {
+      view_id: beds.id.clone(),
+      new_parent_id: garden.id.clone(),
+      prev_view_id: None,
+      from_section: None,
+      to_section: None,
+    })
+    .async_send()
+    .await;
+  sleep(Duration::from_millis(500)).await;
+
+  let results = search(garden.id.clone()).await.unwrap();
+  assert_eq!(results.len(), 1);
+  assert_eq!(results[0].view_id, document.id);
+}
+
+#[tokio::test]
+async fn test_search_manager_ranking_and_pagination() {
+  let test = EventIntegrationTest::new_anon().await;
+
+  // Wait for the index to be created/updated
+  sleep(Duration::from_secs(1)).await;
+
+  let workspace_id = test.get_current_workspace().await.id;
+  let mut views = vec![];
+  for i in 0..3 {
+    views.push(
+      test
+        .create_view(&workspace_id, format!("Flowers {}", i))
+        .await,
+    );
+  }
+  test
+    .appflowy_core
+    .folder_manager
+    .toggle_favorites(&views[1].id)
+    .await
+    .unwrap();
+
+  // Wait for the index to be updated
+  sleep(Duration::from_millis(500)).await;
+
+  let search_manager = &test.appflowy_core.search_manager;
+  let first_page = search_manager
+    .search(
+      "Flowers",
+      None,
+      SearchPage {
+        offset: 0,
+        limit: 2,
+      },
+    )
+    .await;
+  assert_eq!(first_page.len(), 2);
+  // The titles are equally relevant, so the favorite view is ranked first
+  assert_eq!(first_page[0].view_id, views[1].id);
+
+  let second_page = search_manager
+    .search(
+      "Flowers",
+      None,
+      SearchPage {
+        offset: 2,
+        limit: 2,
+      },
+    )
+    .await;
+  assert_eq!(second_page.len(), 1);
+  assert!(first_page
+    .iter()
+    .all(|result| result.view_id != second_page[0].view_id));
+}
+
+#[tokio::test]
+async fn test_search_index_status_and_rebuild() {
+  let test = EventIntegrationTest::new_anon().await;
+
+  // Wait for the index to be created/updated
+  sleep(Duration::from_secs(1)).await;
+
+  let workspace_id = test.get_current_workspace().await.id;
+  test.create_view(&workspace_id, "Flowers".to_owned()).await;
+  sleep(Duration::from_millis(500)).await;
+
+  let status = EventBuilder::new(test.clone())
+    .event(SearchEvent::GetIndexStatus)
+    .async_send()
+    .await
+    .parse::<RepeatedSearchIndexStatusPB>();
+  assert_eq!(status.items.len(), 3);
+  let folder_status = &status.items[0];
+  assert_eq!(folder_status.index_type, SearchIndexTypePB::Folder);
+  assert!(folder_status.is_available);
+  assert!(folder_status.error.is_none());
+  assert!(folder_status.last_indexed_at.is_some());
+  // Workspace + Get started + Flowers
+  assert_eq!(folder_status.doc_count, 3);
+
+  let error = EventBuilder::new(test.clone())
+    .event(SearchEvent::RebuildIndex)
+    .async_send()
+    .await
+    .error();
+  assert!(error.is_none());
+
+  // Wait for the views to be indexed again
+  sleep(Duration::from_secs(2)).await;
+
+  let status = EventBuilder::new(test.clone())
+    .event(SearchEvent::GetIndexStatus)
+    .async_send()
+    .await
+    .parse::<RepeatedSearchIndexStatusPB>();
+  assert_eq!(status.items[0].doc_count, 3);
+
+  let results = test
+    .get_folder_search_handler()
+    .perform_search("Flowers".to_string(), None, DEFAULT_SEARCH_LIMIT)
+    .await
+    .unwrap();
+  assert_eq!(results.len(), 1);
+}
+
 /// Using this method to create a folder test asset. Only use when you want to create a new asset.
 /// The file will be created at tests/asset/{file_name}.zip and it will be committed to the repo.
 ///
@@ -1 +1,4 @@
+mod database_search_test;
+mod document_search_test;
 mod folder_search_test;
+mod search_history_test;
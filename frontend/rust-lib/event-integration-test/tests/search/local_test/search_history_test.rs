@@ -0,0 +1,158 @@
+use event_integration_test::event_builder::EventBuilder;
+use event_integration_test::EventIntegrationTest;
+use flowy_search::entities::{
+  PinSearchHistoryPayloadPB, RepeatedSearchHistoryIdPB, RepeatedSearchHistoryItemPB,
+  RunSavedSearchPayloadPB, SaveSearchPayloadPB, SearchHistoryItemPB, SearchQueryPB,
+  SearchResultNotificationPB,
+};
+use flowy_search::event_map::SearchEvent;
+use std::time::Duration;
+use tokio::time::{sleep, timeout};
+
+async fn search(test: &EventIntegrationTest, query: &str) {
+  EventBuilder::new(test.clone())
+    .event(SearchEvent::Search)
+    .payload(SearchQueryPB {
+      search: query.to_string(),
+      ..Default::default()
+    })
+    .async_send()
+    .await;
+}
+
+async fn get_search_history(test: &EventIntegrationTest) -> Vec<SearchHistoryItemPB> {
+  EventBuilder::new(test.clone())
+    .event(SearchEvent::GetSearchHistory)
+    .async_send()
+    .await
+    .parse::<RepeatedSearchHistoryItemPB>()
+    .items
+}
+
+#[tokio::test]
+async fn test_record_recent_searches() {
+  let test = EventIntegrationTest::new_anon().await;
+
+  // The prefixes of a query that is typed are replaced by the query
+  search(&test, "Flo").await;
+  search(&test, "Flow").await;
+  search(&test, "Flowers").await;
+  search(&test, "Trees").await;
+  // Searching a query again only moves it to the top
+  search(&test, "Flowers").await;
+
+  let history = get_search_history(&test).await;
+  let queries = history
+    .iter()
+    .map(|item| item.query.as_str())
+    .collect::<Vec<_>>();
+  assert_eq!(queries.len(), 2);
+  assert!(queries.contains(&"Flowers"));
+  assert!(queries.contains(&"Trees"));
+  assert!(history.iter().all(|item| item.name.is_none()));
+}
+
+#[tokio::test]
+async fn test_save_pin_and_delete_search() {
+  let test = EventIntegrationTest::new_anon().await;
+  search(&test, "Trees").await;
+
+  let saved = EventBuilder::new(test.clone())
+    .event(SearchEvent::SaveSearch)
+    .payload(SaveSearchPayloadPB {
+      name: "My flowers".to_string(),
+      query: "Flowers".to_string(),
+      filter: None,
+    })
+    .async_send()
+    .await
+    .parse::<SearchHistoryItemPB>();
+  assert_eq!(saved.name, Some("My flowers".to_string()));
+
+  let error = EventBuilder::new(test.clone())
+    .event(SearchEvent::PinSearchHistory)
+    .payload(PinSearchHistoryPayloadPB {
+      id: saved.id.clone(),
+      is_pinned: true,
+    })
+    .async_send()
+    .await
+    .error();
+  assert!(error.is_none());
+
+  // The pinned search is listed first
+  sleep(Duration::from_secs(1)).await;
+  search(&test, "Grass").await;
+  let history = get_search_history(&test).await;
+  assert_eq!(history.len(), 3);
+  assert_eq!(history[0].id, saved.id);
+  assert!(history[0].is_pinned);
+
+  EventBuilder::new(test.clone())
+    .event(SearchEvent::DeleteSearchHistory)
+    .payload(RepeatedSearchHistoryIdPB {
+      items: vec![saved.id.clone()],
+    })
+    .async_send()
+    .await;
+  let history = get_search_history(&test).await;
+  assert_eq!(history.len(), 2);
+  assert!(history.iter().all(|item| item.id != saved.id));
+}
+
+#[tokio::test]
+async fn test_run_live_saved_search() {
+  let test = EventIntegrationTest::new_anon().await;
+
+  // Wait for the index to be created/updated
+  sleep(Duration::from_secs(1)).await;
+  let workspace_id = test.get_current_workspace().await.id;
+  test.create_view(&workspace_id, "Flowers".to_owned()).await;
+  sleep(Duration::from_millis(500)).await;
+
+  let saved = EventBuilder::new(test.clone())
+    .event(SearchEvent::SaveSearch)
+    .payload(SaveSearchPayloadPB {
+      name: "My flowers".to_string(),
+      query: "Flowers".to_string(),
+      filter: None,
+    })
+    .async_send()
+    .await
+    .parse::<SearchHistoryItemPB>();
+
+  let channel = "live_search".to_string();
+  let expected_channel = Some(channel.clone());
+  let mut rx = test
+    .notification_sender
+    .subscribe_with_condition::<SearchResultNotificationPB, _>(
+      "SEARCH_IDENTIFIER",
+      move |notification| notification.channel == expected_channel,
+    );
+  EventBuilder::new(test.clone())
+    .event(SearchEvent::RunSavedSearch)
+    .payload(RunSavedSearchPayloadPB {
+      id: saved.id,
+      channel: Some(channel),
+      live: true,
+      ..Default::default()
+    })
+    .async_send()
+    .await;
+
+  let results = timeout(Duration::from_secs(5), rx.recv())
+    .await
+    .unwrap()
+    .unwrap();
+  assert_eq!(results.items.len(), 1);
+
+  // The results are sent again when a matching view is indexed
+  test
+    .create_view(&workspace_id, "More flowers".to_owned())
+    .await;
+  let results = timeout(Duration::from_secs(5), rx.recv())
+    .await
+    .unwrap()
+    .unwrap();
+  assert_eq!(results.items.len(), 2);
+}
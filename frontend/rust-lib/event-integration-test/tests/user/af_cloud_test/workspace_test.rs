@@ -2,8 +2,11 @@ use collab::core::collab::DataSource::DocStateV1;
 use collab::core::origin::CollabOrigin;
 use collab_entity::CollabType;
 use collab_folder::Folder;
+use event_integration_test::event_builder::EventBuilder;
 use event_integration_test::user_event::use_localhost_af_cloud;
 use event_integration_test::EventIntegrationTest;
+use flowy_folder::entities::MoveViewToWorkspacePayloadPB;
+use flowy_folder::event_map::FolderEvent;
 use std::time::Duration;
 use tokio::task::LocalSet;
 use tokio::time::sleep;
@@ -96,6 +99,69 @@ async fn af_cloud_create_workspace_test() {
   }
 }
 
+#[tokio::test]
+async fn af_cloud_move_view_to_workspace_test() {
+  use_localhost_af_cloud().await;
+  let test = EventIntegrationTest::new().await;
+  let _ = test.af_cloud_sign_up().await;
+
+  let first_workspace = test.get_current_workspace().await;
+  let document = test.create_document("A").await;
+  let _child = test.create_view(&document.id, "A1".to_string()).await;
+  let user_workspace = test.create_workspace("second workspace").await;
+
+  let error = EventBuilder::new(test.clone())
+    .event(FolderEvent::MoveViewToWorkspace)
+    .payload(MoveViewToWorkspacePayloadPB {
+      view_id: document.id.clone(),
+      workspace_id: user_workspace.workspace_id.clone(),
+      parent_view_id: None,
+      keep_original: false,
+    })
+    .async_send()
+    .await
+    .error();
+  assert!(error.is_none());
+
+  // the original view is moved to the trash
+  let views = test.get_all_workspace_views().await;
+  assert!(views.iter().all(|view| view.id != document.id));
+
+  test.open_workspace(&user_workspace.workspace_id).await;
+  let views = test.get_all_workspace_views().await;
+  let moved_view = views
+    .iter()
+    .find(|view| view.name == "A")
+    .expect("moved view not found");
+  assert_ne!(moved_view.id, document.id);
+  assert_eq!(moved_view.parent_view_id, user_workspace.workspace_id);
+  let child_views = test.get_view(&moved_view.id).await.child_views;
+  assert_eq!(child_views.len(), 1);
+  assert_eq!(child_views[0].name, "A1");
+
+  // the views are created when they are moved, so opening the workspace again doesn't copy them
+  test.open_workspace(&first_workspace.id).await;
+  test.open_workspace(&user_workspace.workspace_id).await;
+  let views = test.get_all_workspace_views().await;
+  assert_eq!(views.iter().filter(|view| view.name == "A").count(), 1);
+
+  // moving a view to the workspace that it belongs to is not allowed
+  test.open_workspace(&first_workspace.id).await;
+  let other = test.create_document("B").await;
+  let error = EventBuilder::new(test.clone())
+    .event(FolderEvent::MoveViewToWorkspace)
+    .payload(MoveViewToWorkspacePayloadPB {
+      view_id: other.id.clone(),
+      workspace_id: first_workspace.id.clone(),
+      parent_view_id: None,
+      keep_original: true,
+    })
+    .async_send()
+    .await
+    .error();
+  assert!(error.is_some());
+}
+
 #[tokio::test]
 async fn af_cloud_open_workspace_test() {
   use_localhost_af_cloud().await;
@@ -5,8 +5,8 @@ pub use client_api::entity::ai_dto::{
 };
 pub use client_api::entity::billing_dto::SubscriptionPlan;
 pub use client_api::entity::chat_dto::{
-  ChatMessage, ChatMessageMetadata, ChatMessageType, ChatRAGData, ChatSettings, ContextLoader,
-  MessageCursor, RepeatedChatMessage, UpdateChatParams,
+  ChatAuthor, ChatAuthorType, ChatMessage, ChatMessageMetadata, ChatMessageType, ChatRAGData,
+  ChatSettings, ContextLoader, MessageCursor, RepeatedChatMessage, UpdateChatParams,
 };
 pub use client_api::entity::QuestionStreamValue;
 use client_api::error::AppResponseError;
@@ -84,6 +84,14 @@ pub trait ChatCloudService: Send + Sync + 'static {
     complete_type: CompletionType,
   ) -> Result<StreamComplete, FlowyError>;
 
+  /// Completes the text with the instructions of a prompt that is defined by the user.
+  async fn stream_complete_with_prompt(
+    &self,
+    workspace_id: &str,
+    text: &str,
+    prompt: &str,
+  ) -> Result<StreamComplete, FlowyError>;
+
   async fn index_file(
     &self,
     workspace_id: &str,
@@ -1,10 +1,20 @@
+use crate::ai_usage::{AIUsageController, AIUsageGroup, AIUsageLimit, AIUsageSummary};
 use crate::chat::Chat;
+use crate::chat_export::{chat_to_document_markdown, chat_to_markdown};
+use crate::custom_prompt::CustomPromptController;
 use crate::entities::{
-  ChatInfoPB, ChatMessageListPB, ChatMessagePB, FilePB, RepeatedRelatedQuestionPB,
+  ChatInfoPB, ChatMessageBranchesPB, ChatMessageListPB, ChatMessagePB, FilePB,
+  RepeatedRelatedQuestionPB,
 };
 use crate::local_ai::local_llm_chat::LocalAIController;
+use crate::local_ai::view_rag::ViewRetriever;
 use crate::middleware::chat_service_mw::AICloudServiceMiddleware;
-use crate::persistence::{insert_chat, read_chat_metadata, ChatTable};
+use crate::openai_compatible::chat_service::OpenAICompatibleChatService;
+use crate::openai_compatible::setting::OpenAICompatibleSetting;
+use crate::persistence::{
+  insert_chat, read_chat_metadata, search_chat_messages, update_chat, ChatMessageSearchRow,
+  ChatTable, ChatTableChangeset, ChatTableMetadata,
+};
 
 use appflowy_plugin::manager::PluginManager;
 use dashmap::DashMap;
@@ -19,8 +29,12 @@ use flowy_storage_pub::storage::StorageService;
 use lib_infra::async_trait::async_trait;
 use lib_infra::util::timestamp;
 use std::path::PathBuf;
-use std::sync::{Arc, Weak};
-use tracing::{info, trace};
+use std::sync::{Arc, RwLock, Weak};
+use tracing::{error, info, trace};
+
+/// The title of an exported chat whose name is unknown.
+const DEFAULT_EXPORT_TITLE: &str = "Chat";
+const DEFAULT_SEARCH_LIMIT: i64 = 20;
 
 pub trait AIUserService: Send + Sync + 'static {
   fn user_id(&self) -> Result<i64, FlowyError>;
@@ -37,6 +51,50 @@ pub trait AIQueryService: Send + Sync + 'static {
     parent_view_id: &str,
     chat_id: &str,
   ) -> Result<Vec<String>, FlowyError>;
+
+  /// Returns the ids of the chats in the current workspace, the chats in the trash are excluded.
+  async fn query_chat_ids(&self) -> Result<Vec<String>, FlowyError>;
+}
+
+/// The text of a view that is used as the context of a chat.
+pub struct ViewContent {
+  pub name: String,
+  pub blocks: Vec<ViewContentBlock>,
+}
+
+/// A block of a document, or a row of a database.
+pub struct ViewContentBlock {
+  pub block_id: String,
+  pub text: String,
+}
+
+#[async_trait]
+pub trait AIViewContentService: Send + Sync + 'static {
+  /// Returns the text of a document or a database view, None if the view has no text content.
+  async fn query_view_content(&self, view_id: &str) -> Result<Option<ViewContent>, FlowyError>;
+}
+
+#[async_trait]
+pub trait AIDocumentService: Send + Sync + 'static {
+  async fn query_view_name(&self, view_id: &str) -> Result<String, FlowyError>;
+
+  /// Creates a document with the content of the Markdown under the parent view, returns the id of
+  /// the document.
+  async fn create_document(
+    &self,
+    parent_view_id: &str,
+    name: &str,
+    markdown: String,
+  ) -> Result<String, FlowyError>;
+}
+
+/// Stores the custom completion prompts, so they are shared by the devices of the user.
+#[async_trait]
+pub trait AIPromptSyncService: Send + Sync + 'static {
+  /// Returns the prompts that are serialized as JSON.
+  async fn get_prompts(&self) -> Result<Vec<String>, FlowyError>;
+
+  async fn set_prompt(&self, prompt_id: &str, data: String) -> Result<(), FlowyError>;
 }
 
 pub struct AIManager {
@@ -45,6 +103,11 @@ pub struct AIManager {
   pub query_service: Arc<dyn AIQueryService>,
   chats: Arc<DashMap<String, Arc<Chat>>>,
   pub local_ai_controller: Arc<LocalAIController>,
+  pub custom_prompt_controller: Arc<CustomPromptController>,
+  pub usage_controller: Arc<AIUsageController>,
+  openai_compatible_service: Arc<OpenAICompatibleChatService>,
+  document_service: RwLock<Option<Arc<dyn AIDocumentService>>>,
+  view_retriever: Arc<ViewRetriever>,
 }
 
 impl AIManager {
@@ -64,12 +127,28 @@ impl AIManager {
       chat_cloud_service.clone(),
     ));
     let query_service = Arc::new(query_service);
+    let view_retriever = Arc::new(ViewRetriever::new(
+      user_service.clone(),
+      local_ai_controller.clone(),
+    ));
+    let custom_prompt_controller = Arc::new(CustomPromptController::new(user_service.clone()));
+    let usage_controller = Arc::new(AIUsageController::new(
+      user_service.clone(),
+      store_preferences.clone(),
+    ));
+    let openai_compatible_service = Arc::new(OpenAICompatibleChatService::new(
+      user_service.clone(),
+      store_preferences,
+    ));
 
     // setup local chat service
     let cloud_service_wm = Arc::new(AICloudServiceMiddleware::new(
       user_service.clone(),
       chat_cloud_service,
       local_ai_controller.clone(),
+      openai_compatible_service.clone(),
+      view_retriever.clone(),
+      usage_controller.clone(),
       storage_service,
     ));
 
@@ -78,10 +157,44 @@ impl AIManager {
       user_service,
       chats: Arc::new(DashMap::new()),
       local_ai_controller,
+      custom_prompt_controller,
+      usage_controller,
       query_service,
+      openai_compatible_service,
+      document_service: RwLock::new(None),
+      view_retriever,
     }
   }
 
+  /// Sets the service that reads the views of the chats, the views are used as the context of the
+  /// questions when the local AI is running.
+  pub fn register_view_content_service(&self, content_service: Arc<dyn AIViewContentService>) {
+    self
+      .cloud_service_wm
+      .set_view_content_service(content_service);
+  }
+
+  /// Sets the service that creates the documents that the chats are exported to.
+  pub fn register_document_service(&self, document_service: Arc<dyn AIDocumentService>) {
+    if let Ok(mut service) = self.document_service.write() {
+      *service = Some(document_service);
+    }
+  }
+
+  /// Sets the service that syncs the custom completion prompts.
+  pub fn register_prompt_sync_service(&self, sync_service: Arc<dyn AIPromptSyncService>) {
+    self.custom_prompt_controller.set_sync_service(sync_service);
+  }
+
+  fn document_service(&self) -> FlowyResult<Arc<dyn AIDocumentService>> {
+    self
+      .document_service
+      .read()
+      .ok()
+      .and_then(|service| service.clone())
+      .ok_or_else(|| FlowyError::internal().with_context("The document service is not registered"))
+  }
+
   pub async fn initialize(&self, _workspace_id: &str) -> Result<(), FlowyError> {
     // Ignore following error
     let _ = self.local_ai_controller.refresh().await;
@@ -157,9 +270,9 @@ impl AIManager {
 
     self
       .cloud_service_wm
-      .create_chat(uid, &workspace_id, chat_id, rag_ids)
+      .create_chat(uid, &workspace_id, chat_id, rag_ids.clone())
       .await?;
-    save_chat(self.user_service.sqlite_connection(*uid)?, chat_id)?;
+    save_chat(self.user_service.sqlite_connection(*uid)?, chat_id, rag_ids)?;
 
     let chat = Arc::new(Chat::new(
       self.user_service.user_id().unwrap(),
@@ -271,6 +384,121 @@ impl AIManager {
     Ok(resp)
   }
 
+  pub async fn regenerate_answer(
+    &self,
+    chat_id: &str,
+    question_message_id: i64,
+    answer_stream_port: i64,
+  ) -> FlowyResult<()> {
+    let chat = self.get_or_create_chat_instance(chat_id).await?;
+    chat
+      .regenerate_answer(question_message_id, answer_stream_port)
+      .await
+  }
+
+  pub async fn edit_question(
+    &self,
+    chat_id: &str,
+    question_message_id: i64,
+    message: &str,
+    answer_stream_port: i64,
+    question_stream_port: i64,
+  ) -> FlowyResult<ChatMessagePB> {
+    let chat = self.get_or_create_chat_instance(chat_id).await?;
+    chat
+      .edit_question(
+        question_message_id,
+        message,
+        answer_stream_port,
+        question_stream_port,
+      )
+      .await
+  }
+
+  pub async fn get_message_branches(
+    &self,
+    chat_id: &str,
+    message_id: i64,
+  ) -> FlowyResult<ChatMessageBranchesPB> {
+    let chat = self.get_or_create_chat_instance(chat_id).await?;
+    chat.get_message_branches(message_id)
+  }
+
+  pub async fn switch_message_branch(&self, chat_id: &str, message_id: i64) -> FlowyResult<()> {
+    let chat = self.get_or_create_chat_instance(chat_id).await?;
+    chat.switch_message_branch(message_id)
+  }
+
+  pub async fn export_chat_to_markdown(
+    &self,
+    chat_id: &str,
+    include_related_questions: bool,
+  ) -> FlowyResult<String> {
+    let chat = self.get_or_create_chat_instance(chat_id).await?;
+    let messages = chat.export_messages(include_related_questions).await?;
+    let title = match self.document_service() {
+      Ok(service) => service.query_view_name(chat_id).await.unwrap_or_default(),
+      Err(_) => String::new(),
+    };
+    let title = if title.is_empty() {
+      DEFAULT_EXPORT_TITLE
+    } else {
+      title.as_str()
+    };
+    Ok(chat_to_markdown(title, &messages))
+  }
+
+  /// Creates a document with the messages of the chat under the parent view. The document is named
+  /// after the chat if no name is given.
+  pub async fn export_chat_to_document(
+    &self,
+    chat_id: &str,
+    parent_view_id: &str,
+    name: Option<String>,
+    include_related_questions: bool,
+  ) -> FlowyResult<String> {
+    let document_service = self.document_service()?;
+    let chat = self.get_or_create_chat_instance(chat_id).await?;
+    let messages = chat.export_messages(include_related_questions).await?;
+    let name = match name.filter(|name| !name.trim().is_empty()) {
+      Some(name) => name,
+      None => document_service.query_view_name(chat_id).await?,
+    };
+    info!(
+      "[Chat] export chat {} to document under {}, messages: {}",
+      chat_id,
+      parent_view_id,
+      messages.len()
+    );
+    document_service
+      .create_document(parent_view_id, &name, chat_to_document_markdown(&messages))
+      .await
+  }
+
+  /// Returns the messages of the chats in the current workspace that match the query, the best
+  /// matches first.
+  pub async fn search_chat_messages(
+    &self,
+    query: &str,
+    limit: i64,
+  ) -> FlowyResult<Vec<ChatMessageSearchRow>> {
+    let limit = if limit > 0 {
+      limit
+    } else {
+      DEFAULT_SEARCH_LIMIT
+    };
+    let chat_ids = self.query_service.query_chat_ids().await?;
+    let uid = self.user_service.user_id()?;
+    let mut conn = self.user_service.sqlite_connection(uid)?;
+    let rows = search_chat_messages(&mut conn, &chat_ids, query, limit)?;
+    trace!(
+      "[Chat] search messages: chats={}, hits={}",
+      chat_ids.len(),
+      rows.len()
+    );
+    Ok(rows)
+  }
+
   pub async fn stop_stream(&self, chat_id: &str) -> Result<(), FlowyError> {
     let chat = self.get_or_create_chat_instance(chat_id).await?;
     chat.stop_stream_message().await;
@@ -285,7 +513,63 @@ impl AIManager {
 
   pub fn local_ai_purchased(&self) {}
 
+  pub fn get_openai_compatible_setting(&self) -> FlowyResult<OpenAICompatibleSetting> {
+    let workspace_id = self.user_service.workspace_id()?;
+    Ok(self.openai_compatible_service.get_setting(&workspace_id))
+  }
+
+  /// Updates the OpenAI-compatible endpoint of the current workspace. The chats and the
+  /// completions of the workspace use the endpoint while it is enabled.
+  pub fn update_openai_compatible_setting(
+    &self,
+    setting: OpenAICompatibleSetting,
+  ) -> FlowyResult<()> {
+    let workspace_id = self.user_service.workspace_id()?;
+    info!(
+      "[AI] update OpenAI-compatible endpoint: enabled={}, base_url={}, model={}",
+      setting.enabled, setting.base_url, setting.model
+    );
+    self
+      .openai_compatible_service
+      .update_setting(&workspace_id, &setting)
+  }
+
+  /// Returns the AI usage of the current workspace that was recorded in `[start, end)`.
+  pub fn get_ai_usage_summary(
+    &self,
+    group: AIUsageGroup,
+    start: i64,
+    end: i64,
+  ) -> FlowyResult<Vec<AIUsageSummary>> {
+    let workspace_id = self.user_service.workspace_id()?;
+    self
+      .usage_controller
+      .get_summary(&workspace_id, group, start, end)
+  }
+
+  pub fn get_ai_usage_limit(&self) -> FlowyResult<AIUsageLimit> {
+    let workspace_id = self.user_service.workspace_id()?;
+    Ok(self.usage_controller.get_limit(&workspace_id))
+  }
+
+  /// Updates the daily limits of the current workspace. The chats, the completions and the AI
+  /// fields of the workspace fail with `ErrorCode::AIUsageLimitExceeded` once a limit is reached.
+  pub fn update_ai_usage_limit(&self, limit: AIUsageLimit) -> FlowyResult<()> {
+    let workspace_id = self.user_service.workspace_id()?;
+    info!(
+      "[AI] update AI usage limit: daily_requests={}, daily_tokens={}",
+      limit.daily_requests, limit.daily_tokens
+    );
+    self.usage_controller.set_limit(&workspace_id, &limit)
+  }
+
   pub async fn update_rag_ids(&self, chat_id: &str, rag_ids: Vec<String>) -> FlowyResult<()> {
+    // The local AI uses the views that are saved with the chat, so the views are saved even if
+    // they were all removed
+    if let Err(err) = self.save_rag_ids(chat_id, rag_ids.clone()) {
+      error!("[Chat] failed to save rag_ids of chat {}: {}", chat_id, err);
+    }
+
     if !rag_ids.is_empty() {
       let workspace_id = self.user_service.workspace_id()?;
       let update_setting = UpdateChatParams {
@@ -300,15 +584,37 @@ impl AIManager {
     }
     Ok(())
   }
+
+  /// Removes the embeddings of the views that were deleted or moved to the trash, so their
+  /// passages are not added to the questions anymore.
+  pub fn remove_view_embeddings(&self, view_ids: &[String]) -> FlowyResult<()> {
+    self.view_retriever.remove_views(view_ids)
+  }
+
+  fn save_rag_ids(&self, chat_id: &str, rag_ids: Vec<String>) -> FlowyResult<()> {
+    let uid = self.user_service.user_id()?;
+    let mut conn = self.user_service.sqlite_connection(uid)?;
+    let mut metadata = read_chat_metadata(&mut conn, chat_id)?;
+    metadata.rag_ids = rag_ids;
+    update_chat(
+      &mut conn,
+      ChatTableChangeset::from_metadata(chat_id, metadata),
+    )?;
+    Ok(())
+  }
 }
 
-fn save_chat(conn: DBConnection, chat_id: &str) -> FlowyResult<()> {
+fn save_chat(conn: DBConnection, chat_id: &str, rag_ids: Vec<String>) -> FlowyResult<()> {
+  let metadata = ChatTableMetadata {
+    rag_ids,
+    ..Default::default()
+  };
   let row = ChatTable {
     chat_id: chat_id.to_string(),
     created_at: timestamp(),
     name: "".to_string(),
     local_files: "".to_string(),
-    metadata: "".to_string(),
+    metadata: serde_json::to_string(&metadata)?,
     local_enabled: false,
     sync_to_cloud: false,
   };
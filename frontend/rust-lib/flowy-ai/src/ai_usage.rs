@@ -0,0 +1,442 @@
+use crate::ai_manager::AIUserService;
+use crate::persistence::{insert_ai_usage, select_ai_usage, AIUsageTable};
+use chrono::{Local, TimeZone};
+use flowy_error::{FlowyError, FlowyResult};
+use flowy_sqlite::kv::KVStorePreferences;
+use futures::stream::BoxStream;
+use futures::{stream, StreamExt};
+use lib_infra::util::timestamp;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tracing::{error, trace};
+
+const AI_USAGE_LIMIT_KEY: &str = "appflowy_ai_usage_limit:v0";
+
+/// The model that is reported for the requests that are answered by AppFlowy Cloud.
+pub const APPFLOWY_CLOUD_MODEL: &str = "AppFlowy Cloud";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AIUsageType {
+  ChatMessage = 0,
+  Completion = 1,
+  AIField = 2,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AIUsageProvider {
+  AppFlowyCloud = 0,
+  LocalAI = 1,
+  OpenAICompatible = 2,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AIUsageOutcome {
+  Success = 0,
+  Failure = 1,
+  /// The response was stopped before it was finished.
+  Cancelled = 2,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AIUsageGroup {
+  Day,
+  Chat,
+  Model,
+}
+
+/// The soft limits of the AI usage of a workspace, a limit of 0 means unlimited. The limits are
+/// checked before a request is sent, so the request that crosses a limit is still answered.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AIUsageLimit {
+  pub daily_requests: i64,
+  pub daily_tokens: i64,
+}
+
+impl AIUsageLimit {
+  pub fn is_unlimited(&self) -> bool {
+    self.daily_requests == 0 && self.daily_tokens == 0
+  }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AIUsageSummary {
+  /// The local date, the chat id or the model, depending on the [AIUsageGroup].
+  pub key: String,
+  pub requests: i64,
+  pub failed_requests: i64,
+  /// Estimated from the text of the request only. The chat history and the passages of the views
+  /// that are sent with a question aren't counted, so the input of a chat is undercounted.
+  pub input_tokens: i64,
+  pub output_tokens: i64,
+  pub average_latency_ms: i64,
+}
+
+impl AIUsageSummary {
+  pub fn total_tokens(&self) -> i64 {
+    self.input_tokens + self.output_tokens
+  }
+}
+
+/// Records the AI requests of the workspaces and enforces their usage limits.
+pub struct AIUsageController {
+  user_service: Arc<dyn AIUserService>,
+  store_preferences: Arc<KVStorePreferences>,
+}
+
+impl AIUsageController {
+  pub fn new(
+    user_service: Arc<dyn AIUserService>,
+    store_preferences: Arc<KVStorePreferences>,
+  ) -> Self {
+    Self {
+      user_service,
+      store_preferences,
+    }
+  }
+
+  pub fn get_limit(&self, workspace_id: &str) -> AIUsageLimit {
+    self
+      .store_preferences
+      .get_object::<AIUsageLimit>(&limit_key(workspace_id))
+      .unwrap_or_default()
+  }
+
+  pub fn set_limit(&self, workspace_id: &str, limit: &AIUsageLimit) -> FlowyResult<()> {
+    if limit.daily_requests < 0 || limit.daily_tokens < 0 {
+      return Err(FlowyError::invalid_data().with_context("The usage limit must not be negative"));
+    }
+    self
+      .store_preferences
+      .set_object(&limit_key(workspace_id), limit)
+      .map_err(|err| FlowyError::internal().with_context(err))?;
+    Ok(())
+  }
+
+  /// Returns [FlowyError::ai_usage_limit_exceeded] if the workspace reached one of its daily
+  /// limits.
+  pub fn check_limit(&self, workspace_id: &str) -> FlowyResult<()> {
+    let limit = self.get_limit(workspace_id);
+    if limit.is_unlimited() {
+      return Ok(());
+    }
+
+    let usage = self.get_today_usage(workspace_id)?;
+    if limit.daily_requests > 0 && usage.requests >= limit.daily_requests {
+      return Err(FlowyError::ai_usage_limit_exceeded().with_context(format!(
+        "The workspace reached its limit of {} AI requests per day",
+        limit.daily_requests
+      )));
+    }
+    if limit.daily_tokens > 0 && usage.total_tokens() >= limit.daily_tokens {
+      return Err(FlowyError::ai_usage_limit_exceeded().with_context(format!(
+        "The workspace reached its limit of {} AI tokens per day",
+        limit.daily_tokens
+      )));
+    }
+    Ok(())
+  }
+
+  /// Starts recording a request, the usage is saved when the returned recorder is dropped.
+  pub fn start(
+    &self,
+    workspace_id: &str,
+    usage_type: AIUsageType,
+    provider: AIUsageProvider,
+    model: String,
+    chat_id: &str,
+    input: &str,
+  ) -> AIUsageRecorder {
+    AIUsageRecorder {
+      user_service: self.user_service.clone(),
+      usage: AIUsageTable {
+        id: uuid::Uuid::new_v4().to_string(),
+        workspace_id: workspace_id.to_string(),
+        chat_id: chat_id.to_string(),
+        usage_type: usage_type as i32,
+        provider: provider as i32,
+        model,
+        input_tokens: estimate_tokens(input),
+        output_tokens: 0,
+        is_estimated: true,
+        latency_ms: 0,
+        outcome: AIUsageOutcome::Cancelled as i32,
+        created_at: timestamp(),
+      },
+      output: String::new(),
+      outcome: None,
+      started_at: Instant::now(),
+    }
+  }
+
+  /// Returns the usage of the workspace that was recorded in `[start, end)`.
+  pub fn get_summary(
+    &self,
+    workspace_id: &str,
+    group: AIUsageGroup,
+    start: i64,
+    end: i64,
+  ) -> FlowyResult<Vec<AIUsageSummary>> {
+    let records = self.select_usage(workspace_id, start, end)?;
+    Ok(summarize_usage(&records, group))
+  }
+
+  pub fn get_today_usage(&self, workspace_id: &str) -> FlowyResult<AIUsageSummary> {
+    let records = self.select_usage(workspace_id, start_of_today(), i64::MAX)?;
+    let usage = summarize_usage(&records, AIUsageGroup::Day)
+      .into_iter()
+      .fold(AIUsageSummary::default(), |mut total, summary| {
+        total.requests += summary.requests;
+        total.failed_requests += summary.failed_requests;
+        total.input_tokens += summary.input_tokens;
+        total.output_tokens += summary.output_tokens;
+        total
+      });
+    Ok(usage)
+  }
+
+  fn select_usage(
+    &self,
+    workspace_id: &str,
+    start: i64,
+    end: i64,
+  ) -> FlowyResult<Vec<AIUsageTable>> {
+    let uid = self.user_service.user_id()?;
+    let mut conn = self.user_service.sqlite_connection(uid)?;
+    let records = select_ai_usage(&mut conn, workspace_id, start, end)?;
+    Ok(records)
+  }
+}
+
+/// Collects the response of a request. The usage is saved when the recorder is dropped, a request
+/// that isn't finished by then is saved as [AIUsageOutcome::Cancelled].
+pub struct AIUsageRecorder {
+  user_service: Arc<dyn AIUserService>,
+  usage: AIUsageTable,
+  output: String,
+  outcome: Option<AIUsageOutcome>,
+  started_at: Instant,
+}
+
+impl AIUsageRecorder {
+  pub fn append_output(&mut self, text: &str) {
+    self.output.push_str(text);
+  }
+
+  /// Finishes the request with the result of a call that isn't streamed.
+  pub fn finish<T>(mut self, result: &FlowyResult<T>, output: impl FnOnce(&T) -> String) {
+    match result {
+      Ok(value) => {
+        self.output = output(value);
+        self.outcome = Some(AIUsageOutcome::Success);
+      },
+      Err(_) => self.outcome = Some(AIUsageOutcome::Failure),
+    }
+  }
+
+  fn set_outcome(&mut self, outcome: AIUsageOutcome) {
+    // A stream that failed doesn't succeed when it ends
+    if self.outcome.is_none() {
+      self.outcome = Some(outcome);
+    }
+  }
+
+  fn save(&mut self) -> FlowyResult<()> {
+    self.usage.outcome = self.outcome.unwrap_or(AIUsageOutcome::Cancelled) as i32;
+    self.usage.output_tokens = estimate_tokens(&self.output);
+    self.usage.latency_ms = self.started_at.elapsed().as_millis() as i64;
+    trace!(
+      "[AI Usage] type={}, model={}, input_tokens={}, output_tokens={}, outcome={}",
+      self.usage.usage_type,
+      self.usage.model,
+      self.usage.input_tokens,
+      self.usage.output_tokens,
+      self.usage.outcome
+    );
+
+    let uid = self.user_service.user_id()?;
+    let mut conn = self.user_service.sqlite_connection(uid)?;
+    insert_ai_usage(&mut conn, &self.usage)
+  }
+}
+
+impl Drop for AIUsageRecorder {
+  fn drop(&mut self) {
+    if let Err(err) = self.save() {
+      error!("[AI Usage] failed to record usage: {}", err);
+    }
+  }
+}
+
+/// Records the values of the stream as the response of the request. The request succeeds if the
+/// stream ends without an error.
+pub fn record_stream<T: Send + 'static>(
+  mut recorder: AIUsageRecorder,
+  result: FlowyResult<BoxStream<'static, FlowyResult<T>>>,
+  output: fn(&T) -> Option<String>,
+) -> FlowyResult<BoxStream<'static, FlowyResult<T>>> {
+  let values = match result {
+    Ok(values) => values,
+    Err(err) => {
+      recorder.set_outcome(AIUsageOutcome::Failure);
+      return Err(err);
+    },
+  };
+
+  let recorder = Arc::new(Mutex::new(recorder));
+  let value_recorder = recorder.clone();
+  let values = values.map(move |value| {
+    if let Ok(mut recorder) = value_recorder.lock() {
+      match &value {
+        Ok(value) => {
+          if let Some(text) = output(value) {
+            recorder.append_output(&text);
+          }
+        },
+        Err(_) => recorder.set_outcome(AIUsageOutcome::Failure),
+      }
+    }
+    value
+  });
+  let end = stream::once(async move {
+    if let Ok(mut recorder) = recorder.lock() {
+      recorder.set_outcome(AIUsageOutcome::Success);
+    }
+  })
+  .filter_map(|_| async { None });
+  Ok(values.chain(end).boxed())
+}
+
+/// Estimates the number of tokens of the text: about four characters per token, and one token
+/// per character for the CJK scripts.
+pub fn estimate_tokens(text: &str) -> i64 {
+  let (wide, narrow) = text.chars().fold((0, 0), |(wide, narrow), c| {
+    if is_wide_char(c) {
+      (wide + 1, narrow)
+    } else {
+      (wide, narrow + 1)
+    }
+  });
+  wide + (narrow + 3) / 4
+}
+
+fn is_wide_char(c: char) -> bool {
+  matches!(c as u32, 0x2E80..=0x9FFF | 0xAC00..=0xD7AF | 0xF900..=0xFAFF | 0xFF00..=0xFFEF)
+}
+
+fn summarize_usage(records: &[AIUsageTable], group: AIUsageGroup) -> Vec<AIUsageSummary> {
+  let mut summaries = BTreeMap::<String, (AIUsageSummary, i64)>::new();
+  for record in records {
+    let key = match group {
+      AIUsageGroup::Day => local_date(record.created_at),
+      AIUsageGroup::Chat => record.chat_id.clone(),
+      AIUsageGroup::Model => record.model.clone(),
+    };
+    let (summary, total_latency_ms) = summaries.entry(key.clone()).or_insert_with(|| {
+      (
+        AIUsageSummary {
+          key,
+          ..Default::default()
+        },
+        0,
+      )
+    });
+    summary.requests += 1;
+    if record.outcome == AIUsageOutcome::Failure as i32 {
+      summary.failed_requests += 1;
+    }
+    summary.input_tokens += record.input_tokens;
+    summary.output_tokens += record.output_tokens;
+    *total_latency_ms += record.latency_ms;
+  }
+
+  summaries
+    .into_values()
+    .map(|(mut summary, total_latency_ms)| {
+      summary.average_latency_ms = total_latency_ms / summary.requests;
+      summary
+    })
+    .collect()
+}
+
+fn local_date(timestamp: i64) -> String {
+  Local
+    .timestamp_opt(timestamp, 0)
+    .earliest()
+    .map(|time| time.format("%Y-%m-%d").to_string())
+    .unwrap_or_default()
+}
+
+fn start_of_today() -> i64 {
+  Local::now()
+    .date_naive()
+    .and_hms_opt(0, 0, 0)
+    .and_then(|midnight| Local.from_local_datetime(&midnight).earliest())
+    .map(|midnight| midnight.timestamp())
+    // The day doesn't start at midnight if the clocks are changed at midnight
+    .unwrap_or_else(|| timestamp() - 24 * 60 * 60)
+}
+
+fn limit_key(workspace_id: &str) -> String {
+  format!("{}:{}", AI_USAGE_LIMIT_KEY, workspace_id)
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn usage(chat_id: &str, model: &str, created_at: i64, outcome: AIUsageOutcome) -> AIUsageTable {
+    AIUsageTable {
+      id: uuid::Uuid::new_v4().to_string(),
+      workspace_id: "w1".to_string(),
+      chat_id: chat_id.to_string(),
+      usage_type: AIUsageType::ChatMessage as i32,
+      provider: AIUsageProvider::LocalAI as i32,
+      model: model.to_string(),
+      input_tokens: 10,
+      output_tokens: 20,
+      is_estimated: true,
+      latency_ms: 100 * (outcome as i64 + 1),
+      outcome: outcome as i32,
+      created_at,
+    }
+  }
+
+  #[test]
+  fn estimate_tokens_test() {
+    assert_eq!(estimate_tokens(""), 0);
+    assert_eq!(estimate_tokens("hello"), 2);
+    assert_eq!(estimate_tokens("hello world!"), 3);
+    assert_eq!(estimate_tokens("你好"), 2);
+  }
+
+  #[test]
+  fn summarize_usage_test() {
+    let day = 24 * 60 * 60;
+    let now = timestamp();
+    let records = vec![
+      usage("c1", "llama3.2", now - 2 * day, AIUsageOutcome::Success),
+      usage("c1", "llama3.2", now, AIUsageOutcome::Failure),
+      usage("", "gpt-4o", now, AIUsageOutcome::Success),
+    ];
+
+    let by_chat = summarize_usage(&records, AIUsageGroup::Chat);
+    assert_eq!(by_chat.len(), 2);
+    assert_eq!(by_chat[0].key, "");
+    assert_eq!(by_chat[1].key, "c1");
+    assert_eq!(by_chat[1].requests, 2);
+    assert_eq!(by_chat[1].failed_requests, 1);
+    assert_eq!(by_chat[1].total_tokens(), 60);
+    assert_eq!(by_chat[1].average_latency_ms, 150);
+
+    let by_model = summarize_usage(&records, AIUsageGroup::Model);
+    assert_eq!(by_model[0].key, "gpt-4o");
+    assert_eq!(by_model[1].key, "llama3.2");
+
+    let by_day = summarize_usage(&records, AIUsageGroup::Day);
+    assert_eq!(by_day.len(), 2);
+    assert_eq!(by_day[1].key, local_date(now));
+    assert_eq!(by_day[1].requests, 2);
+  }
+}
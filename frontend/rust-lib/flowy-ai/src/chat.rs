@@ -1,15 +1,20 @@
 use crate::ai_manager::AIUserService;
+use crate::chat_export::ChatExportMessage;
 use crate::entities::{
-  ChatMessageErrorPB, ChatMessageListPB, ChatMessagePB, RepeatedRelatedQuestionPB,
+  ChatMessageBranchesPB, ChatMessageErrorPB, ChatMessageListPB, ChatMessagePB,
+  RepeatedRelatedQuestionPB,
 };
 use crate::middleware::chat_service_mw::AICloudServiceMiddleware;
 use crate::notification::{chat_notification_builder, ChatNotification};
-use crate::persistence::{insert_chat_messages, select_chat_messages, ChatMessageTable};
+use crate::persistence::{
+  insert_chat_messages, link_unparented_questions, select_chat_messages, select_message_branches,
+  select_single_message, set_active_message_branch, ChatMessageTable, CHAT_ROOT_MESSAGE_ID,
+};
 use crate::stream_message::StreamMessage;
 use allo_isolate::Isolate;
 use flowy_ai_pub::cloud::{
-  ChatCloudService, ChatMessage, ChatMessageMetadata, ChatMessageType, MessageCursor,
-  QuestionStreamValue,
+  ChatAuthorType, ChatCloudService, ChatMessage, ChatMessageMetadata, ChatMessageType,
+  MessageCursor, QuestionStreamValue,
 };
 use flowy_error::{FlowyError, FlowyResult};
 use flowy_sqlite::DBConnection;
@@ -21,6 +26,9 @@ use std::sync::Arc;
 use tokio::sync::{Mutex, RwLock};
 use tracing::{error, instrument, trace};
 
+/// The number of messages that are loaded from the cloud at a time when a chat is exported.
+const EXPORT_PAGE_SIZE: u64 = 100;
+
 enum PrevMessageState {
   HasMore,
   NoMore,
@@ -94,24 +102,170 @@ impl Chat {
       metadata
     );
 
-    // clear
+    let workspace_id = self.user_service.workspace_id()?;
+    // The limit is checked before the question is created, otherwise a question without an answer
+    // is left in the chat
+    self.chat_service.check_usage_limit(&workspace_id)?;
+    self.reset_stream_buffer().await;
+    let parent_message_id = self.last_message_id()?;
+    let question = self
+      .send_question(
+        &workspace_id,
+        message,
+        message_type,
+        metadata,
+        question_stream_port,
+        parent_message_id,
+      )
+      .await?;
+    self.spawn_stream_answer(workspace_id, question.message_id, answer_stream_port, false)?;
+
+    let question_pb = ChatMessagePB::from(question);
+    Ok(question_pb)
+  }
+
+  /// Streams a new answer to the question. The previous answers are kept as the branches of the
+  /// question.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn regenerate_answer(
+    &self,
+    question_message_id: i64,
+    answer_stream_port: i64,
+  ) -> FlowyResult<()> {
+    trace!(
+      "[Chat] regenerate answer: chat_id={}, question_message_id={}",
+      self.chat_id,
+      question_message_id
+    );
+    let question = self.get_message_record(question_message_id)?;
+    if question.author_type == ChatAuthorType::AI as i64 {
+      return Err(FlowyError::invalid_data().with_context("Only a question can be answered"));
+    }
+
+    self.reset_stream_buffer().await;
+    let workspace_id = self.user_service.workspace_id()?;
+    self.spawn_stream_answer(workspace_id, question_message_id, answer_stream_port, true)?;
+    Ok(())
+  }
+
+  /// Asks the edited question instead of the question, and streams its answer. The question and
+  /// the messages after it are kept as a branch.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn edit_question(
+    &self,
+    question_message_id: i64,
+    message: &str,
+    answer_stream_port: i64,
+    question_stream_port: i64,
+  ) -> FlowyResult<ChatMessagePB> {
+    trace!(
+      "[Chat] edit question: chat_id={}, question_message_id={}, message={}",
+      self.chat_id,
+      question_message_id,
+      message
+    );
+    link_unparented_questions(
+      &mut self.user_service.sqlite_connection(self.uid)?,
+      &self.chat_id,
+    )?;
+    let question = self.get_message_record(question_message_id)?;
+    if question.author_type == ChatAuthorType::AI as i64 {
+      return Err(FlowyError::invalid_data().with_context("Only a question can be edited"));
+    }
+    let parent_message_id = question.parent_message_id.unwrap_or(CHAT_ROOT_MESSAGE_ID);
+
+    let workspace_id = self.user_service.workspace_id()?;
+    self.chat_service.check_usage_limit(&workspace_id)?;
+    self.reset_stream_buffer().await;
+    let question = self
+      .send_question(
+        &workspace_id,
+        message,
+        ChatMessageType::User,
+        vec![],
+        question_stream_port,
+        parent_message_id,
+      )
+      .await?;
+    set_active_message_branch(
+      &mut self.user_service.sqlite_connection(self.uid)?,
+      &self.chat_id,
+      question.message_id,
+    )?;
+    self.spawn_stream_answer(workspace_id, question.message_id, answer_stream_port, false)?;
+    Ok(ChatMessagePB::from(question))
+  }
+
+  /// Returns the answers of the same question, or the edited versions of the same question.
+  pub fn get_message_branches(&self, message_id: i64) -> FlowyResult<ChatMessageBranchesPB> {
+    let conn = self.user_service.sqlite_connection(self.uid)?;
+    let branches = select_message_branches(conn, &self.chat_id, message_id)?.ok_or_else(|| {
+      FlowyError::record_not_found().with_context(format!("Message not found: {}", message_id))
+    })?;
+    Ok(ChatMessageBranchesPB {
+      message_id,
+      branches: branches
+        .messages
+        .into_iter()
+        .map(ChatMessagePB::from)
+        .collect(),
+      active_message_id: branches.active_message_id,
+    })
+  }
+
+  /// Shows the message and the messages after it instead of its siblings.
+  pub fn switch_message_branch(&self, message_id: i64) -> FlowyResult<()> {
+    let mut conn = self.user_service.sqlite_connection(self.uid)?;
+    set_active_message_branch(&mut conn, &self.chat_id, message_id)?;
+    Ok(())
+  }
+
+  async fn reset_stream_buffer(&self) {
     self
       .stop_stream
       .store(false, std::sync::atomic::Ordering::SeqCst);
     self.stream_buffer.lock().await.clear();
+  }
 
+  /// The id of the last message that is shown, which is the parent of the next question.
+  fn last_message_id(&self) -> FlowyResult<i64> {
+    let conn = self.user_service.sqlite_connection(self.uid)?;
+    let last_message = select_chat_messages(conn, &self.chat_id, 1, None, None)?;
+    Ok(
+      last_message
+        .first()
+        .map(|message| message.message_id)
+        .unwrap_or(CHAT_ROOT_MESSAGE_ID),
+    )
+  }
+
+  fn get_message_record(&self, message_id: i64) -> FlowyResult<ChatMessageTable> {
+    let conn = self.user_service.sqlite_connection(self.uid)?;
+    select_single_message(conn, message_id)?
+      .filter(|message| message.chat_id == self.chat_id)
+      .ok_or_else(|| {
+        FlowyError::record_not_found().with_context(format!("Message not found: {}", message_id))
+      })
+  }
+
+  async fn send_question(
+    &self,
+    workspace_id: &str,
+    message: &str,
+    message_type: ChatMessageType,
+    metadata: Vec<ChatMessageMetadata>,
+    question_stream_port: i64,
+    parent_message_id: i64,
+  ) -> FlowyResult<ChatMessage> {
     let mut question_sink = IsolateSink::new(Isolate::new(question_stream_port));
-    let answer_stream_buffer = self.stream_buffer.clone();
     let uid = self.user_service.user_id()?;
-    let workspace_id = self.user_service.workspace_id()?;
-
     let _ = question_sink
       .send(StreamMessage::Text(message.to_string()).to_string())
       .await;
     let question = self
       .chat_service
       .create_question(
-        &workspace_id,
+        workspace_id,
         &self.chat_id,
         message,
         message_type,
@@ -136,11 +290,27 @@ impl Chat {
     let _ = question_sink.send(StreamMessage::Done.to_string()).await;
 
     // Save message to disk
-    save_and_notify_message(uid, &self.chat_id, &self.user_service, question.clone())?;
+    save_and_notify_message(
+      uid,
+      &self.chat_id,
+      &self.user_service,
+      question.clone(),
+      Some(parent_message_id),
+    )?;
+    Ok(question)
+  }
 
+  fn spawn_stream_answer(
+    &self,
+    workspace_id: String,
+    question_id: i64,
+    answer_stream_port: i64,
+    is_regenerated: bool,
+  ) -> FlowyResult<()> {
+    let uid = self.user_service.user_id()?;
+    let answer_stream_buffer = self.stream_buffer.clone();
     let stop_stream = self.stop_stream.clone();
     let chat_id = self.chat_id.clone();
-    let question_id = question.message_id;
     let cloud_service = self.chat_service.clone();
     let user_service = self.user_service.clone();
     tokio::spawn(async move {
@@ -214,12 +384,20 @@ impl Chat {
       let answer = cloud_service
         .create_answer(&workspace_id, &chat_id, &content, question_id, metadata)
         .await?;
-      save_and_notify_message(uid, &chat_id, &user_service, answer)?;
+      let answer_id = answer.message_id;
+      save_and_notify_message(uid, &chat_id, &user_service, answer, Some(question_id))?;
+      if is_regenerated {
+        // Show the new answer instead of the previous ones
+        set_active_message_branch(
+          &mut user_service.sqlite_connection(uid)?,
+          &chat_id,
+          answer_id,
+        )?;
+      }
       Ok::<(), FlowyError>(())
     });
 
-    let question_pb = ChatMessagePB::from(question);
-    Ok(question_pb)
+    Ok(())
   }
 
   /// Load chat messages for a given `chat_id`.
@@ -351,6 +529,7 @@ impl Chat {
             user_service.sqlite_connection(uid)?,
             &chat_id,
             resp.messages.clone(),
+            None,
           ) {
             error!("Failed to save chat:{} messages: {}", chat_id, err);
           }
@@ -425,11 +604,87 @@ impl Chat {
       .get_answer(&workspace_id, &self.chat_id, question_message_id)
       .await?;
 
-    save_and_notify_message(self.uid, &self.chat_id, &self.user_service, answer.clone())?;
+    save_and_notify_message(
+      self.uid,
+      &self.chat_id,
+      &self.user_service,
+      answer.clone(),
+      Some(question_message_id),
+    )?;
     let pb = ChatMessagePB::from(answer);
     Ok(pb)
   }
 
+  /// Returns the messages that are shown, from the first one to the latest one. The messages
+  /// that are only in the cloud are saved to disk first, so the whole conversation is exported.
+  #[instrument(level = "debug", skip_all, err)]
+  pub async fn export_messages(
+    &self,
+    include_related_questions: bool,
+  ) -> FlowyResult<Vec<ChatExportMessage>> {
+    if let Err(err) = self.save_remote_history().await {
+      // The messages on disk are exported when the cloud is not reachable
+      error!(
+        "[Chat] failed to load the history of chat {}: {}",
+        self.chat_id, err
+      );
+    }
+
+    let conn = self.user_service.sqlite_connection(self.uid)?;
+    let records = select_chat_messages(conn, &self.chat_id, i64::MAX, None, None)?;
+    let mut messages = records
+      .into_iter()
+      .rev()
+      .map(ChatExportMessage::from)
+      .collect::<Vec<_>>();
+
+    // The related questions are suggested for the latest answer, the local AI only suggests them
+    // for the whole chat, so they are fetched once and added to the last answer
+    let last_answer = messages
+      .iter_mut()
+      .rev()
+      .find(|message| message.author_type == ChatAuthorType::AI as i64)
+      .filter(|_| include_related_questions);
+    if let Some(message) = last_answer {
+      match self.get_related_question(message.message_id).await {
+        Ok(resp) => {
+          message.related_questions = resp.items.into_iter().map(|item| item.content).collect()
+        },
+        Err(err) => error!(
+          "[Chat] failed to get related questions of message {}: {}",
+          message.message_id, err
+        ),
+      }
+    }
+    Ok(messages)
+  }
+
+  async fn save_remote_history(&self) -> FlowyResult<()> {
+    let workspace_id = self.user_service.workspace_id()?;
+    let mut cursor = MessageCursor::NextBack;
+    loop {
+      let resp = self
+        .chat_service
+        .get_chat_messages(&workspace_id, &self.chat_id, cursor, EXPORT_PAGE_SIZE)
+        .await?;
+      let has_more = resp.has_more;
+      let Some(first_message_id) = resp.messages.iter().map(|message| message.message_id).min()
+      else {
+        return Ok(());
+      };
+      save_chat_message_disk(
+        self.user_service.sqlite_connection(self.uid)?,
+        &self.chat_id,
+        resp.messages,
+        None,
+      )?;
+      if !has_more {
+        return Ok(());
+      }
+      cursor = MessageCursor::BeforeMessageId(first_message_id);
+    }
+  }
+
   async fn load_local_chat_messages(
     &self,
     limit: i64,
@@ -446,15 +701,7 @@ impl Chat {
     )?;
     let messages = records
       .into_iter()
-      .map(|record| ChatMessagePB {
-        message_id: record.message_id,
-        content: record.content,
-        created_at: record.created_at,
-        author_type: record.author_type,
-        author_id: record.author_id,
-        reply_message_id: record.reply_message_id,
-        metadata: record.metadata,
-      })
+      .map(ChatMessagePB::from)
       .collect::<Vec<_>>();
 
     Ok(messages)
@@ -499,10 +746,12 @@ impl Chat {
   }
 }
 
+/// The `parent_message_id` of the messages that are loaded from the server is kept as it is.
 fn save_chat_message_disk(
   conn: DBConnection,
   chat_id: &str,
   messages: Vec<ChatMessage>,
+  parent_message_id: Option<i64>,
 ) -> FlowyResult<()> {
   let records = messages
     .into_iter()
@@ -515,6 +764,8 @@ fn save_chat_message_disk(
       author_id: message.author.author_id.to_string(),
       reply_message_id: message.reply_message_id,
       metadata: Some(serde_json::to_string(&message.meta_data).unwrap_or_default()),
+      parent_message_id,
+      is_hidden: false,
     })
     .collect::<Vec<_>>();
   insert_chat_messages(conn, &records)?;
@@ -559,12 +810,14 @@ pub(crate) fn save_and_notify_message(
   chat_id: &str,
   user_service: &Arc<dyn AIUserService>,
   message: ChatMessage,
+  parent_message_id: Option<i64>,
 ) -> Result<(), FlowyError> {
   trace!("[Chat] save answer: answer={:?}", message);
   save_chat_message_disk(
     user_service.sqlite_connection(uid)?,
     chat_id,
     vec![message.clone()],
+    parent_message_id,
   )?;
   let pb = ChatMessagePB::from(message);
   chat_notification_builder(chat_id, ChatNotification::DidReceiveChatMessage)
@@ -0,0 +1,207 @@
+use crate::persistence::ChatMessageTable;
+use flowy_ai_pub::cloud::ChatAuthorType;
+use serde_json::Value;
+use std::collections::HashSet;
+
+/// The source that is used as the context of a message: an AppFlowy view or a file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChatExportSource {
+  pub id: String,
+  pub name: String,
+  pub source: String,
+}
+
+pub struct ChatExportMessage {
+  pub message_id: i64,
+  pub author_type: i64,
+  pub content: String,
+  pub sources: Vec<ChatExportSource>,
+  pub related_questions: Vec<String>,
+}
+
+impl ChatExportMessage {
+  fn is_answer(&self) -> bool {
+    self.author_type == ChatAuthorType::AI as i64
+  }
+}
+
+impl From<ChatMessageTable> for ChatExportMessage {
+  fn from(record: ChatMessageTable) -> Self {
+    let sources = record
+      .metadata
+      .as_deref()
+      .map(parse_message_sources)
+      .unwrap_or_default();
+    Self {
+      message_id: record.message_id,
+      author_type: record.author_type,
+      content: record.content,
+      sources,
+      related_questions: vec![],
+    }
+  }
+}
+
+const APPFLOWY_SOURCE: &str = "appflowy";
+
+/// Renders the messages as a Markdown document with the title as its heading.
+pub fn chat_to_markdown(title: &str, messages: &[ChatExportMessage]) -> String {
+  let mut sections = vec![format!("# {}", title)];
+  sections.extend(messages.iter().map(message_to_markdown));
+  let mut markdown = sections.join("\n\n");
+  markdown.push('\n');
+  markdown
+}
+
+/// Renders the messages as the Markdown of a document, the document is named after the chat. A
+/// divider is inserted before each question except the first one.
+pub fn chat_to_document_markdown(messages: &[ChatExportMessage]) -> String {
+  let mut markdown = String::new();
+  for (index, message) in messages.iter().enumerate() {
+    if index > 0 {
+      markdown.push_str(if message.is_answer() {
+        "\n\n"
+      } else {
+        "\n\n---\n\n"
+      });
+    }
+    markdown.push_str(&message_to_markdown(message));
+  }
+  markdown.push('\n');
+  markdown
+}
+
+fn message_to_markdown(message: &ChatExportMessage) -> String {
+  let mut section = vec![
+    format!("## {}", author_name(message)),
+    message.content.trim().to_string(),
+  ];
+  if !message.sources.is_empty() {
+    let sources = message
+      .sources
+      .iter()
+      .map(|source| format!("- {}", source_label(source)))
+      .collect::<Vec<_>>()
+      .join("\n");
+    section.push(format!("**Sources**\n\n{}", sources));
+  }
+  if !message.related_questions.is_empty() {
+    let questions = message
+      .related_questions
+      .iter()
+      .map(|question| format!("- {}", question))
+      .collect::<Vec<_>>()
+      .join("\n");
+    section.push(format!("**Related questions**\n\n{}", questions));
+  }
+  section.join("\n\n")
+}
+
+/// Returns the views and the files in the metadata of a message. The metadata is a source or a
+/// list of sources, the entries without id, such as the progress of an answer, are skipped.
+pub fn parse_message_sources(metadata: &str) -> Vec<ChatExportSource> {
+  let values = match serde_json::from_str::<Value>(metadata) {
+    Ok(Value::Array(values)) => values,
+    Ok(value @ Value::Object(_)) => vec![value],
+    _ => return vec![],
+  };
+
+  let mut ids = HashSet::new();
+  values
+    .iter()
+    .filter_map(|value| {
+      let id = value.get("id")?.as_str()?.to_string();
+      let name = value
+        .get("name")
+        .and_then(Value::as_str)
+        .filter(|name| !name.is_empty())
+        .unwrap_or(id.as_str())
+        .to_string();
+      let source = value
+        .get("source")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+      Some(ChatExportSource { id, name, source })
+    })
+    // The passages of a view share the id of the view
+    .filter(|source| ids.insert(source.id.clone()))
+    .collect()
+}
+
+fn author_name(message: &ChatExportMessage) -> &'static str {
+  if message.is_answer() {
+    "AI"
+  } else {
+    "You"
+  }
+}
+
+fn source_label(source: &ChatExportSource) -> String {
+  if source.source.is_empty() || source.source == APPFLOWY_SOURCE {
+    source.name.clone()
+  } else {
+    format!("{} ({})", source.name, source.source)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn message(author_type: ChatAuthorType, content: &str) -> ChatExportMessage {
+    ChatExportMessage {
+      message_id: 0,
+      author_type: author_type as i64,
+      content: content.to_string(),
+      sources: vec![],
+      related_questions: vec![],
+    }
+  }
+
+  #[test]
+  fn parse_message_sources_test() {
+    let metadata = r#"[
+      {"id": "v1", "name": "Notes", "source": "appflowy", "block_id": "b1"},
+      {"id": "v1", "name": "Notes", "source": "appflowy", "block_id": "b2"},
+      {"id": "f1", "name": "", "source": "/tmp/report.pdf"},
+      {"step": "Searching"}
+    ]"#;
+    let sources = parse_message_sources(metadata);
+    assert_eq!(sources.len(), 2);
+    assert_eq!(source_label(&sources[0]), "Notes");
+    assert_eq!(source_label(&sources[1]), "f1 (/tmp/report.pdf)");
+
+    let sources = parse_message_sources(r#"{"id": "v2", "name": "Plan", "source": "appflowy"}"#);
+    assert_eq!(sources[0].name, "Plan");
+    assert!(parse_message_sources("not json").is_empty());
+  }
+
+  #[test]
+  fn chat_to_markdown_test() {
+    let question = message(ChatAuthorType::Human, "What is AppFlowy?");
+    let mut answer = message(ChatAuthorType::AI, "An open source workspace.");
+    answer.sources =
+      parse_message_sources(r#"{"id": "v1", "name": "Intro", "source": "appflowy"}"#);
+    answer.related_questions = vec!["Is it free?".to_string()];
+
+    let markdown = chat_to_markdown("About", &[question, answer]);
+    assert_eq!(
+      markdown,
+      "# About\n\n## You\n\nWhat is AppFlowy?\n\n## AI\n\nAn open source workspace.\n\n**Sources**\n\n- Intro\n\n**Related questions**\n\n- Is it free?\n"
+    );
+  }
+
+  #[test]
+  fn chat_to_document_markdown_test() {
+    let messages = vec![
+      message(ChatAuthorType::Human, "Q1"),
+      message(ChatAuthorType::AI, "A1"),
+      message(ChatAuthorType::Human, "Q2"),
+    ];
+    assert_eq!(
+      chat_to_document_markdown(&messages),
+      "## You\n\nQ1\n\n## AI\n\nA1\n\n---\n\n## You\n\nQ2\n"
+    );
+  }
+}
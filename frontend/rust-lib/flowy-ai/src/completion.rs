@@ -1,4 +1,5 @@
 use crate::ai_manager::AIUserService;
+use crate::custom_prompt::{render_prompt, CustomPromptController};
 use crate::entities::{CompleteTextPB, CompleteTextTaskPB, CompletionTypePB};
 use allo_isolate::Isolate;
 
@@ -16,17 +17,20 @@ pub struct AICompletion {
   tasks: Arc<DashMap<String, tokio::sync::mpsc::Sender<()>>>,
   cloud_service: Weak<dyn ChatCloudService>,
   user_service: Weak<dyn AIUserService>,
+  custom_prompt_controller: Weak<CustomPromptController>,
 }
 
 impl AICompletion {
   pub fn new(
     cloud_service: Weak<dyn ChatCloudService>,
     user_service: Weak<dyn AIUserService>,
+    custom_prompt_controller: Weak<CustomPromptController>,
   ) -> Self {
     Self {
       tasks: Arc::new(DashMap::new()),
       cloud_service,
       user_service,
+      custom_prompt_controller,
     }
   }
 
@@ -39,8 +43,27 @@ impl AICompletion {
       .upgrade()
       .ok_or_else(FlowyError::internal)?
       .workspace_id()?;
+    // The custom prompt is used instead of the completion type
+    let prompt = match complete.custom_prompt_id.as_deref() {
+      Some(prompt_id) => {
+        let prompt = self
+          .custom_prompt_controller
+          .upgrade()
+          .ok_or_else(FlowyError::internal)?
+          .get_prompt(prompt_id)?;
+        let context = complete.context.as_deref().unwrap_or_default();
+        Some(render_prompt(&prompt.template, context))
+      },
+      None => None,
+    };
     let (tx, rx) = tokio::sync::mpsc::channel(1);
-    let task = CompletionTask::new(workspace_id, complete, self.cloud_service.clone(), rx);
+    let task = CompletionTask::new(
+      workspace_id,
+      complete,
+      prompt,
+      self.cloud_service.clone(),
+      rx,
+    );
     let task_id = task.task_id.clone();
     self.tasks.insert(task_id.clone(), tx);
 
@@ -60,6 +83,7 @@ pub struct CompletionTask {
   task_id: String,
   stop_rx: tokio::sync::mpsc::Receiver<()>,
   context: CompleteTextPB,
+  prompt: Option<String>,
   cloud_service: Weak<dyn ChatCloudService>,
 }
 
@@ -67,6 +91,7 @@ impl CompletionTask {
   pub fn new(
     workspace_id: String,
     context: CompleteTextPB,
+    prompt: Option<String>,
     cloud_service: Weak<dyn ChatCloudService>,
     stop_rx: tokio::sync::mpsc::Receiver<()>,
   ) -> Self {
@@ -74,6 +99,7 @@ impl CompletionTask {
       workspace_id,
       task_id: uuid::Uuid::new_v4().to_string(),
       context,
+      prompt,
       cloud_service,
       stop_rx,
     }
@@ -95,10 +121,19 @@ impl CompletionTask {
         };
 
         let _ = sink.send("start:".to_string()).await;
-        match cloud_service
-          .stream_complete(&self.workspace_id, &self.context.text, complete_type)
-          .await
-        {
+        let result = match &self.prompt {
+          Some(prompt) => {
+            cloud_service
+              .stream_complete_with_prompt(&self.workspace_id, &self.context.text, prompt)
+              .await
+          },
+          None => {
+            cloud_service
+              .stream_complete(&self.workspace_id, &self.context.text, complete_type)
+              .await
+          },
+        };
+        match result {
           Ok(mut stream) => loop {
             select! {
                 _ = self.stop_rx.recv() => {
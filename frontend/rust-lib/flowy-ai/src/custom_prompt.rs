@@ -0,0 +1,282 @@
+use crate::ai_manager::{AIPromptSyncService, AIUserService};
+use crate::persistence::{
+  select_custom_prompt, select_custom_prompts, upsert_custom_prompt, CustomPromptTable,
+};
+use flowy_error::{FlowyError, FlowyResult};
+use flowy_sqlite::DBConnection;
+use lib_infra::util::timestamp;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use tracing::{error, trace};
+
+/// Refers to the text that is selected by the user. The selection is sent as the text to complete,
+/// so it's not repeated in the prompt.
+pub const SELECTION_PLACEHOLDER: &str = "{{selection}}";
+const SELECTION_REFERENCE: &str = "the given text";
+/// Replaced with the text around the selection.
+pub const CONTEXT_PLACEHOLDER: &str = "{{context}}";
+
+/// The completion prompts that are defined by the user. The prompts are stored on disk and synced
+/// with the user awareness of the workspace.
+pub struct CustomPromptController {
+  user_service: Arc<dyn AIUserService>,
+  sync_service: RwLock<Option<Arc<dyn AIPromptSyncService>>>,
+}
+
+impl CustomPromptController {
+  pub fn new(user_service: Arc<dyn AIUserService>) -> Self {
+    Self {
+      user_service,
+      sync_service: RwLock::new(None),
+    }
+  }
+
+  pub fn set_sync_service(&self, sync_service: Arc<dyn AIPromptSyncService>) {
+    if let Ok(mut service) = self.sync_service.write() {
+      *service = Some(sync_service);
+    }
+  }
+
+  /// Returns the prompts of the current workspace, the prompts that were changed on other devices
+  /// are merged first.
+  pub async fn get_prompts(&self) -> FlowyResult<Vec<CustomPromptTable>> {
+    if let Err(err) = self.sync_prompts().await {
+      error!("[AI Prompt] failed to sync custom prompts: {}", err);
+    }
+
+    let workspace_id = self.user_service.workspace_id()?;
+    let mut conn = self.sqlite_connection()?;
+    let prompts = select_custom_prompts(&mut conn, &workspace_id)?
+      .into_iter()
+      .filter(|prompt| !prompt.is_deleted)
+      .collect();
+    Ok(prompts)
+  }
+
+  /// Returns the prompt if it's in the current workspace and not deleted.
+  pub fn get_prompt(&self, id: &str) -> FlowyResult<CustomPromptTable> {
+    let workspace_id = self.user_service.workspace_id()?;
+    let mut conn = self.sqlite_connection()?;
+    select_custom_prompt(&mut conn, id)?
+      .filter(|prompt| !prompt.is_deleted && prompt.workspace_id == workspace_id)
+      .ok_or_else(|| {
+        FlowyError::record_not_found().with_context(format!("Custom prompt not found: {}", id))
+      })
+  }
+
+  pub async fn create_prompt(
+    &self,
+    name: String,
+    template: String,
+  ) -> FlowyResult<CustomPromptTable> {
+    if name.trim().is_empty() || template.trim().is_empty() {
+      return Err(
+        FlowyError::invalid_data().with_context("The name and the template can't be empty"),
+      );
+    }
+    let now = timestamp();
+    let prompt = CustomPromptTable {
+      id: uuid::Uuid::new_v4().to_string(),
+      workspace_id: self.user_service.workspace_id()?,
+      name,
+      template,
+      is_deleted: false,
+      created_at: now,
+      updated_at: now,
+    };
+    self.save_prompt(&prompt).await?;
+    Ok(prompt)
+  }
+
+  pub async fn update_prompt(
+    &self,
+    id: &str,
+    name: Option<String>,
+    template: Option<String>,
+  ) -> FlowyResult<CustomPromptTable> {
+    let mut prompt = self.get_prompt(id)?;
+    // The name and the template can't be empty
+    if let Some(name) = name.filter(|name| !name.trim().is_empty()) {
+      prompt.name = name;
+    }
+    if let Some(template) = template.filter(|template| !template.trim().is_empty()) {
+      prompt.template = template;
+    }
+    prompt.updated_at = timestamp();
+    self.save_prompt(&prompt).await?;
+    Ok(prompt)
+  }
+
+  pub async fn delete_prompt(&self, id: &str) -> FlowyResult<()> {
+    let mut prompt = self.get_prompt(id)?;
+    prompt.is_deleted = true;
+    prompt.updated_at = timestamp();
+    self.save_prompt(&prompt).await
+  }
+
+  async fn save_prompt(&self, prompt: &CustomPromptTable) -> FlowyResult<()> {
+    let mut conn = self.sqlite_connection()?;
+    upsert_custom_prompt(&mut conn, prompt)?;
+    drop(conn);
+
+    // The prompt is pushed again the next time the prompts are synced if it fails
+    if let Some(sync_service) = self.sync_service() {
+      if let Err(err) = push_prompt(sync_service.as_ref(), prompt).await {
+        error!(
+          "[AI Prompt] failed to sync custom prompt {}: {}",
+          prompt.id, err
+        );
+      }
+    }
+    Ok(())
+  }
+
+  async fn sync_prompts(&self) -> FlowyResult<()> {
+    let Some(sync_service) = self.sync_service() else {
+      return Ok(());
+    };
+    let workspace_id = self.user_service.workspace_id()?;
+    let remote = sync_service
+      .get_prompts()
+      .await?
+      .into_iter()
+      .filter_map(
+        |data| match serde_json::from_str::<CustomPromptTable>(&data) {
+          Ok(prompt) => Some(prompt),
+          Err(err) => {
+            error!("[AI Prompt] invalid custom prompt: {}", err);
+            None
+          },
+        },
+      )
+      .collect::<Vec<_>>();
+
+    let mut conn = self.sqlite_connection()?;
+    let local = select_custom_prompts(&mut conn, &workspace_id)?;
+    let (pulled, pushed) = merge_prompts(local, remote);
+    trace!(
+      "[AI Prompt] sync custom prompts: pulled={}, pushed={}",
+      pulled.len(),
+      pushed.len()
+    );
+    for mut prompt in pulled {
+      prompt.workspace_id = workspace_id.clone();
+      upsert_custom_prompt(&mut conn, &prompt)?;
+    }
+    drop(conn);
+
+    for prompt in pushed {
+      push_prompt(sync_service.as_ref(), &prompt).await?;
+    }
+    Ok(())
+  }
+
+  fn sync_service(&self) -> Option<Arc<dyn AIPromptSyncService>> {
+    self
+      .sync_service
+      .read()
+      .ok()
+      .and_then(|service| service.clone())
+  }
+
+  fn sqlite_connection(&self) -> FlowyResult<DBConnection> {
+    let uid = self.user_service.user_id()?;
+    self.user_service.sqlite_connection(uid)
+  }
+}
+
+/// Fills the placeholders of the template. The selected text is sent as the text to complete, so
+/// the selection placeholder refers to it instead of repeating it, and a template without
+/// placeholders is applied to the selection.
+pub fn render_prompt(template: &str, context: &str) -> String {
+  template
+    .replace(SELECTION_PLACEHOLDER, SELECTION_REFERENCE)
+    .replace(CONTEXT_PLACEHOLDER, context)
+}
+
+async fn push_prompt(
+  sync_service: &dyn AIPromptSyncService,
+  prompt: &CustomPromptTable,
+) -> FlowyResult<()> {
+  let data = serde_json::to_string(prompt)?;
+  sync_service.set_prompt(&prompt.id, data).await
+}
+
+/// Returns the prompts that are newer on the other devices, and the prompts that are newer on this
+/// device. The deleted prompts are merged like the other changes.
+fn merge_prompts(
+  local: Vec<CustomPromptTable>,
+  remote: Vec<CustomPromptTable>,
+) -> (Vec<CustomPromptTable>, Vec<CustomPromptTable>) {
+  let mut local = local
+    .into_iter()
+    .map(|prompt| (prompt.id.clone(), prompt))
+    .collect::<HashMap<_, _>>();
+
+  let mut pulled = vec![];
+  for prompt in remote {
+    match local.get(&prompt.id).map(|existing| existing.updated_at) {
+      Some(updated_at) if updated_at > prompt.updated_at => {},
+      Some(updated_at) if updated_at == prompt.updated_at => {
+        local.remove(&prompt.id);
+      },
+      _ => {
+        local.remove(&prompt.id);
+        pulled.push(prompt);
+      },
+    }
+  }
+  // The prompts that are left are missing or outdated on the other devices
+  let pushed = local.into_values().collect();
+  (pulled, pushed)
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn prompt(id: &str, name: &str, updated_at: i64) -> CustomPromptTable {
+    CustomPromptTable {
+      id: id.to_string(),
+      workspace_id: "w1".to_string(),
+      name: name.to_string(),
+      template: "Translate {{selection}} into French".to_string(),
+      is_deleted: false,
+      created_at: 1,
+      updated_at,
+    }
+  }
+
+  #[test]
+  fn render_prompt_test() {
+    let prompt = render_prompt("Rewrite {{selection}} so it fits {{context}}", "a letter");
+    assert_eq!(prompt, "Rewrite the given text so it fits a letter");
+    assert_eq!(render_prompt("Be brief", ""), "Be brief");
+  }
+
+  #[test]
+  fn merge_prompts_test() {
+    let local = vec![
+      prompt("1", "local newer", 5),
+      prompt("2", "local older", 1),
+      prompt("3", "same", 3),
+      prompt("4", "local only", 1),
+    ];
+    let mut deleted = prompt("2", "remote newer", 4);
+    deleted.is_deleted = true;
+    let remote = vec![
+      prompt("1", "remote older", 2),
+      deleted,
+      prompt("3", "same", 3),
+      prompt("5", "remote only", 1),
+    ];
+
+    let (pulled, pushed) = merge_prompts(local, remote);
+    let mut pulled = pulled.into_iter().map(|p| p.id).collect::<Vec<_>>();
+    let mut pushed = pushed.into_iter().map(|p| p.name).collect::<Vec<_>>();
+    pulled.sort();
+    pushed.sort();
+    assert_eq!(pulled, vec!["2", "5"]);
+    assert_eq!(pushed, vec!["local newer", "local only"]);
+  }
+}
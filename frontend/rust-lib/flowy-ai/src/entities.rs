@@ -2,7 +2,10 @@ use crate::local_ai::local_llm_chat::LLMModelInfo;
 use appflowy_plugin::core::plugin::RunningState;
 use std::collections::HashMap;
 
+use crate::ai_usage::{AIUsageGroup, AIUsageLimit, AIUsageSummary};
 use crate::local_ai::local_llm_resource::PendingResource;
+use crate::openai_compatible::setting::OpenAICompatibleSetting;
+use crate::persistence::{ChatMessageSearchRow, ChatMessageTable, CustomPromptTable};
 use flowy_ai_pub::cloud::{
   ChatMessage, LLMModel, RelatedQuestion, RepeatedChatMessage, RepeatedRelatedQuestion,
 };
@@ -213,6 +216,20 @@ impl From<ChatMessage> for ChatMessagePB {
   }
 }
 
+impl From<ChatMessageTable> for ChatMessagePB {
+  fn from(record: ChatMessageTable) -> Self {
+    ChatMessagePB {
+      message_id: record.message_id,
+      content: record.content,
+      created_at: record.created_at,
+      author_type: record.author_type,
+      author_id: record.author_id,
+      reply_message_id: record.reply_message_id,
+      metadata: record.metadata,
+    }
+  }
+}
+
 #[derive(Debug, Clone, Default, ProtoBuf)]
 pub struct RepeatedChatMessagePB {
   #[pb(index = 1)]
@@ -236,6 +253,148 @@ pub struct ChatMessageIdPB {
   pub message_id: i64,
 }
 
+#[derive(Default, ProtoBuf, Validate, Clone, Debug)]
+pub struct RegenerateAnswerPB {
+  #[pb(index = 1)]
+  #[validate(custom(function = "required_not_empty_str"))]
+  pub chat_id: String,
+
+  #[pb(index = 2)]
+  pub question_message_id: i64,
+
+  #[pb(index = 3)]
+  pub answer_stream_port: i64,
+}
+
+#[derive(Default, ProtoBuf, Validate, Clone, Debug)]
+pub struct EditQuestionPB {
+  #[pb(index = 1)]
+  #[validate(custom(function = "required_not_empty_str"))]
+  pub chat_id: String,
+
+  /// The id of the question that is edited
+  #[pb(index = 2)]
+  pub message_id: i64,
+
+  #[pb(index = 3)]
+  #[validate(custom(function = "required_not_empty_str"))]
+  pub message: String,
+
+  #[pb(index = 4)]
+  pub answer_stream_port: i64,
+
+  #[pb(index = 5)]
+  pub question_stream_port: i64,
+}
+
+/// The answers of the same question, or the edited versions of the same question.
+#[derive(Debug, Clone, Default, ProtoBuf)]
+pub struct ChatMessageBranchesPB {
+  #[pb(index = 1)]
+  pub message_id: i64,
+
+  /// Ordered by the time they were created, including the message itself.
+  #[pb(index = 2)]
+  pub branches: Vec<ChatMessagePB>,
+
+  #[pb(index = 3)]
+  pub active_message_id: i64,
+}
+
+#[derive(Default, ProtoBuf, Validate, Clone, Debug)]
+pub struct ExportChatPB {
+  #[pb(index = 1)]
+  #[validate(custom(function = "required_not_empty_str"))]
+  pub chat_id: String,
+
+  /// Fetch the related questions of the last answer from the AI service
+  #[pb(index = 2)]
+  pub include_related_questions: bool,
+}
+
+#[derive(Default, ProtoBuf, Clone, Debug)]
+pub struct ChatMarkdownPB {
+  #[pb(index = 1)]
+  pub chat_id: String,
+
+  #[pb(index = 2)]
+  pub markdown: String,
+}
+
+#[derive(Default, ProtoBuf, Validate, Clone, Debug)]
+pub struct ExportChatToDocumentPB {
+  #[pb(index = 1)]
+  #[validate(custom(function = "required_not_empty_str"))]
+  pub chat_id: String,
+
+  #[pb(index = 2)]
+  #[validate(custom(function = "required_not_empty_str"))]
+  pub parent_view_id: String,
+
+  /// The name of the document, the name of the chat is used if it's not set
+  #[pb(index = 3, one_of)]
+  pub name: Option<String>,
+
+  /// Add the related questions of the last answer from the AI service
+  #[pb(index = 4)]
+  pub include_related_questions: bool,
+}
+
+#[derive(Default, ProtoBuf, Clone, Debug)]
+pub struct ExportedChatDocumentPB {
+  #[pb(index = 1)]
+  pub view_id: String,
+}
+
+#[derive(Default, ProtoBuf, Validate, Clone, Debug)]
+pub struct SearchChatMessagesPB {
+  #[pb(index = 1)]
+  #[validate(custom(function = "required_not_empty_str"))]
+  pub query: String,
+
+  /// The maximum number of hits, 20 if it's not set
+  #[pb(index = 2)]
+  pub limit: i64,
+}
+
+#[derive(Default, ProtoBuf, Clone, Debug)]
+pub struct ChatMessageSearchHitPB {
+  #[pb(index = 1)]
+  pub chat_id: String,
+
+  #[pb(index = 2)]
+  pub message_id: i64,
+
+  /// The part of the message around the matched words, which are wrapped in `**`. The start of
+  /// the message if all the words are shorter than three characters.
+  #[pb(index = 3)]
+  pub snippet: String,
+
+  #[pb(index = 4)]
+  pub created_at: i64,
+
+  #[pb(index = 5)]
+  pub author_type: i64,
+}
+
+impl From<ChatMessageSearchRow> for ChatMessageSearchHitPB {
+  fn from(row: ChatMessageSearchRow) -> Self {
+    Self {
+      chat_id: row.chat_id,
+      message_id: row.message_id,
+      snippet: row.snippet,
+      created_at: row.created_at,
+      author_type: row.author_type,
+    }
+  }
+}
+
+#[derive(Default, ProtoBuf, Clone, Debug)]
+pub struct RepeatedChatMessageSearchHitPB {
+  #[pb(index = 1)]
+  pub items: Vec<ChatMessageSearchHitPB>,
+}
+
 #[derive(Debug, Clone, Default, ProtoBuf)]
 pub struct RelatedQuestionPB {
   #[pb(index = 1)]
@@ -330,6 +489,14 @@ pub struct CompleteTextPB {
 
   #[pb(index = 3)]
   pub stream_port: i64,
+
+  /// Complete with the custom prompt instead of the completion type
+  #[pb(index = 4, one_of)]
+  pub custom_prompt_id: Option<String>,
+
+  /// The text around the selection, used by the custom prompts
+  #[pb(index = 5, one_of)]
+  pub context: Option<String>,
 }
 
 #[derive(Default, ProtoBuf, Clone, Debug)]
@@ -349,6 +516,75 @@ pub enum CompletionTypePB {
   ContinueWriting = 5,
 }
 
+/// A completion prompt that is defined by the user. The template can refer to the selected text
+/// with `{{selection}}` and to the text around it with `{{context}}`.
+#[derive(Default, ProtoBuf, Clone, Debug)]
+pub struct CustomPromptPB {
+  #[pb(index = 1)]
+  pub id: String,
+
+  #[pb(index = 2)]
+  pub name: String,
+
+  #[pb(index = 3)]
+  pub template: String,
+
+  #[pb(index = 4)]
+  pub created_at: i64,
+
+  #[pb(index = 5)]
+  pub updated_at: i64,
+}
+
+impl From<CustomPromptTable> for CustomPromptPB {
+  fn from(value: CustomPromptTable) -> Self {
+    CustomPromptPB {
+      id: value.id,
+      name: value.name,
+      template: value.template,
+      created_at: value.created_at,
+      updated_at: value.updated_at,
+    }
+  }
+}
+
+#[derive(Default, ProtoBuf, Clone, Debug)]
+pub struct RepeatedCustomPromptPB {
+  #[pb(index = 1)]
+  pub items: Vec<CustomPromptPB>,
+}
+
+#[derive(Default, ProtoBuf, Validate, Clone, Debug)]
+pub struct CreateCustomPromptPB {
+  #[pb(index = 1)]
+  #[validate(custom(function = "required_not_empty_str"))]
+  pub name: String,
+
+  #[pb(index = 2)]
+  #[validate(custom(function = "required_not_empty_str"))]
+  pub template: String,
+}
+
+#[derive(Default, ProtoBuf, Validate, Clone, Debug)]
+pub struct UpdateCustomPromptPB {
+  #[pb(index = 1)]
+  #[validate(custom(function = "required_not_empty_str"))]
+  pub id: String,
+
+  #[pb(index = 2, one_of)]
+  pub name: Option<String>,
+
+  #[pb(index = 3, one_of)]
+  pub template: Option<String>,
+}
+
+#[derive(Default, ProtoBuf, Validate, Clone, Debug)]
+pub struct CustomPromptIdPB {
+  #[pb(index = 1)]
+  #[validate(custom(function = "required_not_empty_str"))]
+  pub id: String,
+}
+
 #[derive(Default, ProtoBuf, Clone, Debug)]
 pub struct ChatStatePB {
   #[pb(index = 1)]
@@ -380,6 +616,10 @@ pub struct ChatFilePB {
 pub struct DownloadLLMPB {
   #[pb(index = 1)]
   pub progress_stream: i64,
+
+  /// Limits the download speed of the models. Unlimited if None or not positive.
+  #[pb(index = 2, one_of)]
+  pub max_bytes_per_sec: Option<i64>,
 }
 
 #[derive(Default, ProtoBuf, Clone, Debug)]
@@ -525,3 +765,145 @@ pub struct CreateChatContextPB {
   #[validate(custom(function = "required_not_empty_str"))]
   pub chat_id: String,
 }
+
+/// The OpenAI-compatible endpoint of the current workspace, e.g. an Ollama or a llama.cpp server.
+#[derive(Default, ProtoBuf, Clone, Debug)]
+pub struct OpenAICompatibleSettingPB {
+  #[pb(index = 1)]
+  pub enabled: bool,
+
+  #[pb(index = 2)]
+  pub base_url: String,
+
+  #[pb(index = 3)]
+  pub model: String,
+
+  #[pb(index = 4)]
+  pub api_key: String,
+}
+
+impl From<OpenAICompatibleSetting> for OpenAICompatibleSettingPB {
+  fn from(setting: OpenAICompatibleSetting) -> Self {
+    Self {
+      enabled: setting.enabled,
+      base_url: setting.base_url,
+      model: setting.model,
+      api_key: setting.api_key,
+    }
+  }
+}
+
+impl From<OpenAICompatibleSettingPB> for OpenAICompatibleSetting {
+  fn from(pb: OpenAICompatibleSettingPB) -> Self {
+    Self {
+      enabled: pb.enabled,
+      base_url: pb.base_url.trim().to_string(),
+      model: pb.model.trim().to_string(),
+      api_key: pb.api_key,
+    }
+  }
+}
+
+#[derive(Debug, Default, Clone, ProtoBuf_Enum, PartialEq, Eq, Copy)]
+pub enum AIUsageGroupPB {
+  #[default]
+  Day = 0,
+  Chat = 1,
+  Model = 2,
+}
+
+impl From<AIUsageGroupPB> for AIUsageGroup {
+  fn from(pb: AIUsageGroupPB) -> Self {
+    match pb {
+      AIUsageGroupPB::Day => AIUsageGroup::Day,
+      AIUsageGroupPB::Chat => AIUsageGroup::Chat,
+      AIUsageGroupPB::Model => AIUsageGroup::Model,
+    }
+  }
+}
+
+#[derive(Default, ProtoBuf, Clone, Debug)]
+pub struct AIUsageQueryPB {
+  #[pb(index = 1)]
+  pub group_by: AIUsageGroupPB,
+
+  /// The timestamp in seconds
+  #[pb(index = 2)]
+  pub start_time: i64,
+
+  /// The timestamp in seconds, 0 means now
+  #[pb(index = 3)]
+  pub end_time: i64,
+}
+
+#[derive(Default, ProtoBuf, Clone, Debug)]
+pub struct AIUsageSummaryPB {
+  /// The local date in `YYYY-MM-DD` format, the chat id or the model. The chat id is empty for
+  /// the completions and the AI fields.
+  #[pb(index = 1)]
+  pub key: String,
+
+  #[pb(index = 2)]
+  pub request_count: i64,
+
+  #[pb(index = 3)]
+  pub failed_count: i64,
+
+  /// Estimated from the question or the text to complete. The chat history and the passages of
+  /// the views that are sent with a question aren't included, so the chat input is undercounted.
+  #[pb(index = 4)]
+  pub input_tokens: i64,
+
+  #[pb(index = 5)]
+  pub output_tokens: i64,
+
+  #[pb(index = 6)]
+  pub average_latency_ms: i64,
+}
+
+impl From<AIUsageSummary> for AIUsageSummaryPB {
+  fn from(summary: AIUsageSummary) -> Self {
+    Self {
+      key: summary.key,
+      request_count: summary.requests,
+      failed_count: summary.failed_requests,
+      input_tokens: summary.input_tokens,
+      output_tokens: summary.output_tokens,
+      average_latency_ms: summary.average_latency_ms,
+    }
+  }
+}
+
+#[derive(Default, ProtoBuf, Clone, Debug)]
+pub struct RepeatedAIUsageSummaryPB {
+  #[pb(index = 1)]
+  pub items: Vec<AIUsageSummaryPB>,
+}
+
+/// The daily AI usage limits of the current workspace, 0 means unlimited.
+#[derive(Default, ProtoBuf, Clone, Debug)]
+pub struct AIUsageLimitPB {
+  #[pb(index = 1)]
+  pub daily_request_limit: i64,
+
+  #[pb(index = 2)]
+  pub daily_token_limit: i64,
+}
+
+impl From<AIUsageLimit> for AIUsageLimitPB {
+  fn from(limit: AIUsageLimit) -> Self {
+    Self {
+      daily_request_limit: limit.daily_requests,
+      daily_token_limit: limit.daily_tokens,
+    }
+  }
+}
+
+impl From<AIUsageLimitPB> for AIUsageLimit {
+  fn from(pb: AIUsageLimitPB) -> Self {
+    Self {
+      daily_requests: pb.daily_request_limit,
+      daily_tokens: pb.daily_token_limit,
+    }
+  }
+}
@@ -261,9 +261,13 @@ pub(crate) async fn download_llm_resource_handler(
   let data = data.into_inner();
   let ai_manager = upgrade_ai_manager(ai_manager)?;
   let text_sink = IsolateSink::new(Isolate::new(data.progress_stream));
+  let max_bytes_per_sec = data
+    .max_bytes_per_sec
+    .filter(|value| *value > 0)
+    .map(|value| value as u64);
   let task_id = ai_manager
     .local_ai_controller
-    .start_downloading(text_sink)
+    .start_downloading(text_sink, max_bytes_per_sec)
     .await?;
   data_result_ok(DownloadTaskPB { task_id })
 }
@@ -420,3 +424,232 @@ pub(crate) async fn get_chat_info_handler(
   let pb = ai_manager.get_chat_info(&chat_id).await?;
   data_result_ok(pb)
 }
+
+#[tracing::instrument(level = "debug", skip_all, err)]
+pub(crate) async fn get_openai_compatible_setting_handler(
+  ai_manager: AFPluginState<Weak<AIManager>>,
+) -> DataResult<OpenAICompatibleSettingPB, FlowyError> {
+  let ai_manager = upgrade_ai_manager(ai_manager)?;
+  let setting = ai_manager.get_openai_compatible_setting()?;
+  data_result_ok(setting.into())
+}
+
+#[tracing::instrument(level = "debug", skip_all, err)]
+pub(crate) async fn update_openai_compatible_setting_handler(
+  data: AFPluginData<OpenAICompatibleSettingPB>,
+  ai_manager: AFPluginState<Weak<AIManager>>,
+) -> FlowyResult<()> {
+  let data = data.into_inner();
+  let ai_manager = upgrade_ai_manager(ai_manager)?;
+  ai_manager.update_openai_compatible_setting(data.into())?;
+  Ok(())
+}
+
+#[tracing::instrument(level = "debug", skip_all, err)]
+pub(crate) async fn regenerate_answer_handler(
+  data: AFPluginData<RegenerateAnswerPB>,
+  ai_manager: AFPluginState<Weak<AIManager>>,
+) -> FlowyResult<()> {
+  let data = data.try_into_inner()?;
+  let ai_manager = upgrade_ai_manager(ai_manager)?;
+  ai_manager
+    .regenerate_answer(
+      &data.chat_id,
+      data.question_message_id,
+      data.answer_stream_port,
+    )
+    .await?;
+  Ok(())
+}
+
+#[tracing::instrument(level = "debug", skip_all, err)]
+pub(crate) async fn edit_question_handler(
+  data: AFPluginData<EditQuestionPB>,
+  ai_manager: AFPluginState<Weak<AIManager>>,
+) -> DataResult<ChatMessagePB, FlowyError> {
+  let data = data.try_into_inner()?;
+  let ai_manager = upgrade_ai_manager(ai_manager)?;
+  let question = ai_manager
+    .edit_question(
+      &data.chat_id,
+      data.message_id,
+      &data.message,
+      data.answer_stream_port,
+      data.question_stream_port,
+    )
+    .await?;
+  data_result_ok(question)
+}
+
+#[tracing::instrument(level = "debug", skip_all, err)]
+pub(crate) async fn get_message_branches_handler(
+  data: AFPluginData<ChatMessageIdPB>,
+  ai_manager: AFPluginState<Weak<AIManager>>,
+) -> DataResult<ChatMessageBranchesPB, FlowyError> {
+  let data = data.into_inner();
+  let ai_manager = upgrade_ai_manager(ai_manager)?;
+  let branches = ai_manager
+    .get_message_branches(&data.chat_id, data.message_id)
+    .await?;
+  data_result_ok(branches)
+}
+
+#[tracing::instrument(level = "debug", skip_all, err)]
+pub(crate) async fn switch_message_branch_handler(
+  data: AFPluginData<ChatMessageIdPB>,
+  ai_manager: AFPluginState<Weak<AIManager>>,
+) -> FlowyResult<()> {
+  let data = data.into_inner();
+  let ai_manager = upgrade_ai_manager(ai_manager)?;
+  ai_manager
+    .switch_message_branch(&data.chat_id, data.message_id)
+    .await?;
+  Ok(())
+}
+
+#[tracing::instrument(level = "debug", skip_all, err)]
+pub(crate) async fn export_chat_to_markdown_handler(
+  data: AFPluginData<ExportChatPB>,
+  ai_manager: AFPluginState<Weak<AIManager>>,
+) -> DataResult<ChatMarkdownPB, FlowyError> {
+  let data = data.try_into_inner()?;
+  let ai_manager = upgrade_ai_manager(ai_manager)?;
+  let markdown = ai_manager
+    .export_chat_to_markdown(&data.chat_id, data.include_related_questions)
+    .await?;
+  data_result_ok(ChatMarkdownPB {
+    chat_id: data.chat_id,
+    markdown,
+  })
+}
+
+#[tracing::instrument(level = "debug", skip_all, err)]
+pub(crate) async fn export_chat_to_document_handler(
+  data: AFPluginData<ExportChatToDocumentPB>,
+  ai_manager: AFPluginState<Weak<AIManager>>,
+) -> DataResult<ExportedChatDocumentPB, FlowyError> {
+  let data = data.try_into_inner()?;
+  let ai_manager = upgrade_ai_manager(ai_manager)?;
+  let view_id = ai_manager
+    .export_chat_to_document(
+      &data.chat_id,
+      &data.parent_view_id,
+      data.name,
+      data.include_related_questions,
+    )
+    .await?;
+  data_result_ok(ExportedChatDocumentPB { view_id })
+}
+
+#[tracing::instrument(level = "debug", skip_all, err)]
+pub(crate) async fn search_chat_messages_handler(
+  data: AFPluginData<SearchChatMessagesPB>,
+  ai_manager: AFPluginState<Weak<AIManager>>,
+) -> DataResult<RepeatedChatMessageSearchHitPB, FlowyError> {
+  let data = data.try_into_inner()?;
+  let ai_manager = upgrade_ai_manager(ai_manager)?;
+  let items = ai_manager
+    .search_chat_messages(&data.query, data.limit)
+    .await?
+    .into_iter()
+    .map(ChatMessageSearchHitPB::from)
+    .collect();
+  data_result_ok(RepeatedChatMessageSearchHitPB { items })
+}
+
+#[tracing::instrument(level = "debug", skip_all, err)]
+pub(crate) async fn get_custom_prompts_handler(
+  ai_manager: AFPluginState<Weak<AIManager>>,
+) -> DataResult<RepeatedCustomPromptPB, FlowyError> {
+  let ai_manager = upgrade_ai_manager(ai_manager)?;
+  let items = ai_manager
+    .custom_prompt_controller
+    .get_prompts()
+    .await?
+    .into_iter()
+    .map(CustomPromptPB::from)
+    .collect();
+  data_result_ok(RepeatedCustomPromptPB { items })
+}
+
+#[tracing::instrument(level = "debug", skip_all, err)]
+pub(crate) async fn create_custom_prompt_handler(
+  data: AFPluginData<CreateCustomPromptPB>,
+  ai_manager: AFPluginState<Weak<AIManager>>,
+) -> DataResult<CustomPromptPB, FlowyError> {
+  let data = data.try_into_inner()?;
+  let ai_manager = upgrade_ai_manager(ai_manager)?;
+  let prompt = ai_manager
+    .custom_prompt_controller
+    .create_prompt(data.name, data.template)
+    .await?;
+  data_result_ok(CustomPromptPB::from(prompt))
+}
+
+#[tracing::instrument(level = "debug", skip_all, err)]
+pub(crate) async fn update_custom_prompt_handler(
+  data: AFPluginData<UpdateCustomPromptPB>,
+  ai_manager: AFPluginState<Weak<AIManager>>,
+) -> DataResult<CustomPromptPB, FlowyError> {
+  let data = data.try_into_inner()?;
+  let ai_manager = upgrade_ai_manager(ai_manager)?;
+  let prompt = ai_manager
+    .custom_prompt_controller
+    .update_prompt(&data.id, data.name, data.template)
+    .await?;
+  data_result_ok(CustomPromptPB::from(prompt))
+}
+
+#[tracing::instrument(level = "debug", skip_all, err)]
+pub(crate) async fn delete_custom_prompt_handler(
+  data: AFPluginData<CustomPromptIdPB>,
+  ai_manager: AFPluginState<Weak<AIManager>>,
+) -> FlowyResult<()> {
+  let data = data.try_into_inner()?;
+  let ai_manager = upgrade_ai_manager(ai_manager)?;
+  ai_manager
+    .custom_prompt_controller
+    .delete_prompt(&data.id)
+    .await?;
+  Ok(())
+}
+
+#[tracing::instrument(level = "debug", skip_all, err)]
+pub(crate) async fn get_ai_usage_summary_handler(
+  data: AFPluginData<AIUsageQueryPB>,
+  ai_manager: AFPluginState<Weak<AIManager>>,
+) -> DataResult<RepeatedAIUsageSummaryPB, FlowyError> {
+  let data = data.into_inner();
+  let ai_manager = upgrade_ai_manager(ai_manager)?;
+  let end_time = if data.end_time > 0 {
+    data.end_time
+  } else {
+    i64::MAX
+  };
+  let items = ai_manager
+    .get_ai_usage_summary(data.group_by.into(), data.start_time, end_time)?
+    .into_iter()
+    .map(AIUsageSummaryPB::from)
+    .collect();
+  data_result_ok(RepeatedAIUsageSummaryPB { items })
+}
+
+#[tracing::instrument(level = "debug", skip_all, err)]
+pub(crate) async fn get_ai_usage_limit_handler(
+  ai_manager: AFPluginState<Weak<AIManager>>,
+) -> DataResult<AIUsageLimitPB, FlowyError> {
+  let ai_manager = upgrade_ai_manager(ai_manager)?;
+  let limit = ai_manager.get_ai_usage_limit()?;
+  data_result_ok(limit.into())
+}
+
+#[tracing::instrument(level = "debug", skip_all, err)]
+pub(crate) async fn update_ai_usage_limit_handler(
+  data: AFPluginData<AIUsageLimitPB>,
+  ai_manager: AFPluginState<Weak<AIManager>>,
+) -> FlowyResult<()> {
+  let data = data.into_inner();
+  let ai_manager = upgrade_ai_manager(ai_manager)?;
+  ai_manager.update_ai_usage_limit(data.into())?;
+  Ok(())
+}
@@ -12,7 +12,13 @@ use crate::event_handler::*;
 pub fn init(ai_manager: Weak<AIManager>) -> AFPlugin {
   let user_service = Arc::downgrade(&ai_manager.upgrade().unwrap().user_service);
   let cloud_service = Arc::downgrade(&ai_manager.upgrade().unwrap().cloud_service_wm);
-  let ai_tools = Arc::new(AICompletion::new(cloud_service, user_service));
+  let custom_prompt_controller =
+    Arc::downgrade(&ai_manager.upgrade().unwrap().custom_prompt_controller);
+  let ai_tools = Arc::new(AICompletion::new(
+    cloud_service,
+    user_service,
+    custom_prompt_controller,
+  ));
   AFPlugin::new()
     .name("flowy-ai")
     .state(ai_manager)
@@ -57,6 +63,34 @@ pub fn init(ai_manager: Weak<AIManager>) -> AFPlugin {
     .event(AIEvent::GetOfflineAIAppLink, get_offline_app_handler)
     .event(AIEvent::CreateChatContext, create_chat_context_handler)
     .event(AIEvent::GetChatInfo, create_chat_context_handler)
+    .event(
+      AIEvent::GetOpenAICompatibleSetting,
+      get_openai_compatible_setting_handler,
+    )
+    .event(
+      AIEvent::UpdateOpenAICompatibleSetting,
+      update_openai_compatible_setting_handler,
+    )
+    .event(AIEvent::RegenerateAnswer, regenerate_answer_handler)
+    .event(AIEvent::EditQuestion, edit_question_handler)
+    .event(AIEvent::GetMessageBranches, get_message_branches_handler)
+    .event(AIEvent::SwitchMessageBranch, switch_message_branch_handler)
+    .event(
+      AIEvent::ExportChatToMarkdown,
+      export_chat_to_markdown_handler,
+    )
+    .event(
+      AIEvent::ExportChatToDocument,
+      export_chat_to_document_handler,
+    )
+    .event(AIEvent::GetCustomPrompts, get_custom_prompts_handler)
+    .event(AIEvent::CreateCustomPrompt, create_custom_prompt_handler)
+    .event(AIEvent::UpdateCustomPrompt, update_custom_prompt_handler)
+    .event(AIEvent::DeleteCustomPrompt, delete_custom_prompt_handler)
+    .event(AIEvent::GetAIUsageSummary, get_ai_usage_summary_handler)
+    .event(AIEvent::GetAIUsageLimit, get_ai_usage_limit_handler)
+    .event(AIEvent::UpdateAIUsageLimit, update_ai_usage_limit_handler)
+    .event(AIEvent::SearchChatMessages, search_chat_messages_handler)
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Display, Hash, ProtoBuf_Enum, Flowy_Event)]
@@ -142,4 +176,64 @@ pub enum AIEvent {
 
   #[event(input = "ChatId", output = "ChatInfoPB")]
   GetChatInfo = 24,
+
+  /// Return the OpenAI-compatible endpoint of the current workspace
+  #[event(output = "OpenAICompatibleSettingPB")]
+  GetOpenAICompatibleSetting = 25,
+
+  /// Chat and complete text with an OpenAI-compatible endpoint instead of AppFlowy Cloud
+  #[event(input = "OpenAICompatibleSettingPB")]
+  UpdateOpenAICompatibleSetting = 26,
+
+  /// Stream a new answer to a question, the previous answers are kept as branches
+  #[event(input = "RegenerateAnswerPB")]
+  RegenerateAnswer = 27,
+
+  /// Ask an edited question instead of a question, the question is kept as a branch
+  #[event(input = "EditQuestionPB", output = "ChatMessagePB")]
+  EditQuestion = 28,
+
+  #[event(input = "ChatMessageIdPB", output = "ChatMessageBranchesPB")]
+  GetMessageBranches = 29,
+
+  /// Show the message and the messages after it instead of its siblings
+  #[event(input = "ChatMessageIdPB")]
+  SwitchMessageBranch = 30,
+
+  #[event(input = "ExportChatPB", output = "ChatMarkdownPB")]
+  ExportChatToMarkdown = 31,
+
+  /// Create a document with the messages of the chat, return the id of the document
+  #[event(input = "ExportChatToDocumentPB", output = "ExportedChatDocumentPB")]
+  ExportChatToDocument = 32,
+
+  /// Return the custom completion prompts of the current workspace
+  #[event(output = "RepeatedCustomPromptPB")]
+  GetCustomPrompts = 33,
+
+  #[event(input = "CreateCustomPromptPB", output = "CustomPromptPB")]
+  CreateCustomPrompt = 34,
+
+  #[event(input = "UpdateCustomPromptPB", output = "CustomPromptPB")]
+  UpdateCustomPrompt = 35,
+
+  #[event(input = "CustomPromptIdPB")]
+  DeleteCustomPrompt = 36,
+
+  /// Return the AI usage of the current workspace grouped by day, chat or model
+  #[event(input = "AIUsageQueryPB", output = "RepeatedAIUsageSummaryPB")]
+  GetAIUsageSummary = 37,
+
+  #[event(output = "AIUsageLimitPB")]
+  GetAIUsageLimit = 38,
+
+  #[event(input = "AIUsageLimitPB")]
+  UpdateAIUsageLimit = 39,
+
+  /// Search the messages of all the chats in the current workspace
+  #[event(
+    input = "SearchChatMessagesPB",
+    output = "RepeatedChatMessageSearchHitPB"
+  )]
+  SearchChatMessages = 40,
 }
@@ -2,12 +2,16 @@ mod event_handler;
 pub mod event_map;
 
 pub mod ai_manager;
+pub mod ai_usage;
 mod chat;
+pub mod chat_export;
 mod completion;
+mod custom_prompt;
 pub mod entities;
 mod local_ai;
 mod middleware;
 pub mod notification;
+mod openai_compatible;
 mod persistence;
 mod protobuf;
 mod stream_message;
@@ -117,6 +117,7 @@ impl LocalAIController {
     let cloned_llm_chat = this.local_ai.clone();
     let cloned_llm_res = this.local_ai_resource.clone();
     let mut offline_ai_watch = this.local_ai_resource.subscribe_offline_app_state();
+    let mut corrupted_model_rx = this.local_ai_resource.subscribe_corrupted_model();
     tokio::spawn(async move {
       let init_fn = || {
         if let Ok(chat_config) = cloned_llm_res.get_chat_config(rag_enabled) {
@@ -134,6 +135,15 @@ impl LocalAIController {
           _ = rx.recv() => {
               init_fn();
           },
+          Ok(file_name) = corrupted_model_rx.recv() => {
+              info!("[LLM Resource] Download the corrupted model {:?} again", file_name);
+              // The plugin is initialized when the download finishes
+              let progress_sink = futures::sink::drain()
+                .sink_map_err(|err| -> Error { match err {} });
+              if let Err(err) = cloned_llm_res.start_downloading(progress_sink, None).await {
+                error!("[LLM Resource] Failed to download the model again: {:?}", err);
+              }
+          },
           else => { break; }
         }
       }
@@ -307,13 +317,34 @@ impl LocalAIController {
     self.local_ai_resource.get_selected_model()
   }
 
-  pub async fn start_downloading<T>(&self, progress_sink: T) -> FlowyResult<String>
+  /// Returns the embedding of the text, generated by the embedding model of the local AI.
+  pub async fn embed_text(&self, text: &str) -> FlowyResult<Vec<f32>> {
+    if !self.is_running() {
+      return Err(FlowyError::local_ai_unavailable());
+    }
+    let embeddings = self
+      .local_ai
+      .generate_embedding(text)
+      .await
+      .map_err(|err| FlowyError::local_ai().with_context(err))?;
+    embeddings
+      .into_iter()
+      .next()
+      .map(|embedding| embedding.into_iter().map(|value| value as f32).collect())
+      .ok_or_else(|| FlowyError::local_ai().with_context("The embedding is empty"))
+  }
+
+  pub async fn start_downloading<T>(
+    &self,
+    progress_sink: T,
+    max_bytes_per_sec: Option<u64>,
+  ) -> FlowyResult<String>
   where
     T: Sink<String, Error = anyhow::Error> + Unpin + Sync + Send + 'static,
   {
     let task_id = self
       .local_ai_resource
-      .start_downloading(progress_sink)
+      .start_downloading(progress_sink, max_bytes_per_sec)
       .await?;
     Ok(task_id)
   }
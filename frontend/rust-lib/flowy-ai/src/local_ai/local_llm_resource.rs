@@ -1,7 +1,9 @@
 use crate::ai_manager::AIUserService;
 use crate::entities::{LocalModelResourcePB, PendingResourcePB, PendingResourceTypePB};
 use crate::local_ai::local_llm_chat::{LLMModelInfo, LLMSetting};
-use crate::local_ai::model_request::download_model;
+use crate::local_ai::model_request::{
+  download_model, fetch_remote_sha256, file_sha256, DownloadOptions,
+};
 
 use appflowy_local_ai::chat_plugin::AIPluginConfig;
 use flowy_ai_pub::cloud::{LLMModel, LocalAIConfig, ModelInfo};
@@ -11,9 +13,13 @@ use futures_util::SinkExt;
 use lib_infra::async_trait::async_trait;
 
 use arc_swap::ArcSwapOption;
+use dashmap::DashMap;
 use lib_infra::util::{get_operating_system, OperatingSystem};
-use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::UNIX_EPOCH;
 
 use crate::local_ai::watch::offline_app_path;
 #[cfg(target_os = "macos")]
@@ -31,8 +37,125 @@ pub trait LLMResourceService: Send + Sync + 'static {
 }
 
 const LLM_MODEL_DIR: &str = "models";
+const MODEL_MANIFEST_FILE: &str = "manifest.json";
 const DOWNLOAD_FINISH: &str = "finish";
 
+/// Records the size and the SHA-256 of the downloaded models, so a corrupted model file is
+/// detected and downloaded again. Stored in the model dir. The SHA-256 is the one that the server
+/// announces for the model, or the hash of the download if the server doesn't announce one.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ModelManifest {
+  /// The file name of the model to its entry.
+  models: HashMap<String, ModelManifestEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ModelManifestEntry {
+  download_url: String,
+  file_size: u64,
+  sha256: String,
+  /// The modification time of the file, in milliseconds, when its hash was verified. The file
+  /// isn't hashed again while its size and modification time don't change.
+  #[serde(default)]
+  verified_mtime: Option<i64>,
+}
+
+impl ModelManifest {
+  fn read(model_dir: &Path) -> Self {
+    std::fs::read(model_dir.join(MODEL_MANIFEST_FILE))
+      .ok()
+      .and_then(|data| serde_json::from_slice(&data).ok())
+      .unwrap_or_default()
+  }
+
+  fn write(&self, model_dir: &Path) -> Result<(), anyhow::Error> {
+    std::fs::write(
+      model_dir.join(MODEL_MANIFEST_FILE),
+      serde_json::to_vec_pretty(self)?,
+    )?;
+    Ok(())
+  }
+
+  /// The entry of the model if it was downloaded from the url. The entry of a model that was
+  /// downloaded from another url is outdated.
+  fn get(&self, file_name: &str, download_url: &str) -> Option<&ModelManifestEntry> {
+    self
+      .models
+      .get(file_name)
+      .filter(|entry| entry.download_url == download_url)
+  }
+
+  /// Returns false if the size of the model file doesn't match the manifest. The hash of the file
+  /// is verified separately, see [LocalAIResourceController::is_model_verified].
+  fn is_model_file_complete(&self, model: &ModelInfo, path: &Path) -> bool {
+    let Ok(metadata) = std::fs::metadata(path) else {
+      return false;
+    };
+    match self.get(&model.file_name, &model.download_url) {
+      None => true,
+      Some(entry) => metadata.len() == entry.file_size,
+    }
+  }
+
+  /// Returns false if the SHA-256 of the model file doesn't match the `upstream_sha256` that the
+  /// server announces, or the manifest if the server doesn't announce one. The entry of a file that
+  /// doesn't match is dropped, so the hash of the next download is recorded. A model without any
+  /// expected hash, e.g. downloaded by a previous version, can't be verified.
+  async fn verify_model_file(
+    &mut self,
+    model: &ModelInfo,
+    path: &Path,
+    upstream_sha256: Option<String>,
+  ) -> bool {
+    let Ok(metadata) = std::fs::metadata(path) else {
+      return false;
+    };
+    let entry = self.get(&model.file_name, &model.download_url);
+    let Some(expected_sha256) = upstream_sha256.or_else(|| entry.map(|e| e.sha256.clone())) else {
+      return true;
+    };
+    let mtime = modified_millis(&metadata);
+    if let Some(entry) = entry {
+      if mtime.is_some()
+        && entry.verified_mtime == mtime
+        && entry.file_size == metadata.len()
+        && entry.sha256.eq_ignore_ascii_case(&expected_sha256)
+      {
+        return true;
+      }
+    }
+
+    match file_sha256(path).await {
+      Ok(sha256) if sha256.eq_ignore_ascii_case(&expected_sha256) => {
+        self.models.insert(
+          model.file_name.clone(),
+          ModelManifestEntry {
+            download_url: model.download_url.clone(),
+            file_size: metadata.len(),
+            sha256,
+            verified_mtime: mtime,
+          },
+        );
+        true
+      },
+      Ok(_) => {
+        self.models.remove(&model.file_name);
+        false
+      },
+      Err(err) => {
+        warn!("[LLM Resource] Failed to hash model {:?}: {:?}", path, err);
+        false
+      },
+    }
+  }
+}
+
+fn modified_millis(metadata: &std::fs::Metadata) -> Option<i64> {
+  let modified = metadata.modified().ok()?;
+  let duration = modified.duration_since(UNIX_EPOCH).ok()?;
+  Some(duration.as_millis() as i64)
+}
+
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub enum WatchDiskEvent {
@@ -40,6 +163,14 @@ pub enum WatchDiskEvent {
   Remove,
 }
 
+/// The result of verifying a model file with the hash of the manifest. It's cached for the file
+/// path, so a model file is hashed once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ModelFileState {
+  Verifying,
+  Verified,
+}
+
 pub enum PendingResource {
   OfflineApp,
   ModelInfoRes(Vec<ModelInfo>),
@@ -73,6 +204,8 @@ pub struct LocalAIResourceController {
   #[allow(dead_code)]
   offline_app_disk_watch: Option<WatchContext>,
   offline_app_state_sender: tokio::sync::broadcast::Sender<WatchDiskEvent>,
+  model_file_states: Arc<DashMap<PathBuf, ModelFileState>>,
+  corrupted_model_sender: tokio::sync::broadcast::Sender<String>,
 }
 
 impl LocalAIResourceController {
@@ -82,6 +215,7 @@ impl LocalAIResourceController {
     resource_notify: tokio::sync::mpsc::Sender<()>,
   ) -> Self {
     let (offline_app_state_sender, _) = tokio::sync::broadcast::channel(1);
+    let (corrupted_model_sender, _) = tokio::sync::broadcast::channel(2);
     let llm_setting = resource_service.retrieve_setting().map(Arc::new);
     #[cfg(target_os = "macos")]
     let mut offline_app_disk_watch: Option<WatchContext> = None;
@@ -116,6 +250,8 @@ impl LocalAIResourceController {
       #[cfg(target_os = "macos")]
       offline_app_disk_watch,
       offline_app_state_sender,
+      model_file_states: Default::default(),
+      corrupted_model_sender,
     }
   }
 
@@ -124,6 +260,12 @@ impl LocalAIResourceController {
     self.offline_app_state_sender.subscribe()
   }
 
+  /// Receives the file name of a model that doesn't match the hash of the manifest. The model file
+  /// is removed, so it's downloaded again.
+  pub fn subscribe_corrupted_model(&self) -> tokio::sync::broadcast::Receiver<String> {
+    self.corrupted_model_sender.subscribe()
+  }
+
   fn set_llm_setting(&self, llm_setting: LLMSetting) {
     self.llm_setting.store(Some(llm_setting.into()));
   }
@@ -253,20 +395,16 @@ impl LocalAIResourceController {
           resources.push(PendingResource::OfflineApp);
         }
 
-        let chat_model = self.model_path(&llm_setting.llm_model.chat_model.file_name)?;
-        if !chat_model.exists() {
-          resources.push(PendingResource::ModelInfoRes(vec![llm_setting
-            .llm_model
-            .chat_model
-            .clone()]));
-        }
-
-        let embedding_model = self.model_path(&llm_setting.llm_model.embedding_model.file_name)?;
-        if !embedding_model.exists() {
-          resources.push(PendingResource::ModelInfoRes(vec![llm_setting
-            .llm_model
-            .embedding_model
-            .clone()]));
+        let manifest = ModelManifest::read(&self.user_model_folder()?);
+        for model in [
+          &llm_setting.llm_model.chat_model,
+          &llm_setting.llm_model.embedding_model,
+        ] {
+          let path = self.model_path(&model.file_name)?;
+          if !manifest.is_model_file_complete(model, &path) || !self.is_model_verified(model, &path)
+          {
+            resources.push(PendingResource::ModelInfoRes(vec![model.clone()]));
+          }
         }
 
         Ok(resources)
@@ -274,8 +412,55 @@ impl LocalAIResourceController {
     }
   }
 
+  /// Returns true if the model file matches the hash of the manifest. The file is hashed in the
+  /// background when it's first checked, and the model is pending until then. A corrupted model
+  /// file is removed and downloaded again.
+  fn is_model_verified(&self, model: &ModelInfo, path: &Path) -> bool {
+    if let Some(state) = self.model_file_states.get(path) {
+      return *state == ModelFileState::Verified;
+    }
+
+    self
+      .model_file_states
+      .insert(path.to_path_buf(), ModelFileState::Verifying);
+    let model = model.clone();
+    let path = path.to_path_buf();
+    let model_file_states = self.model_file_states.clone();
+    let resource_notify = self.resource_notify.clone();
+    let corrupted_model_sender = self.corrupted_model_sender.clone();
+    tokio::spawn(async move {
+      let Some(model_dir) = path.parent() else {
+        return;
+      };
+      let mut manifest = ModelManifest::read(model_dir);
+      let is_verified = manifest.verify_model_file(&model, &path, None).await;
+      if let Err(err) = manifest.write(model_dir) {
+        error!("[LLM Resource] Failed to write model manifest: {:?}", err);
+      }
+      if is_verified {
+        model_file_states.insert(path, ModelFileState::Verified);
+        let _ = resource_notify.send(()).await;
+      } else {
+        warn!(
+          "[LLM Resource] Model {:?} is corrupted, download it again",
+          model.file_name
+        );
+        let _ = fs::remove_file(&path).await;
+        model_file_states.remove(&path);
+        let _ = corrupted_model_sender.send(model.file_name);
+      }
+    });
+    false
+  }
+
+  /// Downloads the models that are missing or corrupted. The download speed is limited to
+  /// `max_bytes_per_sec` if it's not None.
   #[instrument(level = "info", skip_all, err)]
-  pub async fn start_downloading<T>(&self, mut progress_sink: T) -> FlowyResult<String>
+  pub async fn start_downloading<T>(
+    &self,
+    mut progress_sink: T,
+    max_bytes_per_sec: Option<u64>,
+  ) -> FlowyResult<String>
   where
     T: Sink<String, Error = anyhow::Error> + Unpin + Sync + Send + 'static,
   {
@@ -337,23 +522,34 @@ impl LocalAIResourceController {
       })?;
     }
 
+    let model_file_states = self.model_file_states.clone();
     tokio::spawn(async move {
       // After download the plugin, start downloading models
-      let chat_model_file = (
-        model_dir.join(&llm_setting.llm_model.chat_model.file_name),
-        &llm_setting.llm_model.chat_model.file_name,
-        &llm_setting.llm_model.chat_model.name,
-        &llm_setting.llm_model.chat_model.download_url,
-      );
-      let embedding_model_file = (
-        model_dir.join(&llm_setting.llm_model.embedding_model.file_name),
-        &llm_setting.llm_model.embedding_model.file_name,
-        &llm_setting.llm_model.embedding_model.name,
-        &llm_setting.llm_model.embedding_model.download_url,
-      );
-      for (file_path, file_name, model_name, url) in [chat_model_file, embedding_model_file] {
+      let mut manifest = ModelManifest::read(&model_dir);
+      for model in [
+        &llm_setting.llm_model.chat_model,
+        &llm_setting.llm_model.embedding_model,
+      ] {
+        let (file_name, model_name, url) = (&model.file_name, &model.name, &model.download_url);
+        let file_path = model_dir.join(file_name);
         if file_path.exists() {
-          continue;
+          let upstream_sha256 = fetch_remote_sha256(url).await;
+          let is_verified = manifest
+            .verify_model_file(model, &file_path, upstream_sha256)
+            .await;
+          if let Err(err) = manifest.write(&model_dir) {
+            error!("[LLM Resource] Failed to write model manifest: {:?}", err);
+          }
+          if is_verified {
+            model_file_states.insert(file_path, ModelFileState::Verified);
+            continue;
+          }
+          warn!(
+            "[LLM Resource] Model {:?} is corrupted, download it again",
+            file_name
+          );
+          let _ = fs::remove_file(&file_path).await;
+          model_file_states.remove(&file_path);
         }
 
         info!("[LLM Resource] Downloading model: {:?}", file_name);
@@ -371,16 +567,43 @@ impl LocalAIResourceController {
             warn!("Failed to send progress: {:?}", err);
           }
         });
+        // The download is verified with the SHA-256 that the server announces, not with the hash
+        // of a previous download
+        let options = DownloadOptions {
+          max_bytes_per_sec,
+          ..Default::default()
+        };
         match download_model(
           url,
           &model_dir,
           file_name,
           Some(progress),
           Some(download_task.cancel_token.clone()),
+          options,
         )
         .await
         {
-          Ok(_) => info!("[LLM Resource] Downloaded model: {:?}", file_name),
+          Ok(downloaded) => {
+            info!("[LLM Resource] Downloaded model: {:?}", file_name);
+            // Models without the SHA256 header are verified with the hash of the download
+            let verified_mtime = std::fs::metadata(&downloaded.path)
+              .ok()
+              .and_then(|metadata| modified_millis(&metadata));
+            manifest.models.insert(
+              file_name.clone(),
+              ModelManifestEntry {
+                download_url: url.clone(),
+                file_size: downloaded.file_size,
+                sha256: downloaded.sha256,
+                verified_mtime,
+              },
+            );
+            if let Err(err) = manifest.write(&model_dir) {
+              error!("[LLM Resource] Failed to write model manifest: {:?}", err);
+            }
+            // The download is already hashed
+            model_file_states.insert(downloaded.path, ModelFileState::Verified);
+          },
           Err(err) => {
             error!(
               "[LLM Resource] Failed to download model for given url: {:?}, error: {:?}",
@@ -3,4 +3,5 @@ pub mod local_llm_resource;
 mod model_request;
 
 pub mod stream_util;
+pub mod view_rag;
 pub mod watch;
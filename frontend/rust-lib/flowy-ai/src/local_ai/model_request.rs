@@ -1,19 +1,68 @@
 use anyhow::{anyhow, Result};
 use base64::engine::general_purpose::STANDARD;
 use base64::Engine;
+use futures::future::try_join_all;
+use reqwest::header::{CONTENT_RANGE, RANGE};
 use reqwest::{Client, Response, StatusCode};
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tokio::fs::{self, File};
-use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+use tokio::fs::{self, File, OpenOptions};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt, SeekFrom};
 
 use tokio_util::sync::CancellationToken;
-use tracing::{instrument, trace};
+use tracing::{instrument, trace, warn};
 
 type ProgressCallback = Arc<dyn Fn(u64, u64) + Send + Sync>;
 
+/// The number of ranges of a model that are downloaded at the same time.
+const DEFAULT_PARALLEL_CHUNKS: usize = 4;
+/// A file is only split into ranges that are at least this large.
+const MIN_CHUNK_SIZE: u64 = 8 * 1024 * 1024;
+/// The number of times a range is requested again after the connection failed.
+const MAX_CHUNK_RETRIES: u64 = 5;
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+/// The max time to wait for the response headers or the next bytes of the body. A stalled
+/// connection fails and the range is requested again.
+const READ_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone)]
+pub struct DownloadOptions {
+  pub parallel_chunks: usize,
+  /// The max download speed of all the ranges together. Unlimited if None.
+  pub max_bytes_per_sec: Option<u64>,
+  /// The hex encoded SHA-256 of the file. The `SHA256` header of the response is used if None.
+  pub expected_sha256: Option<String>,
+}
+
+impl Default for DownloadOptions {
+  fn default() -> Self {
+    Self {
+      parallel_chunks: DEFAULT_PARALLEL_CHUNKS,
+      max_bytes_per_sec: None,
+      expected_sha256: None,
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct DownloadedModel {
+  pub path: PathBuf,
+  pub file_size: u64,
+  /// The hex encoded SHA-256 of the file.
+  pub sha256: String,
+}
+
+/// Downloads the model to `model_path/model_filename`.
+///
+/// The file is written to a `.part` file next to a `.part.json` file that records the downloaded
+/// ranges, so an interrupted download resumes where it stopped if the server supports range
+/// requests. The file is verified with the expected SHA-256 before it's renamed, and a resumed
+/// file that doesn't match is downloaded again from the start.
 #[instrument(level = "trace", skip_all, err)]
 pub async fn download_model(
   url: &str,
@@ -21,104 +70,531 @@ pub async fn download_model(
   model_filename: &str,
   progress_callback: Option<ProgressCallback>,
   cancel_token: Option<CancellationToken>,
-) -> Result<PathBuf, anyhow::Error> {
-  let client = Client::new();
-  let mut response = make_request(&client, url, None).await?;
-  let total_size_in_bytes = response.content_length().unwrap_or(0);
+  options: DownloadOptions,
+) -> Result<DownloadedModel, anyhow::Error> {
+  let client = http_client()?;
   let partial_path = model_path.join(format!("{}.part", model_filename));
+  let state_path = model_path.join(format!("{}.part.json", model_filename));
   let download_path = model_path.join(model_filename);
-  let mut part_file = File::create(&partial_path).await?;
-  let mut downloaded: u64 = 0;
-
-  let debounce_duration = Duration::from_millis(100);
-  let mut last_update = Instant::now()
-    .checked_sub(debounce_duration)
-    .unwrap_or(Instant::now());
-
-  while let Some(chunk) = response.chunk().await? {
-    if let Some(cancel_token) = &cancel_token {
-      if cancel_token.is_cancelled() {
-        trace!("Download canceled by client");
-        fs::remove_file(&partial_path).await?;
-        return Err(anyhow!("Download canceled"));
-      }
-    }
 
-    part_file.write_all(&chunk).await?;
-    downloaded += chunk.len() as u64;
+  let remote = probe_remote_file(&client, url).await?;
+  let mut resumed = load_partial_download(&state_path, &partial_path, url, &remote).await;
+  loop {
+    let is_resumed = resumed.is_some();
+    let partial = match resumed.take() {
+      Some(partial) => {
+        trace!(
+          "Resume download of {} from {} bytes",
+          model_filename,
+          partial.downloaded()
+        );
+        partial
+      },
+      None => create_partial_download(&partial_path, url, &remote, &options).await?,
+    };
+
+    let result = fetch_partial_download(
+      &client,
+      &partial_path,
+      &state_path,
+      partial,
+      &progress_callback,
+      cancel_token.as_ref(),
+      &options,
+    )
+    .await;
+    let partial = match result {
+      Ok(partial) => partial,
+      Err(err) => {
+        if cancel_token
+          .as_ref()
+          .is_some_and(|token| token.is_cancelled())
+        {
+          trace!("Download canceled by client");
+          remove_partial_download(&partial_path, &state_path).await;
+        }
+        return Err(err);
+      },
+    };
 
-    if let Some(progress_callback) = &progress_callback {
-      let now = Instant::now();
-      if now.duration_since(last_update) >= debounce_duration {
-        progress_callback(downloaded, total_size_in_bytes);
-        last_update = now;
+    // Verify file integrity
+    let file_size = fs::metadata(&partial_path).await?.len();
+    let calculated_sha256 = file_sha256(&partial_path).await?;
+    let expected_sha256 = options.expected_sha256.clone().or(partial.sha256);
+    if let Some(expected_sha256) = expected_sha256 {
+      if !calculated_sha256.eq_ignore_ascii_case(&expected_sha256) {
+        trace!(
+          "Expected Sha256: {}, calculated Sha256:{}",
+          expected_sha256,
+          calculated_sha256
+        );
+        remove_partial_download(&partial_path, &state_path).await;
+        if is_resumed {
+          // The part file might be corrupted by the previous download
+          warn!(
+            "Resumed download of {} is corrupted, restart it",
+            model_filename
+          );
+          continue;
+        }
+        return Err(anyhow!(
+          "Sha256 mismatch: expected {}, got {}",
+          expected_sha256,
+          calculated_sha256
+        ));
       }
     }
-  }
 
-  // Verify file integrity
-  let header_sha256 = response
-    .headers()
-    .get("SHA256")
-    .and_then(|value| value.to_str().ok())
-    .and_then(|value| STANDARD.decode(value).ok());
+    let _ = fs::remove_file(&state_path).await;
+    fs::rename(&partial_path, &download_path).await?;
+    return Ok(DownloadedModel {
+      path: download_path,
+      file_size,
+      sha256: calculated_sha256,
+    });
+  }
+}
 
-  part_file.seek(tokio::io::SeekFrom::Start(0)).await?;
+/// Returns the hex encoded SHA-256 of the file.
+pub async fn file_sha256(path: &Path) -> Result<String, anyhow::Error> {
+  let mut file = File::open(path).await?;
   let mut hasher = Sha256::new();
   let block_size = 2_usize.pow(20); // 1 MB
   let mut buffer = vec![0; block_size];
-  while let Ok(bytes_read) = part_file.read(&mut buffer).await {
+  loop {
+    let bytes_read = file.read(&mut buffer).await?;
     if bytes_read == 0 {
       break;
     }
     hasher.update(&buffer[..bytes_read]);
   }
-  let calculated_sha256 = hasher.finalize();
-  if let Some(header_sha256) = header_sha256 {
-    if calculated_sha256.as_slice() != header_sha256.as_slice() {
-      trace!(
-        "Header Sha256: {:?}, calculated Sha256:{:?}",
-        header_sha256,
-        calculated_sha256
-      );
-
-      fs::remove_file(&partial_path).await?;
-      return Err(anyhow!(
-        "Sha256 mismatch: expected {:?}, got {:?}",
-        header_sha256,
-        calculated_sha256
-      ));
+  Ok(to_hex(hasher.finalize().as_slice()))
+}
+
+/// Returns the hex encoded SHA-256 that the server announces for the file in the `SHA256` header,
+/// None if the server doesn't announce it or can't be reached.
+pub async fn fetch_remote_sha256(url: &str) -> Option<String> {
+  let remote = match http_client() {
+    Ok(client) => probe_remote_file(&client, url).await,
+    Err(err) => Err(err),
+  };
+  match remote {
+    Ok(remote) => remote.sha256,
+    Err(err) => {
+      warn!("Failed to fetch the SHA-256 of {}: {:?}", url, err);
+      None
+    },
+  }
+}
+
+/// The whole download isn't limited by a timeout, because a model might take hours to download.
+/// The reads are limited by `READ_TIMEOUT` instead.
+fn http_client() -> Result<Client, anyhow::Error> {
+  let client = Client::builder().connect_timeout(CONNECT_TIMEOUT).build()?;
+  Ok(client)
+}
+
+struct RemoteFile {
+  total_size: Option<u64>,
+  accept_ranges: bool,
+  sha256: Option<String>,
+}
+
+/// Requests the first byte of the file to find out its size and whether the server supports range
+/// requests.
+async fn probe_remote_file(client: &Client, url: &str) -> Result<RemoteFile, anyhow::Error> {
+  let response = make_request(client, url, Some((0, 0))).await?;
+  let sha256 = response_sha256(&response);
+  if response.status() == StatusCode::PARTIAL_CONTENT {
+    let total_size = response
+      .headers()
+      .get(CONTENT_RANGE)
+      .and_then(|value| value.to_str().ok())
+      .and_then(|value| value.rsplit_once('/'))
+      .and_then(|(_, total)| total.parse::<u64>().ok());
+    Ok(RemoteFile {
+      total_size,
+      accept_ranges: total_size.is_some(),
+      sha256,
+    })
+  } else {
+    Ok(RemoteFile {
+      total_size: response.content_length(),
+      accept_ranges: false,
+      sha256,
+    })
+  }
+}
+
+/// The state of a download that is saved next to the part file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PartialDownload {
+  url: String,
+  total_size: u64,
+  /// False if the server doesn't support range requests, the download then can't be resumed.
+  resumable: bool,
+  sha256: Option<String>,
+  chunks: Vec<ChunkState>,
+}
+
+impl PartialDownload {
+  fn downloaded(&self) -> u64 {
+    self.chunks.iter().map(|chunk| chunk.downloaded).sum()
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct ChunkState {
+  start: u64,
+  /// Exclusive. `u64::MAX` if the size of the file is unknown.
+  end: u64,
+  downloaded: u64,
+}
+
+impl ChunkState {
+  fn offset(&self) -> u64 {
+    self.start + self.downloaded
+  }
+
+  fn remaining(&self) -> u64 {
+    self.end.saturating_sub(self.offset())
+  }
+}
+
+async fn load_partial_download(
+  state_path: &Path,
+  partial_path: &Path,
+  url: &str,
+  remote: &RemoteFile,
+) -> Option<PartialDownload> {
+  let data = fs::read(state_path).await.ok()?;
+  let partial = serde_json::from_slice::<PartialDownload>(&data).ok()?;
+  let partial_size = fs::metadata(partial_path).await.ok()?.len();
+  let is_same_file = partial.url == url
+    && remote.accept_ranges
+    && remote.total_size == Some(partial.total_size)
+    && partial_size == partial.total_size
+    && (remote.sha256.is_none() || remote.sha256 == partial.sha256);
+  (partial.resumable && is_same_file).then_some(partial)
+}
+
+async fn create_partial_download(
+  partial_path: &Path,
+  url: &str,
+  remote: &RemoteFile,
+  options: &DownloadOptions,
+) -> Result<PartialDownload, anyhow::Error> {
+  let file = File::create(partial_path).await?;
+  let partial = match (remote.accept_ranges, remote.total_size) {
+    (true, Some(total_size)) => {
+      file.set_len(total_size).await?;
+      PartialDownload {
+        url: url.to_string(),
+        total_size,
+        resumable: true,
+        sha256: remote.sha256.clone(),
+        chunks: split_into_chunks(total_size, options.parallel_chunks),
+      }
+    },
+    _ => PartialDownload {
+      url: url.to_string(),
+      total_size: remote.total_size.unwrap_or(0),
+      resumable: false,
+      sha256: remote.sha256.clone(),
+      chunks: vec![ChunkState {
+        start: 0,
+        end: remote.total_size.unwrap_or(u64::MAX),
+        downloaded: 0,
+      }],
+    },
+  };
+  Ok(partial)
+}
+
+async fn remove_partial_download(partial_path: &Path, state_path: &Path) {
+  let _ = fs::remove_file(partial_path).await;
+  let _ = fs::remove_file(state_path).await;
+}
+
+fn split_into_chunks(total_size: u64, parallel_chunks: usize) -> Vec<ChunkState> {
+  let count = (total_size / MIN_CHUNK_SIZE).clamp(1, parallel_chunks.max(1) as u64);
+  let chunk_size = total_size.div_ceil(count).max(1);
+  (0..count)
+    .map(|index| index * chunk_size)
+    .take_while(|start| *start < total_size)
+    .map(|start| ChunkState {
+      start,
+      end: (start + chunk_size).min(total_size),
+      downloaded: 0,
+    })
+    .collect()
+}
+
+/// Downloads the remaining ranges at the same time. The progress is reported and the state is
+/// saved periodically, so the download can be resumed if it fails.
+async fn fetch_partial_download(
+  client: &Client,
+  partial_path: &Path,
+  state_path: &Path,
+  mut partial: PartialDownload,
+  progress_callback: &Option<ProgressCallback>,
+  cancel_token: Option<&CancellationToken>,
+  options: &DownloadOptions,
+) -> Result<PartialDownload, anyhow::Error> {
+  let counters = partial
+    .chunks
+    .iter()
+    .map(|chunk| AtomicU64::new(chunk.downloaded))
+    .collect::<Vec<_>>();
+  let limiter = options
+    .max_bytes_per_sec
+    .filter(|max_bytes_per_sec| *max_bytes_per_sec > 0)
+    .map(BandwidthLimiter::new);
+  let fetch_chunks = partial
+    .chunks
+    .iter()
+    .zip(&counters)
+    .map(|(chunk, downloaded)| {
+      fetch_chunk(
+        client,
+        &partial.url,
+        partial_path,
+        chunk,
+        partial.resumable,
+        downloaded,
+        limiter.as_ref(),
+        cancel_token,
+      )
+    });
+  let mut fetch_chunks = Box::pin(try_join_all(fetch_chunks));
+
+  let snapshot = |partial: &PartialDownload| {
+    let mut partial = partial.clone();
+    for (chunk, downloaded) in partial.chunks.iter_mut().zip(&counters) {
+      chunk.downloaded = downloaded.load(Ordering::SeqCst);
+    }
+    partial
+  };
+  let report_progress = |partial: &PartialDownload| {
+    if let Some(progress_callback) = progress_callback {
+      progress_callback(partial.downloaded(), partial.total_size);
+    }
+  };
+
+  let mut interval = tokio::time::interval(PROGRESS_INTERVAL);
+  let result = loop {
+    tokio::select! {
+      result = &mut fetch_chunks => break result,
+      _ = interval.tick() => {
+        let current = snapshot(&partial);
+        report_progress(&current);
+        if current.resumable {
+          save_partial_download(state_path, &current).await;
+        }
+      },
+    }
+  };
+  drop(fetch_chunks);
+
+  partial = snapshot(&partial);
+  if partial.resumable {
+    save_partial_download(state_path, &partial).await;
+  }
+  result?;
+  report_progress(&partial);
+  Ok(partial)
+}
+
+async fn save_partial_download(state_path: &Path, partial: &PartialDownload) {
+  match serde_json::to_vec(partial) {
+    Ok(data) => {
+      if let Err(err) = fs::write(state_path, data).await {
+        warn!("Failed to save the download state: {:?}", err);
+      }
+    },
+    Err(err) => warn!("Failed to serialize the download state: {:?}", err),
+  }
+}
+
+/// Downloads the range of the chunk. The range is requested again from where it stopped if the
+/// connection fails.
+#[allow(clippy::too_many_arguments)]
+async fn fetch_chunk(
+  client: &Client,
+  url: &str,
+  partial_path: &Path,
+  chunk: &ChunkState,
+  use_range: bool,
+  downloaded: &AtomicU64,
+  limiter: Option<&BandwidthLimiter>,
+  cancel_token: Option<&CancellationToken>,
+) -> Result<(), anyhow::Error> {
+  let mut retries = 0;
+  loop {
+    let result = fetch_chunk_once(
+      client,
+      url,
+      partial_path,
+      chunk,
+      use_range,
+      downloaded,
+      limiter,
+      cancel_token,
+    )
+    .await;
+    match result {
+      Ok(()) => return Ok(()),
+      Err(err) => {
+        let is_canceled = cancel_token.is_some_and(|token| token.is_cancelled());
+        if is_canceled || retries >= MAX_CHUNK_RETRIES {
+          return Err(err);
+        }
+        retries += 1;
+        warn!(
+          "Download of range {}-{} failed, retry {}/{}: {:?}",
+          chunk.start, chunk.end, retries, MAX_CHUNK_RETRIES, err
+        );
+        if !use_range {
+          downloaded.store(0, Ordering::SeqCst);
+        }
+        tokio::time::sleep(Duration::from_secs(retries)).await;
+      },
+    }
+  }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn fetch_chunk_once(
+  client: &Client,
+  url: &str,
+  partial_path: &Path,
+  chunk: &ChunkState,
+  use_range: bool,
+  downloaded: &AtomicU64,
+  limiter: Option<&BandwidthLimiter>,
+  cancel_token: Option<&CancellationToken>,
+) -> Result<(), anyhow::Error> {
+  let current = |downloaded: &AtomicU64| ChunkState {
+    downloaded: downloaded.load(Ordering::SeqCst),
+    ..chunk.clone()
+  };
+  let state = current(downloaded);
+  if state.remaining() == 0 {
+    return Ok(());
+  }
+
+  let range = use_range.then(|| (state.offset(), chunk.end - 1));
+  let mut response = make_request(client, url, range).await?;
+  if use_range && response.status() != StatusCode::PARTIAL_CONTENT {
+    return Err(anyhow!("The server doesn't support range requests"));
+  }
+
+  let mut file = OpenOptions::new().write(true).open(partial_path).await?;
+  file.seek(SeekFrom::Start(state.offset())).await?;
+  while let Some(bytes) = tokio::time::timeout(READ_TIMEOUT, response.chunk())
+    .await
+    .map_err(|_| anyhow!("Timed out reading range {}-{}", chunk.start, chunk.end))??
+  {
+    if cancel_token.is_some_and(|token| token.is_cancelled()) {
+      return Err(anyhow!("Download canceled"));
+    }
+
+    // Ignore the bytes after the range if the server sends more than requested
+    let remaining = current(downloaded).remaining();
+    let len = (bytes.len() as u64).min(remaining) as usize;
+    file.write_all(&bytes[..len]).await?;
+    downloaded.fetch_add(len as u64, Ordering::SeqCst);
+    if let Some(limiter) = limiter {
+      limiter.consume(len as u64).await;
+    }
+    if current(downloaded).remaining() == 0 {
+      break;
+    }
+  }
+  file.flush().await?;
+
+  let state = current(downloaded);
+  if use_range && state.remaining() > 0 {
+    return Err(anyhow!(
+      "Connection closed at byte {} of range {}-{}",
+      state.offset(),
+      chunk.start,
+      chunk.end
+    ));
+  }
+  if !use_range {
+    // The previous attempt might have written more bytes
+    file.set_len(state.offset()).await?;
+  }
+  Ok(())
+}
+
+/// Limits the download speed of all the ranges of a file together.
+struct BandwidthLimiter {
+  max_bytes_per_sec: u64,
+  started_at: Instant,
+  consumed: AtomicU64,
+}
+
+impl BandwidthLimiter {
+  fn new(max_bytes_per_sec: u64) -> Self {
+    Self {
+      max_bytes_per_sec,
+      started_at: Instant::now(),
+      consumed: AtomicU64::new(0),
     }
   }
 
-  fs::rename(&partial_path, &download_path).await?;
-  Ok(download_path)
+  /// Waits until the downloaded bytes are within the limit.
+  async fn consume(&self, bytes: u64) {
+    let consumed = self.consumed.fetch_add(bytes, Ordering::SeqCst) + bytes;
+    let expected = Duration::from_secs_f64(consumed as f64 / self.max_bytes_per_sec as f64);
+    let elapsed = self.started_at.elapsed();
+    if expected > elapsed {
+      tokio::time::sleep(expected - elapsed).await;
+    }
+  }
 }
 
 async fn make_request(
   client: &Client,
   url: &str,
-  offset: Option<u64>,
+  range: Option<(u64, u64)>,
 ) -> Result<Response, anyhow::Error> {
   let mut request = client.get(url);
-  if let Some(offset) = offset {
-    println!(
-      "\nDownload interrupted, resuming from byte position {}",
-      offset
-    );
-    request = request.header("Range", format!("bytes={}-", offset));
+  if let Some((start, end)) = range {
+    trace!("Request bytes {}-{} of {}", start, end, url);
+    request = request.header(RANGE, format!("bytes={}-{}", start, end));
   }
-  let response = request.send().await?;
+  let response = tokio::time::timeout(READ_TIMEOUT, request.send())
+    .await
+    .map_err(|_| anyhow!("Timed out waiting for the response of {}", url))??;
   if !(response.status().is_success() || response.status() == StatusCode::PARTIAL_CONTENT) {
     return Err(anyhow!(response.text().await?));
   }
   Ok(response)
 }
 
+/// The base64 encoded `SHA256` header of the response, as hex.
+fn response_sha256(response: &Response) -> Option<String> {
+  response
+    .headers()
+    .get("SHA256")
+    .and_then(|value| value.to_str().ok())
+    .and_then(|value| STANDARD.decode(value).ok())
+    .map(|value| to_hex(&value))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+  bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
 #[cfg(test)]
 mod test {
   use super::*;
   use std::env::temp_dir;
+  use tokio::net::TcpListener;
+
   #[tokio::test]
   async fn retrieve_gpt4all_model_test() {
     for url in [
@@ -146,14 +622,206 @@ mod test {
           println!("{}/{}", a, b);
         })),
         Some(cancel_token),
+        DownloadOptions::default(),
       ).await.unwrap();
 
       let file_path = temp_dir.join(file_name);
-      assert_eq!(download_file, file_path);
+      assert_eq!(download_file.path, file_path);
 
       println!("File path: {:?}", file_path);
       assert!(file_path.exists());
       std::fs::remove_file(file_path).unwrap();
     }
   }
+
+  /// Serves the content with range requests support, and records the requested ranges.
+  async fn range_server(content: Vec<u8>) -> (String, Arc<std::sync::Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/model.gguf", listener.local_addr().unwrap());
+    let ranges = Arc::new(std::sync::Mutex::new(vec![]));
+    let cloned_ranges = ranges.clone();
+    tokio::spawn(async move {
+      loop {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let content = content.clone();
+        let ranges = cloned_ranges.clone();
+        tokio::spawn(async move {
+          let mut request = vec![];
+          let mut buf = [0; 1024];
+          while !String::from_utf8_lossy(&request).contains("\r\n\r\n") {
+            let n = socket.read(&mut buf).await.unwrap();
+            if n == 0 {
+              return;
+            }
+            request.extend_from_slice(&buf[..n]);
+          }
+          let request = String::from_utf8_lossy(&request).to_string();
+          let range = request.lines().find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            name
+              .eq_ignore_ascii_case("range")
+              .then(|| value.trim().trim_start_matches("bytes=").to_string())
+          });
+          let (status, start, end) = match &range {
+            Some(range) => {
+              ranges.lock().unwrap().push(range.clone());
+              let (start, end) = range.split_once('-').unwrap();
+              let start = start.parse::<usize>().unwrap();
+              let end = end.parse::<usize>().unwrap().min(content.len() - 1);
+              ("206 Partial Content", start, end + 1)
+            },
+            None => ("200 OK", 0, content.len()),
+          };
+          let body = &content[start..end];
+          let header = format!(
+            "HTTP/1.1 {}\r\ncontent-length: {}\r\ncontent-range: bytes {}-{}/{}\r\nconnection: close\r\n\r\n",
+            status,
+            body.len(),
+            start,
+            end - 1,
+            content.len()
+          );
+          let _ = socket.write_all(header.as_bytes()).await;
+          let _ = socket.write_all(body).await;
+          let _ = socket.shutdown().await;
+        });
+      }
+    });
+    (url, ranges)
+  }
+
+  fn test_content(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i % 251) as u8).collect()
+  }
+
+  fn test_dir(name: &str) -> PathBuf {
+    let dir = temp_dir().join(format!("download_llm_{}_{}", name, uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+  }
+
+  #[tokio::test]
+  async fn download_in_parallel_chunks_test() {
+    let content = test_content(3 * MIN_CHUNK_SIZE as usize + 10);
+    let sha256 = to_hex(Sha256::digest(&content).as_slice());
+    let (url, ranges) = range_server(content.clone()).await;
+    let dir = test_dir("parallel");
+
+    let model = download_model(
+      &url,
+      &dir,
+      "model.gguf",
+      None,
+      None,
+      DownloadOptions {
+        expected_sha256: Some(sha256.clone()),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+    assert_eq!(model.sha256, sha256);
+    assert_eq!(model.file_size, content.len() as u64);
+    assert_eq!(std::fs::read(&model.path).unwrap(), content);
+    assert!(!dir.join("model.gguf.part").exists());
+    assert!(!dir.join("model.gguf.part.json").exists());
+    // The probe request and one request for each chunk
+    assert_eq!(ranges.lock().unwrap().len(), 4);
+  }
+
+  #[tokio::test]
+  async fn resume_partial_download_test() {
+    let content = test_content(1000);
+    let (url, ranges) = range_server(content.clone()).await;
+    let dir = test_dir("resume");
+
+    // The first 600 bytes were downloaded before the download was interrupted
+    let mut part = content[..600].to_vec();
+    part.resize(content.len(), 0);
+    std::fs::write(dir.join("model.gguf.part"), part).unwrap();
+    let partial = PartialDownload {
+      url: url.clone(),
+      total_size: content.len() as u64,
+      resumable: true,
+      sha256: None,
+      chunks: vec![ChunkState {
+        start: 0,
+        end: content.len() as u64,
+        downloaded: 600,
+      }],
+    };
+    std::fs::write(
+      dir.join("model.gguf.part.json"),
+      serde_json::to_vec(&partial).unwrap(),
+    )
+    .unwrap();
+
+    let model = download_model(&url, &dir, "model.gguf", None, None, Default::default())
+      .await
+      .unwrap();
+    assert_eq!(std::fs::read(&model.path).unwrap(), content);
+    assert_eq!(*ranges.lock().unwrap(), vec!["0-0", "600-999"]);
+  }
+
+  #[tokio::test]
+  async fn refetch_corrupted_partial_download_test() {
+    let content = test_content(1000);
+    let sha256 = to_hex(Sha256::digest(&content).as_slice());
+    let (url, ranges) = range_server(content.clone()).await;
+    let dir = test_dir("corrupted");
+
+    // The downloaded bytes are corrupted
+    std::fs::write(dir.join("model.gguf.part"), vec![0; content.len()]).unwrap();
+    let partial = PartialDownload {
+      url: url.clone(),
+      total_size: content.len() as u64,
+      resumable: true,
+      sha256: None,
+      chunks: vec![ChunkState {
+        start: 0,
+        end: content.len() as u64,
+        downloaded: 600,
+      }],
+    };
+    std::fs::write(
+      dir.join("model.gguf.part.json"),
+      serde_json::to_vec(&partial).unwrap(),
+    )
+    .unwrap();
+
+    let model = download_model(
+      &url,
+      &dir,
+      "model.gguf",
+      None,
+      None,
+      DownloadOptions {
+        expected_sha256: Some(sha256),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+    assert_eq!(std::fs::read(&model.path).unwrap(), content);
+    assert_eq!(*ranges.lock().unwrap(), vec!["0-0", "600-999", "0-999"]);
+  }
+
+  #[test]
+  fn split_into_chunks_test() {
+    assert_eq!(
+      split_into_chunks(10, 4),
+      vec![ChunkState {
+        start: 0,
+        end: 10,
+        downloaded: 0
+      }]
+    );
+
+    let total_size = 10 * MIN_CHUNK_SIZE + 3;
+    let chunks = split_into_chunks(total_size, 4);
+    assert_eq!(chunks.len(), 4);
+    assert_eq!(chunks[0].start, 0);
+    assert_eq!(chunks[3].end, total_size);
+    assert!(chunks.windows(2).all(|pair| pair[0].end == pair[1].start));
+  }
 }
@@ -0,0 +1,351 @@
+use crate::ai_manager::{AIUserService, AIViewContentService, ViewContent};
+use crate::local_ai::local_llm_chat::LocalAIController;
+use crate::persistence::{
+  delete_view_embeddings, replace_view_embeddings, select_view_embeddings, ViewEmbeddingTable,
+};
+use flowy_error::FlowyResult;
+use lib_infra::util::timestamp;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use tracing::{trace, warn};
+
+/// The max number of characters of a chunk. A longer block is split into several chunks.
+const MAX_CHUNK_CHARS: usize = 800;
+
+/// The max number of passages that are added to a question.
+pub const VIEW_PASSAGE_TOP_K: usize = 5;
+
+/// The passages that are less similar to the question than this are ignored.
+const MIN_PASSAGE_SCORE: f32 = 0.3;
+
+/// A chunk of a view that is relevant to a question.
+#[derive(Debug, Clone)]
+pub struct ViewPassage {
+  pub view_id: String,
+  pub view_name: String,
+  /// The id of the block of a document, or the id of the row of a database.
+  pub block_id: String,
+  pub content: String,
+  pub score: f32,
+}
+
+/// Retrieves the passages of the views of a chat that are the most relevant to a question. The
+/// views are chunked and embedded with the local AI model, and the embeddings are stored on the
+/// local disk, so only the chunks that changed are embedded again.
+pub struct ViewRetriever {
+  user_service: Arc<dyn AIUserService>,
+  local_ai_controller: Arc<LocalAIController>,
+  content_service: RwLock<Option<Arc<dyn AIViewContentService>>>,
+  /// The hash of the content of each view when it was indexed, the views whose content didn't
+  /// change since then are not indexed again.
+  indexed_hashes: RwLock<HashMap<String, String>>,
+}
+
+impl ViewRetriever {
+  pub fn new(
+    user_service: Arc<dyn AIUserService>,
+    local_ai_controller: Arc<LocalAIController>,
+  ) -> Self {
+    Self {
+      user_service,
+      local_ai_controller,
+      content_service: RwLock::new(None),
+      indexed_hashes: RwLock::new(HashMap::new()),
+    }
+  }
+
+  pub fn set_content_service(&self, content_service: Arc<dyn AIViewContentService>) {
+    if let Ok(mut service) = self.content_service.write() {
+      *service = Some(content_service);
+    }
+  }
+
+  /// Indexes the changed chunks of the views, then returns the `top_k` passages that are the
+  /// most similar to the question.
+  pub async fn retrieve(
+    &self,
+    view_ids: &[String],
+    question: &str,
+    top_k: usize,
+  ) -> FlowyResult<Vec<ViewPassage>> {
+    let content_service = self
+      .content_service
+      .read()
+      .ok()
+      .and_then(|service| service.clone());
+    let (Some(content_service), Some(model)) = (
+      content_service,
+      self.local_ai_controller.get_current_model(),
+    ) else {
+      return Ok(vec![]);
+    };
+    if view_ids.is_empty() {
+      return Ok(vec![]);
+    }
+
+    let embedding_model = model.embedding_model.name;
+    let mut view_names = HashMap::new();
+    for view_id in view_ids {
+      match content_service.query_view_content(view_id).await {
+        Ok(Some(content)) => {
+          view_names.insert(view_id.clone(), content.name.clone());
+          let content_hash = view_content_hash(&content, &embedding_model);
+          let is_indexed = self
+            .indexed_hashes
+            .read()
+            .map(|hashes| hashes.get(view_id) == Some(&content_hash))
+            .unwrap_or(false);
+          if is_indexed {
+            continue;
+          }
+          match self.index_view(view_id, content, &embedding_model).await {
+            Ok(_) => {
+              if let Ok(mut hashes) = self.indexed_hashes.write() {
+                hashes.insert(view_id.clone(), content_hash);
+              }
+            },
+            Err(err) => warn!("[AI RAG] failed to index view {}: {}", view_id, err),
+          }
+        },
+        Ok(None) => {},
+        Err(err) => warn!("[AI RAG] failed to read view {}: {}", view_id, err),
+      }
+    }
+
+    let question_embedding = self.local_ai_controller.embed_text(question).await?;
+    let uid = self.user_service.user_id()?;
+    let rows = select_view_embeddings(
+      self.user_service.sqlite_connection(uid)?,
+      view_ids,
+      &embedding_model,
+    )?;
+    let mut passages = rows
+      .into_iter()
+      .filter_map(|row| {
+        let score = cosine_similarity(&question_embedding, &embedding_from_bytes(&row.embedding));
+        (score >= MIN_PASSAGE_SCORE).then(|| ViewPassage {
+          view_name: view_names.get(&row.view_id).cloned().unwrap_or_default(),
+          view_id: row.view_id,
+          block_id: row.block_id,
+          content: row.content,
+          score,
+        })
+      })
+      .collect::<Vec<_>>();
+    passages.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
+    passages.truncate(top_k);
+    trace!("[AI RAG] retrieved {} passages", passages.len());
+    Ok(passages)
+  }
+
+  /// Removes the chunks of the views, they are not retrieved anymore.
+  pub fn remove_views(&self, view_ids: &[String]) -> FlowyResult<()> {
+    if let Ok(mut hashes) = self.indexed_hashes.write() {
+      for view_id in view_ids {
+        hashes.remove(view_id);
+      }
+    }
+    let uid = self.user_service.user_id()?;
+    let count = delete_view_embeddings(self.user_service.sqlite_connection(uid)?, view_ids)?;
+    trace!("[AI RAG] removed {} chunks of views {:?}", count, view_ids);
+    Ok(())
+  }
+
+  async fn index_view(
+    &self,
+    view_id: &str,
+    content: ViewContent,
+    embedding_model: &str,
+  ) -> FlowyResult<()> {
+    let uid = self.user_service.user_id()?;
+    let mut existing = select_view_embeddings(
+      self.user_service.sqlite_connection(uid)?,
+      &[view_id.to_string()],
+      embedding_model,
+    )?
+    .into_iter()
+    .map(|row| ((row.block_id.clone(), row.chunk_index), row))
+    .collect::<HashMap<_, _>>();
+
+    let mut rows = vec![];
+    let mut is_changed = false;
+    for block in content.blocks {
+      for (chunk_index, chunk) in split_into_chunks(&block.text).into_iter().enumerate() {
+        let chunk_index = chunk_index as i32;
+        let content_hash = format!("{:x}", Sha256::digest(chunk.as_bytes()));
+        match existing.remove(&(block.block_id.clone(), chunk_index)) {
+          Some(row) if row.content_hash == content_hash => rows.push(row),
+          _ => {
+            is_changed = true;
+            let embedding = self.local_ai_controller.embed_text(&chunk).await?;
+            rows.push(ViewEmbeddingTable {
+              view_id: view_id.to_string(),
+              block_id: block.block_id.clone(),
+              chunk_index,
+              content: chunk,
+              content_hash,
+              embedding_model: embedding_model.to_string(),
+              embedding: embedding_to_bytes(&embedding),
+              updated_at: timestamp(),
+            });
+          },
+        }
+      }
+    }
+
+    // The remaining chunks were removed from the view
+    if is_changed || !existing.is_empty() {
+      trace!("[AI RAG] index view {}: {} chunks", view_id, rows.len());
+      replace_view_embeddings(self.user_service.sqlite_connection(uid)?, view_id, &rows)?;
+    }
+    Ok(())
+  }
+}
+
+/// The hash of the blocks of the view and the model that embeds them.
+fn view_content_hash(content: &ViewContent, embedding_model: &str) -> String {
+  let mut hasher = Sha256::new();
+  hasher.update(embedding_model.as_bytes());
+  for block in &content.blocks {
+    hasher.update(block.block_id.as_bytes());
+    hasher.update([0]);
+    hasher.update(block.text.as_bytes());
+    hasher.update([0]);
+  }
+  format!("{:x}", hasher.finalize())
+}
+
+/// Adds the passages to the question, so the model answers with the content of the views. The
+/// passages are numbered, so the answer can refer to them.
+pub fn question_with_passages(question: &str, passages: &[ViewPassage]) -> String {
+  if passages.is_empty() {
+    return question.to_string();
+  }
+
+  let context = passages
+    .iter()
+    .enumerate()
+    .map(|(index, passage)| format!("[{}] {}\n{}", index + 1, passage.view_name, passage.content))
+    .collect::<Vec<_>>()
+    .join("\n\n");
+  format!(
+    "Answer the question with the following passages of the workspace. Refer to a passage by its number, e.g. [1].\n\n{}\n\nQuestion: {}",
+    context, question
+  )
+}
+
+/// The sources of the answer, saved in the metadata of the answer. The numbers of the passages in
+/// the answer are the positions of the sources.
+pub fn passage_citations(passages: &[ViewPassage]) -> Value {
+  Value::Array(
+    passages
+      .iter()
+      .map(|passage| {
+        json!({
+          "id": passage.view_id,
+          "name": passage.view_name,
+          "source": "appflowy",
+          "block_id": passage.block_id,
+        })
+      })
+      .collect(),
+  )
+}
+
+/// Splits the text into chunks of at most [MAX_CHUNK_CHARS] characters, at whitespace if possible.
+fn split_into_chunks(text: &str) -> Vec<String> {
+  let text = text.trim();
+  let mut chunks = vec![];
+  let mut chunk = String::new();
+  let mut chunk_chars = 0;
+  for word in text.split_inclusive(char::is_whitespace) {
+    let word_chars = word.chars().count();
+    if chunk_chars + word_chars > MAX_CHUNK_CHARS && !chunk.is_empty() {
+      chunks.push(chunk.trim().to_string());
+      chunk.clear();
+      chunk_chars = 0;
+    }
+
+    // A word without whitespace that is longer than a chunk, e.g. CJK text, is split anywhere
+    let mut chars = word.chars().peekable();
+    while chars.peek().is_some() {
+      let take = (MAX_CHUNK_CHARS - chunk_chars).max(1);
+      let part = chars.by_ref().take(take).collect::<String>();
+      chunk_chars += part.chars().count();
+      chunk.push_str(&part);
+      if chunk_chars >= MAX_CHUNK_CHARS {
+        chunks.push(chunk.trim().to_string());
+        chunk.clear();
+        chunk_chars = 0;
+      }
+    }
+  }
+  if !chunk.trim().is_empty() {
+    chunks.push(chunk.trim().to_string());
+  }
+  chunks.retain(|chunk| !chunk.is_empty());
+  chunks
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+  if a.len() != b.len() || a.is_empty() {
+    return 0.0;
+  }
+  let (mut dot, mut norm_a, mut norm_b) = (0.0, 0.0, 0.0);
+  for (x, y) in a.iter().zip(b) {
+    dot += x * y;
+    norm_a += x * x;
+    norm_b += y * y;
+  }
+  if norm_a == 0.0 || norm_b == 0.0 {
+    return 0.0;
+  }
+  dot / (norm_a.sqrt() * norm_b.sqrt())
+}
+
+fn embedding_to_bytes(embedding: &[f32]) -> Vec<u8> {
+  embedding.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn embedding_from_bytes(bytes: &[u8]) -> Vec<f32> {
+  bytes
+    .chunks_exact(4)
+    .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    .collect()
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn split_into_chunks_test() {
+    assert!(split_into_chunks("  ").is_empty());
+    assert_eq!(split_into_chunks("hello world"), vec!["hello world"]);
+
+    let text = "word ".repeat(400);
+    let chunks = split_into_chunks(&text);
+    assert_eq!(chunks.len(), 3);
+    assert!(chunks
+      .iter()
+      .all(|chunk| chunk.chars().count() <= MAX_CHUNK_CHARS));
+    assert_eq!(chunks.join(" "), text.trim());
+
+    let text = "文".repeat(MAX_CHUNK_CHARS + 10);
+    let chunks = split_into_chunks(&text);
+    assert_eq!(chunks.len(), 2);
+    assert_eq!(chunks[1].chars().count(), 10);
+  }
+
+  #[test]
+  fn embedding_bytes_test() {
+    let embedding = vec![0.5, -1.25, 3.0];
+    let bytes = embedding_to_bytes(&embedding);
+    assert_eq!(embedding_from_bytes(&bytes), embedding);
+    assert!((cosine_similarity(&embedding, &embedding) - 1.0).abs() < 1e-6);
+    assert_eq!(cosine_similarity(&embedding, &[1.0]), 0.0);
+  }
+}
@@ -1,17 +1,27 @@
-use crate::ai_manager::AIUserService;
+use crate::ai_manager::{AIUserService, AIViewContentService};
+use crate::ai_usage::{
+  record_stream, AIUsageController, AIUsageProvider, AIUsageRecorder, AIUsageType,
+  APPFLOWY_CLOUD_MODEL,
+};
 use crate::entities::{ChatStatePB, ModelTypePB};
 use crate::local_ai::local_llm_chat::LocalAIController;
+use crate::local_ai::view_rag::{
+  passage_citations, question_with_passages, ViewPassage, ViewRetriever, VIEW_PASSAGE_TOP_K,
+};
 use crate::notification::{
   chat_notification_builder, ChatNotification, APPFLOWY_AI_NOTIFICATION_KEY,
 };
-use crate::persistence::{select_single_message, ChatMessageTable};
+use crate::openai_compatible::chat_service::OpenAICompatibleChatService;
+use crate::persistence::{read_chat_metadata, select_single_message, ChatMessageTable};
 use appflowy_plugin::error::PluginError;
+use bytes::Bytes;
 use std::collections::HashMap;
 
 use flowy_ai_pub::cloud::{
   ChatCloudService, ChatMessage, ChatMessageMetadata, ChatMessageType, ChatSettings,
-  CompletionType, LocalAIConfig, MessageCursor, RelatedQuestion, RepeatedChatMessage,
-  RepeatedRelatedQuestion, StreamAnswer, StreamComplete, SubscriptionPlan, UpdateChatParams,
+  CompletionType, LocalAIConfig, MessageCursor, QuestionStreamValue, RelatedQuestion,
+  RepeatedChatMessage, RepeatedRelatedQuestion, StreamAnswer, StreamComplete, SubscriptionPlan,
+  UpdateChatParams,
 };
 use flowy_error::{FlowyError, FlowyResult};
 use futures::{stream, Sink, StreamExt, TryStreamExt};
@@ -24,12 +34,15 @@ use futures_util::SinkExt;
 use serde_json::{json, Value};
 use std::path::Path;
 use std::sync::{Arc, Weak};
-use tracing::trace;
+use tracing::{error, trace};
 
 pub struct AICloudServiceMiddleware {
   cloud_service: Arc<dyn ChatCloudService>,
   user_service: Arc<dyn AIUserService>,
   local_llm_controller: Arc<LocalAIController>,
+  openai_compatible_service: Arc<OpenAICompatibleChatService>,
+  view_retriever: Arc<ViewRetriever>,
+  usage_controller: Arc<AIUsageController>,
   storage_service: Weak<dyn StorageService>,
 }
 
@@ -38,16 +51,77 @@ impl AICloudServiceMiddleware {
     user_service: Arc<dyn AIUserService>,
     cloud_service: Arc<dyn ChatCloudService>,
     local_llm_controller: Arc<LocalAIController>,
+    openai_compatible_service: Arc<OpenAICompatibleChatService>,
+    view_retriever: Arc<ViewRetriever>,
+    usage_controller: Arc<AIUsageController>,
     storage_service: Weak<dyn StorageService>,
   ) -> Self {
     Self {
       user_service,
       cloud_service,
       local_llm_controller,
+      openai_compatible_service,
+      view_retriever,
+      usage_controller,
       storage_service,
     }
   }
 
+  pub fn set_view_content_service(&self, content_service: Arc<dyn AIViewContentService>) {
+    self.view_retriever.set_content_service(content_service);
+  }
+
+  /// Returns the OpenAI-compatible service if an endpoint is configured for the workspace. The
+  /// configured endpoint takes precedence over the local AI plugin and AppFlowy Cloud.
+  fn openai_compatible_service(&self, workspace_id: &str) -> Option<&OpenAICompatibleChatService> {
+    if self.openai_compatible_service.is_enabled(workspace_id) {
+      Some(&self.openai_compatible_service)
+    } else {
+      None
+    }
+  }
+
+  /// Returns [FlowyError::ai_usage_limit_exceeded] if the workspace reached its AI usage limit.
+  /// A chat checks the limit before it creates the question.
+  pub fn check_usage_limit(&self, workspace_id: &str) -> FlowyResult<()> {
+    self.usage_controller.check_limit(workspace_id)
+  }
+
+  /// Returns an error if the workspace reached its AI usage limit, otherwise starts recording the
+  /// usage of the request with the provider that answers it.
+  fn start_usage(
+    &self,
+    workspace_id: &str,
+    usage_type: AIUsageType,
+    chat_id: &str,
+    input: &str,
+  ) -> FlowyResult<AIUsageRecorder> {
+    self.usage_controller.check_limit(workspace_id)?;
+    let (provider, model) = if let Some(service) = self.openai_compatible_service(workspace_id) {
+      (
+        AIUsageProvider::OpenAICompatible,
+        service.get_setting(workspace_id).model,
+      )
+    } else if self.local_llm_controller.is_running() {
+      let model = self
+        .local_llm_controller
+        .get_current_model()
+        .map(|model| model.chat_model.name)
+        .unwrap_or_default();
+      (AIUsageProvider::LocalAI, model)
+    } else {
+      (
+        AIUsageProvider::AppFlowyCloud,
+        APPFLOWY_CLOUD_MODEL.to_string(),
+      )
+    };
+    Ok(
+      self
+        .usage_controller
+        .start(workspace_id, usage_type, provider, model, chat_id, input),
+    )
+  }
+
   pub fn is_local_ai_enabled(&self) -> bool {
     self.local_llm_controller.is_enabled()
   }
@@ -89,6 +163,55 @@ impl AICloudServiceMiddleware {
     Ok(row)
   }
 
+  /// Returns the passages of the views of the chat that are relevant to the question. The
+  /// question is answered without the passages if they can't be retrieved.
+  async fn retrieve_view_passages(&self, chat_id: &str, question: &str) -> Vec<ViewPassage> {
+    let rag_ids = match self.get_chat_rag_ids(chat_id) {
+      Ok(rag_ids) => rag_ids,
+      Err(err) => {
+        error!(
+          "[AI RAG] failed to read rag_ids of chat {}: {}",
+          chat_id, err
+        );
+        return vec![];
+      },
+    };
+    self
+      .view_retriever
+      .retrieve(&rag_ids, question, VIEW_PASSAGE_TOP_K)
+      .await
+      .unwrap_or_else(|err| {
+        error!("[AI RAG] failed to retrieve passages: {}", err);
+        vec![]
+      })
+  }
+
+  async fn stream_local_prompt(
+    &self,
+    chat_id: &str,
+    question: &str,
+  ) -> Result<StreamComplete, PluginError> {
+    self.local_llm_controller.create_chat(chat_id).await?;
+    let stream = self
+      .local_llm_controller
+      .stream_question(chat_id, question, json!([]))
+      .await?;
+    let answer = QuestionStream::new(stream).filter_map(|value| async move {
+      match value {
+        Ok(QuestionStreamValue::Answer { value }) => Some(Ok(Bytes::from(value))),
+        Ok(_) => None,
+        Err(err) => Some(Err(err)),
+      }
+    });
+    Ok(answer.boxed())
+  }
+
+  fn get_chat_rag_ids(&self, chat_id: &str) -> FlowyResult<Vec<String>> {
+    let uid = self.user_service.user_id()?;
+    let mut conn = self.user_service.sqlite_connection(uid)?;
+    Ok(read_chat_metadata(&mut conn, chat_id)?.rag_ids)
+  }
+
   fn handle_plugin_error(&self, err: PluginError) {
     if matches!(
       err,
@@ -116,6 +239,11 @@ impl ChatCloudService for AICloudServiceMiddleware {
     chat_id: &str,
     rag_ids: Vec<String>,
   ) -> Result<(), FlowyError> {
+    if let Some(service) = self.openai_compatible_service(workspace_id) {
+      return service
+        .create_chat(uid, workspace_id, chat_id, rag_ids)
+        .await;
+    }
     self
       .cloud_service
       .create_chat(uid, workspace_id, chat_id, rag_ids)
@@ -130,6 +258,11 @@ impl ChatCloudService for AICloudServiceMiddleware {
     message_type: ChatMessageType,
     metadata: &[ChatMessageMetadata],
   ) -> Result<ChatMessage, FlowyError> {
+    if let Some(service) = self.openai_compatible_service(workspace_id) {
+      return service
+        .create_question(workspace_id, chat_id, message, message_type, metadata)
+        .await;
+    }
     self
       .cloud_service
       .create_question(workspace_id, chat_id, message, message_type, metadata)
@@ -144,6 +277,11 @@ impl ChatCloudService for AICloudServiceMiddleware {
     question_id: i64,
     metadata: Option<serde_json::Value>,
   ) -> Result<ChatMessage, FlowyError> {
+    if let Some(service) = self.openai_compatible_service(workspace_id) {
+      return service
+        .create_answer(workspace_id, chat_id, message, question_id, metadata)
+        .await;
+    }
     self
       .cloud_service
       .create_answer(workspace_id, chat_id, message, question_id, metadata)
@@ -156,14 +294,36 @@ impl ChatCloudService for AICloudServiceMiddleware {
     chat_id: &str,
     question_id: i64,
   ) -> Result<StreamAnswer, FlowyError> {
-    if self.local_llm_controller.is_running() {
+    let question = self
+      .get_message_record(question_id)
+      .map(|row| row.content)
+      .unwrap_or_default();
+    let recorder = self.start_usage(workspace_id, AIUsageType::ChatMessage, chat_id, &question)?;
+    let result = if let Some(service) = self.openai_compatible_service(workspace_id) {
+      service
+        .stream_answer(workspace_id, chat_id, question_id)
+        .await
+    } else if self.local_llm_controller.is_running() {
       let row = self.get_message_record(question_id)?;
+      let passages = self.retrieve_view_passages(chat_id, &row.content).await;
+      let question = question_with_passages(&row.content, &passages);
       match self
         .local_llm_controller
-        .stream_question(chat_id, &row.content, json!([]))
+        .stream_question(chat_id, &question, json!([]))
         .await
       {
-        Ok(stream) => Ok(QuestionStream::new(stream).boxed()),
+        Ok(stream) => {
+          let answer = QuestionStream::new(stream);
+          if passages.is_empty() {
+            Ok(answer.boxed())
+          } else {
+            // The citations are sent first, so they are saved as the metadata of the answer
+            let citations = QuestionStreamValue::Metadata {
+              value: passage_citations(&passages),
+            };
+            Ok(stream::once(async { Ok(citations) }).chain(answer).boxed())
+          }
+        },
         Err(err) => {
           self.handle_plugin_error(err);
           Ok(stream::once(async { Err(FlowyError::local_ai_unavailable()) }).boxed())
@@ -174,7 +334,11 @@ impl ChatCloudService for AICloudServiceMiddleware {
         .cloud_service
         .stream_answer(workspace_id, chat_id, question_id)
         .await
-    }
+    };
+    record_stream(recorder, result, |value| match value {
+      QuestionStreamValue::Answer { value } => Some(value.clone()),
+      _ => None,
+    })
   }
 
   async fn get_answer(
@@ -183,18 +347,35 @@ impl ChatCloudService for AICloudServiceMiddleware {
     chat_id: &str,
     question_message_id: i64,
   ) -> Result<ChatMessage, FlowyError> {
-    if self.local_llm_controller.is_running() {
+    let question = self
+      .get_message_record(question_message_id)
+      .map(|row| row.content)
+      .unwrap_or_default();
+    let recorder = self.start_usage(workspace_id, AIUsageType::ChatMessage, chat_id, &question)?;
+    let result = if let Some(service) = self.openai_compatible_service(workspace_id) {
+      service
+        .get_answer(workspace_id, chat_id, question_message_id)
+        .await
+    } else if self.local_llm_controller.is_running() {
       let content = self.get_message_record(question_message_id)?.content;
+      let passages = self.retrieve_view_passages(chat_id, &content).await;
+      let question = question_with_passages(&content, &passages);
       match self
         .local_llm_controller
-        .ask_question(chat_id, &content)
+        .ask_question(chat_id, &question)
         .await
       {
         Ok(answer) => {
-          // TODO(nathan): metadata
+          let metadata = (!passages.is_empty()).then(|| passage_citations(&passages));
           let message = self
             .cloud_service
-            .create_answer(workspace_id, chat_id, &answer, question_message_id, None)
+            .create_answer(
+              workspace_id,
+              chat_id,
+              &answer,
+              question_message_id,
+              metadata,
+            )
             .await?;
           Ok(message)
         },
@@ -208,7 +389,9 @@ impl ChatCloudService for AICloudServiceMiddleware {
         .cloud_service
         .get_answer(workspace_id, chat_id, question_message_id)
         .await
-    }
+    };
+    recorder.finish(&result, |answer| answer.content.clone());
+    result
   }
 
   async fn get_chat_messages(
@@ -218,6 +401,11 @@ impl ChatCloudService for AICloudServiceMiddleware {
     offset: MessageCursor,
     limit: u64,
   ) -> Result<RepeatedChatMessage, FlowyError> {
+    if let Some(service) = self.openai_compatible_service(workspace_id) {
+      return service
+        .get_chat_messages(workspace_id, chat_id, offset, limit)
+        .await;
+    }
     self
       .cloud_service
       .get_chat_messages(workspace_id, chat_id, offset, limit)
@@ -230,7 +418,11 @@ impl ChatCloudService for AICloudServiceMiddleware {
     chat_id: &str,
     message_id: i64,
   ) -> Result<RepeatedRelatedQuestion, FlowyError> {
-    if self.local_llm_controller.is_running() {
+    if let Some(service) = self.openai_compatible_service(workspace_id) {
+      service
+        .get_related_message(workspace_id, chat_id, message_id)
+        .await
+    } else if self.local_llm_controller.is_running() {
       let questions = self
         .local_llm_controller
         .get_related_question(chat_id)
@@ -261,7 +453,12 @@ impl ChatCloudService for AICloudServiceMiddleware {
     text: &str,
     complete_type: CompletionType,
   ) -> Result<StreamComplete, FlowyError> {
-    if self.local_llm_controller.is_running() {
+    let recorder = self.start_usage(workspace_id, AIUsageType::Completion, "", text)?;
+    let result = if let Some(service) = self.openai_compatible_service(workspace_id) {
+      service
+        .stream_complete(workspace_id, text, complete_type)
+        .await
+    } else if self.local_llm_controller.is_running() {
       match self
         .local_llm_controller
         .complete_text(text, complete_type as u8)
@@ -282,7 +479,50 @@ impl ChatCloudService for AICloudServiceMiddleware {
         .cloud_service
         .stream_complete(workspace_id, text, complete_type)
         .await
-    }
+    };
+    record_stream(recorder, result, completion_text)
+  }
+
+  async fn stream_complete_with_prompt(
+    &self,
+    workspace_id: &str,
+    text: &str,
+    prompt: &str,
+  ) -> Result<StreamComplete, FlowyError> {
+    let input = format!("{}\n\n{}", prompt, text);
+    let recorder = self.start_usage(workspace_id, AIUsageType::Completion, "", &input)?;
+    let result = if let Some(service) = self.openai_compatible_service(workspace_id) {
+      service
+        .stream_complete_with_prompt(workspace_id, text, prompt)
+        .await
+    } else if self.local_llm_controller.is_running() {
+      // The plugin only completes with the built-in prompts, so the prompt is asked in a chat of
+      // its own. The chat is closed when the answer is finished or dropped.
+      let chat_guard = LocalChatGuard {
+        controller: Arc::downgrade(&self.local_llm_controller),
+        chat_id: uuid::Uuid::new_v4().to_string(),
+      };
+      match self.stream_local_prompt(&chat_guard.chat_id, &input).await {
+        Ok(answer) => Ok(
+          answer
+            .map(move |value| {
+              let _ = &chat_guard;
+              value
+            })
+            .boxed(),
+        ),
+        Err(err) => {
+          self.handle_plugin_error(err);
+          Ok(stream::once(async { Err(FlowyError::local_ai_unavailable()) }).boxed())
+        },
+      }
+    } else {
+      self
+        .cloud_service
+        .stream_complete_with_prompt(workspace_id, text, prompt)
+        .await
+    };
+    record_stream(recorder, result, completion_text)
   }
 
   async fn index_file(
@@ -292,7 +532,11 @@ impl ChatCloudService for AICloudServiceMiddleware {
     chat_id: &str,
     metadata: Option<HashMap<String, Value>>,
   ) -> Result<(), FlowyError> {
-    if self.local_llm_controller.is_running() {
+    if let Some(service) = self.openai_compatible_service(workspace_id) {
+      service
+        .index_file(workspace_id, file_path, chat_id, metadata)
+        .await
+    } else if self.local_llm_controller.is_running() {
       self
         .local_llm_controller
         .index_file(chat_id, Some(file_path.to_path_buf()), None, metadata)
@@ -341,3 +585,22 @@ impl ChatCloudService for AICloudServiceMiddleware {
       .await
   }
 }
+
+fn completion_text(value: &Bytes) -> Option<String> {
+  Some(String::from_utf8_lossy(value).to_string())
+}
+
+/// Closes the chat of the local AI when it's dropped, i.e. when the answer is finished or the
+/// stream is dropped before.
+struct LocalChatGuard {
+  controller: Weak<LocalAIController>,
+  chat_id: String,
+}
+
+impl Drop for LocalChatGuard {
+  fn drop(&mut self) {
+    if let Some(controller) = self.controller.upgrade() {
+      controller.close_chat(&self.chat_id);
+    }
+  }
+}
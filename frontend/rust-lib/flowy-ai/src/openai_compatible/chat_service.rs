@@ -0,0 +1,314 @@
+use crate::ai_manager::AIUserService;
+use crate::openai_compatible::client::{OpenAIChatMessage, OpenAICompatibleClient};
+use crate::openai_compatible::setting::{OpenAICompatibleSetting, OpenAICompatibleSettingStore};
+use crate::persistence::select_chat_messages;
+use bytes::Bytes;
+use flowy_ai_pub::cloud::{
+  ChatAuthor, ChatAuthorType, ChatCloudService, ChatMessage, ChatMessageMetadata, ChatMessageType,
+  ChatSettings, CompletionType, LocalAIConfig, MessageCursor, QuestionStreamValue,
+  RepeatedChatMessage, RepeatedRelatedQuestion, StreamAnswer, StreamComplete, SubscriptionPlan,
+  UpdateChatParams,
+};
+use flowy_error::{FlowyError, FlowyResult};
+use flowy_sqlite::kv::KVStorePreferences;
+use futures::{StreamExt, TryStreamExt};
+use lib_infra::async_trait::async_trait;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::trace;
+
+/// The max number of previous messages of a chat that are sent with a question.
+const CHAT_HISTORY_LIMIT: i64 = 20;
+
+/// A [ChatCloudService] that answers with the OpenAI-compatible endpoint that is configured for
+/// the workspace. The messages only exist on the local disk, so the ids of the messages are
+/// generated locally.
+pub struct OpenAICompatibleChatService {
+  user_service: Arc<dyn AIUserService>,
+  settings: OpenAICompatibleSettingStore,
+  last_message_id: AtomicI64,
+}
+
+impl OpenAICompatibleChatService {
+  pub fn new(
+    user_service: Arc<dyn AIUserService>,
+    store_preferences: Arc<KVStorePreferences>,
+  ) -> Self {
+    Self {
+      user_service,
+      settings: OpenAICompatibleSettingStore::new(store_preferences),
+      last_message_id: AtomicI64::new(0),
+    }
+  }
+
+  pub fn is_enabled(&self, workspace_id: &str) -> bool {
+    self.settings.get(workspace_id).is_enabled()
+  }
+
+  pub fn get_setting(&self, workspace_id: &str) -> OpenAICompatibleSetting {
+    self.settings.get(workspace_id)
+  }
+
+  pub fn update_setting(
+    &self,
+    workspace_id: &str,
+    setting: &OpenAICompatibleSetting,
+  ) -> FlowyResult<()> {
+    self.settings.set(workspace_id, setting)
+  }
+
+  fn client(&self, workspace_id: &str) -> FlowyResult<OpenAICompatibleClient> {
+    let setting = self.settings.get(workspace_id);
+    if !setting.is_enabled() {
+      return Err(
+        FlowyError::local_ai_unavailable()
+          .with_context("The OpenAI-compatible endpoint is not configured"),
+      );
+    }
+    Ok(OpenAICompatibleClient::new(&setting))
+  }
+
+  /// Returns an id that is greater than the ids of the previous messages, so the messages are
+  /// ordered by their ids like the messages of AppFlowy Cloud.
+  fn next_message_id(&self) -> i64 {
+    let now = SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .map(|duration| duration.as_micros() as i64)
+      .unwrap_or_default();
+    let prev = self
+      .last_message_id
+      .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |last| {
+        Some(now.max(last + 1))
+      })
+      .unwrap_or_default();
+    now.max(prev + 1)
+  }
+
+  /// Returns the previous messages of the chat and the question, in the order they were sent.
+  fn chat_history(&self, chat_id: &str, question_id: i64) -> FlowyResult<Vec<OpenAIChatMessage>> {
+    let uid = self.user_service.user_id()?;
+    let conn = self.user_service.sqlite_connection(uid)?;
+    let rows = select_chat_messages(
+      conn,
+      chat_id,
+      CHAT_HISTORY_LIMIT,
+      None,
+      Some(question_id + 1),
+    )?;
+    if rows.first().map(|row| row.message_id) != Some(question_id) {
+      return Err(
+        FlowyError::record_not_found().with_context(format!("Message not found: {}", question_id)),
+      );
+    }
+
+    let messages = rows
+      .into_iter()
+      .rev()
+      .map(|row| {
+        if row.author_type == ChatAuthorType::AI as i64 {
+          OpenAIChatMessage::assistant(row.content)
+        } else {
+          OpenAIChatMessage::user(row.content)
+        }
+      })
+      .collect();
+    Ok(messages)
+  }
+}
+
+#[async_trait]
+impl ChatCloudService for OpenAICompatibleChatService {
+  async fn create_chat(
+    &self,
+    _uid: &i64,
+    _workspace_id: &str,
+    chat_id: &str,
+    _rag_ids: Vec<String>,
+  ) -> Result<(), FlowyError> {
+    trace!("[OpenAI Compatible] create chat: {}", chat_id);
+    Ok(())
+  }
+
+  async fn create_question(
+    &self,
+    _workspace_id: &str,
+    _chat_id: &str,
+    message: &str,
+    _message_type: ChatMessageType,
+    _metadata: &[ChatMessageMetadata],
+  ) -> Result<ChatMessage, FlowyError> {
+    let uid = self.user_service.user_id()?;
+    Ok(ChatMessage::new(
+      ChatAuthor::new(uid, ChatAuthorType::Human),
+      self.next_message_id(),
+      message.to_string(),
+      Value::Null,
+    ))
+  }
+
+  async fn create_answer(
+    &self,
+    _workspace_id: &str,
+    _chat_id: &str,
+    message: &str,
+    question_id: i64,
+    metadata: Option<serde_json::Value>,
+  ) -> Result<ChatMessage, FlowyError> {
+    let mut answer = ChatMessage::new(
+      ChatAuthor::ai(),
+      self.next_message_id(),
+      message.to_string(),
+      metadata.unwrap_or_default(),
+    );
+    answer.reply_message_id = Some(question_id);
+    Ok(answer)
+  }
+
+  async fn stream_answer(
+    &self,
+    workspace_id: &str,
+    chat_id: &str,
+    message_id: i64,
+  ) -> Result<StreamAnswer, FlowyError> {
+    let client = self.client(workspace_id)?;
+    let messages = self.chat_history(chat_id, message_id)?;
+    let stream = client.stream_chat_completion(messages).await?;
+    Ok(
+      stream
+        .map_ok(|value| QuestionStreamValue::Answer { value })
+        .boxed(),
+    )
+  }
+
+  async fn get_answer(
+    &self,
+    workspace_id: &str,
+    chat_id: &str,
+    question_message_id: i64,
+  ) -> Result<ChatMessage, FlowyError> {
+    let client = self.client(workspace_id)?;
+    let messages = self.chat_history(chat_id, question_message_id)?;
+    let content = client.chat_completion(messages).await?;
+    self
+      .create_answer(workspace_id, chat_id, &content, question_message_id, None)
+      .await
+  }
+
+  async fn get_chat_messages(
+    &self,
+    _workspace_id: &str,
+    _chat_id: &str,
+    _offset: MessageCursor,
+    _limit: u64,
+  ) -> Result<RepeatedChatMessage, FlowyError> {
+    // All the messages are already on the local disk
+    Ok(RepeatedChatMessage {
+      messages: vec![],
+      has_more: false,
+      total: 0,
+    })
+  }
+
+  async fn get_related_message(
+    &self,
+    _workspace_id: &str,
+    _chat_id: &str,
+    message_id: i64,
+  ) -> Result<RepeatedRelatedQuestion, FlowyError> {
+    Ok(RepeatedRelatedQuestion {
+      message_id,
+      items: vec![],
+    })
+  }
+
+  async fn stream_complete(
+    &self,
+    workspace_id: &str,
+    text: &str,
+    complete_type: CompletionType,
+  ) -> Result<StreamComplete, FlowyError> {
+    let client = self.client(workspace_id)?;
+    let messages = vec![
+      OpenAIChatMessage::system(completion_prompt(complete_type)),
+      OpenAIChatMessage::user(text),
+    ];
+    let stream = client.stream_chat_completion(messages).await?;
+    Ok(stream.map_ok(Bytes::from).boxed())
+  }
+
+  async fn stream_complete_with_prompt(
+    &self,
+    workspace_id: &str,
+    text: &str,
+    prompt: &str,
+  ) -> Result<StreamComplete, FlowyError> {
+    let client = self.client(workspace_id)?;
+    let messages = vec![
+      OpenAIChatMessage::system(prompt),
+      OpenAIChatMessage::user(text),
+    ];
+    let stream = client.stream_chat_completion(messages).await?;
+    Ok(stream.map_ok(Bytes::from).boxed())
+  }
+
+  async fn index_file(
+    &self,
+    _workspace_id: &str,
+    _file_path: &Path,
+    _chat_id: &str,
+    _metadata: Option<HashMap<String, Value>>,
+  ) -> Result<(), FlowyError> {
+    Err(
+      FlowyError::not_support()
+        .with_context("Chat with file is not supported by the OpenAI-compatible endpoint"),
+    )
+  }
+
+  async fn get_local_ai_config(&self, _workspace_id: &str) -> Result<LocalAIConfig, FlowyError> {
+    Err(FlowyError::not_support())
+  }
+
+  async fn get_workspace_plan(
+    &self,
+    _workspace_id: &str,
+  ) -> Result<Vec<SubscriptionPlan>, FlowyError> {
+    Err(FlowyError::not_support())
+  }
+
+  async fn get_chat_settings(
+    &self,
+    _workspace_id: &str,
+    _chat_id: &str,
+  ) -> Result<ChatSettings, FlowyError> {
+    Err(FlowyError::not_support())
+  }
+
+  async fn update_chat_settings(
+    &self,
+    _workspace_id: &str,
+    _chat_id: &str,
+    _params: UpdateChatParams,
+  ) -> Result<(), FlowyError> {
+    Err(FlowyError::not_support())
+  }
+}
+
+fn completion_prompt(complete_type: CompletionType) -> &'static str {
+  match complete_type {
+    CompletionType::ImproveWriting => {
+      "Improve the writing of the text. Only reply with the improved text."
+    },
+    CompletionType::SpellingAndGrammar => {
+      "Fix the spelling and the grammar of the text. Only reply with the corrected text."
+    },
+    CompletionType::MakeShorter => "Make the text shorter. Only reply with the shorter text.",
+    CompletionType::MakeLonger => "Make the text longer. Only reply with the longer text.",
+    CompletionType::ContinueWriting => {
+      "Continue writing after the text. Only reply with the text that follows it."
+    },
+  }
+}
@@ -0,0 +1,371 @@
+use std::collections::VecDeque;
+
+use flowy_error::{FlowyError, FlowyResult};
+use futures::stream::{self, BoxStream};
+use futures::StreamExt;
+use reqwest::header::{AUTHORIZATION, CONTENT_TYPE};
+use reqwest::{Client, Response};
+use serde::Serialize;
+use serde_json::{json, Value};
+use tracing::trace;
+
+use crate::openai_compatible::setting::OpenAICompatibleSetting;
+
+pub type CompletionStream = BoxStream<'static, FlowyResult<String>>;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OpenAIChatMessage {
+  pub role: &'static str,
+  pub content: String,
+}
+
+impl OpenAIChatMessage {
+  pub fn system(content: impl Into<String>) -> Self {
+    Self {
+      role: "system",
+      content: content.into(),
+    }
+  }
+
+  pub fn user(content: impl Into<String>) -> Self {
+    Self {
+      role: "user",
+      content: content.into(),
+    }
+  }
+
+  pub fn assistant(content: impl Into<String>) -> Self {
+    Self {
+      role: "assistant",
+      content: content.into(),
+    }
+  }
+}
+
+/// A client of the `/v1/chat/completions` endpoint of an OpenAI-compatible server, e.g. Ollama or
+/// the llama.cpp server.
+pub struct OpenAICompatibleClient {
+  http: Client,
+  endpoint: String,
+  model: String,
+  api_key: String,
+}
+
+impl OpenAICompatibleClient {
+  pub fn new(setting: &OpenAICompatibleSetting) -> Self {
+    Self {
+      http: Client::new(),
+      endpoint: setting.chat_completions_url(),
+      model: setting.model.clone(),
+      api_key: setting.api_key.clone(),
+    }
+  }
+
+  /// Streams the content of the answer, the chunks are sent as soon as the server sends them.
+  pub async fn stream_chat_completion(
+    &self,
+    messages: Vec<OpenAIChatMessage>,
+  ) -> FlowyResult<CompletionStream> {
+    let response = self.send(messages, true).await?;
+    let state = StreamState {
+      response,
+      decoder: ChatCompletionStreamDecoder::default(),
+      pending: VecDeque::new(),
+    };
+
+    let stream = stream::unfold(state, |mut state| async move {
+      loop {
+        if let Some(content) = state.pending.pop_front() {
+          return Some((Ok(content), state));
+        }
+        if state.decoder.is_finished() {
+          return None;
+        }
+
+        match state.response.chunk().await {
+          Ok(Some(chunk)) => match state.decoder.decode(&chunk) {
+            Ok(contents) => state.pending.extend(contents),
+            Err(err) => {
+              state.decoder.finish();
+              return Some((Err(err), state));
+            },
+          },
+          Ok(None) => {
+            state.decoder.finish();
+          },
+          Err(err) => {
+            state.decoder.finish();
+            return Some((Err(FlowyError::from(err)), state));
+          },
+        }
+      }
+    });
+    Ok(stream.boxed())
+  }
+
+  /// Returns the whole content of the answer.
+  pub async fn chat_completion(&self, messages: Vec<OpenAIChatMessage>) -> FlowyResult<String> {
+    let response = self.send(messages, false).await?;
+    let bytes = response.bytes().await?;
+    let value: Value = serde_json::from_slice(&bytes)?;
+    if let Some(error) = response_error(&value) {
+      return Err(error);
+    }
+
+    value["choices"][0]["message"]["content"]
+      .as_str()
+      .map(|content| content.to_string())
+      .ok_or_else(|| FlowyError::http().with_context("The response has no message content"))
+  }
+
+  async fn send(&self, messages: Vec<OpenAIChatMessage>, stream: bool) -> FlowyResult<Response> {
+    trace!(
+      "[OpenAI Compatible] request: endpoint={}, model={}, messages={}",
+      self.endpoint,
+      self.model,
+      messages.len()
+    );
+    let body = json!({
+      "model": self.model,
+      "messages": messages,
+      "stream": stream,
+    });
+
+    let mut request = self
+      .http
+      .post(&self.endpoint)
+      .header(CONTENT_TYPE, "application/json")
+      .body(serde_json::to_vec(&body)?);
+    if !self.api_key.is_empty() {
+      request = request.header(AUTHORIZATION, format!("Bearer {}", self.api_key));
+    }
+
+    let response = request.send().await?;
+    let status = response.status();
+    if !status.is_success() {
+      let text = response.text().await.unwrap_or_default();
+      let message = serde_json::from_str::<Value>(&text)
+        .ok()
+        .and_then(|value| response_error(&value))
+        .map(|error| error.msg)
+        .unwrap_or(text);
+      return Err(FlowyError::http().with_context(format!("{}: {}", status, message)));
+    }
+    Ok(response)
+  }
+}
+
+struct StreamState {
+  response: Response,
+  decoder: ChatCompletionStreamDecoder,
+  pending: VecDeque<String>,
+}
+
+/// Decodes the server-sent events of a streamed chat completion. A chunk of the response might
+/// end in the middle of an event, so the incomplete line is kept until the next chunk.
+#[derive(Default)]
+pub(crate) struct ChatCompletionStreamDecoder {
+  buffer: Vec<u8>,
+  is_finished: bool,
+}
+
+impl ChatCompletionStreamDecoder {
+  /// Returns the content of the events that are completed by the chunk.
+  pub(crate) fn decode(&mut self, chunk: &[u8]) -> FlowyResult<Vec<String>> {
+    self.buffer.extend_from_slice(chunk);
+    let mut contents = vec![];
+    while let Some(position) = self.buffer.iter().position(|b| *b == b'\n') {
+      let line = self.buffer.drain(..=position).collect::<Vec<_>>();
+      let line = String::from_utf8_lossy(&line);
+      // Other fields of the event, e.g. `event:` or the `:` comments, are ignored
+      let Some(data) = line.trim().strip_prefix("data:") else {
+        continue;
+      };
+
+      let data = data.trim();
+      if data == "[DONE]" {
+        self.finish();
+        break;
+      }
+
+      let value: Value = serde_json::from_str(data)?;
+      if let Some(error) = response_error(&value) {
+        return Err(error);
+      }
+      if let Some(content) = value["choices"][0]["delta"]["content"].as_str() {
+        if !content.is_empty() {
+          contents.push(content.to_string());
+        }
+      }
+    }
+    Ok(contents)
+  }
+
+  pub(crate) fn is_finished(&self) -> bool {
+    self.is_finished
+  }
+
+  pub(crate) fn finish(&mut self) {
+    self.is_finished = true;
+    self.buffer.clear();
+  }
+}
+
+fn response_error(value: &Value) -> Option<FlowyError> {
+  let error = value.get("error")?;
+  let message = error["message"]
+    .as_str()
+    .map(|message| message.to_string())
+    .unwrap_or_else(|| error.to_string());
+  Some(FlowyError::http().with_context(message))
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use futures::TryStreamExt;
+  use tokio::io::{AsyncReadExt, AsyncWriteExt};
+  use tokio::net::TcpListener;
+  use tokio::task::JoinHandle;
+
+  /// Serves one request with the given response and returns the raw request.
+  async fn mock_server(
+    status: &str,
+    content_type: &str,
+    body: &str,
+  ) -> (String, JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let response = format!(
+      "HTTP/1.1 {}\r\ncontent-type: {}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+      status,
+      content_type,
+      body.len(),
+      body
+    );
+    let handle = tokio::spawn(async move {
+      let (mut socket, _) = listener.accept().await.unwrap();
+      let mut request = vec![];
+      let mut buf = [0; 4096];
+      loop {
+        let n = socket.read(&mut buf).await.unwrap();
+        request.extend_from_slice(&buf[..n]);
+        let text = String::from_utf8_lossy(&request).to_string();
+        if let Some(header_end) = text.find("\r\n\r\n") {
+          let content_length = text[..header_end]
+            .lines()
+            .find_map(|line| {
+              let (name, value) = line.split_once(':')?;
+              if name.eq_ignore_ascii_case("content-length") {
+                value.trim().parse::<usize>().ok()
+              } else {
+                None
+              }
+            })
+            .unwrap_or(0);
+          if request.len() >= header_end + 4 + content_length {
+            break;
+          }
+        }
+        if n == 0 {
+          break;
+        }
+      }
+      socket.write_all(response.as_bytes()).await.unwrap();
+      socket.shutdown().await.unwrap();
+      String::from_utf8_lossy(&request).to_string()
+    });
+    (base_url, handle)
+  }
+
+  fn setting(base_url: String, api_key: &str) -> OpenAICompatibleSetting {
+    OpenAICompatibleSetting {
+      enabled: true,
+      base_url,
+      model: "llama3.2".to_string(),
+      api_key: api_key.to_string(),
+    }
+  }
+
+  #[tokio::test]
+  async fn stream_chat_completion_test() {
+    let body = [
+      r#"data: {"choices":[{"index":0,"delta":{"role":"assistant","content":""}}]}"#,
+      r#"data: {"choices":[{"index":0,"delta":{"content":"Hello"}}]}"#,
+      r#": keep-alive"#,
+      r#"data: {"choices":[{"index":0,"delta":{"content":" world"}}]}"#,
+      r#"data: {"choices":[{"index":0,"delta":{},"finish_reason":"stop"}]}"#,
+      "data: [DONE]",
+      "",
+    ]
+    .join("\n\n");
+    let (base_url, server) = mock_server("200 OK", "text/event-stream", &body).await;
+
+    let client = OpenAICompatibleClient::new(&setting(base_url, "secret"));
+    let stream = client
+      .stream_chat_completion(vec![
+        OpenAIChatMessage::system("Be brief"),
+        OpenAIChatMessage::user("Say hello"),
+      ])
+      .await
+      .unwrap();
+    let contents = stream.try_collect::<Vec<_>>().await.unwrap();
+    assert_eq!(contents, vec!["Hello", " world"]);
+
+    let request = server.await.unwrap();
+    assert!(request.starts_with("POST /v1/chat/completions"));
+    assert!(request
+      .to_lowercase()
+      .contains("authorization: bearer secret"));
+    let body = request.split("\r\n\r\n").nth(1).unwrap();
+    let body: Value = serde_json::from_str(body).unwrap();
+    assert_eq!(body["model"], "llama3.2");
+    assert_eq!(body["stream"], true);
+    assert_eq!(body["messages"][1]["role"], "user");
+    assert_eq!(body["messages"][1]["content"], "Say hello");
+  }
+
+  #[tokio::test]
+  async fn chat_completion_test() {
+    let body = r#"{"choices":[{"index":0,"message":{"role":"assistant","content":"Hi"}}]}"#;
+    let (base_url, server) = mock_server("200 OK", "application/json", body).await;
+
+    let client = OpenAICompatibleClient::new(&setting(format!("{}/v1/", base_url), ""));
+    let content = client
+      .chat_completion(vec![OpenAIChatMessage::user("Say hello")])
+      .await
+      .unwrap();
+    assert_eq!(content, "Hi");
+
+    let request = server.await.unwrap();
+    assert!(request.starts_with("POST /v1/chat/completions"));
+    assert!(!request.to_lowercase().contains("authorization:"));
+  }
+
+  #[tokio::test]
+  async fn chat_completion_error_test() {
+    let body = r#"{"error":{"message":"model 'llama3.2' not found"}}"#;
+    let (base_url, _server) = mock_server("404 Not Found", "application/json", body).await;
+
+    let client = OpenAICompatibleClient::new(&setting(base_url, ""));
+    let error = client
+      .stream_chat_completion(vec![OpenAIChatMessage::user("Say hello")])
+      .await
+      .err()
+      .unwrap();
+    assert!(error.msg.contains("not found"));
+  }
+
+  #[test]
+  fn decode_split_event_test() {
+    let mut decoder = ChatCompletionStreamDecoder::default();
+    let contents = decoder
+      .decode(br#"data: {"choices":[{"delta":{"content":"Hel"#)
+      .unwrap();
+    assert!(contents.is_empty());
+
+    let contents = decoder.decode(b"lo\"}}]}\r\n\r\ndata: [DONE]\n\n").unwrap();
+    assert_eq!(contents, vec!["Hello"]);
+    assert!(decoder.is_finished());
+  }
+}
@@ -0,0 +1,3 @@
+pub mod chat_service;
+pub mod client;
+pub mod setting;
@@ -0,0 +1,78 @@
+use std::sync::Arc;
+
+use flowy_error::{FlowyError, FlowyResult};
+use flowy_sqlite::kv::KVStorePreferences;
+use serde::{Deserialize, Serialize};
+
+const OPENAI_COMPATIBLE_SETTING_KEY: &str = "appflowy_openai_compatible_ai_setting:v0";
+
+/// The OpenAI-compatible endpoint that is used for the chats and the completions of a workspace
+/// instead of AppFlowy Cloud.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpenAICompatibleSetting {
+  pub enabled: bool,
+  /// The URL of the server, with or without the `/v1` path, e.g. `http://192.168.1.2:11434`.
+  pub base_url: String,
+  pub model: String,
+  /// Sent as a bearer token if it isn't empty.
+  pub api_key: String,
+}
+
+impl OpenAICompatibleSetting {
+  pub fn is_enabled(&self) -> bool {
+    self.enabled && !self.base_url.is_empty() && !self.model.is_empty()
+  }
+
+  pub fn validate(&self) -> FlowyResult<()> {
+    if !self.enabled {
+      return Ok(());
+    }
+    if !self.base_url.starts_with("http://") && !self.base_url.starts_with("https://") {
+      return Err(FlowyError::invalid_data().with_context("The base URL must be an http(s) URL"));
+    }
+    if self.model.trim().is_empty() {
+      return Err(FlowyError::invalid_data().with_context("The model must not be empty"));
+    }
+    Ok(())
+  }
+
+  pub fn chat_completions_url(&self) -> String {
+    let base_url = self.base_url.trim().trim_end_matches('/');
+    if base_url.ends_with("/v1") {
+      format!("{}/chat/completions", base_url)
+    } else {
+      format!("{}/v1/chat/completions", base_url)
+    }
+  }
+}
+
+/// Stores the [OpenAICompatibleSetting] of each workspace.
+pub struct OpenAICompatibleSettingStore {
+  store_preferences: Arc<KVStorePreferences>,
+}
+
+impl OpenAICompatibleSettingStore {
+  pub fn new(store_preferences: Arc<KVStorePreferences>) -> Self {
+    Self { store_preferences }
+  }
+
+  pub fn get(&self, workspace_id: &str) -> OpenAICompatibleSetting {
+    self
+      .store_preferences
+      .get_object::<OpenAICompatibleSetting>(&setting_key(workspace_id))
+      .unwrap_or_default()
+  }
+
+  pub fn set(&self, workspace_id: &str, setting: &OpenAICompatibleSetting) -> FlowyResult<()> {
+    setting.validate()?;
+    self
+      .store_preferences
+      .set_object(&setting_key(workspace_id), setting)
+      .map_err(|err| FlowyError::internal().with_context(err))?;
+    Ok(())
+  }
+}
+
+fn setting_key(workspace_id: &str) -> String {
+  format!("{}:{}", OPENAI_COMPATIBLE_SETTING_KEY, workspace_id)
+}
@@ -0,0 +1,51 @@
+use diesel::sqlite::SqliteConnection;
+use flowy_error::FlowyResult;
+use flowy_sqlite::{
+  diesel, insert_into,
+  query_dsl::*,
+  schema::{ai_usage_table, ai_usage_table::dsl},
+  ExpressionMethods, Identifiable, Insertable, QueryResult, Queryable,
+};
+
+/// A request that is sent to an AI model. The token counts are estimated from the text of the
+/// request and the response.
+#[derive(Debug, Clone, Queryable, Insertable, Identifiable)]
+#[diesel(table_name = ai_usage_table)]
+#[diesel(primary_key(id))]
+pub struct AIUsageTable {
+  pub id: String,
+  pub workspace_id: String,
+  /// Empty if the request doesn't belong to a chat.
+  pub chat_id: String,
+  pub usage_type: i32,
+  pub provider: i32,
+  pub model: String,
+  pub input_tokens: i64,
+  pub output_tokens: i64,
+  pub is_estimated: bool,
+  pub latency_ms: i64,
+  pub outcome: i32,
+  pub created_at: i64,
+}
+
+pub fn insert_ai_usage(conn: &mut SqliteConnection, usage: &AIUsageTable) -> FlowyResult<()> {
+  insert_into(ai_usage_table::table)
+    .values(usage)
+    .execute(conn)?;
+  Ok(())
+}
+
+/// Returns the usage of the workspace that was recorded in `[start, end)`, ordered by time.
+pub fn select_ai_usage(
+  conn: &mut SqliteConnection,
+  workspace_id_val: &str,
+  start: i64,
+  end: i64,
+) -> QueryResult<Vec<AIUsageTable>> {
+  dsl::ai_usage_table
+    .filter(ai_usage_table::workspace_id.eq(workspace_id_val))
+    .filter(ai_usage_table::created_at.ge(start))
+    .filter(ai_usage_table::created_at.lt(end))
+    .order(ai_usage_table::created_at.asc())
+    .load::<AIUsageTable>(conn)
+}
@@ -0,0 +1,331 @@
+use diesel::sqlite::SqliteConnection;
+use flowy_ai_pub::cloud::ChatAuthorType;
+use flowy_error::{FlowyError, FlowyResult};
+use flowy_sqlite::upsert::excluded;
+use flowy_sqlite::{
+  diesel, insert_into,
+  query_dsl::*,
+  schema::{chat_message_branch_table, chat_message_table},
+  DBConnection, ExpressionMethods, Insertable, QueryResult, Queryable,
+};
+use std::collections::{HashMap, HashSet};
+
+use crate::persistence::ChatMessageTable;
+
+/// The parent of the first question of a chat. The questions that were saved before the messages
+/// could be branched have no parent, and are always shown.
+pub const CHAT_ROOT_MESSAGE_ID: i64 = 0;
+
+/// The message that is shown among the messages with the same parent and author type.
+#[derive(Queryable, Insertable)]
+#[diesel(table_name = chat_message_branch_table)]
+pub struct ChatMessageBranchTable {
+  pub chat_id: String,
+  pub parent_message_id: i64,
+  pub author_type: i64,
+  pub active_message_id: i64,
+}
+
+/// How a message is linked to the previous message of the conversation.
+#[derive(Debug, Clone, Copy, Queryable)]
+pub struct ChatMessageLink {
+  pub message_id: i64,
+  pub author_type: i64,
+  pub reply_message_id: Option<i64>,
+  pub parent_message_id: Option<i64>,
+}
+
+impl ChatMessageLink {
+  /// The parent of an answer is the question it replies to.
+  pub fn parent_id(&self) -> Option<i64> {
+    if self.author_type == ChatAuthorType::AI as i64 {
+      self.reply_message_id.or(self.parent_message_id)
+    } else {
+      self.parent_message_id
+    }
+  }
+
+  fn branch_key(&self) -> Option<(i64, i64)> {
+    self.parent_id().map(|parent| (parent, self.author_type))
+  }
+}
+
+/// The sibling messages of a message, including the message itself.
+pub struct ChatMessageBranches {
+  pub messages: Vec<ChatMessageTable>,
+  pub active_message_id: i64,
+}
+
+pub fn select_chat_message_links(
+  conn: &mut SqliteConnection,
+  chat_id_val: &str,
+) -> QueryResult<Vec<ChatMessageLink>> {
+  chat_message_table::table
+    .filter(chat_message_table::chat_id.eq(chat_id_val))
+    .select((
+      chat_message_table::message_id,
+      chat_message_table::author_type,
+      chat_message_table::reply_message_id,
+      chat_message_table::parent_message_id,
+    ))
+    .order(chat_message_table::message_id.asc())
+    .load::<ChatMessageLink>(conn)
+}
+
+fn select_active_branches(
+  conn: &mut SqliteConnection,
+  chat_id_val: &str,
+) -> QueryResult<HashMap<(i64, i64), i64>> {
+  let rows = chat_message_branch_table::table
+    .filter(chat_message_branch_table::chat_id.eq(chat_id_val))
+    .load::<ChatMessageBranchTable>(conn)?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| {
+        (
+          (row.parent_message_id, row.author_type),
+          row.active_message_id,
+        )
+      })
+      .collect(),
+  )
+}
+
+/// Flags the messages of the branches that are not shown, and the messages after them, as hidden,
+/// so the shown messages are selected without walking the branches.
+pub fn update_hidden_messages(conn: &mut SqliteConnection, chat_id_val: &str) -> QueryResult<()> {
+  let links = select_chat_message_links(conn, chat_id_val)?;
+  let branches = select_active_branches(conn, chat_id_val)?;
+  let hidden_message_ids = hidden_message_ids(&links, &branches)
+    .into_iter()
+    .collect::<Vec<_>>();
+  diesel::update(
+    chat_message_table::table
+      .filter(chat_message_table::chat_id.eq(chat_id_val))
+      .filter(chat_message_table::is_hidden.eq(true))
+      .filter(chat_message_table::message_id.ne_all(&hidden_message_ids)),
+  )
+  .set(chat_message_table::is_hidden.eq(false))
+  .execute(conn)?;
+  diesel::update(
+    chat_message_table::table
+      .filter(chat_message_table::chat_id.eq(chat_id_val))
+      .filter(chat_message_table::is_hidden.eq(false))
+      .filter(chat_message_table::message_id.eq_any(&hidden_message_ids)),
+  )
+  .set(chat_message_table::is_hidden.eq(true))
+  .execute(conn)?;
+  Ok(())
+}
+
+/// Returns the siblings of the message and the one that is shown. None if the message doesn't
+/// exist.
+pub fn select_message_branches(
+  mut conn: DBConnection,
+  chat_id_val: &str,
+  message_id_val: i64,
+) -> QueryResult<Option<ChatMessageBranches>> {
+  let links = select_chat_message_links(&mut conn, chat_id_val)?;
+  let Some(link) = links
+    .iter()
+    .find(|link| link.message_id == message_id_val)
+    .copied()
+  else {
+    return Ok(None);
+  };
+
+  let sibling_ids = match link.branch_key() {
+    None => vec![link.message_id],
+    Some(key) => links
+      .iter()
+      .filter(|other| other.branch_key() == Some(key))
+      .map(|other| other.message_id)
+      .collect(),
+  };
+  let branches = select_active_branches(&mut conn, chat_id_val)?;
+  let active_message_id = active_sibling(
+    &sibling_ids,
+    link.branch_key().and_then(|key| branches.get(&key)),
+  );
+  let messages = chat_message_table::table
+    .filter(chat_message_table::message_id.eq_any(sibling_ids))
+    .order(chat_message_table::message_id.asc())
+    .load::<ChatMessageTable>(&mut *conn)?;
+  Ok(Some(ChatMessageBranches {
+    messages,
+    active_message_id,
+  }))
+}
+
+/// Shows the message instead of its siblings.
+pub fn set_active_message_branch(
+  conn: &mut SqliteConnection,
+  chat_id_val: &str,
+  message_id_val: i64,
+) -> FlowyResult<()> {
+  let link = select_chat_message_links(conn, chat_id_val)?
+    .into_iter()
+    .find(|link| link.message_id == message_id_val)
+    .ok_or_else(|| {
+      FlowyError::record_not_found().with_context(format!("Message not found: {}", message_id_val))
+    })?;
+  let Some((parent_message_id, author_type)) = link.branch_key() else {
+    // A message without parent has no siblings
+    return Ok(());
+  };
+
+  let row = ChatMessageBranchTable {
+    chat_id: chat_id_val.to_string(),
+    parent_message_id,
+    author_type,
+    active_message_id: message_id_val,
+  };
+  conn.immediate_transaction(|conn| {
+    insert_into(chat_message_branch_table::table)
+      .values(&row)
+      .on_conflict((
+        chat_message_branch_table::chat_id,
+        chat_message_branch_table::parent_message_id,
+        chat_message_branch_table::author_type,
+      ))
+      .do_update()
+      .set(
+        chat_message_branch_table::active_message_id
+          .eq(excluded(chat_message_branch_table::active_message_id)),
+      )
+      .execute(conn)?;
+    update_hidden_messages(conn, chat_id_val)?;
+    Ok::<(), FlowyError>(())
+  })
+}
+
+/// Links the questions that were saved before the messages could be branched to the previous
+/// message, so the messages after an edited question are hidden with it.
+pub fn link_unparented_questions(
+  conn: &mut SqliteConnection,
+  chat_id_val: &str,
+) -> FlowyResult<()> {
+  let links = select_chat_message_links(conn, chat_id_val)?;
+  conn.immediate_transaction(|conn| {
+    let mut prev_message_id = CHAT_ROOT_MESSAGE_ID;
+    for link in links {
+      if link.author_type != ChatAuthorType::AI as i64 && link.parent_message_id.is_none() {
+        diesel::update(
+          chat_message_table::table.filter(chat_message_table::message_id.eq(link.message_id)),
+        )
+        .set(chat_message_table::parent_message_id.eq(Some(prev_message_id)))
+        .execute(conn)?;
+      }
+      prev_message_id = link.message_id;
+    }
+    update_hidden_messages(conn, chat_id_val)?;
+    Ok::<(), FlowyError>(())
+  })
+}
+
+/// The sibling that is shown: the selected one, or the latest one.
+fn active_sibling(sibling_ids: &[i64], selected: Option<&i64>) -> i64 {
+  selected
+    .filter(|selected| sibling_ids.contains(selected))
+    .copied()
+    .or_else(|| sibling_ids.iter().max().copied())
+    .unwrap_or_default()
+}
+
+fn hidden_message_ids(
+  links: &[ChatMessageLink],
+  branches: &HashMap<(i64, i64), i64>,
+) -> HashSet<i64> {
+  let mut siblings = HashMap::<(i64, i64), Vec<i64>>::new();
+  let mut children = HashMap::<i64, Vec<i64>>::new();
+  for link in links {
+    if let Some(key) = link.branch_key() {
+      siblings.entry(key).or_default().push(link.message_id);
+      children.entry(key.0).or_default().push(link.message_id);
+    }
+  }
+
+  let mut stack = vec![];
+  for (key, sibling_ids) in &siblings {
+    if sibling_ids.len() > 1 {
+      let active = active_sibling(sibling_ids, branches.get(key));
+      stack.extend(sibling_ids.iter().filter(|id| **id != active));
+    }
+  }
+
+  let mut hidden = HashSet::new();
+  while let Some(message_id) = stack.pop() {
+    if hidden.insert(message_id) {
+      if let Some(children) = children.get(&message_id) {
+        stack.extend(children);
+      }
+    }
+  }
+  hidden
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn question(message_id: i64, parent_message_id: Option<i64>) -> ChatMessageLink {
+    ChatMessageLink {
+      message_id,
+      author_type: ChatAuthorType::Human as i64,
+      reply_message_id: None,
+      parent_message_id,
+    }
+  }
+
+  fn answer(message_id: i64, question_id: i64) -> ChatMessageLink {
+    ChatMessageLink {
+      message_id,
+      author_type: ChatAuthorType::AI as i64,
+      reply_message_id: Some(question_id),
+      parent_message_id: None,
+    }
+  }
+
+  #[test]
+  fn hidden_message_ids_test() {
+    // 1 -> 2 -> 3 -> 4, then 2 was regenerated as 5, and 3 was edited as 6 -> 7
+    let links = vec![
+      question(1, Some(CHAT_ROOT_MESSAGE_ID)),
+      answer(2, 1),
+      question(3, Some(2)),
+      answer(4, 3),
+      answer(5, 1),
+      question(6, Some(2)),
+      answer(7, 6),
+    ];
+
+    // The latest siblings are shown, 6 is hidden with its parent 2
+    let hidden = hidden_message_ids(&links, &HashMap::new());
+    assert_eq!(hidden, HashSet::from([2, 3, 4, 6, 7]));
+
+    let branches = HashMap::from([((1, ChatAuthorType::AI as i64), 2)]);
+    let hidden = hidden_message_ids(&links, &branches);
+    assert_eq!(hidden, HashSet::from([3, 4, 5]));
+
+    let branches = HashMap::from([
+      ((1, ChatAuthorType::AI as i64), 2),
+      ((2, ChatAuthorType::Human as i64), 3),
+    ]);
+    let hidden = hidden_message_ids(&links, &branches);
+    assert_eq!(hidden, HashSet::from([5, 6, 7]));
+  }
+
+  #[test]
+  fn unparented_messages_are_shown_test() {
+    let links = vec![
+      question(1, None),
+      answer(2, 1),
+      question(3, None),
+      answer(4, 3),
+    ];
+    assert!(hidden_message_ids(&links, &HashMap::new()).is_empty());
+    assert_eq!(active_sibling(&[2, 5], Some(&9)), 5);
+    assert_eq!(active_sibling(&[2, 5], Some(&2)), 2);
+  }
+}
@@ -0,0 +1,167 @@
+use diesel::sqlite::{Sqlite, SqliteConnection};
+use flowy_sqlite::{
+  diesel, sql_query,
+  sql_types::{BigInt, Text},
+  QueryResult, QueryableByName, RunQueryDsl,
+};
+
+/// The marker around the matched terms in the snippet of a [ChatMessageSearchRow].
+pub const SEARCH_HIGHLIGHT_MARKER: &str = "**";
+/// The maximum number of tokens in the snippet of a [ChatMessageSearchRow]. The content is split
+/// into trigrams, so a token is about one character.
+const SEARCH_SNIPPET_TOKENS: i64 = 64;
+/// The trigram tokenizer can't match a term that is shorter than this.
+const TRIGRAM_LEN: usize = 3;
+
+/// A message whose content matches a search. The `chat_message_fts` table is kept in sync with
+/// the `chat_message_table` by triggers, so the messages are searchable once they are saved.
+#[derive(Debug, Clone, QueryableByName)]
+pub struct ChatMessageSearchRow {
+  #[diesel(sql_type = BigInt)]
+  pub message_id: i64,
+  #[diesel(sql_type = Text)]
+  pub chat_id: String,
+  #[diesel(sql_type = Text)]
+  pub snippet: String,
+  #[diesel(sql_type = BigInt)]
+  pub created_at: i64,
+  #[diesel(sql_type = BigInt)]
+  pub author_type: i64,
+}
+
+/// Returns the messages of the chats that match the query, the best matches first. The hidden
+/// branch messages aren't returned.
+pub fn search_chat_messages(
+  conn: &mut SqliteConnection,
+  chat_ids: &[String],
+  query: &str,
+  limit: i64,
+) -> QueryResult<Vec<ChatMessageSearchRow>> {
+  let terms = SearchTerms::from_query(query);
+  if terms.is_empty() || chat_ids.is_empty() {
+    return Ok(vec![]);
+  }
+
+  let placeholders = vec!["?"; chat_ids.len()].join(", ");
+  let like_filter = " AND message.content LIKE ? ESCAPE '\\'".repeat(terms.like_patterns.len());
+  // The terms that are too short for the trigram index are only matched with LIKE, the snippet
+  // of such a search is the start of the message
+  let sql = if terms.match_query.is_some() {
+    format!(
+      "SELECT message.message_id, message.chat_id, message.created_at, message.author_type, \
+       snippet(chat_message_fts, 0, '{marker}', '{marker}', '...', {tokens}) AS snippet \
+       FROM chat_message_fts \
+       JOIN chat_message_table AS message ON message.message_id = chat_message_fts.rowid \
+       WHERE chat_message_fts MATCH ? AND message.chat_id IN ({placeholders}) \
+       AND message.is_hidden = 0{like_filter} \
+       ORDER BY rank LIMIT ?",
+      marker = SEARCH_HIGHLIGHT_MARKER,
+      tokens = SEARCH_SNIPPET_TOKENS,
+      placeholders = placeholders,
+      like_filter = like_filter,
+    )
+  } else {
+    format!(
+      "SELECT message.message_id, message.chat_id, message.created_at, message.author_type, \
+       substr(message.content, 1, {tokens}) AS snippet \
+       FROM chat_message_table AS message \
+       WHERE message.chat_id IN ({placeholders}) AND message.is_hidden = 0{like_filter} \
+       ORDER BY message.created_at DESC LIMIT ?",
+      tokens = SEARCH_SNIPPET_TOKENS,
+      placeholders = placeholders,
+      like_filter = like_filter,
+    )
+  };
+
+  let mut query = sql_query(sql).into_boxed::<Sqlite>();
+  if let Some(match_query) = terms.match_query {
+    query = query.bind::<Text, _>(match_query);
+  }
+  for chat_id in chat_ids {
+    query = query.bind::<Text, _>(chat_id.clone());
+  }
+  for pattern in terms.like_patterns {
+    query = query.bind::<Text, _>(pattern);
+  }
+  query
+    .bind::<BigInt, _>(limit)
+    .load::<ChatMessageSearchRow>(conn)
+}
+
+/// The text that the user typed, split into an FTS5 query and the LIKE patterns of the terms
+/// that are too short for the trigram tokenizer.
+#[derive(Debug, PartialEq, Eq)]
+struct SearchTerms {
+  match_query: Option<String>,
+  like_patterns: Vec<String>,
+}
+
+impl SearchTerms {
+  /// Each term is quoted, so the FTS5 operators are matched as plain text. The trigram tokenizer
+  /// matches substrings, so a term that is still being typed matches without a prefix query.
+  fn from_query(query: &str) -> Self {
+    let (long_terms, short_terms): (Vec<_>, Vec<_>) = query
+      .split_whitespace()
+      .partition(|term| term.chars().count() >= TRIGRAM_LEN);
+    let match_query = if long_terms.is_empty() {
+      None
+    } else {
+      Some(
+        long_terms
+          .iter()
+          .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+          .collect::<Vec<_>>()
+          .join(" "),
+      )
+    };
+    let like_patterns = short_terms
+      .iter()
+      .map(|term| {
+        let escaped = term
+          .replace('\\', "\\\\")
+          .replace('%', "\\%")
+          .replace('_', "\\_");
+        format!("%{}%", escaped)
+      })
+      .collect();
+    Self {
+      match_query,
+      like_patterns,
+    }
+  }
+
+  fn is_empty(&self) -> bool {
+    self.match_query.is_none() && self.like_patterns.is_empty()
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn search_terms_test() {
+    assert!(SearchTerms::from_query("  ").is_empty());
+    assert_eq!(
+      SearchTerms::from_query("borrow checker"),
+      SearchTerms {
+        match_query: Some("\"borrow\" \"checker\"".to_string()),
+        like_patterns: vec![],
+      }
+    );
+    assert_eq!(
+      SearchTerms::from_query("say \"hi\" OR"),
+      SearchTerms {
+        match_query: Some("\"say\" \"\"\"hi\"\"\"".to_string()),
+        like_patterns: vec!["%OR%".to_string()],
+      }
+    );
+    assert_eq!(
+      SearchTerms::from_query("重命名 字段 5%"),
+      SearchTerms {
+        match_query: Some("\"重命名\"".to_string()),
+        like_patterns: vec!["%字段%".to_string(), "%5\\%%".to_string()],
+      }
+    );
+  }
+}
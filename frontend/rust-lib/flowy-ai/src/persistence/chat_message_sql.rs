@@ -1,3 +1,4 @@
+use crate::persistence::update_hidden_messages;
 use flowy_error::{FlowyError, FlowyResult};
 use flowy_sqlite::upsert::excluded;
 use flowy_sqlite::{
@@ -20,6 +21,12 @@ pub struct ChatMessageTable {
   pub author_id: String,
   pub reply_message_id: Option<i64>,
   pub metadata: Option<String>,
+  /// The previous message of a question in the conversation. An answer's parent is the question
+  /// it replies to.
+  pub parent_message_id: Option<i64>,
+  /// True if the message is in a branch that is not shown, or after such a message. Updated
+  /// whenever the messages or the shown branches of the chat change.
+  pub is_hidden: bool,
 }
 
 pub fn insert_chat_messages(
@@ -41,12 +48,23 @@ pub fn insert_chat_messages(
         ))
         .execute(conn)?;
     }
+
+    let mut chat_ids = new_messages
+      .iter()
+      .map(|message| message.chat_id.as_str())
+      .collect::<Vec<_>>();
+    chat_ids.sort_unstable();
+    chat_ids.dedup();
+    for chat_id in chat_ids {
+      update_hidden_messages(conn, chat_id)?;
+    }
     Ok::<(), FlowyError>(())
   })?;
 
   Ok(())
 }
 
+/// Returns the messages of the branches that are shown.
 pub fn select_chat_messages(
   mut conn: DBConnection,
   chat_id_val: &str,
@@ -56,6 +74,7 @@ pub fn select_chat_messages(
 ) -> QueryResult<Vec<ChatMessageTable>> {
   let mut query = dsl::chat_message_table
     .filter(chat_message_table::chat_id.eq(chat_id_val))
+    .filter(chat_message_table::is_hidden.eq(false))
     .into_boxed();
   if let Some(after_message_id) = after_message_id {
     query = query.filter(chat_message_table::message_id.gt(after_message_id));
@@ -25,6 +25,9 @@ pub struct ChatTable {
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ChatTableMetadata {
   pub files: Vec<ChatTableFile>,
+  /// The views that are used as the context of the chat.
+  #[serde(default)]
+  pub rag_ids: Vec<String>,
 }
 
 impl ChatTableMetadata {
@@ -56,8 +59,9 @@ pub struct ChatTableChangeset {
 }
 
 impl ChatTableChangeset {
-  pub fn from_metadata(metadata: ChatTableMetadata) -> Self {
+  pub fn from_metadata(chat_id: &str, metadata: ChatTableMetadata) -> Self {
     ChatTableChangeset {
+      chat_id: chat_id.to_string(),
       metadata: serde_json::to_string(&metadata).ok(),
       ..Default::default()
     }
@@ -76,7 +80,6 @@ pub fn insert_chat(mut conn: DBConnection, new_chat: &ChatTable) -> QueryResult<
     .execute(&mut *conn)
 }
 
-#[allow(dead_code)]
 pub fn update_chat(
   conn: &mut SqliteConnection,
   changeset: ChatTableChangeset,
@@ -94,7 +97,6 @@ pub fn read_chat(mut conn: DBConnection, chat_id_val: &str) -> QueryResult<ChatT
   Ok(row)
 }
 
-#[allow(dead_code)]
 pub fn read_chat_metadata(
   conn: &mut SqliteConnection,
   chat_id_val: &str,
@@ -0,0 +1,65 @@
+use diesel::sqlite::SqliteConnection;
+use flowy_error::FlowyResult;
+use flowy_sqlite::upsert::excluded;
+use flowy_sqlite::{
+  diesel, insert_into,
+  query_dsl::*,
+  schema::{custom_prompt_table, custom_prompt_table::dsl},
+  ExpressionMethods, Identifiable, Insertable, OptionalExtension, QueryResult, Queryable,
+};
+use serde::{Deserialize, Serialize};
+
+/// A completion prompt that is defined by the user. The deleted prompts are kept, so the deletion
+/// is synced to the other devices.
+#[derive(Debug, Clone, Queryable, Insertable, Identifiable, Serialize, Deserialize)]
+#[diesel(table_name = custom_prompt_table)]
+#[diesel(primary_key(id))]
+pub struct CustomPromptTable {
+  pub id: String,
+  pub workspace_id: String,
+  pub name: String,
+  pub template: String,
+  pub is_deleted: bool,
+  pub created_at: i64,
+  pub updated_at: i64,
+}
+
+pub fn upsert_custom_prompt(
+  conn: &mut SqliteConnection,
+  prompt: &CustomPromptTable,
+) -> FlowyResult<()> {
+  insert_into(custom_prompt_table::table)
+    .values(prompt)
+    .on_conflict(custom_prompt_table::id)
+    .do_update()
+    .set((
+      custom_prompt_table::name.eq(excluded(custom_prompt_table::name)),
+      custom_prompt_table::template.eq(excluded(custom_prompt_table::template)),
+      custom_prompt_table::is_deleted.eq(excluded(custom_prompt_table::is_deleted)),
+      custom_prompt_table::updated_at.eq(excluded(custom_prompt_table::updated_at)),
+    ))
+    .execute(conn)?;
+  Ok(())
+}
+
+/// Returns the prompts of the workspace including the deleted ones, ordered by creation time.
+pub fn select_custom_prompts(
+  conn: &mut SqliteConnection,
+  workspace_id_val: &str,
+) -> QueryResult<Vec<CustomPromptTable>> {
+  dsl::custom_prompt_table
+    .filter(custom_prompt_table::workspace_id.eq(workspace_id_val))
+    .order(custom_prompt_table::created_at.asc())
+    .load::<CustomPromptTable>(conn)
+}
+
+pub fn select_custom_prompt(
+  conn: &mut SqliteConnection,
+  id_val: &str,
+) -> QueryResult<Option<CustomPromptTable>> {
+  dsl::custom_prompt_table
+    .filter(custom_prompt_table::id.eq(id_val))
+    .filter(custom_prompt_table::is_deleted.eq(false))
+    .first::<CustomPromptTable>(conn)
+    .optional()
+}
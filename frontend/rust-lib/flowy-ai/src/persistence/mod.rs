@@ -1,5 +1,15 @@
+mod ai_usage_sql;
+mod chat_message_branch_sql;
+mod chat_message_search_sql;
 mod chat_message_sql;
 mod chat_sql;
+mod custom_prompt_sql;
+mod view_embedding_sql;
 
+pub use ai_usage_sql::*;
+pub use chat_message_branch_sql::*;
+pub use chat_message_search_sql::*;
 pub use chat_message_sql::*;
 pub use chat_sql::*;
+pub use custom_prompt_sql::*;
+pub use view_embedding_sql::*;
@@ -0,0 +1,63 @@
+use flowy_error::{FlowyError, FlowyResult};
+use flowy_sqlite::{
+  diesel, insert_into,
+  query_dsl::*,
+  schema::{view_embedding_table, view_embedding_table::dsl},
+  DBConnection, ExpressionMethods, Insertable, QueryResult, Queryable,
+};
+
+/// A chunk of the text of a view and its embedding. The chunks of a document are cut from its
+/// blocks, the chunks of a database from its rows, so `block_id` is the id of the block or the row.
+#[derive(Queryable, Insertable, Clone, Debug)]
+#[diesel(table_name = view_embedding_table)]
+pub struct ViewEmbeddingTable {
+  pub view_id: String,
+  pub block_id: String,
+  pub chunk_index: i32,
+  pub content: String,
+  pub content_hash: String,
+  pub embedding_model: String,
+  pub embedding: Vec<u8>,
+  pub updated_at: i64,
+}
+
+/// Returns the chunks of the views that were embedded with the given model.
+pub fn select_view_embeddings(
+  mut conn: DBConnection,
+  view_ids: &[String],
+  embedding_model: &str,
+) -> QueryResult<Vec<ViewEmbeddingTable>> {
+  dsl::view_embedding_table
+    .filter(view_embedding_table::view_id.eq_any(view_ids))
+    .filter(view_embedding_table::embedding_model.eq(embedding_model))
+    .order((
+      view_embedding_table::view_id,
+      view_embedding_table::block_id,
+      view_embedding_table::chunk_index,
+    ))
+    .load::<ViewEmbeddingTable>(&mut *conn)
+}
+
+/// Deletes the chunks of the views, e.g. after the views were deleted or moved to the trash.
+pub fn delete_view_embeddings(mut conn: DBConnection, view_ids: &[String]) -> QueryResult<usize> {
+  diesel::delete(dsl::view_embedding_table.filter(view_embedding_table::view_id.eq_any(view_ids)))
+    .execute(&mut *conn)
+}
+
+/// Replaces all the chunks of the view with the given chunks.
+pub fn replace_view_embeddings(
+  mut conn: DBConnection,
+  view_id: &str,
+  rows: &[ViewEmbeddingTable],
+) -> FlowyResult<()> {
+  conn.immediate_transaction(|conn| {
+    diesel::delete(dsl::view_embedding_table.filter(view_embedding_table::view_id.eq(view_id)))
+      .execute(conn)?;
+    if !rows.is_empty() {
+      insert_into(view_embedding_table::table)
+        .values(rows)
+        .execute(conn)?;
+    }
+    Ok::<(), FlowyError>(())
+  })
+}
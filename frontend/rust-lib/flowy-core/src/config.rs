@@ -6,6 +6,7 @@ use semver::Version;
 use tracing::{error, info};
 
 use flowy_server_pub::af_cloud_config::AFCloudConfiguration;
+use flowy_server_pub::s3_config::S3Configuration;
 use flowy_user::services::entities::URL_SAFE_ENGINE;
 use lib_infra::file_util::copy_dir_recursive;
 use lib_infra::util::OperatingSystem;
@@ -28,6 +29,9 @@ pub struct AppFlowyCoreConfig {
   pub application_path: String,
   pub(crate) log_filter: String,
   pub cloud_config: Option<AFCloudConfiguration>,
+  /// The S3-compatible bucket that stores the files of the local users. None when the files are
+  /// stored on the device.
+  pub s3_config: Option<S3Configuration>,
 }
 
 impl fmt::Debug for AppFlowyCoreConfig {
@@ -42,6 +46,9 @@ impl fmt::Debug for AppFlowyCoreConfig {
       debug.field("gotrue_url", &config.gotrue_url);
       debug.field("enable_sync_trace", &config.enable_sync_trace);
     }
+    if let Some(config) = &self.s3_config {
+      debug.field("s3_config", config);
+    }
     debug.finish()
   }
 }
@@ -84,6 +91,7 @@ impl AppFlowyCoreConfig {
     name: String,
   ) -> Self {
     let cloud_config = AFCloudConfiguration::from_env().ok();
+    let s3_config = S3Configuration::from_env().ok();
     let mut log_crates = vec![];
     let storage_path = match &cloud_config {
       None => custom_application_path,
@@ -110,6 +118,7 @@ impl AppFlowyCoreConfig {
       platform,
       log_filter,
       cloud_config,
+      s3_config,
     }
   }
 
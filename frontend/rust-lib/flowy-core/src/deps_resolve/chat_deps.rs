@@ -1,12 +1,26 @@
-use flowy_ai::ai_manager::{AIManager, AIQueryService, AIUserService};
+use collab_document::importer::md_importer::MDImporter;
+use flowy_ai::ai_manager::{
+  AIDocumentService, AIManager, AIPromptSyncService, AIQueryService, AIUserService,
+  AIViewContentService, ViewContent, ViewContentBlock,
+};
 use flowy_ai_pub::cloud::ChatCloudService;
+use flowy_database2::DatabaseManager;
+use flowy_document::entities::DocumentDataPB;
+use flowy_document::manager::DocumentManager;
+use flowy_document::parser::document_text::extract_block_texts;
 use flowy_error::FlowyError;
+use flowy_folder::entities::{CreateViewParams, ViewLayoutPB};
+use flowy_folder::manager::FolderManager;
+use flowy_folder::view_operation::ViewData;
 use flowy_folder::ViewLayout;
+use flowy_folder_pub::cloud::gen_view_id;
 use flowy_folder_pub::query::FolderQueryService;
 use flowy_sqlite::kv::KVStorePreferences;
 use flowy_sqlite::DBConnection;
 use flowy_storage_pub::storage::StorageService;
 use flowy_user::services::authenticate_user::AuthenticateUser;
+use flowy_user::user_manager::UserManager;
+use lib_dispatch::prelude::ToBytes;
 use lib_infra::async_trait::async_trait;
 use std::path::PathBuf;
 use std::sync::{Arc, Weak};
@@ -32,6 +46,181 @@ impl ChatDepsResolver {
       },
     ))
   }
+
+  /// The views are read and created with the folder, the document and the database managers,
+  /// which are created after the [AIManager].
+  pub fn register_view_services(
+    ai_manager: &Arc<AIManager>,
+    folder_manager: &Arc<FolderManager>,
+    document_manager: &Arc<DocumentManager>,
+    database_manager: &Arc<DatabaseManager>,
+  ) {
+    ai_manager.register_view_content_service(Arc::new(ChatViewContentServiceImpl {
+      folder_manager: Arc::downgrade(folder_manager),
+      document_manager: Arc::downgrade(document_manager),
+      database_manager: Arc::downgrade(database_manager),
+    }));
+    ai_manager.register_document_service(Arc::new(ChatDocumentServiceImpl {
+      folder_manager: Arc::downgrade(folder_manager),
+    }));
+  }
+}
+
+impl ChatDepsResolver {
+  /// The custom prompts are synced with the user awareness of the [UserManager].
+  pub fn register_prompt_sync_service(
+    ai_manager: &Arc<AIManager>,
+    user_manager: &Arc<UserManager>,
+  ) {
+    ai_manager.register_prompt_sync_service(Arc::new(ChatPromptSyncServiceImpl(Arc::downgrade(
+      user_manager,
+    ))));
+  }
+}
+
+struct ChatPromptSyncServiceImpl(Weak<UserManager>);
+
+impl ChatPromptSyncServiceImpl {
+  fn upgrade_user_manager(&self) -> Result<Arc<UserManager>, FlowyError> {
+    self
+      .0
+      .upgrade()
+      .ok_or_else(|| FlowyError::internal().with_context("The user manager is dropped"))
+  }
+}
+
+#[async_trait]
+impl AIPromptSyncService for ChatPromptSyncServiceImpl {
+  async fn get_prompts(&self) -> Result<Vec<String>, FlowyError> {
+    self.upgrade_user_manager()?.get_custom_prompts().await
+  }
+
+  async fn set_prompt(&self, prompt_id: &str, data: String) -> Result<(), FlowyError> {
+    self
+      .upgrade_user_manager()?
+      .set_custom_prompt(prompt_id, data)
+      .await
+  }
+}
+
+struct ChatDocumentServiceImpl {
+  folder_manager: Weak<FolderManager>,
+}
+
+impl ChatDocumentServiceImpl {
+  fn upgrade_folder_manager(&self) -> Result<Arc<FolderManager>, FlowyError> {
+    self
+      .folder_manager
+      .upgrade()
+      .ok_or_else(|| FlowyError::internal().with_context("The folder manager is dropped"))
+  }
+}
+
+#[async_trait]
+impl AIDocumentService for ChatDocumentServiceImpl {
+  async fn query_view_name(&self, view_id: &str) -> Result<String, FlowyError> {
+    let view = self.upgrade_folder_manager()?.get_view(view_id).await?;
+    Ok(view.name.clone())
+  }
+
+  async fn create_document(
+    &self,
+    parent_view_id: &str,
+    name: &str,
+    markdown: String,
+  ) -> Result<String, FlowyError> {
+    let folder_manager = self.upgrade_folder_manager()?;
+    let view_id = gen_view_id().to_string();
+    let data = MDImporter::new(None)
+      .import(&view_id, markdown)
+      .map_err(|err| FlowyError::invalid_data().with_context(err))?;
+    let data_bytes = DocumentDataPB::from(data)
+      .into_bytes()
+      .map_err(|_| FlowyError::invalid_data())?;
+    let params = CreateViewParams {
+      parent_view_id: parent_view_id.to_string(),
+      name: name.to_string(),
+      layout: ViewLayoutPB::Document,
+      view_id,
+      initial_data: ViewData::Data(data_bytes),
+      meta: Default::default(),
+      set_as_current: false,
+      index: None,
+      section: None,
+      icon: None,
+      extra: None,
+    };
+    let (view, _) = folder_manager.create_view_with_params(params, true).await?;
+    Ok(view.id)
+  }
+}
+
+struct ChatViewContentServiceImpl {
+  folder_manager: Weak<FolderManager>,
+  document_manager: Weak<DocumentManager>,
+  database_manager: Weak<DatabaseManager>,
+}
+
+#[async_trait]
+impl AIViewContentService for ChatViewContentServiceImpl {
+  async fn query_view_content(&self, view_id: &str) -> Result<Option<ViewContent>, FlowyError> {
+    let folder_manager = self
+      .folder_manager
+      .upgrade()
+      .ok_or_else(|| FlowyError::internal().with_context("The folder manager is dropped"))?;
+    let view = folder_manager.get_view_pb(view_id).await?;
+    let blocks =
+      match view.layout {
+        ViewLayoutPB::Document => {
+          let document_manager = self.document_manager.upgrade().ok_or_else(|| {
+            FlowyError::internal().with_context("The document manager is dropped")
+          })?;
+          let data = document_manager.get_document_data(view_id).await?;
+          extract_block_texts(&data)
+            .into_iter()
+            .map(|text| ViewContentBlock {
+              block_id: text.block_id,
+              text: text.text,
+            })
+            .collect()
+        },
+        ViewLayoutPB::Grid | ViewLayoutPB::Board | ViewLayoutPB::Calendar => {
+          let database_manager = self.database_manager.upgrade().ok_or_else(|| {
+            FlowyError::internal().with_context("The database manager is dropped")
+          })?;
+          let database = database_manager
+            .get_database_editor_with_view_id(view_id)
+            .await?;
+          let mut blocks = vec![];
+          for row_id in database.get_row_ids().await {
+            let Some(data) = database.get_row_search_data(&row_id).await else {
+              continue;
+            };
+            // Each cell is labeled with its field, so the model knows what the values are
+            let text = data
+              .cells
+              .into_iter()
+              .filter(|cell| !cell.text.trim().is_empty())
+              .map(|cell| format!("{}: {}", cell.field_name, cell.text))
+              .collect::<Vec<_>>()
+              .join("\n");
+            if !text.is_empty() {
+              blocks.push(ViewContentBlock {
+                block_id: row_id.to_string(),
+                text,
+              });
+            }
+          }
+          blocks
+        },
+        _ => return Ok(None),
+      };
+
+    Ok(Some(ViewContent {
+      name: view.name,
+      blocks,
+    }))
+  }
 }
 
 struct ChatQueryServiceImpl {
@@ -56,6 +245,15 @@ impl AIQueryService for ChatQueryServiceImpl {
 
     Ok(ids)
   }
+
+  async fn query_chat_ids(&self) -> Result<Vec<String>, FlowyError> {
+    Ok(
+      self
+        .folder_query
+        .get_view_ids_with_view_layout(ViewLayout::Chat)
+        .await,
+    )
+  }
 }
 
 struct ChatUserServiceImpl(Weak<AuthenticateUser>);
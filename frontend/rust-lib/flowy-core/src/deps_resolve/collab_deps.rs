@@ -1,6 +1,5 @@
 use collab_entity::CollabType;
 use collab_integrate::{CollabSnapshot, PersistenceError, SnapshotPersistence};
-use diesel::dsl::count_star;
 use diesel::SqliteConnection;
 use flowy_error::FlowyError;
 use flowy_sqlite::{
@@ -11,9 +10,18 @@ use flowy_user::services::authenticate_user::AuthenticateUser;
 
 use collab_integrate::collab_builder::WorkspaceCollabIntegrate;
 use lib_infra::util::timestamp;
+use std::collections::HashSet;
 use std::sync::{Arc, Weak};
 use tracing::debug;
 
+const HOUR_IN_SECONDS: i64 = 60 * 60;
+const DAY_IN_SECONDS: i64 = 24 * HOUR_IN_SECONDS;
+
+/// Snapshots created within the last day are kept one per hour, snapshots created within the last
+/// month are kept one per day. Older snapshots are removed.
+const HOURLY_RETENTION: i64 = DAY_IN_SECONDS;
+const DAILY_RETENTION: i64 = 30 * DAY_IN_SECONDS;
+
 pub struct SnapshotDBImpl(pub Weak<AuthenticateUser>);
 
 impl SnapshotPersistence for SnapshotDBImpl {
@@ -52,7 +60,7 @@ impl SnapshotPersistence for SnapshotDBImpl {
 #[diesel(table_name = collab_snapshot)]
 pub(crate) struct CollabSnapshotRow {
   pub(crate) id: String,
-  object_id: String,
+  pub(crate) object_id: String,
   title: String,
   desc: String,
   collab_type: String,
@@ -109,29 +117,19 @@ impl CollabSnapshotSql {
         ))
         .execute(conn)?;
 
-      // Count the total number of snapshots for the specific object_id
-      let total_snapshots: i64 = dsl::collab_snapshot
+      // Apply the retention rules to the snapshots of the specific object_id
+      let snapshots: Vec<(String, i64)> = dsl::collab_snapshot
         .filter(dsl::object_id.eq(&row.object_id))
-        .select(count_star())
-        .first(conn)?;
-
-      // If there are more than 5 snapshots, delete the oldest one
-      if total_snapshots > 5 {
-        let ids_to_delete: Vec<String> = dsl::collab_snapshot
-          .filter(dsl::object_id.eq(&row.object_id))
-          .order(dsl::timestamp.asc())
-          .select(dsl::id)
-          .limit(1)
-          .load(conn)?;
-
+        .select((dsl::id, dsl::timestamp))
+        .load(conn)?;
+      let ids_to_delete = expired_snapshot_ids(snapshots, timestamp());
+      if !ids_to_delete.is_empty() {
         debug!(
           "Delete {} snapshots for object_id: {}",
           ids_to_delete.len(),
           row.object_id
         );
-        for id in ids_to_delete {
-          delete(dsl::collab_snapshot.filter(dsl::id.eq(id))).execute(conn)?;
-        }
+        delete(dsl::collab_snapshot.filter(dsl::id.eq_any(ids_to_delete))).execute(conn)?;
       }
 
       Ok(())
@@ -145,6 +143,7 @@ impl CollabSnapshotSql {
   ) -> Result<Vec<CollabSnapshotMeta>, FlowyError> {
     let results = collab_snapshot::table
       .filter(collab_snapshot::object_id.eq(object_id))
+      .order(collab_snapshot::timestamp.desc())
       .select((
         collab_snapshot::id,
         collab_snapshot::object_id,
@@ -166,6 +165,20 @@ impl CollabSnapshotSql {
     Ok(snapshots)
   }
 
+  /// Return the latest snapshots of the object, including the snapshot data.
+  pub(crate) fn get_latest_snapshots(
+    object_id: &str,
+    limit: usize,
+    conn: &mut SqliteConnection,
+  ) -> Result<Vec<CollabSnapshotRow>, FlowyError> {
+    let rows = dsl::collab_snapshot
+      .filter(dsl::object_id.eq(object_id))
+      .order(dsl::timestamp.desc())
+      .limit(limit as i64)
+      .load::<CollabSnapshotRow>(conn)?;
+    Ok(rows)
+  }
+
   pub(crate) fn get_snapshot(
     object_id: &str,
     conn: &mut SqliteConnection,
@@ -209,6 +222,32 @@ impl CollabSnapshotSql {
   }
 }
 
+/// Return the ids of the snapshots that are no longer covered by the retention rules. The newest
+/// snapshot of each hourly/daily bucket is kept.
+fn expired_snapshot_ids(mut snapshots: Vec<(String, i64)>, now: i64) -> Vec<String> {
+  snapshots.sort_by(|a, b| b.1.cmp(&a.1));
+  let mut kept_buckets = HashSet::new();
+  snapshots
+    .into_iter()
+    .filter_map(|(id, timestamp)| {
+      let age = now - timestamp;
+      let bucket = if age <= HOURLY_RETENTION {
+        (true, timestamp / HOUR_IN_SECONDS)
+      } else if age <= DAILY_RETENTION {
+        (false, timestamp / DAY_IN_SECONDS)
+      } else {
+        return Some(id);
+      };
+
+      if kept_buckets.insert(bucket) {
+        None
+      } else {
+        Some(id)
+      }
+    })
+    .collect()
+}
+
 pub(crate) struct WorkspaceCollabIntegrateImpl(pub Weak<AuthenticateUser>);
 
 impl WorkspaceCollabIntegrateImpl {
@@ -231,3 +270,26 @@ impl WorkspaceCollabIntegrate for WorkspaceCollabIntegrateImpl {
     Ok(self.upgrade_user()?.user_config.device_id.clone())
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn snapshot_retention_test() {
+    let now = 100 * DAY_IN_SECONDS;
+    let snapshots = vec![
+      ("a".to_string(), now - 10),
+      // same hour as "a"
+      ("b".to_string(), now - 20),
+      ("c".to_string(), now - 2 * HOUR_IN_SECONDS),
+      ("d".to_string(), now - 3 * DAY_IN_SECONDS + 20),
+      // same day as "d"
+      ("e".to_string(), now - 3 * DAY_IN_SECONDS + 10),
+      ("f".to_string(), now - 31 * DAY_IN_SECONDS),
+    ];
+    let mut expired = expired_snapshot_ids(snapshots, now);
+    expired.sort();
+    assert_eq!(expired, vec!["b", "e", "f"]);
+  }
+}
@@ -2,6 +2,7 @@ use appflowy_local_ai::ai_ops::{LocalAITranslateItem, LocalAITranslateRowData};
 use collab_integrate::collab_builder::AppFlowyCollabBuilder;
 use collab_integrate::CollabKVDB;
 use flowy_ai::ai_manager::AIManager;
+use flowy_ai::ai_usage::{AIUsageProvider, AIUsageRecorder, AIUsageType, APPFLOWY_CLOUD_MODEL};
 use flowy_database2::{DatabaseManager, DatabaseUser};
 use flowy_database_pub::cloud::{
   DatabaseAIService, DatabaseCloudService, SummaryRowContent, TranslateRowContent,
@@ -43,6 +44,38 @@ struct DatabaseAIServiceMiddleware {
   ai_manager: Arc<AIManager>,
   ai_service: Arc<dyn DatabaseAIService>,
 }
+
+impl DatabaseAIServiceMiddleware {
+  /// Returns an error if the workspace reached its AI usage limit, otherwise starts recording the
+  /// usage of the AI field.
+  fn start_usage(&self, workspace_id: &str, input: &str) -> Result<AIUsageRecorder, FlowyError> {
+    let usage_controller = &self.ai_manager.usage_controller;
+    usage_controller.check_limit(workspace_id)?;
+    let (provider, model) = if self.ai_manager.local_ai_controller.is_running() {
+      let model = self
+        .ai_manager
+        .local_ai_controller
+        .get_current_model()
+        .map(|model| model.chat_model.name)
+        .unwrap_or_default();
+      (AIUsageProvider::LocalAI, model)
+    } else {
+      (
+        AIUsageProvider::AppFlowyCloud,
+        APPFLOWY_CLOUD_MODEL.to_string(),
+      )
+    };
+    Ok(usage_controller.start(
+      workspace_id,
+      AIUsageType::AIField,
+      provider,
+      model,
+      "",
+      input,
+    ))
+  }
+}
+
 #[async_trait]
 impl DatabaseAIService for DatabaseAIServiceMiddleware {
   async fn summary_database_row(
@@ -51,7 +84,9 @@ impl DatabaseAIService for DatabaseAIServiceMiddleware {
     object_id: &str,
     summary_row: SummaryRowContent,
   ) -> Result<String, FlowyError> {
-    if self.ai_manager.local_ai_controller.is_running() {
+    let input = summary_row.values().cloned().collect::<Vec<_>>().join("\n");
+    let recorder = self.start_usage(workspace_id, &input)?;
+    let result = if self.ai_manager.local_ai_controller.is_running() {
       self
         .ai_manager
         .local_ai_controller
@@ -63,7 +98,9 @@ impl DatabaseAIService for DatabaseAIServiceMiddleware {
         .ai_service
         .summary_database_row(workspace_id, object_id, summary_row)
         .await
-    }
+    };
+    recorder.finish(&result, |summary| summary.clone());
+    result
   }
 
   async fn translate_database_row(
@@ -72,7 +109,14 @@ impl DatabaseAIService for DatabaseAIServiceMiddleware {
     translate_row: TranslateRowContent,
     language: &str,
   ) -> Result<TranslateRowResponse, FlowyError> {
-    if self.ai_manager.local_ai_controller.is_running() {
+    let input = translate_row
+      .iter()
+      .map(|item| format!("{}: {}", item.title, item.content))
+      .chain(std::iter::once(language.to_string()))
+      .collect::<Vec<_>>()
+      .join("\n");
+    let recorder = self.start_usage(workspace_id, &input)?;
+    let result = if self.ai_manager.local_ai_controller.is_running() {
       let data = LocalAITranslateRowData {
         cells: translate_row
           .into_iter()
@@ -89,15 +133,24 @@ impl DatabaseAIService for DatabaseAIServiceMiddleware {
         .local_ai_controller
         .translate_database_row(data)
         .await
-        .map_err(|err| FlowyError::local_ai().with_context(err))?;
+        .map_err(|err| FlowyError::local_ai().with_context(err));
 
-      Ok(TranslateRowResponse { items: resp.items })
+      resp.map(|resp| TranslateRowResponse { items: resp.items })
     } else {
       self
         .ai_service
         .translate_database_row(workspace_id, translate_row, language)
         .await
-    }
+    };
+    recorder.finish(&result, |resp| {
+      resp
+        .items
+        .iter()
+        .flat_map(|item| item.values().cloned())
+        .collect::<Vec<_>>()
+        .join("\n")
+    });
+    result
   }
 }
 
@@ -128,4 +181,14 @@ impl DatabaseUser for DatabaseUserImpl {
   fn workspace_database_object_id(&self) -> Result<String, FlowyError> {
     self.upgrade_user()?.workspace_database_object_id()
   }
+
+  fn get_workspace_database_object_id(
+    &self,
+    uid: i64,
+    workspace_id: &str,
+  ) -> Result<String, FlowyError> {
+    self
+      .upgrade_user()?
+      .get_workspace_database_object_id(uid, workspace_id)
+  }
 }
@@ -70,7 +70,7 @@ impl DocumentSnapshotService for DocumentSnapshotImpl {
     let mut db = authenticate_user.get_sqlite_connection(uid)?;
     CollabSnapshotSql::get_snapshot(snapshot_id, &mut db)
       .map(|row| DocumentSnapshotData {
-        object_id: row.id,
+        object_id: row.object_id,
         encoded_v1: row.data,
       })
       .ok_or(
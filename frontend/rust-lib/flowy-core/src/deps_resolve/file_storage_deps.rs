@@ -1,8 +1,16 @@
-use flowy_error::FlowyError;
+use flowy_database2::DatabaseManager;
+use flowy_document::manager::DocumentManager;
+use flowy_document::parser::document_files::{collect_urls, extract_file_urls};
+use flowy_error::{FlowyError, FlowyResult};
+use flowy_folder::entities::ViewLayoutPB;
+use flowy_folder::manager::FolderManager;
+use flowy_sqlite::kv::KVStorePreferences;
 use flowy_sqlite::DBConnection;
-use flowy_storage::manager::{StorageManager, StorageUserService};
+use flowy_storage::manager::{StorageFileReferenceService, StorageManager, StorageUserService};
 use flowy_storage_pub::cloud::StorageCloudService;
 use flowy_user::services::authenticate_user::AuthenticateUser;
+use lib_infra::async_trait::async_trait;
+use std::collections::HashSet;
 use std::sync::{Arc, Weak};
 
 pub struct FileStorageResolver;
@@ -12,12 +20,31 @@ impl FileStorageResolver {
     authenticate_user: Weak<AuthenticateUser>,
     cloud_service: Arc<dyn StorageCloudService>,
     root: &str,
+    store_preferences: Arc<KVStorePreferences>,
   ) -> Arc<StorageManager> {
     let user_service = FileStorageServiceImpl {
       user: authenticate_user,
       root_dir: root.to_owned(),
     };
-    Arc::new(StorageManager::new(cloud_service, Arc::new(user_service)))
+    Arc::new(StorageManager::new(
+      cloud_service,
+      Arc::new(user_service),
+      store_preferences,
+    ))
+  }
+
+  /// The orphaned files are found by collecting the files that the views of the workspace refer to.
+  pub fn register_file_reference_service(
+    storage_manager: &Arc<StorageManager>,
+    folder_manager: &Arc<FolderManager>,
+    document_manager: &Arc<DocumentManager>,
+    database_manager: &Arc<DatabaseManager>,
+  ) {
+    storage_manager.register_file_reference_service(Arc::new(FileReferenceServiceImpl {
+      folder_manager: Arc::downgrade(folder_manager),
+      document_manager: Arc::downgrade(document_manager),
+      database_manager: Arc::downgrade(database_manager),
+    }));
   }
 }
 
@@ -52,3 +79,63 @@ impl StorageUserService for FileStorageServiceImpl {
     &self.root_dir
   }
 }
+
+struct FileReferenceServiceImpl {
+  folder_manager: Weak<FolderManager>,
+  document_manager: Weak<DocumentManager>,
+  database_manager: Weak<DatabaseManager>,
+}
+
+#[async_trait]
+impl StorageFileReferenceService for FileReferenceServiceImpl {
+  async fn collect_file_urls(&self, workspace_id: &str) -> FlowyResult<HashSet<String>> {
+    let (Some(folder_manager), Some(document_manager), Some(database_manager)) = (
+      self.folder_manager.upgrade(),
+      self.document_manager.upgrade(),
+      self.database_manager.upgrade(),
+    ) else {
+      return Err(FlowyError::internal().with_context("The workspace is not available"));
+    };
+    if folder_manager.get_current_workspace().await?.id != workspace_id {
+      return Err(FlowyError::internal().with_context("The workspace was switched"));
+    }
+
+    let mut urls = vec![];
+    let mut doc_ids = vec![];
+    // The views in the trash are included, their files are needed when they are restored
+    for view in folder_manager.get_all_views_with_trash_pb().await? {
+      if let Some(icon) = view.icon {
+        urls.push(icon.value);
+      }
+      if let Some(extra) = view.extra.as_deref() {
+        if let Ok(extra) = serde_json::from_str(extra) {
+          collect_urls(&extra, &mut urls);
+        }
+      }
+      if view.layout == ViewLayoutPB::Document {
+        doc_ids.push(view.id);
+      }
+    }
+
+    for meta in database_manager.get_all_databases_meta().await {
+      // The editors that are opened only to collect the files are closed again
+      let references = database_manager
+        .with_database_editor(&meta.database_id, |database| async move {
+          Ok(database.get_row_file_references().await)
+        })
+        .await?;
+      urls.extend(references.urls);
+      doc_ids.extend(references.document_ids);
+    }
+
+    for doc_id in doc_ids {
+      match document_manager.get_document_data(&doc_id).await {
+        Ok(data) => urls.extend(extract_file_urls(&data)),
+        // The document of a row isn't created until the row is opened
+        Err(err) if err.is_record_not_found() => continue,
+        Err(err) => return Err(err),
+      }
+    }
+    Ok(urls.into_iter().collect())
+  }
+}
@@ -1,5 +1,6 @@
 use bytes::Bytes;
 
+use collab_database::database::DatabaseData;
 use collab_entity::{CollabType, EncodedCollab};
 use collab_folder::hierarchy_builder::NestedViewBuilder;
 use collab_integrate::collab_builder::AppFlowyCollabBuilder;
@@ -7,28 +8,38 @@ use collab_integrate::CollabKVDB;
 use flowy_ai::ai_manager::AIManager;
 use flowy_database2::entities::DatabaseLayoutPB;
 use flowy_database2::services::share::csv::CSVFormat;
-use flowy_database2::template::{make_default_board, make_default_calendar, make_default_grid};
+use flowy_database2::template::{
+  collect_database_template_ids, database_file_urls, make_default_board, make_default_calendar,
+  make_default_grid, remap_database_template_ids, replace_database_file_urls,
+};
 use flowy_database2::DatabaseManager;
 use flowy_document::entities::DocumentDataPB;
 use flowy_document::manager::DocumentManager;
 use flowy_document::parser::json::parser::JsonToDocumentParser;
+use flowy_document::parser::view_link::{extract_view_links, remap_view_ids, DocumentViewLinkType};
 use flowy_error::{FlowyError, FlowyResult};
 use flowy_folder::entities::{CreateViewParams, ViewLayoutPB};
 use flowy_folder::manager::{FolderManager, FolderUser};
 use flowy_folder::share::ImportType;
 use flowy_folder::view_operation::{
   DatabaseEncodedCollab, DocumentEncodedCollab, EncodedCollabType, FolderOperationHandler,
-  ImportedData, View, ViewData,
+  ImportedData, TemplateIdMap, TemplateViewData, View, ViewData, ViewLink, ViewLinkType,
 };
 use flowy_folder::ViewLayout;
+use flowy_search::database::indexer::DatabaseIndexManagerImpl;
+use flowy_search::document::indexer::DocumentIndexManagerImpl;
 use flowy_search::folder::indexer::FolderIndexManagerImpl;
 use flowy_sqlite::kv::KVStorePreferences;
+use flowy_sqlite::DBConnection;
+use flowy_storage_pub::storage::StorageService;
 use flowy_user::services::authenticate_user::AuthenticateUser;
 use flowy_user::services::data_import::{load_collab_by_object_id, load_collab_by_object_ids};
 use lib_dispatch::prelude::ToBytes;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
 use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
 use tokio::sync::RwLock;
 
 use crate::integrate::server::ServerProvider;
@@ -69,11 +80,23 @@ pub fn register_handlers(
   document_manager: Arc<DocumentManager>,
   database_manager: Arc<DatabaseManager>,
   chat_manager: Arc<AIManager>,
+  storage_service: Weak<dyn StorageService>,
+  document_indexer: Weak<DocumentIndexManagerImpl>,
+  database_indexer: Weak<DatabaseIndexManagerImpl>,
 ) {
-  let document_folder_operation = Arc::new(DocumentFolderOperation(document_manager));
+  let document_folder_operation = Arc::new(DocumentFolderOperation(
+    document_manager,
+    document_indexer,
+    Arc::downgrade(&chat_manager),
+  ));
   folder_manager.register_operation_handler(ViewLayout::Document, document_folder_operation);
 
-  let database_folder_operation = Arc::new(DatabaseFolderOperation(database_manager));
+  let database_folder_operation = Arc::new(DatabaseFolderOperation(
+    database_manager,
+    storage_service,
+    database_indexer,
+    Arc::downgrade(&chat_manager),
+  ));
   let chat_folder_operation = Arc::new(ChatFolderOperation(chat_manager));
   folder_manager.register_operation_handler(ViewLayout::Board, database_folder_operation.clone());
   folder_manager.register_operation_handler(ViewLayout::Grid, database_folder_operation.clone());
@@ -81,6 +104,98 @@ pub fn register_handlers(
   folder_manager.register_operation_handler(ViewLayout::Chat, chat_folder_operation);
 }
 
+/// Keep the view links of the documents up to date. The links of a document are re-computed
+/// shortly after its content was changed, so a burst of edits only triggers one update.
+pub fn subscribe_document_view_links(
+  folder_manager: Weak<FolderManager>,
+  document_manager: &DocumentManager,
+) {
+  let mut rx = document_manager.subscribe_document_changed();
+  tokio::spawn(async move {
+    loop {
+      let doc_id = match rx.recv().await {
+        Ok(doc_id) => doc_id,
+        Err(RecvError::Lagged(_)) => continue,
+        Err(RecvError::Closed) => break,
+      };
+
+      let mut changed_doc_ids = HashSet::from([doc_id]);
+      tokio::time::sleep(VIEW_LINK_INDEX_DELAY).await;
+      while let Ok(doc_id) = rx.try_recv() {
+        changed_doc_ids.insert(doc_id);
+      }
+
+      let Some(folder_manager) = folder_manager.upgrade() else {
+        break;
+      };
+      for doc_id in changed_doc_ids {
+        if let Err(err) = folder_manager.index_view_links(&doc_id).await {
+          tracing::warn!("Failed to index view links of {}: {}", doc_id, err);
+        }
+      }
+    }
+  });
+}
+
+/// Keep the relation links of the databases up to date. The links of a database are re-computed
+/// shortly after its rows or fields were changed, so a burst of edits only triggers one update.
+pub fn subscribe_database_view_links(
+  folder_manager: Weak<FolderManager>,
+  database_manager: &Arc<DatabaseManager>,
+) {
+  let mut row_rx = database_manager.subscribe_row_changed();
+  let mut field_rx = database_manager.subscribe_field_changed();
+  let weak_database_manager = Arc::downgrade(database_manager);
+  tokio::spawn(async move {
+    loop {
+      let database_id = tokio::select! {
+        changed = row_rx.recv() => match changed {
+          Ok(changed) => changed.database_id,
+          Err(RecvError::Lagged(_)) => continue,
+          Err(RecvError::Closed) => break,
+        },
+        changed = field_rx.recv() => match changed {
+          Ok(database_id) => database_id,
+          Err(RecvError::Lagged(_)) => continue,
+          Err(RecvError::Closed) => break,
+        },
+      };
+
+      let mut changed_database_ids = HashSet::from([database_id]);
+      tokio::time::sleep(VIEW_LINK_INDEX_DELAY).await;
+      while let Ok(changed) = row_rx.try_recv() {
+        changed_database_ids.insert(changed.database_id);
+      }
+      while let Ok(database_id) = field_rx.try_recv() {
+        changed_database_ids.insert(database_id);
+      }
+
+      let (Some(folder_manager), Some(database_manager)) =
+        (folder_manager.upgrade(), weak_database_manager.upgrade())
+      else {
+        break;
+      };
+      for database_id in changed_database_ids {
+        let result = match database_manager
+          .get_database_inline_view_id(&database_id)
+          .await
+        {
+          Ok(view_id) => folder_manager.index_view_links(&view_id).await,
+          Err(err) => Err(err),
+        };
+        if let Err(err) = result {
+          tracing::warn!("Failed to index view links of {}: {}", database_id, err);
+        }
+      }
+    }
+  });
+}
+
+const VIEW_LINK_INDEX_DELAY: Duration = Duration::from_secs(3);
+
+/// The meta key of a view that creates a database from a template.
+const TEMPLATE_DATABASE_KEY: &str = "template_database";
+
 struct FolderUserImpl {
   authenticate_user: Weak<AuthenticateUser>,
 }
@@ -108,12 +223,33 @@ impl FolderUser for FolderUserImpl {
     self.upgrade_user()?.get_collab_db(uid)
   }
 
+  fn sqlite_connection(&self, uid: i64) -> Result<DBConnection, FlowyError> {
+    self.upgrade_user()?.get_sqlite_connection(uid)
+  }
+
   fn is_folder_exist_on_disk(&self, uid: i64, workspace_id: &str) -> FlowyResult<bool> {
     self.upgrade_user()?.is_collab_on_disk(uid, workspace_id)
   }
+
+  fn is_user_workspace(&self, uid: i64, workspace_id: &str) -> FlowyResult<bool> {
+    self.upgrade_user()?.is_user_workspace(uid, workspace_id)
+  }
 }
 
-struct DocumentFolderOperation(Arc<DocumentManager>);
+/// The embeddings of the view are only used by the chats while the view is in the workspace.
+fn remove_view_embeddings(ai_manager: &Weak<AIManager>, view_id: &str) {
+  if let Some(ai_manager) = ai_manager.upgrade() {
+    if let Err(err) = ai_manager.remove_view_embeddings(&[view_id.to_string()]) {
+      tracing::error!("remove the embeddings of {} failed: {}", view_id, err);
+    }
+  }
+}
+
+struct DocumentFolderOperation(
+  Arc<DocumentManager>,
+  Weak<DocumentIndexManagerImpl>,
+  Weak<AIManager>,
+);
 #[async_trait]
 impl FolderOperationHandler for DocumentFolderOperation {
   async fn create_workspace_view(
@@ -160,6 +296,17 @@ impl FolderOperationHandler for DocumentFolderOperation {
       Ok(_) => tracing::trace!("Delete document: {}", view_id),
       Err(e) => tracing::error!("🔴delete document failed: {}", e),
     }
+    if let Some(document_indexer) = self.1.upgrade() {
+      if let Err(err) = document_indexer.remove_documents(vec![view_id.to_string()]) {
+        tracing::error!("remove the content index of {} failed: {}", view_id, err);
+      }
+    }
+    remove_view_embeddings(&self.2, view_id);
+    Ok(())
+  }
+
+  async fn did_move_view_to_trash(&self, view_id: &str) -> Result<(), FlowyError> {
+    remove_view_embeddings(&self.2, view_id);
     Ok(())
   }
 
@@ -169,6 +316,90 @@ impl FolderOperationHandler for DocumentFolderOperation {
     Ok(data_bytes)
   }
 
+  async fn view_data_from_snapshot(
+    &self,
+    view_id: &str,
+    snapshot_id: &str,
+  ) -> Result<Bytes, FlowyError> {
+    let snapshot = self.0.get_document_snapshot(snapshot_id).await?;
+    if snapshot.object_id != view_id {
+      return Err(FlowyError::invalid_data().with_context(format!(
+        "Snapshot {} doesn't belong to document {}",
+        snapshot_id, view_id
+      )));
+    }
+    let data: DocumentDataPB = self
+      .0
+      .document_data_from_snapshot(view_id, snapshot.encoded_v1)?
+      .into();
+    let data_bytes = data.into_bytes().map_err(|_| FlowyError::invalid_data())?;
+    Ok(data_bytes)
+  }
+
+  async fn export_template_data(&self, view_id: &str) -> Result<serde_json::Value, FlowyError> {
+    let data = self.0.get_document_data(view_id).await?;
+    Ok(serde_json::to_value(data)?)
+  }
+
+  async fn view_data_from_template(
+    &self,
+    data: serde_json::Value,
+    ids: &mut TemplateIdMap,
+  ) -> Result<TemplateViewData, FlowyError> {
+    let mut data = serde_json::from_value(data)?;
+    remap_view_ids(&mut data, &ids.view_ids);
+    let data = DocumentDataPB::from(data);
+    let data_bytes = data.into_bytes().map_err(|_| FlowyError::invalid_data())?;
+    Ok(data_bytes.into())
+  }
+
+  async fn prepare_transfer_data(
+    &self,
+    data: serde_json::Value,
+    target_workspace_id: &str,
+    view_id: &str,
+  ) -> Result<serde_json::Value, FlowyError> {
+    let mut data = serde_json::from_value(data)?;
+    self
+      .0
+      .copy_document_files(&mut data, target_workspace_id, view_id)
+      .await?;
+    Ok(serde_json::to_value(data)?)
+  }
+
+  async fn create_transfer_collabs(
+    &self,
+    _target_workspace_id: &str,
+    views: Vec<(String, serde_json::Value)>,
+    ids: &TemplateIdMap,
+  ) -> Result<Vec<ImportedData>, FlowyError> {
+    let mut collabs = vec![];
+    for (view_id, data) in views {
+      let mut data = serde_json::from_value(data)?;
+      remap_view_ids(&mut data, &ids.view_ids);
+      let encoded_collab = self.0.encode_document_data(&view_id, data).await?;
+      collabs.push((view_id, CollabType::Document, encoded_collab));
+    }
+    Ok(collabs)
+  }
+
+  async fn get_view_links(&self, view_id: &str) -> Result<Vec<ViewLink>, FlowyError> {
+    let data = self.0.get_document_data(view_id).await?;
+    let links = extract_view_links(&data)
+      .into_iter()
+      .map(|link| ViewLink {
+        target_view_id: link.view_id,
+        block_id: link.block_id,
+        link_type: match link.link_type {
+          DocumentViewLinkType::Mention => ViewLinkType::Mention,
+          DocumentViewLinkType::SubPage => ViewLinkType::SubPage,
+          DocumentViewLinkType::Database => ViewLinkType::DatabaseReference,
+        },
+      })
+      .collect();
+    Ok(links)
+  }
+
   async fn create_view_with_view_data(
     &self,
     user_id: i64,
@@ -285,7 +516,12 @@ impl FolderOperationHandler for DocumentFolderOperation {
   }
 }
 
-struct DatabaseFolderOperation(Arc<DatabaseManager>);
+struct DatabaseFolderOperation(
+  Arc<DatabaseManager>,
+  Weak<dyn StorageService>,
+  Weak<DatabaseIndexManagerImpl>,
+  Weak<AIManager>,
+);
 
 #[async_trait]
 impl FolderOperationHandler for DatabaseFolderOperation {
@@ -300,10 +536,32 @@ impl FolderOperationHandler for DatabaseFolderOperation {
   }
 
   async fn delete_view(&self, view_id: &str) -> Result<(), FlowyError> {
+    // The database is deleted with its inline view
+    let database_id = match self.0.get_database_id_with_view_id(view_id).await {
+      Ok(database_id) => self
+        .0
+        .get_database_inline_view_id(&database_id)
+        .await
+        .ok()
+        .filter(|inline_view_id| inline_view_id == view_id)
+        .map(|_| database_id),
+      Err(_) => None,
+    };
     match self.0.delete_database_view(view_id).await {
       Ok(_) => tracing::trace!("Delete database view: {}", view_id),
       Err(e) => tracing::error!("🔴delete database failed: {}", e),
     }
+    if let (Some(database_id), Some(database_indexer)) = (database_id, self.2.upgrade()) {
+      if let Err(err) = database_indexer.remove_database(&database_id) {
+        tracing::error!("remove the row index of {} failed: {}", database_id, err);
+      }
+    }
+    remove_view_embeddings(&self.3, view_id);
+    Ok(())
+  }
+
+  async fn did_move_view_to_trash(&self, view_id: &str) -> Result<(), FlowyError> {
+    remove_view_embeddings(&self.3, view_id);
     Ok(())
   }
 
@@ -417,6 +675,174 @@ impl FolderOperationHandler for DatabaseFolderOperation {
     Ok(Bytes::from(view_id.to_string()))
   }
 
+  /// Only the given view of the database is saved. The other views of the database are saved as
+  /// separated views if they are in the saved subtree.
+  async fn export_template_data(&self, view_id: &str) -> Result<serde_json::Value, FlowyError> {
+    let mut data = self.0.get_database_data(view_id).await?;
+    data.views.retain(|view| view.id == view_id);
+    Ok(serde_json::to_value(data)?)
+  }
+
+  fn collect_template_ids(
+    &self,
+    data: &serde_json::Value,
+    ids: &mut TemplateIdMap,
+  ) -> Result<(), FlowyError> {
+    let data: DatabaseData = serde_json::from_value(data.clone())?;
+    collect_database_template_ids(&data, &mut ids.object_ids);
+    Ok(())
+  }
+
+  /// The first view of a database in the template creates the database. The other views of the
+  /// same database are created as linked views of the copy.
+  async fn view_data_from_template(
+    &self,
+    data: serde_json::Value,
+    ids: &mut TemplateIdMap,
+  ) -> Result<TemplateViewData, FlowyError> {
+    let mut data: DatabaseData = serde_json::from_value(data)?;
+    remap_database_template_ids(&mut data, &ids.object_ids);
+    if !ids.created_object_ids.insert(data.database_id.clone()) {
+      return Ok(TemplateViewData {
+        initial_data: ViewData::Empty,
+        meta: HashMap::from([("database_id".to_string(), data.database_id)]),
+      });
+    }
+    Ok(TemplateViewData {
+      initial_data: ViewData::Data(Bytes::from(serde_json::to_vec(&data)?)),
+      meta: HashMap::from([(TEMPLATE_DATABASE_KEY.to_string(), "true".to_string())]),
+    })
+  }
+
+  /// Upload a copy of the files of the media cells to the target workspace.
+  async fn prepare_transfer_data(
+    &self,
+    data: serde_json::Value,
+    target_workspace_id: &str,
+    view_id: &str,
+  ) -> Result<serde_json::Value, FlowyError> {
+    let mut data: DatabaseData = serde_json::from_value(data)?;
+    let storage_service = self.1.upgrade().ok_or_else(|| {
+      FlowyError::internal().with_context("The file storage service is already dropped")
+    })?;
+    let mut urls = HashMap::new();
+    for url in database_file_urls(&data) {
+      if urls.contains_key(&url) {
+        continue;
+      }
+      if let Some(new_url) = storage_service
+        .copy_object(&url, target_workspace_id, view_id)
+        .await?
+      {
+        urls.insert(url, new_url);
+      }
+    }
+    replace_database_file_urls(&mut data, &urls);
+    Ok(serde_json::to_value(data)?)
+  }
+
+  /// The views of the same database are created as the views of one copy of the database, which
+  /// is added to the workspace database of the target workspace.
+  async fn create_transfer_collabs(
+    &self,
+    target_workspace_id: &str,
+    views: Vec<(String, serde_json::Value)>,
+    ids: &TemplateIdMap,
+  ) -> Result<Vec<ImportedData>, FlowyError> {
+    let mut databases: Vec<(DatabaseData, Vec<String>)> = vec![];
+    for (view_id, data) in views {
+      let mut data: DatabaseData = serde_json::from_value(data)?;
+      remap_database_template_ids(&mut data, &ids.object_ids);
+      match databases
+        .iter_mut()
+        .find(|(database, _)| database.database_id == data.database_id)
+      {
+        Some((database, view_ids)) => {
+          database.views.extend(data.views);
+          view_ids.push(view_id);
+        },
+        None => databases.push((data, vec![view_id])),
+      }
+    }
+
+    let mut collabs = vec![];
+    let mut view_ids_by_database_id = HashMap::new();
+    for (data, view_ids) in databases {
+      let database_id = data.database_id.clone();
+      let encoded_database = self.0.encode_database_with_ids(&view_ids, data).await?;
+      collabs.extend(
+        std::iter::once(encoded_database.encoded_database_collab)
+          .chain(encoded_database.encoded_row_collabs)
+          .map(|encoded| {
+            (
+              encoded.object_id,
+              encoded.collab_type,
+              encoded.encoded_collab,
+            )
+          }),
+      );
+      view_ids_by_database_id.insert(database_id, view_ids);
+    }
+    self
+      .0
+      .track_databases_in_workspace(target_workspace_id, view_ids_by_database_id)
+      .await?;
+    Ok(collabs)
+  }
+
+  /// The relations belong to the rows of the database, so they are the links of its inline view.
+  /// Each row that links to the rows of another database is a link to the inline view of that
+  /// database.
+  async fn get_view_links(&self, view_id: &str) -> Result<Vec<ViewLink>, FlowyError> {
+    let database = self.0.get_database_editor_with_view_id(view_id).await?;
+    if database.get_inline_view_id().await != view_id {
+      return Ok(vec![]);
+    }
+
+    let mut target_view_ids = HashMap::new();
+    let mut links = vec![];
+    for relation in database.get_row_relations().await {
+      if !target_view_ids.contains_key(&relation.related_database_id) {
+        let target_view_id = self
+          .0
+          .get_database_inline_view_id(&relation.related_database_id)
+          .await
+          .map_err(|err| {
+            tracing::warn!(
+              "Can't find the view of related database: {}, {}",
+              relation.related_database_id,
+              err
+            );
+          })
+          .ok();
+        target_view_ids.insert(relation.related_database_id.clone(), target_view_id);
+      }
+      if let Some(Some(target_view_id)) = target_view_ids.get(&relation.related_database_id) {
+        links.push(ViewLink {
+          target_view_id: target_view_id.clone(),
+          block_id: relation.row_id.to_string(),
+          link_type: ViewLinkType::DatabaseRelation,
+        });
+      }
+    }
+    Ok(links)
+  }
+
+  /// The restored database only contains the given view. The rows that were deleted after the
+  /// snapshot was taken can't be restored, because the rows are not part of the snapshot.
+  async fn view_data_from_snapshot(
+    &self,
+    view_id: &str,
+    snapshot_id: &str,
+  ) -> Result<Bytes, FlowyError> {
+    let mut data = self
+      .0
+      .get_database_data_from_snapshot(view_id, snapshot_id)
+      .await?;
+    data.views.retain(|view| view.id == view_id);
+    Ok(Bytes::from(serde_json::to_vec(&data)?))
+  }
+
   /// Create a database view with duplicated data.
   /// If the ext contains the {"database_id": "xx"}, then it will link
   /// to the existing database.
@@ -437,10 +863,19 @@ impl FolderOperationHandler for DatabaseFolderOperation {
           Ok(Some(encoded_collab))
         },
         ViewData::Data(data) => {
-          let encoded_collab = self
-            .0
-            .create_database_with_data(&params.view_id, data.to_vec())
-            .await?;
+          // The ids of a database created from a template are generated when the template is
+          // instantiated, so they are kept.
+          let encoded_collab = if params.meta.contains_key(TEMPLATE_DATABASE_KEY) {
+            self
+              .0
+              .create_database_with_ids(&params.view_id, data.to_vec())
+              .await?
+          } else {
+            self
+              .0
+              .create_database_with_data(&params.view_id, data.to_vec())
+              .await?
+          };
           Ok(Some(encoded_collab))
         },
         ViewData::Empty => Ok(None),
@@ -707,4 +1142,22 @@ impl FolderQueryService for FolderQueryServiceImpl {
       },
     }
   }
+
+  async fn get_view_ids_with_view_layout(&self, view_layout: ViewLayout) -> Vec<String> {
+    let Some(folder_manager) = self.folder_manager.upgrade() else {
+      return vec![];
+    };
+    let view_layout = ViewLayoutPB::from(view_layout);
+    match folder_manager.get_all_views_pb().await {
+      Ok(views) => views
+        .into_iter()
+        .filter(|view| view.layout == view_layout)
+        .map(|view| view.id)
+        .collect(),
+      Err(err) => {
+        tracing::error!("Failed to get all views: {}", err);
+        vec![]
+      },
+    }
+  }
 }
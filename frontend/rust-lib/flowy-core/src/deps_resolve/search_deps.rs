@@ -1,19 +1,504 @@
+use collab_database::rows::RowId;
+use flowy_database2::services::database::{RowSearchCell, RowSearchData};
+use flowy_database2::DatabaseManager;
+use flowy_document::manager::DocumentManager;
+use flowy_document::parser::document_text::extract_block_texts;
+use flowy_error::{FlowyError, FlowyResult};
+use flowy_folder::entities::ViewLayoutPB;
 use flowy_folder::manager::FolderManager;
+use flowy_search::database::entities::{DatabaseCellIndexData, DatabaseRowIndexData};
+use flowy_search::database::handler::DatabaseSearchHandler;
+use flowy_search::database::indexer::DatabaseIndexManagerImpl;
+use flowy_search::document::entities::DocumentBlockIndexData;
+use flowy_search::document::handler::DocumentSearchHandler;
+use flowy_search::document::indexer::DocumentIndexManagerImpl;
 use flowy_search::folder::handler::FolderSearchHandler;
 use flowy_search::folder::indexer::FolderIndexManagerImpl;
-use flowy_search::services::manager::SearchManager;
+use flowy_search::services::manager::{IndexRebuildProgress, SearchIndexRebuilder, SearchManager};
 use flowy_search_pub::cloud::SearchCloudService;
-use std::sync::Arc;
+use flowy_user::services::authenticate_user::AuthenticateUser;
+use lib_infra::async_trait::async_trait;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
 
 pub struct SearchDepsResolver();
 impl SearchDepsResolver {
+  #[allow(clippy::too_many_arguments)]
   pub async fn resolve(
+    authenticate_user: Weak<AuthenticateUser>,
     folder_indexer: Arc<FolderIndexManagerImpl>,
-    _cloud_service: Arc<dyn SearchCloudService>,
-    _folder_manager: Arc<FolderManager>,
+    document_indexer: Arc<DocumentIndexManagerImpl>,
+    database_indexer: Arc<DatabaseIndexManagerImpl>,
+    cloud_service: Arc<dyn SearchCloudService>,
+    folder_manager: Arc<FolderManager>,
+    document_manager: &Arc<DocumentManager>,
+    database_manager: &Arc<DatabaseManager>,
   ) -> Arc<SearchManager> {
+    let index_rebuilder = Arc::new(SearchIndexRebuilderImpl {
+      authenticate_user: authenticate_user.clone(),
+      folder_manager: Arc::downgrade(&folder_manager),
+      document_manager: Arc::downgrade(document_manager),
+      database_manager: Arc::downgrade(database_manager),
+      document_indexer: Arc::downgrade(&document_indexer),
+      database_indexer: Arc::downgrade(&database_indexer),
+    });
     let folder_handler = Arc::new(FolderSearchHandler::new(folder_indexer));
-    // let document_handler = Arc::new(DocumentSearchHandler::new(cloud_service, folder_manager));
-    Arc::new(SearchManager::new(vec![folder_handler]))
+    let document_handler = Arc::new(DocumentSearchHandler::new(
+      cloud_service,
+      folder_manager.clone(),
+      document_indexer,
+    ));
+    let database_handler = Arc::new(DatabaseSearchHandler::new(
+      folder_manager.clone(),
+      database_indexer,
+    ));
+    Arc::new(
+      SearchManager::new(
+        vec![folder_handler, document_handler, database_handler],
+        Arc::downgrade(&folder_manager),
+        authenticate_user,
+      )
+      .with_index_rebuilder(index_rebuilder),
+    )
   }
 }
+
+/// Indexes the views, the documents and the databases of the current workspace after the search
+/// indexes were rebuilt.
+struct SearchIndexRebuilderImpl {
+  authenticate_user: Weak<AuthenticateUser>,
+  folder_manager: Weak<FolderManager>,
+  document_manager: Weak<DocumentManager>,
+  database_manager: Weak<DatabaseManager>,
+  document_indexer: Weak<DocumentIndexManagerImpl>,
+  database_indexer: Weak<DatabaseIndexManagerImpl>,
+}
+
+#[async_trait]
+impl SearchIndexRebuilder for SearchIndexRebuilderImpl {
+  async fn index_workspace(&self, progress: IndexRebuildProgress) -> FlowyResult<u64> {
+    let (
+      Some(authenticate_user),
+      Some(folder_manager),
+      Some(document_manager),
+      Some(database_manager),
+      Some(document_indexer),
+      Some(database_indexer),
+    ) = (
+      self.authenticate_user.upgrade(),
+      self.folder_manager.upgrade(),
+      self.document_manager.upgrade(),
+      self.database_manager.upgrade(),
+      self.document_indexer.upgrade(),
+      self.database_indexer.upgrade(),
+    )
+    else {
+      return Err(FlowyError::internal().with_context("The workspace is not available"));
+    };
+    let workspace_id = authenticate_user.workspace_id()?;
+
+    let doc_ids = folder_manager
+      .get_all_views_pb()
+      .await?
+      .into_iter()
+      .filter(|view| view.layout == ViewLayoutPB::Document)
+      .map(|view| view.id)
+      .collect::<Vec<_>>();
+    let database_ids = database_manager
+      .get_all_databases_meta()
+      .await
+      .into_iter()
+      .map(|meta| meta.database_id)
+      .collect::<Vec<_>>();
+
+    // All the views are indexed at once, each document and database is indexed separately
+    let mut indexed = folder_manager.index_all_views().await? as u64;
+    let total = indexed + (doc_ids.len() + database_ids.len()) as u64;
+    progress(indexed, total);
+
+    for doc_id in doc_ids {
+      if let Err(err) =
+        index_document_content(&document_indexer, &document_manager, &doc_id, &workspace_id).await
+      {
+        tracing::warn!("Failed to index the content of {}: {}", doc_id, err);
+      }
+      indexed += 1;
+      progress(indexed, total);
+    }
+    for database_id in database_ids {
+      if let Err(err) = index_database_rows(
+        &database_indexer,
+        &database_manager,
+        &database_id,
+        &workspace_id,
+      )
+      .await
+      {
+        tracing::warn!("Failed to index the rows of {}: {}", database_id, err);
+      }
+      indexed += 1;
+      progress(indexed, total);
+    }
+    Ok(indexed)
+  }
+}
+
+/// Keep the content index of the documents up to date. Like the view links, a document is
+/// re-indexed shortly after its content was changed, so a burst of edits only triggers one update.
+pub fn subscribe_document_content_index(
+  document_indexer: Weak<DocumentIndexManagerImpl>,
+  authenticate_user: Weak<AuthenticateUser>,
+  document_manager: &Arc<DocumentManager>,
+) {
+  let mut rx = document_manager.subscribe_document_changed();
+  let weak_document_manager = Arc::downgrade(document_manager);
+  tokio::spawn(async move {
+    loop {
+      let doc_id = match rx.recv().await {
+        Ok(doc_id) => doc_id,
+        Err(RecvError::Lagged(_)) => continue,
+        Err(RecvError::Closed) => break,
+      };
+
+      let mut changed_doc_ids = HashSet::from([doc_id]);
+      tokio::time::sleep(DOCUMENT_CONTENT_INDEX_DELAY).await;
+      while let Ok(doc_id) = rx.try_recv() {
+        changed_doc_ids.insert(doc_id);
+      }
+
+      let (Some(document_indexer), Some(document_manager), Some(authenticate_user)) = (
+        document_indexer.upgrade(),
+        weak_document_manager.upgrade(),
+        authenticate_user.upgrade(),
+      ) else {
+        break;
+      };
+      let Ok(workspace_id) = authenticate_user.workspace_id() else {
+        continue;
+      };
+      for doc_id in changed_doc_ids {
+        if let Err(err) =
+          index_document_content(&document_indexer, &document_manager, &doc_id, &workspace_id).await
+        {
+          tracing::warn!("Failed to index the content of {}: {}", doc_id, err);
+        }
+      }
+    }
+  });
+}
+
+/// Index the content of the documents of the workspace that are not in the index yet. The
+/// documents that were never opened on this device, or were indexed before the index existed,
+/// are only searchable after this.
+pub fn index_workspace_documents(
+  document_indexer: Weak<DocumentIndexManagerImpl>,
+  folder_manager: Weak<FolderManager>,
+  document_manager: Weak<DocumentManager>,
+  workspace_id: String,
+) {
+  tokio::spawn(async move {
+    let (Some(document_indexer), Some(folder_manager), Some(document_manager)) = (
+      document_indexer.upgrade(),
+      folder_manager.upgrade(),
+      document_manager.upgrade(),
+    ) else {
+      return;
+    };
+    let views = match folder_manager.get_all_views_pb().await {
+      Ok(views) => views,
+      Err(err) => {
+        tracing::warn!("Failed to get the views to index: {}", err);
+        return;
+      },
+    };
+
+    for view in views {
+      if view.layout != ViewLayoutPB::Document || document_indexer.is_document_indexed(&view.id) {
+        continue;
+      }
+      if let Err(err) = index_document_content(
+        &document_indexer,
+        &document_manager,
+        &view.id,
+        &workspace_id,
+      )
+      .await
+      {
+        tracing::warn!("Failed to index the content of {}: {}", view.id, err);
+      }
+    }
+  });
+}
+
+async fn index_document_content(
+  document_indexer: &DocumentIndexManagerImpl,
+  document_manager: &DocumentManager,
+  doc_id: &str,
+  workspace_id: &str,
+) -> FlowyResult<()> {
+  let data = document_manager.get_document_data(doc_id).await?;
+  let blocks = extract_block_texts(&data)
+    .into_iter()
+    .map(|text| DocumentBlockIndexData {
+      block_id: text.block_id,
+      content: text.text,
+    })
+    .collect();
+  document_indexer.index_document(doc_id, workspace_id, blocks)
+}
+
+const DOCUMENT_CONTENT_INDEX_DELAY: Duration = Duration::from_secs(3);
+
+/// Keep the row index of the databases up to date. The changed rows are collected for a short
+/// while, so editing a cell only triggers one update of its row.
+pub fn subscribe_database_row_index(
+  database_indexer: Weak<DatabaseIndexManagerImpl>,
+  authenticate_user: Weak<AuthenticateUser>,
+  database_manager: &Arc<DatabaseManager>,
+) {
+  let mut rx = database_manager.subscribe_row_changed();
+  let weak_database_manager = Arc::downgrade(database_manager);
+  tokio::spawn(async move {
+    loop {
+      let changed = match rx.recv().await {
+        Ok(changed) => changed,
+        Err(RecvError::Lagged(_)) => continue,
+        Err(RecvError::Closed) => break,
+      };
+
+      let mut changed_rows = HashSet::from([(changed.database_id, changed.row_id)]);
+      tokio::time::sleep(DATABASE_ROW_INDEX_DELAY).await;
+      while let Ok(changed) = rx.try_recv() {
+        changed_rows.insert((changed.database_id, changed.row_id));
+      }
+
+      let (Some(database_indexer), Some(database_manager), Some(authenticate_user)) = (
+        database_indexer.upgrade(),
+        weak_database_manager.upgrade(),
+        authenticate_user.upgrade(),
+      ) else {
+        break;
+      };
+      let Ok(workspace_id) = authenticate_user.workspace_id() else {
+        continue;
+      };
+      for (database_id, row_id) in changed_rows {
+        if let Err(err) = index_database_row(
+          &database_indexer,
+          &database_manager,
+          &database_id,
+          &row_id,
+          &workspace_id,
+        )
+        .await
+        {
+          tracing::warn!("Failed to index the row {}: {}", row_id, err);
+        }
+      }
+    }
+  });
+}
+
+/// Re-index the rows of the databases whose searchable fields were renamed, removed or changed
+/// their type. The version of every row changes with its fields, so all the rows are updated and
+/// the cells of a removed field are dropped from the index.
+pub fn subscribe_database_field_index(
+  database_indexer: Weak<DatabaseIndexManagerImpl>,
+  authenticate_user: Weak<AuthenticateUser>,
+  database_manager: &Arc<DatabaseManager>,
+) {
+  let mut rx = database_manager.subscribe_field_changed();
+  let weak_database_manager = Arc::downgrade(database_manager);
+  tokio::spawn(async move {
+    loop {
+      let database_id = match rx.recv().await {
+        Ok(database_id) => database_id,
+        Err(RecvError::Lagged(_)) => continue,
+        Err(RecvError::Closed) => break,
+      };
+
+      let mut changed_database_ids = HashSet::from([database_id]);
+      tokio::time::sleep(DATABASE_ROW_INDEX_DELAY).await;
+      while let Ok(database_id) = rx.try_recv() {
+        changed_database_ids.insert(database_id);
+      }
+
+      let (Some(database_indexer), Some(database_manager), Some(authenticate_user)) = (
+        database_indexer.upgrade(),
+        weak_database_manager.upgrade(),
+        authenticate_user.upgrade(),
+      ) else {
+        break;
+      };
+      let Ok(workspace_id) = authenticate_user.workspace_id() else {
+        continue;
+      };
+      let mut versions = match database_indexer.get_row_versions(&workspace_id) {
+        Ok(versions) => versions,
+        Err(err) => {
+          tracing::warn!("Failed to get the indexed rows: {}", err);
+          continue;
+        },
+      };
+      for database_id in changed_database_ids {
+        let indexed_versions = versions.remove(&database_id).unwrap_or_default();
+        if let Err(err) = update_database_rows(
+          &database_indexer,
+          &database_manager,
+          &database_id,
+          &workspace_id,
+          indexed_versions,
+        )
+        .await
+        {
+          tracing::warn!("Failed to index the rows of {}: {}", database_id, err);
+        }
+      }
+    }
+  });
+}
+
+/// Bring the row index of the databases of the workspace up to date. Only the rows whose version
+/// differs from the indexed version are indexed again, the rows that are no longer in their
+/// database and the databases that were removed are dropped from the index.
+pub fn index_workspace_databases(
+  database_indexer: Weak<DatabaseIndexManagerImpl>,
+  database_manager: Weak<DatabaseManager>,
+  workspace_id: String,
+) {
+  tokio::spawn(async move {
+    let (Some(database_indexer), Some(database_manager)) =
+      (database_indexer.upgrade(), database_manager.upgrade())
+    else {
+      return;
+    };
+    let mut versions = match database_indexer.get_row_versions(&workspace_id) {
+      Ok(versions) => versions,
+      Err(err) => {
+        tracing::warn!("Failed to get the indexed rows: {}", err);
+        return;
+      },
+    };
+
+    for meta in database_manager.get_all_databases_meta().await {
+      let indexed_versions = versions.remove(&meta.database_id).unwrap_or_default();
+      if let Err(err) = update_database_rows(
+        &database_indexer,
+        &database_manager,
+        &meta.database_id,
+        &workspace_id,
+        indexed_versions,
+      )
+      .await
+      {
+        tracing::warn!("Failed to index the rows of {}: {}", meta.database_id, err);
+      }
+    }
+
+    // The remaining databases were deleted while the index wasn't observing them
+    for database_id in versions.into_keys() {
+      if let Err(err) = database_indexer.remove_database(&database_id) {
+        tracing::warn!("Failed to remove the rows of {}: {}", database_id, err);
+      }
+    }
+  });
+}
+
+async fn index_database_row(
+  database_indexer: &DatabaseIndexManagerImpl,
+  database_manager: &DatabaseManager,
+  database_id: &str,
+  row_id: &RowId,
+  workspace_id: &str,
+) -> FlowyResult<()> {
+  database_manager
+    .with_database_editor(database_id, |database| async move {
+      match database.get_row_search_data(row_id).await {
+        Some(data) => database_indexer.index_row(
+          database_id,
+          &database.get_inline_view_id().await,
+          workspace_id,
+          row_index_data(row_id.to_string(), data),
+        ),
+        // The row was removed from the database
+        None => database_indexer.remove_rows(vec![row_id.to_string()]),
+      }
+    })
+    .await
+}
+
+/// Index the rows of the database whose version differs from the given indexed versions and
+/// remove the indexed rows that are no longer in the database.
+async fn update_database_rows(
+  database_indexer: &DatabaseIndexManagerImpl,
+  database_manager: &DatabaseManager,
+  database_id: &str,
+  workspace_id: &str,
+  mut indexed_versions: HashMap<String, String>,
+) -> FlowyResult<()> {
+  database_manager
+    .with_database_editor(database_id, |database| async move {
+      let mut rows = vec![];
+      for row_id in database.get_row_ids().await {
+        if let Some(data) = database.get_row_search_data(&row_id).await {
+          let indexed_version = indexed_versions.remove(row_id.as_str());
+          if indexed_version.as_ref() != Some(&data.version) {
+            rows.push(row_index_data(row_id.to_string(), data));
+          }
+        }
+      }
+      database_indexer.update_rows(
+        database_id,
+        &database.get_inline_view_id().await,
+        workspace_id,
+        rows,
+        indexed_versions.into_keys().collect(),
+      )
+    })
+    .await
+}
+
+async fn index_database_rows(
+  database_indexer: &DatabaseIndexManagerImpl,
+  database_manager: &DatabaseManager,
+  database_id: &str,
+  workspace_id: &str,
+) -> FlowyResult<()> {
+  database_manager
+    .with_database_editor(database_id, |database| async move {
+      let mut rows = vec![];
+      for row_id in database.get_row_ids().await {
+        if let Some(data) = database.get_row_search_data(&row_id).await {
+          rows.push(row_index_data(row_id.to_string(), data));
+        }
+      }
+      database_indexer.index_database(
+        database_id,
+        &database.get_inline_view_id().await,
+        workspace_id,
+        rows,
+      )
+    })
+    .await
+}
+
+fn row_index_data(row_id: String, data: RowSearchData) -> DatabaseRowIndexData {
+  DatabaseRowIndexData {
+    row_id,
+    version: data.version,
+    cells: data.cells.into_iter().map(cell_index_data).collect(),
+  }
+}
+
+fn cell_index_data(cell: RowSearchCell) -> DatabaseCellIndexData {
+  DatabaseCellIndexData {
+    field_id: cell.field_id,
+    field_name: cell.field_name,
+    is_primary: cell.is_primary,
+    content: cell.text,
+  }
+}
+
+const DATABASE_ROW_INDEX_DELAY: Duration = Duration::from_secs(1);
@@ -5,6 +5,8 @@ use flowy_database2::DatabaseManager;
 use flowy_error::FlowyResult;
 use flowy_folder::manager::FolderManager;
 use flowy_folder_pub::entities::ImportFrom;
+use flowy_search::database::indexer::DatabaseIndexManagerImpl;
+use flowy_search::document::indexer::DocumentIndexManagerImpl;
 use flowy_sqlite::kv::KVStorePreferences;
 use flowy_user::services::authenticate_user::AuthenticateUser;
 use flowy_user::user_manager::UserManager;
@@ -24,10 +26,14 @@ impl UserDepsResolver {
     store_preference: Arc<KVStorePreferences>,
     database_manager: Arc<DatabaseManager>,
     folder_manager: Arc<FolderManager>,
+    document_indexer: Arc<DocumentIndexManagerImpl>,
+    database_indexer: Arc<DatabaseIndexManagerImpl>,
   ) -> Arc<UserManager> {
     let workspace_service_impl = Arc::new(UserWorkspaceServiceImpl {
       database_manager,
       folder_manager,
+      document_indexer,
+      database_indexer,
     });
     UserManager::new(
       server_provider,
@@ -42,6 +48,8 @@ impl UserDepsResolver {
 pub struct UserWorkspaceServiceImpl {
   pub database_manager: Arc<DatabaseManager>,
   pub folder_manager: Arc<FolderManager>,
+  pub document_indexer: Arc<DocumentIndexManagerImpl>,
+  pub database_indexer: Arc<DatabaseIndexManagerImpl>,
 }
 
 #[async_trait]
@@ -84,6 +92,18 @@ impl UserWorkspaceService for UserWorkspaceServiceImpl {
   fn did_delete_workspace(&self, workspace_id: String) -> FlowyResult<()> {
     // The remove_indices_for_workspace should not block the deletion of the workspace
     // Log the error and continue
+    if let Err(err) = self
+      .document_indexer
+      .remove_indices_for_workspace(&workspace_id)
+    {
+      info!("Error removing document indices for workspace: {}", err);
+    }
+    if let Err(err) = self
+      .database_indexer
+      .remove_indices_for_workspace(&workspace_id)
+    {
+      info!("Error removing database indices for workspace: {}", err);
+    }
     if let Err(err) = self
       .folder_manager
       .remove_indices_for_workspace(workspace_id)
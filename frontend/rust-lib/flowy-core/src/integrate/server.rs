@@ -1,6 +1,7 @@
 use arc_swap::ArcSwapOption;
 use dashmap::DashMap;
 use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
 use std::sync::{Arc, Weak};
 
@@ -9,12 +10,16 @@ use serde_repr::*;
 use flowy_error::{FlowyError, FlowyResult};
 use flowy_server::af_cloud::define::ServerUser;
 use flowy_server::af_cloud::AppFlowyCloudServer;
-use flowy_server::local_server::{LocalServer, LocalServerDB};
+use flowy_server::local_server::{LocalCollabSnapshot, LocalServer, LocalServerDB};
+use flowy_server::s3::S3FileStorageServiceImpl;
 use flowy_server::{AppFlowyEncryption, AppFlowyServer, EncryptionImpl};
 use flowy_server_pub::AuthenticatorType;
 use flowy_sqlite::kv::KVStorePreferences;
+use flowy_storage_pub::cloud::StorageCloudService;
+use flowy_user::services::authenticate_user::AuthenticateUser;
 use flowy_user_pub::entities::*;
 
+use crate::deps_resolve::CollabSnapshotSql;
 use crate::AppFlowyCoreConfig;
 
 #[derive(Debug, Clone, Hash, Eq, PartialEq, Serialize_repr, Deserialize_repr)]
@@ -58,6 +63,7 @@ pub struct ServerProvider {
   /// The authenticator type of the user.
   authenticator: AtomicU8,
   user: Arc<dyn ServerUser>,
+  authenticate_user: Weak<AuthenticateUser>,
   pub(crate) uid: Arc<ArcSwapOption<i64>>,
 }
 
@@ -67,6 +73,7 @@ impl ServerProvider {
     server: Server,
     store_preferences: Weak<KVStorePreferences>,
     server_user: impl ServerUser + 'static,
+    authenticate_user: Weak<AuthenticateUser>,
   ) -> Self {
     let user = Arc::new(server_user);
     let encryption = EncryptionImpl::new(None);
@@ -79,6 +86,7 @@ impl ServerProvider {
       store_preferences,
       uid: Default::default(),
       user,
+      authenticate_user,
     }
   }
 
@@ -101,6 +109,25 @@ impl ServerProvider {
     }
   }
 
+  /// Returns true if the files are stored in the user data folder instead of a remote storage.
+  pub fn is_file_storage_on_device(&self) -> bool {
+    self.get_server_type().is_local() && self.config.s3_config.is_none()
+  }
+
+  /// Returns the file storage of the current server. The local server has no file storage when
+  /// the S3 configuration is invalid, and the error of the configuration is returned instead.
+  pub(crate) fn get_file_storage(&self) -> FlowyResult<Arc<dyn StorageCloudService>> {
+    self.get_server()?.file_storage().ok_or_else(|| {
+      self
+        .config
+        .s3_config
+        .clone()
+        .filter(|_| self.get_server_type().is_local())
+        .and_then(|config| S3FileStorageServiceImpl::new(config).err())
+        .unwrap_or_else(FlowyError::internal)
+    })
+  }
+
   pub fn get_authenticator(&self) -> Authenticator {
     Authenticator::from(self.authenticator.load(Ordering::Acquire) as i32)
   }
@@ -117,8 +144,9 @@ impl ServerProvider {
       Server::Local => {
         let local_db = Arc::new(LocalServerDBImpl {
           storage_path: self.config.storage_path.clone(),
+          authenticate_user: self.authenticate_user.clone(),
         });
-        let server = Arc::new(LocalServer::new(local_db));
+        let server = Arc::new(LocalServer::new(local_db, self.config.s3_config.clone()));
         Ok::<Arc<dyn AppFlowyServer>, FlowyError>(server)
       },
       Server::AppFlowyCloud => {
@@ -175,6 +203,7 @@ pub fn current_server_type() -> Server {
 struct LocalServerDBImpl {
   #[allow(dead_code)]
   storage_path: String,
+  authenticate_user: Weak<AuthenticateUser>,
 }
 
 impl LocalServerDB for LocalServerDBImpl {
@@ -191,4 +220,55 @@ impl LocalServerDB for LocalServerDBImpl {
         .with_context("LocalServer doesn't support get_user_workspace"),
     )
   }
+
+  fn get_collab_snapshots(
+    &self,
+    object_id: &str,
+    limit: usize,
+  ) -> Result<Vec<LocalCollabSnapshot>, FlowyError> {
+    let authenticate_user = self
+      .authenticate_user
+      .upgrade()
+      .ok_or(FlowyError::internal().with_context("Unexpected error: UserSession is None"))?;
+    let uid = authenticate_user.user_id()?;
+    let mut conn = authenticate_user.get_sqlite_connection(uid)?;
+    let snapshots = CollabSnapshotSql::get_latest_snapshots(object_id, limit, &mut conn)?
+      .into_iter()
+      .map(|row| LocalCollabSnapshot {
+        snapshot_id: row.id,
+        object_id: row.object_id,
+        data: row.data,
+        created_at: row.timestamp,
+      })
+      .collect();
+    Ok(snapshots)
+  }
+
+  fn get_collab_snapshot(
+    &self,
+    snapshot_id: &str,
+  ) -> Result<Option<LocalCollabSnapshot>, FlowyError> {
+    let authenticate_user = self
+      .authenticate_user
+      .upgrade()
+      .ok_or(FlowyError::internal().with_context("Unexpected error: UserSession is None"))?;
+    let uid = authenticate_user.user_id()?;
+    let mut conn = authenticate_user.get_sqlite_connection(uid)?;
+    let snapshot =
+      CollabSnapshotSql::get_snapshot(snapshot_id, &mut conn).map(|row| LocalCollabSnapshot {
+        snapshot_id: row.id,
+        object_id: row.object_id,
+        data: row.data,
+        created_at: row.timestamp,
+      });
+    Ok(snapshot)
+  }
+
+  fn get_user_data_dir(&self) -> Result<PathBuf, FlowyError> {
+    let authenticate_user = self
+      .authenticate_user
+      .upgrade()
+      .ok_or(FlowyError::internal().with_context("Unexpected error: UserSession is None"))?;
+    authenticate_user.get_user_data_dir()
+  }
 }
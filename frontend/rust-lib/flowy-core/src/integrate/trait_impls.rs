@@ -37,7 +37,7 @@ use flowy_folder_pub::cloud::{
 };
 use flowy_folder_pub::entities::PublishPayload;
 use flowy_server_pub::af_cloud_config::AFCloudConfiguration;
-use flowy_storage_pub::cloud::{ObjectIdentity, ObjectValue, StorageCloudService};
+use flowy_storage_pub::cloud::{ObjectIdentity, ObjectRange, ObjectValue, StorageCloudService};
 use flowy_storage_pub::storage::{CompletedPartRequest, CreateUploadResponse, UploadPartResponse};
 use flowy_user_pub::cloud::{UserCloudService, UserCloudServiceProvider};
 use flowy_user_pub::entities::{Authenticator, UserTokenState};
@@ -48,57 +48,54 @@ use crate::integrate::server::{Server, ServerProvider};
 #[async_trait]
 impl StorageCloudService for ServerProvider {
   async fn get_object_url(&self, object_id: ObjectIdentity) -> Result<String, FlowyError> {
-    let storage = self
-      .get_server()?
-      .file_storage()
-      .ok_or(FlowyError::internal())?;
+    let storage = self.get_file_storage()?;
     storage.get_object_url(object_id).await
   }
 
   async fn put_object(&self, url: String, val: ObjectValue) -> Result<(), FlowyError> {
-    let storage = self
-      .get_server()?
-      .file_storage()
-      .ok_or(FlowyError::internal())?;
+    let storage = self.get_file_storage()?;
     storage.put_object(url, val).await
   }
 
   async fn delete_object(&self, url: &str) -> Result<(), FlowyError> {
-    let storage = self
-      .get_server()?
-      .file_storage()
-      .ok_or(FlowyError::internal())?;
+    let storage = self.get_file_storage()?;
     storage.delete_object(url).await
   }
 
   async fn get_object(&self, url: String) -> Result<ObjectValue, FlowyError> {
-    let storage = self
-      .get_server()?
-      .file_storage()
-      .ok_or(FlowyError::internal())?;
+    let storage = self.get_file_storage()?;
     storage.get_object(url).await
   }
 
+  async fn get_download_url(&self, url: &str) -> Result<String, FlowyError> {
+    let storage = self.get_file_storage()?;
+    storage.get_download_url(url).await
+  }
+
+  async fn get_object_range(
+    &self,
+    url: String,
+    offset: u64,
+    length: u64,
+  ) -> Result<ObjectRange, FlowyError> {
+    let storage = self.get_file_storage()?;
+    storage.get_object_range(url, offset, length).await
+  }
+
   async fn get_object_url_v1(
     &self,
     workspace_id: &str,
     parent_dir: &str,
     file_id: &str,
   ) -> FlowyResult<String> {
-    let server = self.get_server()?;
-    let storage = server.file_storage().ok_or(FlowyError::internal())?;
+    let storage = self.get_file_storage()?;
     storage
       .get_object_url_v1(workspace_id, parent_dir, file_id)
       .await
   }
 
   async fn parse_object_url_v1(&self, url: &str) -> Option<(String, String, String)> {
-    self
-      .get_server()
-      .ok()?
-      .file_storage()?
-      .parse_object_url_v1(url)
-      .await
+    self.get_file_storage().ok()?.parse_object_url_v1(url).await
   }
 
   async fn create_upload(
@@ -109,8 +106,7 @@ impl StorageCloudService for ServerProvider {
     content_type: &str,
     file_size: u64,
   ) -> Result<CreateUploadResponse, FlowyError> {
-    let server = self.get_server()?;
-    let storage = server.file_storage().ok_or(FlowyError::internal())?;
+    let storage = self.get_file_storage()?;
     storage
       .create_upload(workspace_id, parent_dir, file_id, content_type, file_size)
       .await
@@ -125,8 +121,7 @@ impl StorageCloudService for ServerProvider {
     part_number: i32,
     body: Vec<u8>,
   ) -> Result<UploadPartResponse, FlowyError> {
-    let server = self.get_server();
-    let storage = server?.file_storage().ok_or(FlowyError::internal())?;
+    let storage = self.get_file_storage()?;
     storage
       .upload_part(
         workspace_id,
@@ -147,8 +142,7 @@ impl StorageCloudService for ServerProvider {
     file_id: &str,
     parts: Vec<CompletedPartRequest>,
   ) -> Result<(), FlowyError> {
-    let server = self.get_server();
-    let storage = server?.file_storage().ok_or(FlowyError::internal())?;
+    let storage = self.get_file_storage()?;
     storage
       .complete_upload(workspace_id, parent_dir, upload_id, file_id, parts)
       .await
@@ -269,6 +263,19 @@ impl FolderCloudService for ServerProvider {
       .await
   }
 
+  async fn get_folder_snapshot(
+    &self,
+    workspace_id: &str,
+    snapshot_id: &str,
+  ) -> Result<Option<FolderSnapshot>, FlowyError> {
+    let server = self.get_server()?;
+
+    server
+      .folder_service()
+      .get_folder_snapshot(workspace_id, snapshot_id)
+      .await
+  }
+
   async fn get_folder_doc_state(
     &self,
     workspace_id: &str,
@@ -478,6 +485,19 @@ impl DatabaseCloudService for ServerProvider {
       .get_database_collab_object_snapshots(&database_id, limit)
       .await
   }
+
+  async fn get_database_collab_object_snapshot(
+    &self,
+    object_id: &str,
+    snapshot_id: &str,
+  ) -> Result<Option<DatabaseSnapshot>, FlowyError> {
+    let server = self.get_server()?;
+
+    server
+      .database_service()
+      .get_database_collab_object_snapshot(object_id, snapshot_id)
+      .await
+  }
 }
 
 #[async_trait]
@@ -756,6 +776,19 @@ impl ChatCloudService for ServerProvider {
       .await
   }
 
+  async fn stream_complete_with_prompt(
+    &self,
+    workspace_id: &str,
+    text: &str,
+    prompt: &str,
+  ) -> Result<StreamComplete, FlowyError> {
+    let server = self.get_server()?;
+    server
+      .chat_service()
+      .stream_complete_with_prompt(workspace_id, text, prompt)
+      .await
+  }
+
   async fn index_file(
     &self,
     workspace_id: &str,
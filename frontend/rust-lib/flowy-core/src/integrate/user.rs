@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
 
 use anyhow::Context;
 use client_api::entity::billing_dto::SubscriptionPlan;
@@ -11,12 +11,17 @@ use flowy_database2::DatabaseManager;
 use flowy_document::manager::DocumentManager;
 use flowy_error::FlowyResult;
 use flowy_folder::manager::{FolderInitDataSource, FolderManager};
+use flowy_search::database::indexer::DatabaseIndexManagerImpl;
+use flowy_search::document::indexer::DocumentIndexManagerImpl;
+use flowy_search::folder::indexer::FolderIndexManagerImpl;
 use flowy_storage::manager::StorageManager;
 use flowy_user::event_map::UserStatusCallback;
+use flowy_user::services::authenticate_user::AuthenticateUser;
 use flowy_user_pub::cloud::{UserCloudConfig, UserCloudServiceProvider};
 use flowy_user_pub::entities::{Authenticator, UserProfile, UserWorkspace};
 use lib_infra::async_trait::async_trait;
 
+use crate::deps_resolve::{index_workspace_databases, index_workspace_documents};
 use crate::integrate::server::{Server, ServerProvider};
 
 pub(crate) struct UserStatusCallbackImpl {
@@ -27,6 +32,40 @@ pub(crate) struct UserStatusCallbackImpl {
   pub(crate) server_provider: Arc<ServerProvider>,
   pub(crate) storage_manager: Arc<StorageManager>,
   pub(crate) ai_manager: Arc<AIManager>,
+  pub(crate) authenticate_user: Weak<AuthenticateUser>,
+  pub(crate) folder_indexer: Arc<FolderIndexManagerImpl>,
+  pub(crate) document_indexer: Arc<DocumentIndexManagerImpl>,
+  pub(crate) database_indexer: Arc<DatabaseIndexManagerImpl>,
+}
+
+impl UserStatusCallbackImpl {
+  /// Switch the search indexes to the language of the workspace before the workspace is opened,
+  /// so its content is indexed and searched with the tokenizer of its language.
+  fn use_workspace_search_language(&self, workspace_id: &str) {
+    let Some(authenticate_user) = self.authenticate_user.upgrade() else {
+      return;
+    };
+    let language_code = authenticate_user.get_workspace_language_code(workspace_id);
+    self.folder_indexer.set_language_code(&language_code);
+    self.document_indexer.set_language_code(&language_code);
+    self.database_indexer.set_language_code(&language_code);
+  }
+
+  /// Index the content of the documents and databases in the background once the workspace is
+  /// opened.
+  fn index_workspace_content(&self, workspace_id: &str) {
+    index_workspace_documents(
+      Arc::downgrade(&self.document_indexer),
+      Arc::downgrade(&self.folder_manager),
+      Arc::downgrade(&self.document_manager),
+      workspace_id.to_string(),
+    );
+    index_workspace_databases(
+      Arc::downgrade(&self.database_indexer),
+      Arc::downgrade(&self.database_manager),
+      workspace_id.to_string(),
+    );
+  }
 }
 
 #[async_trait]
@@ -55,6 +94,7 @@ impl UserStatusCallback for UserStatusCallbackImpl {
       }
     }
 
+    self.use_workspace_search_language(&user_workspace.id);
     self
       .folder_manager
       .initialize(
@@ -71,6 +111,7 @@ impl UserStatusCallback for UserStatusCallbackImpl {
       .await?;
     self.document_manager.initialize(user_id).await?;
     self.ai_manager.initialize(&user_workspace.id).await?;
+    self.index_workspace_content(&user_workspace.id);
     Ok(())
   }
 
@@ -88,6 +129,7 @@ impl UserStatusCallback for UserStatusCallbackImpl {
       device_id
     );
 
+    self.use_workspace_search_language(&user_workspace.id);
     self
       .folder_manager
       .initialize_with_workspace_id(user_id)
@@ -97,6 +139,7 @@ impl UserStatusCallback for UserStatusCallbackImpl {
       .initialize(user_id, authenticator.is_local())
       .await?;
     self.document_manager.initialize(user_id).await?;
+    self.index_workspace_content(&user_workspace.id);
     Ok(())
   }
 
@@ -152,6 +195,7 @@ impl UserStatusCallback for UserStatusCallbackImpl {
       },
     };
 
+    self.use_workspace_search_language(&user_workspace.id);
     self
       .folder_manager
       .initialize_with_new_user(
@@ -189,6 +233,7 @@ impl UserStatusCallback for UserStatusCallbackImpl {
     user_workspace: &UserWorkspace,
     authenticator: &Authenticator,
   ) -> FlowyResult<()> {
+    self.use_workspace_search_language(&user_workspace.id);
     self
       .folder_manager
       .initialize_with_workspace_id(user_id)
@@ -200,13 +245,18 @@ impl UserStatusCallback for UserStatusCallbackImpl {
     self.document_manager.initialize(user_id).await?;
     self.ai_manager.initialize(&user_workspace.id).await?;
     self.storage_manager.initialize(&user_workspace.id).await;
+    self.index_workspace_content(&user_workspace.id);
     Ok(())
   }
 
   fn did_update_network(&self, reachable: bool) {
     info!("Notify did update network: reachable: {}", reachable);
     self.collab_builder.update_network(reachable);
-    self.storage_manager.update_network_reachable(reachable);
+    // The files stored on the device don't need the network to be uploaded.
+    let is_file_on_device = self.server_provider.is_file_storage_on_device();
+    self
+      .storage_manager
+      .update_network_reachable(reachable || is_file_on_device);
   }
 
   fn did_update_plans(&self, plans: Vec<SubscriptionPlan>) {
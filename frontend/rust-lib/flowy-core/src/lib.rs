@@ -1,5 +1,7 @@
 #![allow(unused_doc_comments)]
 
+use flowy_search::database::indexer::DatabaseIndexManagerImpl;
+use flowy_search::document::indexer::DocumentIndexManagerImpl;
 use flowy_search::folder::indexer::FolderIndexManagerImpl;
 use flowy_search::services::manager::SearchManager;
 use std::sync::{Arc, Weak};
@@ -132,6 +134,7 @@ impl AppFlowyCore {
       server_type,
       Arc::downgrade(&store_preference),
       ServerUserImpl(Arc::downgrade(&authenticate_user)),
+      Arc::downgrade(&authenticate_user),
     ));
 
     event!(tracing::Level::DEBUG, "Init managers",);
@@ -143,6 +146,9 @@ impl AppFlowyCore {
       document_manager,
       collab_builder,
       search_manager,
+      folder_indexer,
+      document_indexer,
+      database_indexer,
       ai_manager,
       storage_manager,
     ) = async {
@@ -150,6 +156,7 @@ impl AppFlowyCore {
         Arc::downgrade(&authenticate_user),
         server_provider.clone(),
         &user_config.storage_path,
+        store_preference.clone(),
       );
       /// The shared collab builder is used to build the [Collab] instance. The plugins will be loaded
       /// on demand based on the [CollabPluginConfig].
@@ -164,6 +171,12 @@ impl AppFlowyCore {
       let folder_indexer = Arc::new(FolderIndexManagerImpl::new(Some(Arc::downgrade(
         &authenticate_user,
       ))));
+      let document_indexer = Arc::new(DocumentIndexManagerImpl::new(Some(Arc::downgrade(
+        &authenticate_user,
+      ))));
+      let database_indexer = Arc::new(DatabaseIndexManagerImpl::new(Some(Arc::downgrade(
+        &authenticate_user,
+      ))));
 
       let folder_manager = FolderDepsResolver::resolve(
         Arc::downgrade(&authenticate_user),
@@ -202,6 +215,19 @@ impl AppFlowyCore {
         Arc::downgrade(&storage_manager.storage_service),
       );
 
+      ChatDepsResolver::register_view_services(
+        &ai_manager,
+        &folder_manager,
+        &document_manager,
+        &database_manager,
+      );
+      FileStorageResolver::register_file_reference_service(
+        &storage_manager,
+        &folder_manager,
+        &document_manager,
+        &database_manager,
+      );
+
       let user_manager = UserDepsResolver::resolve(
         authenticate_user.clone(),
         collab_builder.clone(),
@@ -209,13 +235,21 @@ impl AppFlowyCore {
         store_preference.clone(),
         database_manager.clone(),
         folder_manager.clone(),
+        document_indexer.clone(),
+        database_indexer.clone(),
       )
       .await;
+      ChatDepsResolver::register_prompt_sync_service(&ai_manager, &user_manager);
 
       let search_manager = SearchDepsResolver::resolve(
-        folder_indexer,
+        Arc::downgrade(&authenticate_user),
+        folder_indexer.clone(),
+        document_indexer.clone(),
+        database_indexer.clone(),
         server_provider.clone(),
         folder_manager.clone(),
+        &document_manager,
+        &database_manager,
       )
       .await;
 
@@ -225,7 +259,27 @@ impl AppFlowyCore {
         document_manager.clone(),
         database_manager.clone(),
         ai_manager.clone(),
+        Arc::downgrade(&storage_manager.storage_service),
+        Arc::downgrade(&document_indexer),
+        Arc::downgrade(&database_indexer),
       );
+      subscribe_document_view_links(Arc::downgrade(&folder_manager), &document_manager);
+      subscribe_document_content_index(
+        Arc::downgrade(&document_indexer),
+        Arc::downgrade(&authenticate_user),
+        &document_manager,
+      );
+      subscribe_database_row_index(
+        Arc::downgrade(&database_indexer),
+        Arc::downgrade(&authenticate_user),
+        &database_manager,
+      );
+      subscribe_database_field_index(
+        Arc::downgrade(&database_indexer),
+        Arc::downgrade(&authenticate_user),
+        &database_manager,
+      );
+      subscribe_database_view_links(Arc::downgrade(&folder_manager), &database_manager);
 
       (
         user_manager,
@@ -235,6 +289,9 @@ impl AppFlowyCore {
         document_manager,
         collab_builder,
         search_manager,
+        folder_indexer,
+        document_indexer,
+        database_indexer,
         ai_manager,
         storage_manager,
       )
@@ -249,6 +306,10 @@ impl AppFlowyCore {
       server_provider: server_provider.clone(),
       storage_manager: storage_manager.clone(),
       ai_manager: ai_manager.clone(),
+      authenticate_user: Arc::downgrade(&authenticate_user),
+      folder_indexer,
+      document_indexer,
+      database_indexer,
     };
 
     let collab_interact_impl = CollabInteractImpl {
@@ -66,10 +66,20 @@ pub trait DatabaseCloudService: Send + Sync {
     object_id: &str,
     limit: usize,
   ) -> Result<Vec<DatabaseSnapshot>, FlowyError>;
+
+  /// Returns the snapshot of the database collab with the given id, or None if the snapshot
+  /// doesn't exist.
+  async fn get_database_collab_object_snapshot(
+    &self,
+    _object_id: &str,
+    _snapshot_id: &str,
+  ) -> Result<Option<DatabaseSnapshot>, FlowyError> {
+    Err(FlowyError::not_support())
+  }
 }
 
 pub struct DatabaseSnapshot {
-  pub snapshot_id: i64,
+  pub snapshot_id: String,
   pub database_id: String,
   pub data: Vec<u8>,
   pub created_at: i64,
@@ -313,7 +313,7 @@ pub struct RepeatedDatabaseSnapshotPB {
 #[derive(Debug, Default, ProtoBuf)]
 pub struct DatabaseSnapshotPB {
   #[pb(index = 1)]
-  pub snapshot_id: i64,
+  pub snapshot_id: String,
 
   #[pb(index = 2)]
   pub snapshot_desc: String,
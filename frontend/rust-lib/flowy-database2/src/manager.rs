@@ -5,7 +5,7 @@ use collab::core::collab::DataSource;
 use collab::core::origin::CollabOrigin;
 use collab::lock::RwLock;
 use collab::preclude::Collab;
-use collab_database::database::{Database, DatabaseData};
+use collab_database::database::{Database, DatabaseContext, DatabaseData};
 use collab_database::entity::{CreateDatabaseParams, CreateViewParams, EncodedDatabase};
 use collab_database::error::DatabaseError;
 use collab_database::fields::translate_type_option::TranslateTypeOption;
@@ -19,10 +19,12 @@ use collab_database::workspace_database::{
 use collab_entity::{CollabObject, CollabType, EncodedCollab};
 use collab_plugins::local_storage::kv::KVTransactionDB;
 use rayon::prelude::*;
+use std::borrow::Borrow;
 use std::collections::HashMap;
+use std::future::Future;
 use std::sync::{Arc, Weak};
 use std::time::Duration;
-use tokio::sync::Mutex;
+use tokio::sync::{broadcast, Mutex};
 use tracing::{error, info, instrument, trace};
 
 use collab_integrate::collab_builder::{AppFlowyCollabBuilder, CollabBuilderConfig};
@@ -37,7 +39,7 @@ use lib_infra::priority_task::TaskDispatcher;
 
 use crate::entities::{DatabaseLayoutPB, DatabaseSnapshotPB, FieldType, RowMetaPB};
 use crate::services::cell::stringify_cell;
-use crate::services::database::DatabaseEditor;
+use crate::services::database::{DatabaseEditor, DatabaseRowChanged};
 use crate::services::database_view::DatabaseLayoutDepsResolver;
 use crate::services::field_settings::default_field_settings_by_layout_map;
 use crate::services::share::csv::{CSVFormat, CSVImporter, ImportResult};
@@ -48,6 +50,12 @@ pub trait DatabaseUser: Send + Sync {
   fn collab_db(&self, uid: i64) -> Result<Weak<CollabKVDB>, FlowyError>;
   fn workspace_id(&self) -> Result<String, FlowyError>;
   fn workspace_database_object_id(&self) -> Result<String, FlowyError>;
+  /// Returns the id of the workspace database of the given workspace of the user.
+  fn get_workspace_database_object_id(
+    &self,
+    uid: i64,
+    workspace_id: &str,
+  ) -> Result<String, FlowyError>;
 }
 
 pub(crate) type DatabaseEditorMap = HashMap<String, Arc<DatabaseEditor>>;
@@ -60,6 +68,8 @@ pub struct DatabaseManager {
   collab_builder: Arc<AppFlowyCollabBuilder>,
   cloud_service: Arc<dyn DatabaseCloudService>,
   ai_service: Arc<dyn DatabaseAIService>,
+  row_changed_tx: broadcast::Sender<DatabaseRowChanged>,
+  field_changed_tx: broadcast::Sender<String>,
 }
 
 impl DatabaseManager {
@@ -70,6 +80,8 @@ impl DatabaseManager {
     cloud_service: Arc<dyn DatabaseCloudService>,
     ai_service: Arc<dyn DatabaseAIService>,
   ) -> Self {
+    let (row_changed_tx, _) = broadcast::channel(100);
+    let (field_changed_tx, _) = broadcast::channel(100);
     Self {
       user: database_user,
       workspace_database_manager: Default::default(),
@@ -79,9 +91,23 @@ impl DatabaseManager {
       collab_builder,
       cloud_service,
       ai_service,
+      row_changed_tx,
+      field_changed_tx,
     }
   }
 
+  /// Subscribe to the rows of the opened databases that were inserted, removed or whose cells
+  /// were updated.
+  pub fn subscribe_row_changed(&self) -> broadcast::Receiver<DatabaseRowChanged> {
+    self.row_changed_tx.subscribe()
+  }
+
+  /// Subscribe to the ids of the opened databases whose fields were renamed, removed or whose
+  /// type or type option was changed.
+  pub fn subscribe_field_changed(&self) -> broadcast::Receiver<String> {
+    self.field_changed_tx.subscribe()
+  }
+
   /// When initialize with new workspace, all the resources will be cleared.
   pub async fn initialize(&self, uid: i64, is_local_user: bool) -> FlowyResult<()> {
     // 1. Clear all existing tasks
@@ -240,6 +266,51 @@ impl DatabaseManager {
     Ok(editor)
   }
 
+  /// Returns true if the editor of the database is open.
+  pub async fn is_database_editor_open(&self, database_id: &str) -> bool {
+    self.editors.lock().await.contains_key(database_id)
+  }
+
+  /// Closes the editor of the database if none of its views is open. Used to release the
+  /// databases that are opened only to be read.
+  pub async fn close_unused_database_editor(&self, database_id: &str) -> FlowyResult<()> {
+    let mut editors = self.editors.lock().await;
+    let Some(editor) = editors.get(database_id).cloned() else {
+      return Ok(());
+    };
+    if editor.num_of_opening_views().await > 0 {
+      return Ok(());
+    }
+    editors.remove(database_id);
+    drop(editors);
+
+    trace!("[Database]: close unused database editor:{}", database_id);
+    editor.close_database().await;
+    let workspace_database = self.workspace_database()?;
+    workspace_database.write().await.close_database(database_id);
+    Ok(())
+  }
+
+  /// Runs the given function with the editor of the database. The editor is closed again if it
+  /// was opened only for the function, also when opening the database or the function fails.
+  pub async fn with_database_editor<F, Fut, T>(&self, database_id: &str, f: F) -> FlowyResult<T>
+  where
+    F: FnOnce(Arc<DatabaseEditor>) -> Fut,
+    Fut: Future<Output = FlowyResult<T>>,
+  {
+    let is_open = self.is_database_editor_open(database_id).await;
+    let result = match self.get_or_init_database_editor(database_id).await {
+      Ok(editor) => f(editor).await,
+      Err(err) => Err(err),
+    };
+    if !is_open {
+      if let Err(err) = self.close_unused_database_editor(database_id).await {
+        error!("Failed to close the database {}: {}", database_id, err);
+      }
+    }
+    result
+  }
+
   #[instrument(level = "trace", skip_all, err)]
   pub async fn open_database(&self, database_id: &str) -> FlowyResult<Arc<DatabaseEditor>> {
     let workspace_database = self.workspace_database()?;
@@ -262,6 +333,8 @@ impl DatabaseManager {
       database,
       self.task_scheduler.clone(),
       self.collab_builder.clone(),
+      self.row_changed_tx.clone(),
+      self.field_changed_tx.clone(),
     )
     .await?;
 
@@ -399,6 +472,112 @@ impl DatabaseManager {
     Ok(encoded_collab)
   }
 
+  /// Create a new database with the given data like [Self::create_database_with_data], but the
+  /// database and its rows keep the ids of the data instead of getting new ones. The caller must
+  /// make sure the ids are unused, for example the ids that are generated for the databases of a
+  /// template by [crate::template::collect_database_template_ids].
+  #[tracing::instrument(level = "trace", skip_all, err)]
+  pub async fn create_database_with_ids(
+    &self,
+    new_database_view_id: &str,
+    data: Vec<u8>,
+  ) -> FlowyResult<EncodedCollab> {
+    let database_data = DatabaseData::from_json_bytes(data)?;
+    let create_database_params =
+      create_database_params_with_ids(database_data, &[new_database_view_id.to_string()])?;
+
+    let lock = self.workspace_database()?;
+    let mut wdb = lock.write().await;
+    let database = wdb.create_database(create_database_params).await?;
+    drop(wdb);
+
+    let encoded_collab = database
+      .read()
+      .await
+      .encode_collab_v1(|collab| CollabType::Database.validate_require_data(collab))
+      .map_err(|err| FlowyError::internal().with_context(err))?;
+    Ok(encoded_collab)
+  }
+
+  /// Return the collabs of a database that is created with the given data like
+  /// [Self::create_database_with_ids], the views of the data get the given ids in order. Nothing
+  /// is written to the disk, so the database can be created in another workspace.
+  pub async fn encode_database_with_ids(
+    &self,
+    view_ids: &[String],
+    data: DatabaseData,
+  ) -> FlowyResult<EncodedDatabase> {
+    let params = create_database_params_with_ids(data, view_ids)?;
+    let context = DatabaseContext::new(Arc::new(MemoryDatabaseCollabServiceImpl));
+    let database = Database::create_with_view(params, context).await?;
+    let encoded_database = database.encode_database_collabs().await?;
+    Ok(encoded_database)
+  }
+
+  /// Add the databases to the workspace database of another workspace of the user. The workspace
+  /// database is loaded from the disk, or from the cloud if the workspace was never opened on this
+  /// device, and written back to the disk. The changes are synced when the workspace is opened.
+  #[instrument(level = "trace", skip_all, err)]
+  pub async fn track_databases_in_workspace(
+    &self,
+    workspace_id: &str,
+    view_ids_by_database_id: HashMap<String, Vec<String>>,
+  ) -> FlowyResult<()> {
+    let uid = self.user.user_id()?;
+    let object_id = self
+      .user
+      .get_workspace_database_object_id(uid, workspace_id)?;
+    let collab_db = self
+      .user
+      .collab_db(uid)?
+      .upgrade()
+      .ok_or_else(|| FlowyError::internal().with_context("The collab db is already dropped"))?;
+
+    let mut collab = Collab::new_with_origin(CollabOrigin::Empty, &object_id, vec![], false);
+    let read_txn = collab_db.read_txn();
+    if read_txn.is_exist(uid, workspace_id, &object_id) {
+      read_txn
+        .load_doc_with_txn(uid, workspace_id, &object_id, &mut collab.transact_mut())
+        .map_err(internal_error)?;
+    } else if let Some(encoded_collab) = self
+      .cloud_service
+      .get_database_encode_collab(&object_id, CollabType::WorkspaceDatabase, workspace_id)
+      .await?
+    {
+      collab = Collab::new_with_source(
+        CollabOrigin::Empty,
+        &object_id,
+        DataSource::from(encoded_collab),
+        vec![],
+        false,
+      )
+      .map_err(internal_error)?;
+    }
+    drop(read_txn);
+
+    let mut wdb =
+      WorkspaceDatabaseManager::open(&object_id, collab, MemoryDatabaseCollabServiceImpl)?;
+    for (database_id, view_ids) in view_ids_by_database_id {
+      wdb.track_database(&database_id, view_ids);
+    }
+    let collab: &Collab = wdb.borrow();
+    let encoded_collab = collab
+      .encode_collab_v1(|collab| CollabType::WorkspaceDatabase.validate_require_data(collab))
+      .map_err(internal_error)?;
+    let write_txn = collab_db.write_txn();
+    write_txn
+      .flush_doc(
+        uid,
+        workspace_id,
+        &object_id,
+        encoded_collab.state_vector.to_vec(),
+        encoded_collab.doc_state.to_vec(),
+      )
+      .map_err(internal_error)?;
+    write_txn.commit_transaction().map_err(internal_error)?;
+    Ok(())
+  }
+
   /// When duplicating a database view, it will duplicate all the database views and replace the duplicated
   /// database_view_id with the new_database_view_id. The new database id is the ID created by Folder.
   #[tracing::instrument(level = "trace", skip_all, err)]
@@ -552,6 +731,38 @@ impl DatabaseManager {
     Ok(snapshots)
   }
 
+  /// Return the [DatabaseData] of the database at the time of the given snapshot. The fields and
+  /// the views come from the snapshot. The rows are loaded from the disk, so the rows that were
+  /// deleted after the snapshot was taken are missing.
+  pub async fn get_database_data_from_snapshot(
+    &self,
+    view_id: &str,
+    snapshot_id: &str,
+  ) -> FlowyResult<DatabaseData> {
+    let database_id = self.get_database_id_with_view_id(view_id).await?;
+    let snapshot = self
+      .cloud_service
+      .get_database_collab_object_snapshot(&database_id, snapshot_id)
+      .await?
+      .ok_or_else(|| {
+        FlowyError::record_not_found().with_context(format!("Snapshot {} not found", snapshot_id))
+      })?;
+
+    let collab_service = SnapshotDatabaseCollabServiceImpl {
+      database_id: database_id.clone(),
+      doc_state: snapshot.data,
+      inner: WorkspaceDatabaseCollabServiceImpl::new(
+        false,
+        self.user.clone(),
+        self.collab_builder.clone(),
+        self.cloud_service.clone(),
+      ),
+    };
+    let database =
+      Database::open(&database_id, DatabaseContext::new(Arc::new(collab_service))).await?;
+    Ok(database.get_database_data().await)
+  }
+
   fn workspace_database(&self) -> FlowyResult<Arc<RwLock<WorkspaceDatabaseManager>>> {
     self
       .workspace_database_manager
@@ -685,6 +896,39 @@ impl DatabaseManager {
   }
 }
 
+/// Returns the params that create a database with the database and row ids of the given data. The
+/// views of the data get the given ids in order.
+fn create_database_params_with_ids(
+  data: DatabaseData,
+  view_ids: &[String],
+) -> FlowyResult<CreateDatabaseParams> {
+  if data.views.is_empty() || data.views.len() != view_ids.len() {
+    return Err(FlowyError::invalid_data().with_context("The views of the database are invalid"));
+  }
+
+  let database_id = data.database_id.clone();
+  let row_ids = data
+    .rows
+    .iter()
+    .map(|row| row.id.clone())
+    .collect::<Vec<_>>();
+  let database_view_id = data.views[0].id.clone();
+  let mut params = CreateDatabaseParams::from_database_data(data, &database_view_id, &view_ids[0]);
+  if params.rows.len() != row_ids.len() || params.views.len() != view_ids.len() {
+    return Err(FlowyError::invalid_data().with_context("The database data is invalid"));
+  }
+  params.database_id = database_id.clone();
+  for (view, view_id) in params.views.iter_mut().zip(view_ids) {
+    view.view_id = view_id.clone();
+    view.database_id = database_id.clone();
+  }
+  for (row, row_id) in params.rows.iter_mut().zip(row_ids) {
+    row.id = row_id;
+    row.database_id = database_id.clone();
+  }
+  Ok(params)
+}
+
 struct WorkspaceDatabaseCollabServiceImpl {
   is_local_user: bool,
   user: Arc<dyn DatabaseUser>,
@@ -931,6 +1175,86 @@ impl DatabaseCollabService for WorkspaceDatabaseCollabServiceImpl {
   }
 }
 
+/// Builds the database collab from the doc state of a snapshot, the other collabs are built by the
+/// [WorkspaceDatabaseCollabServiceImpl]. Nothing is written to the disk, so opening the snapshot
+/// doesn't change the current database.
+struct SnapshotDatabaseCollabServiceImpl {
+  database_id: String,
+  doc_state: Vec<u8>,
+  inner: WorkspaceDatabaseCollabServiceImpl,
+}
+
+#[async_trait]
+impl DatabaseCollabService for SnapshotDatabaseCollabServiceImpl {
+  async fn build_collab(
+    &self,
+    object_id: &str,
+    collab_type: CollabType,
+    encoded_collab: Option<(EncodedCollab, bool)>,
+  ) -> Result<Collab, DatabaseError> {
+    if object_id != self.database_id {
+      return self
+        .inner
+        .build_collab(object_id, collab_type, encoded_collab)
+        .await;
+    }
+
+    Collab::new_with_source(
+      CollabOrigin::Empty,
+      object_id,
+      DataSource::DocStateV1(self.doc_state.clone()),
+      vec![],
+      false,
+    )
+    .map_err(|err| DatabaseError::Internal(err.into()))
+  }
+
+  async fn get_collabs(
+    &self,
+    object_ids: Vec<String>,
+    collab_type: CollabType,
+  ) -> Result<EncodeCollabByOid, DatabaseError> {
+    self.inner.get_collabs(object_ids, collab_type).await
+  }
+
+  fn persistence(&self) -> Option<Arc<dyn DatabaseCollabPersistenceService>> {
+    None
+  }
+}
+
+/// Builds the collabs in memory without loading or writing anything, so the database can belong to
+/// another workspace than the current one.
+struct MemoryDatabaseCollabServiceImpl;
+
+#[async_trait]
+impl DatabaseCollabService for MemoryDatabaseCollabServiceImpl {
+  async fn build_collab(
+    &self,
+    object_id: &str,
+    _collab_type: CollabType,
+    encoded_collab: Option<(EncodedCollab, bool)>,
+  ) -> Result<Collab, DatabaseError> {
+    let data_source = match encoded_collab {
+      Some((encoded_collab, _)) => DataSource::from(encoded_collab),
+      None => DataSource::Disk(None),
+    };
+    Collab::new_with_source(CollabOrigin::Empty, object_id, data_source, vec![], false)
+      .map_err(|err| DatabaseError::Internal(err.into()))
+  }
+
+  async fn get_collabs(
+    &self,
+    _object_ids: Vec<String>,
+    _collab_type: CollabType,
+  ) -> Result<EncodeCollabByOid, DatabaseError> {
+    Ok(EncodeCollabByOid::new())
+  }
+
+  fn persistence(&self) -> Option<Arc<dyn DatabaseCollabPersistenceService>> {
+    None
+  }
+}
+
 pub struct DatabasePersistenceImpl {
   user: Arc<dyn DatabaseUser>,
 }
@@ -1,7 +1,7 @@
 use crate::entities::*;
 use crate::notification::{database_notification_builder, DatabaseNotification};
 use crate::services::calculations::Calculation;
-use crate::services::cell::{apply_cell_changeset, get_cell_protobuf, CellCache};
+use crate::services::cell::{apply_cell_changeset, get_cell_protobuf, stringify_cell, CellCache};
 use crate::services::database::database_observe::*;
 use crate::services::database::util::database_view_setting_pb_from_view;
 use crate::services::database_view::{
@@ -30,6 +30,7 @@ use collab_database::fields::media_type_option::MediaCellData;
 use collab_database::fields::relation_type_option::RelationTypeOption;
 use collab_database::fields::{Field, TypeOptionData};
 use collab_database::rows::{Cell, Cells, DatabaseRow, Row, RowCell, RowDetail, RowId, RowUpdate};
+use collab_database::template::relation_parse::RelationCellData;
 use collab_database::template::timestamp_parse::TimestampCellData;
 use collab_database::views::{
   DatabaseLayout, FilterMap, LayoutSetting, OrderObjectPosition, RowOrder,
@@ -43,7 +44,9 @@ use futures::{pin_mut, StreamExt};
 use lib_infra::box_any::BoxAny;
 use lib_infra::priority_task::TaskDispatcher;
 use lib_infra::util::timestamp;
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
 use std::sync::{Arc, Weak};
 use std::time::Duration;
 use tokio::select;
@@ -57,7 +60,7 @@ use tracing::{debug, error, event, info, instrument, trace, warn};
 type OpenDatabaseResult = oneshot::Sender<FlowyResult<DatabasePB>>;
 
 pub struct DatabaseEditor {
-  database_id: String,
+  pub(crate) database_id: String,
   pub(crate) database: Arc<RwLock<Database>>,
   pub cell_cache: CellCache,
   pub(crate) database_views: Arc<DatabaseViews>,
@@ -70,6 +73,8 @@ pub struct DatabaseEditor {
   database_cancellation: Arc<RwLock<Option<CancellationToken>>>,
   un_finalized_rows_cancellation: Arc<ArcSwapOption<CancellationToken>>,
   finalized_rows: Arc<moka::future::Cache<String, Weak<RwLock<DatabaseRow>>>>,
+  pub(crate) row_changed_tx: broadcast::Sender<DatabaseRowChanged>,
+  field_changed_tx: broadcast::Sender<String>,
 }
 
 impl DatabaseEditor {
@@ -78,6 +83,8 @@ impl DatabaseEditor {
     database: Arc<RwLock<Database>>,
     task_scheduler: Arc<TokioRwLock<TaskDispatcher>>,
     collab_builder: Arc<AppFlowyCollabBuilder>,
+    row_changed_tx: broadcast::Sender<DatabaseRowChanged>,
+    field_changed_tx: broadcast::Sender<String>,
   ) -> FlowyResult<Arc<Self>> {
     let finalized_rows: moka::future::Cache<String, Weak<RwLock<DatabaseRow>>> =
       moka::future::Cache::builder()
@@ -95,7 +102,13 @@ impl DatabaseEditor {
     // Receive database sync state and send to frontend via the notification
     observe_sync_state(&database_id, &database).await;
     // observe_field_change(&database_id, &database).await;
-    observe_rows_change(&database_id, &database, &notification_sender).await;
+    observe_rows_change(
+      &database_id,
+      &database,
+      &notification_sender,
+      row_changed_tx.clone(),
+    )
+    .await;
 
     // Used to cache the view of the database for fast access.
     let editor_by_view_id = Arc::new(RwLock::new(EditorByViewId::default()));
@@ -141,6 +154,8 @@ impl DatabaseEditor {
       database_cancellation,
       un_finalized_rows_cancellation: Arc::new(Default::default()),
       finalized_rows: Arc::new(finalized_rows),
+      row_changed_tx,
+      field_changed_tx,
     });
     observe_block_event(&database_id, &this).await;
     observe_view_change(&database_id, &this).await;
@@ -395,6 +410,7 @@ impl DatabaseEditor {
         .set_icon_if_not_none(params.icon);
     });
     notify_did_update_database_field(&database, &params.field_id)?;
+    let _ = self.field_changed_tx.send(self.database_id.clone());
     Ok(())
   }
 
@@ -426,6 +442,7 @@ impl DatabaseEditor {
     for view in self.database_views.editors().await {
       view.v_did_delete_field(field_id).await;
     }
+    let _ = self.field_changed_tx.send(database_id);
 
     Ok(())
   }
@@ -475,6 +492,7 @@ impl DatabaseEditor {
         .v_did_update_field_type_option(&old_field)
         .await?;
     }
+    let _ = self.field_changed_tx.send(self.database_id.clone());
     Ok(())
   }
 
@@ -527,6 +545,7 @@ impl DatabaseEditor {
       let database = self.database.read().await;
 
       notify_did_update_database_field(&database, field_id)?;
+      let _ = self.field_changed_tx.send(self.database_id.clone());
     }
 
     Ok(())
@@ -1751,6 +1770,140 @@ impl DatabaseEditor {
     Ok(type_option.database_id)
   }
 
+  /// Returns the rows whose relation cells link to the rows of another database. The rows that
+  /// link to the rows of the same database are skipped.
+  pub async fn get_row_relations(&self) -> Vec<RowRelation> {
+    let database = self.database.read().await;
+    let relation_fields = database
+      .get_fields(None)
+      .into_iter()
+      .filter(|field| FieldType::from(field.field_type) == FieldType::Relation)
+      .filter_map(|field| {
+        let type_option = field.get_type_option::<RelationTypeOption>(FieldType::Relation)?;
+        if type_option.database_id.is_empty() || type_option.database_id == self.database_id {
+          return None;
+        }
+        Some((field.id, type_option.database_id))
+      })
+      .collect::<Vec<_>>();
+    if relation_fields.is_empty() {
+      return vec![];
+    }
+
+    let mut relations = vec![];
+    for row_order in database.get_all_row_orders().await {
+      let row = database.get_row(&row_order.id).await;
+      for (field_id, related_database_id) in &relation_fields {
+        let Some(cell) = row.cells.get(field_id) else {
+          continue;
+        };
+        let cell_data = RelationCellData::from(cell);
+        if !cell_data.row_ids.is_empty() {
+          relations.push(RowRelation {
+            row_id: row_order.id.clone(),
+            related_database_id: related_database_id.clone(),
+          });
+        }
+      }
+    }
+    relations
+  }
+
+  /// Returns the text of the primary cell and the text-like cells of the row, which are indexed
+  /// for search, with the version of the row. Returns None if the row was removed from the
+  /// database.
+  pub async fn get_row_search_data(&self, row_id: &RowId) -> Option<RowSearchData> {
+    let database = self.database.read().await;
+    let inline_view_id = database.get_inline_view_id();
+    if !database.contains_row(&inline_view_id, row_id) {
+      return None;
+    }
+    let row = database.get_row(row_id).await;
+    let fields = database
+      .get_fields(None)
+      .into_iter()
+      .filter(|field| {
+        field.is_primary
+          || matches!(
+            FieldType::from(field.field_type),
+            FieldType::RichText
+              | FieldType::URL
+              | FieldType::SingleSelect
+              | FieldType::MultiSelect
+              | FieldType::Checklist
+          )
+      })
+      .collect::<Vec<_>>();
+    drop(database);
+
+    // The fields are part of the version, so the row is indexed again when a searchable field
+    // is renamed, removed or changes its type.
+    let mut hasher = DefaultHasher::new();
+    for field in &fields {
+      (&field.id, &field.name, field.field_type, field.is_primary).hash(&mut hasher);
+    }
+    let version = format!("{}:{:x}", row.modified_at, hasher.finish());
+
+    let cells = fields
+      .into_iter()
+      .filter_map(|field| {
+        let text = stringify_cell(row.cells.get(&field.id)?, &field);
+        if text.trim().is_empty() {
+          return None;
+        }
+        Some(RowSearchCell {
+          field_id: field.id,
+          field_name: field.name,
+          is_primary: field.is_primary,
+          text,
+        })
+      })
+      .collect();
+    Some(RowSearchData { version, cells })
+  }
+
+  /// Returns the urls of the files that the rows refer to, which are the files of the media cells
+  /// and the icons and covers of the rows, and the ids of the row documents. The documents that
+  /// are marked as empty are included too, the mark might be stale.
+  pub async fn get_row_file_references(&self) -> RowFileReferences {
+    let database = self.database.read().await;
+    let media_field_ids = database
+      .get_fields(None)
+      .into_iter()
+      .filter(|field| FieldType::from(field.field_type) == FieldType::Media)
+      .map(|field| field.id)
+      .collect::<Vec<_>>();
+
+    let mut references = RowFileReferences::default();
+    for row_order in database.get_all_row_orders().await {
+      let row_id = row_order.id;
+      let row = database.get_row(&row_id).await;
+      for field_id in &media_field_ids {
+        if let Some(cell) = row.cells.get(field_id) {
+          let cell_data = MediaCellData::from(cell);
+          references
+            .urls
+            .extend(cell_data.files.into_iter().map(|file| file.url));
+        }
+      }
+
+      if let Some(row_meta) = database.get_row_meta(&row_id).await {
+        references.urls.extend(row_meta.icon_url);
+        references
+          .urls
+          .extend(row_meta.cover.map(|cover| cover.data));
+      }
+      references
+        .document_ids
+        .extend(database.get_row_document_id(&row_id));
+    }
+    references
+  }
+
+  pub async fn get_inline_view_id(&self) -> String {
+    self.database.read().await.get_inline_view_id()
+  }
+
   pub async fn get_row_index(&self, view_id: &str, row_id: &RowId) -> Option<usize> {
     self.database.read().await.get_row_index(view_id, row_id)
   }
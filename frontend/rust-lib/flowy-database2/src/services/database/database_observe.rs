@@ -2,7 +2,7 @@ use crate::entities::{DatabaseSyncStatePB, DidFetchRowPB, RowsChangePB};
 use crate::notification::{
   database_notification_builder, DatabaseNotification, DATABASE_OBSERVABLE_SOURCE,
 };
-use crate::services::database::{DatabaseEditor, UpdatedRow};
+use crate::services::database::{DatabaseEditor, DatabaseRowChanged, UpdatedRow};
 use crate::services::database_view::DatabaseViewEditor;
 use collab::lock::RwLock;
 use collab_database::blocks::BlockEvent;
@@ -15,6 +15,7 @@ use flowy_notification::{DebounceNotificationSender, NotificationBuilder};
 use futures::StreamExt;
 
 use std::sync::Arc;
+use tokio::sync::broadcast;
 use tracing::{error, trace, warn};
 
 pub(crate) async fn observe_sync_state(database_id: &str, database: &Arc<RwLock<Database>>) {
@@ -41,6 +42,7 @@ pub(crate) async fn observe_rows_change(
   database_id: &str,
   database: &Arc<RwLock<Database>>,
   notification_sender: &Arc<DebounceNotificationSender>,
+  row_changed_tx: broadcast::Sender<DatabaseRowChanged>,
 ) {
   let notification_sender = notification_sender.clone();
   let database_id = database_id.to_string();
@@ -68,6 +70,11 @@ pub(crate) async fn observe_rows_change(
               for view in views {
                 notify_row(&notification_sender, &view.id, &field_id, &row_id);
               }
+
+              let _ = row_changed_tx.send(DatabaseRowChanged {
+                database_id: database_id.clone(),
+                row_id,
+              });
             },
             _ => {
               warn!("unhandled row change: {:?}", row_change);
@@ -222,6 +229,10 @@ async fn handle_did_update_row_orders(
 
       // insert row order in database view cache
       view_editor.insert_row(row.clone(), index, &row_order).await;
+      let _ = database_editor.row_changed_tx.send(DatabaseRowChanged {
+        database_id: database_editor.database_id.clone(),
+        row_id: row_order.id.clone(),
+      });
 
       let is_move_row = is_move_row(&view_editor, &row_order, &delete_row_indexes).await;
       if let Some((index, row_detail)) = view_editor.v_get_row(&row_order.id).await {
@@ -253,6 +264,10 @@ async fn handle_did_update_row_orders(
         let row_id = lazy_row.id.to_string();
         let mut row_change = row_changes.entry(view_editor.view_id.clone()).or_default();
         row_change.deleted_rows.push(row_id);
+        let _ = database_editor.row_changed_tx.send(DatabaseRowChanged {
+          database_id: database_editor.database_id.clone(),
+          row_id: lazy_row.id.clone(),
+        });
 
         // notify the view
         if let Some(row) = view_editor.row_by_row_id.get(lazy_row.id.as_str()) {
@@ -12,6 +12,45 @@ pub enum DatabaseRowEvent {
   },
 }
 
+/// Sent when a row of the database was inserted, removed or its cells were updated, either
+/// locally or by a remote update.
+#[derive(Debug, Clone)]
+pub struct DatabaseRowChanged {
+  pub database_id: String,
+  pub row_id: RowId,
+}
+
+/// The searchable cells of a row. The version changes whenever the row is modified or the
+/// searchable fields of its database change.
+#[derive(Debug, Clone)]
+pub struct RowSearchData {
+  pub version: String,
+  pub cells: Vec<RowSearchCell>,
+}
+
+/// The text of a cell that can be searched.
+#[derive(Debug, Clone)]
+pub struct RowSearchCell {
+  pub field_id: String,
+  pub field_name: String,
+  pub is_primary: bool,
+  pub text: String,
+}
+
+/// A row whose relation cell links to the rows of another database.
+#[derive(Debug, Clone)]
+pub struct RowRelation {
+  pub row_id: RowId,
+  pub related_database_id: String,
+}
+
+/// The files that the rows of a database refer to.
+#[derive(Debug, Clone, Default)]
+pub struct RowFileReferences {
+  pub urls: Vec<String>,
+  pub document_ids: Vec<String>,
+}
+
 #[derive(Debug, Clone)]
 pub struct InsertedRow {
   pub row_detail: RowDetail,
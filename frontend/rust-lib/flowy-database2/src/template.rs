@@ -1,9 +1,14 @@
-use collab_database::database::{gen_database_id, gen_row_id, timestamp};
+use std::collections::HashMap;
+
+use collab_database::database::{gen_database_id, gen_row_id, timestamp, DatabaseData};
 use collab_database::entity::{CreateDatabaseParams, CreateViewParams};
+use collab_database::fields::media_type_option::MediaCellData;
+use collab_database::fields::relation_type_option::RelationTypeOption;
 use collab_database::fields::select_type_option::{
   SelectOption, SelectOptionColor, SingleSelectTypeOption,
 };
-use collab_database::rows::CreateRowParams;
+use collab_database::rows::{CreateRowParams, RowId};
+use collab_database::template::relation_parse::RelationCellData;
 use collab_database::views::{DatabaseLayout, LayoutSettings};
 
 use crate::entities::FieldType;
@@ -175,3 +180,105 @@ pub fn make_default_calendar(view_id: &str, name: &str) -> CreateDatabaseParams
     fields,
   }
 }
+
+/// Assigns a new id to the database and to each row of the given template data. The ids are only
+/// generated once, so the views of the same database in a template share the same copy.
+pub fn collect_database_template_ids(data: &DatabaseData, ids: &mut HashMap<String, String>) {
+  ids
+    .entry(data.database_id.clone())
+    .or_insert_with(gen_database_id);
+  for row in data.rows.iter() {
+    ids
+      .entry(row.id.to_string())
+      .or_insert_with(|| gen_row_id().to_string());
+  }
+}
+
+/// Replaces the ids of the database, the rows and the related databases with the ids collected
+/// by [collect_database_template_ids]. The ids that are not part of the template are kept.
+pub fn remap_database_template_ids(data: &mut DatabaseData, ids: &HashMap<String, String>) {
+  let remap = |id: &str| ids.get(id).cloned().unwrap_or_else(|| id.to_string());
+  data.database_id = remap(&data.database_id);
+  for view in data.views.iter_mut() {
+    view.database_id = remap(&view.database_id);
+    for row_order in view.row_orders.iter_mut() {
+      row_order.id = RowId::from(remap(&row_order.id));
+    }
+  }
+
+  let mut relation_field_ids = vec![];
+  for field in data.fields.iter_mut() {
+    if FieldType::from(field.field_type) != FieldType::Relation {
+      continue;
+    }
+    if let Some(mut type_option) = field.get_type_option::<RelationTypeOption>(FieldType::Relation)
+    {
+      type_option.database_id = remap(&type_option.database_id);
+      field
+        .type_options
+        .insert(FieldType::Relation.to_string(), type_option.into());
+    }
+    relation_field_ids.push(field.id.clone());
+  }
+
+  for row in data.rows.iter_mut() {
+    row.id = RowId::from(remap(&row.id));
+    row.database_id = data.database_id.clone();
+    for field_id in relation_field_ids.iter() {
+      if let Some(cell) = row.cells.get_mut(field_id) {
+        let cell_data = RelationCellData::from(&*cell);
+        let cell_data = RelationCellData {
+          row_ids: cell_data
+            .row_ids
+            .iter()
+            .map(|row_id| RowId::from(remap(row_id)))
+            .collect(),
+        };
+        *cell = cell_data.into();
+      }
+    }
+  }
+}
+
+/// Returns the urls of the files of the media cells in the given data.
+pub fn database_file_urls(data: &DatabaseData) -> Vec<String> {
+  let media_field_ids = media_field_ids(data);
+  let mut urls = vec![];
+  for row in data.rows.iter() {
+    for field_id in media_field_ids.iter() {
+      if let Some(cell) = row.cells.get(field_id) {
+        let cell_data = MediaCellData::from(cell);
+        urls.extend(cell_data.files.into_iter().map(|file| file.url));
+      }
+    }
+  }
+  urls
+}
+
+/// Replaces the urls of the files of the media cells with the urls in the given map. The urls
+/// that are not in the map are kept.
+pub fn replace_database_file_urls(data: &mut DatabaseData, urls: &HashMap<String, String>) {
+  let media_field_ids = media_field_ids(data);
+  for row in data.rows.iter_mut() {
+    for field_id in media_field_ids.iter() {
+      if let Some(cell) = row.cells.get_mut(field_id) {
+        let mut cell_data = MediaCellData::from(&*cell);
+        for file in cell_data.files.iter_mut() {
+          if let Some(url) = urls.get(&file.url) {
+            file.url = url.clone();
+          }
+        }
+        *cell = cell_data.into();
+      }
+    }
+  }
+}
+
+fn media_field_ids(data: &DatabaseData) -> Vec<String> {
+  data
+    .fields
+    .iter()
+    .filter(|field| FieldType::from(field.field_type) == FieldType::Media)
+    .map(|field| field.id.clone())
+    .collect()
+}
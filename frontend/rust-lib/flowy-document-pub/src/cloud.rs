@@ -37,7 +37,7 @@ pub trait DocumentCloudService: Send + Sync + 'static {
 }
 
 pub struct DocumentSnapshot {
-  pub snapshot_id: i64,
+  pub snapshot_id: String,
   pub document_id: String,
   pub data: Vec<u8>,
   pub created_at: i64,
@@ -6,6 +6,21 @@ use collab::preclude::Collab;
 use collab_document::document::Document;
 use futures::StreamExt;
 use lib_infra::sync_trace;
+use tokio::sync::broadcast;
+
+/// Notify the subscribers of the [DocumentManager](crate::manager::DocumentManager) that the
+/// content of the document was changed. Unlike [subscribe_document_changed], it's registered for
+/// every opened document, including the documents that are not synced.
+pub fn subscribe_document_content_changed(
+  doc_id: &str,
+  document: &mut Document,
+  document_changed_tx: broadcast::Sender<String>,
+) {
+  let doc_id = doc_id.to_owned();
+  document.subscribe_block_changed("content_changed", move |_, _| {
+    let _ = document_changed_tx.send(doc_id.clone());
+  });
+}
 
 pub fn subscribe_document_changed(doc_id: &str, document: &mut Document) {
   let doc_id_clone_for_block_changed = doc_id.to_owned();
@@ -17,11 +17,13 @@ use collab_entity::CollabType;
 use collab_plugins::CollabKVDB;
 use dashmap::DashMap;
 use lib_infra::util::timestamp;
+use tokio::sync::broadcast;
 use tracing::{event, instrument};
 use tracing::{info, trace};
 
 use crate::document::{
-  subscribe_document_changed, subscribe_document_snapshot_state, subscribe_document_sync_state,
+  subscribe_document_changed, subscribe_document_content_changed,
+  subscribe_document_snapshot_state, subscribe_document_sync_state,
 };
 use collab_integrate::collab_builder::{
   AppFlowyCollabBuilder, CollabBuilderConfig, CollabPersistenceImpl,
@@ -34,6 +36,7 @@ use crate::entities::UpdateDocumentAwarenessStatePB;
 use crate::entities::{
   DocumentSnapshotData, DocumentSnapshotMeta, DocumentSnapshotMetaPB, DocumentSnapshotPB,
 };
+use crate::parser::constant::URL;
 use crate::reminder::DocumentReminderAction;
 
 pub trait DocumentUserService: Send + Sync {
@@ -59,6 +62,7 @@ pub struct DocumentManager {
   cloud_service: Arc<dyn DocumentCloudService>,
   storage_service: Weak<dyn StorageService>,
   snapshot_service: Arc<dyn DocumentSnapshotService>,
+  document_changed_tx: broadcast::Sender<String>,
 }
 
 impl DocumentManager {
@@ -69,6 +73,7 @@ impl DocumentManager {
     storage_service: Weak<dyn StorageService>,
     snapshot_service: Arc<dyn DocumentSnapshotService>,
   ) -> Self {
+    let (document_changed_tx, _) = broadcast::channel(100);
     Self {
       user_service,
       collab_builder,
@@ -77,9 +82,16 @@ impl DocumentManager {
       cloud_service,
       storage_service,
       snapshot_service,
+      document_changed_tx,
     }
   }
 
+  /// Subscribe to the ids of the opened documents whose content was changed, either locally or
+  /// by a remote update.
+  pub fn subscribe_document_changed(&self) -> broadcast::Receiver<String> {
+    self.document_changed_tx.subscribe()
+  }
+
   /// Get the encoded collab of the document.
   pub async fn get_encoded_collab_with_view_id(&self, doc_id: &str) -> FlowyResult<EncodedCollab> {
     let uid = self.user_service.user_id()?;
@@ -252,6 +264,11 @@ impl DocumentManager {
       .await;
     match result {
       Ok(document) => {
+        subscribe_document_content_changed(
+          doc_id,
+          &mut *document.write().await,
+          self.document_changed_tx.clone(),
+        );
         // Only push the document to the cache if the sync is enabled.
         if enable_sync {
           {
@@ -396,6 +413,35 @@ impl DocumentManager {
     Ok(metas)
   }
 
+  /// Return the doc state of a new document with the given data. Nothing is written to the disk,
+  /// so the document can be created in another workspace.
+  pub async fn encode_document_data(
+    &self,
+    doc_id: &str,
+    data: DocumentData,
+  ) -> FlowyResult<EncodedCollab> {
+    doc_state_from_document_data(doc_id, Some(data)).await
+  }
+
+  /// Return the [DocumentData] stored in the doc state of a document snapshot.
+  pub fn document_data_from_snapshot(
+    &self,
+    doc_id: &str,
+    doc_state: Vec<u8>,
+  ) -> FlowyResult<DocumentData> {
+    let collab = Collab::new_with_source(
+      CollabOrigin::Empty,
+      doc_id,
+      DataSource::DocStateV1(doc_state),
+      vec![],
+      false,
+    )
+    .map_err(internal_error)?;
+    let document = Document::open(collab)?;
+    let data = document.get_document_data()?;
+    Ok(data)
+  }
+
   pub async fn get_document_snapshot(&self, snapshot_id: &str) -> FlowyResult<DocumentSnapshotPB> {
     let snapshot = self
       .snapshot_service
@@ -424,7 +470,9 @@ impl DocumentManager {
 
   pub async fn download_file(&self, local_file_path: String, url: String) -> FlowyResult<()> {
     let storage_service = self.storage_service_upgrade()?;
-    storage_service.download_object(url, local_file_path)?;
+    storage_service
+      .download_object(url, local_file_path)
+      .await?;
     Ok(())
   }
 
@@ -434,6 +482,35 @@ impl DocumentManager {
     Ok(())
   }
 
+  /// Upload a copy of the files referenced by the blocks of the document to the given workspace
+  /// and point the blocks to the copies. Returns an error if any of the files can't be copied.
+  pub async fn copy_document_files(
+    &self,
+    data: &mut DocumentData,
+    workspace_id: &str,
+    document_id: &str,
+  ) -> FlowyResult<()> {
+    let storage_service = self.storage_service_upgrade()?;
+    for block in data.blocks.values_mut() {
+      let Some(url) = block.data.get(URL).and_then(|url| url.as_str()) else {
+        continue;
+      };
+      let new_url = storage_service
+        .copy_object(url, workspace_id, document_id)
+        .await
+        .map_err(|err| {
+          let msg = format!("copy file of block {} failed: {}", block.id, err.msg);
+          err.with_context(msg)
+        })?;
+      if let Some(new_url) = new_url {
+        block
+          .data
+          .insert(URL.to_string(), serde_json::Value::String(new_url));
+      }
+    }
+    Ok(())
+  }
+
   async fn is_doc_exist(&self, doc_id: &str) -> FlowyResult<bool> {
     let uid = self.user_service.user_id()?;
     let workspace_id = self.user_service.workspace_id()?;
@@ -0,0 +1,27 @@
+use crate::parser::constant::DELTA;
+use collab_document::blocks::DocumentData;
+use serde_json::Value;
+
+/// Returns the urls in the data of the blocks, for example the urls of the images, the files and
+/// the cover of the document. The text of the blocks is not included.
+pub fn extract_file_urls(data: &DocumentData) -> Vec<String> {
+  let mut urls = vec![];
+  for block in data.blocks.values() {
+    for (key, value) in block.data.iter() {
+      if key != DELTA {
+        collect_urls(value, &mut urls);
+      }
+    }
+  }
+  urls
+}
+
+/// Collects the urls in the json value, the urls might be nested, like the images of a gallery.
+pub fn collect_urls(value: &Value, urls: &mut Vec<String>) {
+  match value {
+    Value::String(s) if s.contains("://") => urls.push(s.clone()),
+    Value::Array(values) => values.iter().for_each(|value| collect_urls(value, urls)),
+    Value::Object(map) => map.values().for_each(|value| collect_urls(value, urls)),
+    _ => {},
+  }
+}
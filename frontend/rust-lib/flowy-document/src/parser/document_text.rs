@@ -0,0 +1,37 @@
+use crate::parser::utils::{delta_to_text, get_delta_for_block};
+use collab_document::blocks::DocumentData;
+
+/// The plain text of a block of the document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentBlockText {
+  pub block_id: String,
+  pub text: String,
+}
+
+/// Returns the plain text of the blocks that have text, in the order they appear in the
+/// document. Used to index the content of the document.
+pub fn extract_block_texts(data: &DocumentData) -> Vec<DocumentBlockText> {
+  let mut texts = vec![];
+  let mut stack = vec![data.page_id.clone()];
+  while let Some(block_id) = stack.pop() {
+    let Some(block) = data.blocks.get(&block_id) else {
+      continue;
+    };
+
+    if let Some(delta) = get_delta_for_block(&block_id, data) {
+      let text = delta_to_text(&delta);
+      if !text.trim().is_empty() {
+        texts.push(DocumentBlockText {
+          block_id: block_id.clone(),
+          text,
+        });
+      }
+    }
+
+    if let Some(children) = data.meta.children_map.get(&block.children) {
+      // reverse the children to keep the order
+      stack.extend(children.iter().rev().cloned());
+    }
+  }
+  texts
+}
@@ -1,6 +1,9 @@
 pub mod constant;
 pub mod document_data_parser;
+pub mod document_files;
+pub mod document_text;
 pub mod external;
 pub mod json;
 pub mod parser_entities;
 pub mod utils;
+pub mod view_link;
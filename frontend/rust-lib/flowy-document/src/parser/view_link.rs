@@ -0,0 +1,136 @@
+use crate::parser::constant::MENTION;
+use crate::parser::utils::get_delta_for_block;
+use collab_document::blocks::DocumentData;
+use serde_json::Value;
+use std::collections::HashMap;
+
+const MENTION_TYPE: &str = "type";
+const MENTION_PAGE_ID: &str = "page_id";
+const MENTION_TYPE_PAGE: &str = "page";
+const MENTION_TYPE_CHILD_PAGE: &str = "childPage";
+const VIEW_ID: &str = "view_id";
+const PARENT_ID: &str = "parent_id";
+const SUB_PAGE: &str = "sub_page";
+const GRID: &str = "grid";
+const BOARD: &str = "board";
+const CALENDAR: &str = "calendar";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DocumentViewLinkType {
+  /// The view is mentioned in the text of a block.
+  Mention,
+  /// The view is a sub page embedded in the document.
+  SubPage,
+  /// The view is a database view embedded in the document.
+  Database,
+}
+
+/// A reference from a block of the document to another view.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DocumentViewLink {
+  pub view_id: String,
+  pub block_id: String,
+  pub link_type: DocumentViewLinkType,
+}
+
+/// Returns all the views that are referenced by the document, including the page mentions in the
+/// text of the blocks, the sub pages and the embedded database views.
+pub fn extract_view_links(data: &DocumentData) -> Vec<DocumentViewLink> {
+  let mut links = vec![];
+  for (block_id, block) in data.blocks.iter() {
+    let embedded_link_type = match block.ty.as_str() {
+      SUB_PAGE => Some(DocumentViewLinkType::SubPage),
+      GRID | BOARD | CALENDAR => Some(DocumentViewLinkType::Database),
+      _ => None,
+    };
+    if let Some(link_type) = embedded_link_type {
+      if let Some(view_id) = block.data.get(VIEW_ID).and_then(Value::as_str) {
+        links.push(DocumentViewLink {
+          view_id: view_id.to_string(),
+          block_id: block_id.clone(),
+          link_type,
+        });
+      }
+    }
+
+    let delta = get_delta_for_block(block_id, data).unwrap_or_default();
+    for insert in delta {
+      let mention = insert
+        .attributes
+        .as_ref()
+        .and_then(|attributes| attributes.get(MENTION));
+      if let Some(view_id) = mention.and_then(mentioned_page_id) {
+        links.push(DocumentViewLink {
+          view_id,
+          block_id: block_id.clone(),
+          link_type: DocumentViewLinkType::Mention,
+        });
+      }
+    }
+  }
+  links.sort_by(|a, b| (&a.view_id, &a.block_id).cmp(&(&b.view_id, &b.block_id)));
+  links.dedup();
+  links
+}
+
+fn mentioned_page_id(mention: &Value) -> Option<String> {
+  let mention_type = mention.get(MENTION_TYPE)?.as_str()?;
+  if mention_type != MENTION_TYPE_PAGE && mention_type != MENTION_TYPE_CHILD_PAGE {
+    return None;
+  }
+  mention
+    .get(MENTION_PAGE_ID)?
+    .as_str()
+    .filter(|page_id| !page_id.is_empty())
+    .map(|page_id| page_id.to_string())
+}
+
+/// Replaces the ids of the referenced views with the ids in the given map. Used when a set of
+/// views is copied with fresh ids and the references between them must point to the copies.
+/// References to views that are not in the map are left untouched.
+pub fn remap_view_ids(data: &mut DocumentData, view_ids: &HashMap<String, String>) {
+  for block in data.blocks.values_mut() {
+    for key in [VIEW_ID, PARENT_ID] {
+      let new_id = block
+        .data
+        .get(key)
+        .and_then(Value::as_str)
+        .and_then(|id| view_ids.get(id));
+      if let Some(new_id) = new_id {
+        block
+          .data
+          .insert(key.to_string(), Value::String(new_id.clone()));
+      }
+    }
+  }
+
+  if let Some(text_map) = data.meta.text_map.as_mut() {
+    for delta_str in text_map.values_mut() {
+      let Ok(mut delta) = serde_json::from_str::<Value>(delta_str) else {
+        continue;
+      };
+      let mut changed = false;
+      if let Some(inserts) = delta.as_array_mut() {
+        for insert in inserts {
+          let mention = insert
+            .get_mut("attributes")
+            .and_then(|attributes| attributes.get_mut(MENTION))
+            .and_then(Value::as_object_mut);
+          if let Some(mention) = mention {
+            let new_id = mention
+              .get(MENTION_PAGE_ID)
+              .and_then(Value::as_str)
+              .and_then(|id| view_ids.get(id));
+            if let Some(new_id) = new_id {
+              mention.insert(MENTION_PAGE_ID.to_string(), Value::String(new_id.clone()));
+              changed = true;
+            }
+          }
+        }
+      }
+      if changed {
+        *delta_str = delta.to_string();
+      }
+    }
+  }
+}
@@ -190,7 +190,7 @@ impl StorageService for DocumentTestFileStorageService {
     todo!()
   }
 
-  fn download_object(&self, _url: String, _local_file_path: String) -> FlowyResult<()> {
+  async fn download_object(&self, _url: String, _local_file_path: String) -> FlowyResult<()> {
     todo!()
   }
 
@@ -223,6 +223,15 @@ impl StorageService for DocumentTestFileStorageService {
   ) -> Result<Option<FileProgressReceiver>, FlowyError> {
     todo!()
   }
+
+  async fn copy_object(
+    &self,
+    _url: &str,
+    _workspace_id: &str,
+    _parent_dir: &str,
+  ) -> Result<Option<String>, FlowyError> {
+    todo!()
+  }
 }
 
 struct DefaultCollabStorageProvider();
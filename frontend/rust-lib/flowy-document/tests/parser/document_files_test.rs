@@ -0,0 +1,48 @@
+use collab_document::blocks::DocumentData;
+use flowy_document::parser::document_files::extract_file_urls;
+use flowy_document::parser::json::parser::JsonToDocumentParser;
+
+const DOCUMENT_JSON: &str = r#"{
+  "type": "page",
+  "data": { "cover_selection": "https://appflowy.io/cover.png", "cover_selection_type": "CoverType.file" },
+  "children": [
+    {
+      "type": "paragraph",
+      "data": { "delta": [{ "insert": "see https://appflowy.io/text.png" }] },
+      "children": []
+    },
+    {
+      "type": "image",
+      "data": { "url": "https://appflowy.io/image.png", "image_type": 2 },
+      "children": []
+    },
+    {
+      "type": "multi_image",
+      "data": { "images": [{ "url": "https://appflowy.io/a.png" }, { "url": "https://appflowy.io/b.png" }] },
+      "children": []
+    },
+    {
+      "type": "image",
+      "data": { "url": "/tmp/local.png", "image_type": 0 },
+      "children": []
+    }
+  ]
+}"#;
+
+#[test]
+fn extract_file_urls_test() {
+  let data: DocumentData = JsonToDocumentParser::json_str_to_document(DOCUMENT_JSON)
+    .unwrap()
+    .into();
+  let mut urls = extract_file_urls(&data);
+  urls.sort();
+  assert_eq!(
+    urls,
+    vec![
+      "https://appflowy.io/a.png",
+      "https://appflowy.io/b.png",
+      "https://appflowy.io/cover.png",
+      "https://appflowy.io/image.png",
+    ]
+  );
+}
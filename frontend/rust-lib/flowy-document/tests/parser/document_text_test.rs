@@ -0,0 +1,52 @@
+use collab_document::blocks::DocumentData;
+use flowy_document::parser::document_text::extract_block_texts;
+use flowy_document::parser::json::parser::JsonToDocumentParser;
+
+const DOCUMENT_JSON: &str = r#"{
+  "type": "page",
+  "data": {},
+  "children": [
+    {
+      "type": "heading",
+      "data": { "level": 1, "delta": [{ "insert": "Travel plan" }] },
+      "children": []
+    },
+    {
+      "type": "bulleted_list",
+      "data": { "delta": [{ "insert": "Pack the " }, { "insert": "tent", "attributes": { "bold": true } }] },
+      "children": [
+        {
+          "type": "paragraph",
+          "data": { "delta": [{ "insert": "and the stove" }] },
+          "children": []
+        }
+      ]
+    },
+    {
+      "type": "paragraph",
+      "data": { "delta": [{ "insert": "   " }] },
+      "children": []
+    },
+    {
+      "type": "divider",
+      "data": {},
+      "children": []
+    }
+  ]
+}"#;
+
+#[test]
+fn extract_block_texts_test() {
+  let data: DocumentData = JsonToDocumentParser::json_str_to_document(DOCUMENT_JSON)
+    .unwrap()
+    .into();
+  let texts = extract_block_texts(&data)
+    .into_iter()
+    .map(|text| text.text)
+    .collect::<Vec<_>>();
+  assert_eq!(texts, vec!["Travel plan", "Pack the tent", "and the stove"]);
+
+  for text in extract_block_texts(&data) {
+    assert!(data.blocks.contains_key(&text.block_id));
+  }
+}
@@ -1,4 +1,7 @@
 mod document_data_parser_test;
+mod document_files_test;
+mod document_text_test;
 mod html;
 mod json;
 mod parse_to_html_text;
+mod view_link_test;
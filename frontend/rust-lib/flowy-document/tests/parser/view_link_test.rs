@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+
+use collab_document::blocks::DocumentData;
+use flowy_document::parser::json::parser::JsonToDocumentParser;
+use flowy_document::parser::view_link::{extract_view_links, remap_view_ids, DocumentViewLinkType};
+
+const DOCUMENT_JSON: &str = r#"{
+  "type": "page",
+  "data": {},
+  "children": [
+    {
+      "type": "paragraph",
+      "data": {
+        "delta": [
+          { "insert": "see " },
+          { "insert": "$", "attributes": { "mention": { "type": "page", "page_id": "view_1" } } },
+          { "insert": "$", "attributes": { "mention": { "type": "date", "date": "2024-01-01" } } }
+        ]
+      },
+      "children": []
+    },
+    {
+      "type": "sub_page",
+      "data": { "view_id": "view_2" },
+      "children": []
+    },
+    {
+      "type": "grid",
+      "data": { "view_id": "view_3", "parent_id": "view_0" },
+      "children": []
+    }
+  ]
+}"#;
+
+fn document_data() -> DocumentData {
+  JsonToDocumentParser::json_str_to_document(DOCUMENT_JSON)
+    .unwrap()
+    .into()
+}
+
+#[test]
+fn extract_view_links_test() {
+  let links = extract_view_links(&document_data());
+  let links = links
+    .into_iter()
+    .map(|link| (link.view_id, link.link_type))
+    .collect::<Vec<_>>();
+  assert_eq!(
+    links,
+    vec![
+      ("view_1".to_string(), DocumentViewLinkType::Mention),
+      ("view_2".to_string(), DocumentViewLinkType::SubPage),
+      ("view_3".to_string(), DocumentViewLinkType::Database),
+    ]
+  );
+}
+
+#[test]
+fn remap_view_ids_test() {
+  let mut document_data = document_data();
+  let view_ids = HashMap::from([
+    ("view_1".to_string(), "new_view_1".to_string()),
+    ("view_3".to_string(), "new_view_3".to_string()),
+    ("view_0".to_string(), "new_view_0".to_string()),
+  ]);
+  remap_view_ids(&mut document_data, &view_ids);
+
+  let links = extract_view_links(&document_data)
+    .into_iter()
+    .map(|link| link.view_id)
+    .collect::<Vec<_>>();
+  assert_eq!(links, vec!["new_view_1", "new_view_3", "view_2"]);
+
+  let grid = document_data
+    .blocks
+    .values()
+    .find(|block| block.ty == "grid")
+    .unwrap();
+  assert_eq!(grid.data.get("parent_id").unwrap(), "new_view_0");
+}
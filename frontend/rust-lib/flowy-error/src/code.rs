@@ -356,6 +356,9 @@ pub enum ErrorCode {
 
   #[error("Requested namespace has one or more invalid characters")]
   CustomNamespaceInvalidCharacter = 122,
+
+  #[error("AI usage limit of the workspace exceeded")]
+  AIUsageLimitExceeded = 123,
 }
 
 impl ErrorCode {
@@ -91,6 +91,10 @@ impl FlowyError {
     self.code == ErrorCode::AIResponseLimitExceeded
   }
 
+  pub fn is_ai_usage_limit_exceeded(&self) -> bool {
+    self.code == ErrorCode::AIUsageLimitExceeded
+  }
+
   static_flowy_error!(internal, ErrorCode::Internal);
   static_flowy_error!(record_not_found, ErrorCode::RecordNotFound);
   static_flowy_error!(workspace_initialize, ErrorCode::WorkspaceInitializeError);
@@ -139,6 +143,7 @@ impl FlowyError {
   static_flowy_error!(workspace_data_not_match, ErrorCode::WorkspaceDataNotMatch);
   static_flowy_error!(local_ai, ErrorCode::LocalAIError);
   static_flowy_error!(local_ai_unavailable, ErrorCode::LocalAIUnavailable);
+  static_flowy_error!(ai_usage_limit_exceeded, ErrorCode::AIUsageLimitExceeded);
   static_flowy_error!(response_timeout, ErrorCode::ResponseTimeout);
   static_flowy_error!(file_storage_limit, ErrorCode::FileStorageLimitExceeded);
 }
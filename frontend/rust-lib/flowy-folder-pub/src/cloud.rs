@@ -32,6 +32,16 @@ pub trait FolderCloudService: Send + Sync + 'static {
     limit: usize,
   ) -> Result<Vec<FolderSnapshot>, FlowyError>;
 
+  /// Returns the snapshot of the workspace folder with the given id, or None if the snapshot
+  /// doesn't exist.
+  async fn get_folder_snapshot(
+    &self,
+    _workspace_id: &str,
+    _snapshot_id: &str,
+  ) -> Result<Option<FolderSnapshot>, FlowyError> {
+    Err(FlowyError::not_support())
+  }
+
   async fn get_folder_doc_state(
     &self,
     workspace_id: &str,
@@ -106,7 +116,7 @@ pub struct FolderCollabParams {
 }
 
 pub struct FolderSnapshot {
-  pub snapshot_id: i64,
+  pub snapshot_id: String,
   pub database_id: String,
   pub data: Vec<u8>,
   pub created_at: i64,
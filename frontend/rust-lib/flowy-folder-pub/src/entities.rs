@@ -116,3 +116,32 @@ pub enum PublishPayload {
   Database(PublishDatabasePayload),
   Unknown,
 }
+
+/// The key of the view's extra that holds the tags of the view.
+pub const VIEW_TAGS_KEY: &str = "tags";
+
+/// Returns the tags stored in the extra of a view. The extra is a JSON object and the tags are
+/// stored as an array of strings under [VIEW_TAGS_KEY].
+pub fn view_tags_from_extra(extra: Option<&str>) -> Vec<String> {
+  extra
+    .and_then(|extra| serde_json::from_str::<serde_json::Value>(extra).ok())
+    .and_then(|mut extra| extra.get_mut(VIEW_TAGS_KEY).map(serde_json::Value::take))
+    .and_then(|tags| serde_json::from_value::<Vec<String>>(tags).ok())
+    .unwrap_or_default()
+}
+
+/// Returns the extra of a view with the tags replaced by the given tags. The other values of the
+/// extra are kept.
+pub fn view_extra_with_tags(extra: Option<&str>, tags: &[String]) -> String {
+  let mut extra = extra
+    .and_then(|extra| {
+      serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(extra).ok()
+    })
+    .unwrap_or_default();
+  if tags.is_empty() {
+    extra.remove(VIEW_TAGS_KEY);
+  } else {
+    extra.insert(VIEW_TAGS_KEY.to_string(), serde_json::json!(tags));
+  }
+  serde_json::Value::Object(extra).to_string()
+}
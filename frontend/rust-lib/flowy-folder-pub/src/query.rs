@@ -8,4 +8,8 @@ pub trait FolderQueryService: Send + Sync + 'static {
     parent_view_id: &str,
     view_layout: ViewLayout,
   ) -> Vec<String>;
+
+  /// Returns the ids of the views in the current workspace with the given layout, excluding the
+  /// views in the trash.
+  async fn get_view_ids_with_view_layout(&self, view_layout: ViewLayout) -> Vec<String>;
 }
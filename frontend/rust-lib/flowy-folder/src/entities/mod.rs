@@ -2,13 +2,21 @@ pub mod icon;
 mod import;
 mod parser;
 pub mod publish;
+pub mod template;
+pub mod transfer;
 pub mod trash;
 pub mod view;
+pub mod view_link;
+pub mod view_tag;
 pub mod workspace;
 
 pub use icon::*;
 pub use import::*;
 pub use publish::*;
+pub use template::*;
+pub use transfer::*;
 pub use trash::*;
 pub use view::*;
+pub use view_link::*;
+pub use view_tag::*;
 pub use workspace::*;
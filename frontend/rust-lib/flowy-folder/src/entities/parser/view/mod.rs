@@ -1,7 +1,9 @@
 mod view_id;
 mod view_name;
+mod view_tag;
 mod view_thumbnail;
 
 pub use view_id::*;
 pub use view_name::*;
+pub use view_tag::*;
 pub use view_thumbnail::*;
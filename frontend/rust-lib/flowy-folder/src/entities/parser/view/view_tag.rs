@@ -0,0 +1,24 @@
+use flowy_error::ErrorCode;
+use unicode_segmentation::UnicodeSegmentation;
+
+const VIEW_TAG_MAX_LENGTH: usize = 64;
+
+#[derive(Debug)]
+pub struct ViewTagName(pub String);
+
+impl ViewTagName {
+  pub fn parse(s: String) -> Result<ViewTagName, ErrorCode> {
+    let s = s.trim().to_string();
+    if s.is_empty() || s.graphemes(true).count() > VIEW_TAG_MAX_LENGTH {
+      return Err(ErrorCode::InvalidParams);
+    }
+
+    Ok(Self(s))
+  }
+}
+
+impl AsRef<str> for ViewTagName {
+  fn as_ref(&self) -> &str {
+    &self.0
+  }
+}
@@ -0,0 +1,77 @@
+use flowy_derive::ProtoBuf;
+use flowy_error::ErrorCode;
+
+use crate::entities::parser::view::{ViewIdentify, ViewName};
+
+#[derive(Default, ProtoBuf, Debug, Clone)]
+pub struct SaveViewAsTemplatePayloadPB {
+  #[pb(index = 1)]
+  pub view_id: String,
+
+  #[pb(index = 2)]
+  pub name: String,
+
+  /// The path of the template file to write.
+  #[pb(index = 3)]
+  pub file_path: String,
+}
+
+#[derive(Debug)]
+pub struct SaveViewAsTemplateParams {
+  pub view_id: String,
+  pub name: String,
+  pub file_path: String,
+}
+
+impl TryInto<SaveViewAsTemplateParams> for SaveViewAsTemplatePayloadPB {
+  type Error = ErrorCode;
+
+  fn try_into(self) -> Result<SaveViewAsTemplateParams, Self::Error> {
+    let view_id = ViewIdentify::parse(self.view_id)?.0;
+    let name = ViewName::parse(self.name)?.0;
+    if self.file_path.is_empty() {
+      return Err(ErrorCode::InvalidParams);
+    }
+    Ok(SaveViewAsTemplateParams {
+      view_id,
+      name,
+      file_path: self.file_path,
+    })
+  }
+}
+
+#[derive(Default, ProtoBuf, Debug, Clone)]
+pub struct CreateViewFromTemplatePayloadPB {
+  /// The path of the template file saved by the SaveViewAsTemplate event.
+  #[pb(index = 1)]
+  pub file_path: String,
+
+  /// The view that the template will be created under. If it's not set, the views of the template
+  /// are created at the top level of the current workspace.
+  #[pb(index = 2, one_of)]
+  pub parent_view_id: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct CreateViewFromTemplateParams {
+  pub file_path: String,
+  pub parent_view_id: Option<String>,
+}
+
+impl TryInto<CreateViewFromTemplateParams> for CreateViewFromTemplatePayloadPB {
+  type Error = ErrorCode;
+
+  fn try_into(self) -> Result<CreateViewFromTemplateParams, Self::Error> {
+    if self.file_path.is_empty() {
+      return Err(ErrorCode::InvalidParams);
+    }
+    let parent_view_id = match self.parent_view_id {
+      Some(parent_view_id) => Some(ViewIdentify::parse(parent_view_id)?.0),
+      None => None,
+    };
+    Ok(CreateViewFromTemplateParams {
+      file_path: self.file_path,
+      parent_view_id,
+    })
+  }
+}
@@ -0,0 +1,53 @@
+use flowy_derive::ProtoBuf;
+use flowy_error::ErrorCode;
+
+use crate::entities::parser::view::ViewIdentify;
+
+#[derive(Default, ProtoBuf, Debug, Clone)]
+pub struct MoveViewToWorkspacePayloadPB {
+  #[pb(index = 1)]
+  pub view_id: String,
+
+  /// The workspace that the view and its children will be moved to. The user must be a member
+  /// of the workspace.
+  #[pb(index = 2)]
+  pub workspace_id: String,
+
+  /// The view of the target workspace that the views will be created under. If it's not set,
+  /// the views are created at the top level of the target workspace.
+  #[pb(index = 3, one_of)]
+  pub parent_view_id: Option<String>,
+
+  /// Keep the views in the current workspace. The views are copied instead of moved.
+  #[pb(index = 4)]
+  pub keep_original: bool,
+}
+
+#[derive(Debug)]
+pub struct MoveViewToWorkspaceParams {
+  pub view_id: String,
+  pub workspace_id: String,
+  pub parent_view_id: Option<String>,
+  pub keep_original: bool,
+}
+
+impl TryInto<MoveViewToWorkspaceParams> for MoveViewToWorkspacePayloadPB {
+  type Error = ErrorCode;
+
+  fn try_into(self) -> Result<MoveViewToWorkspaceParams, Self::Error> {
+    let view_id = ViewIdentify::parse(self.view_id)?.0;
+    if self.workspace_id.is_empty() {
+      return Err(ErrorCode::InvalidParams);
+    }
+    let parent_view_id = match self.parent_view_id {
+      Some(parent_view_id) => Some(ViewIdentify::parse(parent_view_id)?.0),
+      None => None,
+    };
+    Ok(MoveViewToWorkspaceParams {
+      view_id,
+      workspace_id: self.workspace_id,
+      parent_view_id,
+      keep_original: self.keep_original,
+    })
+  }
+}
@@ -7,6 +7,7 @@ use std::sync::Arc;
 use flowy_derive::{ProtoBuf, ProtoBuf_Enum};
 use flowy_error::ErrorCode;
 use flowy_folder_pub::cloud::gen_view_id;
+use flowy_folder_pub::entities::view_tags_from_extra;
 
 use crate::entities::icon::ViewIconPB;
 use crate::entities::parser::view::{ViewIdentify, ViewName, ViewThumbnail};
@@ -72,6 +73,10 @@ pub struct ViewPB {
   // user_id
   #[pb(index = 12, one_of)]
   pub last_edited_by: Option<i64>,
+
+  /// The tags of the view. The tags are stored in the extra of the view.
+  #[pb(index = 13)]
+  pub tags: Vec<String>,
 }
 
 pub fn view_pb_without_child_views(view: View) -> ViewPB {
@@ -84,6 +89,7 @@ pub fn view_pb_without_child_views(view: View) -> ViewPB {
     layout: view.layout.into(),
     icon: view.icon.clone().map(|icon| icon.into()),
     is_favorite: view.is_favorite,
+    tags: view_tags_from_extra(view.extra.as_deref()),
     extra: view.extra,
     created_by: view.created_by,
     last_edited: view.last_edited_time,
@@ -102,6 +108,7 @@ pub fn view_pb_without_child_views_from_arc(view: Arc<View>) -> ViewPB {
     icon: view.icon.clone().map(|icon| icon.into()),
     is_favorite: view.is_favorite,
     extra: view.extra.clone(),
+    tags: view_tags_from_extra(view.extra.as_deref()),
     created_by: view.created_by,
     last_edited: view.last_edited_time,
     last_edited_by: view.last_edited_by,
@@ -123,6 +130,7 @@ pub fn view_pb_with_child_views(view: Arc<View>, child_views: Vec<Arc<View>>) ->
     icon: view.icon.clone().map(|icon| icon.into()),
     is_favorite: view.is_favorite,
     extra: view.extra.clone(),
+    tags: view_tags_from_extra(view.extra.as_deref()),
     created_by: view.created_by,
     last_edited: view.last_edited_time,
     last_edited_by: view.last_edited_by,
@@ -656,6 +664,37 @@ impl TryInto<DuplicateViewParams> for DuplicateViewPayloadPB {
   }
 }
 
+#[derive(Default, ProtoBuf)]
+pub struct RestoreViewSnapshotPayloadPB {
+  #[pb(index = 1)]
+  pub view_id: String,
+
+  // The id of the snapshot returned by the snapshot events of the view.
+  #[pb(index = 2)]
+  pub snapshot_id: String,
+}
+
+#[derive(Debug)]
+pub struct RestoreViewSnapshotParams {
+  pub view_id: String,
+  pub snapshot_id: String,
+}
+
+impl TryInto<RestoreViewSnapshotParams> for RestoreViewSnapshotPayloadPB {
+  type Error = ErrorCode;
+
+  fn try_into(self) -> Result<RestoreViewSnapshotParams, Self::Error> {
+    let view_id = ViewIdentify::parse(self.view_id)?.0;
+    if self.snapshot_id.is_empty() {
+      return Err(ErrorCode::InvalidParams);
+    }
+    Ok(RestoreViewSnapshotParams {
+      view_id,
+      snapshot_id: self.snapshot_id,
+    })
+  }
+}
+
 // impl<'de> Deserialize<'de> for ViewDataType {
 //     fn deserialize<D>(deserializer: D) -> Result<Self, <D as Deserializer<'de>>::Error>
 //     where
@@ -0,0 +1,51 @@
+use flowy_derive::{ProtoBuf, ProtoBuf_Enum};
+
+use crate::entities::ViewPB;
+use crate::view_operation::ViewLinkType;
+
+#[derive(Eq, PartialEq, Hash, Debug, ProtoBuf_Enum, Clone, Default)]
+pub enum ViewLinkTypePB {
+  #[default]
+  Mention = 0,
+  SubPage = 1,
+  DatabaseReference = 2,
+  DatabaseRelation = 3,
+}
+
+impl From<ViewLinkType> for ViewLinkTypePB {
+  fn from(value: ViewLinkType) -> Self {
+    match value {
+      ViewLinkType::Mention => ViewLinkTypePB::Mention,
+      ViewLinkType::SubPage => ViewLinkTypePB::SubPage,
+      ViewLinkType::DatabaseReference => ViewLinkTypePB::DatabaseReference,
+      ViewLinkType::DatabaseRelation => ViewLinkTypePB::DatabaseRelation,
+    }
+  }
+}
+
+#[derive(Eq, PartialEq, ProtoBuf, Default, Debug, Clone)]
+pub struct ViewLinkPB {
+  #[pb(index = 1)]
+  pub source_view_id: String,
+
+  #[pb(index = 2)]
+  pub target_view_id: String,
+
+  /// The id of the block or the field that holds the link.
+  #[pb(index = 3)]
+  pub block_id: String,
+
+  #[pb(index = 4)]
+  pub link_type: ViewLinkTypePB,
+
+  /// The view on the other side of the link. For backlinks, it's the source view. For outgoing
+  /// links, it's the target view.
+  #[pb(index = 5)]
+  pub view: ViewPB,
+}
+
+#[derive(Eq, PartialEq, ProtoBuf, Default, Debug, Clone)]
+pub struct RepeatedViewLinkPB {
+  #[pb(index = 1)]
+  pub items: Vec<ViewLinkPB>,
+}
@@ -0,0 +1,58 @@
+use flowy_derive::ProtoBuf;
+use flowy_error::ErrorCode;
+
+use crate::entities::parser::view::{ViewIdentify, ViewTagName};
+
+#[derive(Default, ProtoBuf, Debug, Clone)]
+pub struct UpdateViewTagsPayloadPB {
+  #[pb(index = 1)]
+  pub view_id: String,
+
+  #[pb(index = 2)]
+  pub tags: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct UpdateViewTagsParams {
+  pub view_id: String,
+  pub tags: Vec<String>,
+}
+
+impl TryInto<UpdateViewTagsParams> for UpdateViewTagsPayloadPB {
+  type Error = ErrorCode;
+
+  fn try_into(self) -> Result<UpdateViewTagsParams, Self::Error> {
+    let view_id = ViewIdentify::parse(self.view_id)?.0;
+    let tags = self
+      .tags
+      .into_iter()
+      .map(|tag| ViewTagName::parse(tag).map(|tag| tag.0))
+      .collect::<Result<Vec<_>, _>>()?;
+    if tags.is_empty() {
+      return Err(ErrorCode::InvalidParams);
+    }
+    Ok(UpdateViewTagsParams { view_id, tags })
+  }
+}
+
+#[derive(Default, ProtoBuf, Debug, Clone)]
+pub struct ViewTagNamePB {
+  #[pb(index = 1)]
+  pub name: String,
+}
+
+#[derive(Eq, PartialEq, ProtoBuf, Default, Debug, Clone)]
+pub struct ViewTagPB {
+  #[pb(index = 1)]
+  pub name: String,
+
+  /// The number of views that have the tag.
+  #[pb(index = 2)]
+  pub view_count: i64,
+}
+
+#[derive(Eq, PartialEq, ProtoBuf, Default, Debug, Clone)]
+pub struct RepeatedViewTagPB {
+  #[pb(index = 1)]
+  pub items: Vec<ViewTagPB>,
+}
@@ -188,7 +188,7 @@ pub struct RepeatedFolderSnapshotPB {
 #[derive(Debug, Default, ProtoBuf)]
 pub struct FolderSnapshotPB {
   #[pb(index = 1)]
-  pub snapshot_id: i64,
+  pub snapshot_id: String,
 
   #[pb(index = 2)]
   pub snapshot_desc: String,
@@ -200,6 +200,12 @@ pub struct FolderSnapshotPB {
   pub data: Vec<u8>,
 }
 
+#[derive(Debug, Default, ProtoBuf)]
+pub struct RestoreFolderSnapshotPayloadPB {
+  #[pb(index = 1)]
+  pub snapshot_id: String,
+}
+
 #[derive(Debug, Default, ProtoBuf)]
 pub struct FolderSnapshotStatePB {
   #[pb(index = 1)]
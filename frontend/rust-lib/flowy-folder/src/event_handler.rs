@@ -405,6 +405,128 @@ pub(crate) async fn get_folder_snapshots_handler(
   data_result_ok(RepeatedFolderSnapshotPB { items: snapshots })
 }
 
+#[tracing::instrument(level = "debug", skip(data, folder), err)]
+pub(crate) async fn restore_view_snapshot_handler(
+  data: AFPluginData<RestoreViewSnapshotPayloadPB>,
+  folder: AFPluginState<Weak<FolderManager>>,
+) -> DataResult<ViewPB, FlowyError> {
+  let folder = upgrade_folder(folder)?;
+  let params: RestoreViewSnapshotParams = data.into_inner().try_into()?;
+  let view_pb = folder.restore_view_from_snapshot(params).await?;
+  data_result_ok(view_pb)
+}
+
+#[tracing::instrument(level = "debug", skip(data, folder), err)]
+pub(crate) async fn get_view_backlinks_handler(
+  data: AFPluginData<ViewIdPB>,
+  folder: AFPluginState<Weak<FolderManager>>,
+) -> DataResult<RepeatedViewLinkPB, FlowyError> {
+  let folder = upgrade_folder(folder)?;
+  let view_id = data.into_inner().value;
+  let items = folder.get_view_backlinks(&view_id).await?;
+  data_result_ok(RepeatedViewLinkPB { items })
+}
+
+#[tracing::instrument(level = "debug", skip(data, folder), err)]
+pub(crate) async fn get_view_outgoing_links_handler(
+  data: AFPluginData<ViewIdPB>,
+  folder: AFPluginState<Weak<FolderManager>>,
+) -> DataResult<RepeatedViewLinkPB, FlowyError> {
+  let folder = upgrade_folder(folder)?;
+  let view_id = data.into_inner().value;
+  let items = folder.get_view_outgoing_links(&view_id).await?;
+  data_result_ok(RepeatedViewLinkPB { items })
+}
+
+#[tracing::instrument(level = "debug", skip(data, folder), err)]
+pub(crate) async fn save_view_as_template_handler(
+  data: AFPluginData<SaveViewAsTemplatePayloadPB>,
+  folder: AFPluginState<Weak<FolderManager>>,
+) -> Result<(), FlowyError> {
+  let folder = upgrade_folder(folder)?;
+  let params: SaveViewAsTemplateParams = data.into_inner().try_into()?;
+  folder.save_view_as_template(params).await?;
+  Ok(())
+}
+
+#[tracing::instrument(level = "debug", skip(data, folder), err)]
+pub(crate) async fn create_view_from_template_handler(
+  data: AFPluginData<CreateViewFromTemplatePayloadPB>,
+  folder: AFPluginState<Weak<FolderManager>>,
+) -> DataResult<ViewPB, FlowyError> {
+  let folder = upgrade_folder(folder)?;
+  let params: CreateViewFromTemplateParams = data.into_inner().try_into()?;
+  let view_pb = folder.create_view_from_template(params).await?;
+  data_result_ok(view_pb)
+}
+
+#[tracing::instrument(level = "debug", skip(data, folder), err)]
+pub(crate) async fn move_view_to_workspace_handler(
+  data: AFPluginData<MoveViewToWorkspacePayloadPB>,
+  folder: AFPluginState<Weak<FolderManager>>,
+) -> Result<(), FlowyError> {
+  let folder = upgrade_folder(folder)?;
+  let params: MoveViewToWorkspaceParams = data.into_inner().try_into()?;
+  folder.move_view_to_workspace(params).await?;
+  Ok(())
+}
+
+#[tracing::instrument(level = "debug", skip(data, folder), err)]
+pub(crate) async fn add_view_tags_handler(
+  data: AFPluginData<UpdateViewTagsPayloadPB>,
+  folder: AFPluginState<Weak<FolderManager>>,
+) -> Result<(), FlowyError> {
+  let folder = upgrade_folder(folder)?;
+  let params: UpdateViewTagsParams = data.into_inner().try_into()?;
+  folder.add_view_tags(params).await?;
+  Ok(())
+}
+
+#[tracing::instrument(level = "debug", skip(data, folder), err)]
+pub(crate) async fn remove_view_tags_handler(
+  data: AFPluginData<UpdateViewTagsPayloadPB>,
+  folder: AFPluginState<Weak<FolderManager>>,
+) -> Result<(), FlowyError> {
+  let folder = upgrade_folder(folder)?;
+  let params: UpdateViewTagsParams = data.into_inner().try_into()?;
+  folder.remove_view_tags(params).await?;
+  Ok(())
+}
+
+#[tracing::instrument(level = "debug", skip(folder), err)]
+pub(crate) async fn get_workspace_view_tags_handler(
+  folder: AFPluginState<Weak<FolderManager>>,
+) -> DataResult<RepeatedViewTagPB, FlowyError> {
+  let folder = upgrade_folder(folder)?;
+  let items = folder.get_workspace_view_tags().await?;
+  data_result_ok(RepeatedViewTagPB { items })
+}
+
+#[tracing::instrument(level = "debug", skip(data, folder), err)]
+pub(crate) async fn get_views_by_tag_handler(
+  data: AFPluginData<ViewTagNamePB>,
+  folder: AFPluginState<Weak<FolderManager>>,
+) -> DataResult<RepeatedViewPB, FlowyError> {
+  let folder = upgrade_folder(folder)?;
+  let tag = data.into_inner().name;
+  let items = folder.get_views_by_tag(&tag).await?;
+  data_result_ok(RepeatedViewPB { items })
+}
+
+#[tracing::instrument(level = "debug", skip(data, folder), err)]
+pub(crate) async fn restore_folder_snapshot_handler(
+  data: AFPluginData<RestoreFolderSnapshotPayloadPB>,
+  folder: AFPluginState<Weak<FolderManager>>,
+) -> DataResult<RepeatedViewPB, FlowyError> {
+  let folder = upgrade_folder(folder)?;
+  let snapshot_id = data.into_inner().snapshot_id;
+  if snapshot_id.is_empty() {
+    return Err(FlowyError::invalid_data().with_context("The snapshot id is empty"));
+  }
+  let views = folder.restore_folder_snapshot(&snapshot_id).await?;
+  data_result_ok(RepeatedViewPB { items: views })
+}
+
 #[tracing::instrument(level = "debug", skip(data, folder), err)]
 pub(crate) async fn update_view_visibility_status_handler(
   data: AFPluginData<UpdateViewVisibilityStatusPayloadPB>,
@@ -53,6 +53,17 @@ pub fn init(folder: Weak<FolderManager>) -> AFPlugin {
     .event(FolderEvent::GetDefaultPublishInfo, get_default_publish_info_handler)
     .event(FolderEvent::SetDefaultPublishView, set_default_publish_view_handler)
     .event(FolderEvent::RemoveDefaultPublishView, remove_default_publish_view_handler)
+    .event(FolderEvent::RestoreViewSnapshot, restore_view_snapshot_handler)
+    .event(FolderEvent::RestoreFolderSnapshot, restore_folder_snapshot_handler)
+    .event(FolderEvent::GetViewBacklinks, get_view_backlinks_handler)
+    .event(FolderEvent::GetViewOutgoingLinks, get_view_outgoing_links_handler)
+    .event(FolderEvent::SaveViewAsTemplate, save_view_as_template_handler)
+    .event(FolderEvent::CreateViewFromTemplate, create_view_from_template_handler)
+    .event(FolderEvent::MoveViewToWorkspace, move_view_to_workspace_handler)
+    .event(FolderEvent::AddViewTags, add_view_tags_handler)
+    .event(FolderEvent::RemoveViewTags, remove_view_tags_handler)
+    .event(FolderEvent::GetWorkspaceViewTags, get_workspace_view_tags_handler)
+    .event(FolderEvent::GetViewsByTag, get_views_by_tag_handler)
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Display, Hash, ProtoBuf_Enum, Flowy_Event)]
@@ -220,4 +231,50 @@ pub enum FolderEvent {
 
   #[event()]
   RemoveDefaultPublishView = 53,
+
+  /// Create a new view next to the given view with the content of one of its snapshots.
+  #[event(input = "RestoreViewSnapshotPayloadPB", output = "ViewPB")]
+  RestoreViewSnapshot = 54,
+
+  /// Restore the structure of the workspace to the state of one of its folder snapshots. Returns
+  /// the views whose state was restored.
+  #[event(input = "RestoreFolderSnapshotPayloadPB", output = "RepeatedViewPB")]
+  RestoreFolderSnapshot = 55,
+
+  /// Return the views that mention, embed or relate to the given view.
+  #[event(input = "ViewIdPB", output = "RepeatedViewLinkPB")]
+  GetViewBacklinks = 56,
+
+  /// Return the views that the given view mentions, embeds or relates to.
+  #[event(input = "ViewIdPB", output = "RepeatedViewLinkPB")]
+  GetViewOutgoingLinks = 57,
+
+  /// Save the view and its children as a template file.
+  #[event(input = "SaveViewAsTemplatePayloadPB")]
+  SaveViewAsTemplate = 58,
+
+  /// Create the views of a template file in the current workspace.
+  #[event(input = "CreateViewFromTemplatePayloadPB", output = "ViewPB")]
+  CreateViewFromTemplate = 59,
+
+  /// Move or copy the view and its children to another workspace of the user. The views are
+  /// created in the target workspace when it's opened.
+  #[event(input = "MoveViewToWorkspacePayloadPB")]
+  MoveViewToWorkspace = 60,
+
+  /// Add tags to the view.
+  #[event(input = "UpdateViewTagsPayloadPB")]
+  AddViewTags = 61,
+
+  /// Remove tags from the view.
+  #[event(input = "UpdateViewTagsPayloadPB")]
+  RemoveViewTags = 62,
+
+  /// Return all the tags of the views in the current workspace.
+  #[event(output = "RepeatedViewTagPB")]
+  GetWorkspaceViewTags = 63,
+
+  /// Return the views that have the given tag.
+  #[event(input = "ViewTagNamePB", output = "RepeatedViewPB")]
+  GetViewsByTag = 64,
 }
@@ -7,11 +7,15 @@ pub mod manager;
 pub mod notification;
 pub mod protobuf;
 mod user_default;
+pub mod view_link;
 pub mod view_operation;
+pub mod view_tag;
 
 mod manager_init;
 mod manager_observer;
 
 pub mod publish_util;
 pub mod share;
+pub mod template;
+pub mod transfer;
 mod util;
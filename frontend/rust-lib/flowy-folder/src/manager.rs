@@ -2,8 +2,9 @@ use crate::entities::icon::UpdateViewIconParams;
 use crate::entities::{
   view_pb_with_child_views, view_pb_without_child_views, view_pb_without_child_views_from_arc,
   CreateViewParams, CreateWorkspaceParams, DeletedViewPB, DuplicateViewParams, FolderSnapshotPB,
-  MoveNestedViewParams, RepeatedTrashPB, RepeatedViewIdPB, RepeatedViewPB, UpdateViewParams,
-  ViewLayoutPB, ViewPB, ViewSectionPB, WorkspacePB, WorkspaceSettingPB,
+  MoveNestedViewParams, RepeatedTrashPB, RepeatedViewIdPB, RepeatedViewPB,
+  RestoreViewSnapshotParams, UpdateViewParams, ViewLayoutPB, ViewLinkPB, ViewPB, ViewSectionPB,
+  WorkspacePB, WorkspaceSettingPB,
 };
 use crate::manager_observer::{
   notify_child_views_changed, notify_did_update_workspace, notify_parent_view_did_change,
@@ -14,7 +15,11 @@ use crate::notification::{
 };
 use crate::publish_util::{generate_publish_name, view_pb_to_publish_view};
 use crate::share::{ImportData, ImportItem, ImportParams};
+use crate::template::collab_type_of_layout;
 use crate::util::{folder_not_init_error, workspace_data_not_sync_error};
+use crate::view_link::{
+  delete_view_links, replace_view_links, select_backlinks, select_outgoing_links, ViewLinkTable,
+};
 use crate::view_operation::{
   create_view, EncodedCollabType, FolderOperationHandler, FolderOperationHandlers, ViewData,
 };
@@ -22,6 +27,7 @@ use arc_swap::ArcSwapOption;
 use client_api::entity::workspace_dto::PublishInfoView;
 use client_api::entity::PublishInfo;
 use collab::core::collab::DataSource;
+use collab::core::origin::CollabOrigin;
 use collab::lock::RwLock;
 use collab_entity::{CollabType, EncodedCollab};
 use collab_folder::hierarchy_builder::{ParentChildViews, SpacePermission, ViewExtraBuilder};
@@ -41,8 +47,9 @@ use flowy_folder_pub::entities::{
 };
 use flowy_search_pub::entities::FolderIndexManager;
 use flowy_sqlite::kv::KVStorePreferences;
+use flowy_sqlite::DBConnection;
 use futures::future;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Display, Formatter};
 use std::sync::{Arc, Weak};
 use tokio::sync::RwLockWriteGuard;
@@ -52,8 +59,12 @@ pub trait FolderUser: Send + Sync {
   fn user_id(&self) -> Result<i64, FlowyError>;
   fn workspace_id(&self) -> Result<String, FlowyError>;
   fn collab_db(&self, uid: i64) -> Result<Weak<CollabKVDB>, FlowyError>;
+  fn sqlite_connection(&self, uid: i64) -> Result<DBConnection, FlowyError>;
 
   fn is_folder_exist_on_disk(&self, uid: i64, workspace_id: &str) -> FlowyResult<bool>;
+
+  /// Returns true if the user is a member of the given workspace.
+  fn is_user_workspace(&self, uid: i64, workspace_id: &str) -> FlowyResult<bool>;
 }
 
 pub struct FolderManager {
@@ -725,6 +736,22 @@ impl FolderManager {
     Ok(views)
   }
 
+  /// Returns all the views of the workspace without the child views. Unlike
+  /// [Self::get_all_views_pb], the views in the trash are included, since they can be restored.
+  pub async fn get_all_views_with_trash_pb(&self) -> FlowyResult<Vec<ViewPB>> {
+    let lock = self
+      .mutex_folder
+      .load_full()
+      .ok_or_else(folder_not_init_error)?;
+    let folder = lock.read().await;
+    let views = folder
+      .get_all_views()
+      .into_iter()
+      .map(view_pb_without_child_views_from_arc)
+      .collect::<Vec<_>>();
+    Ok(views)
+  }
+
   /// Retrieves the ancestors of the view corresponding to the specified view ID, including the view itself.
   ///
   /// For example, if the view hierarchy is as follows:
@@ -775,8 +802,32 @@ impl FolderManager {
       if let Some(view) = folder.get_view(view_id) {
         Self::unfavorite_view_and_decendants(view.clone(), &mut folder);
         folder.add_trash_view_ids(vec![view_id.to_string()]);
+        // The descendants are in the trash with the view
+        let mut trashed_views = vec![view.clone()];
+        let mut visited = HashSet::from([view.id.clone()]);
+        let mut index = 0;
+        while index < trashed_views.len() {
+          let children = folder.get_views_belong_to(&trashed_views[index].id);
+          trashed_views.extend(
+            children
+              .into_iter()
+              .filter(|child| visited.insert(child.id.clone())),
+          );
+          index += 1;
+        }
         drop(folder);
 
+        for trashed_view in trashed_views {
+          if let Ok(handler) = self.get_handler(&trashed_view.layout) {
+            if let Err(err) = handler.did_move_view_to_trash(&trashed_view.id).await {
+              error!(
+                "Failed to handle the trashed view {}: {:?}",
+                trashed_view.id, err
+              );
+            }
+          }
+        }
+
         // notify the parent view that the view is moved to trash
         folder_notification_builder(view_id, FolderNotification::DidMoveViewToTrash)
           .payload(DeletedViewPB {
@@ -1133,35 +1184,14 @@ impl FolderManager {
         icon: view.icon.clone(),
       };
 
-      // set the notify_workspace_update to false to avoid multiple notifications
-      let (duplicated_view, encoded_collab) = self
-        .create_view_with_params(duplicate_params, false)
+      let duplicated_view = self
+        .create_view_and_collect_collab(duplicate_params, sync_after_create, &mut objects)
         .await?;
 
       if is_source_view {
         new_view_id.clone_from(&duplicated_view.id);
       }
 
-      if sync_after_create {
-        if let Some(encoded_collab) = encoded_collab {
-          let object_id = duplicated_view.id.clone();
-          let collab_type = match duplicated_view.layout {
-            ViewLayout::Document => CollabType::Document,
-            ViewLayout::Board | ViewLayout::Grid | ViewLayout::Calendar => CollabType::Database,
-            ViewLayout::Chat => CollabType::Unknown,
-          };
-          // don't block the whole import process if the view can't be encoded
-          if collab_type != CollabType::Unknown {
-            match self.get_folder_collab_params(object_id, collab_type, encoded_collab) {
-              Ok(params) => objects.push(params),
-              Err(e) => {
-                error!("duplicate error {}", e);
-              },
-            }
-          }
-        }
-      }
-
       if include_children {
         let child_views = self.get_views_belong_to(&current_view_id).await?;
         // reverse the child views to keep the order
@@ -1195,6 +1225,272 @@ impl FolderManager {
     Ok(duplicated_view)
   }
 
+  /// Create a view without notifying the workspace update, the caller notifies once all its views
+  /// are created. When `sync_after_create` is true, the collab of the view is added to `objects`,
+  /// so the views can be uploaded in a batch.
+  pub(crate) async fn create_view_and_collect_collab(
+    &self,
+    params: CreateViewParams,
+    sync_after_create: bool,
+    objects: &mut Vec<FolderCollabParams>,
+  ) -> FlowyResult<View> {
+    let (view, encoded_collab) = self.create_view_with_params(params, false).await?;
+    if sync_after_create {
+      if let Some(encoded_collab) = encoded_collab {
+        let object_id = view.id.clone();
+        let collab_type = collab_type_of_layout(&view.layout);
+        // don't block the whole process if the view can't be encoded
+        if collab_type != CollabType::Unknown {
+          match self.get_folder_collab_params(object_id, collab_type, encoded_collab) {
+            Ok(params) => objects.push(params),
+            Err(e) => {
+              error!("create view error {}", e);
+            },
+          }
+        }
+      }
+    }
+    Ok(view)
+  }
+
+  /// Create a new view with the content of a snapshot of the given view. The new view is inserted
+  /// right after the given view, so the current content of the view is left untouched.
+  #[tracing::instrument(level = "debug", skip_all, err)]
+  pub(crate) async fn restore_view_from_snapshot(
+    &self,
+    params: RestoreViewSnapshotParams,
+  ) -> Result<ViewPB, FlowyError> {
+    let view = self.get_view(&params.view_id).await?;
+    let handler = self.get_handler(&view.layout)?;
+    let view_data = handler
+      .view_data_from_snapshot(&view.id, &params.snapshot_id)
+      .await?;
+
+    let index = self
+      .get_view_relation(&view.id)
+      .await
+      .and_then(|(_, _, views)| views.iter().position(|id| *id == view.id))
+      .map(|i| i as u32 + 1);
+    let name = format!(
+      "{} (restored)",
+      if view.name.is_empty() {
+        "Untitled"
+      } else {
+        view.name.as_str()
+      }
+    );
+    let restore_params = CreateViewParams {
+      parent_view_id: view.parent_view_id.clone(),
+      name,
+      layout: view.layout.clone().into(),
+      initial_data: ViewData::Data(view_data),
+      view_id: gen_view_id().to_string(),
+      meta: Default::default(),
+      set_as_current: false,
+      index,
+      section: None,
+      extra: view.extra.clone(),
+      icon: view.icon.clone(),
+    };
+    let (restored_view, _) = self.create_view_with_params(restore_params, true).await?;
+
+    let workspace_id = self.user.workspace_id()?;
+    if let Some(lock) = self.mutex_folder.load_full() {
+      let folder = lock.read().await;
+      notify_parent_view_did_change(&workspace_id, &folder, vec![view.parent_view_id.clone()]);
+    }
+    self.get_view_pb(&restored_view.id).await
+  }
+
+  /// Re-compute the outgoing links of the view from its content and persist them. The links are
+  /// used to answer the backlinks queries of the views that are referenced by this view.
+  #[tracing::instrument(level = "trace", skip(self), err)]
+  pub async fn index_view_links(&self, view_id: &str) -> FlowyResult<()> {
+    let view = self.get_view(view_id).await?;
+    let handler = self.get_handler(&view.layout)?;
+    index_view_links_with_handler(&self.user, handler, view_id).await
+  }
+
+  /// Returns the links that point to the given view. Links from views that were deleted or moved
+  /// to the trash are skipped.
+  #[tracing::instrument(level = "debug", skip(self), err)]
+  pub(crate) async fn get_view_backlinks(&self, view_id: &str) -> FlowyResult<Vec<ViewLinkPB>> {
+    let uid = self.user.user_id()?;
+    let workspace_id = self.user.workspace_id()?;
+    let rows = select_backlinks(self.user.sqlite_connection(uid)?, &workspace_id, view_id)?;
+    self
+      .view_links_to_pb(rows, |row| row.source_view_id.clone())
+      .await
+  }
+
+  /// Returns the links that the given view points to.
+  #[tracing::instrument(level = "debug", skip(self), err)]
+  pub(crate) async fn get_view_outgoing_links(
+    &self,
+    view_id: &str,
+  ) -> FlowyResult<Vec<ViewLinkPB>> {
+    let uid = self.user.user_id()?;
+    let workspace_id = self.user.workspace_id()?;
+    let rows = select_outgoing_links(self.user.sqlite_connection(uid)?, &workspace_id, view_id)?;
+    self
+      .view_links_to_pb(rows, |row| row.target_view_id.clone())
+      .await
+  }
+
+  async fn view_links_to_pb(
+    &self,
+    rows: Vec<ViewLinkTable>,
+    linked_view_id: impl Fn(&ViewLinkTable) -> String,
+  ) -> FlowyResult<Vec<ViewLinkPB>> {
+    let view_ids = rows.iter().map(&linked_view_id).collect::<Vec<_>>();
+    let views = self
+      .get_view_pbs_without_children(view_ids)
+      .await?
+      .into_iter()
+      .map(|view| (view.id.clone(), view))
+      .collect::<HashMap<_, _>>();
+
+    let links = rows
+      .into_iter()
+      .filter_map(|row| {
+        let view = views.get(&linked_view_id(&row))?.clone();
+        Some(ViewLinkPB {
+          link_type: row.link_type().into(),
+          source_view_id: row.source_view_id,
+          target_view_id: row.target_view_id,
+          block_id: row.block_id,
+          view,
+        })
+      })
+      .collect();
+    Ok(links)
+  }
+
+  /// Restore the structure of the workspace to the state of the given folder snapshot. The views
+  /// get back their parent, position and name, and the views that were moved to the trash after
+  /// the snapshot was taken are restored from the trash. The content of the views is left
+  /// untouched, and the views that were deleted permanently can't be restored.
+  ///
+  /// Returns the views whose state was restored.
+  #[tracing::instrument(level = "debug", skip(self), err)]
+  pub(crate) async fn restore_folder_snapshot(
+    &self,
+    snapshot_id: &str,
+  ) -> Result<Vec<ViewPB>, FlowyError> {
+    let uid = self.user.user_id()?;
+    let workspace_id = self.user.workspace_id()?;
+    let snapshot = self
+      .cloud_service
+      .get_folder_snapshot(&workspace_id, snapshot_id)
+      .await?
+      .ok_or_else(|| {
+        FlowyError::record_not_found().with_context(format!("Snapshot {} not found", snapshot_id))
+      })?;
+    let snapshot_folder = Folder::from_collab_doc_state(
+      uid,
+      CollabOrigin::Empty,
+      DataSource::DocStateV1(snapshot.data),
+      &workspace_id,
+      vec![],
+    )?;
+    let snapshot_trash_ids = snapshot_folder
+      .get_all_trash_sections()
+      .into_iter()
+      .map(|trash| trash.id)
+      .collect::<HashSet<_>>();
+
+    let lock = self
+      .mutex_folder
+      .load_full()
+      .ok_or_else(folder_not_init_error)?;
+    let mut folder = lock.write().await;
+    let mut restored_view_ids = vec![];
+    let mut changed_parent_ids = HashSet::new();
+    let mut untrashed_view_ids = vec![];
+    let mut parent_ids = vec![workspace_id.clone()];
+    while let Some(parent_id) = parent_ids.pop() {
+      let mut prev_view_id: Option<String> = None;
+      for view in snapshot_folder.get_views_belong_to(&parent_id) {
+        parent_ids.push(view.id.clone());
+        let current_view = match folder.get_view(&view.id) {
+          None => {
+            info!(
+              "[Snapshot]: view {} was deleted, skip restoring it",
+              view.id
+            );
+            continue;
+          },
+          Some(current_view) => current_view,
+        };
+
+        let current_siblings = folder
+          .get_views_belong_to(&parent_id)
+          .into_iter()
+          .map(|view| view.id.clone())
+          .collect::<Vec<_>>();
+        let current_prev_view_id = current_siblings
+          .iter()
+          .position(|id| *id == view.id)
+          .and_then(|index| index.checked_sub(1))
+          .map(|index| current_siblings[index].clone());
+        let mut is_restored = false;
+        if current_view.parent_view_id != parent_id || current_prev_view_id != prev_view_id {
+          changed_parent_ids.insert(current_view.parent_view_id.clone());
+          changed_parent_ids.insert(parent_id.clone());
+          folder.move_nested_view(&view.id, &parent_id, prev_view_id.clone());
+          is_restored = true;
+        }
+        if current_view.name != view.name {
+          folder.update_view(&view.id, |update| {
+            update.set_name_if_not_none(Some(view.name.clone())).done()
+          });
+          changed_parent_ids.insert(parent_id.clone());
+          is_restored = true;
+        }
+        if folder.is_view_in_section(Section::Trash, &view.id)
+          && !snapshot_trash_ids.contains(&view.id)
+        {
+          untrashed_view_ids.push(view.id.clone());
+          is_restored = true;
+        }
+        if is_restored {
+          restored_view_ids.push(view.id.clone());
+        }
+        prev_view_id = Some(view.id.clone());
+      }
+    }
+
+    if !untrashed_view_ids.is_empty() {
+      folder.delete_trash_view_ids(untrashed_view_ids);
+      folder_notification_builder("trash", FolderNotification::DidUpdateTrash)
+        .payload(RepeatedTrashPB {
+          items: folder
+            .get_my_trash_info()
+            .into_iter()
+            .map(|trash| trash.into())
+            .collect(),
+        })
+        .send();
+    }
+    notify_parent_view_did_change(
+      &workspace_id,
+      &folder,
+      changed_parent_ids.into_iter().collect(),
+    );
+    info!(
+      "[Snapshot]: restored {} views from folder snapshot {}",
+      restored_view_ids.len(),
+      snapshot_id
+    );
+
+    let restored_views = restored_view_ids
+      .iter()
+      .filter_map(|view_id| folder.get_view(view_id))
+      .map(view_pb_without_child_views_from_arc)
+      .collect();
+    Ok(restored_views)
+  }
+
   #[tracing::instrument(level = "trace", skip(self), err)]
   pub(crate) async fn set_current_view(&self, view_id: String) -> Result<(), FlowyError> {
     if let Some(lock) = self.mutex_folder.load_full() {
@@ -1214,6 +1510,17 @@ impl FolderManager {
           error!("Open view error: {:?}", err);
         }
       }
+      // The links are indexed in the background, opening the view shouldn't wait for its
+      // content to be parsed.
+      if let Ok(handler) = self.get_handler(&view_layout) {
+        let user = self.user.clone();
+        let view_id = view_id.clone();
+        tokio::spawn(async move {
+          if let Err(err) = index_view_links_with_handler(&user, handler, &view_id).await {
+            error!("Index view links error: {:?}", err);
+          }
+        });
+      }
     }
 
     let workspace_id = self.user.workspace_id()?;
@@ -1624,7 +1931,7 @@ impl FolderManager {
   }
 
   #[tracing::instrument(level = "debug", skip(self))]
-  pub(crate) async fn get_my_recent_sections(&self) -> Vec<SectionItem> {
+  pub async fn get_my_recent_sections(&self) -> Vec<SectionItem> {
     self.get_sections(Section::Recent).await
   }
 
@@ -1692,6 +1999,16 @@ impl FolderManager {
           handler.delete_view(view_id).await?;
         }
       }
+
+      let uid = self.user.user_id()?;
+      let workspace_id = self.user.workspace_id()?;
+      if let Err(err) = delete_view_links(
+        self.user.sqlite_connection(uid)?,
+        &workspace_id,
+        &[view_id.to_string()],
+      ) {
+        error!("Delete view links error: {:?}", err);
+      }
     }
     Ok(())
   }
@@ -1797,7 +2114,7 @@ impl FolderManager {
   }
 
   /// Update the view with the provided view_id using the specified function.
-  async fn update_view<F>(&self, view_id: &str, f: F) -> FlowyResult<()>
+  pub(crate) async fn update_view<F>(&self, view_id: &str, f: F) -> FlowyResult<()>
   where
     F: FnOnce(ViewUpdate) -> Option<View>,
   {
@@ -1834,7 +2151,10 @@ impl FolderManager {
   }
 
   /// Returns a handler that implements the [FolderOperationHandler] trait
-  fn get_handler(&self, view_layout: &ViewLayout) -> FlowyResult<Arc<dyn FolderOperationHandler>> {
+  pub(crate) fn get_handler(
+    &self,
+    view_layout: &ViewLayout,
+  ) -> FlowyResult<Arc<dyn FolderOperationHandler>> {
     match self.operation_handlers.get(view_layout) {
       None => Err(FlowyError::internal().with_context(format!(
         "Get data processor failed. Unknown layout type: {:?}",
@@ -1844,7 +2164,7 @@ impl FolderManager {
     }
   }
 
-  fn get_folder_collab_params(
+  pub(crate) fn get_folder_collab_params(
     &self,
     object_id: String,
     collab_type: CollabType,
@@ -1965,7 +2285,7 @@ impl FolderManager {
   }
 
   /// Filter the views that are in the trash and belong to the other private sections.
-  fn get_view_ids_should_be_filtered(folder: &Folder) -> Vec<String> {
+  pub(crate) fn get_view_ids_should_be_filtered(folder: &Folder) -> Vec<String> {
     let trash_ids = Self::get_all_trash_ids(folder);
     let other_private_view_ids = Self::get_other_private_view_ids(folder);
     [trash_ids, other_private_view_ids].concat()
@@ -1997,6 +2317,41 @@ impl FolderManager {
 
     Ok(())
   }
+
+  /// Index all the views of the current workspace, e.g. after the search index was rebuilt.
+  /// Returns the number of the indexed views.
+  pub async fn index_all_views(&self) -> FlowyResult<usize> {
+    let lock = self
+      .mutex_folder
+      .load_full()
+      .ok_or_else(folder_not_init_error)?;
+    let (views, workspace_id) = {
+      let folder = lock.read().await;
+      let workspace_id = folder
+        .get_workspace_id()
+        .ok_or_else(folder_not_init_error)?;
+      (folder.get_all_views(), workspace_id)
+    };
+
+    let num_views = views.len();
+    let folder_indexer = self.folder_indexer.clone();
+    tokio::task::spawn_blocking(move || folder_indexer.index_all_views(views, workspace_id))
+      .await
+      .map_err(internal_error)?;
+    Ok(num_views)
+  }
+}
+
+async fn index_view_links_with_handler(
+  user: &Arc<dyn FolderUser>,
+  handler: Arc<dyn FolderOperationHandler>,
+  view_id: &str,
+) -> FlowyResult<()> {
+  let links = handler.get_view_links(view_id).await?;
+  let uid = user.user_id()?;
+  let workspace_id = user.workspace_id()?;
+  let conn = user.sqlite_connection(uid)?;
+  replace_view_links(conn, &workspace_id, view_id, links)
 }
 
 /// Return the views that belong to the workspace. The views are filtered by the trash and all the private views.
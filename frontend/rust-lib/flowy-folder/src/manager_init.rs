@@ -139,6 +139,7 @@ impl FolderManager {
       view_rx,
       weak_mutex_folder.clone(),
       Arc::downgrade(&self.user),
+      Arc::downgrade(&self.folder_indexer),
     );
 
     Ok(())
@@ -177,7 +178,9 @@ impl FolderManager {
       .store_preferences
       .get_object::<EncodedCollab>(&workspace_id);
 
-    if let Some(encoded_collab) = encoded_collab {
+    // Only the changes are indexed if the index is not empty. The index is empty after it was
+    // removed by a schema migration.
+    if let Some(encoded_collab) = encoded_collab.filter(|_| self.folder_indexer.is_indexed()) {
       if let Ok(changes) = folder.calculate_view_changes(encoded_collab) {
         let folder_indexer = self.folder_indexer.clone();
 
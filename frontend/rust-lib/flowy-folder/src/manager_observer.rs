@@ -10,10 +10,11 @@ use collab_folder::{
   Folder, SectionChange, SectionChangeReceiver, TrashSectionChange, View, ViewChange,
   ViewChangeReceiver,
 };
+use flowy_search_pub::entities::FolderIndexManager;
 use lib_infra::sync_trace;
 
 use std::collections::HashSet;
-use std::sync::Weak;
+use std::sync::{Arc, Weak};
 use tokio_stream::wrappers::WatchStream;
 use tokio_stream::StreamExt;
 use tracing::{event, trace, Level};
@@ -24,6 +25,7 @@ pub(crate) fn subscribe_folder_view_changed(
   mut rx: ViewChangeReceiver,
   weak_mutex_folder: Weak<RwLock<Folder>>,
   user: Weak<dyn FolderUser>,
+  folder_indexer: Weak<dyn FolderIndexManager>,
 ) {
   tokio::spawn(async move {
     while let Ok(value) = rx.recv().await {
@@ -49,6 +51,7 @@ pub(crate) fn subscribe_folder_view_changed(
             let parent_view_id = view.parent_view_id.clone();
             notify_parent_view_did_change(&workspace_id, &folder, vec![parent_view_id]);
             sync_trace!("[Folder] create view: {:?}", view);
+            index_view_metadata(&folder_indexer, &folder, Arc::new(view), &workspace_id);
           },
           ViewChange::DidDeleteView { views } => {
             for view in views {
@@ -69,7 +72,9 @@ pub(crate) fn subscribe_folder_view_changed(
               ChildViewChangeReason::Update,
             );
             let folder = lock.read().await;
-            notify_parent_view_did_change(&workspace_id, &folder, vec![view.parent_view_id]);
+            let parent_view_id = view.parent_view_id.clone();
+            index_view_metadata(&folder_indexer, &folder, Arc::new(view), &workspace_id);
+            notify_parent_view_did_change(&workspace_id, &folder, vec![parent_view_id]);
           },
         };
       }
@@ -77,6 +82,73 @@ pub(crate) fn subscribe_folder_view_changed(
   });
 }
 
+/// The index content of the folder collab doesn't contain the metadata and the ancestors of the
+/// view, which are used to filter the search results, so the view is indexed explicitly. Only the
+/// changed view is indexed, unless it was moved, in which case its descendants are indexed with
+/// their new ancestors too.
+fn index_view_metadata(
+  folder_indexer: &Weak<dyn FolderIndexManager>,
+  folder: &Folder,
+  view: Arc<View>,
+  workspace_id: &str,
+) {
+  // The folder might belong to another workspace when the workspace was switched
+  if folder.get_workspace_id().as_deref() != Some(workspace_id) {
+    return;
+  }
+  let Some(folder_indexer) = folder_indexer.upgrade() else {
+    return;
+  };
+
+  let ancestor_ids = folder_ancestor_ids(folder, &view);
+  let moved = folder_indexer
+    .get_indexed_ancestor_ids(&view.id)
+    .is_some_and(|indexed_ancestor_ids| indexed_ancestor_ids != ancestor_ids);
+  let mut views = vec![];
+  if moved {
+    let mut parents = vec![(view.id.clone(), ancestor_ids.clone())];
+    let mut visited = HashSet::from([view.id.clone()]);
+    while let Some((parent_id, parent_ancestor_ids)) = parents.pop() {
+      for child in folder.get_views_belong_to(&parent_id) {
+        if !visited.insert(child.id.clone()) {
+          continue;
+        }
+        let mut child_ancestor_ids = vec![parent_id.clone()];
+        child_ancestor_ids.extend(parent_ancestor_ids.iter().cloned());
+        parents.push((child.id.clone(), child_ancestor_ids.clone()));
+        views.push((child, child_ancestor_ids));
+      }
+    }
+  }
+  views.insert(0, (view, ancestor_ids));
+
+  let workspace_id = workspace_id.to_string();
+  tokio::task::spawn_blocking(move || {
+    folder_indexer.index_views(views, workspace_id);
+  });
+}
+
+/// Returns the ids of the ancestors of the view, from the parent to the root.
+fn folder_ancestor_ids(folder: &Folder, view: &View) -> Vec<String> {
+  let mut ancestor_ids: Vec<String> = vec![];
+  let mut parent_view_id = view.parent_view_id.clone();
+  while !parent_view_id.is_empty() {
+    // The parents might form a cycle in a corrupted folder
+    if parent_view_id == view.id || ancestor_ids.contains(&parent_view_id) {
+      break;
+    }
+    let next_parent_view_id = folder
+      .get_view(&parent_view_id)
+      .map(|parent| parent.parent_view_id.clone());
+    ancestor_ids.push(parent_view_id);
+    match next_parent_view_id {
+      Some(id) => parent_view_id = id,
+      None => break,
+    }
+  }
+  ancestor_ids
+}
+
 pub(crate) fn subscribe_folder_sync_state_changed(
   workspace_id: String,
   mut folder_sync_state_rx: WatchStream<SyncState>,
@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use collab_entity::CollabType;
+use collab_folder::{ViewIcon, ViewLayout};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::info;
+
+use flowy_error::{ErrorCode, FlowyError, FlowyResult};
+use flowy_folder_pub::cloud::gen_view_id;
+use lib_infra::util::timestamp;
+
+use crate::entities::{
+  CreateViewFromTemplateParams, CreateViewParams, SaveViewAsTemplateParams, ViewPB, ViewSectionPB,
+};
+use crate::manager::FolderManager;
+use crate::manager_observer::notify_parent_view_did_change;
+use crate::util::folder_not_init_error;
+use crate::view_operation::TemplateIdMap;
+
+const TEMPLATE_VERSION: u32 = 1;
+
+/// A view subtree that is saved to a file. The template doesn't depend on the workspace it was
+/// created in, so it can be instantiated into any workspace.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ViewTemplate {
+  pub version: u32,
+  pub name: String,
+  pub created_at: i64,
+  /// The views of the subtree. A parent view always comes before its children and the first
+  /// view is the root of the subtree.
+  pub views: Vec<ViewTemplateItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ViewTemplateItem {
+  pub view_id: String,
+  pub parent_view_id: String,
+  pub name: String,
+  pub layout: ViewLayout,
+  pub icon: Option<ViewIcon>,
+  pub extra: Option<String>,
+  /// The content of the view. The format is defined by the [FolderOperationHandler] of the
+  /// layout.
+  pub data: Value,
+}
+
+impl FolderManager {
+  /// Save the view and all its children as a template file. The views in the trash and the
+  /// chats are skipped.
+  #[tracing::instrument(level = "debug", skip_all, err)]
+  pub(crate) async fn save_view_as_template(
+    &self,
+    params: SaveViewAsTemplateParams,
+  ) -> FlowyResult<()> {
+    let views = self.export_view_subtree(&params.view_id).await?;
+    let template = ViewTemplate {
+      version: TEMPLATE_VERSION,
+      name: params.name,
+      created_at: timestamp(),
+      views,
+    };
+    let json = serde_json::to_vec_pretty(&template)?;
+    std::fs::write(&params.file_path, json).map_err(|err| {
+      FlowyError::internal().with_context(format!(
+        "Failed to write the template to {}: {}",
+        params.file_path, err
+      ))
+    })?;
+    Ok(())
+  }
+
+  /// Export the view and all its children. The views in the trash and the chats are skipped.
+  /// A parent view always comes before its children and the first view is the given view.
+  pub(crate) async fn export_view_subtree(
+    &self,
+    view_id: &str,
+  ) -> FlowyResult<Vec<ViewTemplateItem>> {
+    let lock = self
+      .mutex_folder
+      .load_full()
+      .ok_or_else(folder_not_init_error)?;
+    let filtered_view_ids = {
+      let folder = lock.read().await;
+      Self::get_view_ids_should_be_filtered(&folder)
+    };
+
+    let mut views = vec![];
+    let mut stack = vec![view_id.to_string()];
+    while let Some(view_id) = stack.pop() {
+      let view = self.get_view(&view_id).await?;
+      let handler = self.get_handler(&view.layout)?;
+      info!(
+        "{} export view {}, name:{}, layout:{:?}",
+        handler.name(),
+        view.id,
+        view.name,
+        view.layout
+      );
+      let data = handler.export_template_data(&view.id).await?;
+      views.push(ViewTemplateItem {
+        view_id: view.id.clone(),
+        parent_view_id: view.parent_view_id.clone(),
+        name: view.name.clone(),
+        layout: view.layout.clone(),
+        icon: view.icon.clone(),
+        extra: view.extra.clone(),
+        data,
+      });
+
+      let child_views = self.get_views_belong_to(&view_id).await?;
+      // reverse the child views to keep the order
+      for child_view in child_views.iter().rev() {
+        if !filtered_view_ids.contains(&child_view.id) && child_view.layout != ViewLayout::Chat {
+          stack.push(child_view.id.clone());
+        }
+      }
+    }
+    Ok(views)
+  }
+
+  /// Create the views of the template under the given parent view of the current workspace. Each
+  /// view gets a new id and the references between the views of the template are updated to
+  /// point to the new views. Returns the root view of the created views.
+  #[tracing::instrument(level = "debug", skip_all, err)]
+  pub(crate) async fn create_view_from_template(
+    &self,
+    params: CreateViewFromTemplateParams,
+  ) -> FlowyResult<ViewPB> {
+    let template = read_view_template(&params.file_path)?;
+    if template.version > TEMPLATE_VERSION {
+      return Err(FlowyError::new(
+        ErrorCode::InvalidParams,
+        format!("Unsupported template version: {}", template.version),
+      ));
+    }
+    let root_view = template
+      .views
+      .first()
+      .ok_or_else(|| FlowyError::invalid_data().with_context("The template is empty"))?;
+
+    let workspace_id = self.user.workspace_id()?;
+    let parent_view_id = params.parent_view_id.unwrap_or(workspace_id.clone());
+
+    // The root view is attached to the given parent view. The other views keep their parent
+    // in the template.
+    let mut view_ids = HashMap::new();
+    view_ids.insert(root_view.parent_view_id.clone(), parent_view_id.clone());
+    for view in &template.views {
+      view_ids.insert(view.view_id.clone(), gen_view_id().to_string());
+    }
+    let root_view_id = view_ids[&root_view.view_id].clone();
+    self
+      .create_views_from_template_items(template.views, &mut TemplateIdMap::new(view_ids))
+      .await?;
+    self.get_view_pb(&root_view_id).await
+  }
+
+  /// Create the given views in the current workspace. The [ViewTemplateItem::view_id] and the
+  /// [ViewTemplateItem::parent_view_id] of each view are replaced with the ids in the given map.
+  /// The objects that are shared by several views, like a database, are copied once.
+  pub(crate) async fn create_views_from_template_items(
+    &self,
+    views: Vec<ViewTemplateItem>,
+    ids: &mut TemplateIdMap,
+  ) -> FlowyResult<()> {
+    let Some(root_view) = views.first() else {
+      return Ok(());
+    };
+    let workspace_id = self.user.workspace_id()?;
+    let root_parent_view_id = ids
+      .view_ids
+      .get(&root_view.parent_view_id)
+      .cloned()
+      .unwrap_or_else(|| workspace_id.clone());
+
+    for view in &views {
+      self
+        .get_handler(&view.layout)?
+        .collect_template_ids(&view.data, ids)?;
+    }
+
+    let mut objects = vec![];
+    for view in views {
+      let parent_view_id = ids
+        .view_ids
+        .get(&view.parent_view_id)
+        .cloned()
+        .ok_or_else(|| {
+          FlowyError::invalid_data().with_context(format!(
+            "The parent of the view {} is not in the template",
+            view.view_id
+          ))
+        })?;
+      let view_id = ids.view_ids.get(&view.view_id).cloned().ok_or_else(|| {
+        FlowyError::invalid_data().with_context(format!("The view {} has no new id", view.view_id))
+      })?;
+      let handler = self.get_handler(&view.layout)?;
+      let view_data = handler.view_data_from_template(view.data, ids).await?;
+      let create_params = CreateViewParams {
+        parent_view_id,
+        name: view.name,
+        layout: view.layout.clone().into(),
+        initial_data: view_data.initial_data,
+        view_id,
+        meta: view_data.meta,
+        set_as_current: false,
+        index: None,
+        section: Some(ViewSectionPB::Public),
+        extra: view.extra,
+        icon: view.icon,
+      };
+      self
+        .create_view_and_collect_collab(create_params, true, &mut objects)
+        .await?;
+    }
+
+    self
+      .cloud_service
+      .batch_create_folder_collab_objects(&workspace_id, objects)
+      .await?;
+
+    if let Some(lock) = self.mutex_folder.load_full() {
+      let folder = lock.read().await;
+      notify_parent_view_did_change(&workspace_id, &folder, vec![root_parent_view_id]);
+    }
+    Ok(())
+  }
+}
+
+pub(crate) fn collab_type_of_layout(layout: &ViewLayout) -> CollabType {
+  match layout {
+    ViewLayout::Document => CollabType::Document,
+    ViewLayout::Board | ViewLayout::Grid | ViewLayout::Calendar => CollabType::Database,
+    ViewLayout::Chat => CollabType::Unknown,
+  }
+}
+
+fn read_view_template(file_path: &str) -> FlowyResult<ViewTemplate> {
+  if !Path::new(file_path).exists() {
+    return Err(
+      FlowyError::record_not_found()
+        .with_context(format!("The template file {} does not exist", file_path)),
+    );
+  }
+  let bytes = std::fs::read(file_path).map_err(|err| {
+    FlowyError::internal().with_context(format!(
+      "Failed to read the template {}: {}",
+      file_path, err
+    ))
+  })?;
+  serde_json::from_slice(&bytes)
+    .map_err(|err| FlowyError::invalid_data().with_context(format!("Invalid template: {}", err)))
+}
@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+
+use collab::core::collab::DataSource;
+use collab::core::origin::CollabOrigin;
+use collab::preclude::Collab;
+use collab_entity::CollabType;
+use collab_folder::{Folder, ViewLayout};
+use collab_integrate::collab_builder::CollabPersistenceImpl;
+use collab_integrate::CollabKVAction;
+use collab_plugins::local_storage::kv::KVTransactionDB;
+use tracing::info;
+
+use flowy_error::{internal_error, ErrorCode, FlowyError, FlowyResult};
+use flowy_folder_pub::cloud::gen_view_id;
+
+use crate::entities::{CreateViewParams, MoveViewToWorkspaceParams, ViewSectionPB};
+use crate::manager::FolderManager;
+use crate::template::{collab_type_of_layout, ViewTemplateItem};
+use crate::view_operation::{create_view, ImportedData, TemplateIdMap, ViewData};
+
+impl FolderManager {
+  /// Move the view and its children to another workspace of the user. The collabs of the copies
+  /// are written to the target workspace and uploaded, and the copies are added to the folder of
+  /// the target workspace, which is synced the next time the workspace is opened. The original
+  /// views are moved to the trash only after the copy is saved, unless they are kept.
+  #[tracing::instrument(level = "debug", skip_all, err)]
+  pub(crate) async fn move_view_to_workspace(
+    &self,
+    params: MoveViewToWorkspaceParams,
+  ) -> FlowyResult<()> {
+    let uid = self.user.user_id()?;
+    let workspace_id = self.user.workspace_id()?;
+    if params.workspace_id == workspace_id {
+      return Err(FlowyError::new(
+        ErrorCode::InvalidParams,
+        "The view is already in the workspace",
+      ));
+    }
+    if !self.user.is_user_workspace(uid, &params.workspace_id)? {
+      return Err(FlowyError::record_not_found().with_context(format!(
+        "The workspace {} is not found",
+        params.workspace_id
+      )));
+    }
+
+    let mut views = self.export_view_subtree(&params.view_id).await?;
+    let root_parent_view_id = views
+      .first()
+      .map(|view| view.parent_view_id.clone())
+      .unwrap_or_default();
+    let mut view_ids = HashMap::new();
+    view_ids.insert(
+      root_parent_view_id,
+      params
+        .parent_view_id
+        .unwrap_or_else(|| params.workspace_id.clone()),
+    );
+    for view in &views {
+      view_ids.insert(view.view_id.clone(), gen_view_id().to_string());
+    }
+    let mut ids = TemplateIdMap::new(view_ids);
+    for view in &views {
+      self
+        .get_handler(&view.layout)?
+        .collect_template_ids(&view.data, &mut ids)?;
+    }
+
+    for view in views.iter_mut() {
+      let handler = self.get_handler(&view.layout)?;
+      let data = std::mem::take(&mut view.data);
+      view.data = handler
+        .prepare_transfer_data(data, &params.workspace_id, &ids.view_ids[&view.view_id])
+        .await?;
+    }
+
+    let collabs = self
+      .create_transfer_collabs(&params.workspace_id, &mut views, &ids)
+      .await?;
+    self.write_transfer_collabs(uid, &params.workspace_id, &collabs)?;
+    let objects = collabs
+      .into_iter()
+      .map(|(object_id, collab_type, encoded_collab)| {
+        self.get_folder_collab_params(object_id, collab_type, encoded_collab)
+      })
+      .collect::<FlowyResult<Vec<_>>>()?;
+    self
+      .cloud_service
+      .batch_create_folder_collab_objects(&params.workspace_id, objects)
+      .await?;
+    self
+      .insert_transfer_views(uid, &params.workspace_id, views, &ids)
+      .await?;
+    info!(
+      "view {} is moved to workspace {}",
+      params.view_id, params.workspace_id
+    );
+
+    if !params.keep_original {
+      self.move_view_to_trash(&params.view_id).await?;
+    }
+    Ok(())
+  }
+
+  /// The views are passed to the handlers by the type of their collab, so the views that share
+  /// an object, like the views of a database, are handled together.
+  async fn create_transfer_collabs(
+    &self,
+    target_workspace_id: &str,
+    views: &mut [ViewTemplateItem],
+    ids: &TemplateIdMap,
+  ) -> FlowyResult<Vec<ImportedData>> {
+    let mut views_by_collab_type: Vec<(ViewLayout, Vec<(String, serde_json::Value)>)> = vec![];
+    for view in views.iter_mut() {
+      let collab_type = collab_type_of_layout(&view.layout);
+      let data = (
+        ids.view_ids[&view.view_id].clone(),
+        std::mem::take(&mut view.data),
+      );
+      match views_by_collab_type
+        .iter_mut()
+        .find(|(layout, _)| collab_type_of_layout(layout) == collab_type)
+      {
+        Some((_, views)) => views.push(data),
+        None => views_by_collab_type.push((view.layout.clone(), vec![data])),
+      }
+    }
+
+    let mut collabs = vec![];
+    for (layout, views) in views_by_collab_type {
+      collabs.extend(
+        self
+          .get_handler(&layout)?
+          .create_transfer_collabs(target_workspace_id, views, ids)
+          .await?,
+      );
+    }
+    Ok(collabs)
+  }
+
+  fn write_transfer_collabs(
+    &self,
+    uid: i64,
+    target_workspace_id: &str,
+    collabs: &[ImportedData],
+  ) -> FlowyResult<()> {
+    let collab_db = self
+      .user
+      .collab_db(uid)?
+      .upgrade()
+      .ok_or_else(|| FlowyError::internal().with_context("The collab db is already dropped"))?;
+    let write_txn = collab_db.write_txn();
+    for (object_id, _, encoded_collab) in collabs {
+      write_txn
+        .flush_doc(
+          uid,
+          target_workspace_id,
+          object_id,
+          encoded_collab.state_vector.to_vec(),
+          encoded_collab.doc_state.to_vec(),
+        )
+        .map_err(internal_error)?;
+    }
+    write_txn.commit_transaction().map_err(internal_error)?;
+    Ok(())
+  }
+
+  /// Add the views to the folder of the target workspace. The folder is loaded from the disk, or
+  /// from the cloud if the workspace was never opened on this device, and written back to the
+  /// disk.
+  async fn insert_transfer_views(
+    &self,
+    uid: i64,
+    target_workspace_id: &str,
+    views: Vec<ViewTemplateItem>,
+    ids: &TemplateIdMap,
+  ) -> FlowyResult<()> {
+    let collab_db = self.user.collab_db(uid)?;
+    let is_exist = collab_db
+      .upgrade()
+      .map(|db| {
+        db.read_txn()
+          .is_exist(uid, target_workspace_id, target_workspace_id)
+      })
+      .unwrap_or(false);
+    let data_source = if is_exist {
+      CollabPersistenceImpl::new(collab_db.clone(), uid, target_workspace_id.to_string())
+        .into_data_source()
+    } else {
+      let doc_state = self
+        .cloud_service
+        .get_folder_doc_state(
+          target_workspace_id,
+          uid,
+          CollabType::Folder,
+          target_workspace_id,
+        )
+        .await?;
+      DataSource::DocStateV1(doc_state)
+    };
+    let collab = Collab::new_with_source(
+      CollabOrigin::Empty,
+      target_workspace_id,
+      data_source,
+      vec![],
+      false,
+    )
+    .map_err(internal_error)?;
+    let mut folder = Folder::open(uid, collab, None).map_err(internal_error)?;
+
+    for view in views {
+      let mut parent_view_id = ids.view_ids[&view.parent_view_id].clone();
+      // The parent of the root view might be deleted from the target workspace.
+      if parent_view_id != target_workspace_id && folder.get_view(&parent_view_id).is_none() {
+        parent_view_id = target_workspace_id.to_string();
+      }
+      let params = CreateViewParams {
+        parent_view_id,
+        name: view.name,
+        layout: view.layout.clone().into(),
+        initial_data: ViewData::Empty,
+        view_id: ids.view_ids[&view.view_id].clone(),
+        meta: Default::default(),
+        set_as_current: false,
+        index: None,
+        section: Some(ViewSectionPB::Public),
+        extra: view.extra,
+        icon: view.icon,
+      };
+      folder.insert_view(create_view(uid, params, view.layout), None);
+    }
+
+    let encoded_collab = folder.encode_collab().map_err(internal_error)?;
+    self.write_transfer_collabs(
+      uid,
+      target_workspace_id,
+      &[(
+        target_workspace_id.to_string(),
+        CollabType::Folder,
+        encoded_collab,
+      )],
+    )
+  }
+}
@@ -0,0 +1,120 @@
+use flowy_error::{FlowyError, FlowyResult};
+use flowy_sqlite::schema::{view_link_table, view_link_table::dsl};
+use flowy_sqlite::{
+  diesel, insert_into, query_dsl::*, BoolExpressionMethods, DBConnection, ExpressionMethods,
+  Insertable, Queryable,
+};
+
+use crate::view_operation::{ViewLink, ViewLinkType};
+
+#[derive(Queryable, Insertable, Debug, Clone)]
+#[diesel(table_name = view_link_table)]
+pub struct ViewLinkTable {
+  pub workspace_id: String,
+  pub source_view_id: String,
+  pub target_view_id: String,
+  pub block_id: String,
+  pub link_type: i32,
+}
+
+impl ViewLinkTable {
+  pub fn new(workspace_id: &str, source_view_id: &str, link: ViewLink) -> Self {
+    Self {
+      workspace_id: workspace_id.to_string(),
+      source_view_id: source_view_id.to_string(),
+      target_view_id: link.target_view_id,
+      block_id: link.block_id,
+      link_type: link.link_type as i32,
+    }
+  }
+
+  pub fn link_type(&self) -> ViewLinkType {
+    ViewLinkType::from(self.link_type)
+  }
+}
+
+/// Replace all the outgoing links of the source view with the given links.
+pub fn replace_view_links(
+  mut conn: DBConnection,
+  workspace_id: &str,
+  source_view_id: &str,
+  links: Vec<ViewLink>,
+) -> FlowyResult<()> {
+  conn.immediate_transaction(|conn| {
+    diesel::delete(
+      dsl::view_link_table.filter(
+        view_link_table::workspace_id
+          .eq(workspace_id)
+          .and(view_link_table::source_view_id.eq(source_view_id)),
+      ),
+    )
+    .execute(conn)?;
+
+    for link in links {
+      // A view that mentions itself is not a backlink
+      if link.target_view_id == source_view_id {
+        continue;
+      }
+      let row = ViewLinkTable::new(workspace_id, source_view_id, link);
+      let _ = insert_into(view_link_table::table)
+        .values(&row)
+        .on_conflict_do_nothing()
+        .execute(conn)?;
+    }
+    Ok::<(), FlowyError>(())
+  })?;
+  Ok(())
+}
+
+/// Returns the links that point to the given view.
+pub fn select_backlinks(
+  mut conn: DBConnection,
+  workspace_id: &str,
+  target_view_id: &str,
+) -> FlowyResult<Vec<ViewLinkTable>> {
+  let rows = dsl::view_link_table
+    .filter(
+      view_link_table::workspace_id
+        .eq(workspace_id)
+        .and(view_link_table::target_view_id.eq(target_view_id)),
+    )
+    .order(view_link_table::source_view_id.asc())
+    .load::<ViewLinkTable>(&mut *conn)?;
+  Ok(rows)
+}
+
+/// Returns the links that the given view points to.
+pub fn select_outgoing_links(
+  mut conn: DBConnection,
+  workspace_id: &str,
+  source_view_id: &str,
+) -> FlowyResult<Vec<ViewLinkTable>> {
+  let rows = dsl::view_link_table
+    .filter(
+      view_link_table::workspace_id
+        .eq(workspace_id)
+        .and(view_link_table::source_view_id.eq(source_view_id)),
+    )
+    .order(view_link_table::target_view_id.asc())
+    .load::<ViewLinkTable>(&mut *conn)?;
+  Ok(rows)
+}
+
+/// Remove the links from or to the given views. Called when the views are deleted permanently.
+pub fn delete_view_links(
+  mut conn: DBConnection,
+  workspace_id: &str,
+  view_ids: &[String],
+) -> FlowyResult<()> {
+  diesel::delete(
+    dsl::view_link_table.filter(
+      view_link_table::workspace_id.eq(workspace_id).and(
+        view_link_table::source_view_id
+          .eq_any(view_ids)
+          .or(view_link_table::target_view_id.eq_any(view_ids)),
+      ),
+    ),
+  )
+  .execute(&mut *conn)?;
+  Ok(())
+}
@@ -6,7 +6,7 @@ use collab_folder::hierarchy_builder::NestedViewBuilder;
 pub use collab_folder::View;
 use collab_folder::ViewLayout;
 use dashmap::DashMap;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -40,6 +40,40 @@ pub struct DatabaseEncodedCollab {
 
 pub type ImportedData = (String, CollabType, EncodedCollab);
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(i32)]
+pub enum ViewLinkType {
+  /// The target view is mentioned in the content of the source view.
+  Mention = 0,
+  /// The target view is a sub page of the source view.
+  SubPage = 1,
+  /// The target view is a database view that is embedded in the source view.
+  DatabaseReference = 2,
+  /// The source view has a relation field that points to the database of the target view.
+  DatabaseRelation = 3,
+}
+
+impl From<i32> for ViewLinkType {
+  fn from(value: i32) -> Self {
+    match value {
+      1 => ViewLinkType::SubPage,
+      2 => ViewLinkType::DatabaseReference,
+      3 => ViewLinkType::DatabaseRelation,
+      _ => ViewLinkType::Mention,
+    }
+  }
+}
+
+/// A reference from the content of a view to another view.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ViewLink {
+  pub target_view_id: String,
+  /// The id of the block or the field that holds the reference. Empty if the reference is not
+  /// attached to a specific part of the view.
+  pub block_id: String,
+  pub link_type: ViewLinkType,
+}
+
 /// The handler will be used to handler the folder operation for a specific
 /// view layout. Each [ViewLayout] will have a handler. So when creating a new
 /// view, the [ViewLayout] will be used to get the handler.
@@ -68,6 +102,77 @@ pub trait FolderOperationHandler: Send + Sync {
   /// Returns the [ViewData] that can be used to create the same view.
   async fn duplicate_view(&self, view_id: &str) -> Result<Bytes, FlowyError>;
 
+  /// Returns the [ViewData] that can be used to create a view with the content of the given
+  /// snapshot of the view's collab.
+  async fn view_data_from_snapshot(
+    &self,
+    _view_id: &str,
+    _snapshot_id: &str,
+  ) -> Result<Bytes, FlowyError> {
+    Err(FlowyError::not_support())
+  }
+
+  /// Returns the views that are referenced by the content of the given view. The links are
+  /// used to build the backlinks of the views.
+  async fn get_view_links(&self, _view_id: &str) -> Result<Vec<ViewLink>, FlowyError> {
+    Ok(vec![])
+  }
+
+  /// Returns the content of the view in a form that doesn't depend on the current workspace.
+  /// It's used to save the view as a template.
+  async fn export_template_data(&self, _view_id: &str) -> Result<serde_json::Value, FlowyError> {
+    Err(FlowyError::not_support())
+  }
+
+  /// Called with the data of every view of a template before any of the views is created. The
+  /// handler assigns new ids to the objects that can be shared by several views of the template,
+  /// for example a database, so all the views refer to the same copy.
+  fn collect_template_ids(
+    &self,
+    _data: &serde_json::Value,
+    _ids: &mut TemplateIdMap,
+  ) -> Result<(), FlowyError> {
+    Ok(())
+  }
+
+  /// Returns the data that can be used to create a view with the data returned by
+  /// [FolderOperationHandler::export_template_data]. The references between the views of the
+  /// template are updated with the ids in the given map.
+  async fn view_data_from_template(
+    &self,
+    _data: serde_json::Value,
+    _ids: &mut TemplateIdMap,
+  ) -> Result<TemplateViewData, FlowyError> {
+    Err(FlowyError::not_support())
+  }
+
+  /// Called before the data returned by [FolderOperationHandler::export_template_data] is moved
+  /// to another workspace. The view will be created with the given id in the target workspace.
+  /// The handler can copy the resources that belong to the current workspace, such as the
+  /// uploaded files.
+  async fn prepare_transfer_data(
+    &self,
+    data: serde_json::Value,
+    _target_workspace_id: &str,
+    _view_id: &str,
+  ) -> Result<serde_json::Value, FlowyError> {
+    Ok(data)
+  }
+
+  /// Returns the collabs of the views that are moved to another workspace. Each view is given
+  /// with its id in the target workspace and the data returned by
+  /// [FolderOperationHandler::prepare_transfer_data]. The references between the views are
+  /// updated with the ids in the given map. The handler registers the objects that the target
+  /// workspace keeps track of, like the databases, and the caller writes the returned collabs.
+  async fn create_transfer_collabs(
+    &self,
+    _target_workspace_id: &str,
+    _views: Vec<(String, serde_json::Value)>,
+    _ids: &TemplateIdMap,
+  ) -> Result<Vec<ImportedData>, FlowyError> {
+    Err(FlowyError::not_support())
+  }
+
   /// get the encoded collab data from the disk.
   async fn get_encoded_collab_v1_from_disk(
     &self,
@@ -133,6 +238,11 @@ pub trait FolderOperationHandler: Send + Sync {
     path: String,
   ) -> Result<(), FlowyError>;
 
+  /// Called when the view or one of its ancestors is moved to the trash.
+  async fn did_move_view_to_trash(&self, _view_id: &str) -> Result<(), FlowyError> {
+    Ok(())
+  }
+
   /// Called when the view is updated. The handler is the `old` registered handler.
   async fn did_update_view(&self, _old: &View, _new: &View) -> Result<(), FlowyError> {
     Ok(())
@@ -172,6 +282,49 @@ pub(crate) fn create_view(uid: i64, params: CreateViewParams, layout: ViewLayout
   }
 }
 
+/// The ids of the objects of a template and the ids of their copies. The map is shared by all the
+/// views of the template, so the references between the views point to the copies.
+#[derive(Debug, Default, Clone)]
+pub struct TemplateIdMap {
+  /// The ids of the views of the template.
+  pub view_ids: HashMap<String, String>,
+  /// The ids of the other objects that are referenced across the views, for example the
+  /// databases and their rows.
+  pub object_ids: HashMap<String, String>,
+  /// The copies that were already created by a previous view of the template.
+  pub created_object_ids: HashSet<String>,
+}
+
+impl TemplateIdMap {
+  pub fn new(view_ids: HashMap<String, String>) -> Self {
+    Self {
+      view_ids,
+      ..Default::default()
+    }
+  }
+
+  /// Returns the new id of the view or the object, or None if it's not part of the template.
+  pub fn get(&self, id: &str) -> Option<&String> {
+    self.view_ids.get(id).or_else(|| self.object_ids.get(id))
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct TemplateViewData {
+  pub initial_data: ViewData,
+  /// Passed to [FolderOperationHandler::create_view_with_view_data] as the meta of the view.
+  pub meta: HashMap<String, String>,
+}
+
+impl From<Bytes> for TemplateViewData {
+  fn from(data: Bytes) -> Self {
+    Self {
+      initial_data: ViewData::Data(data),
+      meta: Default::default(),
+    }
+  }
+}
+
 #[derive(Debug, Clone)]
 pub enum ViewData {
   /// Indicate the data is duplicated from another view.
@@ -0,0 +1,124 @@
+use std::collections::BTreeMap;
+
+use tokio::task::spawn_blocking;
+
+use flowy_error::FlowyResult;
+use flowy_folder_pub::entities::{view_extra_with_tags, view_tags_from_extra};
+use flowy_search_pub::entities::IndexableData;
+
+use crate::entities::{
+  view_pb_without_child_views_from_arc, UpdateViewTagsParams, ViewPB, ViewTagPB,
+};
+use crate::manager::FolderManager;
+use crate::util::folder_not_init_error;
+
+impl FolderManager {
+  /// Add the tags to the view. The tags that the view already has are ignored, tags are compared
+  /// case-insensitively.
+  #[tracing::instrument(level = "debug", skip(self), err)]
+  pub(crate) async fn add_view_tags(&self, params: UpdateViewTagsParams) -> FlowyResult<()> {
+    let view = self.get_view(&params.view_id).await?;
+    let mut tags = view_tags_from_extra(view.extra.as_deref());
+    for tag in params.tags {
+      if !tags.iter().any(|existing| is_same_tag(existing, &tag)) {
+        tags.push(tag);
+      }
+    }
+    self
+      .set_view_tags(&params.view_id, view.extra.as_deref(), tags)
+      .await
+  }
+
+  /// Remove the tags from the view.
+  #[tracing::instrument(level = "debug", skip(self), err)]
+  pub(crate) async fn remove_view_tags(&self, params: UpdateViewTagsParams) -> FlowyResult<()> {
+    let view = self.get_view(&params.view_id).await?;
+    let mut tags = view_tags_from_extra(view.extra.as_deref());
+    tags.retain(|existing| !params.tags.iter().any(|tag| is_same_tag(existing, tag)));
+    self
+      .set_view_tags(&params.view_id, view.extra.as_deref(), tags)
+      .await
+  }
+
+  /// Returns all the tags of the views in the current workspace, sorted by name. The views in the
+  /// trash and the private views of other members are not counted.
+  pub(crate) async fn get_workspace_view_tags(&self) -> FlowyResult<Vec<ViewTagPB>> {
+    let lock = self
+      .mutex_folder
+      .load_full()
+      .ok_or_else(folder_not_init_error)?;
+    let folder = lock.read().await;
+    let filtered_view_ids = Self::get_view_ids_should_be_filtered(&folder);
+
+    // Keyed by the lowercase name. The name of the first view that has the tag is used.
+    let mut tags: BTreeMap<String, ViewTagPB> = BTreeMap::new();
+    for view in folder.get_all_views() {
+      if filtered_view_ids.contains(&view.id) {
+        continue;
+      }
+      for tag in view_tags_from_extra(view.extra.as_deref()) {
+        tags
+          .entry(tag.to_lowercase())
+          .or_insert_with(|| ViewTagPB {
+            name: tag,
+            view_count: 0,
+          })
+          .view_count += 1;
+      }
+    }
+    Ok(tags.into_values().collect())
+  }
+
+  /// Returns the views of the current workspace that have the given tag.
+  pub(crate) async fn get_views_by_tag(&self, tag: &str) -> FlowyResult<Vec<ViewPB>> {
+    let lock = self
+      .mutex_folder
+      .load_full()
+      .ok_or_else(folder_not_init_error)?;
+    let folder = lock.read().await;
+    let filtered_view_ids = Self::get_view_ids_should_be_filtered(&folder);
+
+    let views = folder
+      .get_all_views()
+      .into_iter()
+      .filter(|view| !filtered_view_ids.contains(&view.id))
+      .filter(|view| {
+        view_tags_from_extra(view.extra.as_deref())
+          .iter()
+          .any(|existing| is_same_tag(existing, tag))
+      })
+      .map(view_pb_without_child_views_from_arc)
+      .collect();
+    Ok(views)
+  }
+
+  async fn set_view_tags(
+    &self,
+    view_id: &str,
+    extra: Option<&str>,
+    tags: Vec<String>,
+  ) -> FlowyResult<()> {
+    let extra = view_extra_with_tags(extra, &tags);
+    self
+      .update_view(view_id, |update| {
+        update.set_extra_if_not_none(Some(extra)).done()
+      })
+      .await?;
+
+    // The tags are not part of the index content of the folder collab, so the index is updated
+    // explicitly.
+    let workspace_id = self.user.workspace_id()?;
+    let view = self.get_view(view_id).await?;
+    let folder_indexer = self.folder_indexer.clone();
+    spawn_blocking(move || {
+      if let Err(err) = folder_indexer.update_index(IndexableData::from_view(view, workspace_id)) {
+        tracing::error!("Failed to index the tags of the view: {}", err);
+      }
+    });
+    Ok(())
+  }
+}
+
+fn is_same_tag(a: &str, b: &str) -> bool {
+  a.to_lowercase() == b.to_lowercase()
+}
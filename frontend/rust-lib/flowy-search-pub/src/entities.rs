@@ -4,6 +4,7 @@ use std::sync::Arc;
 use collab::core::collab::IndexContentReceiver;
 use collab_folder::{folder_diff::FolderViewChange, View, ViewIcon, ViewLayout};
 use flowy_error::FlowyError;
+use flowy_folder_pub::entities::view_tags_from_extra;
 
 pub struct IndexableData {
   pub id: String,
@@ -11,6 +12,23 @@ pub struct IndexableData {
   pub icon: Option<ViewIcon>,
   pub layout: ViewLayout,
   pub workspace_id: String,
+  /// The tags of the view. None if the tags are unknown, in which case the indexed tags are kept
+  /// when the index is updated.
+  pub tags: Option<Vec<String>>,
+  /// The metadata of the view that is used to filter the search results. None if the metadata is
+  /// unknown, in which case the indexed metadata is kept when the index is updated.
+  pub meta: Option<IndexableViewMeta>,
+  /// The ids of the ancestors of the view, from the parent to the root. None if the ancestors are
+  /// unknown, in which case the indexed ancestors are kept when the index is updated.
+  pub ancestor_ids: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IndexableViewMeta {
+  pub created_at: i64,
+  pub last_edited_time: i64,
+  pub created_by: Option<i64>,
+  pub last_edited_by: Option<i64>,
 }
 
 impl IndexableData {
@@ -21,8 +39,21 @@ impl IndexableData {
       icon: view.icon.clone(),
       layout: view.layout.clone(),
       workspace_id: workspace_id.clone(),
+      tags: Some(view_tags_from_extra(view.extra.as_deref())),
+      meta: Some(IndexableViewMeta {
+        created_at: view.created_at,
+        last_edited_time: view.last_edited_time,
+        created_by: view.created_by,
+        last_edited_by: view.last_edited_by,
+      }),
+      ancestor_ids: None,
     }
   }
+
+  pub fn with_ancestor_ids(mut self, ancestor_ids: Vec<String>) -> Self {
+    self.ancestor_ids = Some(ancestor_ids);
+    self
+  }
 }
 
 pub trait IndexManager: Send + Sync {
@@ -44,4 +75,10 @@ pub trait FolderIndexManager: IndexManager {
     changes: Vec<FolderViewChange>,
     workspace_id: String,
   );
+  /// Returns the indexed ancestors of the view, from the parent to the root. None if the view is
+  /// not indexed.
+  fn get_indexed_ancestor_ids(&self, view_id: &str) -> Option<Vec<String>>;
+  /// Indexes the views with their metadata and ancestors, each view is given with the ids of its
+  /// ancestors. The views are replaced in one commit.
+  fn index_views(&self, views: Vec<(Arc<View>, Vec<String>)>, workspace_id: String);
 }
@@ -0,0 +1,33 @@
+use crate::services::snippet::ContentSnippet;
+
+/// A row that is added to the database index. The row is indexed again only when its version
+/// changes.
+#[derive(Debug, Clone)]
+pub struct DatabaseRowIndexData {
+  pub row_id: String,
+  pub version: String,
+  pub cells: Vec<DatabaseCellIndexData>,
+}
+
+/// The text of a cell that is added to the database index.
+#[derive(Debug, Clone)]
+pub struct DatabaseCellIndexData {
+  pub field_id: String,
+  pub field_name: String,
+  pub is_primary: bool,
+  pub content: String,
+}
+
+/// A cell of a database row that matches the search query.
+#[derive(Debug, Clone)]
+pub struct DatabaseRowIndexHit {
+  pub database_id: String,
+  pub view_id: String,
+  pub row_id: String,
+  pub row_title: String,
+  pub field_id: String,
+  pub field_name: String,
+  pub snippet: ContentSnippet,
+  pub workspace_id: String,
+  pub score: f32,
+}
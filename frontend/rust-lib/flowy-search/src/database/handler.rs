@@ -0,0 +1,122 @@
+use std::sync::Arc;
+use tracing::trace;
+
+use flowy_error::FlowyResult;
+use flowy_folder::manager::FolderManager;
+use lib_infra::async_trait::async_trait;
+use tokio::sync::broadcast;
+
+use super::indexer::DatabaseIndexManagerImpl;
+use crate::document::handler::view_result_icon;
+use crate::{
+  entities::{IndexTypePB, SearchFilterPB, SearchHighlightPB, SearchResultPB},
+  services::index_store::IndexStatus,
+  services::manager::{SearchHandler, SearchType, SearchViews},
+  services::ranking::normalize_index_score,
+};
+
+pub struct DatabaseSearchHandler {
+  pub folder_manager: Arc<FolderManager>,
+  pub index_manager: Arc<DatabaseIndexManagerImpl>,
+}
+
+impl DatabaseSearchHandler {
+  pub fn new(
+    folder_manager: Arc<FolderManager>,
+    index_manager: Arc<DatabaseIndexManagerImpl>,
+  ) -> Self {
+    Self {
+      folder_manager,
+      index_manager,
+    }
+  }
+}
+
+#[async_trait]
+impl SearchHandler for DatabaseSearchHandler {
+  fn search_type(&self) -> SearchType {
+    SearchType::Database
+  }
+
+  async fn perform_search(
+    &self,
+    query: String,
+    filter: Option<SearchFilterPB>,
+    limit: usize,
+  ) -> FlowyResult<Vec<SearchResultPB>> {
+    // Notice that `get_all_view_pb` returns Views that don't include trashed and private views
+    let views = self
+      .folder_manager
+      .get_all_views_pb()
+      .await?
+      .into_iter()
+      .map(|view| (view.id.clone(), view))
+      .collect::<SearchViews>();
+    self
+      .perform_search_in_views(query, filter, &views, limit)
+      .await
+  }
+
+  async fn perform_search_in_views(
+    &self,
+    query: String,
+    filter: Option<SearchFilterPB>,
+    views: &SearchViews,
+    limit: usize,
+  ) -> FlowyResult<Vec<SearchResultPB>> {
+    let filter = filter.unwrap_or_default();
+
+    // The index might contain databases that were deleted or moved to the trash
+    let view_ids = views
+      .values()
+      .filter(|view| filter.matches_view(view, views))
+      .map(|view| view.id.clone())
+      .collect::<Vec<_>>();
+    let hits =
+      self
+        .index_manager
+        .search(&query, filter.workspace_id.as_deref(), &view_ids, limit)?;
+    trace!("[Search] local database search results: {:?}", hits);
+
+    let search_results = hits
+      .into_iter()
+      .filter_map(|hit| {
+        let view = views.get(&hit.view_id)?;
+        let data = if hit.row_title.is_empty() {
+          view.name.clone()
+        } else {
+          hit.row_title
+        };
+        Some(SearchResultPB {
+          index_type: IndexTypePB::DatabaseRow,
+          view_id: hit.view_id,
+          id: hit.row_id,
+          data,
+          icon: Some(view_result_icon(view)),
+          score: normalize_index_score(hit.score as f64),
+          workspace_id: hit.workspace_id,
+          highlights: SearchHighlightPB::from_snippet(&hit.snippet),
+          preview: Some(hit.snippet.fragment),
+          field_id: Some(hit.field_id),
+        })
+      })
+      .collect();
+    Ok(search_results)
+  }
+
+  fn index_count(&self) -> u64 {
+    self.index_manager.num_docs()
+  }
+
+  fn index_status(&self) -> IndexStatus {
+    self.index_manager.status()
+  }
+
+  fn rebuild_index(&self) -> FlowyResult<()> {
+    self.index_manager.rebuild()
+  }
+
+  fn subscribe_index_changed(&self) -> broadcast::Receiver<()> {
+    self.index_manager.subscribe_index_changed()
+  }
+}
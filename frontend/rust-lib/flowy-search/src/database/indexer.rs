@@ -0,0 +1,353 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Weak};
+
+use flowy_error::FlowyResult;
+use flowy_user::services::authenticate_user::AuthenticateUser;
+use tantivy::collector::{DocSetCollector, TopDocs};
+use tantivy::query::{BooleanQuery, Occur, Query, QueryParser, TermQuery, TermSetQuery};
+use tantivy::schema::{Field, IndexRecordOption, Value};
+use tantivy::{doc, IndexWriter, SnippetGenerator, TantivyDocument, Term};
+use tokio::sync::broadcast;
+
+use super::entities::{DatabaseRowIndexData, DatabaseRowIndexHit};
+use super::schema::{
+  DatabaseSchema, DATABASE_CONTENT_FIELD_NAME, DATABASE_DATABASE_ID_FIELD_NAME,
+  DATABASE_FIELD_ID_FIELD_NAME, DATABASE_FIELD_NAME_FIELD_NAME, DATABASE_ROW_ID_FIELD_NAME,
+  DATABASE_ROW_TITLE_FIELD_NAME, DATABASE_ROW_VERSION_FIELD_NAME, DATABASE_SCHEMA_VERSION,
+  DATABASE_VIEW_ID_FIELD_NAME, DATABASE_WORKSPACE_ID_FIELD_NAME,
+};
+use crate::services::index_store::{IndexStatus, IndexStore, IndexWriterBudget};
+use crate::services::snippet::{content_snippet, SNIPPET_MAX_NUM_CHARS};
+use crate::services::tokenizer::with_cjk_char_queries;
+
+const DATABASE_INDEX_DIR: &str = "database_index";
+
+struct DatabaseSchemaFields {
+  database_id: Field,
+  view_id: Field,
+  row_id: Field,
+  row_title: Field,
+  field_id: Field,
+  field_name: Field,
+  content: Field,
+  workspace_id: Field,
+  row_version: Field,
+}
+
+/// The local full-text index of the database rows. Each searchable cell of a row is indexed
+/// separately, so the matched field can be located in the row.
+#[derive(Clone)]
+pub struct DatabaseIndexManagerImpl {
+  store: Arc<IndexStore>,
+}
+
+impl DatabaseIndexManagerImpl {
+  pub fn new(auth_user: Option<Weak<AuthenticateUser>>) -> Self {
+    // AuthenticateUser is required to get the index path and the language of the tokenizer
+    let auth_user = auth_user.and_then(|auth_user| auth_user.upgrade());
+    if auth_user.is_none() {
+      tracing::error!("DatabaseIndexManager: AuthenticateUser is not available");
+    }
+    let index_path = auth_user.as_ref().map(|auth_user| {
+      auth_user
+        .get_index_path()
+        .join(Path::new(DATABASE_INDEX_DIR))
+    });
+    let language_code = auth_user
+      .map(|auth_user| auth_user.get_search_language_code())
+      .unwrap_or_default();
+
+    let store = IndexStore::open(
+      "DatabaseIndexManager",
+      DatabaseSchema::new().schema,
+      index_path,
+      DATABASE_SCHEMA_VERSION,
+      language_code,
+      IndexWriterBudget::SingleThread {
+        memory_budget: 15_000_000,
+      },
+    );
+    Self {
+      store: Arc::new(store),
+    }
+  }
+
+  /// Replace the indexed cells of the row with the given cells.
+  pub fn index_row(
+    &self,
+    database_id: &str,
+    view_id: &str,
+    workspace_id: &str,
+    row: DatabaseRowIndexData,
+  ) -> FlowyResult<()> {
+    self.update_rows(database_id, view_id, workspace_id, vec![row], vec![])
+  }
+
+  /// Replace the indexed rows of the database with the given rows.
+  pub fn index_database(
+    &self,
+    database_id: &str,
+    view_id: &str,
+    workspace_id: &str,
+    rows: Vec<DatabaseRowIndexData>,
+  ) -> FlowyResult<()> {
+    let fields = self.get_schema_fields()?;
+    let index = self.store.opened()?;
+    let mut index_writer = index.writer()?;
+    index_writer.delete_term(Term::from_field_text(fields.database_id, database_id));
+    for row in rows {
+      add_row(
+        &index_writer,
+        &fields,
+        database_id,
+        view_id,
+        workspace_id,
+        row,
+      );
+    }
+    index_writer.commit()
+  }
+
+  /// Replace the indexed cells of the given rows and remove the rows that are no longer in the
+  /// database, in one commit. Nothing is written if there are no changes.
+  pub fn update_rows(
+    &self,
+    database_id: &str,
+    view_id: &str,
+    workspace_id: &str,
+    rows: Vec<DatabaseRowIndexData>,
+    removed_row_ids: Vec<String>,
+  ) -> FlowyResult<()> {
+    if rows.is_empty() && removed_row_ids.is_empty() {
+      return Ok(());
+    }
+    let fields = self.get_schema_fields()?;
+    let index = self.store.opened()?;
+    let mut index_writer = index.writer()?;
+    for row_id in removed_row_ids {
+      index_writer.delete_term(Term::from_field_text(fields.row_id, &row_id));
+    }
+    for row in rows {
+      index_writer.delete_term(Term::from_field_text(fields.row_id, &row.row_id));
+      add_row(
+        &index_writer,
+        &fields,
+        database_id,
+        view_id,
+        workspace_id,
+        row,
+      );
+    }
+    index_writer.commit()
+  }
+
+  /// Remove the indexed cells of the given rows.
+  pub fn remove_rows(&self, row_ids: Vec<String>) -> FlowyResult<()> {
+    let fields = self.get_schema_fields()?;
+    let index = self.store.opened()?;
+    let mut index_writer = index.writer()?;
+    for row_id in row_ids {
+      index_writer.delete_term(Term::from_field_text(fields.row_id, &row_id));
+    }
+    index_writer.commit()
+  }
+
+  /// Remove the indexed rows of the given database.
+  pub fn remove_database(&self, database_id: &str) -> FlowyResult<()> {
+    let fields = self.get_schema_fields()?;
+    let index = self.store.opened()?;
+    let mut index_writer = index.writer()?;
+    index_writer.delete_term(Term::from_field_text(fields.database_id, database_id));
+    index_writer.commit()
+  }
+
+  /// Remove the indexed rows of all the databases of the workspace.
+  pub fn remove_indices_for_workspace(&self, workspace_id: &str) -> FlowyResult<()> {
+    let fields = self.get_schema_fields()?;
+    let index = self.store.opened()?;
+    let mut index_writer = index.writer()?;
+    index_writer.delete_term(Term::from_field_text(fields.workspace_id, workspace_id));
+    index_writer.commit()
+  }
+
+  /// Returns the versions of the indexed rows of the workspace, grouped by the database id and
+  /// keyed by the row id.
+  pub fn get_row_versions(
+    &self,
+    workspace_id: &str,
+  ) -> FlowyResult<HashMap<String, HashMap<String, String>>> {
+    let fields = self.get_schema_fields()?;
+    let index = self.store.opened()?;
+    let query = TermQuery::new(
+      Term::from_field_text(fields.workspace_id, workspace_id),
+      IndexRecordOption::Basic,
+    );
+    let searcher = index.reader.searcher();
+    let mut versions: HashMap<String, HashMap<String, String>> = HashMap::new();
+    for doc_address in searcher.search(&query, &DocSetCollector)? {
+      let doc: TantivyDocument = searcher.doc(doc_address)?;
+      let text = |field: Field| {
+        doc
+          .get_first(field)
+          .and_then(|value| value.as_str())
+          .unwrap_or_default()
+          .to_string()
+      };
+      versions
+        .entry(text(fields.database_id))
+        .or_default()
+        .insert(text(fields.row_id), text(fields.row_version));
+    }
+    Ok(versions)
+  }
+
+  /// Switches to the index of the language of the opened workspace.
+  pub fn set_language_code(&self, language_code: &str) {
+    self.store.set_language_code(language_code);
+  }
+
+  pub fn num_docs(&self) -> u64 {
+    self.store.num_docs()
+  }
+
+  pub fn status(&self) -> IndexStatus {
+    self.store.status()
+  }
+
+  /// Notifies the receiver whenever the indexed rows change.
+  pub fn subscribe_index_changed(&self) -> broadcast::Receiver<()> {
+    self.store.subscribe_changes()
+  }
+
+  /// Removes all the indexed rows and recreates the index files.
+  pub fn rebuild(&self) -> FlowyResult<()> {
+    self.store.rebuild()
+  }
+
+  /// Returns the cells of the databases of the given views that match the query, the best match
+  /// first. The rows are indexed with the inline view of their database.
+  pub fn search(
+    &self,
+    query: &str,
+    workspace_id: Option<&str>,
+    view_ids: &[String],
+    limit: usize,
+  ) -> FlowyResult<Vec<DatabaseRowIndexHit>> {
+    let fields = self.get_schema_fields()?;
+    let index = self.store.opened()?;
+
+    let query_parser = QueryParser::for_index(&index.index, vec![fields.content]);
+    // The query is typed by the user, so the syntax errors are ignored
+    let (content_query, _) = query_parser.parse_query_lenient(query);
+    let content_query = with_cjk_char_queries(content_query, query, &[fields.content]);
+    let searcher = index.reader.searcher();
+    let mut snippet_generator =
+      SnippetGenerator::create(&searcher, &*content_query, fields.content)?;
+    snippet_generator.set_max_num_chars(SNIPPET_MAX_NUM_CHARS);
+    let mut clauses: Vec<(Occur, Box<dyn Query>)> = vec![(Occur::Must, content_query)];
+    if let Some(workspace_id) = workspace_id {
+      clauses.push((
+        Occur::Must,
+        Box::new(TermQuery::new(
+          Term::from_field_text(fields.workspace_id, workspace_id),
+          IndexRecordOption::Basic,
+        )),
+      ));
+    }
+    // The views are filtered in the query, so the limit isn't taken up by the views that were
+    // deleted or moved to the trash.
+    clauses.push((
+      Occur::Must,
+      Box::new(TermSetQuery::new(
+        view_ids
+          .iter()
+          .map(|view_id| Term::from_field_text(fields.view_id, view_id)),
+      )),
+    ));
+    let query = BooleanQuery::new(clauses);
+
+    let top_docs = searcher.search(&query, &TopDocs::with_limit(limit))?;
+    let mut hits = vec![];
+    for (score, doc_address) in top_docs {
+      let doc: TantivyDocument = searcher.doc(doc_address)?;
+      let text = |field: Field| {
+        doc
+          .get_first(field)
+          .and_then(|value| value.as_str())
+          .unwrap_or_default()
+          .to_string()
+      };
+      hits.push(DatabaseRowIndexHit {
+        database_id: text(fields.database_id),
+        view_id: text(fields.view_id),
+        row_id: text(fields.row_id),
+        row_title: text(fields.row_title),
+        field_id: text(fields.field_id),
+        field_name: text(fields.field_name),
+        snippet: content_snippet(&snippet_generator, &text(fields.content)),
+        workspace_id: text(fields.workspace_id),
+        score,
+      });
+    }
+    Ok(hits)
+  }
+
+  fn get_schema_fields(&self) -> FlowyResult<DatabaseSchemaFields> {
+    let schema = self.store.schema();
+    Ok(DatabaseSchemaFields {
+      database_id: schema.get_field(DATABASE_DATABASE_ID_FIELD_NAME)?,
+      view_id: schema.get_field(DATABASE_VIEW_ID_FIELD_NAME)?,
+      row_id: schema.get_field(DATABASE_ROW_ID_FIELD_NAME)?,
+      row_title: schema.get_field(DATABASE_ROW_TITLE_FIELD_NAME)?,
+      field_id: schema.get_field(DATABASE_FIELD_ID_FIELD_NAME)?,
+      field_name: schema.get_field(DATABASE_FIELD_NAME_FIELD_NAME)?,
+      content: schema.get_field(DATABASE_CONTENT_FIELD_NAME)?,
+      workspace_id: schema.get_field(DATABASE_WORKSPACE_ID_FIELD_NAME)?,
+      row_version: schema.get_field(DATABASE_ROW_VERSION_FIELD_NAME)?,
+    })
+  }
+}
+
+/// Add a document for each cell of the row. The content of the primary cell is stored as the
+/// title of the row, so every matched cell can be shown with it. A row without cells is added
+/// without content, so its version is still recorded.
+fn add_row(
+  index_writer: &IndexWriter,
+  fields: &DatabaseSchemaFields,
+  database_id: &str,
+  view_id: &str,
+  workspace_id: &str,
+  row: DatabaseRowIndexData,
+) {
+  if row.cells.is_empty() {
+    let _ = index_writer.add_document(doc![
+      fields.database_id => database_id,
+      fields.view_id => view_id,
+      fields.row_id => row.row_id,
+      fields.workspace_id => workspace_id,
+      fields.row_version => row.version,
+    ]);
+    return;
+  }
+
+  let row_title = row
+    .cells
+    .iter()
+    .find(|cell| cell.is_primary)
+    .map(|cell| cell.content.clone())
+    .unwrap_or_default();
+  for cell in row.cells {
+    let _ = index_writer.add_document(doc![
+      fields.database_id => database_id,
+      fields.view_id => view_id,
+      fields.row_id => row.row_id.as_str(),
+      fields.row_title => row_title.as_str(),
+      fields.field_id => cell.field_id,
+      fields.field_name => cell.field_name,
+      fields.content => cell.content,
+      fields.workspace_id => workspace_id,
+      fields.row_version => row.version.as_str(),
+    ]);
+  }
+}
@@ -0,0 +1,69 @@
+use tantivy::schema::Schema;
+
+use crate::services::tokenizer::search_text_options;
+
+pub const DATABASE_DATABASE_ID_FIELD_NAME: &str = "database_id";
+pub const DATABASE_VIEW_ID_FIELD_NAME: &str = "view_id";
+pub const DATABASE_ROW_ID_FIELD_NAME: &str = "row_id";
+pub const DATABASE_ROW_TITLE_FIELD_NAME: &str = "row_title";
+pub const DATABASE_FIELD_ID_FIELD_NAME: &str = "field_id";
+pub const DATABASE_FIELD_NAME_FIELD_NAME: &str = "field_name";
+pub const DATABASE_CONTENT_FIELD_NAME: &str = "content";
+pub const DATABASE_WORKSPACE_ID_FIELD_NAME: &str = "workspace_id";
+pub const DATABASE_ROW_VERSION_FIELD_NAME: &str = "row_version";
+
+/// The version of the schema. Increase it when the schema is changed, the index created with an
+/// older version is removed and rebuilt with the new schema.
+pub const DATABASE_SCHEMA_VERSION: u32 = 3;
+
+/// Each document of the index is a searchable cell of a database row. A row without searchable
+/// cells is kept as a document without content, so the version of every indexed row is known.
+///
+/// Do not change the schema without increasing the [DATABASE_SCHEMA_VERSION].
+#[derive(Clone)]
+pub struct DatabaseSchema {
+  pub schema: Schema,
+}
+
+impl DatabaseSchema {
+  pub fn new() -> Self {
+    let mut schema_builder = Schema::builder();
+    schema_builder.add_text_field(
+      DATABASE_DATABASE_ID_FIELD_NAME,
+      tantivy::schema::STRING | tantivy::schema::STORED,
+    );
+    schema_builder.add_text_field(
+      DATABASE_VIEW_ID_FIELD_NAME,
+      tantivy::schema::STRING | tantivy::schema::STORED,
+    );
+    schema_builder.add_text_field(
+      DATABASE_ROW_ID_FIELD_NAME,
+      tantivy::schema::STRING | tantivy::schema::STORED,
+    );
+    schema_builder.add_text_field(DATABASE_ROW_TITLE_FIELD_NAME, tantivy::schema::STORED);
+    schema_builder.add_text_field(
+      DATABASE_FIELD_ID_FIELD_NAME,
+      tantivy::schema::STRING | tantivy::schema::STORED,
+    );
+    schema_builder.add_text_field(DATABASE_FIELD_NAME_FIELD_NAME, tantivy::schema::STORED);
+    schema_builder.add_text_field(
+      DATABASE_CONTENT_FIELD_NAME,
+      search_text_options().set_stored(),
+    );
+    schema_builder.add_text_field(
+      DATABASE_WORKSPACE_ID_FIELD_NAME,
+      tantivy::schema::STRING | tantivy::schema::STORED,
+    );
+    schema_builder.add_text_field(DATABASE_ROW_VERSION_FIELD_NAME, tantivy::schema::STORED);
+
+    let schema = schema_builder.build();
+
+    Self { schema }
+  }
+}
+
+impl Default for DatabaseSchema {
+  fn default() -> Self {
+    Self::new()
+  }
+}
@@ -0,0 +1,18 @@
+use crate::services::snippet::ContentSnippet;
+
+/// The text of a block that is added to the document index.
+#[derive(Debug, Clone)]
+pub struct DocumentBlockIndexData {
+  pub block_id: String,
+  pub content: String,
+}
+
+/// A block of a document that matches the search query.
+#[derive(Debug, Clone)]
+pub struct DocumentIndexHit {
+  pub view_id: String,
+  pub block_id: String,
+  pub snippet: ContentSnippet,
+  pub workspace_id: String,
+  pub score: f32,
+}
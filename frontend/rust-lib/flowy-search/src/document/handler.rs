@@ -1,31 +1,84 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 use tracing::{trace, warn};
 
 use flowy_error::FlowyResult;
+use flowy_folder::entities::{ViewLayoutPB, ViewPB};
 use flowy_folder::{manager::FolderManager, ViewLayout};
 use flowy_search_pub::cloud::SearchCloudService;
 use lib_infra::async_trait::async_trait;
+use tokio::sync::broadcast;
 
+use super::indexer::DocumentIndexManagerImpl;
 use crate::{
-  entities::{IndexTypePB, ResultIconPB, ResultIconTypePB, SearchFilterPB, SearchResultPB},
-  services::manager::{SearchHandler, SearchType},
+  entities::{
+    IndexTypePB, ResultIconPB, ResultIconTypePB, SearchFilterPB, SearchHighlightPB, SearchResultPB,
+  },
+  services::index_store::IndexStatus,
+  services::manager::{SearchHandler, SearchType, SearchViews},
+  services::ranking::{normalize_index_score, sort_by_score},
 };
 
 pub struct DocumentSearchHandler {
   pub cloud_service: Arc<dyn SearchCloudService>,
   pub folder_manager: Arc<FolderManager>,
+  pub index_manager: Arc<DocumentIndexManagerImpl>,
 }
 
 impl DocumentSearchHandler {
   pub fn new(
     cloud_service: Arc<dyn SearchCloudService>,
     folder_manager: Arc<FolderManager>,
+    index_manager: Arc<DocumentIndexManagerImpl>,
   ) -> Self {
     Self {
       cloud_service,
       folder_manager,
+      index_manager,
     }
   }
+
+  /// Search the content of the documents in the local index. Each matched block is returned as a
+  /// [IndexTypePB::DocumentBlock] result, the id of the result is the block id.
+  fn perform_local_search(
+    &self,
+    query: &str,
+    filter: &SearchFilterPB,
+    views: &HashMap<String, ViewPB>,
+    limit: usize,
+  ) -> FlowyResult<Vec<SearchResultPB>> {
+    // The index might contain documents that were deleted or moved to the trash
+    let view_ids = views
+      .values()
+      .filter(|view| view.layout == ViewLayoutPB::Document && filter.matches_view(view, views))
+      .map(|view| view.id.clone())
+      .collect::<Vec<_>>();
+    let hits =
+      self
+        .index_manager
+        .search(query, filter.workspace_id.as_deref(), &view_ids, limit)?;
+    trace!("[Search] local document search results: {:?}", hits);
+
+    let search_results = hits
+      .into_iter()
+      .filter_map(|hit| {
+        let view = views.get(&hit.view_id)?;
+        Some(SearchResultPB {
+          index_type: IndexTypePB::DocumentBlock,
+          view_id: hit.view_id,
+          id: hit.block_id,
+          data: view.name.clone(),
+          icon: Some(view_result_icon(view)),
+          score: normalize_index_score(hit.score as f64),
+          workspace_id: hit.workspace_id,
+          highlights: SearchHighlightPB::from_snippet(&hit.snippet),
+          preview: Some(hit.snippet.fragment),
+          field_id: None,
+        })
+      })
+      .collect();
+    Ok(search_results)
+  }
 }
 
 #[async_trait]
@@ -38,67 +91,116 @@ impl SearchHandler for DocumentSearchHandler {
     &self,
     query: String,
     filter: Option<SearchFilterPB>,
+    limit: usize,
   ) -> FlowyResult<Vec<SearchResultPB>> {
-    let filter = match filter {
-      Some(filter) => filter,
-      None => return Ok(vec![]),
-    };
+    // Grab all views from folder cache
+    // Notice that `get_all_view_pb` returns Views that don't include trashed and private views
+    let views = self
+      .folder_manager
+      .get_all_views_pb()
+      .await?
+      .into_iter()
+      .map(|view| (view.id.clone(), view))
+      .collect::<SearchViews>();
+    self
+      .perform_search_in_views(query, filter, &views, limit)
+      .await
+  }
+
+  async fn perform_search_in_views(
+    &self,
+    query: String,
+    filter: Option<SearchFilterPB>,
+    views: &SearchViews,
+    limit: usize,
+  ) -> FlowyResult<Vec<SearchResultPB>> {
+    let filter = filter.unwrap_or_default();
+    let mut search_results = self
+      .perform_local_search(&query, &filter, views, limit)
+      .unwrap_or_else(|err| {
+        warn!("[Search] local document search failed: {}", err);
+        vec![]
+      });
 
-    let workspace_id = match filter.workspace_id {
+    let workspace_id = match filter.workspace_id.clone() {
       Some(workspace_id) => workspace_id,
-      None => return Ok(vec![]),
+      None => return Ok(search_results),
     };
 
-    let results = self
+    // The cloud search is not available when the user is offline or uses a local server, the
+    // local results are still returned in that case.
+    let results = match self
       .cloud_service
       .document_search(&workspace_id, query)
-      .await?;
+      .await
+    {
+      Ok(results) => results,
+      Err(err) => {
+        warn!("[Search] remote document search failed: {}", err);
+        return Ok(search_results);
+      },
+    };
     trace!("[Search] remote search results: {:?}", results);
 
-    // Grab all views from folder cache
-    // Notice that `get_all_view_pb` returns Views that don't include trashed and private views
-    let mut views = self.folder_manager.get_all_views_pb().await?.into_iter();
-    let mut search_results: Vec<SearchResultPB> = vec![];
-
     for result in results {
-      if let Some(view) = views.find(|v| v.id == result.object_id) {
+      if let Some(view) = views.get(&result.object_id) {
         // If there is no View for the result, we don't add it to the results
-        // If possible we will extract the icon to display for the result
-        let icon: Option<ResultIconPB> = match view.icon.clone() {
-          Some(view_icon) => Some(ResultIconPB::from(view_icon)),
-          None => {
-            let view_layout_ty: i64 = ViewLayout::from(view.layout.clone()).into();
-            Some(ResultIconPB {
-              ty: ResultIconTypePB::Icon,
-              value: view_layout_ty.to_string(),
-            })
-          },
-        };
-
+        if !filter.matches_view(view, views) {
+          continue;
+        }
         search_results.push(SearchResultPB {
           index_type: IndexTypePB::Document,
           view_id: result.object_id.clone(),
           id: result.object_id.clone(),
           data: view.name.clone(),
-          icon,
+          icon: Some(view_result_icon(view)),
           // We reverse the score, the cloud search score is based on
           // 1 being the worst result, and closer to 0 being good result, that is
           // the opposite of local search.
           score: 1.0 - result.score,
           workspace_id: result.workspace_id,
           preview: result.preview,
+          field_id: None,
+          highlights: vec![],
         });
       } else {
         warn!("No view found for search result: {:?}", result);
       }
     }
 
+    sort_by_score(&mut search_results);
+    search_results.truncate(limit);
     trace!("[Search] showing results: {:?}", search_results);
     Ok(search_results)
   }
 
-  /// Ignore for [DocumentSearchHandler]
   fn index_count(&self) -> u64 {
-    0
+    self.index_manager.num_docs()
+  }
+
+  fn index_status(&self) -> IndexStatus {
+    self.index_manager.status()
+  }
+
+  fn rebuild_index(&self) -> FlowyResult<()> {
+    self.index_manager.rebuild()
+  }
+
+  fn subscribe_index_changed(&self) -> broadcast::Receiver<()> {
+    self.index_manager.subscribe_index_changed()
+  }
+}
+
+/// Use the icon of the view, or the icon of its layout if the view has no icon.
+pub(crate) fn view_result_icon(view: &ViewPB) -> ResultIconPB {
+  match view.icon.clone() {
+    Some(view_icon) => ResultIconPB::from(view_icon),
+    None => {
+      let view_layout_ty: i64 = ViewLayout::from(view.layout.clone()).into();
+      ResultIconPB {
+        ty: ResultIconTypePB::Icon,
+        value: view_layout_ty.to_string(),
+      }
+    },
   }
 }
@@ -0,0 +1,222 @@
+use std::path::Path;
+use std::sync::{Arc, Weak};
+
+use flowy_error::FlowyResult;
+use flowy_user::services::authenticate_user::AuthenticateUser;
+use tantivy::collector::{Count, TopDocs};
+use tantivy::query::{BooleanQuery, Occur, Query, QueryParser, TermQuery, TermSetQuery};
+use tantivy::schema::{Field, IndexRecordOption, Value};
+use tantivy::{doc, SnippetGenerator, TantivyDocument, Term};
+use tokio::sync::broadcast;
+
+use super::entities::{DocumentBlockIndexData, DocumentIndexHit};
+use super::schema::{
+  DocumentSchema, DOCUMENT_BLOCK_ID_FIELD_NAME, DOCUMENT_CONTENT_FIELD_NAME,
+  DOCUMENT_SCHEMA_VERSION, DOCUMENT_VIEW_ID_FIELD_NAME, DOCUMENT_WORKSPACE_ID_FIELD_NAME,
+};
+use crate::services::index_store::{IndexStatus, IndexStore, IndexWriterBudget};
+use crate::services::snippet::{content_snippet, SNIPPET_MAX_NUM_CHARS};
+use crate::services::tokenizer::with_cjk_char_queries;
+
+const DOCUMENT_INDEX_DIR: &str = "document_index";
+
+struct DocumentSchemaFields {
+  view_id: Field,
+  block_id: Field,
+  content: Field,
+  workspace_id: Field,
+}
+
+/// The local full-text index of the content of the documents. Each block that has text is
+/// indexed separately, so a match can be located in the document.
+#[derive(Clone)]
+pub struct DocumentIndexManagerImpl {
+  store: Arc<IndexStore>,
+}
+
+impl DocumentIndexManagerImpl {
+  pub fn new(auth_user: Option<Weak<AuthenticateUser>>) -> Self {
+    // AuthenticateUser is required to get the index path and the language of the tokenizer
+    let auth_user = auth_user.and_then(|auth_user| auth_user.upgrade());
+    if auth_user.is_none() {
+      tracing::error!("DocumentIndexManager: AuthenticateUser is not available");
+    }
+    let index_path = auth_user.as_ref().map(|auth_user| {
+      auth_user
+        .get_index_path()
+        .join(Path::new(DOCUMENT_INDEX_DIR))
+    });
+    let language_code = auth_user
+      .map(|auth_user| auth_user.get_search_language_code())
+      .unwrap_or_default();
+
+    let store = IndexStore::open(
+      "DocumentIndexManager",
+      DocumentSchema::new().schema,
+      index_path,
+      DOCUMENT_SCHEMA_VERSION,
+      language_code,
+      IndexWriterBudget::SingleThread {
+        memory_budget: 15_000_000,
+      },
+    );
+    Self {
+      store: Arc::new(store),
+    }
+  }
+
+  /// Replace the indexed blocks of the document with the given blocks.
+  pub fn index_document(
+    &self,
+    view_id: &str,
+    workspace_id: &str,
+    blocks: Vec<DocumentBlockIndexData>,
+  ) -> FlowyResult<()> {
+    let fields = self.get_schema_fields()?;
+    let index = self.store.opened()?;
+    let mut index_writer = index.writer()?;
+    index_writer.delete_term(Term::from_field_text(fields.view_id, view_id));
+    for block in blocks {
+      let _ = index_writer.add_document(doc![
+        fields.view_id => view_id,
+        fields.block_id => block.block_id,
+        fields.content => block.content,
+        fields.workspace_id => workspace_id,
+      ]);
+    }
+    index_writer.commit()
+  }
+
+  /// Remove the indexed blocks of the given documents.
+  pub fn remove_documents(&self, view_ids: Vec<String>) -> FlowyResult<()> {
+    let fields = self.get_schema_fields()?;
+    let index = self.store.opened()?;
+    let mut index_writer = index.writer()?;
+    for view_id in view_ids {
+      index_writer.delete_term(Term::from_field_text(fields.view_id, &view_id));
+    }
+    index_writer.commit()
+  }
+
+  /// Remove the indexed blocks of all the documents of the workspace.
+  pub fn remove_indices_for_workspace(&self, workspace_id: &str) -> FlowyResult<()> {
+    let fields = self.get_schema_fields()?;
+    let index = self.store.opened()?;
+    let mut index_writer = index.writer()?;
+    index_writer.delete_term(Term::from_field_text(fields.workspace_id, workspace_id));
+    index_writer.commit()
+  }
+
+  /// Returns true if any block of the document is indexed.
+  pub fn is_document_indexed(&self, view_id: &str) -> bool {
+    let (Ok(fields), Ok(index)) = (self.get_schema_fields(), self.store.opened()) else {
+      return false;
+    };
+    let query = TermQuery::new(
+      Term::from_field_text(fields.view_id, view_id),
+      IndexRecordOption::Basic,
+    );
+    index
+      .reader
+      .searcher()
+      .search(&query, &Count)
+      .map(|count| count > 0)
+      .unwrap_or(false)
+  }
+
+  /// Switches to the index of the language of the opened workspace.
+  pub fn set_language_code(&self, language_code: &str) {
+    self.store.set_language_code(language_code);
+  }
+
+  pub fn num_docs(&self) -> u64 {
+    self.store.num_docs()
+  }
+
+  pub fn status(&self) -> IndexStatus {
+    self.store.status()
+  }
+
+  /// Notifies the receiver whenever the indexed documents change.
+  pub fn subscribe_index_changed(&self) -> broadcast::Receiver<()> {
+    self.store.subscribe_changes()
+  }
+
+  /// Removes all the indexed documents and recreates the index files.
+  pub fn rebuild(&self) -> FlowyResult<()> {
+    self.store.rebuild()
+  }
+
+  /// Returns the blocks of the given documents that match the query, the best match first.
+  pub fn search(
+    &self,
+    query: &str,
+    workspace_id: Option<&str>,
+    view_ids: &[String],
+    limit: usize,
+  ) -> FlowyResult<Vec<DocumentIndexHit>> {
+    let fields = self.get_schema_fields()?;
+    let index = self.store.opened()?;
+
+    let query_parser = QueryParser::for_index(&index.index, vec![fields.content]);
+    // The query is typed by the user, so the syntax errors are ignored
+    let (content_query, _) = query_parser.parse_query_lenient(query);
+    let content_query = with_cjk_char_queries(content_query, query, &[fields.content]);
+    let searcher = index.reader.searcher();
+    let mut snippet_generator =
+      SnippetGenerator::create(&searcher, &*content_query, fields.content)?;
+    snippet_generator.set_max_num_chars(SNIPPET_MAX_NUM_CHARS);
+    let mut clauses: Vec<(Occur, Box<dyn Query>)> = vec![(Occur::Must, content_query)];
+    if let Some(workspace_id) = workspace_id {
+      clauses.push((
+        Occur::Must,
+        Box::new(TermQuery::new(
+          Term::from_field_text(fields.workspace_id, workspace_id),
+          IndexRecordOption::Basic,
+        )),
+      ));
+    }
+    // The views are filtered in the query, so the limit isn't taken up by the views that were
+    // deleted or moved to the trash.
+    clauses.push((
+      Occur::Must,
+      Box::new(TermSetQuery::new(
+        view_ids
+          .iter()
+          .map(|view_id| Term::from_field_text(fields.view_id, view_id)),
+      )),
+    ));
+    let query = BooleanQuery::new(clauses);
+
+    let top_docs = searcher.search(&query, &TopDocs::with_limit(limit))?;
+    let mut hits = vec![];
+    for (score, doc_address) in top_docs {
+      let doc: TantivyDocument = searcher.doc(doc_address)?;
+      let text = |field: Field| {
+        doc
+          .get_first(field)
+          .and_then(|value| value.as_str())
+          .unwrap_or_default()
+          .to_string()
+      };
+      hits.push(DocumentIndexHit {
+        view_id: text(fields.view_id),
+        block_id: text(fields.block_id),
+        snippet: content_snippet(&snippet_generator, &text(fields.content)),
+        workspace_id: text(fields.workspace_id),
+        score,
+      });
+    }
+    Ok(hits)
+  }
+
+  fn get_schema_fields(&self) -> FlowyResult<DocumentSchemaFields> {
+    let schema = self.store.schema();
+    Ok(DocumentSchemaFields {
+      view_id: schema.get_field(DOCUMENT_VIEW_ID_FIELD_NAME)?,
+      block_id: schema.get_field(DOCUMENT_BLOCK_ID_FIELD_NAME)?,
+      content: schema.get_field(DOCUMENT_CONTENT_FIELD_NAME)?,
+      workspace_id: schema.get_field(DOCUMENT_WORKSPACE_ID_FIELD_NAME)?,
+    })
+  }
+}
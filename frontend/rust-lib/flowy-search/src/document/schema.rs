@@ -0,0 +1,52 @@
+use tantivy::schema::Schema;
+
+use crate::services::tokenizer::search_text_options;
+
+pub const DOCUMENT_VIEW_ID_FIELD_NAME: &str = "view_id";
+pub const DOCUMENT_BLOCK_ID_FIELD_NAME: &str = "block_id";
+pub const DOCUMENT_CONTENT_FIELD_NAME: &str = "content";
+pub const DOCUMENT_WORKSPACE_ID_FIELD_NAME: &str = "workspace_id";
+
+/// The version of the schema. Increase it when the schema is changed, the index created with an
+/// older version is removed and rebuilt with the new schema.
+pub const DOCUMENT_SCHEMA_VERSION: u32 = 2;
+
+/// Each document of the index is a block of a document view that has text.
+///
+/// Do not change the schema without increasing the [DOCUMENT_SCHEMA_VERSION].
+#[derive(Clone)]
+pub struct DocumentSchema {
+  pub schema: Schema,
+}
+
+impl DocumentSchema {
+  pub fn new() -> Self {
+    let mut schema_builder = Schema::builder();
+    schema_builder.add_text_field(
+      DOCUMENT_VIEW_ID_FIELD_NAME,
+      tantivy::schema::STRING | tantivy::schema::STORED,
+    );
+    schema_builder.add_text_field(
+      DOCUMENT_BLOCK_ID_FIELD_NAME,
+      tantivy::schema::STRING | tantivy::schema::STORED,
+    );
+    schema_builder.add_text_field(
+      DOCUMENT_CONTENT_FIELD_NAME,
+      search_text_options().set_stored(),
+    );
+    schema_builder.add_text_field(
+      DOCUMENT_WORKSPACE_ID_FIELD_NAME,
+      tantivy::schema::STRING | tantivy::schema::STORED,
+    );
+
+    let schema = schema_builder.build();
+
+    Self { schema }
+  }
+}
+
+impl Default for DocumentSchema {
+  fn default() -> Self {
+    Self::new()
+  }
+}
@@ -0,0 +1,81 @@
+use flowy_derive::{ProtoBuf, ProtoBuf_Enum};
+
+use crate::services::index_store::IndexStatus;
+use crate::services::manager::SearchType;
+
+#[derive(ProtoBuf_Enum, Eq, PartialEq, Debug, Clone, Default)]
+pub enum SearchIndexTypePB {
+  #[default]
+  Folder = 0,
+  Document = 1,
+  Database = 2,
+}
+
+impl From<SearchType> for SearchIndexTypePB {
+  fn from(search_type: SearchType) -> Self {
+    match search_type {
+      SearchType::Folder => SearchIndexTypePB::Folder,
+      SearchType::Document => SearchIndexTypePB::Document,
+      SearchType::Database => SearchIndexTypePB::Database,
+    }
+  }
+}
+
+/// The health of the local index of a search handler.
+#[derive(ProtoBuf, Default, Debug, Clone)]
+pub struct SearchIndexStatusPB {
+  #[pb(index = 1)]
+  pub index_type: SearchIndexTypePB,
+
+  /// False if the index couldn't be opened. The index has to be rebuilt with
+  /// [SearchEvent::RebuildIndex](crate::event_map::SearchEvent::RebuildIndex) in that case.
+  #[pb(index = 2)]
+  pub is_available: bool,
+
+  #[pb(index = 3)]
+  pub doc_count: u64,
+
+  /// The timestamp in seconds of the last write of the index.
+  #[pb(index = 4, one_of)]
+  pub last_indexed_at: Option<i64>,
+
+  /// The last error of opening or writing the index.
+  #[pb(index = 5, one_of)]
+  pub error: Option<String>,
+}
+
+impl SearchIndexStatusPB {
+  pub fn new(search_type: SearchType, status: IndexStatus) -> Self {
+    Self {
+      index_type: search_type.into(),
+      is_available: status.is_available,
+      doc_count: status.num_docs,
+      last_indexed_at: status.last_indexed_at,
+      error: status.error,
+    }
+  }
+}
+
+#[derive(ProtoBuf, Default, Debug, Clone)]
+pub struct RepeatedSearchIndexStatusPB {
+  #[pb(index = 1)]
+  pub items: Vec<SearchIndexStatusPB>,
+}
+
+/// The progress of rebuilding the search indexes. The views, documents and databases are counted
+/// as indexed objects.
+#[derive(ProtoBuf, Default, Debug, Clone)]
+pub struct SearchIndexRebuildProgressPB {
+  #[pb(index = 1)]
+  pub indexed: u64,
+
+  #[pb(index = 2)]
+  pub total: u64,
+
+  #[pb(index = 3)]
+  pub is_finished: bool,
+
+  /// Set if the rebuild failed, the indexes might be incomplete in that case.
+  #[pb(index = 4, one_of)]
+  pub error: Option<String>,
+}
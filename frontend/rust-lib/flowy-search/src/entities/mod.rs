@@ -1,11 +1,15 @@
+mod index_status;
 mod index_type;
 mod notification;
 mod query;
 mod result;
 mod search_filter;
+mod search_history;
 
+pub use index_status::*;
 pub use index_type::*;
 pub use notification::*;
 pub use query::*;
 pub use result::*;
 pub use search_filter::*;
+pub use search_history::*;
@@ -22,6 +22,7 @@ pub enum SearchNotification {
   #[default]
   Unknown = 0,
   DidUpdateResults = 1,
+  DidUpdateIndexRebuildProgress = 2,
 }
 
 impl std::convert::From<SearchNotification> for i32 {
@@ -34,6 +35,7 @@ impl std::convert::From<i32> for SearchNotification {
   fn from(notification: i32) -> Self {
     match notification {
       1 => SearchNotification::DidUpdateResults,
+      2 => SearchNotification::DidUpdateIndexRebuildProgress,
       _ => SearchNotification::Unknown,
     }
   }
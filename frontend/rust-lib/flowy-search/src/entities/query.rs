@@ -1,12 +1,15 @@
 use flowy_derive::ProtoBuf;
 
 use super::SearchFilterPB;
+use crate::services::manager::SearchPage;
 
 #[derive(Eq, PartialEq, ProtoBuf, Default, Debug, Clone)]
 pub struct SearchQueryPB {
   #[pb(index = 1)]
   pub search: String,
 
+  /// The max number of results. If the limit or the offset is set, the results of all the
+  /// handlers are merged and ranked, and only the page of the results is sent.
   #[pb(index = 2, one_of)]
   pub limit: Option<i64>,
 
@@ -22,4 +25,15 @@ pub struct SearchQueryPB {
   ///
   #[pb(index = 4, one_of)]
   pub channel: Option<String>,
+
+  /// The number of ranked results to skip.
+  #[pb(index = 5, one_of)]
+  pub offset: Option<i64>,
+}
+
+impl SearchQueryPB {
+  /// Returns the requested page of the results, None if the query isn't paginated.
+  pub fn page(&self) -> Option<SearchPage> {
+    SearchPage::from_limit_and_offset(self.limit, self.offset)
+  }
 }
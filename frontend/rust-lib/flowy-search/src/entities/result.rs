@@ -3,6 +3,7 @@ use flowy_derive::{ProtoBuf, ProtoBuf_Enum};
 use flowy_folder::entities::ViewIconPB;
 
 use super::IndexTypePB;
+use crate::services::snippet::ContentSnippet;
 
 #[derive(Debug, Default, ProtoBuf, Clone)]
 pub struct RepeatedSearchResultPB {
@@ -35,6 +36,14 @@ pub struct SearchResultPB {
 
   #[pb(index = 8, one_of)]
   pub preview: Option<String>,
+
+  /// The id of the matched field when the result is a database row.
+  #[pb(index = 9, one_of)]
+  pub field_id: Option<String>,
+
+  /// The ranges of the matched terms in the `preview`.
+  #[pb(index = 10)]
+  pub highlights: Vec<SearchHighlightPB>,
 }
 
 impl SearchResultPB {
@@ -48,10 +57,35 @@ impl SearchResultPB {
       score,
       workspace_id: self.workspace_id.clone(),
       preview: self.preview.clone(),
+      field_id: self.field_id.clone(),
+      highlights: self.highlights.clone(),
     }
   }
 }
 
+/// The range of a matched term, `start` and `end` are offsets in UTF-16 code units.
+#[derive(ProtoBuf, Default, Debug, Clone, PartialEq, Eq)]
+pub struct SearchHighlightPB {
+  #[pb(index = 1)]
+  pub start: u32,
+
+  #[pb(index = 2)]
+  pub end: u32,
+}
+
+impl SearchHighlightPB {
+  pub fn from_snippet(snippet: &ContentSnippet) -> Vec<Self> {
+    snippet
+      .highlights
+      .iter()
+      .map(|(start, end)| SearchHighlightPB {
+        start: *start,
+        end: *end,
+      })
+      .collect()
+  }
+}
+
 #[derive(ProtoBuf_Enum, Clone, Debug, PartialEq, Eq, Default)]
 pub enum ResultIconTypePB {
   #[default]
@@ -1,7 +1,114 @@
+use std::collections::HashMap;
+
 use flowy_derive::ProtoBuf;
+use flowy_folder::entities::{ViewLayoutPB, ViewPB};
 
 #[derive(Eq, PartialEq, ProtoBuf, Default, Debug, Clone)]
 pub struct SearchFilterPB {
   #[pb(index = 1, one_of)]
   pub workspace_id: Option<String>,
+
+  /// Only return the views with one of the layouts. All layouts are returned if it's empty.
+  #[pb(index = 2)]
+  pub layouts: Vec<ViewLayoutFilterPB>,
+
+  #[pb(index = 3, one_of)]
+  pub created_at: Option<SearchDateRangePB>,
+
+  #[pb(index = 4, one_of)]
+  pub last_edited_at: Option<SearchDateRangePB>,
+
+  /// The uid of the user who created the view.
+  #[pb(index = 5, one_of)]
+  pub created_by: Option<i64>,
+
+  /// The uid of the user who edited the view last.
+  #[pb(index = 6, one_of)]
+  pub last_edited_by: Option<i64>,
+
+  /// Only return the views under this view, the view itself is not returned.
+  #[pb(index = 7, one_of)]
+  pub ancestor_view_id: Option<String>,
+}
+
+#[derive(Eq, PartialEq, ProtoBuf, Default, Debug, Clone)]
+pub struct ViewLayoutFilterPB {
+  #[pb(index = 1)]
+  pub layout: ViewLayoutPB,
+}
+
+/// A range of timestamps in seconds, the bounds are inclusive.
+#[derive(Eq, PartialEq, ProtoBuf, Default, Debug, Clone)]
+pub struct SearchDateRangePB {
+  #[pb(index = 1, one_of)]
+  pub start: Option<i64>,
+
+  #[pb(index = 2, one_of)]
+  pub end: Option<i64>,
+}
+
+impl SearchDateRangePB {
+  pub fn contains(&self, timestamp: i64) -> bool {
+    self.start.map_or(true, |start| timestamp >= start)
+      && self.end.map_or(true, |end| timestamp <= end)
+  }
+}
+
+impl SearchFilterPB {
+  /// Returns true if the filter has any condition on the views, besides the workspace.
+  pub fn has_view_conditions(&self) -> bool {
+    !self.layouts.is_empty()
+      || self.created_at.is_some()
+      || self.last_edited_at.is_some()
+      || self.created_by.is_some()
+      || self.last_edited_by.is_some()
+      || self.ancestor_view_id.is_some()
+  }
+
+  /// Returns true if the view matches the view conditions of the filter. The `views` are used to
+  /// find the ancestors of the view.
+  pub fn matches_view(&self, view: &ViewPB, views: &HashMap<String, ViewPB>) -> bool {
+    if !self.layouts.is_empty()
+      && !self
+        .layouts
+        .iter()
+        .any(|filter| filter.layout == view.layout)
+    {
+      return false;
+    }
+    if let Some(range) = &self.created_at {
+      if !range.contains(view.create_time) {
+        return false;
+      }
+    }
+    if let Some(range) = &self.last_edited_at {
+      if !range.contains(view.last_edited) {
+        return false;
+      }
+    }
+    if self.created_by.is_some() && self.created_by != view.created_by {
+      return false;
+    }
+    if self.last_edited_by.is_some() && self.last_edited_by != view.last_edited_by {
+      return false;
+    }
+    if let Some(ancestor_view_id) = &self.ancestor_view_id {
+      let mut parent_view_id = &view.parent_view_id;
+      // The depth is bounded by the number of views, in case the parents form a cycle
+      let mut depth = 0;
+      loop {
+        if parent_view_id == ancestor_view_id {
+          break;
+        }
+        match views.get(parent_view_id) {
+          Some(parent) if depth < views.len() => {
+            parent_view_id = &parent.parent_view_id;
+            depth += 1;
+          },
+          _ => return false,
+        }
+      }
+    }
+    true
+  }
 }
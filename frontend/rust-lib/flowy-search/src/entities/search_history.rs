@@ -0,0 +1,116 @@
+use bytes::Bytes;
+use flowy_derive::ProtoBuf;
+
+use super::SearchFilterPB;
+use crate::services::history::SearchHistoryTable;
+use crate::services::manager::SearchPage;
+
+/// A recent query, or a saved search if it has a name.
+#[derive(ProtoBuf, Default, Debug, Clone)]
+pub struct SearchHistoryItemPB {
+  #[pb(index = 1)]
+  pub id: String,
+
+  #[pb(index = 2)]
+  pub query: String,
+
+  #[pb(index = 3, one_of)]
+  pub filter: Option<SearchFilterPB>,
+
+  /// The name of the saved search, None for a recent query.
+  #[pb(index = 4, one_of)]
+  pub name: Option<String>,
+
+  #[pb(index = 5)]
+  pub is_pinned: bool,
+
+  #[pb(index = 6)]
+  pub created_at: i64,
+
+  #[pb(index = 7)]
+  pub last_used_at: i64,
+}
+
+impl From<SearchHistoryTable> for SearchHistoryItemPB {
+  fn from(row: SearchHistoryTable) -> Self {
+    Self {
+      id: row.id,
+      query: row.query,
+      filter: row
+        .filter
+        .and_then(|filter| SearchFilterPB::try_from(Bytes::from(filter)).ok()),
+      name: row.name,
+      is_pinned: row.is_pinned,
+      created_at: row.created_at,
+      last_used_at: row.last_used_at,
+    }
+  }
+}
+
+#[derive(ProtoBuf, Default, Debug, Clone)]
+pub struct RepeatedSearchHistoryItemPB {
+  #[pb(index = 1)]
+  pub items: Vec<SearchHistoryItemPB>,
+}
+
+#[derive(ProtoBuf, Default, Debug, Clone)]
+pub struct SaveSearchPayloadPB {
+  #[pb(index = 1)]
+  pub name: String,
+
+  #[pb(index = 2)]
+  pub query: String,
+
+  #[pb(index = 3, one_of)]
+  pub filter: Option<SearchFilterPB>,
+}
+
+#[derive(ProtoBuf, Default, Debug, Clone)]
+pub struct PinSearchHistoryPayloadPB {
+  #[pb(index = 1)]
+  pub id: String,
+
+  #[pb(index = 2)]
+  pub is_pinned: bool,
+}
+
+#[derive(ProtoBuf, Default, Debug, Clone)]
+pub struct RepeatedSearchHistoryIdPB {
+  #[pb(index = 1)]
+  pub items: Vec<String>,
+}
+
+/// Runs a recent query or a saved search again. The results are sent like the results of a
+/// [SearchQueryPB](super::SearchQueryPB).
+#[derive(ProtoBuf, Default, Debug, Clone)]
+pub struct RunSavedSearchPayloadPB {
+  #[pb(index = 1)]
+  pub id: String,
+
+  #[pb(index = 2, one_of)]
+  pub channel: Option<String>,
+
+  /// If true, the search is run again whenever the indexed content changes, until another search
+  /// is performed on the channel or the live search is cancelled.
+  #[pb(index = 3)]
+  pub live: bool,
+
+  #[pb(index = 4, one_of)]
+  pub limit: Option<i64>,
+
+  #[pb(index = 5, one_of)]
+  pub offset: Option<i64>,
+}
+
+impl RunSavedSearchPayloadPB {
+  /// Returns the requested page of the results, None if the search isn't paginated.
+  pub fn page(&self) -> Option<SearchPage> {
+    SearchPage::from_limit_and_offset(self.limit, self.offset)
+  }
+}
+
+#[derive(ProtoBuf, Default, Debug, Clone)]
+pub struct SearchChannelPB {
+  #[pb(index = 1, one_of)]
+  pub channel: Option<String>,
+}
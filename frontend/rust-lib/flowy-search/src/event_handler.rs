@@ -1,9 +1,16 @@
 use std::sync::{Arc, Weak};
 
 use flowy_error::{FlowyError, FlowyResult};
-use lib_dispatch::prelude::{AFPluginData, AFPluginState};
+use lib_dispatch::prelude::{data_result_ok, AFPluginData, AFPluginState, DataResult};
 
-use crate::{entities::SearchQueryPB, services::manager::SearchManager};
+use crate::{
+  entities::{
+    PinSearchHistoryPayloadPB, RepeatedSearchHistoryIdPB, RepeatedSearchHistoryItemPB,
+    RepeatedSearchIndexStatusPB, RunSavedSearchPayloadPB, SaveSearchPayloadPB, SearchChannelPB,
+    SearchHistoryItemPB, SearchQueryPB,
+  },
+  services::manager::SearchManager,
+};
 
 fn upgrade_manager(
   search_manager: AFPluginState<Weak<SearchManager>>,
@@ -21,7 +28,91 @@ pub(crate) async fn search_handler(
 ) -> Result<(), FlowyError> {
   let query = data.into_inner();
   let manager = upgrade_manager(manager)?;
-  manager.perform_search(query.search, query.filter, query.channel);
+  let page = query.page();
+  if let Err(err) = manager.record_search(&query.search, query.filter.clone()) {
+    tracing::warn!("Failed to record the search history: {}", err);
+  }
+  manager.perform_search(query.search, query.filter, page, query.channel);
 
   Ok(())
 }
+
+#[tracing::instrument(level = "debug", skip(manager), err)]
+pub(crate) async fn get_index_status_handler(
+  manager: AFPluginState<Weak<SearchManager>>,
+) -> DataResult<RepeatedSearchIndexStatusPB, FlowyError> {
+  let manager = upgrade_manager(manager)?;
+  data_result_ok(RepeatedSearchIndexStatusPB {
+    items: manager.index_status(),
+  })
+}
+
+#[tracing::instrument(level = "debug", skip(manager), err)]
+pub(crate) async fn rebuild_index_handler(
+  manager: AFPluginState<Weak<SearchManager>>,
+) -> Result<(), FlowyError> {
+  let manager = upgrade_manager(manager)?;
+  manager.rebuild_index()
+}
+
+#[tracing::instrument(level = "debug", skip(manager), err)]
+pub(crate) async fn get_search_history_handler(
+  manager: AFPluginState<Weak<SearchManager>>,
+) -> DataResult<RepeatedSearchHistoryItemPB, FlowyError> {
+  let manager = upgrade_manager(manager)?;
+  data_result_ok(RepeatedSearchHistoryItemPB {
+    items: manager.get_search_history()?,
+  })
+}
+
+#[tracing::instrument(level = "debug", skip(data, manager), err)]
+pub(crate) async fn save_search_handler(
+  data: AFPluginData<SaveSearchPayloadPB>,
+  manager: AFPluginState<Weak<SearchManager>>,
+) -> DataResult<SearchHistoryItemPB, FlowyError> {
+  let params = data.into_inner();
+  let manager = upgrade_manager(manager)?;
+  let item = manager.save_search(params.name, params.query, params.filter)?;
+  data_result_ok(item)
+}
+
+#[tracing::instrument(level = "debug", skip(data, manager), err)]
+pub(crate) async fn pin_search_history_handler(
+  data: AFPluginData<PinSearchHistoryPayloadPB>,
+  manager: AFPluginState<Weak<SearchManager>>,
+) -> Result<(), FlowyError> {
+  let params = data.into_inner();
+  let manager = upgrade_manager(manager)?;
+  manager.pin_search_history(&params.id, params.is_pinned)
+}
+
+#[tracing::instrument(level = "debug", skip(data, manager), err)]
+pub(crate) async fn delete_search_history_handler(
+  data: AFPluginData<RepeatedSearchHistoryIdPB>,
+  manager: AFPluginState<Weak<SearchManager>>,
+) -> Result<(), FlowyError> {
+  let params = data.into_inner();
+  let manager = upgrade_manager(manager)?;
+  manager.delete_search_history(params.items)
+}
+
+#[tracing::instrument(level = "debug", skip(data, manager), err)]
+pub(crate) async fn run_saved_search_handler(
+  data: AFPluginData<RunSavedSearchPayloadPB>,
+  manager: AFPluginState<Weak<SearchManager>>,
+) -> Result<(), FlowyError> {
+  let params = data.into_inner();
+  let manager = upgrade_manager(manager)?;
+  manager.run_saved_search(params)
+}
+
+#[tracing::instrument(level = "debug", skip(data, manager), err)]
+pub(crate) async fn cancel_live_search_handler(
+  data: AFPluginData<SearchChannelPB>,
+  manager: AFPluginState<Weak<SearchManager>>,
+) -> Result<(), FlowyError> {
+  let params = data.into_inner();
+  let manager = upgrade_manager(manager)?;
+  manager.cancel_live_search(&params.channel);
+  Ok(())
+}
@@ -4,13 +4,31 @@ use strum_macros::Display;
 use flowy_derive::{Flowy_Event, ProtoBuf_Enum};
 use lib_dispatch::prelude::*;
 
-use crate::{event_handler::search_handler, services::manager::SearchManager};
+use crate::{
+  event_handler::{
+    cancel_live_search_handler, delete_search_history_handler, get_index_status_handler,
+    get_search_history_handler, pin_search_history_handler, rebuild_index_handler,
+    run_saved_search_handler, save_search_handler, search_handler,
+  },
+  services::manager::SearchManager,
+};
 
 pub fn init(search_manager: Weak<SearchManager>) -> AFPlugin {
   AFPlugin::new()
     .state(search_manager)
     .name(env!("CARGO_PKG_NAME"))
     .event(SearchEvent::Search, search_handler)
+    .event(SearchEvent::GetIndexStatus, get_index_status_handler)
+    .event(SearchEvent::RebuildIndex, rebuild_index_handler)
+    .event(SearchEvent::GetSearchHistory, get_search_history_handler)
+    .event(SearchEvent::SaveSearch, save_search_handler)
+    .event(SearchEvent::PinSearchHistory, pin_search_history_handler)
+    .event(
+      SearchEvent::DeleteSearchHistory,
+      delete_search_history_handler,
+    )
+    .event(SearchEvent::RunSavedSearch, run_saved_search_handler)
+    .event(SearchEvent::CancelLiveSearch, cancel_live_search_handler)
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Display, Hash, ProtoBuf_Enum, Flowy_Event)]
@@ -18,4 +36,34 @@ pub fn init(search_manager: Weak<SearchManager>) -> AFPlugin {
 pub enum SearchEvent {
   #[event(input = "SearchQueryPB")]
   Search = 0,
+
+  /// Returns the health of the local search indexes.
+  #[event(output = "RepeatedSearchIndexStatusPB")]
+  GetIndexStatus = 1,
+
+  /// Removes the local search indexes and indexes the workspace again in the background. The
+  /// progress is sent with the `DidUpdateIndexRebuildProgress` notification.
+  #[event()]
+  RebuildIndex = 2,
+
+  /// Returns the recent queries and the saved searches of the current workspace.
+  #[event(output = "RepeatedSearchHistoryItemPB")]
+  GetSearchHistory = 3,
+
+  #[event(input = "SaveSearchPayloadPB", output = "SearchHistoryItemPB")]
+  SaveSearch = 4,
+
+  #[event(input = "PinSearchHistoryPayloadPB")]
+  PinSearchHistory = 5,
+
+  #[event(input = "RepeatedSearchHistoryIdPB")]
+  DeleteSearchHistory = 6,
+
+  /// Runs a recent query or a saved search again, the results are sent like the results of
+  /// [SearchEvent::Search].
+  #[event(input = "RunSavedSearchPayloadPB")]
+  RunSavedSearch = 7,
+
+  #[event(input = "SearchChannelPB")]
+  CancelLiveSearch = 8,
 }
@@ -1,10 +1,13 @@
 use crate::{
   entities::{SearchFilterPB, SearchResultPB},
+  services::index_store::IndexStatus,
   services::manager::{SearchHandler, SearchType},
+  services::ranking::normalize_index_score,
 };
 use flowy_error::FlowyResult;
 use lib_infra::async_trait::async_trait;
 use std::sync::Arc;
+use tokio::sync::broadcast;
 
 use super::indexer::FolderIndexManagerImpl;
 
@@ -28,14 +31,18 @@ impl SearchHandler for FolderSearchHandler {
     &self,
     query: String,
     filter: Option<SearchFilterPB>,
+    limit: usize,
   ) -> FlowyResult<Vec<SearchResultPB>> {
-    let mut results = self.index_manager.search(query, filter.clone())?;
+    let mut results = self.index_manager.search(query, filter.clone(), limit)?;
     if let Some(filter) = filter {
       if let Some(workspace_id) = filter.workspace_id {
         // Filter results by workspace ID
         results.retain(|result| result.workspace_id == workspace_id);
       }
     }
+    for result in results.iter_mut() {
+      result.score = normalize_index_score(result.score);
+    }
 
     Ok(results)
   }
@@ -43,4 +50,16 @@ impl SearchHandler for FolderSearchHandler {
   fn index_count(&self) -> u64 {
     self.index_manager.num_docs()
   }
+
+  fn index_status(&self) -> IndexStatus {
+    self.index_manager.status()
+  }
+
+  fn rebuild_index(&self) -> FlowyResult<()> {
+    self.index_manager.rebuild()
+  }
+
+  fn subscribe_index_changed(&self) -> broadcast::Receiver<()> {
+    self.index_manager.subscribe_index_changed()
+  }
 }
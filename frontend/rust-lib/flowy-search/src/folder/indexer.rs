@@ -1,117 +1,108 @@
 use std::{
   any::Any,
-  collections::HashMap,
-  fs,
-  ops::Deref,
+  collections::{HashMap, HashSet},
+  ops::Bound,
   path::Path,
-  sync::{Arc, Mutex, MutexGuard, Weak},
+  sync::{Arc, Weak},
 };
 
 use crate::{
   entities::{ResultIconTypePB, SearchFilterPB, SearchResultPB},
   folder::schema::{
-    FolderSchema, FOLDER_ICON_FIELD_NAME, FOLDER_ICON_TY_FIELD_NAME, FOLDER_ID_FIELD_NAME,
+    FolderSchema, FOLDER_ANCESTOR_IDS_FIELD_NAME, FOLDER_CREATED_AT_FIELD_NAME,
+    FOLDER_CREATED_BY_FIELD_NAME, FOLDER_ICON_FIELD_NAME, FOLDER_ICON_TY_FIELD_NAME,
+    FOLDER_ID_FIELD_NAME, FOLDER_LAST_EDITED_AT_FIELD_NAME, FOLDER_LAST_EDITED_BY_FIELD_NAME,
+    FOLDER_LAYOUT_FIELD_NAME, FOLDER_SCHEMA_VERSION, FOLDER_TAGS_FIELD_NAME,
     FOLDER_TITLE_FIELD_NAME, FOLDER_WORKSPACE_ID_FIELD_NAME,
   },
 };
 use collab::core::collab::{IndexContent, IndexContentReceiver};
 use collab_folder::{folder_diff::FolderViewChange, View, ViewIcon, ViewIndexContent, ViewLayout};
 use flowy_error::{FlowyError, FlowyResult};
-use flowy_search_pub::entities::{FolderIndexManager, IndexManager, IndexableData};
+use flowy_search_pub::entities::{
+  FolderIndexManager, IndexManager, IndexableData, IndexableViewMeta,
+};
 use flowy_user::services::authenticate_user::AuthenticateUser;
 
-use strsim::levenshtein;
+use tokio::sync::broadcast;
+
 use tantivy::{
-  collector::TopDocs, directory::MmapDirectory, doc, query::QueryParser, schema::Field, Document,
-  Index, IndexReader, IndexWriter, TantivyDocument, Term,
+  collector::{Count, TopDocs},
+  doc,
+  query::{BooleanQuery, Occur, Query, QueryParser, RangeQuery, TermQuery},
+  schema::{Field, IndexRecordOption, Value},
+  Document, TantivyDocument, Term,
 };
 
 use super::entities::FolderIndexData;
+use crate::entities::SearchDateRangePB;
+use crate::services::index_store::{IndexStatus, IndexStore, IndexWriterBudget, OpenedIndex};
+use crate::services::tokenizer::{contains_cjk, with_cjk_char_queries};
 
 #[derive(Clone)]
 pub struct FolderIndexManagerImpl {
-  folder_schema: Option<FolderSchema>,
-  index: Option<Index>,
-  index_reader: Option<IndexReader>,
-  index_writer: Option<Arc<Mutex<IndexWriter>>>,
+  store: Arc<IndexStore>,
 }
 
 const FOLDER_INDEX_DIR: &str = "folder_index";
 
+#[derive(Clone, Copy)]
+struct FolderSchemaFields {
+  id: Field,
+  title: Field,
+  icon: Field,
+  icon_ty: Field,
+  workspace_id: Field,
+  tags: Field,
+  layout: Field,
+  created_at: Field,
+  last_edited_at: Field,
+  created_by: Field,
+  last_edited_by: Field,
+  ancestor_ids: Field,
+}
+
+/// The values of a view that are stored in the index but are not part of the index content of
+/// the folder collab.
+#[derive(Default)]
+struct IndexedViewValues {
+  tags: Vec<String>,
+  meta: Option<IndexableViewMeta>,
+  ancestor_ids: Vec<String>,
+}
+
 impl FolderIndexManagerImpl {
   pub fn new(auth_user: Option<Weak<AuthenticateUser>>) -> Self {
-    let auth_user = match auth_user {
-      Some(auth_user) => auth_user,
-      None => {
-        return FolderIndexManagerImpl::empty();
-      },
-    };
-
     // AuthenticateUser is required to get the index path
-    let authenticate_user = auth_user.upgrade();
+    let auth_user = auth_user.and_then(|auth_user| auth_user.upgrade());
+    if auth_user.is_none() {
+      tracing::error!("FolderIndexManager: AuthenticateUser is not available");
+    }
 
     // Storage path is the users data path with an index directory
-    // Eg. /usr/flowy-data/indexes
-    let storage_path = match authenticate_user {
-      Some(auth_user) => auth_user.get_index_path(),
-      None => {
-        tracing::error!("FolderIndexManager: AuthenticateUser is not available");
-        return FolderIndexManagerImpl::empty();
-      },
-    };
-
-    // We check if the `folder_index` directory exists, if not we create it
-    let index_path = storage_path.join(Path::new(FOLDER_INDEX_DIR));
-    if !index_path.exists() {
-      let res = fs::create_dir_all(&index_path);
-      if let Err(e) = res {
-        tracing::error!(
-          "FolderIndexManager failed to create index directory: {:?}",
-          e
-        );
-        return FolderIndexManagerImpl::empty();
-      }
-    }
+    // Eg. /usr/flowy-data/indexes/folder_index
+    let index_path = auth_user
+      .as_ref()
+      .map(|auth_user| auth_user.get_index_path().join(Path::new(FOLDER_INDEX_DIR)));
+    let language_code = auth_user
+      .map(|auth_user| auth_user.get_search_language_code())
+      .unwrap_or_default();
 
     // The folder schema is used to define the fields of the index along
-    // with how they are stored and if the field is indexed
-    let folder_schema = FolderSchema::new();
-
-    // We open the existing or newly created folder_index directory
-    // This is required by the Tantivy Index, as it will use it to store
-    // and read index data
-    let index = match MmapDirectory::open(index_path) {
-      // We open or create an index that takes the directory r/w and the schema.
-      Ok(dir) => match Index::open_or_create(dir, folder_schema.schema.clone()) {
-        Ok(index) => index,
-        Err(e) => {
-          tracing::error!("FolderIndexManager failed to open index: {:?}", e);
-          return FolderIndexManagerImpl::empty();
-        },
-      },
-      Err(e) => {
-        tracing::error!("FolderIndexManager failed to open index directory: {:?}", e);
-        return FolderIndexManagerImpl::empty();
-      },
-    };
-
-    // We only need one IndexReader per index
-    let index_reader = index.reader();
-    let index_writer = index.writer(50_000_000);
-
-    let (index_reader, index_writer) = match (index_reader, index_writer) {
-      (Ok(reader), Ok(writer)) => (reader, writer),
-      _ => {
-        tracing::error!("FolderIndexManager failed to instantiate index writer and/or reader");
-        return FolderIndexManagerImpl::empty();
+    // with how they are stored and if the field is indexed. The index is
+    // recreated if it was created with another schema or language.
+    let store = IndexStore::open(
+      "FolderIndexManager",
+      FolderSchema::new().schema,
+      index_path,
+      FOLDER_SCHEMA_VERSION,
+      language_code,
+      IndexWriterBudget::Auto {
+        memory_budget: 50_000_000,
       },
-    };
-
+    );
     Self {
-      folder_schema: Some(folder_schema),
-      index: Some(index),
-      index_reader: Some(index_reader),
-      index_writer: Some(Arc::new(Mutex::new(index_writer))),
+      store: Arc::new(store),
     }
   }
 
@@ -120,69 +111,193 @@ impl FolderIndexManagerImpl {
       return Ok(());
     }
 
-    let mut index_writer = self.get_index_writer()?;
-    let folder_schema = self.get_folder_schema()?;
-
-    let id_field = folder_schema.schema.get_field(FOLDER_ID_FIELD_NAME)?;
-    let title_field = folder_schema.schema.get_field(FOLDER_TITLE_FIELD_NAME)?;
-    let icon_field = folder_schema.schema.get_field(FOLDER_ICON_FIELD_NAME)?;
-    let icon_ty_field = folder_schema.schema.get_field(FOLDER_ICON_TY_FIELD_NAME)?;
-    let workspace_id_field = folder_schema
-      .schema
-      .get_field(FOLDER_WORKSPACE_ID_FIELD_NAME)?;
+    let fields = self.get_schema_fields()?;
+    let index = self.store.opened()?;
+    let mut index_writer = index.writer()?;
 
     for data in indexes {
-      let (icon, icon_ty) = self.extract_icon(data.icon, data.layout);
-
-      let _ = index_writer.add_document(doc![
-        id_field => data.id.clone(),
-        title_field => data.data.clone(),
-        icon_field => icon.unwrap_or_default(),
-        icon_ty_field => icon_ty,
-        workspace_id_field => data.workspace_id.clone(),
-      ]);
+      let _ = index_writer.add_document(self.folder_document(&fields, data));
     }
 
-    index_writer.commit()?;
+    index_writer.commit()
+  }
 
-    Ok(())
+  /// Replaces the indexed views with the given views in one commit.
+  fn replace_all(&self, indexes: Vec<IndexableData>) -> Result<(), FlowyError> {
+    if indexes.is_empty() {
+      return Ok(());
+    }
+
+    let fields = self.get_schema_fields()?;
+    let index = self.store.opened()?;
+    let mut index_writer = index.writer()?;
+    for data in indexes {
+      index_writer.delete_term(Term::from_field_text(fields.id, &data.id));
+      let _ = index_writer.add_document(self.folder_document(&fields, data));
+    }
+    index_writer.commit()
+  }
+
+  /// Switches to the index of the language of the opened workspace.
+  pub fn set_language_code(&self, language_code: &str) {
+    self.store.set_language_code(language_code);
   }
 
   pub fn num_docs(&self) -> u64 {
-    self
-      .index_reader
-      .clone()
-      .map(|reader| reader.searcher().num_docs())
-      .unwrap_or(0)
+    self.store.num_docs()
   }
 
-  fn empty() -> Self {
-    Self {
-      folder_schema: None,
-      index: None,
-      index_reader: None,
-      index_writer: None,
+  pub fn status(&self) -> IndexStatus {
+    self.store.status()
+  }
+
+  /// Notifies the receiver whenever the indexed views change.
+  pub fn subscribe_index_changed(&self) -> broadcast::Receiver<()> {
+    self.store.subscribe_changes()
+  }
+
+  /// Removes all the indexed views and recreates the index files. The views have to be indexed
+  /// again with [FolderIndexManager::index_all_views].
+  pub fn rebuild(&self) -> FlowyResult<()> {
+    self.store.rebuild()
+  }
+
+  fn folder_document(&self, fields: &FolderSchemaFields, data: IndexableData) -> TantivyDocument {
+    let (icon, icon_ty) = self.extract_icon(data.icon, data.layout.clone());
+    let layout: i64 = data.layout.into();
+
+    let mut document = doc![
+      fields.id => data.id,
+      fields.title => data.data,
+      fields.icon => icon.unwrap_or_default(),
+      fields.icon_ty => icon_ty,
+      fields.workspace_id => data.workspace_id,
+      fields.layout => layout,
+    ];
+    for tag in data.tags.unwrap_or_default() {
+      document.add_text(fields.tags, tag);
+    }
+    // The views without metadata are excluded by the date and author filters
+    if let Some(meta) = data.meta {
+      document.add_i64(fields.created_at, meta.created_at);
+      document.add_i64(fields.last_edited_at, meta.last_edited_time);
+      if let Some(created_by) = meta.created_by {
+        document.add_i64(fields.created_by, created_by);
+      }
+      if let Some(last_edited_by) = meta.last_edited_by {
+        document.add_i64(fields.last_edited_by, last_edited_by);
+      }
     }
+    for ancestor_id in data.ancestor_ids.unwrap_or_default() {
+      document.add_text(fields.ancestor_ids, ancestor_id);
+    }
+    document
   }
 
-  fn get_index_writer(&self) -> FlowyResult<MutexGuard<IndexWriter>> {
-    match &self.index_writer {
-      Some(index_writer) => match index_writer.deref().lock() {
-        Ok(writer) => Ok(writer),
-        Err(e) => {
-          tracing::error!("FolderIndexManager failed to lock index writer: {:?}", e);
-          Err(FlowyError::folder_index_manager_unavailable())
-        },
-      },
-      None => Err(FlowyError::folder_index_manager_unavailable()),
+  /// Returns the values of the view that are stored in the index.
+  fn get_indexed_values(
+    &self,
+    index: &OpenedIndex,
+    fields: &FolderSchemaFields,
+    id: &str,
+  ) -> IndexedViewValues {
+    let searcher = index.reader.searcher();
+    let query = TermQuery::new(
+      Term::from_field_text(fields.id, id),
+      IndexRecordOption::Basic,
+    );
+    let Ok(top_docs) = searcher.search(&query, &TopDocs::with_limit(1)) else {
+      return IndexedViewValues::default();
+    };
+    let Some(doc) = top_docs
+      .into_iter()
+      .find_map(|(_, doc_address)| searcher.doc::<TantivyDocument>(doc_address).ok())
+    else {
+      return IndexedViewValues::default();
+    };
+
+    let get_texts = |field: Field| {
+      doc
+        .get_all(field)
+        .filter_map(|value| value.as_str().map(|text| text.to_string()))
+        .collect::<Vec<_>>()
+    };
+    let get_i64 = |field: Field| doc.get_first(field).and_then(|value| value.as_i64());
+    let meta = get_i64(fields.created_at).map(|created_at| IndexableViewMeta {
+      created_at,
+      last_edited_time: get_i64(fields.last_edited_at).unwrap_or_default(),
+      created_by: get_i64(fields.created_by),
+      last_edited_by: get_i64(fields.last_edited_by),
+    });
+
+    IndexedViewValues {
+      tags: get_texts(fields.tags),
+      meta,
+      ancestor_ids: get_texts(fields.ancestor_ids),
+    }
+  }
+
+  /// Replaces the index of the view. The values that are unknown are taken from the current index.
+  fn upsert_index(&self, mut data: IndexableData) -> Result<(), FlowyError> {
+    let fields = self.get_schema_fields()?;
+    let index = self.store.opened()?;
+    // The writer is locked before reading the indexed values, so a concurrent update can't be
+    // overwritten with stale values
+    let mut index_writer = index.writer()?;
+
+    if data.tags.is_none() || data.meta.is_none() || data.ancestor_ids.is_none() {
+      let indexed = self.get_indexed_values(&index, &fields, &data.id);
+      data.tags = data.tags.or(Some(indexed.tags));
+      data.meta = data.meta.or(indexed.meta);
+      data.ancestor_ids = data.ancestor_ids.or(Some(indexed.ancestor_ids));
     }
+
+    // Remove old index
+    let delete_term = Term::from_field_text(fields.id, &data.id);
+    index_writer.delete_term(delete_term);
+
+    // Add new index
+    let _ = index_writer.add_document(self.folder_document(&fields, data));
+
+    // The reader is reloaded by the commit, so the next update reads the values of this update
+    index_writer.commit()
   }
 
-  fn get_folder_schema(&self) -> FlowyResult<FolderSchema> {
-    match &self.folder_schema {
-      Some(folder_schema) => Ok(folder_schema.clone()),
-      None => Err(FlowyError::folder_index_manager_unavailable()),
+  /// Indexes the view with its ancestors. If the ancestors changed, i.e. the view was moved, its
+  /// descendants are re-indexed with their new ancestors too.
+  fn index_view_with_ancestors(
+    &self,
+    view: Arc<View>,
+    views: &HashMap<String, Arc<View>>,
+    workspace_id: &str,
+  ) -> Result<(), FlowyError> {
+    let fields = self.get_schema_fields()?;
+    let ancestor_ids = view_ancestor_ids(&view.id, views);
+    let moved = self
+      .get_indexed_values(&self.store.opened()?, &fields, &view.id)
+      .ancestor_ids
+      != ancestor_ids;
+    self.upsert_index(
+      IndexableData::from_view(view.clone(), workspace_id.to_string())
+        .with_ancestor_ids(ancestor_ids),
+    )?;
+
+    if moved {
+      let descendants = view_descendants(&view.id, views)
+        .into_iter()
+        .map(|descendant| {
+          let ancestor_ids = view_ancestor_ids(&descendant.id, views);
+          IndexableData::from_view(descendant, workspace_id.to_string())
+            .with_ancestor_ids(ancestor_ids)
+        })
+        .collect::<Vec<_>>();
+      if !descendants.is_empty() {
+        let ids = descendants.iter().map(|data| data.id.clone()).collect();
+        self.remove_indices(ids)?;
+        self.index_all(descendants)?;
+      }
     }
+    Ok(())
   }
 
   fn extract_icon(
@@ -210,33 +325,60 @@ impl FolderIndexManagerImpl {
   pub fn search(
     &self,
     query: String,
-    _filter: Option<SearchFilterPB>,
+    filter: Option<SearchFilterPB>,
+    limit: usize,
   ) -> Result<Vec<SearchResultPB>, FlowyError> {
-    let folder_schema = self.get_folder_schema()?;
-
-    let (index, index_reader) = self
-      .index
-      .as_ref()
-      .zip(self.index_reader.as_ref())
-      .ok_or_else(FlowyError::folder_index_manager_unavailable)?;
+    if limit == 0 {
+      return Ok(vec![]);
+    }
+    let schema = self.store.schema();
+    let index = self.store.opened()?;
 
-    let title_field = folder_schema.schema.get_field(FOLDER_TITLE_FIELD_NAME)?;
+    let title_field = schema.get_field(FOLDER_TITLE_FIELD_NAME)?;
+    let tags_field = schema.get_field(FOLDER_TAGS_FIELD_NAME)?;
 
     let length = query.len();
     let distance: u8 = if length >= 2 { 2 } else { 1 };
 
-    let mut query_parser = QueryParser::for_index(&index.clone(), vec![title_field]);
-    query_parser.set_field_fuzzy(title_field, true, distance, true);
-    let built_query = query_parser.parse_query(&query.clone())?;
+    // The exact matches are scored with BM25. The fuzzy matches of the title only add a constant
+    // score, so a typo still finds the view but ranks it below the exact matches.
+    let exact_query =
+      QueryParser::for_index(&index.index, vec![title_field, tags_field]).parse_query(&query)?;
+    // A CJK query is split into bigrams, and most of the bigrams are within the fuzzy distance of
+    // each other, so the fuzzy search would match almost any CJK title
+    let text_query: Box<dyn Query> = if contains_cjk(&query) {
+      with_cjk_char_queries(exact_query, &query, &[title_field, tags_field])
+    } else {
+      let mut fuzzy_query_parser = QueryParser::for_index(&index.index, vec![title_field]);
+      fuzzy_query_parser.set_field_fuzzy(title_field, true, distance, true);
+      let fuzzy_query = fuzzy_query_parser.parse_query(&query)?;
+      Box::new(BooleanQuery::new(vec![
+        (Occur::Should, exact_query),
+        (Occur::Should, fuzzy_query),
+      ]))
+    };
+    let built_query: Box<dyn Query> = match filter {
+      Some(filter) if filter.has_view_conditions() => {
+        let mut clauses = vec![(Occur::Must, text_query)];
+        clauses.extend(
+          self
+            .filter_queries(&filter)?
+            .into_iter()
+            .map(|query| (Occur::Must, query)),
+        );
+        Box::new(BooleanQuery::new(clauses))
+      },
+      _ => text_query,
+    };
 
-    let searcher = index_reader.searcher();
+    let searcher = index.reader.searcher();
     let mut search_results: Vec<SearchResultPB> = vec![];
-    let top_docs = searcher.search(&built_query, &TopDocs::with_limit(10))?;
-    for (_score, doc_address) in top_docs {
+    let top_docs = searcher.search(&built_query, &TopDocs::with_limit(limit))?;
+    for (score, doc_address) in top_docs {
       let retrieved_doc: TantivyDocument = searcher.doc(doc_address)?;
 
       let mut content = HashMap::new();
-      let named_doc = retrieved_doc.to_named_doc(&folder_schema.schema);
+      let named_doc = retrieved_doc.to_named_doc(schema);
       for (k, v) in named_doc.0 {
         content.insert(k, v[0].clone());
       }
@@ -247,50 +389,121 @@ impl FolderIndexManagerImpl {
 
       let s = serde_json::to_string(&content)?;
       let result: SearchResultPB = serde_json::from_str::<FolderIndexData>(&s)?.into();
-      let score = self.score_result(&query, &result.data);
-      search_results.push(result.with_score(score));
+      search_results.push(result.with_score(score as f64));
     }
 
     Ok(search_results)
   }
 
-  // Score result by distance
-  fn score_result(&self, query: &str, term: &str) -> f64 {
-    let distance = levenshtein(query, term) as f64;
-    1.0 / (distance + 1.0)
+  /// Returns the queries that the views must match to satisfy the conditions of the filter.
+  fn filter_queries(&self, filter: &SearchFilterPB) -> Result<Vec<Box<dyn Query>>, FlowyError> {
+    let fields = self.get_schema_fields()?;
+    let mut queries: Vec<Box<dyn Query>> = vec![];
+
+    if !filter.layouts.is_empty() {
+      let layout_queries = filter
+        .layouts
+        .iter()
+        .map(|filter| {
+          let layout: i64 = ViewLayout::from(filter.layout.clone()).into();
+          let query: Box<dyn Query> = Box::new(TermQuery::new(
+            Term::from_field_i64(fields.layout, layout),
+            IndexRecordOption::Basic,
+          ));
+          (Occur::Should, query)
+        })
+        .collect();
+      queries.push(Box::new(BooleanQuery::new(layout_queries)));
+    }
+    if let Some(range) = &filter.created_at {
+      queries.push(date_range_query(FOLDER_CREATED_AT_FIELD_NAME, range));
+    }
+    if let Some(range) = &filter.last_edited_at {
+      queries.push(date_range_query(FOLDER_LAST_EDITED_AT_FIELD_NAME, range));
+    }
+    if let Some(created_by) = filter.created_by {
+      queries.push(Box::new(TermQuery::new(
+        Term::from_field_i64(fields.created_by, created_by),
+        IndexRecordOption::Basic,
+      )));
+    }
+    if let Some(last_edited_by) = filter.last_edited_by {
+      queries.push(Box::new(TermQuery::new(
+        Term::from_field_i64(fields.last_edited_by, last_edited_by),
+        IndexRecordOption::Basic,
+      )));
+    }
+    if let Some(ancestor_view_id) = &filter.ancestor_view_id {
+      queries.push(Box::new(TermQuery::new(
+        Term::from_field_text(fields.ancestor_ids, ancestor_view_id),
+        IndexRecordOption::Basic,
+      )));
+    }
+    Ok(queries)
   }
 
-  fn get_schema_fields(&self) -> Result<(Field, Field, Field, Field, Field), FlowyError> {
-    let folder_schema = match self.folder_schema.clone() {
-      Some(schema) => schema,
-      _ => return Err(FlowyError::folder_index_manager_unavailable()),
-    };
+  fn get_schema_fields(&self) -> Result<FolderSchemaFields, FlowyError> {
+    let schema = self.store.schema();
+    Ok(FolderSchemaFields {
+      id: schema.get_field(FOLDER_ID_FIELD_NAME)?,
+      title: schema.get_field(FOLDER_TITLE_FIELD_NAME)?,
+      icon: schema.get_field(FOLDER_ICON_FIELD_NAME)?,
+      icon_ty: schema.get_field(FOLDER_ICON_TY_FIELD_NAME)?,
+      workspace_id: schema.get_field(FOLDER_WORKSPACE_ID_FIELD_NAME)?,
+      tags: schema.get_field(FOLDER_TAGS_FIELD_NAME)?,
+      layout: schema.get_field(FOLDER_LAYOUT_FIELD_NAME)?,
+      created_at: schema.get_field(FOLDER_CREATED_AT_FIELD_NAME)?,
+      last_edited_at: schema.get_field(FOLDER_LAST_EDITED_AT_FIELD_NAME)?,
+      created_by: schema.get_field(FOLDER_CREATED_BY_FIELD_NAME)?,
+      last_edited_by: schema.get_field(FOLDER_LAST_EDITED_BY_FIELD_NAME)?,
+      ancestor_ids: schema.get_field(FOLDER_ANCESTOR_IDS_FIELD_NAME)?,
+    })
+  }
+}
 
-    let id_field = folder_schema.schema.get_field(FOLDER_ID_FIELD_NAME)?;
-    let title_field = folder_schema.schema.get_field(FOLDER_TITLE_FIELD_NAME)?;
-    let icon_field = folder_schema.schema.get_field(FOLDER_ICON_FIELD_NAME)?;
-    let icon_ty_field = folder_schema.schema.get_field(FOLDER_ICON_TY_FIELD_NAME)?;
-    let workspace_id_field = folder_schema
-      .schema
-      .get_field(FOLDER_WORKSPACE_ID_FIELD_NAME)?;
+fn date_range_query(field_name: &str, range: &SearchDateRangePB) -> Box<dyn Query> {
+  let bound = |value: Option<i64>| value.map_or(Bound::Unbounded, Bound::Included);
+  Box::new(RangeQuery::new_i64_bounds(
+    field_name.to_string(),
+    bound(range.start),
+    bound(range.end),
+  ))
+}
+
+/// Returns the ids of the ancestors of the view, from the parent to the root.
+fn view_ancestor_ids(view_id: &str, views: &HashMap<String, Arc<View>>) -> Vec<String> {
+  let mut ancestor_ids = vec![];
+  let mut parent_view_id = views.get(view_id).map(|view| view.parent_view_id.as_str());
+  while let Some(id) = parent_view_id.filter(|id| !id.is_empty()) {
+    // The parents might form a cycle in a corrupted folder
+    if id == view_id || ancestor_ids.iter().any(|ancestor_id| ancestor_id == id) {
+      break;
+    }
+    ancestor_ids.push(id.to_string());
+    parent_view_id = views.get(id).map(|view| view.parent_view_id.as_str());
+  }
+  ancestor_ids
+}
 
-    Ok((
-      id_field,
-      title_field,
-      icon_field,
-      icon_ty_field,
-      workspace_id_field,
-    ))
+/// Returns all the views under the view.
+fn view_descendants(view_id: &str, views: &HashMap<String, Arc<View>>) -> Vec<Arc<View>> {
+  let mut descendants = vec![];
+  let mut visited = HashSet::from([view_id.to_string()]);
+  let mut parent_ids = vec![view_id.to_string()];
+  while let Some(parent_id) = parent_ids.pop() {
+    for view in views.values() {
+      if view.parent_view_id == parent_id && visited.insert(view.id.clone()) {
+        parent_ids.push(view.id.clone());
+        descendants.push(view.clone());
+      }
+    }
   }
+  descendants
 }
 
 impl IndexManager for FolderIndexManagerImpl {
   fn is_indexed(&self) -> bool {
-    self
-      .index_reader
-      .clone()
-      .map(|reader| reader.searcher().num_docs() > 0)
-      .unwrap_or(false)
+    self.store.num_docs() > 0
   }
 
   fn set_index_content_receiver(&self, mut rx: IndexContentReceiver, workspace_id: String) {
@@ -307,6 +520,9 @@ impl IndexManager for FolderIndexManagerImpl {
                 icon: view.icon,
                 layout: view.layout,
                 workspace_id: wid.clone(),
+                tags: None,
+                meta: None,
+                ancestor_ids: None,
               });
             },
             Err(err) => tracing::error!("FolderIndexManager error deserialize: {:?}", err),
@@ -319,6 +535,9 @@ impl IndexManager for FolderIndexManagerImpl {
                 icon: view.icon,
                 layout: view.layout,
                 workspace_id: wid.clone(),
+                tags: None,
+                meta: None,
+                ancestor_ids: None,
               });
             },
             Err(err) => tracing::error!("FolderIndexManager error deserialize: {:?}", err),
@@ -334,85 +553,43 @@ impl IndexManager for FolderIndexManagerImpl {
   }
 
   fn update_index(&self, data: IndexableData) -> Result<(), FlowyError> {
-    let mut index_writer = self.get_index_writer()?;
-
-    let (id_field, title_field, icon_field, icon_ty_field, workspace_id_field) =
-      self.get_schema_fields()?;
-
-    let delete_term = Term::from_field_text(id_field, &data.id.clone());
-
-    // Remove old index
-    index_writer.delete_term(delete_term);
-
-    let (icon, icon_ty) = self.extract_icon(data.icon, data.layout);
-
-    // Add new index
-    let _ = index_writer.add_document(doc![
-      id_field => data.id.clone(),
-      title_field => data.data,
-      icon_field => icon.unwrap_or_default(),
-      icon_ty_field => icon_ty,
-      workspace_id_field => data.workspace_id.clone(),
-    ]);
-
-    index_writer.commit()?;
-
-    Ok(())
+    self.upsert_index(data)
   }
 
   fn remove_indices(&self, ids: Vec<String>) -> Result<(), FlowyError> {
-    let mut index_writer = self.get_index_writer()?;
+    let id_field = self.store.schema().get_field(FOLDER_ID_FIELD_NAME)?;
+    let index = self.store.opened()?;
+    let mut index_writer = index.writer()?;
 
-    let folder_schema = self.get_folder_schema()?;
-    let id_field = folder_schema.schema.get_field(FOLDER_ID_FIELD_NAME)?;
     for id in ids {
       let delete_term = Term::from_field_text(id_field, &id);
       index_writer.delete_term(delete_term);
     }
 
-    index_writer.commit()?;
-
-    Ok(())
+    index_writer.commit()
   }
 
   fn add_index(&self, data: IndexableData) -> Result<(), FlowyError> {
-    let mut index_writer = self.get_index_writer()?;
-
-    let (id_field, title_field, icon_field, icon_ty_field, workspace_id_field) =
-      self.get_schema_fields()?;
-
-    let (icon, icon_ty) = self.extract_icon(data.icon, data.layout);
-
-    // Add new index
-    let _ = index_writer.add_document(doc![
-      id_field => data.id,
-      title_field => data.data,
-      icon_field => icon.unwrap_or_default(),
-      icon_ty_field => icon_ty,
-      workspace_id_field => data.workspace_id,
-    ]);
-
-    index_writer.commit()?;
-
-    Ok(())
+    // The view might have been indexed with its metadata already when it was created, so the
+    // index is replaced instead of adding a duplicate
+    self.upsert_index(data)
   }
 
   /// Removes all indexes that are related by workspace id. This is useful
   /// for cleaning indexes when eg. removing/leaving a workspace.
   ///
   fn remove_indices_for_workspace(&self, workspace_id: String) -> Result<(), FlowyError> {
-    let mut index_writer = self.get_index_writer()?;
-
-    let folder_schema = self.get_folder_schema()?;
-    let id_field = folder_schema
-      .schema
+    let id_field = self
+      .store
+      .schema()
       .get_field(FOLDER_WORKSPACE_ID_FIELD_NAME)?;
+    let index = self.store.opened()?;
+    let mut index_writer = index.writer()?;
+
     let delete_term = Term::from_field_text(id_field, &workspace_id);
     index_writer.delete_term(delete_term);
 
-    index_writer.commit()?;
-
-    Ok(())
+    index_writer.commit()
   }
 
   fn as_any(&self) -> &dyn Any {
@@ -422,9 +599,13 @@ impl IndexManager for FolderIndexManagerImpl {
 
 impl FolderIndexManager for FolderIndexManagerImpl {
   fn index_all_views(&self, views: Vec<Arc<View>>, workspace_id: String) {
+    let views_by_id = views_by_id(&views);
     let indexable_data = views
       .into_iter()
-      .map(|view| IndexableData::from_view(view, workspace_id.clone()))
+      .map(|view| {
+        let ancestor_ids = view_ancestor_ids(&view.id, &views_by_id);
+        IndexableData::from_view(view, workspace_id.clone()).with_ancestor_ids(ancestor_ids)
+      })
       .collect();
 
     let _ = self.index_all(indexable_data);
@@ -436,21 +617,12 @@ impl FolderIndexManager for FolderIndexManagerImpl {
     changes: Vec<FolderViewChange>,
     workspace_id: String,
   ) {
-    let mut views_iter = views.into_iter();
+    let views_by_id = views_by_id(&views);
     for change in changes {
       match change {
-        FolderViewChange::Inserted { view_id } => {
-          let view = views_iter.find(|view| view.id == view_id);
-          if let Some(view) = view {
-            let indexable_data = IndexableData::from_view(view, workspace_id.clone());
-            let _ = self.add_index(indexable_data);
-          }
-        },
-        FolderViewChange::Updated { view_id } => {
-          let view = views_iter.find(|view| view.id == view_id);
-          if let Some(view) = view {
-            let indexable_data = IndexableData::from_view(view, workspace_id.clone());
-            let _ = self.update_index(indexable_data);
+        FolderViewChange::Inserted { view_id } | FolderViewChange::Updated { view_id } => {
+          if let Some(view) = views_by_id.get(&view_id) {
+            let _ = self.index_view_with_ancestors(view.clone(), &views_by_id, &workspace_id);
           }
         },
         FolderViewChange::Deleted { view_ids } => {
@@ -459,4 +631,41 @@ impl FolderIndexManager for FolderIndexManagerImpl {
       };
     }
   }
+
+  fn get_indexed_ancestor_ids(&self, view_id: &str) -> Option<Vec<String>> {
+    let fields = self.get_schema_fields().ok()?;
+    let index = self.store.opened().ok()?;
+    let query = TermQuery::new(
+      Term::from_field_text(fields.id, view_id),
+      IndexRecordOption::Basic,
+    );
+    let count = index.reader.searcher().search(&query, &Count).ok()?;
+    if count == 0 {
+      return None;
+    }
+    Some(
+      self
+        .get_indexed_values(&index, &fields, view_id)
+        .ancestor_ids,
+    )
+  }
+
+  fn index_views(&self, views: Vec<(Arc<View>, Vec<String>)>, workspace_id: String) {
+    let indexes = views
+      .into_iter()
+      .map(|(view, ancestor_ids)| {
+        IndexableData::from_view(view, workspace_id.clone()).with_ancestor_ids(ancestor_ids)
+      })
+      .collect::<Vec<_>>();
+    if let Err(err) = self.replace_all(indexes) {
+      tracing::error!("FolderIndexManager failed to index views: {:?}", err);
+    }
+  }
+}
+
+fn views_by_id(views: &[Arc<View>]) -> HashMap<String, Arc<View>> {
+  views
+    .iter()
+    .map(|view| (view.id.clone(), view.clone()))
+    .collect()
 }
@@ -1,10 +1,29 @@
 use tantivy::schema::Schema;
 
+use crate::services::tokenizer::search_text_options;
+
 pub const FOLDER_ID_FIELD_NAME: &str = "id";
 pub const FOLDER_TITLE_FIELD_NAME: &str = "title";
 pub const FOLDER_ICON_FIELD_NAME: &str = "icon";
 pub const FOLDER_ICON_TY_FIELD_NAME: &str = "icon_ty";
 pub const FOLDER_WORKSPACE_ID_FIELD_NAME: &str = "workspace_id";
+pub const FOLDER_TAGS_FIELD_NAME: &str = "tags";
+pub const FOLDER_LAYOUT_FIELD_NAME: &str = "layout";
+pub const FOLDER_CREATED_AT_FIELD_NAME: &str = "created_at";
+pub const FOLDER_LAST_EDITED_AT_FIELD_NAME: &str = "last_edited_at";
+pub const FOLDER_CREATED_BY_FIELD_NAME: &str = "created_by";
+pub const FOLDER_LAST_EDITED_BY_FIELD_NAME: &str = "last_edited_by";
+pub const FOLDER_ANCESTOR_IDS_FIELD_NAME: &str = "ancestor_ids";
+
+/// The version of the schema. Increase it when the schema is changed, the index created with an
+/// older version is removed and rebuilt with the new schema.
+///
+/// Version history:
+/// 1: add the tags field
+/// 2: add the layout, created_at, last_edited_at, created_by, last_edited_by and ancestor_ids
+///    fast fields that are used to filter the search results
+/// 3: tokenize the title and the tags with the multilingual search tokenizer
+pub const FOLDER_SCHEMA_VERSION: u32 = 3;
 
 #[derive(Clone)]
 pub struct FolderSchema {
@@ -16,7 +35,7 @@ pub struct FolderSchema {
 /// from previously created index, causing tantivy to panic and search to stop functioning.
 ///
 /// If you need to change the schema, create a migration that removes the old index,
-/// and creates a new one with the new schema.
+/// and creates a new one with the new schema. Increasing the [FOLDER_SCHEMA_VERSION] does that.
 ///
 impl FolderSchema {
   pub fn new() -> Self {
@@ -25,10 +44,7 @@ impl FolderSchema {
       FOLDER_ID_FIELD_NAME,
       tantivy::schema::STRING | tantivy::schema::STORED,
     );
-    schema_builder.add_text_field(
-      FOLDER_TITLE_FIELD_NAME,
-      tantivy::schema::TEXT | tantivy::schema::STORED,
-    );
+    schema_builder.add_text_field(FOLDER_TITLE_FIELD_NAME, search_text_options().set_stored());
     schema_builder.add_text_field(
       FOLDER_ICON_FIELD_NAME,
       tantivy::schema::TEXT | tantivy::schema::STORED,
@@ -38,6 +54,31 @@ impl FolderSchema {
       FOLDER_WORKSPACE_ID_FIELD_NAME,
       tantivy::schema::TEXT | tantivy::schema::STORED,
     );
+    schema_builder.add_text_field(FOLDER_TAGS_FIELD_NAME, search_text_options().set_stored());
+    schema_builder.add_i64_field(
+      FOLDER_LAYOUT_FIELD_NAME,
+      tantivy::schema::INDEXED | tantivy::schema::FAST | tantivy::schema::STORED,
+    );
+    schema_builder.add_i64_field(
+      FOLDER_CREATED_AT_FIELD_NAME,
+      tantivy::schema::INDEXED | tantivy::schema::FAST | tantivy::schema::STORED,
+    );
+    schema_builder.add_i64_field(
+      FOLDER_LAST_EDITED_AT_FIELD_NAME,
+      tantivy::schema::INDEXED | tantivy::schema::FAST | tantivy::schema::STORED,
+    );
+    schema_builder.add_i64_field(
+      FOLDER_CREATED_BY_FIELD_NAME,
+      tantivy::schema::INDEXED | tantivy::schema::FAST | tantivy::schema::STORED,
+    );
+    schema_builder.add_i64_field(
+      FOLDER_LAST_EDITED_BY_FIELD_NAME,
+      tantivy::schema::INDEXED | tantivy::schema::FAST | tantivy::schema::STORED,
+    );
+    schema_builder.add_text_field(
+      FOLDER_ANCESTOR_IDS_FIELD_NAME,
+      tantivy::schema::STRING | tantivy::schema::FAST | tantivy::schema::STORED,
+    );
 
     let schema = schema_builder.build();
 
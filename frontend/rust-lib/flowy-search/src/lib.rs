@@ -1,3 +1,4 @@
+pub mod database;
 pub mod document;
 pub mod entities;
 pub mod event_handler;
@@ -0,0 +1,208 @@
+use flowy_error::{FlowyError, FlowyResult};
+use flowy_sqlite::schema::{search_history_table, search_history_table::dsl};
+use flowy_sqlite::{
+  diesel, insert_into, query_dsl::*, BoolExpressionMethods, DBConnection, ExpressionMethods,
+  Insertable, Queryable,
+};
+use lib_infra::util::timestamp;
+
+/// The max number of recent queries that are kept per workspace. The saved and the pinned
+/// searches are not counted.
+pub const SEARCH_HISTORY_LIMIT: usize = 20;
+
+/// A recent query is replaced by a longer query that starts with it within this number of
+/// seconds, so a query that is searched while it is typed is only recorded once.
+const SEARCH_HISTORY_TYPING_WINDOW: i64 = 60;
+
+/// A recent query, or a saved search if it has a name.
+#[derive(Queryable, Insertable, Debug, Clone)]
+#[diesel(table_name = search_history_table)]
+pub struct SearchHistoryTable {
+  pub id: String,
+  pub workspace_id: String,
+  pub query: String,
+  /// The encoded [SearchFilterPB](crate::entities::SearchFilterPB) of the search.
+  pub filter: Option<Vec<u8>>,
+  pub name: Option<String>,
+  pub is_pinned: bool,
+  pub created_at: i64,
+  pub last_used_at: i64,
+}
+
+impl SearchHistoryTable {
+  pub fn new(
+    workspace_id: &str,
+    query: &str,
+    filter: Option<Vec<u8>>,
+    name: Option<String>,
+  ) -> Self {
+    let now = timestamp();
+    Self {
+      id: uuid::Uuid::new_v4().to_string(),
+      workspace_id: workspace_id.to_string(),
+      query: query.to_string(),
+      filter,
+      name,
+      is_pinned: false,
+      created_at: now,
+      last_used_at: now,
+    }
+  }
+
+  fn is_recent_query(&self) -> bool {
+    self.name.is_none() && !self.is_pinned
+  }
+}
+
+/// Records a query in the recent queries of the workspace. Searching the same query again only
+/// updates when it was used, and the oldest recent queries are removed above the
+/// [SEARCH_HISTORY_LIMIT].
+pub fn record_recent_search(
+  mut conn: DBConnection,
+  workspace_id: &str,
+  query: &str,
+  filter: Option<Vec<u8>>,
+) -> FlowyResult<()> {
+  conn.immediate_transaction(|conn| {
+    let now = timestamp();
+    let rows = dsl::search_history_table
+      .filter(search_history_table::workspace_id.eq(workspace_id))
+      .order(search_history_table::last_used_at.desc())
+      .load::<SearchHistoryTable>(conn)?;
+
+    if let Some(row) = rows
+      .iter()
+      .find(|row| row.query == query && row.filter == filter)
+    {
+      diesel::update(dsl::search_history_table.find(&row.id))
+        .set(search_history_table::last_used_at.eq(now))
+        .execute(conn)?;
+      return Ok::<(), FlowyError>(());
+    }
+
+    let typed_ids = rows
+      .iter()
+      .filter(|row| {
+        row.is_recent_query()
+          && row.filter == filter
+          && now - row.last_used_at <= SEARCH_HISTORY_TYPING_WINDOW
+          && query.starts_with(&row.query)
+      })
+      .map(|row| row.id.clone())
+      .collect::<Vec<_>>();
+    let expired_ids = rows
+      .iter()
+      .filter(|row| row.is_recent_query() && !typed_ids.contains(&row.id))
+      .skip(SEARCH_HISTORY_LIMIT - 1)
+      .map(|row| row.id.clone());
+    let removed_ids = typed_ids
+      .iter()
+      .cloned()
+      .chain(expired_ids)
+      .collect::<Vec<_>>();
+    if !removed_ids.is_empty() {
+      diesel::delete(
+        dsl::search_history_table.filter(search_history_table::id.eq_any(removed_ids)),
+      )
+      .execute(conn)?;
+    }
+
+    let row = SearchHistoryTable::new(workspace_id, query, filter, None);
+    insert_into(search_history_table::table)
+      .values(&row)
+      .execute(conn)?;
+    Ok(())
+  })
+}
+
+pub fn insert_saved_search(
+  mut conn: DBConnection,
+  row: SearchHistoryTable,
+) -> FlowyResult<SearchHistoryTable> {
+  insert_into(search_history_table::table)
+    .values(&row)
+    .execute(&mut *conn)?;
+  Ok(row)
+}
+
+/// Returns the recent queries and the saved searches of the workspace, the pinned ones first and
+/// then the most recently used.
+pub fn select_search_history(
+  mut conn: DBConnection,
+  workspace_id: &str,
+) -> FlowyResult<Vec<SearchHistoryTable>> {
+  let rows = dsl::search_history_table
+    .filter(search_history_table::workspace_id.eq(workspace_id))
+    .order((
+      search_history_table::is_pinned.desc(),
+      search_history_table::last_used_at.desc(),
+    ))
+    .load::<SearchHistoryTable>(&mut *conn)?;
+  Ok(rows)
+}
+
+pub fn select_search_history_item(
+  mut conn: DBConnection,
+  workspace_id: &str,
+  id: &str,
+) -> FlowyResult<SearchHistoryTable> {
+  let row = dsl::search_history_table
+    .filter(
+      search_history_table::workspace_id
+        .eq(workspace_id)
+        .and(search_history_table::id.eq(id)),
+    )
+    .first::<SearchHistoryTable>(&mut *conn)?;
+  Ok(row)
+}
+
+pub fn update_search_history_pinned(
+  mut conn: DBConnection,
+  workspace_id: &str,
+  id: &str,
+  is_pinned: bool,
+) -> FlowyResult<()> {
+  diesel::update(
+    dsl::search_history_table.filter(
+      search_history_table::workspace_id
+        .eq(workspace_id)
+        .and(search_history_table::id.eq(id)),
+    ),
+  )
+  .set(search_history_table::is_pinned.eq(is_pinned))
+  .execute(&mut *conn)?;
+  Ok(())
+}
+
+pub fn update_search_history_last_used(
+  mut conn: DBConnection,
+  workspace_id: &str,
+  id: &str,
+) -> FlowyResult<()> {
+  diesel::update(
+    dsl::search_history_table.filter(
+      search_history_table::workspace_id
+        .eq(workspace_id)
+        .and(search_history_table::id.eq(id)),
+    ),
+  )
+  .set(search_history_table::last_used_at.eq(timestamp()))
+  .execute(&mut *conn)?;
+  Ok(())
+}
+
+pub fn delete_search_history(
+  mut conn: DBConnection,
+  workspace_id: &str,
+  ids: &[String],
+) -> FlowyResult<()> {
+  diesel::delete(
+    dsl::search_history_table.filter(
+      search_history_table::workspace_id
+        .eq(workspace_id)
+        .and(search_history_table::id.eq_any(ids)),
+    ),
+  )
+  .execute(&mut *conn)?;
+  Ok(())
+}
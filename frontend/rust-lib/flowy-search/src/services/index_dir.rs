@@ -0,0 +1,55 @@
+use std::fs;
+use std::path::Path;
+
+const SCHEMA_VERSION_FILE: &str = "schema_version";
+
+/// Create the index directory if it doesn't exist. The index that was created with another
+/// version of the schema, or with another tokenizer, is removed, so it will be rebuilt with the
+/// current schema and tokenizer.
+pub(crate) fn prepare_index_dir(
+  index_path: &Path,
+  schema_version: u32,
+  tokenizer_id: &str,
+) -> std::io::Result<()> {
+  let version_path = index_path.join(SCHEMA_VERSION_FILE);
+  let version = fs::read_to_string(&version_path).unwrap_or_default();
+  let current_version = format!("{}\n{}", schema_version, tokenizer_id);
+  if version.trim() != current_version {
+    if index_path.exists() {
+      tracing::info!(
+        "migrate index {:?} from version {:?} to {:?}",
+        index_path,
+        version.trim(),
+        current_version
+      );
+      fs::remove_dir_all(index_path)?;
+    }
+    fs::create_dir_all(index_path)?;
+    fs::write(&version_path, current_version)?;
+  }
+  Ok(())
+}
+
+/// Remove the index directory with all the files of the index, e.g. when the index is corrupted.
+pub(crate) fn remove_index_dir(index_path: &Path) -> std::io::Result<()> {
+  if index_path.exists() {
+    fs::remove_dir_all(index_path)?;
+  }
+  Ok(())
+}
+
+/// Remove the index that was created directly in the directory of the indexes of all the
+/// languages, before each language had its own index.
+pub(crate) fn remove_legacy_index_files(indexes_path: &Path) -> std::io::Result<()> {
+  if !indexes_path.join(SCHEMA_VERSION_FILE).exists() {
+    return Ok(());
+  }
+  tracing::info!("remove legacy index files in {:?}", indexes_path);
+  for entry in fs::read_dir(indexes_path)? {
+    let entry = entry?;
+    if entry.file_type()?.is_file() {
+      fs::remove_file(entry.path())?;
+    }
+  }
+  Ok(())
+}
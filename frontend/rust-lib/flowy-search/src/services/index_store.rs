@@ -0,0 +1,345 @@
+use std::ops::{Deref, DerefMut};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard, RwLock};
+use std::time::UNIX_EPOCH;
+
+use flowy_error::{FlowyError, FlowyResult};
+use lib_infra::util::timestamp;
+use tantivy::directory::MmapDirectory;
+use tantivy::schema::Schema;
+use tantivy::{Index, IndexReader, IndexWriter};
+use tokio::sync::broadcast;
+
+use super::index_dir::{prepare_index_dir, remove_index_dir, remove_legacy_index_files};
+use super::tokenizer::{register_search_tokenizer, search_tokenizer_id};
+
+/// The file that tantivy rewrites on every commit, its modification time is the last time the
+/// index was written when the index is opened.
+const INDEX_META_FILE: &str = "meta.json";
+
+/// The health of a local search index.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IndexStatus {
+  /// False if the index couldn't be opened, the searches of the index fail until it is rebuilt.
+  pub is_available: bool,
+  pub num_docs: u64,
+  /// The timestamp in seconds of the last commit of the index.
+  pub last_indexed_at: Option<i64>,
+  /// The last error of opening or writing the index, it is cleared by a successful commit.
+  pub error: Option<String>,
+}
+
+/// How many threads and how much memory the writer of an index uses.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum IndexWriterBudget {
+  /// The number of threads is chosen by tantivy.
+  Auto {
+    memory_budget: usize,
+  },
+  SingleThread {
+    memory_budget: usize,
+  },
+}
+
+struct IndexLocation {
+  path: PathBuf,
+  schema_version: u32,
+  /// The language of the workspace that is searched. Each language has its own index in `path`,
+  /// because the text is tokenized differently for each language.
+  language_code: RwLock<String>,
+}
+
+impl IndexLocation {
+  fn tokenizer_id(&self) -> String {
+    let language_code = self
+      .language_code
+      .read()
+      .map(|language_code| language_code.clone())
+      .unwrap_or_default();
+    search_tokenizer_id(&language_code)
+  }
+
+  fn index_dir(&self) -> PathBuf {
+    self.path.join(self.tokenizer_id())
+  }
+}
+
+/// Opens a local search index and keeps track of its health. Unlike failing silently, an index that
+/// can't be opened, e.g. because its files are corrupted, is reported by [IndexStore::status] and
+/// can be recreated with [IndexStore::rebuild].
+pub(crate) struct IndexStore {
+  name: &'static str,
+  schema: Schema,
+  location: Option<IndexLocation>,
+  writer_budget: IndexWriterBudget,
+  opened: RwLock<Option<Arc<OpenedIndex>>>,
+  status: Arc<Mutex<IndexStatus>>,
+  changed: broadcast::Sender<()>,
+}
+
+impl IndexStore {
+  /// Opens the index of the language in `index_path`. The index is recreated if it was created
+  /// with another schema version. If `index_path` is None the index is unavailable.
+  pub(crate) fn open(
+    name: &'static str,
+    schema: Schema,
+    index_path: Option<PathBuf>,
+    schema_version: u32,
+    language_code: String,
+    writer_budget: IndexWriterBudget,
+  ) -> Self {
+    let store = Self {
+      name,
+      schema,
+      location: index_path.map(|path| IndexLocation {
+        path,
+        schema_version,
+        language_code: RwLock::new(language_code),
+      }),
+      writer_budget,
+      opened: RwLock::new(None),
+      status: Arc::new(Mutex::new(IndexStatus::default())),
+      changed: broadcast::channel(16).0,
+    };
+    store.reopen(false);
+    store
+  }
+
+  /// Returns the opened index, or an error if the index isn't available.
+  pub(crate) fn opened(&self) -> FlowyResult<Arc<OpenedIndex>> {
+    self
+      .opened
+      .read()
+      .ok()
+      .and_then(|opened| opened.clone())
+      .ok_or_else(FlowyError::folder_index_manager_unavailable)
+  }
+
+  /// Notifies the receiver after each successful commit of the index.
+  pub(crate) fn subscribe_changes(&self) -> broadcast::Receiver<()> {
+    self.changed.subscribe()
+  }
+
+  pub(crate) fn schema(&self) -> &Schema {
+    &self.schema
+  }
+
+  pub(crate) fn num_docs(&self) -> u64 {
+    self
+      .opened()
+      .map(|index| index.reader.searcher().num_docs())
+      .unwrap_or(0)
+  }
+
+  pub(crate) fn status(&self) -> IndexStatus {
+    let mut status = self.status.lock().map(|s| s.clone()).unwrap_or_default();
+    status.is_available = self.opened().is_ok();
+    status.num_docs = self.num_docs();
+    status
+  }
+
+  /// Switches to the index of the language, e.g. when a workspace with another language is
+  /// opened. Nothing changes if the text of both languages is tokenized the same way.
+  pub(crate) fn set_language_code(&self, language_code: &str) {
+    let Some(location) = self.location.as_ref() else {
+      return;
+    };
+    let old_tokenizer_id = location.tokenizer_id();
+    if let Ok(mut current) = location.language_code.write() {
+      *current = language_code.to_string();
+    }
+    if location.tokenizer_id() != old_tokenizer_id {
+      self.reopen(false);
+    }
+  }
+
+  /// Removes all the files of the index and opens an empty index. The pending writes of the old
+  /// index are finished first.
+  pub(crate) fn rebuild(&self) -> FlowyResult<()> {
+    self.reopen(true);
+    match self.opened() {
+      Ok(_) => Ok(()),
+      Err(_) => {
+        let error = self.status().error.unwrap_or_default();
+        Err(FlowyError::folder_index_manager_unavailable().with_context(error))
+      },
+    }
+  }
+
+  fn reopen(&self, remove_files: bool) {
+    // The index is unavailable until it is reopened. The old index is taken out of the lock before
+    // waiting for its writer, because the pending writes might read the index again.
+    let old_index = self
+      .opened
+      .write()
+      .ok()
+      .and_then(|mut opened| opened.take());
+    if let Some(old_index) = old_index {
+      // The writer holds the lock of the index directory until it is dropped
+      if let Some(writer) = old_index.writer.lock().ok().and_then(|mut w| w.take()) {
+        let _ = writer.wait_merging_threads();
+      }
+    }
+
+    match self.open_index(remove_files) {
+      Ok(index) => {
+        let last_indexed_at = self.location.as_ref().and_then(|location| {
+          let modified = location
+            .index_dir()
+            .join(INDEX_META_FILE)
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .ok()?;
+          let seconds = modified.duration_since(UNIX_EPOCH).ok()?.as_secs();
+          Some(seconds as i64)
+        });
+        self.set_status(last_indexed_at, None);
+        if let Ok(mut opened) = self.opened.write() {
+          *opened = Some(Arc::new(index));
+        }
+      },
+      Err(error) => {
+        tracing::error!("{}: {}", self.name, error);
+        self.set_status(None, Some(error));
+      },
+    }
+  }
+
+  fn open_index(&self, remove_files: bool) -> Result<OpenedIndex, String> {
+    // AuthenticateUser is required to get the index path and the language of the tokenizer
+    let location = self
+      .location
+      .as_ref()
+      .ok_or_else(|| "AuthenticateUser is not available".to_string())?;
+
+    let tokenizer_id = location.tokenizer_id();
+    let index_dir = location.index_dir();
+    remove_legacy_index_files(&location.path)
+      .map_err(|e| format!("failed to remove legacy index files: {:?}", e))?;
+    if remove_files {
+      remove_index_dir(&index_dir)
+        .map_err(|e| format!("failed to remove index directory: {:?}", e))?;
+    }
+    prepare_index_dir(&index_dir, location.schema_version, &tokenizer_id)
+      .map_err(|e| format!("failed to create index directory: {:?}", e))?;
+
+    let dir = MmapDirectory::open(&index_dir)
+      .map_err(|e| format!("failed to open index directory: {:?}", e))?;
+    let index = Index::open_or_create(dir, self.schema.clone())
+      .map_err(|e| format!("failed to open index: {:?}", e))?;
+
+    // The text fields are tokenized by the tokenizer of the workspace language
+    let language_code = location
+      .language_code
+      .read()
+      .map(|language_code| language_code.clone())
+      .unwrap_or_default();
+    register_search_tokenizer(&index, &language_code);
+
+    let reader = index
+      .reader()
+      .map_err(|e| format!("failed to instantiate index reader: {:?}", e))?;
+    let writer = match self.writer_budget {
+      IndexWriterBudget::Auto { memory_budget } => index.writer(memory_budget),
+      IndexWriterBudget::SingleThread { memory_budget } => {
+        index.writer_with_num_threads(1, memory_budget)
+      },
+    }
+    .map_err(|e| format!("failed to instantiate index writer: {:?}", e))?;
+
+    Ok(OpenedIndex {
+      index,
+      reader,
+      writer: Mutex::new(Some(writer)),
+      status: self.status.clone(),
+      changed: self.changed.clone(),
+    })
+  }
+
+  fn set_status(&self, last_indexed_at: Option<i64>, error: Option<String>) {
+    if let Ok(mut status) = self.status.lock() {
+      status.last_indexed_at = last_indexed_at;
+      status.error = error;
+    }
+  }
+}
+
+/// An index that was opened by an [IndexStore].
+pub(crate) struct OpenedIndex {
+  pub(crate) index: Index,
+  pub(crate) reader: IndexReader,
+  /// The writer is taken when the index is rebuilt.
+  writer: Mutex<Option<IndexWriter>>,
+  status: Arc<Mutex<IndexStatus>>,
+  changed: broadcast::Sender<()>,
+}
+
+impl OpenedIndex {
+  /// Locks the writer of the index. Only one writer can be used at a time.
+  pub(crate) fn writer(&self) -> FlowyResult<IndexWriterGuard<'_>> {
+    let writer = self.writer.lock().map_err(|e| {
+      tracing::error!("failed to lock index writer: {:?}", e);
+      FlowyError::folder_index_manager_unavailable()
+    })?;
+    if writer.is_none() {
+      return Err(FlowyError::folder_index_manager_unavailable());
+    }
+    Ok(IndexWriterGuard {
+      index: self,
+      writer,
+    })
+  }
+}
+
+pub(crate) struct IndexWriterGuard<'a> {
+  index: &'a OpenedIndex,
+  writer: MutexGuard<'a, Option<IndexWriter>>,
+}
+
+impl IndexWriterGuard<'_> {
+  /// Commits the pending changes and reloads the reader, so the changes are visible to the next
+  /// search. The result is recorded in the [IndexStatus], and the subscribers of
+  /// [IndexStore::subscribe_changes] are notified of a successful commit.
+  pub(crate) fn commit(&mut self) -> FlowyResult<()> {
+    let result = self
+      .writer
+      .as_mut()
+      .ok_or_else(FlowyError::folder_index_manager_unavailable)
+      .and_then(|writer| Ok(writer.commit()?))
+      .and_then(|_| Ok(self.index.reader.reload()?));
+
+    if let Ok(mut status) = self.index.status.lock() {
+      match &result {
+        Ok(_) => {
+          status.last_indexed_at = Some(timestamp());
+          status.error = None;
+        },
+        Err(err) => status.error = Some(err.to_string()),
+      }
+    }
+    if result.is_ok() {
+      let _ = self.index.changed.send(());
+    }
+    result
+  }
+}
+
+impl Deref for IndexWriterGuard<'_> {
+  type Target = IndexWriter;
+
+  fn deref(&self) -> &Self::Target {
+    self
+      .writer
+      .as_ref()
+      .expect("the writer is checked when it is locked")
+  }
+}
+
+impl DerefMut for IndexWriterGuard<'_> {
+  fn deref_mut(&mut self) -> &mut Self::Target {
+    self
+      .writer
+      .as_mut()
+      .expect("the writer is checked when it is locked")
+  }
+}
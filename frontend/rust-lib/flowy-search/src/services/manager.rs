@@ -1,17 +1,84 @@
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, Once, Weak};
+use std::time::Duration;
 
+use super::history::{
+  delete_search_history, insert_saved_search, record_recent_search, select_search_history,
+  select_search_history_item, update_search_history_last_used, update_search_history_pinned,
+  SearchHistoryTable,
+};
+use super::index_store::IndexStatus;
 use super::notifier::{SearchNotifier, SearchResultChanged, SearchResultReceiverRunner};
-use crate::entities::{SearchFilterPB, SearchResultNotificationPB, SearchResultPB};
-use flowy_error::FlowyResult;
+use super::ranking::{sort_by_score, SearchRankingConfig, SearchRankingSignals};
+use crate::entities::{
+  RunSavedSearchPayloadPB, SearchFilterPB, SearchHistoryItemPB, SearchIndexRebuildProgressPB,
+  SearchIndexStatusPB, SearchResultNotificationPB, SearchResultPB,
+};
+use bytes::Bytes;
+use flowy_error::{internal_error, FlowyError, FlowyResult};
+use flowy_folder::entities::ViewPB;
+use flowy_folder::manager::FolderManager;
+use flowy_sqlite::DBConnection;
+use flowy_user::services::authenticate_user::AuthenticateUser;
 
 use lib_infra::async_trait::async_trait;
+use lib_infra::util::timestamp;
 use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::warn;
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+/// The number of results that are returned if the query has no limit.
+pub const DEFAULT_SEARCH_LIMIT: usize = 10;
+
+/// The number of the most recent views that are boosted in the ranking.
+const RANKING_RECENT_VIEW_LIMIT: usize = 20;
+
+/// The live searches are run again after the indexes didn't change for this duration, so a burst
+/// of changes only triggers one update.
+const LIVE_SEARCH_REFRESH_DELAY: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 pub enum SearchType {
   Folder,
   Document,
+  Database,
+}
+
+/// The range of the ranked results that is returned.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct SearchPage {
+  pub offset: usize,
+  pub limit: usize,
+}
+
+impl SearchPage {
+  /// Returns the page of the limit and the offset of a query, None if neither is set, i.e. the
+  /// query isn't paginated.
+  pub fn from_limit_and_offset(limit: Option<i64>, offset: Option<i64>) -> Option<Self> {
+    if limit.is_none() && offset.is_none() {
+      return None;
+    }
+    let default_page = SearchPage::default();
+    Some(SearchPage {
+      offset: offset.map_or(default_page.offset, |offset| offset.max(0) as usize),
+      limit: limit.map_or(default_page.limit, |limit| limit.max(0) as usize),
+    })
+  }
+
+  /// The number of results that each handler has to return, so the page can be filled.
+  pub fn end(&self) -> usize {
+    self.offset + self.limit
+  }
+}
+
+impl Default for SearchPage {
+  fn default() -> Self {
+    Self {
+      offset: 0,
+      limit: DEFAULT_SEARCH_LIMIT,
+    }
+  }
 }
 
 #[async_trait]
@@ -19,28 +86,87 @@ pub trait SearchHandler: Send + Sync + 'static {
   /// returns the type of search this handler is responsible for
   fn search_type(&self) -> SearchType;
 
-  /// performs a search and returns the results
+  /// performs a search and returns at most `limit` results, the best results first
   async fn perform_search(
     &self,
     query: String,
     filter: Option<SearchFilterPB>,
+    limit: usize,
   ) -> FlowyResult<Vec<SearchResultPB>>;
 
+  /// performs a search like [SearchHandler::perform_search] with the views of the workspace that
+  /// were already fetched, so a search with multiple handlers fetches the views only once
+  async fn perform_search_in_views(
+    &self,
+    query: String,
+    filter: Option<SearchFilterPB>,
+    _views: &SearchViews,
+    limit: usize,
+  ) -> FlowyResult<Vec<SearchResultPB>> {
+    self.perform_search(query, filter, limit).await
+  }
+
   /// returns the number of indexed objects
   fn index_count(&self) -> u64;
+
+  /// returns the health of the local index
+  fn index_status(&self) -> IndexStatus;
+
+  /// removes the local index and creates an empty one
+  fn rebuild_index(&self) -> FlowyResult<()>;
+
+  /// notifies the receiver whenever the local index changes
+  fn subscribe_index_changed(&self) -> broadcast::Receiver<()>;
+}
+
+/// Called with the number of indexed objects and the total number of objects to index.
+pub type IndexRebuildProgress = Arc<dyn Fn(u64, u64) + Send + Sync>;
+
+/// Fills the local indexes after they were rebuilt. The indexed objects are owned by other crates,
+/// so the views, documents and databases are indexed by the implementor.
+#[async_trait]
+pub trait SearchIndexRebuilder: Send + Sync + 'static {
+  /// Indexes all the views and contents of the current workspace, and returns the number of
+  /// indexed objects.
+  async fn index_workspace(&self, progress: IndexRebuildProgress) -> FlowyResult<u64>;
 }
 
+/// The views of the workspace by id, without the trashed and private views of other users.
+pub type SearchViews = HashMap<String, ViewPB>;
+
 /// The [SearchManager] is used to inject multiple [SearchHandler]'s
 /// to delegate a search to all relevant handlers, and stream the result
 /// to the client until the query has been fully completed.
 ///
+/// The results are ranked by blending the score of the handler with the recency, the favorites
+/// and the recent views, see [SearchRankingConfig].
+///
 pub struct SearchManager {
   pub handlers: HashMap<SearchType, Arc<dyn SearchHandler>>,
   notifier: SearchNotifier,
+  folder_manager: Weak<FolderManager>,
+  authenticate_user: Weak<AuthenticateUser>,
+  ranking_config: SearchRankingConfig,
+  /// The searches that are run again when the indexes change, by the channel of the results.
+  live_searches: Arc<Mutex<HashMap<Option<String>, LiveSearch>>>,
+  live_search_refresh: Once,
+  index_rebuilder: Option<Arc<dyn SearchIndexRebuilder>>,
+  is_rebuilding_index: Arc<AtomicBool>,
+}
+
+#[derive(Clone)]
+struct LiveSearch {
+  query: String,
+  filter: Option<SearchFilterPB>,
+  page: SearchPage,
 }
 
 impl SearchManager {
-  pub fn new(handlers: Vec<Arc<dyn SearchHandler>>) -> Self {
+  pub fn new(
+    handlers: Vec<Arc<dyn SearchHandler>>,
+    folder_manager: Weak<FolderManager>,
+    authenticate_user: Weak<AuthenticateUser>,
+  ) -> Self {
     let handlers: HashMap<SearchType, Arc<dyn SearchHandler>> = handlers
       .into_iter()
       .map(|handler| (handler.search_type(), handler))
@@ -50,41 +176,468 @@ impl SearchManager {
     let (notifier, _) = broadcast::channel(100);
     tokio::spawn(SearchResultReceiverRunner(Some(notifier.subscribe())).run());
 
-    Self { handlers, notifier }
+    Self {
+      handlers,
+      notifier,
+      folder_manager,
+      authenticate_user,
+      live_searches: Arc::new(Mutex::new(HashMap::new())),
+      live_search_refresh: Once::new(),
+      ranking_config: SearchRankingConfig::default(),
+      index_rebuilder: None,
+      is_rebuilding_index: Arc::new(AtomicBool::new(false)),
+    }
+  }
+
+  pub fn with_ranking_config(mut self, ranking_config: SearchRankingConfig) -> Self {
+    self.ranking_config = ranking_config;
+    self
+  }
+
+  pub fn with_index_rebuilder(mut self, index_rebuilder: Arc<dyn SearchIndexRebuilder>) -> Self {
+    self.index_rebuilder = Some(index_rebuilder);
+    self
   }
 
   pub fn get_handler(&self, search_type: SearchType) -> Option<&Arc<dyn SearchHandler>> {
     self.handlers.get(&search_type)
   }
 
+  /// Streams the results of each handler to the client as soon as the handler completes. If a
+  /// page is given, the results of all the handlers are merged and only the page is sent.
+  ///
+  /// A live search on the same channel is cancelled, the channel shows the results of this search.
   pub fn perform_search(
     &self,
     query: String,
     filter: Option<SearchFilterPB>,
+    page: Option<SearchPage>,
     channel: Option<String>,
   ) {
+    self.cancel_live_search(&channel);
+    if let Some(page) = page {
+      let handlers = self.handlers.values().cloned().collect::<Vec<_>>();
+      let folder_manager = self.folder_manager.clone();
+      let ranking_config = self.ranking_config.clone();
+      let notifier = self.notifier.clone();
+      tokio::spawn(async move {
+        let items = merge_search(
+          handlers,
+          folder_manager,
+          ranking_config,
+          &query,
+          filter,
+          page,
+        )
+        .await;
+        let notification = SearchResultNotificationPB {
+          items,
+          sends: 1,
+          channel,
+          query,
+        };
+        let _ = notifier.send(SearchResultChanged::SearchResultUpdate(notification));
+      });
+      return;
+    }
+
     let max: usize = self.handlers.len();
     let handlers = self.handlers.clone();
-    for (_, handler) in handlers {
-      let q = query.clone();
-      let f = filter.clone();
-      let ch = channel.clone();
-      let notifier = self.notifier.clone();
+    let folder_manager = self.folder_manager.clone();
+    let ranking_config = self.ranking_config.clone();
+    let notifier = self.notifier.clone();
+    tokio::spawn(async move {
+      let views = Arc::new(search_views(&folder_manager).await);
+      let signals = Arc::new(ranking_signals(&folder_manager, &views).await);
+      for (_, handler) in handlers {
+        let q = query.clone();
+        let f = filter.clone();
+        let ch = channel.clone();
+        let notifier = notifier.clone();
+        let ranking_config = ranking_config.clone();
+        let views = views.clone();
+        let signals = signals.clone();
 
-      tokio::spawn(async move {
-        let res = handler.perform_search(q.clone(), f).await;
+        tokio::spawn(async move {
+          let res = handler
+            .perform_search_in_views(q.clone(), f, &views, DEFAULT_SEARCH_LIMIT)
+            .await;
+
+          // The best score of the merged results isn't known while the results are streamed, the
+          // scores of all the handlers are in (0, 1), so they are compared against 1.
+          let items = ranking_config.rank_with_max_score(res.unwrap_or_default(), 1.0, &signals);
 
-        let items = res.unwrap_or_default();
+          let notification = SearchResultNotificationPB {
+            items,
+            sends: max as u64,
+            channel: ch,
+            query: q,
+          };
+
+          let _ = notifier.send(SearchResultChanged::SearchResultUpdate(notification));
+        });
+      }
+    });
+  }
+
+  /// Searches with all the handlers and returns the page of the merged and ranked results.
+  pub async fn search(
+    &self,
+    query: &str,
+    filter: Option<SearchFilterPB>,
+    page: SearchPage,
+  ) -> Vec<SearchResultPB> {
+    merge_search(
+      self.handlers.values().cloned().collect(),
+      self.folder_manager.clone(),
+      self.ranking_config.clone(),
+      query,
+      filter,
+      page,
+    )
+    .await
+  }
 
+  /// Records the query in the recent queries of the current workspace.
+  pub fn record_search(&self, query: &str, filter: Option<SearchFilterPB>) -> FlowyResult<()> {
+    if query.trim().is_empty() {
+      return Ok(());
+    }
+    let (workspace_id, conn) = self.workspace_connection()?;
+    record_recent_search(conn, &workspace_id, query.trim(), encode_filter(filter)?)
+  }
+
+  /// Returns the recent queries and the saved searches of the current workspace.
+  pub fn get_search_history(&self) -> FlowyResult<Vec<SearchHistoryItemPB>> {
+    let (workspace_id, conn) = self.workspace_connection()?;
+    let rows = select_search_history(conn, &workspace_id)?;
+    Ok(rows.into_iter().map(SearchHistoryItemPB::from).collect())
+  }
+
+  pub fn save_search(
+    &self,
+    name: String,
+    query: String,
+    filter: Option<SearchFilterPB>,
+  ) -> FlowyResult<SearchHistoryItemPB> {
+    if name.trim().is_empty() {
+      return Err(FlowyError::invalid_data().with_context("The name of the search is empty"));
+    }
+    let (workspace_id, conn) = self.workspace_connection()?;
+    let row = SearchHistoryTable::new(
+      &workspace_id,
+      &query,
+      encode_filter(filter)?,
+      Some(name.trim().to_string()),
+    );
+    Ok(insert_saved_search(conn, row)?.into())
+  }
+
+  /// A pinned search is listed first and isn't removed from the recent queries.
+  pub fn pin_search_history(&self, id: &str, is_pinned: bool) -> FlowyResult<()> {
+    let (workspace_id, conn) = self.workspace_connection()?;
+    update_search_history_pinned(conn, &workspace_id, id, is_pinned)
+  }
+
+  pub fn delete_search_history(&self, ids: Vec<String>) -> FlowyResult<()> {
+    let (workspace_id, conn) = self.workspace_connection()?;
+    delete_search_history(conn, &workspace_id, &ids)
+  }
+
+  /// Runs a recent query or a saved search again. If the search is live, it is run again whenever
+  /// the indexes change, and the results are sent to the channel of the search.
+  pub fn run_saved_search(&self, params: RunSavedSearchPayloadPB) -> FlowyResult<()> {
+    let (workspace_id, conn) = self.workspace_connection()?;
+    let item =
+      SearchHistoryItemPB::from(select_search_history_item(conn, &workspace_id, &params.id)?);
+    let (_, conn) = self.workspace_connection()?;
+    update_search_history_last_used(conn, &workspace_id, &params.id)?;
+
+    // The results of a live search are merged, so they can replace the previous results
+    let page = if params.live {
+      Some(params.page().unwrap_or_default())
+    } else {
+      params.page()
+    };
+    self.perform_search(
+      item.query.clone(),
+      item.filter.clone(),
+      page,
+      params.channel.clone(),
+    );
+    if let (true, Some(page)) = (params.live, page) {
+      self.live_search_refresh.call_once(|| {
+        for handler in self.handlers.values() {
+          subscribe_live_search_refresh(
+            handler.subscribe_index_changed(),
+            self.handlers.values().map(Arc::downgrade).collect(),
+            self.folder_manager.clone(),
+            self.ranking_config.clone(),
+            self.live_searches.clone(),
+            self.notifier.clone(),
+          );
+        }
+      });
+      if let Ok(mut live_searches) = self.live_searches.lock() {
+        live_searches.insert(
+          params.channel,
+          LiveSearch {
+            query: item.query,
+            filter: item.filter,
+            page,
+          },
+        );
+      }
+    }
+    Ok(())
+  }
+
+  /// Stops sending the updated results of the live search on the channel.
+  pub fn cancel_live_search(&self, channel: &Option<String>) {
+    if let Ok(mut live_searches) = self.live_searches.lock() {
+      live_searches.remove(channel);
+    }
+  }
+
+  fn workspace_connection(&self) -> FlowyResult<(String, DBConnection)> {
+    let authenticate_user = self
+      .authenticate_user
+      .upgrade()
+      .ok_or_else(|| FlowyError::internal().with_context("The user is not available"))?;
+    let uid = authenticate_user.user_id()?;
+    let workspace_id = authenticate_user.workspace_id()?;
+    let conn = authenticate_user.get_sqlite_connection(uid)?;
+    Ok((workspace_id, conn))
+  }
+
+  /// Returns the health of the local index of each handler.
+  pub fn index_status(&self) -> Vec<SearchIndexStatusPB> {
+    let mut items = self
+      .handlers
+      .values()
+      .map(|handler| SearchIndexStatusPB::new(handler.search_type(), handler.index_status()))
+      .collect::<Vec<_>>();
+    items.sort_by_key(|item| item.index_type.clone() as i32);
+    items
+  }
+
+  /// Removes the local indexes of all the handlers, and indexes the workspace again in the
+  /// background. The progress is sent as a [SearchIndexRebuildProgressPB] notification, the last
+  /// notification is marked as finished.
+  pub fn rebuild_index(&self) -> FlowyResult<()> {
+    let index_rebuilder = self
+      .index_rebuilder
+      .clone()
+      .ok_or_else(|| FlowyError::not_support().with_context("The index can't be rebuilt"))?;
+    if self.is_rebuilding_index.swap(true, Ordering::SeqCst) {
+      return Err(FlowyError::internal().with_context("The index is already being rebuilt"));
+    }
+
+    let handlers = self.handlers.values().cloned().collect::<Vec<_>>();
+    let notifier = self.notifier.clone();
+    let is_rebuilding_index = self.is_rebuilding_index.clone();
+    tokio::spawn(async move {
+      let send_progress = move |progress: SearchIndexRebuildProgressPB| {
+        let _ = notifier.send(SearchResultChanged::IndexRebuildProgress(progress));
+      };
+
+      let result = rebuild_indexes(handlers, index_rebuilder, send_progress.clone()).await;
+      let progress = match result {
+        Ok(indexed) => SearchIndexRebuildProgressPB {
+          indexed,
+          total: indexed,
+          is_finished: true,
+          error: None,
+        },
+        Err(err) => {
+          warn!("[Search] failed to rebuild the index: {}", err);
+          SearchIndexRebuildProgressPB {
+            is_finished: true,
+            error: Some(err.to_string()),
+            ..Default::default()
+          }
+        },
+      };
+      is_rebuilding_index.store(false, Ordering::SeqCst);
+      send_progress(progress);
+    });
+    Ok(())
+  }
+}
+
+async fn rebuild_indexes(
+  handlers: Vec<Arc<dyn SearchHandler>>,
+  index_rebuilder: Arc<dyn SearchIndexRebuilder>,
+  send_progress: impl Fn(SearchIndexRebuildProgressPB) + Send + Sync + 'static,
+) -> FlowyResult<u64> {
+  // Removing an index waits for its pending writes
+  tokio::task::spawn_blocking(move || {
+    handlers
+      .iter()
+      .try_for_each(|handler| handler.rebuild_index())
+  })
+  .await
+  .map_err(internal_error)??;
+
+  send_progress(SearchIndexRebuildProgressPB::default());
+  let progress: IndexRebuildProgress = Arc::new(move |indexed, total| {
+    send_progress(SearchIndexRebuildProgressPB {
+      indexed,
+      total,
+      is_finished: false,
+      error: None,
+    })
+  });
+  index_rebuilder.index_workspace(progress).await
+}
+
+fn encode_filter(filter: Option<SearchFilterPB>) -> FlowyResult<Option<Vec<u8>>> {
+  filter
+    .map(|filter| {
+      let bytes: Bytes = filter.try_into().map_err(internal_error)?;
+      Ok(bytes.to_vec())
+    })
+    .transpose()
+}
+
+/// Runs the live searches again when the index of a handler changes.
+fn subscribe_live_search_refresh(
+  mut rx: broadcast::Receiver<()>,
+  handlers: Vec<Weak<dyn SearchHandler>>,
+  folder_manager: Weak<FolderManager>,
+  ranking_config: SearchRankingConfig,
+  live_searches: Arc<Mutex<HashMap<Option<String>, LiveSearch>>>,
+  notifier: SearchNotifier,
+) {
+  tokio::spawn(async move {
+    loop {
+      match rx.recv().await {
+        Ok(_) | Err(RecvError::Lagged(_)) => {},
+        Err(RecvError::Closed) => break,
+      }
+      tokio::time::sleep(LIVE_SEARCH_REFRESH_DELAY).await;
+      while rx.try_recv().is_ok() {}
+
+      let searches = match live_searches.lock() {
+        Ok(live_searches) if !live_searches.is_empty() => live_searches.clone(),
+        _ => continue,
+      };
+      let Some(handlers) = handlers
+        .iter()
+        .map(|handler| handler.upgrade())
+        .collect::<Option<Vec<_>>>()
+      else {
+        break;
+      };
+      for (channel, search) in searches {
+        let items = merge_search(
+          handlers.clone(),
+          folder_manager.clone(),
+          ranking_config.clone(),
+          &search.query,
+          search.filter,
+          search.page,
+        )
+        .await;
         let notification = SearchResultNotificationPB {
           items,
-          sends: max as u64,
-          channel: ch,
-          query: q,
+          sends: 1,
+          channel,
+          query: search.query,
         };
-
         let _ = notifier.send(SearchResultChanged::SearchResultUpdate(notification));
-      });
+      }
+    }
+  });
+}
+
+async fn merge_search(
+  handlers: Vec<Arc<dyn SearchHandler>>,
+  folder_manager: Weak<FolderManager>,
+  ranking_config: SearchRankingConfig,
+  query: &str,
+  filter: Option<SearchFilterPB>,
+  page: SearchPage,
+) -> Vec<SearchResultPB> {
+  let views = search_views(&folder_manager).await;
+  // Every page is sliced from the same ranked candidates, deeper pages extend the candidates
+  let candidate_limit = ranking_config.candidate_limit.max(page.end());
+  let searches = handlers.iter().map(|handler| {
+    let filter = filter.clone();
+    let views = &views;
+    async move {
+      handler
+        .perform_search_in_views(query.to_string(), filter, views, candidate_limit)
+        .await
+        .unwrap_or_else(|err| {
+          warn!(
+            "[Search] {:?} search failed: {}",
+            handler.search_type(),
+            err
+          );
+          vec![]
+        })
+    }
+  });
+  let results = futures::future::join_all(searches).await;
+
+  // The scores are normalized across the merged results of all the handlers
+  let signals = ranking_signals(&folder_manager, &views).await;
+  let mut items = ranking_config.rank(results.into_iter().flatten().collect(), &signals);
+  sort_by_score(&mut items);
+  items
+    .into_iter()
+    .skip(page.offset)
+    .take(page.limit)
+    .collect()
+}
+
+/// Fetches the views of the workspace once per search, they are shared by the handlers and the
+/// ranking.
+async fn search_views(folder_manager: &Weak<FolderManager>) -> SearchViews {
+  let Some(folder_manager) = folder_manager.upgrade() else {
+    return SearchViews::new();
+  };
+  // Notice that `get_all_view_pb` returns Views that don't include trashed and private views
+  match folder_manager.get_all_views_pb().await {
+    Ok(views) => views
+      .into_iter()
+      .map(|view| (view.id.clone(), view))
+      .collect(),
+    Err(err) => {
+      warn!("[Search] failed to get the views: {}", err);
+      SearchViews::new()
+    },
+  }
+}
+
+async fn ranking_signals(
+  folder_manager: &Weak<FolderManager>,
+  views: &SearchViews,
+) -> SearchRankingSignals {
+  let mut signals = SearchRankingSignals {
+    now: timestamp(),
+    ..Default::default()
+  };
+  for view in views.values() {
+    if view.is_favorite {
+      signals.favorite_view_ids.insert(view.id.clone());
     }
+    signals
+      .last_edited
+      .insert(view.id.clone(), view.last_edited);
   }
+  let Some(folder_manager) = folder_manager.upgrade() else {
+    return signals;
+  };
+  // The most recent view is at the end of the list
+  signals.recent_view_ids = folder_manager
+    .get_my_recent_sections()
+    .await
+    .into_iter()
+    .rev()
+    .take(RANKING_RECENT_VIEW_LIMIT)
+    .map(|item| item.id)
+    .collect();
+  signals
 }
@@ -1,2 +1,8 @@
+pub mod history;
+pub(crate) mod index_dir;
+pub mod index_store;
 pub mod manager;
 pub mod notifier;
+pub mod ranking;
+pub mod snippet;
+pub mod tokenizer;
@@ -3,7 +3,9 @@ use flowy_notification::NotificationBuilder;
 use futures::stream::StreamExt;
 use tokio::sync::broadcast;
 
-use crate::entities::{SearchNotification, SearchResultNotificationPB};
+use crate::entities::{
+  SearchIndexRebuildProgressPB, SearchNotification, SearchResultNotificationPB,
+};
 
 const SEARCH_OBSERVABLE_SOURCE: &str = "Search";
 const SEARCH_ID: &str = "SEARCH_IDENTIFIER";
@@ -11,6 +13,7 @@ const SEARCH_ID: &str = "SEARCH_IDENTIFIER";
 #[derive(Clone)]
 pub enum SearchResultChanged {
   SearchResultUpdate(SearchResultNotificationPB),
+  IndexRebuildProgress(SearchIndexRebuildProgressPB),
 }
 
 pub type SearchNotifier = broadcast::Sender<SearchResultChanged>;
@@ -39,6 +42,15 @@ impl SearchResultReceiverRunner {
             .payload(notification)
             .send();
           },
+          SearchResultChanged::IndexRebuildProgress(progress) => {
+            send_notification(
+              SEARCH_ID,
+              SearchNotification::DidUpdateIndexRebuildProgress,
+              None,
+            )
+            .payload(progress)
+            .send();
+          },
         }
       })
       .await;
@@ -0,0 +1,109 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::entities::SearchResultPB;
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/// The weights of the signals that are blended into the score of a search result. The relevance
+/// is the score of the handler normalized to [0, 1], the other signals are in [0, 1] too.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchRankingConfig {
+  pub relevance_weight: f64,
+  pub recency_weight: f64,
+  /// The number of days after which the recency of an edited view is halved.
+  pub recency_half_life_days: f64,
+  pub favorite_weight: f64,
+  pub recent_view_weight: f64,
+  /// The number of results that each handler returns for ranking. The requested page is sliced
+  /// from the ranked candidates, so the order of the results doesn't depend on the page.
+  pub candidate_limit: usize,
+}
+
+impl Default for SearchRankingConfig {
+  fn default() -> Self {
+    Self {
+      relevance_weight: 1.0,
+      recency_weight: 0.2,
+      recency_half_life_days: 30.0,
+      favorite_weight: 0.2,
+      recent_view_weight: 0.1,
+      candidate_limit: 100,
+    }
+  }
+}
+
+/// The signals of the views that are used to rank the search results.
+#[derive(Debug, Clone, Default)]
+pub struct SearchRankingSignals {
+  /// The current timestamp in seconds.
+  pub now: i64,
+  /// The last edited timestamp in seconds of each view.
+  pub last_edited: HashMap<String, i64>,
+  pub favorite_view_ids: HashSet<String>,
+  pub recent_view_ids: HashSet<String>,
+}
+
+impl SearchRankingConfig {
+  /// Returns the score of a result of the view with the given relevance.
+  pub fn score(&self, relevance: f64, view_id: &str, signals: &SearchRankingSignals) -> f64 {
+    let recency = signals
+      .last_edited
+      .get(view_id)
+      .map(|last_edited| {
+        let days = (signals.now - last_edited).max(0) as f64 / SECONDS_PER_DAY;
+        0.5_f64.powf(days / self.recency_half_life_days.max(f64::EPSILON))
+      })
+      .unwrap_or(0.0);
+    let favorite = signals.favorite_view_ids.contains(view_id) as u8 as f64;
+    let recent_view = signals.recent_view_ids.contains(view_id) as u8 as f64;
+
+    self.relevance_weight * relevance
+      + self.recency_weight * recency
+      + self.favorite_weight * favorite
+      + self.recent_view_weight * recent_view
+  }
+
+  /// Ranks the merged results of all the handlers. The scores are divided by the best score of
+  /// the merged results, so the relevance of the results of different handlers stays comparable.
+  pub fn rank(
+    &self,
+    results: Vec<SearchResultPB>,
+    signals: &SearchRankingSignals,
+  ) -> Vec<SearchResultPB> {
+    let max_score = results
+      .iter()
+      .map(|result| result.score)
+      .fold(0.0, f64::max);
+    self.rank_with_max_score(results, max_score, signals)
+  }
+
+  /// Ranks the results with the scores divided by the given best score. It is used when the
+  /// results of a handler are ranked before the results of the other handlers are known.
+  pub fn rank_with_max_score(
+    &self,
+    mut results: Vec<SearchResultPB>,
+    max_score: f64,
+    signals: &SearchRankingSignals,
+  ) -> Vec<SearchResultPB> {
+    for result in results.iter_mut() {
+      let relevance = if max_score > 0.0 {
+        result.score / max_score
+      } else {
+        0.0
+      };
+      result.score = self.score(relevance, &result.view_id, signals);
+    }
+    sort_by_score(&mut results);
+    results
+  }
+}
+
+/// Maps the unbounded score of a tantivy hit to (0, 1), the range of the other search results.
+pub fn normalize_index_score(score: f64) -> f64 {
+  score / (score + 1.0)
+}
+
+/// Sorts the results by descending score.
+pub fn sort_by_score(results: &mut [SearchResultPB]) {
+  results.sort_by(|a, b| b.score.total_cmp(&a.score));
+}
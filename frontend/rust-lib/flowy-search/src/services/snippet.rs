@@ -0,0 +1,44 @@
+use tantivy::SnippetGenerator;
+
+/// The max number of chars of the content that is shown around the matched terms.
+pub const SNIPPET_MAX_NUM_CHARS: usize = 150;
+
+/// A fragment of the indexed content around the matched terms.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContentSnippet {
+  pub fragment: String,
+  /// The ranges of the matched terms in the fragment, `(start, end)` in UTF-16 code units. The
+  /// strings of Dart and JavaScript are indexed with UTF-16 code units, so the clients can use
+  /// the ranges without converting them.
+  pub highlights: Vec<(u32, u32)>,
+}
+
+/// Returns the snippet of the content for the terms of the query the generator was created with.
+/// The beginning of the content is returned if none of the terms is found in the content.
+pub fn content_snippet(generator: &SnippetGenerator, content: &str) -> ContentSnippet {
+  let snippet = generator.snippet(content);
+  let fragment = snippet.fragment();
+  if fragment.is_empty() {
+    return ContentSnippet {
+      fragment: content
+        .chars()
+        .take(SNIPPET_MAX_NUM_CHARS)
+        .collect::<String>(),
+      highlights: vec![],
+    };
+  }
+
+  let utf16_offset = |byte_offset: usize| fragment[..byte_offset].encode_utf16().count() as u32;
+  let highlights = snippet
+    .highlighted()
+    .iter()
+    .map(|section| {
+      let (start, end) = section.bounds();
+      (utf16_offset(start), utf16_offset(end))
+    })
+    .collect();
+  ContentSnippet {
+    fragment: fragment.to_string(),
+    highlights,
+  }
+}
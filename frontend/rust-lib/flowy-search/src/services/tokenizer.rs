@@ -0,0 +1,225 @@
+use tantivy::query::{BooleanQuery, Occur, Query, RegexQuery};
+use tantivy::schema::{Field, IndexRecordOption, TextFieldIndexing, TextOptions};
+use tantivy::tokenizer::{
+  Language, LowerCaser, RemoveLongFilter, Stemmer, TextAnalyzer, Token, TokenStream, Tokenizer,
+};
+use tantivy::Index;
+
+/// The name of the tokenizer of the text fields that are searched by the user.
+pub const SEARCH_TOKENIZER: &str = "flowy_multilingual";
+
+/// Returns the options of a text field that is tokenized with the [SEARCH_TOKENIZER].
+pub fn search_text_options() -> TextOptions {
+  TextOptions::default().set_indexing_options(
+    TextFieldIndexing::default()
+      .set_tokenizer(SEARCH_TOKENIZER)
+      .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+  )
+}
+
+/// Registers the [SEARCH_TOKENIZER] for the language of the app. It must be registered before
+/// the index is read or written.
+pub fn register_search_tokenizer(index: &Index, language_code: &str) {
+  index
+    .tokenizers()
+    .register(SEARCH_TOKENIZER, search_text_analyzer(language_code));
+}
+
+pub fn search_text_analyzer(language_code: &str) -> TextAnalyzer {
+  let builder = TextAnalyzer::builder(MultilingualTokenizer)
+    .filter(RemoveLongFilter::limit(40))
+    .filter(LowerCaser);
+  match stemmer_language(language_code) {
+    Some(language) => builder.filter(Stemmer::new(language)).build(),
+    None => builder.build(),
+  }
+}
+
+/// Identifies the analyzer that [search_text_analyzer] returns for the language. The index has to
+/// be rebuilt when it changes, because the indexed terms were produced by another analyzer.
+pub fn search_tokenizer_id(language_code: &str) -> String {
+  match stemmer_language(language_code) {
+    Some(language) => format!("{}_{:?}", SEARCH_TOKENIZER, language).to_lowercase(),
+    None => SEARCH_TOKENIZER.to_string(),
+  }
+}
+
+/// Returns the stemmer language of a language code like "en" or "pt-BR". The languages without a
+/// stemmer, e.g. Chinese, Japanese and Korean, are only segmented by the [MultilingualTokenizer].
+fn stemmer_language(language_code: &str) -> Option<Language> {
+  let language = language_code
+    .split(['-', '_'])
+    .next()
+    .unwrap_or_default()
+    .to_lowercase();
+  match language.as_str() {
+    "ar" => Some(Language::Arabic),
+    "da" => Some(Language::Danish),
+    "de" => Some(Language::German),
+    "el" => Some(Language::Greek),
+    "en" => Some(Language::English),
+    "es" => Some(Language::Spanish),
+    "fi" => Some(Language::Finnish),
+    "fr" => Some(Language::French),
+    "hu" => Some(Language::Hungarian),
+    "it" => Some(Language::Italian),
+    "nb" | "nn" | "no" => Some(Language::Norwegian),
+    "nl" => Some(Language::Dutch),
+    "pt" => Some(Language::Portuguese),
+    "ro" => Some(Language::Romanian),
+    "ru" => Some(Language::Russian),
+    "sv" => Some(Language::Swedish),
+    "ta" => Some(Language::Tamil),
+    "tr" => Some(Language::Turkish),
+    _ => None,
+  }
+}
+
+/// Returns true if the text contains Chinese, Japanese or Korean characters.
+pub fn contains_cjk(text: &str) -> bool {
+  text.chars().any(is_cjk)
+}
+
+fn is_cjk(c: char) -> bool {
+  matches!(
+    c as u32,
+    0x1100..=0x11FF // Hangul Jamo
+      | 0x3040..=0x30FF // Hiragana and Katakana
+      | 0x3100..=0x312F // Bopomofo
+      | 0x3130..=0x318F // Hangul Compatibility Jamo
+      | 0x3400..=0x4DBF // CJK Unified Ideographs Extension A
+      | 0x4E00..=0x9FFF // CJK Unified Ideographs
+      | 0xAC00..=0xD7AF // Hangul Syllables
+      | 0xF900..=0xFAFF // CJK Compatibility Ideographs
+      | 0xFF66..=0xFF9F // Halfwidth Katakana
+      | 0x20000..=0x2FA1F // CJK Unified Ideographs Extension B and later
+  )
+}
+
+/// Extends the parsed query so the single CJK characters of the query match too. A single CJK
+/// character is indexed as a unigram, but the longer CJK text is only indexed as bigrams, so each
+/// single character also matches the bigrams that start or end with it.
+pub fn with_cjk_char_queries(
+  query: Box<dyn Query>,
+  text: &str,
+  fields: &[Field],
+) -> Box<dyn Query> {
+  let char_queries = tokenize(text)
+    .into_iter()
+    .filter_map(|token| {
+      let mut chars = token.text.chars();
+      match (chars.next(), chars.next()) {
+        (Some(c), None) if is_cjk(c) => Some(c),
+        _ => None,
+      }
+    })
+    .map(|c| {
+      // The CJK characters aren't special characters of the regex
+      let pattern = format!("{c}.?|.{c}");
+      let field_queries = fields
+        .iter()
+        .filter_map(|field| RegexQuery::from_pattern(&pattern, *field).ok())
+        .map(|query| (Occur::Should, Box::new(query) as Box<dyn Query>))
+        .collect::<Vec<_>>();
+      (
+        Occur::Must,
+        Box::new(BooleanQuery::new(field_queries)) as Box<dyn Query>,
+      )
+    })
+    .collect::<Vec<_>>();
+  if char_queries.is_empty() {
+    return query;
+  }
+  Box::new(BooleanQuery::new(vec![
+    (Occur::Should, query),
+    (Occur::Should, Box::new(BooleanQuery::new(char_queries))),
+  ]))
+}
+
+/// Splits the text into words of alphanumeric characters. The CJK text isn't separated by spaces,
+/// so it is split into overlapping bigrams instead, e.g. "搜索引擎" into "搜索", "索引" and "引擎".
+/// A query is split the same way, so any part of a CJK text that has at least two characters
+/// matches, the single characters are matched by [with_cjk_char_queries].
+#[derive(Clone, Default)]
+pub struct MultilingualTokenizer;
+
+impl Tokenizer for MultilingualTokenizer {
+  type TokenStream<'a> = MultilingualTokenStream;
+
+  fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+    MultilingualTokenStream {
+      tokens: tokenize(text),
+      index: 0,
+    }
+  }
+}
+
+pub struct MultilingualTokenStream {
+  tokens: Vec<Token>,
+  index: usize,
+}
+
+impl TokenStream for MultilingualTokenStream {
+  fn advance(&mut self) -> bool {
+    if self.index < self.tokens.len() {
+      self.index += 1;
+      true
+    } else {
+      false
+    }
+  }
+
+  fn token(&self) -> &Token {
+    &self.tokens[self.index - 1]
+  }
+
+  fn token_mut(&mut self) -> &mut Token {
+    &mut self.tokens[self.index - 1]
+  }
+}
+
+fn tokenize(text: &str) -> Vec<Token> {
+  let mut tokens = vec![];
+  let mut chars = text.char_indices().peekable();
+  while let Some(&(start, c)) = chars.peek() {
+    if is_cjk(c) {
+      let mut run = vec![];
+      while let Some(&(offset, c)) = chars.peek() {
+        if !is_cjk(c) {
+          break;
+        }
+        run.push((offset, offset + c.len_utf8()));
+        chars.next();
+      }
+      if run.len() == 1 {
+        push_token(&mut tokens, text, run[0].0, run[0].1);
+      }
+      for bigram in run.windows(2) {
+        push_token(&mut tokens, text, bigram[0].0, bigram[1].1);
+      }
+    } else if c.is_alphanumeric() {
+      let mut end = start;
+      while let Some(&(offset, c)) = chars.peek() {
+        if !c.is_alphanumeric() || is_cjk(c) {
+          break;
+        }
+        end = offset + c.len_utf8();
+        chars.next();
+      }
+      push_token(&mut tokens, text, start, end);
+    } else {
+      chars.next();
+    }
+  }
+  tokens
+}
+
+fn push_token(tokens: &mut Vec<Token>, text: &str, offset_from: usize, offset_to: usize) {
+  tokens.push(Token {
+    offset_from,
+    offset_to,
+    position: tokens.len(),
+    text: text[offset_from..offset_to].to_string(),
+    position_length: 1,
+  });
+}
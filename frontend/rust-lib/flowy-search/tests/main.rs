@@ -1,3 +1,6 @@
 // mod search;
 
+mod ranking_test;
+mod snippet_test;
 mod tantivy_test;
+mod tokenizer_test;
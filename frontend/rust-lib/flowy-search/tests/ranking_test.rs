@@ -0,0 +1,77 @@
+use flowy_search::entities::SearchResultPB;
+use flowy_search::services::ranking::{SearchRankingConfig, SearchRankingSignals};
+
+const DAY: i64 = 86_400;
+
+fn result(view_id: &str, score: f64) -> SearchResultPB {
+  SearchResultPB {
+    view_id: view_id.to_string(),
+    id: view_id.to_string(),
+    score,
+    ..Default::default()
+  }
+}
+
+#[test]
+fn rank_by_relevance_test() {
+  let config = SearchRankingConfig::default();
+  let results = config.rank(
+    vec![result("a", 2.0), result("b", 8.0), result("c", 4.0)],
+    &SearchRankingSignals::default(),
+  );
+  let ids = results
+    .iter()
+    .map(|r| r.view_id.as_str())
+    .collect::<Vec<_>>();
+  assert_eq!(ids, vec!["b", "c", "a"]);
+  // The scores are normalized by the best score
+  assert_eq!(results[0].score, 1.0);
+  assert_eq!(results[2].score, 0.25);
+}
+
+#[test]
+fn rank_with_max_score_test() {
+  let config = SearchRankingConfig::default();
+  let results = config.rank_with_max_score(
+    vec![result("a", 0.2), result("b", 0.4)],
+    1.0,
+    &SearchRankingSignals::default(),
+  );
+  // The scores are not scaled up to the best score of the given results
+  assert_eq!(results[0].view_id, "b");
+  assert_eq!(results[0].score, 0.4);
+  assert_eq!(results[1].score, 0.2);
+}
+
+#[test]
+fn rank_with_signals_test() {
+  let config = SearchRankingConfig::default();
+  let now = 1_700_000_000;
+  let signals = SearchRankingSignals {
+    now,
+    last_edited: [
+      ("a".to_string(), now - 30 * DAY),
+      ("b".to_string(), now - 60 * DAY),
+    ]
+    .into_iter()
+    .collect(),
+    favorite_view_ids: ["c".to_string()].into_iter().collect(),
+    recent_view_ids: Default::default(),
+  };
+
+  // The recency is halved after the half-life
+  let a = config.score(1.0, "a", &signals);
+  let b = config.score(1.0, "b", &signals);
+  assert!((a - (1.0 + config.recency_weight / 2.0)).abs() < 1e-9);
+  assert!((b - (1.0 + config.recency_weight / 4.0)).abs() < 1e-9);
+
+  let results = config.rank(
+    vec![result("a", 1.0), result("b", 1.0), result("c", 1.0)],
+    &signals,
+  );
+  let ids = results
+    .iter()
+    .map(|r| r.view_id.as_str())
+    .collect::<Vec<_>>();
+  assert_eq!(ids, vec!["c", "a", "b"]);
+}
@@ -0,0 +1,31 @@
+use flowy_search::services::snippet::content_snippet;
+use tantivy::query::QueryParser;
+use tantivy::schema::{Schema, STORED, TEXT};
+use tantivy::{Index, SnippetGenerator};
+
+#[test]
+fn content_snippet_highlight_test() {
+  let mut schema_builder = Schema::builder();
+  let content = schema_builder.add_text_field("content", TEXT | STORED);
+  let index = Index::create_in_ram(schema_builder.build());
+  let searcher = index.reader().unwrap().searcher();
+
+  let query = QueryParser::for_index(&index, vec![content])
+    .parse_query("orchids")
+    .unwrap();
+  let generator = SnippetGenerator::create(&searcher, &*query, content).unwrap();
+
+  let snippet = content_snippet(&generator, "Water the orchids");
+  assert_eq!(snippet.fragment, "Water the orchids");
+  assert_eq!(snippet.highlights, vec![(10, 17)]);
+
+  // The offsets are counted in UTF-16 code units
+  let snippet = content_snippet(&generator, "Crème brûlée orchids");
+  assert_eq!(snippet.fragment, "Crème brûlée orchids");
+  assert_eq!(snippet.highlights, vec![(13, 20)]);
+
+  // Returns the beginning of the content if no term matches
+  let snippet = content_snippet(&generator, "Water the roses");
+  assert_eq!(snippet.fragment, "Water the roses");
+  assert!(snippet.highlights.is_empty());
+}
@@ -0,0 +1,106 @@
+use flowy_search::services::tokenizer::{
+  contains_cjk, register_search_tokenizer, search_text_analyzer, search_text_options,
+  search_tokenizer_id, with_cjk_char_queries,
+};
+use tantivy::collector::TopDocs;
+use tantivy::query::QueryParser;
+use tantivy::schema::Schema;
+use tantivy::tokenizer::TokenStream;
+use tantivy::{doc, Index};
+
+fn tokens(language_code: &str, text: &str) -> Vec<String> {
+  let mut analyzer = search_text_analyzer(language_code);
+  let mut stream = analyzer.token_stream(text);
+  let mut tokens = vec![];
+  while stream.advance() {
+    tokens.push(stream.token().text.clone());
+  }
+  tokens
+}
+
+#[test]
+fn multilingual_tokenizer_test() {
+  // The CJK text is split into bigrams, the other words are split on whitespace
+  assert_eq!(
+    tokens("zh", "搜索引擎 AppFlowy"),
+    vec!["搜索", "索引", "引擎", "appflowy"]
+  );
+  assert_eq!(
+    tokens("ja", "東京タワー"),
+    vec!["東京", "京タ", "タワ", "ワー"]
+  );
+  assert_eq!(tokens("ko", "한국어 검색"), vec!["한국", "국어", "검색"]);
+  // A single CJK character is kept as a unigram
+  assert_eq!(tokens("zh", "猫"), vec!["猫"]);
+
+  // The European languages are stemmed
+  assert_eq!(tokens("en", "Running flowers"), vec!["run", "flower"]);
+  assert_eq!(tokens("zh", "Running flowers"), vec!["running", "flowers"]);
+
+  assert!(contains_cjk("AppFlowy 搜索"));
+  assert!(!contains_cjk("AppFlowy search"));
+}
+
+#[test]
+fn search_tokenizer_id_test() {
+  assert_eq!(search_tokenizer_id("en"), search_tokenizer_id("en-US"));
+  assert_ne!(search_tokenizer_id("en"), search_tokenizer_id("de"));
+  assert_eq!(search_tokenizer_id("zh"), search_tokenizer_id("ja"));
+}
+
+#[test]
+fn search_cjk_title_test() {
+  let mut schema_builder = Schema::builder();
+  let title = schema_builder.add_text_field("title", search_text_options().set_stored());
+  let index = Index::create_in_ram(schema_builder.build());
+  register_search_tokenizer(&index, "zh");
+
+  let mut writer = index.writer(15_000_000).unwrap();
+  writer.add_document(doc!(title => "项目管理计划")).unwrap();
+  writer.add_document(doc!(title => "读书笔记")).unwrap();
+  writer.commit().unwrap();
+
+  let searcher = index.reader().unwrap().searcher();
+  let query_parser = QueryParser::for_index(&index, vec![title]);
+  let search = |query: &str| {
+    let query = query_parser.parse_query(query).unwrap();
+    searcher
+      .search(&query, &TopDocs::with_limit(10))
+      .unwrap()
+      .len()
+  };
+  assert_eq!(search("管理"), 1);
+  assert_eq!(search("管理计划"), 1);
+  assert_eq!(search("笔记"), 1);
+  assert_eq!(search("管理笔记"), 0);
+}
+
+#[test]
+fn search_single_cjk_char_test() {
+  let mut schema_builder = Schema::builder();
+  let title = schema_builder.add_text_field("title", search_text_options().set_stored());
+  let index = Index::create_in_ram(schema_builder.build());
+  register_search_tokenizer(&index, "zh");
+
+  let mut writer = index.writer(15_000_000).unwrap();
+  writer.add_document(doc!(title => "项目管理计划")).unwrap();
+  writer.add_document(doc!(title => "猫")).unwrap();
+  writer.commit().unwrap();
+
+  let searcher = index.reader().unwrap().searcher();
+  let query_parser = QueryParser::for_index(&index, vec![title]);
+  let search = |query: &str| {
+    let (parsed_query, _) = query_parser.parse_query_lenient(query);
+    let query = with_cjk_char_queries(parsed_query, query, &[title]);
+    searcher
+      .search(&query, &TopDocs::with_limit(10))
+      .unwrap()
+      .len()
+  };
+  // A single character matches the first, the middle and the last character of a CJK text
+  assert_eq!(search("项"), 1);
+  assert_eq!(search("管"), 1);
+  assert_eq!(search("划"), 1);
+  assert_eq!(search("猫"), 1);
+  assert_eq!(search("狗"), 0);
+}
@@ -19,6 +19,9 @@ if_native! {
     pub mod af_cloud_config {
       pub use crate::native::af_cloud_config::*;
     }
+    pub mod s3_config {
+      pub use crate::native::s3_config::*;
+    }
 }
 
 if_wasm! {
@@ -1 +1,2 @@
 pub mod af_cloud_config;
+pub mod s3_config;
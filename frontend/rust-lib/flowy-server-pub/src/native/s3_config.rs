@@ -0,0 +1,127 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use flowy_error::{ErrorCode, FlowyError};
+
+pub const APPFLOWY_S3_ENDPOINT: &str = "APPFLOWY_S3_ENV_ENDPOINT";
+pub const APPFLOWY_S3_REGION: &str = "APPFLOWY_S3_ENV_REGION";
+pub const APPFLOWY_S3_BUCKET: &str = "APPFLOWY_S3_ENV_BUCKET";
+pub const APPFLOWY_S3_ACCESS_KEY_ID: &str = "APPFLOWY_S3_ENV_ACCESS_KEY_ID";
+pub const APPFLOWY_S3_SECRET_ACCESS_KEY: &str = "APPFLOWY_S3_ENV_SECRET_ACCESS_KEY";
+pub const APPFLOWY_S3_PATH_STYLE: &str = "APPFLOWY_S3_ENV_PATH_STYLE";
+
+const DEFAULT_S3_REGION: &str = "us-east-1";
+const DEFAULT_PRESIGNED_URL_EXPIRES_IN_SECS: u64 = 60 * 60;
+
+/// The bucket of an S3-compatible service (AWS S3, MinIO, Cloudflare R2, ...) that stores the
+/// files of the workspaces.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct S3Configuration {
+  /// The endpoint of the service, for example `https://s3.us-east-1.amazonaws.com` or
+  /// `http://localhost:9000`.
+  pub endpoint: String,
+  pub region: String,
+  pub bucket: String,
+  pub access_key_id: String,
+  pub secret_access_key: String,
+  /// Address the bucket as `<endpoint>/<bucket>` instead of `<bucket>.<endpoint host>`. MinIO
+  /// and most self-hosted services require it.
+  #[serde(default)]
+  pub path_style: bool,
+  #[serde(default = "default_presigned_url_expires_in_secs")]
+  pub presigned_url_expires_in_secs: u64,
+}
+
+fn default_presigned_url_expires_in_secs() -> u64 {
+  DEFAULT_PRESIGNED_URL_EXPIRES_IN_SECS
+}
+
+impl std::fmt::Debug for S3Configuration {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("S3Configuration")
+      .field("endpoint", &self.endpoint)
+      .field("region", &self.region)
+      .field("bucket", &self.bucket)
+      .field("path_style", &self.path_style)
+      .finish()
+  }
+}
+
+impl Display for S3Configuration {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_fmt(format_args!(
+      "endpoint: {}, region: {}, bucket: {}, path_style: {}",
+      self.endpoint, self.region, self.bucket, self.path_style,
+    ))
+  }
+}
+
+impl S3Configuration {
+  /// Returns an error if the endpoint isn't set, which means the S3 storage isn't used. The
+  /// other values are checked by [S3Configuration::validate] when the storage is created, so a
+  /// partial configuration is reported instead of being ignored.
+  pub fn from_env() -> Result<Self, FlowyError> {
+    let endpoint = required_env(APPFLOWY_S3_ENDPOINT)?;
+    let bucket = std::env::var(APPFLOWY_S3_BUCKET).unwrap_or_default();
+    let access_key_id = std::env::var(APPFLOWY_S3_ACCESS_KEY_ID).unwrap_or_default();
+    let secret_access_key = std::env::var(APPFLOWY_S3_SECRET_ACCESS_KEY).unwrap_or_default();
+    let region = std::env::var(APPFLOWY_S3_REGION)
+      .ok()
+      .filter(|region| !region.is_empty())
+      .unwrap_or_else(|| DEFAULT_S3_REGION.to_string());
+    let path_style = std::env::var(APPFLOWY_S3_PATH_STYLE)
+      .map(|v| v == "true" || v == "1")
+      .unwrap_or(false);
+
+    Ok(Self {
+      endpoint,
+      region,
+      bucket,
+      access_key_id,
+      secret_access_key,
+      path_style,
+      presigned_url_expires_in_secs: DEFAULT_PRESIGNED_URL_EXPIRES_IN_SECS,
+    })
+  }
+
+  pub fn validate(&self) -> Result<(), FlowyError> {
+    for (key, value) in [
+      (APPFLOWY_S3_ENDPOINT, &self.endpoint),
+      (APPFLOWY_S3_BUCKET, &self.bucket),
+      (APPFLOWY_S3_ACCESS_KEY_ID, &self.access_key_id),
+      (APPFLOWY_S3_SECRET_ACCESS_KEY, &self.secret_access_key),
+    ] {
+      if value.trim().is_empty() {
+        return Err(FlowyError::new(
+          ErrorCode::InvalidAuthConfig,
+          format!("Missing {}", key),
+        ));
+      }
+    }
+    Ok(())
+  }
+
+  /// Write the configuration to the environment variables.
+  pub fn write_env(&self) {
+    std::env::set_var(APPFLOWY_S3_ENDPOINT, &self.endpoint);
+    std::env::set_var(APPFLOWY_S3_REGION, &self.region);
+    std::env::set_var(APPFLOWY_S3_BUCKET, &self.bucket);
+    std::env::set_var(APPFLOWY_S3_ACCESS_KEY_ID, &self.access_key_id);
+    std::env::set_var(APPFLOWY_S3_SECRET_ACCESS_KEY, &self.secret_access_key);
+    std::env::set_var(
+      APPFLOWY_S3_PATH_STYLE,
+      if self.path_style { "true" } else { "false" },
+    );
+  }
+}
+
+fn required_env(key: &str) -> Result<String, FlowyError> {
+  match std::env::var(key) {
+    Ok(value) if !value.is_empty() => Ok(value),
+    _ => Err(FlowyError::new(
+      ErrorCode::InvalidAuthConfig,
+      format!("Missing {}", key),
+    )),
+  }
+}
@@ -1,5 +1,7 @@
 use crate::af_cloud::AFServer;
-use client_api::entity::ai_dto::{CompleteTextParams, CompletionType, RepeatedRelatedQuestion};
+use client_api::entity::ai_dto::{
+  CompleteTextParams, CompletionType, CustomPrompt, RepeatedRelatedQuestion,
+};
 use client_api::entity::chat_dto::{
   CreateAnswerMessageParams, CreateChatMessageParams, CreateChatParams, MessageCursor,
   RepeatedChatMessage,
@@ -174,6 +176,29 @@ where
     Ok(stream.boxed())
   }
 
+  async fn stream_complete_with_prompt(
+    &self,
+    workspace_id: &str,
+    text: &str,
+    prompt: &str,
+  ) -> Result<StreamComplete, FlowyError> {
+    let params = CompleteTextParams {
+      text: text.to_string(),
+      completion_type: None,
+      custom_prompt: Some(CustomPrompt {
+        system: prompt.to_string(),
+      }),
+    };
+    let stream = self
+      .inner
+      .try_get_client()?
+      .stream_completion_text(workspace_id, params)
+      .await
+      .map_err(FlowyError::from)?
+      .map_err(FlowyError::from);
+    Ok(stream.boxed())
+  }
+
   async fn index_file(
     &self,
     _workspace_id: &str,
@@ -1,7 +1,8 @@
 use crate::af_cloud::AFServer;
+use crate::util::{object_range_from_response, range_header_value};
 use client_api::entity::{CompleteUploadRequest, CreateUploadRequest};
 use flowy_error::{ErrorCode, FlowyError};
-use flowy_storage_pub::cloud::{ObjectIdentity, ObjectValue, StorageCloudService};
+use flowy_storage_pub::cloud::{ObjectIdentity, ObjectRange, ObjectValue, StorageCloudService};
 use flowy_storage_pub::storage::{CompletedPartRequest, CreateUploadResponse, UploadPartResponse};
 use lib_infra::async_trait::async_trait;
 
@@ -10,6 +11,9 @@ pub struct AFCloudFileStorageServiceImpl<T> {
 
   /// Only use in debug mode
   pub maximum_upload_file_size_in_bytes: Option<u64>,
+
+  /// Sends the range requests, which the [client_api::Client] doesn't support.
+  http_client: reqwest::Client,
 }
 
 impl<T> AFCloudFileStorageServiceImpl<T> {
@@ -17,6 +21,7 @@ impl<T> AFCloudFileStorageServiceImpl<T> {
     Self {
       client,
       maximum_upload_file_size_in_bytes,
+      http_client: reqwest::Client::new(),
     }
   }
 }
@@ -54,6 +59,32 @@ where
     })
   }
 
+  async fn get_object_range(
+    &self,
+    url: String,
+    offset: u64,
+    length: u64,
+  ) -> Result<ObjectRange, FlowyError> {
+    let access_token = self.client.try_get_client()?.access_token()?;
+    let resp = self
+      .http_client
+      .get(&url)
+      .bearer_auth(access_token)
+      .header(reqwest::header::RANGE, range_header_value(offset, length))
+      .send()
+      .await?;
+    let status = resp.status();
+    if !status.is_success() {
+      let message = resp.text().await.unwrap_or_default();
+      return if status == reqwest::StatusCode::NOT_FOUND {
+        Err(FlowyError::record_not_found().with_context(message))
+      } else {
+        Err(FlowyError::http().with_context(format!("{}: {}", status, message)))
+      };
+    }
+    object_range_from_response(resp, offset).await
+  }
+
   async fn get_object_url_v1(
     &self,
     workspace_id: &str,
@@ -91,6 +91,15 @@ impl ChatCloudService for DefaultChatCloudServiceImpl {
     Err(FlowyError::not_support().with_context("complete text is not supported in local server."))
   }
 
+  async fn stream_complete_with_prompt(
+    &self,
+    _workspace_id: &str,
+    _text: &str,
+    _prompt: &str,
+  ) -> Result<StreamComplete, FlowyError> {
+    Err(FlowyError::not_support().with_context("complete text is not supported in local server."))
+  }
+
   async fn index_file(
     &self,
     _workspace_id: &str,
@@ -3,6 +3,7 @@ pub use server::*;
 pub mod af_cloud;
 pub mod local_server;
 mod response;
+pub mod s3;
 mod server;
 
 mod default_impl;
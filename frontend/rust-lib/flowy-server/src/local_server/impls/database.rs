@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use collab::entity::EncodedCollab;
 use collab_database::database::default_database_data;
 use collab_database::workspace_database::default_workspace_database_data;
@@ -8,7 +10,11 @@ use flowy_database_pub::cloud::{DatabaseCloudService, DatabaseSnapshot, EncodeCo
 use flowy_error::FlowyError;
 use lib_infra::async_trait::async_trait;
 
-pub(crate) struct LocalServerDatabaseCloudServiceImpl();
+use crate::local_server::LocalServerDB;
+
+pub(crate) struct LocalServerDatabaseCloudServiceImpl {
+  pub db: Arc<dyn LocalServerDB>,
+}
 
 #[async_trait]
 impl DatabaseCloudService for LocalServerDatabaseCloudServiceImpl {
@@ -56,9 +62,38 @@ impl DatabaseCloudService for LocalServerDatabaseCloudServiceImpl {
 
   async fn get_database_collab_object_snapshots(
     &self,
-    _object_id: &str,
-    _limit: usize,
+    object_id: &str,
+    limit: usize,
   ) -> Result<Vec<DatabaseSnapshot>, FlowyError> {
-    Ok(vec![])
+    let snapshots = self
+      .db
+      .get_collab_snapshots(object_id, limit)?
+      .into_iter()
+      .map(|snapshot| DatabaseSnapshot {
+        snapshot_id: snapshot.snapshot_id,
+        database_id: snapshot.object_id,
+        data: snapshot.data,
+        created_at: snapshot.created_at,
+      })
+      .collect();
+    Ok(snapshots)
+  }
+
+  async fn get_database_collab_object_snapshot(
+    &self,
+    object_id: &str,
+    snapshot_id: &str,
+  ) -> Result<Option<DatabaseSnapshot>, FlowyError> {
+    let snapshot = self
+      .db
+      .get_collab_snapshot(snapshot_id)?
+      .filter(|snapshot| snapshot.object_id == object_id)
+      .map(|snapshot| DatabaseSnapshot {
+        snapshot_id: snapshot.snapshot_id,
+        database_id: snapshot.object_id,
+        data: snapshot.data,
+        created_at: snapshot.created_at,
+      });
+    Ok(snapshot)
   }
 }
@@ -1,9 +1,15 @@
+use std::sync::Arc;
+
 use collab::entity::EncodedCollab;
 use flowy_document_pub::cloud::*;
 use flowy_error::{ErrorCode, FlowyError};
 use lib_infra::async_trait::async_trait;
 
-pub(crate) struct LocalServerDocumentCloudServiceImpl();
+use crate::local_server::LocalServerDB;
+
+pub(crate) struct LocalServerDocumentCloudServiceImpl {
+  pub db: Arc<dyn LocalServerDB>,
+}
 
 #[async_trait]
 impl DocumentCloudService for LocalServerDocumentCloudServiceImpl {
@@ -22,11 +28,22 @@ impl DocumentCloudService for LocalServerDocumentCloudServiceImpl {
 
   async fn get_document_snapshots(
     &self,
-    _document_id: &str,
-    _limit: usize,
+    document_id: &str,
+    limit: usize,
     _workspace_id: &str,
   ) -> Result<Vec<DocumentSnapshot>, FlowyError> {
-    Ok(vec![])
+    let snapshots = self
+      .db
+      .get_collab_snapshots(document_id, limit)?
+      .into_iter()
+      .map(|snapshot| DocumentSnapshot {
+        snapshot_id: snapshot.snapshot_id,
+        document_id: snapshot.object_id,
+        data: snapshot.data,
+        created_at: snapshot.created_at,
+      })
+      .collect();
+    Ok(snapshots)
   }
 
   async fn get_document_data(
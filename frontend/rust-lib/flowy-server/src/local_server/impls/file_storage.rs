@@ -0,0 +1,254 @@
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use flowy_error::{ErrorCode, FlowyError, FlowyResult};
+use flowy_storage_pub::cloud::{ObjectIdentity, ObjectRange, ObjectValue, StorageCloudService};
+use flowy_storage_pub::storage::{CompletedPartRequest, CreateUploadResponse, UploadPartResponse};
+use lib_infra::async_trait::async_trait;
+use tokio::fs;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+use tracing::{trace, warn};
+
+use crate::local_server::LocalServerDB;
+
+/// The scheme of the urls that point to the objects stored by [LocalServerFileStorageImpl].
+const LOCAL_FILE_URL_SCHEME: &str = "appflowy-local";
+/// The directory under the user data folder that stores the objects.
+const LOCAL_FILE_DIR: &str = "files";
+/// The directory under [LOCAL_FILE_DIR] that stores the parts of the unfinished uploads.
+const LOCAL_UPLOAD_DIR: &str = ".uploads";
+
+/// Stores the objects of the local users in the user data folder, so images and media files get a
+/// stable url without a server. The objects are laid out as `<workspace_id>/<parent_dir>/<file_id>`.
+/// The file_id is the hash of the file content, so uploading the same file twice keeps one copy.
+pub(crate) struct LocalServerFileStorageImpl {
+  pub db: Arc<dyn LocalServerDB>,
+}
+
+impl LocalServerFileStorageImpl {
+  fn root_dir(&self) -> FlowyResult<PathBuf> {
+    Ok(self.db.get_user_data_dir()?.join(LOCAL_FILE_DIR))
+  }
+
+  fn object_path(&self, url: &str) -> FlowyResult<PathBuf> {
+    let components = url_components(url)
+      .ok_or_else(|| FlowyError::invalid_data().with_context(format!("invalid url: {}", url)))?;
+    let mut path = self.root_dir()?;
+    path.extend(components);
+    Ok(path)
+  }
+
+  fn upload_dir(&self, upload_id: &str) -> FlowyResult<PathBuf> {
+    if !is_valid_component(upload_id) {
+      return Err(
+        FlowyError::invalid_data().with_context(format!("invalid upload id: {}", upload_id)),
+      );
+    }
+    Ok(self.root_dir()?.join(LOCAL_UPLOAD_DIR).join(upload_id))
+  }
+}
+
+#[async_trait]
+impl StorageCloudService for LocalServerFileStorageImpl {
+  async fn get_object_url(&self, object_id: ObjectIdentity) -> Result<String, FlowyError> {
+    let file_name = format!("{}.{}", object_id.file_id, object_id.ext);
+    Ok(local_object_url(&[&object_id.workspace_id, &file_name]))
+  }
+
+  async fn put_object(&self, url: String, object_value: ObjectValue) -> Result<(), FlowyError> {
+    let path = self.object_path(&url)?;
+    write_file(&path, &object_value.raw).await
+  }
+
+  async fn delete_object(&self, url: &str) -> Result<(), FlowyError> {
+    let path = self.object_path(url)?;
+    match fs::remove_file(&path).await {
+      Ok(_) => Ok(()),
+      Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+      Err(err) => Err(err.into()),
+    }
+  }
+
+  async fn get_object(&self, url: String) -> Result<ObjectValue, FlowyError> {
+    let path = self.object_path(&url)?;
+    let raw = fs::read(&path)
+      .await
+      .map_err(|err| read_object_error(err, &url))?;
+    let mime = mime_guess::from_path(&path).first_or_octet_stream();
+    Ok(ObjectValue {
+      raw: raw.into(),
+      mime,
+    })
+  }
+
+  async fn get_object_range(
+    &self,
+    url: String,
+    offset: u64,
+    length: u64,
+  ) -> Result<ObjectRange, FlowyError> {
+    let path = self.object_path(&url)?;
+    let mut file = fs::File::open(&path)
+      .await
+      .map_err(|err| read_object_error(err, &url))?;
+    let total_size = file.metadata().await?.len();
+    file.seek(SeekFrom::Start(offset.min(total_size))).await?;
+    let mut raw = vec![];
+    file.take(length).read_to_end(&mut raw).await?;
+    let mime = mime_guess::from_path(&path).first_or_octet_stream();
+    Ok(ObjectRange {
+      raw: raw.into(),
+      mime,
+      total_size,
+    })
+  }
+
+  async fn get_object_url_v1(
+    &self,
+    workspace_id: &str,
+    parent_dir: &str,
+    file_id: &str,
+  ) -> FlowyResult<String> {
+    Ok(local_object_url(&[workspace_id, parent_dir, file_id]))
+  }
+
+  async fn parse_object_url_v1(&self, url: &str) -> Option<(String, String, String)> {
+    parse_local_object_url_v1(url)
+  }
+
+  async fn create_upload(
+    &self,
+    _workspace_id: &str,
+    _parent_dir: &str,
+    file_id: &str,
+    _content_type: &str,
+    _file_size: u64,
+  ) -> Result<CreateUploadResponse, FlowyError> {
+    let upload_id = uuid::Uuid::new_v4().to_string();
+    fs::create_dir_all(self.upload_dir(&upload_id)?).await?;
+    Ok(CreateUploadResponse {
+      file_id: file_id.to_string(),
+      upload_id,
+    })
+  }
+
+  async fn upload_part(
+    &self,
+    _workspace_id: &str,
+    _parent_dir: &str,
+    upload_id: &str,
+    _file_id: &str,
+    part_number: i32,
+    body: Vec<u8>,
+  ) -> Result<UploadPartResponse, FlowyError> {
+    let part_path = self.upload_dir(upload_id)?.join(part_number.to_string());
+    write_file(&part_path, &body).await?;
+    Ok(UploadPartResponse {
+      e_tag: part_number.to_string(),
+      part_num: part_number,
+    })
+  }
+
+  async fn complete_upload(
+    &self,
+    workspace_id: &str,
+    parent_dir: &str,
+    upload_id: &str,
+    file_id: &str,
+    mut parts: Vec<CompletedPartRequest>,
+  ) -> Result<(), FlowyError> {
+    let upload_dir = self.upload_dir(upload_id)?;
+    let url = local_object_url(&[workspace_id, parent_dir, file_id]);
+    let path = self.object_path(&url)?;
+
+    // The same content was uploaded before, so the parts can be dropped.
+    if fs::try_exists(&path).await.unwrap_or(false) {
+      trace!("[File] local object already exists: {}", url);
+    } else {
+      parts.sort_by_key(|part| part.part_number);
+      let mut content = vec![];
+      for part in parts {
+        let part_path = upload_dir.join(part.part_number.to_string());
+        let bytes = fs::read(&part_path).await.map_err(|err| {
+          FlowyError::new(
+            ErrorCode::Internal,
+            format!("missing part {} of {}: {}", part.part_number, file_id, err),
+          )
+        })?;
+        content.extend(bytes);
+      }
+      write_file(&path, &content).await?;
+    }
+
+    if let Err(err) = fs::remove_dir_all(&upload_dir).await {
+      warn!("[File] remove upload dir {:?} failed: {}", upload_dir, err);
+    }
+    Ok(())
+  }
+}
+
+fn read_object_error(err: std::io::Error, url: &str) -> FlowyError {
+  if err.kind() == std::io::ErrorKind::NotFound {
+    FlowyError::record_not_found().with_context(format!("object not found: {}", url))
+  } else {
+    err.into()
+  }
+}
+
+/// Writes the bytes to a temporary file next to the target and renames it, so a partially
+/// written object is never visible under its url.
+async fn write_file(path: &Path, bytes: &[u8]) -> FlowyResult<()> {
+  if let Some(parent) = path.parent() {
+    fs::create_dir_all(parent).await?;
+  }
+  let tmp_path = path.with_extension(format!("{}.tmp", uuid::Uuid::new_v4()));
+  let mut file = fs::File::create(&tmp_path).await?;
+  file.write_all(bytes).await?;
+  file.sync_all().await?;
+  drop(file);
+  fs::rename(&tmp_path, path).await?;
+  Ok(())
+}
+
+fn local_object_url(components: &[&str]) -> String {
+  format!("{}://{}", LOCAL_FILE_URL_SCHEME, components.join("/"))
+}
+
+/// Returns the path components of a local object url. None if the url doesn't use the
+/// [LOCAL_FILE_URL_SCHEME] or one of its components would escape the storage directory.
+fn url_components(url: &str) -> Option<Vec<&str>> {
+  let path = url
+    .strip_prefix(LOCAL_FILE_URL_SCHEME)?
+    .strip_prefix("://")?;
+  let components = path.split('/').collect::<Vec<_>>();
+  if components
+    .iter()
+    .all(|component| is_valid_component(component))
+  {
+    Some(components)
+  } else {
+    None
+  }
+}
+
+fn is_valid_component(component: &str) -> bool {
+  !component.is_empty()
+    && component != "."
+    && component != ".."
+    && component != LOCAL_UPLOAD_DIR
+    && !component.contains(['/', '\\'])
+}
+
+/// Return workspace_id, parent_dir, file_id of a url created by
+/// [StorageCloudService::get_object_url_v1].
+fn parse_local_object_url_v1(url: &str) -> Option<(String, String, String)> {
+  match url_components(url)?.as_slice() {
+    [workspace_id, parent_dir, file_id] => Some((
+      workspace_id.to_string(),
+      parent_dir.to_string(),
+      file_id.to_string(),
+    )),
+    _ => None,
+  }
+}
@@ -14,7 +14,6 @@ use flowy_folder_pub::entities::PublishPayload;
 use lib_infra::async_trait::async_trait;
 
 pub(crate) struct LocalServerFolderCloudServiceImpl {
-  #[allow(dead_code)]
   pub db: Arc<dyn LocalServerDB>,
 }
 
@@ -47,10 +46,40 @@ impl FolderCloudService for LocalServerFolderCloudServiceImpl {
 
   async fn get_folder_snapshots(
     &self,
-    _workspace_id: &str,
-    _limit: usize,
+    workspace_id: &str,
+    limit: usize,
   ) -> Result<Vec<FolderSnapshot>, FlowyError> {
-    Ok(vec![])
+    // The object id of the folder collab is the workspace id.
+    let snapshots = self
+      .db
+      .get_collab_snapshots(workspace_id, limit)?
+      .into_iter()
+      .map(|snapshot| FolderSnapshot {
+        snapshot_id: snapshot.snapshot_id,
+        database_id: snapshot.object_id,
+        data: snapshot.data,
+        created_at: snapshot.created_at,
+      })
+      .collect();
+    Ok(snapshots)
+  }
+
+  async fn get_folder_snapshot(
+    &self,
+    workspace_id: &str,
+    snapshot_id: &str,
+  ) -> Result<Option<FolderSnapshot>, FlowyError> {
+    let snapshot = self
+      .db
+      .get_collab_snapshot(snapshot_id)?
+      .filter(|snapshot| snapshot.object_id == workspace_id)
+      .map(|snapshot| FolderSnapshot {
+        snapshot_id: snapshot.snapshot_id,
+        database_id: snapshot.object_id,
+        data: snapshot.data,
+        created_at: snapshot.created_at,
+      });
+    Ok(snapshot)
   }
 
   async fn get_folder_doc_state(
@@ -1,12 +1,15 @@
 use flowy_search_pub::cloud::SearchCloudService;
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use tokio::sync::mpsc;
+use tracing::{error, info};
 
 use flowy_database_pub::cloud::{DatabaseAIService, DatabaseCloudService};
 use flowy_document_pub::cloud::DocumentCloudService;
-use flowy_error::FlowyError;
+use flowy_error::{FlowyError, FlowyResult};
 use flowy_folder_pub::cloud::FolderCloudService;
+use flowy_server_pub::s3_config::S3Configuration;
 use flowy_storage_pub::cloud::StorageCloudService;
 // use flowy_user::services::database::{
 //   get_user_profile, get_user_workspace, open_collab_db, open_user_db,
@@ -16,24 +19,55 @@ use flowy_user_pub::entities::*;
 
 use crate::local_server::impls::{
   LocalServerDatabaseCloudServiceImpl, LocalServerDocumentCloudServiceImpl,
-  LocalServerFolderCloudServiceImpl, LocalServerUserAuthServiceImpl,
+  LocalServerFileStorageImpl, LocalServerFolderCloudServiceImpl, LocalServerUserAuthServiceImpl,
 };
+use crate::s3::S3FileStorageServiceImpl;
 use crate::AppFlowyServer;
 
 pub trait LocalServerDB: Send + Sync + 'static {
   fn get_user_profile(&self, uid: i64) -> Result<UserProfile, FlowyError>;
   fn get_user_workspace(&self, uid: i64) -> Result<Option<UserWorkspace>, FlowyError>;
+  /// Return the latest snapshots of the collab object that were saved on the device.
+  fn get_collab_snapshots(
+    &self,
+    object_id: &str,
+    limit: usize,
+  ) -> Result<Vec<LocalCollabSnapshot>, FlowyError>;
+  /// Return the snapshot with the given id, or None if the snapshot doesn't exist.
+  fn get_collab_snapshot(
+    &self,
+    snapshot_id: &str,
+  ) -> Result<Option<LocalCollabSnapshot>, FlowyError>;
+  /// Return the data folder of the current user. The files of the user are stored in it.
+  fn get_user_data_dir(&self) -> Result<PathBuf, FlowyError>;
+}
+
+pub struct LocalCollabSnapshot {
+  pub snapshot_id: String,
+  pub object_id: String,
+  /// The doc state of the collab when the snapshot was taken.
+  pub data: Vec<u8>,
+  pub created_at: i64,
 }
 
 pub struct LocalServer {
   local_db: Arc<dyn LocalServerDB>,
+  /// The bucket that stores the files when the S3 storage is configured. Otherwise, the files
+  /// are stored in the user data folder. The error of an invalid configuration is kept, so the
+  /// files aren't stored on the device by mistake.
+  s3_file_storage: Option<FlowyResult<Arc<S3FileStorageServiceImpl>>>,
   stop_tx: Option<mpsc::Sender<()>>,
 }
 
 impl LocalServer {
-  pub fn new(local_db: Arc<dyn LocalServerDB>) -> Self {
+  pub fn new(local_db: Arc<dyn LocalServerDB>, s3_config: Option<S3Configuration>) -> Self {
+    let s3_file_storage = s3_config.map(|config| {
+      info!("[File] store files in S3 bucket: {}", config);
+      S3FileStorageServiceImpl::new(config).map(Arc::new)
+    });
     Self {
       local_db,
+      s3_file_storage,
       stop_tx: Default::default(),
     }
   }
@@ -60,15 +94,28 @@ impl AppFlowyServer for LocalServer {
   }
 
   fn database_service(&self) -> Arc<dyn DatabaseCloudService> {
-    Arc::new(LocalServerDatabaseCloudServiceImpl())
+    Arc::new(LocalServerDatabaseCloudServiceImpl {
+      db: self.local_db.clone(),
+    })
   }
 
   fn document_service(&self) -> Arc<dyn DocumentCloudService> {
-    Arc::new(LocalServerDocumentCloudServiceImpl())
+    Arc::new(LocalServerDocumentCloudServiceImpl {
+      db: self.local_db.clone(),
+    })
   }
 
   fn file_storage(&self) -> Option<Arc<dyn StorageCloudService>> {
-    None
+    match &self.s3_file_storage {
+      Some(Ok(s3_file_storage)) => Some(s3_file_storage.clone()),
+      Some(Err(err)) => {
+        error!("[File] invalid S3 configuration: {}", err);
+        None
+      },
+      None => Some(Arc::new(LocalServerFileStorageImpl {
+        db: self.local_db.clone(),
+      })),
+    }
   }
 
   fn search_service(&self) -> Option<Arc<dyn SearchCloudService>> {
@@ -0,0 +1,393 @@
+use chrono::Utc;
+use flowy_error::{ErrorCode, FlowyError, FlowyResult};
+use flowy_server_pub::s3_config::S3Configuration;
+use flowy_storage_pub::cloud::{ObjectIdentity, ObjectRange, ObjectValue, StorageCloudService};
+use flowy_storage_pub::storage::{CompletedPartRequest, CreateUploadResponse, UploadPartResponse};
+use lib_infra::async_trait::async_trait;
+use reqwest::header::{AUTHORIZATION, CONTENT_TYPE, ETAG, RANGE};
+use reqwest::{Method, Response, StatusCode};
+use tracing::trace;
+use url::Url;
+
+use crate::s3::{amz_date, canonical_query_string, sha256_hex, uri_encode, S3RequestSigner};
+use crate::util::{object_range_from_response, range_header_value, response_mime};
+
+/// The longest expiration that S3 accepts for a presigned url.
+const MAX_PRESIGNED_URL_EXPIRES_IN_SECS: u64 = 7 * 24 * 60 * 60;
+
+/// Stores the files in the bucket of an S3-compatible service. The objects are stored under the
+/// `<workspace_id>/<parent_dir>/<file_id>` key and uploaded with the multipart upload API, so the
+/// uploads of [flowy_storage::manager::StorageManager] can be resumed part by part.
+pub struct S3FileStorageServiceImpl {
+  config: S3Configuration,
+  signer: S3RequestSigner,
+  client: reqwest::Client,
+  scheme: String,
+  /// The host of the bucket, including the port when it's not the default one.
+  host: String,
+  /// The path of the bucket. Empty when the bucket is addressed by the host.
+  bucket_path: String,
+}
+
+impl S3FileStorageServiceImpl {
+  /// Returns an [ErrorCode::InvalidAuthConfig] error if the configuration is invalid.
+  pub fn new(config: S3Configuration) -> FlowyResult<Self> {
+    config.validate()?;
+    let endpoint = Url::parse(&config.endpoint).map_err(|err| {
+      FlowyError::new(
+        ErrorCode::InvalidAuthConfig,
+        format!("Invalid S3 endpoint: {}", err),
+      )
+    })?;
+    let endpoint_host = endpoint
+      .host_str()
+      .ok_or_else(|| FlowyError::new(ErrorCode::InvalidAuthConfig, "S3 endpoint without host"))?;
+    let endpoint_host = match endpoint.port() {
+      None => endpoint_host.to_string(),
+      Some(port) => format!("{}:{}", endpoint_host, port),
+    };
+    let (host, bucket_path) = if config.path_style {
+      (
+        endpoint_host,
+        format!("/{}", uri_encode(&config.bucket, true)),
+      )
+    } else {
+      (
+        format!("{}.{}", config.bucket, endpoint_host),
+        String::new(),
+      )
+    };
+
+    let signer = S3RequestSigner::new(
+      &config.access_key_id,
+      &config.secret_access_key,
+      &config.region,
+    );
+    Ok(Self {
+      signer,
+      client: reqwest::Client::new(),
+      scheme: endpoint.scheme().to_string(),
+      host,
+      bucket_path,
+      config,
+    })
+  }
+
+  /// Returns a url that downloads the object without any credentials until it expires. The
+  /// images and media of the documents and databases are loaded with it.
+  pub fn presigned_get_url(&self, url: &str) -> FlowyResult<String> {
+    let key = self.object_key(url)?;
+    let path = self.object_path(&key);
+    let expires_in_secs = self
+      .config
+      .presigned_url_expires_in_secs
+      .min(MAX_PRESIGNED_URL_EXPIRES_IN_SECS);
+    let query = self
+      .signer
+      .presigned_query("GET", &self.host, &path, expires_in_secs, Utc::now());
+    Ok(format!("{}://{}{}?{}", self.scheme, self.host, path, query))
+  }
+
+  fn object_url(&self, components: &[&str]) -> String {
+    let key = components
+      .iter()
+      .map(|component| uri_encode(component, true))
+      .collect::<Vec<_>>()
+      .join("/");
+    format!("{}://{}{}", self.scheme, self.host, self.object_path(&key))
+  }
+
+  /// Returns the percent-encoded key of the object url.
+  fn object_key(&self, url: &str) -> FlowyResult<String> {
+    let prefix = format!("{}://{}{}/", self.scheme, self.host, self.bucket_path);
+    url
+      .strip_prefix(&prefix)
+      .filter(|key| !key.is_empty() && !key.contains(['?', '#']))
+      .map(|key| key.to_string())
+      .ok_or_else(|| {
+        FlowyError::invalid_data().with_context(format!("{} is not an object of the bucket", url))
+      })
+  }
+
+  fn object_path(&self, key: &str) -> String {
+    format!("{}/{}", self.bucket_path, key)
+  }
+
+  async fn send(
+    &self,
+    method: Method,
+    key: &str,
+    query: Vec<(String, String)>,
+    content_type: Option<&str>,
+    body: Vec<u8>,
+  ) -> FlowyResult<Response> {
+    let now = Utc::now();
+    let path = self.object_path(key);
+    let payload_hash = sha256_hex(&body);
+    let mut headers = vec![
+      ("x-amz-content-sha256".to_string(), payload_hash.clone()),
+      ("x-amz-date".to_string(), amz_date(now)),
+    ];
+    if let Some(content_type) = content_type {
+      headers.push((CONTENT_TYPE.to_string(), content_type.to_string()));
+    }
+
+    let mut signed_headers = headers.clone();
+    signed_headers.push(("host".to_string(), self.host.clone()));
+    let authorization = self.signer.authorization(
+      method.as_str(),
+      &path,
+      &query,
+      &signed_headers,
+      &payload_hash,
+      now,
+    );
+
+    let mut url = format!("{}://{}{}", self.scheme, self.host, path);
+    if !query.is_empty() {
+      url.push('?');
+      url.push_str(&canonical_query_string(&query));
+    }
+    trace!("[S3] {} {}", method, url);
+    let mut request = self
+      .client
+      .request(method, url)
+      .header(AUTHORIZATION, authorization)
+      .body(body);
+    for (name, value) in headers {
+      request = request.header(name, value);
+    }
+    check_response(request.send().await?).await
+  }
+}
+
+#[async_trait]
+impl StorageCloudService for S3FileStorageServiceImpl {
+  async fn get_object_url(&self, object_id: ObjectIdentity) -> Result<String, FlowyError> {
+    let file_name = format!("{}.{}", object_id.file_id, object_id.ext);
+    Ok(self.object_url(&[object_id.workspace_id.as_str(), file_name.as_str()]))
+  }
+
+  async fn put_object(&self, url: String, object_value: ObjectValue) -> Result<(), FlowyError> {
+    let key = self.object_key(&url)?;
+    self
+      .send(
+        Method::PUT,
+        &key,
+        vec![],
+        Some(object_value.mime.as_ref()),
+        object_value.raw.to_vec(),
+      )
+      .await?;
+    Ok(())
+  }
+
+  async fn delete_object(&self, url: &str) -> Result<(), FlowyError> {
+    let key = self.object_key(url)?;
+    self
+      .send(Method::DELETE, &key, vec![], None, vec![])
+      .await?;
+    Ok(())
+  }
+
+  async fn get_object(&self, url: String) -> Result<ObjectValue, FlowyError> {
+    let presigned_url = self.presigned_get_url(&url)?;
+    let resp = check_response(self.client.get(presigned_url).send().await?).await?;
+    let mime = response_mime(&resp);
+    let raw = resp.bytes().await?;
+    Ok(ObjectValue { raw, mime })
+  }
+
+  async fn get_download_url(&self, url: &str) -> Result<String, FlowyError> {
+    self.presigned_get_url(url)
+  }
+
+  async fn get_object_range(
+    &self,
+    url: String,
+    offset: u64,
+    length: u64,
+  ) -> Result<ObjectRange, FlowyError> {
+    let presigned_url = self.presigned_get_url(&url)?;
+    let resp = check_response(
+      self
+        .client
+        .get(presigned_url)
+        .header(RANGE, range_header_value(offset, length))
+        .send()
+        .await?,
+    )
+    .await?;
+    object_range_from_response(resp, offset).await
+  }
+
+  async fn get_object_url_v1(
+    &self,
+    workspace_id: &str,
+    parent_dir: &str,
+    file_id: &str,
+  ) -> FlowyResult<String> {
+    Ok(self.object_url(&[workspace_id, parent_dir, file_id]))
+  }
+
+  async fn parse_object_url_v1(&self, url: &str) -> Option<(String, String, String)> {
+    let key = self.object_key(url).ok()?;
+    let components = key.split('/').collect::<Vec<_>>();
+    match components.as_slice() {
+      [workspace_id, parent_dir, file_id] => Some((
+        workspace_id.to_string(),
+        parent_dir.to_string(),
+        file_id.to_string(),
+      )),
+      _ => None,
+    }
+  }
+
+  async fn create_upload(
+    &self,
+    workspace_id: &str,
+    parent_dir: &str,
+    file_id: &str,
+    content_type: &str,
+    _file_size: u64,
+  ) -> Result<CreateUploadResponse, FlowyError> {
+    let key = multipart_key(workspace_id, parent_dir, file_id);
+    let resp = self
+      .send(
+        Method::POST,
+        &key,
+        vec![("uploads".to_string(), String::new())],
+        Some(content_type),
+        vec![],
+      )
+      .await?;
+    let body = resp.text().await?;
+    let upload_id = xml_element(&body, "UploadId").ok_or_else(|| {
+      FlowyError::http().with_context(format!("UploadId is missing in the response: {}", body))
+    })?;
+    Ok(CreateUploadResponse {
+      file_id: file_id.to_string(),
+      upload_id,
+    })
+  }
+
+  async fn upload_part(
+    &self,
+    workspace_id: &str,
+    parent_dir: &str,
+    upload_id: &str,
+    file_id: &str,
+    part_number: i32,
+    body: Vec<u8>,
+  ) -> Result<UploadPartResponse, FlowyError> {
+    let key = multipart_key(workspace_id, parent_dir, file_id);
+    let query = vec![
+      ("partNumber".to_string(), part_number.to_string()),
+      ("uploadId".to_string(), upload_id.to_string()),
+    ];
+    let resp = self.send(Method::PUT, &key, query, None, body).await?;
+    let e_tag = resp
+      .headers()
+      .get(ETAG)
+      .and_then(|value| value.to_str().ok())
+      .map(|value| value.to_string())
+      .ok_or_else(|| FlowyError::http().with_context("ETag is missing in the response"))?;
+    Ok(UploadPartResponse {
+      e_tag,
+      part_num: part_number,
+    })
+  }
+
+  async fn complete_upload(
+    &self,
+    workspace_id: &str,
+    parent_dir: &str,
+    upload_id: &str,
+    file_id: &str,
+    mut parts: Vec<CompletedPartRequest>,
+  ) -> Result<(), FlowyError> {
+    let key = multipart_key(workspace_id, parent_dir, file_id);
+    parts.sort_by_key(|part| part.part_number);
+    let parts = parts
+      .iter()
+      .map(|part| {
+        format!(
+          "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
+          part.part_number,
+          xml_escape(&part.e_tag)
+        )
+      })
+      .collect::<String>();
+    let body = format!(
+      "<CompleteMultipartUpload>{}</CompleteMultipartUpload>",
+      parts
+    );
+    let query = vec![("uploadId".to_string(), upload_id.to_string())];
+    let resp = self
+      .send(
+        Method::POST,
+        &key,
+        query,
+        Some("application/xml"),
+        body.into_bytes(),
+      )
+      .await?;
+
+    // The service might fail after responding with 200 OK, in which case the body is an error.
+    let body = resp.text().await?;
+    if body.contains("<Error>") {
+      let message = xml_element(&body, "Message").unwrap_or(body);
+      return Err(FlowyError::http().with_context(message));
+    }
+    Ok(())
+  }
+}
+
+fn multipart_key(workspace_id: &str, parent_dir: &str, file_id: &str) -> String {
+  [workspace_id, parent_dir, file_id]
+    .iter()
+    .map(|component| uri_encode(component, true))
+    .collect::<Vec<_>>()
+    .join("/")
+}
+
+async fn check_response(resp: Response) -> FlowyResult<Response> {
+  let status = resp.status();
+  if status.is_success() {
+    return Ok(resp);
+  }
+
+  let body = resp.text().await.unwrap_or_default();
+  let message = xml_element(&body, "Message").unwrap_or(body);
+  if status == StatusCode::NOT_FOUND {
+    Err(FlowyError::record_not_found().with_context(message))
+  } else {
+    Err(FlowyError::http().with_context(format!("{}: {}", status, message)))
+  }
+}
+
+/// Returns the text of the first element with the given tag. The responses of S3 are small and
+/// flat, so it isn't worth pulling in an XML parser.
+fn xml_element(xml: &str, tag: &str) -> Option<String> {
+  let start_tag = format!("<{}>", tag);
+  let end_tag = format!("</{}>", tag);
+  let start = xml.find(&start_tag)? + start_tag.len();
+  let end = start + xml[start..].find(&end_tag)?;
+  Some(xml_unescape(&xml[start..end]))
+}
+
+fn xml_escape(value: &str) -> String {
+  value
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+}
+
+fn xml_unescape(value: &str) -> String {
+  value
+    .replace("&quot;", "\"")
+    .replace("&lt;", "<")
+    .replace("&gt;", ">")
+    .replace("&amp;", "&")
+}
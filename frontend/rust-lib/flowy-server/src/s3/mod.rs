@@ -0,0 +1,5 @@
+pub use file_storage::*;
+pub use signer::*;
+
+mod file_storage;
+mod signer;
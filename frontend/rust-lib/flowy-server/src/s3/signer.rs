@@ -0,0 +1,188 @@
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+const SIGNING_ALGORITHM: &str = "AWS4-HMAC-SHA256";
+const SERVICE_NAME: &str = "s3";
+/// The payload hash of the presigned urls. The body is unknown when the url is created.
+pub const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+
+/// Signs the requests to an S3-compatible service with the AWS Signature Version 4.
+/// See: https://docs.aws.amazon.com/AmazonS3/latest/API/sig-v4-authenticating-requests.html
+pub struct S3RequestSigner {
+  access_key_id: String,
+  secret_access_key: String,
+  region: String,
+}
+
+impl S3RequestSigner {
+  pub fn new(access_key_id: &str, secret_access_key: &str, region: &str) -> Self {
+    Self {
+      access_key_id: access_key_id.to_string(),
+      secret_access_key: secret_access_key.to_string(),
+      region: region.to_string(),
+    }
+  }
+
+  /// Returns the value of the `Authorization` header of the request. The `headers` must contain
+  /// the `host`, `x-amz-date` and `x-amz-content-sha256` headers that are sent with the request.
+  /// The `path` must be percent-encoded with [uri_encode].
+  pub fn authorization(
+    &self,
+    method: &str,
+    path: &str,
+    query: &[(String, String)],
+    headers: &[(String, String)],
+    payload_hash: &str,
+    now: DateTime<Utc>,
+  ) -> String {
+    let mut headers = headers
+      .iter()
+      .map(|(name, value)| (name.to_lowercase(), value.trim().to_string()))
+      .collect::<Vec<_>>();
+    headers.sort();
+    let signed_headers = headers
+      .iter()
+      .map(|(name, _)| name.as_str())
+      .collect::<Vec<_>>()
+      .join(";");
+    let signature = self.signature(method, path, query, &headers, payload_hash, now);
+    format!(
+      "{} Credential={}/{}, SignedHeaders={}, Signature={}",
+      SIGNING_ALGORITHM,
+      self.access_key_id,
+      self.scope(now),
+      signed_headers,
+      signature
+    )
+  }
+
+  /// Returns the query string of a presigned url. Anyone with the url can send the request until
+  /// it expires, without any credentials.
+  pub fn presigned_query(
+    &self,
+    method: &str,
+    host: &str,
+    path: &str,
+    expires_in_secs: u64,
+    now: DateTime<Utc>,
+  ) -> String {
+    let mut query = vec![
+      ("X-Amz-Algorithm".to_string(), SIGNING_ALGORITHM.to_string()),
+      (
+        "X-Amz-Credential".to_string(),
+        format!("{}/{}", self.access_key_id, self.scope(now)),
+      ),
+      ("X-Amz-Date".to_string(), amz_date(now)),
+      ("X-Amz-Expires".to_string(), expires_in_secs.to_string()),
+      ("X-Amz-SignedHeaders".to_string(), "host".to_string()),
+    ];
+    let headers = vec![("host".to_string(), host.to_string())];
+    let signature = self.signature(method, path, &query, &headers, UNSIGNED_PAYLOAD, now);
+    query.push(("X-Amz-Signature".to_string(), signature));
+    canonical_query_string(&query)
+  }
+
+  /// The `headers` must be lowercase, trimmed and sorted by name.
+  fn signature(
+    &self,
+    method: &str,
+    path: &str,
+    query: &[(String, String)],
+    headers: &[(String, String)],
+    payload_hash: &str,
+    now: DateTime<Utc>,
+  ) -> String {
+    let canonical_headers = headers
+      .iter()
+      .map(|(name, value)| format!("{}:{}\n", name, value))
+      .collect::<String>();
+    let signed_headers = headers
+      .iter()
+      .map(|(name, _)| name.as_str())
+      .collect::<Vec<_>>()
+      .join(";");
+    let canonical_request = [
+      method,
+      path,
+      &canonical_query_string(query),
+      &canonical_headers,
+      &signed_headers,
+      payload_hash,
+    ]
+    .join("\n");
+    let string_to_sign = [
+      SIGNING_ALGORITHM,
+      &amz_date(now),
+      &self.scope(now),
+      &sha256_hex(canonical_request.as_bytes()),
+    ]
+    .join("\n");
+
+    let date = now.format("%Y%m%d").to_string();
+    let mut signing_key = format!("AWS4{}", self.secret_access_key).into_bytes();
+    for message in [
+      date.as_str(),
+      self.region.as_str(),
+      SERVICE_NAME,
+      "aws4_request",
+    ] {
+      signing_key = hmac_sha256(&signing_key, message.as_bytes());
+    }
+    hex::encode(hmac_sha256(&signing_key, string_to_sign.as_bytes()))
+  }
+
+  fn scope(&self, now: DateTime<Utc>) -> String {
+    format!(
+      "{}/{}/{}/aws4_request",
+      now.format("%Y%m%d"),
+      self.region,
+      SERVICE_NAME
+    )
+  }
+}
+
+/// The value of the `x-amz-date` header.
+pub fn amz_date(now: DateTime<Utc>) -> String {
+  now.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+pub fn sha256_hex(data: &[u8]) -> String {
+  hex::encode(Sha256::digest(data))
+}
+
+/// Sorts the parameters by name and percent-encodes them, as the signature requires.
+pub fn canonical_query_string(query: &[(String, String)]) -> String {
+  let mut query = query
+    .iter()
+    .map(|(name, value)| (uri_encode(name, true), uri_encode(value, true)))
+    .collect::<Vec<_>>();
+  query.sort();
+  query
+    .into_iter()
+    .map(|(name, value)| format!("{}={}", name, value))
+    .collect::<Vec<_>>()
+    .join("&")
+}
+
+/// Percent-encodes every byte except the unreserved characters. The `/` is kept when
+/// `encode_slash` is false, which is used for the object keys in the path.
+pub fn uri_encode(value: &str, encode_slash: bool) -> String {
+  let mut encoded = String::with_capacity(value.len());
+  for byte in value.bytes() {
+    match byte {
+      b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+        encoded.push(byte as char)
+      },
+      b'/' if !encode_slash => encoded.push('/'),
+      _ => encoded.push_str(&format!("%{:02X}", byte)),
+    }
+  }
+  encoded
+}
+
+fn hmac_sha256(key: &[u8], message: &[u8]) -> Vec<u8> {
+  let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC can take key of any size");
+  mac.update(message);
+  mac.finalize().into_bytes().to_vec()
+}
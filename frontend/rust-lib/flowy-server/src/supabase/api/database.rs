@@ -86,7 +86,7 @@ where
         .await?
         .into_iter()
         .map(|snapshot| DatabaseSnapshot {
-          snapshot_id: snapshot.sid,
+          snapshot_id: snapshot.sid.to_string(),
           database_id: snapshot.oid,
           data: snapshot.blob,
           created_at: snapshot.created_at,
@@ -68,7 +68,7 @@ where
         .await?
         .into_iter()
         .map(|snapshot| DocumentSnapshot {
-          snapshot_id: snapshot.sid,
+          snapshot_id: snapshot.sid.to_string(),
           document_id: snapshot.oid,
           data: snapshot.blob,
           created_at: snapshot.created_at,
@@ -124,7 +124,7 @@ where
         .await?
         .into_iter()
         .map(|snapshot| FolderSnapshot {
-          snapshot_id: snapshot.sid,
+          snapshot_id: snapshot.sid.to_string(),
           database_id: snapshot.oid,
           data: snapshot.blob,
           created_at: snapshot.created_at,
@@ -1,3 +1,8 @@
+use flowy_error::{FlowyError, FlowyResult};
+use flowy_storage_pub::cloud::ObjectRange;
+use mime_guess::mime::Mime;
+use reqwest::header::{CONTENT_RANGE, CONTENT_TYPE};
+use reqwest::{Response, StatusCode};
 use serde::{Deserialize, Deserializer};
 
 /// Handles the case where the value is null. If the value is null, return the default value of the
@@ -11,3 +16,56 @@ where
   let opt = Option::deserialize(deserializer)?;
   Ok(opt.unwrap_or_default())
 }
+
+/// Returns the value of the `Range` header that requests `length` bytes from `offset`.
+pub(crate) fn range_header_value(offset: u64, length: u64) -> String {
+  format!("bytes={}-{}", offset, offset + length.max(1) - 1)
+}
+
+pub(crate) fn response_mime(resp: &Response) -> Mime {
+  resp
+    .headers()
+    .get(CONTENT_TYPE)
+    .and_then(|value| value.to_str().ok())
+    .and_then(|value| value.parse::<Mime>().ok())
+    .unwrap_or(mime_guess::mime::APPLICATION_OCTET_STREAM)
+}
+
+/// Reads the response of a range request that starts at `offset`. The whole object is sliced
+/// when the service ignored the range.
+pub(crate) async fn object_range_from_response(
+  resp: Response,
+  offset: u64,
+) -> FlowyResult<ObjectRange> {
+  let mime = response_mime(&resp);
+  // The Content-Range header looks like `bytes 0-1023/146515`.
+  let total_size = if resp.status() == StatusCode::PARTIAL_CONTENT {
+    let total_size = resp
+      .headers()
+      .get(CONTENT_RANGE)
+      .and_then(|value| value.to_str().ok())
+      .and_then(|value| value.rsplit('/').next())
+      .and_then(|value| value.parse::<u64>().ok())
+      .ok_or_else(|| FlowyError::http().with_context("Content-Range is missing in the response"))?;
+    Some(total_size)
+  } else {
+    None
+  };
+  let raw = resp.bytes().await?;
+  match total_size {
+    Some(total_size) => Ok(ObjectRange {
+      raw,
+      mime,
+      total_size,
+    }),
+    None => {
+      let total_size = raw.len() as u64;
+      let start = offset.min(total_size) as usize;
+      Ok(ObjectRange {
+        raw: raw.slice(start..),
+        mime,
+        total_size,
+      })
+    },
+  }
+}
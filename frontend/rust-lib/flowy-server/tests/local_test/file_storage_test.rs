@@ -0,0 +1,142 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use flowy_error::FlowyError;
+use flowy_server::local_server::{LocalCollabSnapshot, LocalServer, LocalServerDB};
+use flowy_server::AppFlowyServer;
+use flowy_server_pub::s3_config::S3Configuration;
+use flowy_storage_pub::storage::CompletedPartRequest;
+use flowy_user_pub::entities::{UserProfile, UserWorkspace};
+use uuid::Uuid;
+
+struct FakeLocalServerDB {
+  user_data_dir: PathBuf,
+}
+
+impl LocalServerDB for FakeLocalServerDB {
+  fn get_user_profile(&self, _uid: i64) -> Result<UserProfile, FlowyError> {
+    todo!()
+  }
+
+  fn get_user_workspace(&self, _uid: i64) -> Result<Option<UserWorkspace>, FlowyError> {
+    todo!()
+  }
+
+  fn get_collab_snapshots(
+    &self,
+    _object_id: &str,
+    _limit: usize,
+  ) -> Result<Vec<LocalCollabSnapshot>, FlowyError> {
+    todo!()
+  }
+
+  fn get_collab_snapshot(
+    &self,
+    _snapshot_id: &str,
+  ) -> Result<Option<LocalCollabSnapshot>, FlowyError> {
+    todo!()
+  }
+
+  fn get_user_data_dir(&self) -> Result<PathBuf, FlowyError> {
+    Ok(self.user_data_dir.clone())
+  }
+}
+
+fn local_server() -> (LocalServer, PathBuf) {
+  let user_data_dir = std::env::temp_dir().join(format!("local_file_storage_{}", Uuid::new_v4()));
+  let db = Arc::new(FakeLocalServerDB {
+    user_data_dir: user_data_dir.clone(),
+  });
+  (LocalServer::new(db, None), user_data_dir)
+}
+
+#[tokio::test]
+async fn local_file_storage_multiple_part_upload_test() {
+  let (server, user_data_dir) = local_server();
+  let storage = server.file_storage().unwrap();
+  let workspace_id = Uuid::new_v4().to_string();
+  let parent_dir = Uuid::new_v4().to_string();
+  let file_id = "c29tZV9jb250ZW50.png";
+
+  let url = storage
+    .get_object_url_v1(&workspace_id, &parent_dir, file_id)
+    .await
+    .unwrap();
+  assert_eq!(
+    storage.parse_object_url_v1(&url).await.unwrap(),
+    (
+      workspace_id.clone(),
+      parent_dir.clone(),
+      file_id.to_string()
+    )
+  );
+
+  let upload = storage
+    .create_upload(&workspace_id, &parent_dir, file_id, "image/png", 6)
+    .await
+    .unwrap();
+  let mut parts = vec![];
+  for (part_number, body) in [(2, b"def".to_vec()), (1, b"abc".to_vec())] {
+    let resp = storage
+      .upload_part(
+        &workspace_id,
+        &parent_dir,
+        &upload.upload_id,
+        file_id,
+        part_number,
+        body,
+      )
+      .await
+      .unwrap();
+    parts.push(CompletedPartRequest {
+      e_tag: resp.e_tag,
+      part_number: resp.part_num,
+    });
+  }
+  storage
+    .complete_upload(
+      &workspace_id,
+      &parent_dir,
+      &upload.upload_id,
+      file_id,
+      parts,
+    )
+    .await
+    .unwrap();
+
+  let object = storage.get_object(url.clone()).await.unwrap();
+  assert_eq!(object.raw.as_ref(), b"abcdef");
+  assert_eq!(object.mime, mime_guess::mime::IMAGE_PNG);
+
+  storage.delete_object(&url).await.unwrap();
+  assert!(storage.get_object(url).await.is_err());
+  std::fs::remove_dir_all(user_data_dir).unwrap();
+}
+
+#[tokio::test]
+async fn local_file_storage_reject_invalid_url_test() {
+  let (server, _) = local_server();
+  let storage = server.file_storage().unwrap();
+  for url in [
+    "https://appflowy.io/workspace/parent/file.png",
+    "appflowy-local://workspace/../file.png",
+    "appflowy-local://workspace//file.png",
+  ] {
+    assert!(storage.parse_object_url_v1(url).await.is_none());
+    assert!(storage.get_object(url.to_string()).await.is_err());
+  }
+}
+
+#[test]
+fn local_server_with_invalid_s3_config_test() {
+  let db = Arc::new(FakeLocalServerDB {
+    user_data_dir: std::env::temp_dir(),
+  });
+  let config = S3Configuration {
+    endpoint: "http://localhost:9000".to_string(),
+    ..Default::default()
+  };
+  // The server is still available, but the files aren't stored on the device
+  let server = LocalServer::new(db, Some(config));
+  assert!(server.file_storage().is_none());
+}
@@ -0,0 +1 @@
+mod file_storage_test;
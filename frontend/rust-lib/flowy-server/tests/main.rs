@@ -5,6 +5,8 @@ use tracing_subscriber::util::SubscriberInitExt;
 use tracing_subscriber::EnvFilter;
 
 mod af_cloud_test;
+mod local_test;
+mod s3_test;
 // mod supabase_test;
 
 pub fn setup_log() {
@@ -0,0 +1,93 @@
+use flowy_server::s3::S3FileStorageServiceImpl;
+use flowy_server_pub::s3_config::S3Configuration;
+use flowy_storage_pub::chunked_byte::MIN_CHUNK_SIZE;
+use flowy_storage_pub::cloud::StorageCloudService;
+use flowy_storage_pub::storage::CompletedPartRequest;
+use uuid::Uuid;
+
+use crate::s3_test::util::get_s3_config;
+
+#[tokio::test]
+async fn s3_multiple_part_upload_test() {
+  if let Some(config) = get_s3_config() {
+    let storage = S3FileStorageServiceImpl::new(config).unwrap();
+    let workspace_id = Uuid::new_v4().to_string();
+    let parent_dir = Uuid::new_v4().to_string();
+    let file_id = format!("{}.txt", Uuid::new_v4());
+
+    let url = storage
+      .get_object_url_v1(&workspace_id, &parent_dir, &file_id)
+      .await
+      .unwrap();
+    assert_eq!(
+      storage.parse_object_url_v1(&url).await.unwrap(),
+      (workspace_id.clone(), parent_dir.clone(), file_id.clone())
+    );
+
+    // Every part except the last one must be at least 5MB.
+    let first_part = vec![b'a'; MIN_CHUNK_SIZE];
+    let last_part = b"appflowy".to_vec();
+    let upload = storage
+      .create_upload(
+        &workspace_id,
+        &parent_dir,
+        &file_id,
+        "text/plain",
+        (first_part.len() + last_part.len()) as u64,
+      )
+      .await
+      .unwrap();
+    let mut parts = vec![];
+    for (part_number, body) in [(1, first_part.clone()), (2, last_part.clone())] {
+      let resp = storage
+        .upload_part(
+          &workspace_id,
+          &parent_dir,
+          &upload.upload_id,
+          &file_id,
+          part_number,
+          body,
+        )
+        .await
+        .unwrap();
+      parts.push(CompletedPartRequest {
+        e_tag: resp.e_tag,
+        part_number: resp.part_num,
+      });
+    }
+    storage
+      .complete_upload(
+        &workspace_id,
+        &parent_dir,
+        &upload.upload_id,
+        &file_id,
+        parts,
+      )
+      .await
+      .unwrap();
+
+    let object = storage.get_object(url.clone()).await.unwrap();
+    assert_eq!(object.raw.len(), first_part.len() + last_part.len());
+    assert!(object.raw.ends_with(&last_part));
+    assert_eq!(object.mime.as_ref(), "text/plain");
+
+    // The presigned url downloads the object without the credentials.
+    let presigned_url = storage.get_download_url(&url).await.unwrap();
+    let resp = reqwest::get(presigned_url).await.unwrap();
+    assert!(resp.status().is_success());
+
+    storage.delete_object(&url).await.unwrap();
+    let err = storage.get_object(url).await.unwrap_err();
+    assert!(err.is_record_not_found());
+  }
+}
+
+#[test]
+fn s3_invalid_config_test() {
+  let config = S3Configuration {
+    endpoint: "http://localhost:9000".to_string(),
+    bucket: "appflowy".to_string(),
+    ..Default::default()
+  };
+  assert!(S3FileStorageServiceImpl::new(config).is_err());
+}
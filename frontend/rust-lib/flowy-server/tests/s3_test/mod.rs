@@ -0,0 +1,3 @@
+mod file_storage_test;
+mod signer_test;
+mod util;
@@ -0,0 +1,17 @@
+use flowy_server_pub::s3_config::S3Configuration;
+
+use crate::setup_log;
+
+/// To run the test, start a MinIO server, create a bucket, then create a .env.ci file in the
+/// 'flowy-server' directory and set the following environment variables:
+///
+/// - `APPFLOWY_S3_ENV_ENDPOINT=http://localhost:9000`
+/// - `APPFLOWY_S3_ENV_BUCKET=appflowy`
+/// - `APPFLOWY_S3_ENV_ACCESS_KEY_ID=minioadmin`
+/// - `APPFLOWY_S3_ENV_SECRET_ACCESS_KEY=minioadmin`
+/// - `APPFLOWY_S3_ENV_PATH_STYLE=true`
+pub fn get_s3_config() -> Option<S3Configuration> {
+  dotenv::from_filename("./.env.ci").ok()?;
+  setup_log();
+  S3Configuration::from_env().ok()
+}
@@ -0,0 +1,10 @@
+-- Your SQL goes here
+CREATE TABLE view_link_table (
+    workspace_id TEXT NOT NULL,
+    source_view_id TEXT NOT NULL,
+    target_view_id TEXT NOT NULL,
+    block_id TEXT NOT NULL DEFAULT '',
+    link_type INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (workspace_id, source_view_id, target_view_id, block_id)
+);
+CREATE INDEX idx_view_link_target_view_id ON view_link_table (workspace_id, target_view_id);
@@ -0,0 +1,12 @@
+-- Your SQL goes here
+CREATE TABLE search_history_table (
+    id TEXT NOT NULL PRIMARY KEY,
+    workspace_id TEXT NOT NULL,
+    query TEXT NOT NULL,
+    filter BLOB,
+    name TEXT,
+    is_pinned BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at BIGINT NOT NULL,
+    last_used_at BIGINT NOT NULL
+);
+CREATE INDEX idx_search_history_workspace_id ON search_history_table (workspace_id);
@@ -0,0 +1,12 @@
+-- Your SQL goes here
+CREATE TABLE view_embedding_table (
+    view_id TEXT NOT NULL,
+    block_id TEXT NOT NULL,
+    chunk_index INTEGER NOT NULL,
+    content TEXT NOT NULL,
+    content_hash TEXT NOT NULL,
+    embedding_model TEXT NOT NULL,
+    embedding BLOB NOT NULL,
+    updated_at BIGINT NOT NULL,
+    PRIMARY KEY (view_id, block_id, chunk_index)
+);
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE chat_message_branch_table;
+ALTER TABLE chat_message_table DROP COLUMN is_hidden;
+ALTER TABLE chat_message_table DROP COLUMN parent_message_id;
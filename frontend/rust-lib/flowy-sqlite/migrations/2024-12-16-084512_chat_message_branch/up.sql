@@ -0,0 +1,10 @@
+-- Your SQL goes here
+ALTER TABLE chat_message_table ADD COLUMN parent_message_id BIGINT;
+ALTER TABLE chat_message_table ADD COLUMN is_hidden BOOLEAN NOT NULL DEFAULT 0;
+CREATE TABLE chat_message_branch_table (
+    chat_id TEXT NOT NULL,
+    parent_message_id BIGINT NOT NULL,
+    author_type BIGINT NOT NULL,
+    active_message_id BIGINT NOT NULL,
+    PRIMARY KEY (chat_id, parent_message_id, author_type)
+);
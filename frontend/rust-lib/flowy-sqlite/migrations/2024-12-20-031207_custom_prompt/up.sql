@@ -0,0 +1,11 @@
+-- Your SQL goes here
+CREATE TABLE custom_prompt_table (
+    id TEXT NOT NULL PRIMARY KEY,
+    workspace_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    template TEXT NOT NULL,
+    is_deleted BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at BIGINT NOT NULL,
+    updated_at BIGINT NOT NULL
+);
+CREATE INDEX idx_custom_prompt_workspace_id ON custom_prompt_table (workspace_id);
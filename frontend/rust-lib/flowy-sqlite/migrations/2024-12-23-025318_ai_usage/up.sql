@@ -0,0 +1,16 @@
+-- Your SQL goes here
+CREATE TABLE ai_usage_table (
+    id TEXT NOT NULL PRIMARY KEY,
+    workspace_id TEXT NOT NULL,
+    chat_id TEXT NOT NULL DEFAULT '',
+    usage_type INTEGER NOT NULL,
+    provider INTEGER NOT NULL,
+    model TEXT NOT NULL,
+    input_tokens BIGINT NOT NULL,
+    output_tokens BIGINT NOT NULL,
+    is_estimated BOOLEAN NOT NULL DEFAULT TRUE,
+    latency_ms BIGINT NOT NULL,
+    outcome INTEGER NOT NULL,
+    created_at BIGINT NOT NULL
+);
+CREATE INDEX idx_ai_usage_workspace_id_created_at ON ai_usage_table (workspace_id, created_at);
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER chat_message_fts_after_insert;
+DROP TRIGGER chat_message_fts_after_delete;
+DROP TRIGGER chat_message_fts_after_update;
+DROP TABLE chat_message_fts;
@@ -0,0 +1,21 @@
+-- Your SQL goes here
+-- The trigram tokenizer matches any substring, so the scripts without spaces between the words,
+-- like Chinese and Japanese, are searchable too
+CREATE VIRTUAL TABLE chat_message_fts USING fts5(
+    content,
+    content = 'chat_message_table',
+    content_rowid = 'message_id',
+    tokenize = 'trigram'
+);
+INSERT INTO chat_message_fts (rowid, content) SELECT message_id, content FROM chat_message_table;
+
+CREATE TRIGGER chat_message_fts_after_insert AFTER INSERT ON chat_message_table BEGIN
+    INSERT INTO chat_message_fts (rowid, content) VALUES (new.message_id, new.content);
+END;
+CREATE TRIGGER chat_message_fts_after_delete AFTER DELETE ON chat_message_table BEGIN
+    INSERT INTO chat_message_fts (chat_message_fts, rowid, content) VALUES ('delete', old.message_id, old.content);
+END;
+CREATE TRIGGER chat_message_fts_after_update AFTER UPDATE OF content ON chat_message_table BEGIN
+    INSERT INTO chat_message_fts (chat_message_fts, rowid, content) VALUES ('delete', old.message_id, old.content);
+    INSERT INTO chat_message_fts (rowid, content) VALUES (new.message_id, new.content);
+END;
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS download_file_table_workspace_idx;
+DROP TABLE download_file_table;
@@ -0,0 +1,14 @@
+-- Your SQL goes here
+CREATE TABLE download_file_table (
+    workspace_id TEXT NOT NULL,
+    url TEXT NOT NULL,
+    local_file_path TEXT NOT NULL,
+    file_id TEXT NOT NULL,
+    downloaded_size BIGINT NOT NULL DEFAULT 0,
+    total_size BIGINT NOT NULL DEFAULT 0,
+    created_at BIGINT NOT NULL,
+    is_finish BOOLEAN NOT NULL DEFAULT 0,
+    PRIMARY KEY (url, local_file_path)
+);
+
+CREATE INDEX download_file_table_workspace_idx ON download_file_table (workspace_id, is_finish);
@@ -0,0 +1,8 @@
+-- Your SQL goes here
+CREATE TABLE orphaned_upload_file_table (
+    workspace_id TEXT NOT NULL,
+    parent_dir TEXT NOT NULL,
+    file_id TEXT NOT NULL,
+    marked_at BIGINT NOT NULL,
+    PRIMARY KEY (workspace_id, parent_dir, file_id)
+);
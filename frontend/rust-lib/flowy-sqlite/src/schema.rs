@@ -1,5 +1,22 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    ai_usage_table (id) {
+        id -> Text,
+        workspace_id -> Text,
+        chat_id -> Text,
+        usage_type -> Integer,
+        provider -> Integer,
+        model -> Text,
+        input_tokens -> BigInt,
+        output_tokens -> BigInt,
+        is_estimated -> Bool,
+        latency_ms -> BigInt,
+        outcome -> Integer,
+        created_at -> BigInt,
+    }
+}
+
 diesel::table! {
     chat_local_setting_table (chat_id) {
         chat_id -> Text,
@@ -8,6 +25,15 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    chat_message_branch_table (chat_id, parent_message_id, author_type) {
+        chat_id -> Text,
+        parent_message_id -> BigInt,
+        author_type -> BigInt,
+        active_message_id -> BigInt,
+    }
+}
+
 diesel::table! {
     chat_message_table (message_id) {
         message_id -> BigInt,
@@ -18,6 +44,8 @@ diesel::table! {
         author_id -> Text,
         reply_message_id -> Nullable<BigInt>,
         metadata -> Nullable<Text>,
+        parent_message_id -> Nullable<BigInt>,
+        is_hidden -> Bool,
     }
 }
 
@@ -45,6 +73,53 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    custom_prompt_table (id) {
+        id -> Text,
+        workspace_id -> Text,
+        name -> Text,
+        template -> Text,
+        is_deleted -> Bool,
+        created_at -> BigInt,
+        updated_at -> BigInt,
+    }
+}
+
+diesel::table! {
+    download_file_table (url, local_file_path) {
+        workspace_id -> Text,
+        url -> Text,
+        local_file_path -> Text,
+        file_id -> Text,
+        downloaded_size -> BigInt,
+        total_size -> BigInt,
+        created_at -> BigInt,
+        is_finish -> Bool,
+    }
+}
+
+diesel::table! {
+    orphaned_upload_file_table (workspace_id, parent_dir, file_id) {
+        workspace_id -> Text,
+        parent_dir -> Text,
+        file_id -> Text,
+        marked_at -> BigInt,
+    }
+}
+
+diesel::table! {
+    search_history_table (id) {
+        id -> Text,
+        workspace_id -> Text,
+        query -> Text,
+        filter -> Nullable<Binary>,
+        name -> Nullable<Text>,
+        is_pinned -> Bool,
+        created_at -> BigInt,
+        last_used_at -> BigInt,
+    }
+}
+
 diesel::table! {
     upload_file_part (upload_id, e_tag) {
         upload_id -> Text,
@@ -105,6 +180,29 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    view_embedding_table (view_id, block_id, chunk_index) {
+        view_id -> Text,
+        block_id -> Text,
+        chunk_index -> Integer,
+        content -> Text,
+        content_hash -> Text,
+        embedding_model -> Text,
+        embedding -> Binary,
+        updated_at -> BigInt,
+    }
+}
+
+diesel::table! {
+    view_link_table (workspace_id, source_view_id, target_view_id, block_id) {
+        workspace_id -> Text,
+        source_view_id -> Text,
+        target_view_id -> Text,
+        block_id -> Text,
+        link_type -> Integer,
+    }
+}
+
 diesel::table! {
     workspace_members_table (email, workspace_id) {
         email -> Text,
@@ -118,14 +216,22 @@ diesel::table! {
 }
 
 diesel::allow_tables_to_appear_in_same_query!(
+  ai_usage_table,
   chat_local_setting_table,
+  chat_message_branch_table,
   chat_message_table,
   chat_table,
   collab_snapshot,
+  custom_prompt_table,
+  download_file_table,
+  orphaned_upload_file_table,
+  search_history_table,
   upload_file_part,
   upload_file_table,
   user_data_migration_records,
   user_table,
   user_workspace_table,
+  view_embedding_table,
+  view_link_table,
   workspace_members_table,
 );
@@ -45,6 +45,26 @@ pub trait StorageCloudService: Send + Sync {
   /// - `Ok(File)`: The returned file object.
   /// - `Err(Error)`: An error occurred during the operation.
   async fn get_object(&self, url: String) -> Result<ObjectValue, FlowyError>;
+
+  /// Returns a url that the client can download the object from without the credentials of the
+  /// service. The object url is returned by default, the services that require signed requests
+  /// return a presigned url.
+  async fn get_download_url(&self, url: &str) -> Result<String, FlowyError> {
+    Ok(url.to_string())
+  }
+
+  /// Fetches at most `length` bytes of a storage object, starting at `offset`. Used to download
+  /// large objects in chunks and to resume the interrupted downloads.
+  ///
+  /// If the service ignores the range and returns the whole object, the rest of the object from
+  /// `offset` is returned, so the returned bytes might be longer than `length`.
+  async fn get_object_range(
+    &self,
+    url: String,
+    offset: u64,
+    length: u64,
+  ) -> Result<ObjectRange, FlowyError>;
+
   async fn get_object_url_v1(
     &self,
     workspace_id: &str,
@@ -96,6 +116,15 @@ pub struct ObjectValue {
   pub mime: Mime,
 }
 
+/// A part of a storage object returned by [StorageCloudService::get_object_range].
+#[derive(Clone)]
+pub struct ObjectRange {
+  pub raw: Bytes,
+  pub mime: Mime,
+  /// The size of the whole object.
+  pub total_size: u64,
+}
+
 pub struct StorageObject {
   pub workspace_id: String,
   pub file_name: String,
@@ -11,7 +11,9 @@ use tokio::sync::broadcast;
 pub trait StorageService: Send + Sync {
   async fn delete_object(&self, url: String) -> FlowyResult<()>;
 
-  fn download_object(&self, url: String, local_file_path: String) -> FlowyResult<()>;
+  /// Queues a download of the object to the local file path. The download continues in the
+  /// background and its progress can be observed with [Self::subscribe_file_progress].
+  async fn download_object(&self, url: String, local_file_path: String) -> FlowyResult<()>;
 
   async fn create_upload(
     &self,
@@ -34,6 +36,15 @@ pub trait StorageService: Send + Sync {
     parent_idr: &str,
     file_id: &str,
   ) -> Result<Option<FileProgressReceiver>, FlowyError>;
+
+  /// Upload a copy of the object of the given url to the given workspace and returns the url of
+  /// the copy. Returns None if the url doesn't point to an object of the storage.
+  async fn copy_object(
+    &self,
+    url: &str,
+    workspace_id: &str,
+    parent_dir: &str,
+  ) -> Result<Option<String>, FlowyError>;
 }
 
 pub struct FileProgressReceiver {
@@ -59,6 +70,7 @@ impl DerefMut for FileProgressReceiver {
 pub enum FileUploadState {
   NotStarted,
   Uploading { progress: f64 },
+  Downloading { progress: f64 },
   Finished { file_id: String },
 }
 
@@ -68,6 +80,7 @@ pub struct FileProgress {
   pub file_id: String,
   pub progress: f64,
   pub error: Option<String>,
+  pub is_download: bool,
 }
 
 impl FileProgress {
@@ -77,6 +90,14 @@ impl FileProgress {
       file_id,
       progress: (progress * 10.0).round() / 10.0,
       error: None,
+      is_download: false,
+    }
+  }
+
+  pub fn new_download_progress(file_url: String, file_id: String, progress: f64) -> Self {
+    FileProgress {
+      is_download: true,
+      ..Self::new_progress(file_url, file_id, progress)
     }
   }
 
@@ -86,6 +107,14 @@ impl FileProgress {
       file_id,
       progress: 0.0,
       error: Some(error),
+      is_download: false,
+    }
+  }
+
+  pub fn new_download_error(file_url: String, file_id: String, error: String) -> Self {
+    FileProgress {
+      is_download: true,
+      ..Self::new_error(file_url, file_id, error)
     }
   }
 }
@@ -0,0 +1,401 @@
+use crate::manager::StorageUserService;
+use crate::sqlite_sql::{
+  select_download_file, update_download_file_completed, update_download_file_progress,
+  DownloadFileTable,
+};
+use crate::uploader::Signal;
+use flowy_error::{FlowyError, FlowyResult};
+use flowy_storage_pub::chunked_byte::MIN_CHUNK_SIZE;
+use flowy_storage_pub::cloud::StorageCloudService;
+use flowy_storage_pub::storage::FileProgress;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashSet};
+use std::fmt::Display;
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU8};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+use tokio::sync::{broadcast, watch, RwLock};
+use tracing::{error, info, instrument, trace, warn};
+
+/// The size of the ranges that a file is downloaded in.
+const DOWNLOAD_CHUNK_SIZE: u64 = MIN_CHUNK_SIZE as u64;
+/// The downloads are given up after this many failed attempts, and retried on the next launch.
+const MAX_DOWNLOAD_RETRY_COUNT: u8 = 5;
+const MAX_DOWNLOAD_BACKOFF_SECS: u64 = 60;
+
+pub struct DownloadTaskQueue {
+  tasks: RwLock<BinaryHeap<DownloadTask>>,
+  /// The (url, local_file_path) of the tasks that are being downloaded. A file is only written by
+  /// one task at a time.
+  running_tasks: RwLock<HashSet<(String, String)>>,
+  notifier: watch::Sender<Signal>,
+}
+
+impl DownloadTaskQueue {
+  pub fn new(notifier: watch::Sender<Signal>) -> Self {
+    Self {
+      tasks: Default::default(),
+      running_tasks: Default::default(),
+      notifier,
+    }
+  }
+
+  pub async fn queue_task(&self, task: DownloadTask) {
+    if self.running_tasks.read().await.contains(&task.key()) {
+      trace!("[File] skip queueing {}, it's being downloaded", task);
+      return;
+    }
+
+    trace!("[File] Queued download task: {}", task);
+    let mut tasks = self.tasks.write().await;
+    if !tasks.iter().any(|queued| queued == &task) {
+      tasks.push(task);
+    }
+    drop(tasks);
+    let _ = self.notifier.send_replace(Signal::Proceed);
+  }
+
+  /// Returns false if the task is already being downloaded.
+  async fn start_task(&self, task: &DownloadTask) -> bool {
+    self.running_tasks.write().await.insert(task.key())
+  }
+
+  async fn finish_task(&self, task: &DownloadTask) {
+    self.running_tasks.write().await.remove(&task.key());
+  }
+}
+
+pub struct FileDownloader {
+  cloud_service: Arc<dyn StorageCloudService>,
+  user_service: Arc<dyn StorageUserService>,
+  global_notifier: broadcast::Sender<FileProgress>,
+  queue: Arc<DownloadTaskQueue>,
+  max_downloads: u8,
+  current_downloads: AtomicU8,
+  pause_sync: AtomicBool,
+}
+
+impl Drop for FileDownloader {
+  fn drop(&mut self) {
+    let _ = self.queue.notifier.send(Signal::Stop);
+  }
+}
+
+impl FileDownloader {
+  pub fn new(
+    cloud_service: Arc<dyn StorageCloudService>,
+    user_service: Arc<dyn StorageUserService>,
+    global_notifier: broadcast::Sender<FileProgress>,
+    queue: Arc<DownloadTaskQueue>,
+  ) -> Self {
+    Self {
+      cloud_service,
+      user_service,
+      global_notifier,
+      queue,
+      max_downloads: 3,
+      current_downloads: Default::default(),
+      pause_sync: Default::default(),
+    }
+  }
+
+  pub async fn queue_tasks(&self, tasks: Vec<DownloadTask>) {
+    for task in tasks {
+      self.queue.queue_task(task).await;
+    }
+  }
+
+  pub fn pause(&self) {
+    self
+      .pause_sync
+      .store(true, std::sync::atomic::Ordering::SeqCst);
+  }
+
+  pub fn resume(&self) {
+    self
+      .pause_sync
+      .store(false, std::sync::atomic::Ordering::SeqCst);
+    trace!("[File] Downloader resumed");
+    let _ = self.queue.notifier.send(Signal::ProceedAfterSecs(3));
+  }
+
+  #[instrument(name = "[File]: process next download", level = "debug", skip(self))]
+  pub async fn process_next(self: Arc<Self>) -> Option<()> {
+    if self.pause_sync.load(std::sync::atomic::Ordering::Relaxed) {
+      info!("[File] Downloader is paused");
+      return None;
+    }
+
+    if self
+      .current_downloads
+      .load(std::sync::atomic::Ordering::SeqCst)
+      >= self.max_downloads
+    {
+      let _ = self.queue.notifier.send(Signal::ProceedAfterSecs(10));
+      trace!("[File] max downloads reached, process_next after 10 seconds");
+      return None;
+    }
+
+    let mut task = self.queue.tasks.write().await.pop()?;
+    if !self.queue.start_task(&task).await {
+      trace!("[File] skip {}, it's being downloaded", task);
+      self.queue.notifier.send_replace(Signal::Proceed);
+      return None;
+    }
+    self
+      .current_downloads
+      .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+
+    let result = download_file(
+      &self.cloud_service,
+      &self.user_service,
+      &self.global_notifier,
+      &task,
+    )
+    .await;
+    self.queue.finish_task(&task).await;
+    if let Err(err) = result {
+      error!("[File] failed to download {}: {}", task, err);
+      task.retry_count += 1;
+      if task.retry_count < MAX_DOWNLOAD_RETRY_COUNT {
+        // Retry with an exponential backoff: 2s, 4s, 8s, ...
+        let backoff = (1u64 << task.retry_count).min(MAX_DOWNLOAD_BACKOFF_SECS);
+        info!(
+          "[File] retry download {} after {} seconds, retry_count:{}",
+          task, backoff, task.retry_count
+        );
+        let weak_queue = Arc::downgrade(&self.queue);
+        tokio::spawn(async move {
+          tokio::time::sleep(Duration::from_secs(backoff)).await;
+          if let Some(queue) = weak_queue.upgrade() {
+            queue.queue_task(task).await;
+          }
+        });
+      } else {
+        warn!(
+          "[File] Download has been retried more than {} times: {}",
+          MAX_DOWNLOAD_RETRY_COUNT, task
+        );
+        if let Err(err) = self.global_notifier.send(FileProgress::new_download_error(
+          task.url.clone(),
+          task.file_id.clone(),
+          err.msg.clone(),
+        )) {
+          error!("[File] send global notifier failed: {}", err);
+        }
+      }
+    }
+
+    self
+      .current_downloads
+      .fetch_sub(1, std::sync::atomic::Ordering::SeqCst);
+    self.queue.notifier.send_replace(Signal::Proceed);
+    None
+  }
+}
+
+pub struct FileDownloaderRunner;
+
+impl FileDownloaderRunner {
+  pub async fn run(weak_downloader: Weak<FileDownloader>, mut notifier: watch::Receiver<Signal>) {
+    loop {
+      // stops the runner if the notifier was closed.
+      if notifier.changed().await.is_err() {
+        info!("[File]:Downloader runner stopped, notifier closed");
+        break;
+      }
+
+      if let Some(downloader) = weak_downloader.upgrade() {
+        let value = notifier.borrow().clone();
+        match value {
+          Signal::Stop => {
+            info!("[File]:Downloader runner stopped, stop signal received");
+            break;
+          },
+          Signal::Proceed => {
+            tokio::spawn(downloader.process_next());
+          },
+          Signal::ProceedAfterSecs(secs) => {
+            tokio::time::sleep(Duration::from_secs(secs)).await;
+            tokio::spawn(downloader.process_next());
+          },
+        }
+      } else {
+        info!("[File]:Downloader runner stopped, downloader dropped");
+        break;
+      }
+    }
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct DownloadTask {
+  pub url: String,
+  pub local_file_path: String,
+  pub file_id: String,
+  pub created_at: i64,
+  pub retry_count: u8,
+}
+
+impl From<DownloadFileTable> for DownloadTask {
+  fn from(record: DownloadFileTable) -> Self {
+    Self {
+      url: record.url,
+      local_file_path: record.local_file_path,
+      file_id: record.file_id,
+      created_at: record.created_at,
+      retry_count: 0,
+    }
+  }
+}
+
+impl DownloadTask {
+  fn key(&self) -> (String, String) {
+    (self.url.clone(), self.local_file_path.clone())
+  }
+}
+
+impl Display for DownloadTask {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "DownloadTask: {}", self.file_id)
+  }
+}
+
+impl Eq for DownloadTask {}
+
+impl PartialEq for DownloadTask {
+  fn eq(&self, other: &Self) -> bool {
+    self.url == other.url && self.local_file_path == other.local_file_path
+  }
+}
+
+impl PartialOrd for DownloadTask {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+/// The [BinaryHeap] pops the greatest task first, so the order is reversed to download the
+/// files in the order they were requested.
+impl Ord for DownloadTask {
+  fn cmp(&self, other: &Self) -> Ordering {
+    other.created_at.cmp(&self.created_at)
+  }
+}
+
+/// The data is written to this file while it's downloaded, and moved to the local file path once
+/// it's complete. The length of the file is the offset that an interrupted download resumes from.
+pub(crate) fn partial_download_path(local_file_path: &str) -> PathBuf {
+  PathBuf::from(format!("{}.download", local_file_path))
+}
+
+async fn download_file(
+  cloud_service: &Arc<dyn StorageCloudService>,
+  user_service: &Arc<dyn StorageUserService>,
+  global_notifier: &broadcast::Sender<FileProgress>,
+  task: &DownloadTask,
+) -> FlowyResult<()> {
+  let mut conn = user_service.sqlite_connection(user_service.user_id()?)?;
+  let record = match select_download_file(&mut conn, &task.url, &task.local_file_path)? {
+    None => {
+      info!("[File] skip download, {} was removed", task);
+      return Ok(());
+    },
+    Some(record) if record.is_finish => return Ok(()),
+    Some(record) => record,
+  };
+
+  let partial_path = partial_download_path(&record.local_file_path);
+  if let Some(parent) = partial_path.parent() {
+    tokio::fs::create_dir_all(parent).await?;
+  }
+  let mut offset = tokio::fs::metadata(&partial_path)
+    .await
+    .map(|metadata| metadata.len())
+    .unwrap_or(0);
+  let mut total_size = record.total_size as u64;
+  // The data on the disk can't be trusted if it doesn't match the recorded size.
+  if offset > 0 && (total_size == 0 || offset > total_size) {
+    warn!("[File] restart download {}, offset: {}", task, offset);
+    offset = 0;
+  }
+
+  let mut file = tokio::fs::OpenOptions::new()
+    .create(true)
+    .write(true)
+    .truncate(offset == 0)
+    .open(&partial_path)
+    .await?;
+  if offset > 0 {
+    info!("[File] resume download {} from offset: {}", task, offset);
+    file.seek(SeekFrom::Start(offset)).await?;
+  }
+
+  while total_size == 0 || offset < total_size {
+    let range = cloud_service
+      .get_object_range(task.url.clone(), offset, DOWNLOAD_CHUNK_SIZE)
+      .await?;
+    total_size = range.total_size;
+    if range.raw.is_empty() {
+      break;
+    }
+
+    file.write_all(&range.raw).await?;
+    file.flush().await?;
+    offset += range.raw.len() as u64;
+    update_download_file_progress(
+      &mut conn,
+      &task.url,
+      &task.local_file_path,
+      offset as i64,
+      total_size as i64,
+    )?;
+
+    // The 0.1 is reserved for moving the file to the local file path
+    let progress = (offset as f64 / total_size.max(1) as f64).clamp(0.0, 0.9);
+    trace!("[File] {} download progress: {}", task, progress);
+    if let Err(err) = global_notifier.send(FileProgress::new_download_progress(
+      task.url.clone(),
+      task.file_id.clone(),
+      progress,
+    )) {
+      error!("[File] send global notifier failed: {}", err);
+    }
+  }
+
+  if offset != total_size {
+    return Err(FlowyError::internal().with_context(format!(
+      "downloaded {} bytes of {}, expected {} bytes",
+      offset, task.url, total_size
+    )));
+  }
+
+  file.sync_all().await?;
+  drop(file);
+  move_downloaded_file(&partial_path, Path::new(&task.local_file_path)).await?;
+  update_download_file_completed(&mut conn, &task.url, &task.local_file_path)?;
+  info!(
+    "[File] downloaded {} bytes to file: {}",
+    total_size, task.local_file_path
+  );
+
+  if let Err(err) = global_notifier.send(FileProgress::new_download_progress(
+    task.url.clone(),
+    task.file_id.clone(),
+    1.0,
+  )) {
+    error!("[File] send global notifier failed: {}", err);
+  }
+  Ok(())
+}
+
+async fn move_downloaded_file(partial_path: &Path, local_file_path: &Path) -> FlowyResult<()> {
+  if let Some(parent) = local_file_path.parent() {
+    tokio::fs::create_dir_all(parent).await?;
+  }
+  tokio::fs::rename(partial_path, local_file_path).await?;
+  Ok(())
+}
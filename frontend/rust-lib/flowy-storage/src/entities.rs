@@ -20,3 +20,48 @@ pub struct FileStatePB {
   #[pb(index = 2)]
   pub is_finish: bool,
 }
+
+#[derive(Default, ProtoBuf, Clone, Debug)]
+pub struct DownloadUrlPB {
+  /// The url to download the file from. It might expire, so it shouldn't be saved.
+  #[pb(index = 1)]
+  pub url: String,
+}
+
+#[derive(Default, ProtoBuf, Clone, Debug)]
+pub struct CollectOrphanedFilesPB {
+  /// Report the orphaned files without marking or deleting them.
+  #[pb(index = 1)]
+  pub dry_run: bool,
+}
+
+#[derive(Default, ProtoBuf, Clone, Debug)]
+pub struct OrphanedFilesReportPB {
+  #[pb(index = 1)]
+  pub files: Vec<OrphanedFilePB>,
+
+  #[pb(index = 2)]
+  pub dry_run: bool,
+}
+
+/// An uploaded file that no view of the workspace refers to.
+#[derive(Default, ProtoBuf, Clone, Debug)]
+pub struct OrphanedFilePB {
+  #[pb(index = 1)]
+  pub url: String,
+
+  #[pb(index = 2)]
+  pub parent_dir: String,
+
+  #[pb(index = 3)]
+  pub file_id: String,
+
+  /// The time in seconds when the file was first found unreferenced.
+  #[pb(index = 4)]
+  pub marked_at: i64,
+
+  /// The file was deleted because the grace period has passed. In a dry run, the file would be
+  /// deleted.
+  #[pb(index = 5)]
+  pub is_deleted: bool,
+}
@@ -1,4 +1,7 @@
-use crate::entities::{FileStatePB, QueryFilePB, RegisterStreamPB};
+use crate::entities::{
+  CollectOrphanedFilesPB, DownloadUrlPB, FileStatePB, OrphanedFilesReportPB, QueryFilePB,
+  RegisterStreamPB,
+};
 use crate::manager::StorageManager;
 use flowy_error::{FlowyError, FlowyResult};
 use lib_dispatch::prelude::{data_result_ok, AFPluginData, AFPluginState, DataResult};
@@ -36,3 +39,25 @@ pub(crate) async fn query_file_handler(
   })?;
   data_result_ok(pb)
 }
+
+#[tracing::instrument(level = "debug", skip_all, err)]
+pub(crate) async fn get_download_url_handler(
+  data: AFPluginData<QueryFilePB>,
+  storage_manager: AFPluginState<Weak<StorageManager>>,
+) -> DataResult<DownloadUrlPB, FlowyError> {
+  let manager = upgrade_storage_manager(storage_manager)?;
+  let data = data.into_inner();
+  let url = manager.get_download_url(&data.url).await?;
+  data_result_ok(DownloadUrlPB { url })
+}
+
+#[tracing::instrument(level = "debug", skip_all, err)]
+pub(crate) async fn collect_orphaned_files_handler(
+  data: AFPluginData<CollectOrphanedFilesPB>,
+  storage_manager: AFPluginState<Weak<StorageManager>>,
+) -> DataResult<OrphanedFilesReportPB, FlowyError> {
+  let manager = upgrade_storage_manager(storage_manager)?;
+  let data = data.into_inner();
+  let report = manager.collect_orphaned_files(data.dry_run).await?;
+  data_result_ok(report)
+}
@@ -1,4 +1,7 @@
-use crate::event_handler::{query_file_handler, register_stream_handler};
+use crate::event_handler::{
+  collect_orphaned_files_handler, get_download_url_handler, query_file_handler,
+  register_stream_handler,
+};
 use crate::manager::StorageManager;
 use flowy_derive::{Flowy_Event, ProtoBuf_Enum};
 use lib_dispatch::prelude::*;
@@ -11,6 +14,11 @@ pub fn init(manager: Weak<StorageManager>) -> AFPlugin {
     .state(manager)
     .event(FileStorageEvent::RegisterStream, register_stream_handler)
     .event(FileStorageEvent::QueryFile, query_file_handler)
+    .event(FileStorageEvent::GetDownloadUrl, get_download_url_handler)
+    .event(
+      FileStorageEvent::CollectOrphanedFiles,
+      collect_orphaned_files_handler,
+    )
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Display, Hash, ProtoBuf_Enum, Flowy_Event)]
@@ -22,4 +30,14 @@ pub enum FileStorageEvent {
 
   #[event(input = "QueryFilePB", output = "FileStatePB")]
   QueryFile = 1,
+
+  /// Returns the url that the file can be downloaded from without the credentials of the
+  /// storage, which is a presigned url for the S3 storage.
+  #[event(input = "QueryFilePB", output = "DownloadUrlPB")]
+  GetDownloadUrl = 2,
+
+  /// Mark the uploaded files that aren't referenced by the workspace anymore, and delete the
+  /// files that were marked before the grace period.
+  #[event(input = "CollectOrphanedFilesPB", output = "OrphanedFilesReportPB")]
+  CollectOrphanedFiles = 3,
 }
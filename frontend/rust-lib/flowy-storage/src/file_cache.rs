@@ -64,6 +64,21 @@ impl FileTempStorage {
     Ok(temp_file_path)
   }
 
+  /// Creates a file from bytes in a sub directory of the temporary directory. The file can be
+  /// passed to [Self::create_temp_file_from_existing] without being copied onto itself.
+  pub async fn create_copy_file_from_bytes(
+    &self,
+    file_name: &str,
+    data: &[u8],
+  ) -> io::Result<PathBuf> {
+    let copy_dir = self.storage_dir.join("copy");
+    fs::create_dir_all(&copy_dir).await?;
+    let file_path = copy_dir.join(file_name);
+    let mut file = File::create(&file_path).await?;
+    file.write_all(data).await?;
+    Ok(file_path)
+  }
+
   /// Writes data to the specified temporary file.
   #[allow(dead_code)]
   pub async fn write_to_temp_file(&self, file_path: &Path, data: &[u8]) -> io::Result<()> {
@@ -0,0 +1,267 @@
+use crate::entities::{OrphanedFilePB, OrphanedFilesReportPB};
+use crate::manager::{StorageFileReferenceService, StorageServiceImpl, StorageUserService};
+use crate::sqlite_sql::{
+  delete_orphaned_upload_file, insert_orphaned_upload_file, select_all_upload_files,
+  select_orphaned_upload_files, OrphanedUploadFileTable,
+};
+use flowy_error::{internal_error, FlowyError, FlowyResult};
+use flowy_sqlite::kv::KVStorePreferences;
+use flowy_sqlite::DBConnection;
+use flowy_storage_pub::cloud::StorageCloudService;
+use lib_infra::util::timestamp;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use tracing::{error, info, instrument, warn};
+
+/// An unreferenced file is deleted after it has been unreferenced for this long. A file might be
+/// unreferenced for a while, for example when the block that refers to it was cut and is pasted
+/// again later, or when the view that refers to it wasn't synced to this device yet.
+const ORPHANED_FILE_GRACE_PERIOD_SECS: i64 = 7 * 24 * 60 * 60;
+/// A pass is scheduled whenever the workspace is opened, but it's skipped if the last pass of the
+/// workspace was less than this long ago.
+const FILE_GC_MIN_INTERVAL_SECS: i64 = 24 * 60 * 60;
+
+/// Deletes the files that were uploaded from this device but aren't referenced by any view of the
+/// workspace anymore. Deleting an image block or a media cell doesn't always delete its file, so
+/// the upload records, the temporary files and the objects in the cloud storage would pile up.
+///
+/// Each pass marks the unreferenced files, and deletes the files that were marked for longer than
+/// the grace period. The mark is removed if a file is referenced again.
+pub struct FileGarbageCollector {
+  cloud_service: Arc<dyn StorageCloudService>,
+  user_service: Arc<dyn StorageUserService>,
+  storage_service: Arc<StorageServiceImpl>,
+  store_preferences: Arc<KVStorePreferences>,
+  reference_service: RwLock<Option<Arc<dyn StorageFileReferenceService>>>,
+  is_collecting: AtomicBool,
+}
+
+impl FileGarbageCollector {
+  pub fn new(
+    cloud_service: Arc<dyn StorageCloudService>,
+    user_service: Arc<dyn StorageUserService>,
+    storage_service: Arc<StorageServiceImpl>,
+    store_preferences: Arc<KVStorePreferences>,
+  ) -> Self {
+    Self {
+      cloud_service,
+      user_service,
+      storage_service,
+      store_preferences,
+      reference_service: RwLock::new(None),
+      is_collecting: AtomicBool::new(false),
+    }
+  }
+
+  pub fn set_reference_service(&self, reference_service: Arc<dyn StorageFileReferenceService>) {
+    if let Ok(mut service) = self.reference_service.write() {
+      *service = Some(reference_service);
+    }
+  }
+
+  /// Runs a pass over the uploaded files of the current workspace. Nothing is marked or deleted in
+  /// a dry run, the report shows what the pass would do.
+  #[instrument(level = "debug", skip(self), err)]
+  pub async fn collect(&self, dry_run: bool) -> FlowyResult<OrphanedFilesReportPB> {
+    if self.is_collecting.swap(true, Ordering::SeqCst) {
+      return Err(FlowyError::internal().with_context("The orphaned files are being collected"));
+    }
+    let result = self.collect_orphaned_files(dry_run).await;
+    self.is_collecting.store(false, Ordering::SeqCst);
+    if result.is_ok() && !dry_run {
+      if let Err(err) = self.last_collected_at_key().and_then(|key| {
+        self
+          .store_preferences
+          .set_i64(&key, timestamp())
+          .map_err(internal_error)
+      }) {
+        warn!(
+          "[File] save the time of the orphaned files pass failed: {}",
+          err
+        );
+      }
+    }
+    result
+  }
+
+  /// Runs a pass unless the last pass of the workspace was less than
+  /// [FILE_GC_MIN_INTERVAL_SECS] ago.
+  pub async fn collect_if_due(&self) -> FlowyResult<()> {
+    let key = self.last_collected_at_key()?;
+    if let Some(last_collected_at) = self.store_preferences.get_i64(&key) {
+      if timestamp() - last_collected_at < FILE_GC_MIN_INTERVAL_SECS {
+        info!(
+          "[File] skip collecting orphaned files, the last pass was at {}",
+          last_collected_at
+        );
+        return Ok(());
+      }
+    }
+    self.collect(false).await?;
+    Ok(())
+  }
+
+  fn last_collected_at_key(&self) -> FlowyResult<String> {
+    Ok(format!(
+      "file_gc_last_collected_at_{}_{}",
+      self.user_service.user_id()?,
+      self.user_service.workspace_id()?
+    ))
+  }
+
+  async fn collect_orphaned_files(&self, dry_run: bool) -> FlowyResult<OrphanedFilesReportPB> {
+    let reference_service = self
+      .reference_service
+      .read()
+      .ok()
+      .and_then(|service| service.clone())
+      .ok_or_else(|| {
+        FlowyError::internal().with_context("The file reference service is not registered")
+      })?;
+    let workspace_id = self.user_service.workspace_id()?;
+
+    // A file must never be deleted because its reference couldn't be read, so the pass is aborted
+    // if any of the views fails to load.
+    let referenced_urls = reference_service.collect_file_urls(&workspace_id).await?;
+    let mut referenced_files = HashSet::new();
+    for url in &referenced_urls {
+      if let Some(file) = self.cloud_service.parse_object_url_v1(url).await {
+        referenced_files.insert(file);
+      }
+    }
+
+    let (uploaded_files, mut marked_files) = {
+      let mut conn = self.sqlite_connection()?;
+      let uploaded_files = select_all_upload_files(&mut conn, &workspace_id)?;
+      let marked_files = select_orphaned_upload_files(&mut conn, &workspace_id)?
+        .into_iter()
+        .map(|file| ((file.parent_dir, file.file_id), file.marked_at))
+        .collect::<HashMap<_, _>>();
+      (uploaded_files, marked_files)
+    };
+
+    let now = timestamp();
+    let mut report = OrphanedFilesReportPB {
+      dry_run,
+      ..Default::default()
+    };
+    for file in uploaded_files {
+      let marked_at = marked_files.remove(&(file.parent_dir.clone(), file.file_id.clone()));
+      let url = self
+        .cloud_service
+        .get_object_url_v1(&file.workspace_id, &file.parent_dir, &file.file_id)
+        .await?;
+      let is_referenced = referenced_urls.contains(&url)
+        || referenced_files.contains(&(
+          file.workspace_id.clone(),
+          file.parent_dir.clone(),
+          file.file_id.clone(),
+        ));
+
+      if is_referenced {
+        if marked_at.is_some() && !dry_run {
+          info!("[File] {} is referenced again, unmark it", file.file_id);
+          delete_orphaned_upload_file(
+            &mut self.sqlite_connection()?,
+            &workspace_id,
+            &file.parent_dir,
+            &file.file_id,
+          )?;
+        }
+        continue;
+      }
+
+      let mut orphaned_file = OrphanedFilePB {
+        url: url.clone(),
+        parent_dir: file.parent_dir.clone(),
+        file_id: file.file_id.clone(),
+        marked_at: marked_at.unwrap_or(now),
+        is_deleted: false,
+      };
+      match marked_at {
+        None => {
+          if !dry_run {
+            info!("[File] mark orphaned file: {}", file.file_id);
+            insert_orphaned_upload_file(
+              &mut self.sqlite_connection()?,
+              &OrphanedUploadFileTable {
+                workspace_id: workspace_id.clone(),
+                parent_dir: file.parent_dir.clone(),
+                file_id: file.file_id.clone(),
+                marked_at: now,
+              },
+            )?;
+          }
+        },
+        Some(marked_at) if now - marked_at >= ORPHANED_FILE_GRACE_PERIOD_SECS => {
+          if dry_run {
+            orphaned_file.is_deleted = true;
+          } else {
+            match self
+              .delete_orphaned_file(&workspace_id, &url, &file.parent_dir, &file.file_id)
+              .await
+            {
+              Ok(_) => orphaned_file.is_deleted = true,
+              Err(err) => error!("[File] delete orphaned file {} failed: {}", url, err),
+            }
+          }
+        },
+        Some(_) => {},
+      }
+      report.files.push(orphaned_file);
+    }
+
+    // The marks of the files that were deleted in other ways are not needed anymore.
+    if !dry_run {
+      let mut conn = self.sqlite_connection()?;
+      for (parent_dir, file_id) in marked_files.into_keys() {
+        delete_orphaned_upload_file(&mut conn, &workspace_id, &parent_dir, &file_id)?;
+      }
+    }
+
+    info!(
+      "[File] collected {} orphaned files of workspace {}, dry run: {}",
+      report.files.len(),
+      workspace_id,
+      dry_run
+    );
+    Ok(report)
+  }
+
+  /// The file is deleted from the cloud storage first. If that fails, the record is kept and the
+  /// deletion is retried by the next pass.
+  async fn delete_orphaned_file(
+    &self,
+    workspace_id: &str,
+    url: &str,
+    parent_dir: &str,
+    file_id: &str,
+  ) -> FlowyResult<()> {
+    if let Err(err) = self.cloud_service.delete_object(url).await {
+      if !err.is_record_not_found() {
+        return Err(err);
+      }
+      warn!("[File] orphaned file {} is not in the cloud storage", url);
+    }
+
+    self
+      .storage_service
+      .delete_local_object(workspace_id, parent_dir, file_id)
+      .await?;
+    delete_orphaned_upload_file(
+      &mut self.sqlite_connection()?,
+      workspace_id,
+      parent_dir,
+      file_id,
+    )?;
+    info!("[File] deleted orphaned file: {}", url);
+    Ok(())
+  }
+
+  fn sqlite_connection(&self) -> FlowyResult<DBConnection> {
+    self
+      .user_service
+      .sqlite_connection(self.user_service.user_id()?)
+  }
+}
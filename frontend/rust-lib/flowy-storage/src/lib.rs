@@ -1,7 +1,9 @@
+mod downloader;
 mod entities;
 mod event_handler;
 pub mod event_map;
 mod file_cache;
+mod file_gc;
 pub mod manager;
 mod notification;
 mod protobuf;
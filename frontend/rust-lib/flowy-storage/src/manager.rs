@@ -1,11 +1,17 @@
-use crate::entities::FileStatePB;
+use crate::downloader::{
+  partial_download_path, DownloadTask, DownloadTaskQueue, FileDownloader, FileDownloaderRunner,
+};
+use crate::entities::{FileStatePB, OrphanedFilesReportPB};
 use crate::file_cache::FileTempStorage;
+use crate::file_gc::FileGarbageCollector;
 use crate::notification::{make_notification, StorageNotification};
 use crate::sqlite_sql::{
-  batch_select_upload_file, delete_all_upload_parts, delete_upload_file,
-  delete_upload_file_by_file_id, insert_upload_file, insert_upload_part, is_upload_completed,
-  is_upload_exist, select_upload_file, select_upload_parts, update_upload_file_completed,
-  update_upload_file_upload_id, UploadFilePartTable, UploadFileTable,
+  batch_select_download_file, batch_select_upload_file, delete_all_upload_parts,
+  delete_download_file, delete_upload_file, delete_upload_file_by_file_id, insert_download_file,
+  insert_upload_file, insert_upload_part, is_download_completed, is_upload_completed,
+  is_upload_exist, select_download_file, select_upload_file, select_upload_parts,
+  update_upload_file_completed, update_upload_file_upload_id, DownloadFileTable,
+  UploadFilePartTable, UploadFileTable,
 };
 use crate::uploader::{FileUploader, FileUploaderRunner, Signal, UploadTask, UploadTaskQueue};
 use allo_isolate::Isolate;
@@ -13,6 +19,7 @@ use async_trait::async_trait;
 use collab_importer::util::FileId;
 use dashmap::DashMap;
 use flowy_error::{ErrorCode, FlowyError, FlowyResult};
+use flowy_sqlite::kv::KVStorePreferences;
 use flowy_sqlite::DBConnection;
 use flowy_storage_pub::chunked_byte::{calculate_offsets, ChunkedBytes, MIN_CHUNK_SIZE};
 use flowy_storage_pub::cloud::StorageCloudService;
@@ -23,10 +30,11 @@ use flowy_storage_pub::storage::{
 use lib_infra::box_any::BoxAny;
 use lib_infra::isolate_stream::{IsolateSink, SinkExt};
 use lib_infra::util::timestamp;
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
-use tokio::io::AsyncWriteExt;
+use std::time::Duration;
 use tokio::sync::{broadcast, watch};
 use tracing::{debug, error, info, instrument, trace};
 
@@ -37,12 +45,26 @@ pub trait StorageUserService: Send + Sync + 'static {
   fn get_application_root_dir(&self) -> &str;
 }
 
+#[async_trait]
+pub trait StorageFileReferenceService: Send + Sync + 'static {
+  /// Returns the urls of the files that the documents, the databases and the views of the
+  /// workspace refer to. Returns an error if any of them can't be read, because an uploaded file
+  /// that isn't in the returned urls might be deleted.
+  async fn collect_file_urls(&self, workspace_id: &str) -> FlowyResult<HashSet<String>>;
+}
+
+/// The orphaned files are collected a while after the workspace was opened, so the collection
+/// doesn't slow down the start of the application.
+const FILE_GC_DELAY: Duration = Duration::from_secs(10 * 60);
+
 type GlobalNotifier = broadcast::Sender<FileProgress>;
 pub struct StorageManager {
   pub storage_service: Arc<dyn StorageService>,
   cloud_service: Arc<dyn StorageCloudService>,
   user_service: Arc<dyn StorageUserService>,
   uploader: Arc<FileUploader>,
+  downloader: Arc<FileDownloader>,
+  file_gc: Arc<FileGarbageCollector>,
   progress_notifiers: Arc<DashMap<String, ProgressNotifier>>,
   global_notifier: GlobalNotifier,
 }
@@ -57,6 +79,7 @@ impl StorageManager {
   pub fn new(
     cloud_service: Arc<dyn StorageCloudService>,
     user_service: Arc<dyn StorageUserService>,
+    store_preferences: Arc<KVStorePreferences>,
   ) -> Self {
     let is_exceed_storage_limit = Arc::new(AtomicBool::new(false));
     let temp_storage_path = PathBuf::from(format!(
@@ -67,17 +90,27 @@ impl StorageManager {
     let temp_storage = Arc::new(FileTempStorage::new(temp_storage_path));
     let (notifier, notifier_rx) = watch::channel(Signal::Proceed);
     let task_queue = Arc::new(UploadTaskQueue::new(notifier));
+    let (download_notifier, download_notifier_rx) = watch::channel(Signal::Proceed);
+    let download_queue = Arc::new(DownloadTaskQueue::new(download_notifier));
     let progress_notifiers = Arc::new(DashMap::new());
     let storage_service = Arc::new(StorageServiceImpl {
       cloud_service: cloud_service.clone(),
       user_service: user_service.clone(),
       temp_storage,
       task_queue: task_queue.clone(),
+      download_queue: download_queue.clone(),
       is_exceed_storage_limit: is_exceed_storage_limit.clone(),
       progress_notifiers: progress_notifiers.clone(),
       global_notifier: global_notifier.clone(),
     });
 
+    let file_gc = Arc::new(FileGarbageCollector::new(
+      cloud_service.clone(),
+      user_service.clone(),
+      storage_service.clone(),
+      store_preferences,
+    ));
+
     let uploader = Arc::new(FileUploader::new(
       storage_service.clone(),
       task_queue,
@@ -88,14 +121,31 @@ impl StorageManager {
       notifier_rx,
     ));
 
+    let downloader = Arc::new(FileDownloader::new(
+      cloud_service.clone(),
+      user_service.clone(),
+      global_notifier.clone(),
+      download_queue,
+    ));
+    tokio::spawn(FileDownloaderRunner::run(
+      Arc::downgrade(&downloader),
+      download_notifier_rx,
+    ));
+
     let weak_uploader = Arc::downgrade(&uploader);
+    let weak_downloader = Arc::downgrade(&downloader);
     let cloned_user_service = user_service.clone();
     tokio::spawn(async move {
       if let Some(uploader) = weak_uploader.upgrade() {
-        if let Err(err) = prepare_upload_task(uploader, cloned_user_service).await {
+        if let Err(err) = prepare_upload_task(uploader, cloned_user_service.clone()).await {
           error!("prepare upload task failed: {}", err);
         }
       }
+      if let Some(downloader) = weak_downloader.upgrade() {
+        if let Err(err) = prepare_download_task(downloader, cloned_user_service).await {
+          error!("prepare download task failed: {}", err);
+        }
+      }
     });
 
     let mut rx = global_notifier.subscribe();
@@ -109,6 +159,11 @@ impl StorageManager {
                 file_id: progress.file_id,
               };
               notifier.notify(finish).await;
+            } else if progress.is_download {
+              let progress = FileUploadState::Downloading {
+                progress: progress.progress,
+              };
+              notifier.notify(progress).await;
             } else {
               let progress = FileUploadState::Uploading {
                 progress: progress.progress,
@@ -128,11 +183,28 @@ impl StorageManager {
       cloud_service,
       user_service,
       uploader,
+      downloader,
+      file_gc,
       progress_notifiers,
       global_notifier,
     }
   }
 
+  /// Sets the service that collects the files referenced by the workspace. The orphaned files are
+  /// only collected after the service is registered.
+  pub fn register_file_reference_service(
+    &self,
+    reference_service: Arc<dyn StorageFileReferenceService>,
+  ) {
+    self.file_gc.set_reference_service(reference_service);
+  }
+
+  /// Collects the uploaded files of the current workspace that aren't referenced anymore. See
+  /// [FileGarbageCollector] for details.
+  pub async fn collect_orphaned_files(&self, dry_run: bool) -> FlowyResult<OrphanedFilesReportPB> {
+    self.file_gc.collect(dry_run).await
+  }
+
   pub async fn register_file_progress_stream(&self, port: i64) {
     info!("register file progress stream: {}", port);
     let mut sink = IsolateSink::new(Isolate::new(port));
@@ -148,6 +220,12 @@ impl StorageManager {
     });
   }
 
+  /// Returns the url that the images and the media of the views are loaded from. See
+  /// [StorageCloudService::get_download_url].
+  pub async fn get_download_url(&self, url: &str) -> FlowyResult<String> {
+    self.cloud_service.get_download_url(url).await
+  }
+
   pub async fn query_file_state(&self, url: &str) -> Option<FileStatePB> {
     let (workspace_id, parent_dir, file_id) = self.cloud_service.parse_object_url_v1(url).await?;
     let current_workspace_id = self.user_service.workspace_id().ok()?;
@@ -176,13 +254,30 @@ impl StorageManager {
     if let Err(err) = prepare_upload_task(self.uploader.clone(), self.user_service.clone()).await {
       error!("prepare {} upload task failed: {}", workspace_id, err);
     }
+    if let Err(err) =
+      prepare_download_task(self.downloader.clone(), self.user_service.clone()).await
+    {
+      error!("prepare {} download task failed: {}", workspace_id, err);
+    }
+
+    let weak_file_gc = Arc::downgrade(&self.file_gc);
+    tokio::spawn(async move {
+      tokio::time::sleep(FILE_GC_DELAY).await;
+      if let Some(file_gc) = weak_file_gc.upgrade() {
+        if let Err(err) = file_gc.collect_if_due().await {
+          error!("[File] collect orphaned files failed: {}", err);
+        }
+      }
+    });
   }
 
   pub fn update_network_reachable(&self, reachable: bool) {
     if reachable {
       self.uploader.resume();
+      self.downloader.resume();
     } else {
       self.uploader.pause();
+      self.downloader.pause();
     }
   }
 
@@ -246,16 +341,82 @@ async fn prepare_upload_task(
   Ok(())
 }
 
+/// Queues the downloads that were not finished before the application was closed.
+async fn prepare_download_task(
+  downloader: Arc<FileDownloader>,
+  user_service: Arc<dyn StorageUserService>,
+) -> FlowyResult<()> {
+  if let Ok(uid) = user_service.user_id() {
+    let workspace_id = user_service.workspace_id()?;
+    let conn = user_service.sqlite_connection(uid)?;
+    let tasks = batch_select_download_file(conn, &workspace_id, 100, false)?
+      .into_iter()
+      .map(DownloadTask::from)
+      .collect::<Vec<_>>();
+    info!("[File] prepare download task: {}", tasks.len());
+    downloader.queue_tasks(tasks).await;
+  }
+  Ok(())
+}
+
 pub struct StorageServiceImpl {
   cloud_service: Arc<dyn StorageCloudService>,
   user_service: Arc<dyn StorageUserService>,
   temp_storage: Arc<FileTempStorage>,
   task_queue: Arc<UploadTaskQueue>,
+  download_queue: Arc<DownloadTaskQueue>,
   is_exceed_storage_limit: Arc<AtomicBool>,
   progress_notifiers: Arc<DashMap<String, ProgressNotifier>>,
   global_notifier: GlobalNotifier,
 }
 
+impl StorageServiceImpl {
+  /// Removes the upload task, the progress notifier, the upload record and the temporary file of
+  /// the object from the device. The object in the cloud storage is kept.
+  pub(crate) async fn delete_local_object(
+    &self,
+    workspace_id: &str,
+    parent_dir: &str,
+    file_id: &str,
+  ) -> FlowyResult<()> {
+    self
+      .task_queue
+      .remove_task(workspace_id, parent_dir, file_id)
+      .await;
+
+    trace!("[File] delete progress notifier: {}", file_id);
+    self.progress_notifiers.remove(file_id);
+    match delete_upload_file_by_file_id(
+      self
+        .user_service
+        .sqlite_connection(self.user_service.user_id()?)?,
+      workspace_id,
+      parent_dir,
+      file_id,
+    ) {
+      Ok(Some(file)) => {
+        let file_path = file.local_file_path;
+        match tokio::fs::remove_file(&file_path).await {
+          Ok(_) => debug!("[File] deleted file from local disk: {}", file_path),
+          Err(err) => {
+            error!("[File] delete file at {} failed: {}", file_path, err);
+          },
+        }
+      },
+      Ok(None) => {
+        info!(
+          "[File]: can not find file record for file: {} when delete",
+          file_id
+        );
+      },
+      Err(err) => {
+        error!("[File] delete upload file failed: {}", err);
+      },
+    }
+    Ok(())
+  }
+}
+
 #[async_trait]
 impl StorageService for StorageServiceImpl {
   async fn delete_object(&self, url: String) -> FlowyResult<()> {
@@ -266,72 +427,61 @@ impl StorageService for StorageServiceImpl {
         "[File] delete object: workspace: {}, parent_dir: {}, file_id: {}",
         workspace_id, parent_dir, file_id
       );
-
       self
-        .task_queue
-        .remove_task(&workspace_id, &parent_dir, &file_id)
-        .await;
-
-      trace!("[File] delete progress notifier: {}", file_id);
-      self.progress_notifiers.remove(&file_id);
-      match delete_upload_file_by_file_id(
-        self
-          .user_service
-          .sqlite_connection(self.user_service.user_id()?)?,
-        &workspace_id,
-        &parent_dir,
-        &file_id,
-      ) {
-        Ok(Some(file)) => {
-          let file_path = file.local_file_path;
-          match tokio::fs::remove_file(&file_path).await {
-            Ok(_) => debug!("[File] deleted file from local disk: {}", file_path),
-            Err(err) => {
-              error!("[File] delete file at {} failed: {}", file_path, err);
-            },
-          }
-        },
-        Ok(None) => {
-          info!(
-            "[File]: can not find file record for url: {} when delete",
-            url
-          );
-        },
-        Err(err) => {
-          error!("[File] delete upload file failed: {}", err);
-        },
-      }
+        .delete_local_object(&workspace_id, &parent_dir, &file_id)
+        .await?;
     }
 
     let _ = self.cloud_service.delete_object(&url).await;
     Ok(())
   }
 
-  fn download_object(&self, url: String, local_file_path: String) -> FlowyResult<()> {
-    let cloud_service = self.cloud_service.clone();
-    tokio::spawn(async move {
-      if tokio::fs::metadata(&local_file_path).await.is_ok() {
-        tracing::warn!("file already exist in user local disk: {}", local_file_path);
-        return Ok(());
-      }
-      let object_value = cloud_service.get_object(url).await?;
-      let mut file = tokio::fs::OpenOptions::new()
-        .create(true)
-        .truncate(true)
-        .write(true)
-        .open(&local_file_path)
-        .await?;
+  async fn download_object(&self, url: String, local_file_path: String) -> FlowyResult<()> {
+    if tokio::fs::metadata(&local_file_path).await.is_ok() {
+      tracing::warn!("file already exist in user local disk: {}", local_file_path);
+      return Ok(());
+    }
 
-      match file.write(&object_value.raw).await {
-        Ok(n) => {
-          info!("downloaded {} bytes to file: {}", n, local_file_path);
-        },
-        Err(err) => {
-          error!("write file failed: {}", err);
-        },
-      }
-      Ok::<_, FlowyError>(())
-    });
+    let workspace_id = self.user_service.workspace_id()?;
+    // The url of the objects uploaded before the v1 api ends with the file name.
+    let file_id = match self.cloud_service.parse_object_url_v1(&url).await {
+      Some((_, _, file_id)) => file_id,
+      None => url.rsplit('/').next().unwrap_or_default().to_string(),
+    };
+
+    let mut conn = self
+      .user_service
+      .sqlite_connection(self.user_service.user_id()?)?;
+    let task = match select_download_file(&mut conn, &url, &local_file_path)? {
+      Some(record) if !record.is_finish => DownloadTask::from(record),
+      existing => {
+        // The file was downloaded before and removed from the disk, so download it again.
+        if existing.is_some() {
+          delete_download_file(&mut conn, &url, &local_file_path)?;
+        }
+        if let Err(err) = tokio::fs::remove_file(partial_download_path(&local_file_path)).await {
+          trace!("[File] no partial download to remove: {}", err);
+        }
+        let record = DownloadFileTable {
+          workspace_id,
+          url,
+          local_file_path,
+          file_id,
+          downloaded_size: 0,
+          total_size: 0,
+          created_at: timestamp(),
+          is_finish: false,
+        };
+        insert_download_file(conn, &record)?;
+        DownloadTask::from(record)
+      },
+    };
+
+    self
+      .progress_notifiers
+      .entry(task.file_id.clone())
+      .or_insert_with(|| ProgressNotifier::new(task.file_id.clone()));
+    self.download_queue.queue_task(task).await;
     Ok(())
   }
 
@@ -479,6 +629,7 @@ impl StorageService for StorageServiceImpl {
         .sqlite_connection(self.user_service.user_id()?)?;
       let workspace_id = self.user_service.workspace_id()?;
       is_upload_completed(&mut conn, &workspace_id, parent_idr, file_id).unwrap_or(false)
+        || is_download_completed(&mut conn, &workspace_id, file_id).unwrap_or(false)
     };
 
     if is_completed {
@@ -491,6 +642,63 @@ impl StorageService for StorageServiceImpl {
       .or_insert_with(|| ProgressNotifier::new(file_id.to_string()));
     Ok(Some(notifier.subscribe()))
   }
+
+  async fn copy_object(
+    &self,
+    url: &str,
+    workspace_id: &str,
+    parent_dir: &str,
+  ) -> Result<Option<String>, FlowyError> {
+    let (source_workspace_id, source_parent_dir, file_id) =
+      match self.cloud_service.parse_object_url_v1(url).await {
+        None => return Ok(None),
+        Some(value) => value,
+      };
+
+    // The file might not be uploaded yet. In that case, the local file is still kept in the
+    // temporary storage.
+    let local_file_path = {
+      let mut conn = self
+        .user_service
+        .sqlite_connection(self.user_service.user_id()?)?;
+      select_upload_file(
+        &mut conn,
+        &source_workspace_id,
+        &source_parent_dir,
+        &file_id,
+      )?
+      .map(|file| file.local_file_path)
+    };
+    let data = match local_file_path {
+      Some(path) if tokio::fs::metadata(&path).await.is_ok() => tokio::fs::read(&path).await?,
+      _ => self
+        .cloud_service
+        .get_object(url.to_string())
+        .await?
+        .raw
+        .to_vec(),
+    };
+
+    let copy_file_path = self
+      .temp_storage
+      .create_copy_file_from_bytes(&file_id, &data)
+      .await
+      .map_err(|err| {
+        FlowyError::internal().with_context(format!("create copy file failed: {}", err))
+      })?;
+    let result = self
+      .create_upload(workspace_id, parent_dir, &copy_file_path.to_string_lossy())
+      .await;
+    if let Err(err) = self.temp_storage.delete_temp_file(&copy_file_path).await {
+      error!("[File] delete copy file failed: {}", err);
+    }
+    let (upload, _) = result?;
+    info!(
+      "[File] copied object {} to workspace: {}, url: {}",
+      file_id, workspace_id, upload.url
+    );
+    Ok(Some(upload.url))
+  }
 }
 
 async fn create_upload_record(
@@ -747,6 +955,13 @@ async fn handle_upload_error(
       .send();
   }
 
+  if err.code == ErrorCode::InvalidAuthConfig {
+    error!("[File] invalid storage configuration: {}", err);
+    make_notification(StorageNotification::InvalidStorageConfig)
+      .payload(err.clone())
+      .send();
+  }
+
   if err.is_single_file_limit_exceeded() {
     info!("[File] file exceed limit:{}", file_url);
     if let Err(err) = storage_service.delete_object(file_url.to_string()).await {
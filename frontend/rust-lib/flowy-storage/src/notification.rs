@@ -9,6 +9,8 @@ pub(crate) enum StorageNotification {
   FileStorageLimitExceeded = 0,
 
   SingleFileLimitExceeded = 1,
+
+  InvalidStorageConfig = 2,
 }
 
 impl std::convert::From<StorageNotification> for i32 {
@@ -1,7 +1,9 @@
 use flowy_error::{FlowyError, FlowyResult};
 use flowy_sqlite::result::DatabaseErrorKind;
 use flowy_sqlite::result::Error::DatabaseError;
-use flowy_sqlite::schema::{upload_file_part, upload_file_table};
+use flowy_sqlite::schema::{
+  download_file_table, orphaned_upload_file_table, upload_file_part, upload_file_table,
+};
 use flowy_sqlite::{
   diesel, AsChangeset, BoolExpressionMethods, DBConnection, ExpressionMethods, Identifiable,
   Insertable, OptionalExtension, QueryDsl, Queryable, RunQueryDsl, SqliteConnection,
@@ -33,6 +35,35 @@ pub struct UploadFilePartTable {
   pub part_num: i32,
 }
 
+/// A file that is downloaded to the device. The downloads are resumed from the `downloaded_size`
+/// when the application restarts.
+#[derive(Queryable, Insertable, AsChangeset, Identifiable, Debug, Clone)]
+#[diesel(table_name = download_file_table)]
+#[diesel(primary_key(url, local_file_path))]
+pub struct DownloadFileTable {
+  pub workspace_id: String,
+  pub url: String,
+  pub local_file_path: String,
+  pub file_id: String,
+  pub downloaded_size: i64,
+  /// Zero until the size of the object is known.
+  pub total_size: i64,
+  pub created_at: i64,
+  pub is_finish: bool,
+}
+
+/// An uploaded file that no view of the workspace refers to anymore. The file is deleted once it
+/// has been unreferenced for longer than the grace period.
+#[derive(Queryable, Insertable, AsChangeset, Identifiable, Debug, Clone)]
+#[diesel(table_name = orphaned_upload_file_table)]
+#[diesel(primary_key(workspace_id, parent_dir, file_id))]
+pub struct OrphanedUploadFileTable {
+  pub workspace_id: String,
+  pub parent_dir: String,
+  pub file_id: String,
+  pub marked_at: i64,
+}
+
 pub fn is_upload_file_exist(
   conn: &mut SqliteConnection,
   workspace_id: &str,
@@ -256,3 +287,178 @@ pub fn select_upload_file(
     .optional()?;
   Ok(result)
 }
+
+pub fn insert_download_file(
+  mut conn: DBConnection,
+  download_file: &DownloadFileTable,
+) -> FlowyResult<()> {
+  trace!("[File]: insert download file: {:?}", download_file);
+  match diesel::insert_into(download_file_table::table)
+    .values(download_file)
+    .execute(&mut *conn)
+  {
+    Ok(_) => Ok(()),
+    Err(DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => Err(FlowyError::new(
+      flowy_error::ErrorCode::DuplicateSqliteRecord,
+      "Download file already exists",
+    )),
+    Err(e) => Err(e.into()),
+  }
+}
+
+pub fn select_download_file(
+  conn: &mut SqliteConnection,
+  url: &str,
+  local_file_path: &str,
+) -> FlowyResult<Option<DownloadFileTable>> {
+  let result = download_file_table::dsl::download_file_table
+    .filter(
+      download_file_table::url
+        .eq(url)
+        .and(download_file_table::local_file_path.eq(local_file_path)),
+    )
+    .first::<DownloadFileTable>(conn)
+    .optional()?;
+  Ok(result)
+}
+
+pub fn batch_select_download_file(
+  mut conn: DBConnection,
+  workspace_id: &str,
+  limit: i32,
+  is_finish: bool,
+) -> FlowyResult<Vec<DownloadFileTable>> {
+  let results = download_file_table::dsl::download_file_table
+    .filter(download_file_table::workspace_id.eq(workspace_id))
+    .filter(download_file_table::is_finish.eq(is_finish))
+    .order(download_file_table::created_at.desc())
+    .limit(limit.into())
+    .load::<DownloadFileTable>(&mut *conn)?;
+  Ok(results)
+}
+
+pub fn is_download_completed(
+  conn: &mut SqliteConnection,
+  workspace_id: &str,
+  file_id: &str,
+) -> FlowyResult<bool> {
+  let result = download_file_table::dsl::download_file_table
+    .filter(
+      download_file_table::workspace_id
+        .eq(workspace_id)
+        .and(download_file_table::file_id.eq(file_id))
+        .and(download_file_table::is_finish.eq(true)),
+    )
+    .first::<DownloadFileTable>(conn)
+    .optional()?;
+  Ok(result.is_some())
+}
+
+pub fn update_download_file_progress(
+  conn: &mut SqliteConnection,
+  url: &str,
+  local_file_path: &str,
+  downloaded_size: i64,
+  total_size: i64,
+) -> FlowyResult<()> {
+  diesel::update(
+    download_file_table::dsl::download_file_table.filter(
+      download_file_table::url
+        .eq(url)
+        .and(download_file_table::local_file_path.eq(local_file_path)),
+    ),
+  )
+  .set((
+    download_file_table::downloaded_size.eq(downloaded_size),
+    download_file_table::total_size.eq(total_size),
+  ))
+  .execute(conn)?;
+  Ok(())
+}
+
+pub fn update_download_file_completed(
+  conn: &mut SqliteConnection,
+  url: &str,
+  local_file_path: &str,
+) -> FlowyResult<()> {
+  diesel::update(
+    download_file_table::dsl::download_file_table.filter(
+      download_file_table::url
+        .eq(url)
+        .and(download_file_table::local_file_path.eq(local_file_path)),
+    ),
+  )
+  .set(download_file_table::is_finish.eq(true))
+  .execute(conn)?;
+  Ok(())
+}
+
+pub fn delete_download_file(
+  conn: &mut SqliteConnection,
+  url: &str,
+  local_file_path: &str,
+) -> FlowyResult<()> {
+  diesel::delete(
+    download_file_table::dsl::download_file_table.filter(
+      download_file_table::url
+        .eq(url)
+        .and(download_file_table::local_file_path.eq(local_file_path)),
+    ),
+  )
+  .execute(conn)?;
+  Ok(())
+}
+
+pub fn select_all_upload_files(
+  conn: &mut SqliteConnection,
+  workspace_id: &str,
+) -> FlowyResult<Vec<UploadFileTable>> {
+  let results = upload_file_table::dsl::upload_file_table
+    .filter(upload_file_table::workspace_id.eq(workspace_id))
+    .load::<UploadFileTable>(conn)?;
+  Ok(results)
+}
+
+pub fn select_orphaned_upload_files(
+  conn: &mut SqliteConnection,
+  workspace_id: &str,
+) -> FlowyResult<Vec<OrphanedUploadFileTable>> {
+  let results = orphaned_upload_file_table::dsl::orphaned_upload_file_table
+    .filter(orphaned_upload_file_table::workspace_id.eq(workspace_id))
+    .load::<OrphanedUploadFileTable>(conn)?;
+  Ok(results)
+}
+
+pub fn insert_orphaned_upload_file(
+  conn: &mut SqliteConnection,
+  orphaned_file: &OrphanedUploadFileTable,
+) -> FlowyResult<()> {
+  diesel::insert_into(orphaned_upload_file_table::table)
+    .values(orphaned_file)
+    .on_conflict((
+      orphaned_upload_file_table::workspace_id,
+      orphaned_upload_file_table::parent_dir,
+      orphaned_upload_file_table::file_id,
+    ))
+    .do_nothing()
+    .execute(conn)?;
+  Ok(())
+}
+
+pub fn delete_orphaned_upload_file(
+  conn: &mut SqliteConnection,
+  workspace_id: &str,
+  parent_dir: &str,
+  file_id: &str,
+) -> FlowyResult<()> {
+  diesel::delete(
+    orphaned_upload_file_table::dsl::orphaned_upload_file_table.filter(
+      orphaned_upload_file_table::workspace_id
+        .eq(workspace_id)
+        .and(orphaned_upload_file_table::parent_dir.eq(parent_dir))
+        .and(orphaned_upload_file_table::file_id.eq(file_id)),
+    ),
+  )
+  .execute(conn)?;
+  Ok(())
+}
@@ -0,0 +1 @@
+mod storage_test;
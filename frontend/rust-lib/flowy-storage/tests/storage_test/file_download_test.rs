@@ -0,0 +1,101 @@
+use std::env::temp_dir;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use flowy_storage::sqlite_sql::{
+  batch_select_download_file, insert_download_file, select_download_file,
+  update_download_file_completed, update_download_file_progress, DownloadFileTable,
+};
+use flowy_storage_pub::storage::FileUploadState;
+
+use crate::storage_test::util::{
+  object_url, test_database, test_storage_manager, TestCloudService, WORKSPACE_ID,
+};
+
+const PARENT_DIR: &str = "document";
+const FILE_ID: &str = "file.bin";
+
+#[test]
+fn download_file_record_test() {
+  let db = test_database();
+  let record = DownloadFileTable {
+    workspace_id: WORKSPACE_ID.to_string(),
+    url: uuid::Uuid::new_v4().to_string(),
+    local_file_path: "/tmp/file.bin".to_string(),
+    file_id: FILE_ID.to_string(),
+    downloaded_size: 0,
+    total_size: 0,
+    created_at: 1,
+    is_finish: false,
+  };
+  insert_download_file(db.get_connection().unwrap(), &record).unwrap();
+  let err = insert_download_file(db.get_connection().unwrap(), &record).unwrap_err();
+  assert_eq!(err.code, flowy_error::ErrorCode::DuplicateSqliteRecord);
+
+  let mut conn = db.get_connection().unwrap();
+  update_download_file_progress(&mut conn, &record.url, &record.local_file_path, 10, 20).unwrap();
+  let saved = select_download_file(&mut conn, &record.url, &record.local_file_path)
+    .unwrap()
+    .unwrap();
+  assert_eq!(saved.downloaded_size, 10);
+  assert_eq!(saved.total_size, 20);
+
+  let pending = batch_select_download_file(db.get_connection().unwrap(), WORKSPACE_ID, 10, false);
+  assert_eq!(pending.unwrap().len(), 1);
+  update_download_file_completed(&mut conn, &record.url, &record.local_file_path).unwrap();
+  let pending = batch_select_download_file(db.get_connection().unwrap(), WORKSPACE_ID, 10, false);
+  assert!(pending.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn download_object_in_chunks_with_retry_test() {
+  let db = Arc::new(test_database());
+  // Larger than two download chunks, so the object is fetched in three ranges.
+  let content = (0..12 * 1024 * 1024)
+    .map(|i| (i % 251) as u8)
+    .collect::<Vec<_>>();
+  // The first request fails, so the download has to be retried.
+  let cloud_service = Arc::new(TestCloudService {
+    content: Bytes::from(content.clone()),
+    is_flaky: AtomicBool::new(true),
+    ..Default::default()
+  });
+  let manager = test_storage_manager(db, cloud_service);
+
+  let local_file_path = temp_dir()
+    .join(format!("download-{}.bin", uuid::Uuid::new_v4()))
+    .to_string_lossy()
+    .to_string();
+  manager
+    .storage_service
+    .download_object(
+      object_url(WORKSPACE_ID, PARENT_DIR, FILE_ID),
+      local_file_path.clone(),
+    )
+    .await
+    .unwrap();
+  let mut rx = manager
+    .subscribe_file_state(PARENT_DIR, FILE_ID)
+    .await
+    .unwrap()
+    .unwrap();
+
+  let mut progresses = vec![];
+  tokio::time::timeout(Duration::from_secs(60), async {
+    while let Ok(state) = rx.recv().await {
+      match state {
+        FileUploadState::Downloading { progress } => progresses.push(progress),
+        FileUploadState::Finished { .. } => break,
+        _ => {},
+      }
+    }
+  })
+  .await
+  .unwrap();
+
+  assert!(!progresses.is_empty());
+  assert_eq!(tokio::fs::read(&local_file_path).await.unwrap(), content);
+  let _ = tokio::fs::remove_file(&local_file_path).await;
+}
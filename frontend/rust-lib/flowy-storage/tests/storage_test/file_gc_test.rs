@@ -0,0 +1,122 @@
+use std::collections::HashSet;
+use std::env::temp_dir;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use flowy_error::FlowyResult;
+use flowy_sqlite::Database;
+use flowy_storage::manager::StorageFileReferenceService;
+use flowy_storage::sqlite_sql::{
+  insert_orphaned_upload_file, insert_upload_file, select_all_upload_files,
+  select_orphaned_upload_files, OrphanedUploadFileTable, UploadFileTable,
+};
+use lib_infra::util::timestamp;
+
+use crate::storage_test::util::{
+  object_url, test_database, test_storage_manager, TestCloudService, WORKSPACE_ID,
+};
+
+const PARENT_DIR: &str = "document";
+
+#[tokio::test]
+async fn collect_orphaned_files_test() {
+  let db = Arc::new(test_database());
+  for file_id in ["referenced", "unmarked", "expired"] {
+    insert_upload_file(db.get_connection().unwrap(), &uploaded_file(file_id)).unwrap();
+  }
+  // The file was found unreferenced more than a week ago
+  insert_orphaned_upload_file(
+    &mut db.get_connection().unwrap(),
+    &OrphanedUploadFileTable {
+      workspace_id: WORKSPACE_ID.to_string(),
+      parent_dir: PARENT_DIR.to_string(),
+      file_id: "expired".to_string(),
+      marked_at: timestamp() - 8 * 24 * 60 * 60,
+    },
+  )
+  .unwrap();
+
+  let cloud_service = Arc::new(TestCloudService::default());
+  let reference_service = Arc::new(TestReferenceService {
+    urls: Mutex::new(HashSet::from([file_url("referenced")])),
+  });
+  let manager = test_storage_manager(db.clone(), cloud_service.clone());
+  manager.register_file_reference_service(reference_service.clone());
+
+  // Nothing is marked or deleted in a dry run
+  let report = manager.collect_orphaned_files(true).await.unwrap();
+  assert!(report.dry_run);
+  let mut files = report
+    .files
+    .iter()
+    .map(|file| (file.file_id.as_str(), file.is_deleted))
+    .collect::<Vec<_>>();
+  files.sort();
+  assert_eq!(files, vec![("expired", true), ("unmarked", false)]);
+  assert!(cloud_service.deleted_urls.lock().unwrap().is_empty());
+  assert_eq!(orphaned_file_ids(&db), vec!["expired"]);
+
+  // The expired file is deleted, the other unreferenced file is marked
+  manager.collect_orphaned_files(false).await.unwrap();
+  assert_eq!(
+    *cloud_service.deleted_urls.lock().unwrap(),
+    vec![file_url("expired")]
+  );
+  assert_eq!(orphaned_file_ids(&db), vec!["unmarked"]);
+  let mut uploaded_file_ids =
+    select_all_upload_files(&mut db.get_connection().unwrap(), WORKSPACE_ID)
+      .unwrap()
+      .into_iter()
+      .map(|file| file.file_id)
+      .collect::<Vec<_>>();
+  uploaded_file_ids.sort();
+  assert_eq!(uploaded_file_ids, vec!["referenced", "unmarked"]);
+
+  // The mark is removed when the file is referenced again
+  reference_service
+    .urls
+    .lock()
+    .unwrap()
+    .insert(file_url("unmarked"));
+  let report = manager.collect_orphaned_files(false).await.unwrap();
+  assert!(report.files.is_empty());
+  assert!(orphaned_file_ids(&db).is_empty());
+}
+
+fn uploaded_file(file_id: &str) -> UploadFileTable {
+  UploadFileTable {
+    workspace_id: WORKSPACE_ID.to_string(),
+    file_id: file_id.to_string(),
+    parent_dir: PARENT_DIR.to_string(),
+    local_file_path: temp_dir().join(file_id).to_string_lossy().to_string(),
+    content_type: "image/png".to_string(),
+    chunk_size: 0,
+    num_chunk: 0,
+    upload_id: uuid::Uuid::new_v4().to_string(),
+    created_at: timestamp(),
+    is_finish: true,
+  }
+}
+
+fn orphaned_file_ids(db: &Database) -> Vec<String> {
+  select_orphaned_upload_files(&mut db.get_connection().unwrap(), WORKSPACE_ID)
+    .unwrap()
+    .into_iter()
+    .map(|file| file.file_id)
+    .collect()
+}
+
+fn file_url(file_id: &str) -> String {
+  object_url(WORKSPACE_ID, PARENT_DIR, file_id)
+}
+
+struct TestReferenceService {
+  urls: Mutex<HashSet<String>>,
+}
+
+#[async_trait]
+impl StorageFileReferenceService for TestReferenceService {
+  async fn collect_file_urls(&self, _workspace_id: &str) -> FlowyResult<HashSet<String>> {
+    Ok(self.urls.lock().unwrap().clone())
+  }
+}
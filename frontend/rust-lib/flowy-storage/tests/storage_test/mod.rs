@@ -0,0 +1,4 @@
+mod file_download_test;
+mod file_gc_test;
+mod multiple_part_upload_test;
+mod util;
@@ -1,26 +1,21 @@
 use collab_importer::util::FileId;
-use flowy_sqlite::Database;
 use flowy_storage::sqlite_sql::{
   batch_select_upload_file, delete_upload_file, insert_upload_file, insert_upload_part,
   select_latest_upload_part, select_upload_parts, UploadFilePartTable, UploadFileTable,
 };
 use flowy_storage_pub::chunked_byte::{ChunkedBytes, MIN_CHUNK_SIZE};
 use rand::distributions::Alphanumeric;
-use rand::{thread_rng, Rng};
-use std::env::temp_dir;
+use rand::Rng;
 use std::fs::File;
 use std::io::Write;
 use std::path::PathBuf;
 use std::time::Duration;
 
-pub fn test_database() -> (Database, PathBuf) {
-  let db_path = temp_dir().join(format!("test-{}.db", generate_random_string(8)));
-  (flowy_sqlite::init(&db_path).unwrap(), db_path)
-}
+use crate::storage_test::util::test_database;
 
 #[tokio::test]
 async fn test_insert_new_upload() {
-  let (db, _) = test_database();
+  let db = test_database();
 
   let workspace_id = uuid::Uuid::new_v4().to_string();
 
@@ -61,7 +56,7 @@ async fn test_insert_new_upload() {
 
 #[tokio::test]
 async fn test_upload_part_test() {
-  let (db, _) = test_database();
+  let db = test_database();
 
   let workspace_id = uuid::Uuid::new_v4().to_string();
 
@@ -117,15 +112,6 @@ async fn test_upload_part_test() {
   assert!(parts.is_empty())
 }
 
-pub fn generate_random_string(len: usize) -> String {
-  let rng = thread_rng();
-  rng
-    .sample_iter(&Alphanumeric)
-    .take(len)
-    .map(char::from)
-    .collect()
-}
-
 fn create_temp_file_with_random_content(
   size_in_bytes: usize,
 ) -> Result<String, Box<dyn std::error::Error>> {
@@ -0,0 +1,166 @@
+use std::env::temp_dir;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use flowy_error::{FlowyError, FlowyResult};
+use flowy_sqlite::kv::KVStorePreferences;
+use flowy_sqlite::{DBConnection, Database};
+use flowy_storage::manager::{StorageManager, StorageUserService};
+use flowy_storage_pub::cloud::{ObjectIdentity, ObjectRange, ObjectValue, StorageCloudService};
+use flowy_storage_pub::storage::{CompletedPartRequest, CreateUploadResponse, UploadPartResponse};
+
+pub const WORKSPACE_ID: &str = "workspace";
+
+pub fn test_database() -> Database {
+  let db_path = temp_dir().join(format!("test-{}.db", uuid::Uuid::new_v4()));
+  flowy_sqlite::init(db_path).unwrap()
+}
+
+pub fn test_storage_manager(
+  db: Arc<Database>,
+  cloud_service: Arc<TestCloudService>,
+) -> StorageManager {
+  let kv_dir = temp_dir().join(format!("kv-{}", uuid::Uuid::new_v4()));
+  std::fs::create_dir_all(&kv_dir).unwrap();
+  let store_preferences = KVStorePreferences::new(&kv_dir.to_string_lossy()).unwrap();
+  StorageManager::new(
+    cloud_service,
+    Arc::new(TestUserService {
+      db,
+      root: temp_dir().to_string_lossy().to_string(),
+    }),
+    Arc::new(store_preferences),
+  )
+}
+
+/// The url of the object in the [TestCloudService].
+pub fn object_url(workspace_id: &str, parent_dir: &str, file_id: &str) -> String {
+  format!(
+    "https://appflowy.io/{}/{}/{}",
+    workspace_id, parent_dir, file_id
+  )
+}
+
+struct TestUserService {
+  db: Arc<Database>,
+  root: String,
+}
+
+impl StorageUserService for TestUserService {
+  fn user_id(&self) -> Result<i64, FlowyError> {
+    Ok(1)
+  }
+
+  fn workspace_id(&self) -> Result<String, FlowyError> {
+    Ok(WORKSPACE_ID.to_string())
+  }
+
+  fn sqlite_connection(&self, _uid: i64) -> Result<DBConnection, FlowyError> {
+    self.db.get_connection()
+  }
+
+  fn get_application_root_dir(&self) -> &str {
+    &self.root
+  }
+}
+
+/// Serves the content for every object in ranges, and records the deleted objects. The first range
+/// request fails if `is_flaky` is set, so the download has to be retried.
+#[derive(Default)]
+pub struct TestCloudService {
+  pub content: Bytes,
+  pub is_flaky: AtomicBool,
+  pub deleted_urls: Mutex<Vec<String>>,
+}
+
+#[async_trait]
+impl StorageCloudService for TestCloudService {
+  async fn get_object_url(&self, _object_id: ObjectIdentity) -> Result<String, FlowyError> {
+    todo!()
+  }
+
+  async fn put_object(&self, _url: String, _object_value: ObjectValue) -> Result<(), FlowyError> {
+    todo!()
+  }
+
+  async fn delete_object(&self, url: &str) -> Result<(), FlowyError> {
+    self.deleted_urls.lock().unwrap().push(url.to_string());
+    Ok(())
+  }
+
+  async fn get_object(&self, _url: String) -> Result<ObjectValue, FlowyError> {
+    todo!()
+  }
+
+  async fn get_object_range(
+    &self,
+    _url: String,
+    offset: u64,
+    length: u64,
+  ) -> Result<ObjectRange, FlowyError> {
+    if self.is_flaky.swap(false, Ordering::SeqCst) {
+      return Err(FlowyError::http().with_context("connection reset"));
+    }
+    let total_size = self.content.len() as u64;
+    let start = offset.min(total_size) as usize;
+    let end = (offset + length).min(total_size) as usize;
+    Ok(ObjectRange {
+      raw: self.content.slice(start..end),
+      mime: mime_guess::mime::APPLICATION_OCTET_STREAM,
+      total_size,
+    })
+  }
+
+  async fn get_object_url_v1(
+    &self,
+    workspace_id: &str,
+    parent_dir: &str,
+    file_id: &str,
+  ) -> FlowyResult<String> {
+    Ok(object_url(workspace_id, parent_dir, file_id))
+  }
+
+  async fn parse_object_url_v1(&self, url: &str) -> Option<(String, String, String)> {
+    let mut components = url
+      .strip_prefix("https://appflowy.io/")?
+      .split('/')
+      .map(|s| s.to_string());
+    Some((components.next()?, components.next()?, components.next()?))
+  }
+
+  async fn create_upload(
+    &self,
+    _workspace_id: &str,
+    _parent_dir: &str,
+    _file_id: &str,
+    _content_type: &str,
+    _file_size: u64,
+  ) -> Result<CreateUploadResponse, FlowyError> {
+    todo!()
+  }
+
+  async fn upload_part(
+    &self,
+    _workspace_id: &str,
+    _parent_dir: &str,
+    _upload_id: &str,
+    _file_id: &str,
+    _part_number: i32,
+    _body: Vec<u8>,
+  ) -> Result<UploadPartResponse, FlowyError> {
+    todo!()
+  }
+
+  async fn complete_upload(
+    &self,
+    _workspace_id: &str,
+    _parent_dir: &str,
+    _upload_id: &str,
+    _file_id: &str,
+    _parts: Vec<CompletedPartRequest>,
+  ) -> Result<(), FlowyError> {
+    todo!()
+  }
+}
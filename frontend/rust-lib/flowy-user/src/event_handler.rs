@@ -158,7 +158,7 @@ pub async fn update_user_profile_handler(
   Ok(())
 }
 
-const APPEARANCE_SETTING_CACHE_KEY: &str = "appearance_settings";
+pub(crate) const APPEARANCE_SETTING_CACHE_KEY: &str = "appearance_settings";
 
 #[tracing::instrument(level = "debug", skip_all, err)]
 pub async fn set_appearance_setting(
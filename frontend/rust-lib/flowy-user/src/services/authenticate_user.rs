@@ -1,7 +1,12 @@
+use crate::entities::AppearanceSettingsPB;
+use crate::event_handler::APPEARANCE_SETTING_CACHE_KEY;
 use crate::migrations::session_migration::migrate_session_with_user_uuid;
 use crate::services::db::UserDB;
 use crate::services::entities::{UserConfig, UserPaths};
 use crate::services::sqlite_sql::user_sql::vacuum_database;
+use crate::services::sqlite_sql::workspace_sql::{
+  get_all_user_workspace_op, get_user_workspace_op,
+};
 use collab_integrate::CollabKVDB;
 
 use arc_swap::ArcSwapOption;
@@ -17,6 +22,7 @@ use std::sync::{Arc, Weak};
 use tracing::{error, info};
 
 const SQLITE_VACUUM_042: &str = "sqlite_vacuum_042_version";
+const WORKSPACE_SEARCH_LANGUAGE: &str = "workspace_search_language";
 
 pub struct AuthenticateUser {
   pub user_config: UserConfig,
@@ -94,6 +100,38 @@ impl AuthenticateUser {
     PathBuf::from(self.user_paths.user_data_dir(uid)).join("indexes")
   }
 
+  /// Returns the language code of the app that is set in the appearance settings, e.g. "en".
+  pub fn get_language_code(&self) -> String {
+    self
+      .store_preferences
+      .get_object::<AppearanceSettingsPB>(APPEARANCE_SETTING_CACHE_KEY)
+      .map(|setting| setting.locale)
+      .unwrap_or_default()
+      .language_code
+  }
+
+  /// Returns the language of the search index of the workspace. It is the language of the app
+  /// when the workspace is indexed for the first time and is kept afterwards, so changing the app
+  /// language doesn't change how the content of the existing workspaces is tokenized.
+  pub fn get_workspace_language_code(&self, workspace_id: &str) -> String {
+    let key = format!("{}:{}", WORKSPACE_SEARCH_LANGUAGE, workspace_id);
+    if let Some(language_code) = self.store_preferences.get_str(&key) {
+      return language_code;
+    }
+    let language_code = self.get_language_code();
+    self.store_preferences.set_str(&key, &language_code);
+    language_code
+  }
+
+  /// Returns the language of the search index of the current workspace, or the language of the
+  /// app if no workspace is opened.
+  pub fn get_search_language_code(&self) -> String {
+    match self.workspace_id() {
+      Ok(workspace_id) => self.get_workspace_language_code(&workspace_id),
+      Err(_) => self.get_language_code(),
+    }
+  }
+
   pub fn get_user_data_dir(&self) -> FlowyResult<PathBuf> {
     let uid = self.user_id()?;
     Ok(PathBuf::from(self.user_paths.user_data_dir(uid)))
@@ -117,6 +155,30 @@ impl AuthenticateUser {
     Ok(read_txn.is_exist(uid, session.user_workspace.id.as_str(), object_id))
   }
 
+  pub fn get_workspace_database_object_id(
+    &self,
+    uid: i64,
+    workspace_id: &str,
+  ) -> FlowyResult<String> {
+    let conn = self.get_sqlite_connection(uid)?;
+    let workspace = get_user_workspace_op(workspace_id, conn).ok_or_else(|| {
+      FlowyError::record_not_found()
+        .with_context(format!("The workspace {} is not found", workspace_id))
+    })?;
+    Ok(workspace.workspace_database_id)
+  }
+
+  /// Returns true if the user is a member of the given workspace.
+  pub fn is_user_workspace(&self, uid: i64, workspace_id: &str) -> FlowyResult<bool> {
+    let conn = self.get_sqlite_connection(uid)?;
+    let workspaces = get_all_user_workspace_op(uid, conn)?;
+    Ok(
+      workspaces
+        .iter()
+        .any(|workspace| workspace.id == workspace_id),
+    )
+  }
+
   pub fn set_session(&self, session: Option<Arc<Session>>) -> Result<(), FlowyError> {
     match session {
       None => {
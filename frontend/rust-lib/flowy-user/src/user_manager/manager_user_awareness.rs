@@ -1,8 +1,10 @@
+use std::borrow::BorrowMut;
 use std::sync::{Arc, Weak};
 
 use anyhow::Context;
 use collab::core::collab::DataSource;
 use collab::lock::RwLock;
+use collab::preclude::{Any, Collab, Map, MapRef, Out};
 use collab_entity::reminder::Reminder;
 use collab_entity::CollabType;
 use collab_integrate::collab_builder::{
@@ -20,6 +22,9 @@ use crate::entities::ReminderPB;
 use crate::user_manager::UserManager;
 use flowy_user_pub::session::Session;
 
+/// The map of the user awareness that stores the custom AI prompts.
+const CUSTOM_PROMPTS_KEY: &str = "custom_prompts";
+
 impl UserManager {
   /// Adds a new reminder based on the given payload.
   ///
@@ -112,6 +117,39 @@ impl UserManager {
     reminders.unwrap_or_default()
   }
 
+  /// Returns the custom AI prompts that are stored in the user awareness, so they are synced
+  /// between the devices of the user. Each prompt is a JSON string keyed by the id of the prompt.
+  pub async fn get_custom_prompts(&self) -> FlowyResult<Vec<String>> {
+    self
+      .mut_awareness(|user_awareness| {
+        let collab: &mut Collab = user_awareness.borrow_mut();
+        let txn = collab.context.transact();
+        match collab.data.get(&txn, CUSTOM_PROMPTS_KEY) {
+          Some(Out::YMap(prompts)) => prompts
+            .iter(&txn)
+            .filter_map(|(_, value)| match value {
+              Out::Any(Any::String(data)) => Some(data.to_string()),
+              _ => None,
+            })
+            .collect(),
+          _ => vec![],
+        }
+      })
+      .await
+  }
+
+  /// Saves the custom AI prompt in the user awareness, replacing the prompt with the same id.
+  pub async fn set_custom_prompt(&self, prompt_id: &str, data: String) -> FlowyResult<()> {
+    self
+      .mut_awareness(|user_awareness| {
+        let collab: &mut Collab = user_awareness.borrow_mut();
+        let mut txn = collab.context.transact_mut();
+        let prompts: MapRef = collab.data.get_or_init(&mut txn, CUSTOM_PROMPTS_KEY);
+        prompts.insert(&mut txn, prompt_id, data);
+      })
+      .await
+  }
+
   /// Init UserAwareness for user
   /// 1. check if user awareness exists on disk. If yes init awareness from disk
   /// 2. If not, init awareness from server.
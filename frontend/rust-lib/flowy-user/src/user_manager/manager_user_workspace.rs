@@ -383,6 +383,12 @@ impl UserManager {
     Ok(())
   }
 
+  /// Only expose this method for testing
+  #[cfg(debug_assertions)]
+  pub fn save_user_workspace(&self, uid: i64, user_workspace: &UserWorkspace) -> FlowyResult<()> {
+    save_user_workspace(uid, self.db_connection(uid)?, user_workspace)
+  }
+
   pub fn get_user_workspace(&self, uid: i64, workspace_id: &str) -> Option<UserWorkspace> {
     let conn = self.db_connection(uid).ok()?;
     get_user_workspace_op(workspace_id, conn)